@@ -15,8 +15,7 @@
 
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use ring::{
-    aead::{self, BoundKey},
-    error,
+    aead, error,
     rand::{SecureRandom, SystemRandom},
 };
 
@@ -0,0 +1,87 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Measures how ECDSA P-256 signing throughput scales with the number of
+//! threads signing with the same key pair concurrently.
+//!
+//! Criterion measures the latency of a single operation, so this isn't a
+//! Criterion benchmark. Each thread signs in a loop for `DURATION`, and the
+//! total number of signatures per second is reported for each thread count.
+//! With no contention between threads, throughput should scale linearly up to
+//! the number of cores.
+
+#![allow(missing_docs)]
+
+use ring::{
+    rand,
+    signature::{self, EcdsaKeyPair},
+};
+use std::{
+    hint::black_box,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+const DURATION: Duration = Duration::from_secs(1);
+
+const THREAD_COUNTS: &[usize] = &[1, 2, 4, 8];
+
+fn main() {
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    let rng = rand::SystemRandom::new();
+    let pkcs8_bytes = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+    let key_pair = EcdsaKeyPair::from_pkcs8(alg, pkcs8_bytes.as_ref(), &rng).unwrap();
+
+    println!(
+        "ecdsa_p256_sign, {} available cores",
+        thread::available_parallelism().map_or(1, |n| n.get())
+    );
+
+    let mut single_threaded = None;
+    for &threads in THREAD_COUNTS {
+        let per_second = signatures_per_second(&key_pair, threads);
+        let baseline = *single_threaded.get_or_insert(per_second);
+        println!(
+            "{threads:>2} threads: {per_second:>10.0} signatures/s ({:.2}x)",
+            per_second / baseline
+        );
+    }
+}
+
+fn signatures_per_second(key_pair: &EcdsaKeyPair, threads: usize) -> f64 {
+    let stop = AtomicBool::new(false);
+    let start = Instant::now();
+    let total: u64 = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    let rng = rand::SystemRandom::new();
+                    let mut count = 0u64;
+                    while !stop.load(Ordering::Relaxed) {
+                        let _ = black_box(key_pair.sign(&rng, black_box(&[])).unwrap());
+                        count += 1;
+                    }
+                    count
+                })
+            })
+            .collect();
+        thread::sleep(DURATION);
+        stop.store(true, Ordering::Relaxed);
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+    // Use the actual elapsed time, which includes each thread's last
+    // signature.
+    total as f64 / start.elapsed().as_secs_f64()
+}
@@ -0,0 +1,89 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+#![allow(missing_docs)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ring::digest;
+
+static ALGORITHMS: &[(&str, &digest::Algorithm)] = &[
+    ("sha256", &digest::SHA256),
+    ("sha512_256", &digest::SHA512_256),
+    ("sha384", &digest::SHA384),
+    ("sha512", &digest::SHA512),
+];
+
+static INPUT_LENGTHS: &[usize] = &[
+    // A typical key or nonce.
+    32,
+    // ~1 packet of data in TLS.
+    1350,
+    8192,
+    1024 * 1024,
+];
+
+fn digest(c: &mut Criterion) {
+    for &input_len in INPUT_LENGTHS {
+        let mut group = c.benchmark_group(format!("digest::{}", input_len));
+        group.throughput(Throughput::Bytes(input_len as u64));
+        let input = vec![0u8; input_len];
+        for (alg_name, algorithm) in ALGORITHMS {
+            group.bench_with_input(BenchmarkId::from_parameter(alg_name), &input, |b, input| {
+                b.iter(|| digest::digest(algorithm, black_box(input)))
+            });
+        }
+        group.finish();
+    }
+}
+
+// Hashing many small inputs, constructing a new `Context` for each one vs.
+// resetting one `Context`.
+fn context_reuse(c: &mut Criterion) {
+    const INPUTS: usize = 100_000;
+    const INPUT_LEN: usize = 64;
+
+    let mut group = c.benchmark_group("digest::context_reuse");
+    group.throughput(Throughput::Bytes((INPUTS * INPUT_LEN) as u64));
+    let input = [0u8; INPUT_LEN];
+
+    group.bench_function("new", |b| {
+        b.iter(|| {
+            let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+            for _ in 0..INPUTS {
+                let mut ctx = digest::Context::new(&digest::SHA256);
+                ctx.update(black_box(&input));
+                ctx.finish_into(&mut out).unwrap();
+            }
+            out
+        })
+    });
+
+    group.bench_function("reset", |b| {
+        b.iter(|| {
+            let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            for _ in 0..INPUTS {
+                ctx.reset();
+                ctx.update(black_box(&input));
+                ctx.finish_copy_into(&mut out).unwrap();
+            }
+            out
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(digest_benches, digest, context_reuse);
+
+criterion_main!(digest_benches);
@@ -0,0 +1,92 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![allow(missing_docs)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ring::{
+    rand,
+    signature::{self, Ed25519KeyPair},
+};
+
+static BATCH_SIZES: &[usize] = &[100, 1000, 10000];
+
+// About the size of a transaction endorsement.
+const MESSAGE_LEN: usize = 128;
+
+fn sign_batch(c: &mut Criterion) {
+    let rng = rand::SystemRandom::new();
+    let pkcs8_bytes = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8_bytes.as_ref()).unwrap();
+
+    for &batch_size in BATCH_SIZES {
+        let mut group = c.benchmark_group(format!("ed25519_sign::{}", batch_size));
+        group.throughput(Throughput::Elements(batch_size as u64));
+        let messages: Vec<Vec<u8>> = (0..batch_size)
+            .map(|i| {
+                let mut message = vec![0u8; MESSAGE_LEN];
+                message[..8].copy_from_slice(&(i as u64).to_be_bytes());
+                message
+            })
+            .collect();
+        let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter("single"),
+            &messages,
+            |b, messages| {
+                b.iter(|| {
+                    black_box(messages)
+                        .iter()
+                        .map(|m| key_pair.sign(m))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::from_parameter("batch"),
+            &messages,
+            |b, messages| b.iter(|| key_pair.sign_batch(black_box(messages))),
+        );
+        group.finish();
+    }
+}
+
+// A key that signs a single message and is then thrown away.
+fn keygen_sign(c: &mut Criterion) {
+    let rng = rand::SystemRandom::new();
+    let message = [0u8; MESSAGE_LEN];
+
+    let mut group = c.benchmark_group("ed25519_keygen_sign");
+    group.bench_function("pkcs8", |b| {
+        b.iter(|| {
+            let pkcs8_bytes = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+            let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8_bytes.as_ref()).unwrap();
+            key_pair.sign(black_box(&message))
+        })
+    });
+    group.bench_function("generate", |b| {
+        b.iter(|| {
+            let key_pair = Ed25519KeyPair::generate(&rng).unwrap();
+            key_pair.sign(black_box(&message))
+        })
+    });
+    group.bench_function("one-shot", |b| {
+        b.iter(|| signature::ed25519_keygen_sign(&rng, black_box(&message)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(ed25519, sign_batch, keygen_sign);
+criterion_main!(ed25519);
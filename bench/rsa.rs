@@ -0,0 +1,92 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![allow(missing_docs)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ring::{rand, rsa, signature};
+
+static PUBLIC_KEYS: &[(&str, &[u8])] = &[(
+    "2048",
+    include_bytes!("../src/rsa/signature_rsa_example_public_key.der"),
+)];
+
+static KEY_PAIRS: &[(&str, &[u8])] = &[(
+    "2048",
+    include_bytes!("../src/rsa/signature_rsa_example_private_key.der"),
+)];
+
+// Compares the cost of loading a public key from its DER encoding, which
+// recomputes the Montgomery constants of the modulus, against reloading it
+// from `ParsedPublicKey::to_cached_bytes()`.
+fn load_public_key(c: &mut Criterion) {
+    for (key_name, public_key) in PUBLIC_KEYS {
+        c.bench_function(&bench_name(key_name, "from_der"), |b| {
+            b.iter(|| {
+                let key = rsa::ParsedPublicKey::from_der(black_box(public_key)).unwrap();
+                let _r = black_box(key);
+            })
+        });
+
+        c.bench_function(&bench_name(key_name, "from_cached_bytes"), |b| {
+            let cached = rsa::ParsedPublicKey::from_der(public_key)
+                .unwrap()
+                .to_cached_bytes();
+            b.iter(|| {
+                let key = rsa::ParsedPublicKey::from_cached_bytes(black_box(&cached)).unwrap();
+                let _r = black_box(key);
+            })
+        });
+    }
+}
+
+// The public exponent of the key is 65537, as it is for almost all
+// certificates, so this measures the exponentiation done for each
+// certificate signature verified during a TLS handshake.
+fn verify(c: &mut Criterion) {
+    const MESSAGE: &[u8] = b"hello, world";
+
+    for (key_name, key_pair) in KEY_PAIRS {
+        let key_pair = rsa::KeyPair::from_der(key_pair).unwrap();
+        let mut sig = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &signature::RSA_PKCS1_SHA256,
+                &rand::SystemRandom::new(),
+                MESSAGE,
+                &mut sig,
+            )
+            .unwrap();
+        let public_key = rsa::ParsedPublicKey::from_der(key_pair.public().as_ref()).unwrap();
+
+        c.bench_function(&bench_name(key_name, "verify_pkcs1_sha256"), |b| {
+            b.iter(|| {
+                public_key
+                    .verify(
+                        &signature::RSA_PKCS1_2048_8192_SHA256,
+                        black_box(MESSAGE),
+                        black_box(&sig),
+                    )
+                    .unwrap()
+            })
+        });
+    }
+}
+
+fn bench_name(key_name: &str, bench_name: &str) -> String {
+    format!("rsa_{}_{}", key_name, bench_name)
+}
+
+criterion_group!(rsa, load_public_key, verify);
+criterion_main!(rsa);
@@ -343,9 +343,36 @@ fn ring_build_rs_main() {
         &ring_core_prefix(),
         use_pregenerated,
     );
+    emit_max_rsa_bits();
     emit_rerun_if_changed()
 }
 
+/// Passes the largest supported RSA modulus size, in bits, to the Rust code
+/// as `$RING_MAX_RSA_BITS`.
+///
+/// It defaults to 8192. Users that need larger RSA keys can set
+/// `$RING_MAX_RSA_BITS` to a multiple of 64 up to 16384; the limit determines
+/// the size of some stack buffers, so it can't be unbounded.
+fn emit_max_rsa_bits() {
+    const RING_MAX_RSA_BITS: &str = "RING_MAX_RSA_BITS";
+    const DEFAULT: u32 = 8192;
+    const MAX: u32 = 16384;
+
+    let max_rsa_bits = match read_env_var(RING_MAX_RSA_BITS) {
+        None => DEFAULT,
+        Some(s) => match s.to_str().and_then(|s| s.parse::<u32>().ok()) {
+            Some(bits) if (DEFAULT..=MAX).contains(&bits) && bits % 64 == 0 => bits,
+            _ => {
+                panic!(
+                    "${} must be a multiple of 64 from {} to {}",
+                    RING_MAX_RSA_BITS, DEFAULT, MAX
+                );
+            }
+        },
+    };
+    println!("cargo:rustc-env={}={}", RING_MAX_RSA_BITS, max_rsa_bits);
+}
+
 fn pregenerate_asm_main() {
     println!("cargo:rustc-cfg=pregenerate_asm_only");
 
@@ -981,6 +1008,7 @@ fn prefix_all_symbols(pp: char, prefix_prefix: &str, prefix: &str) -> String {
         "x25519_fe_mul_ttt",
         "x25519_fe_neg",
         "x25519_fe_tobytes",
+        "x25519_ge_double_scalarmult_consttime",
         "x25519_ge_double_scalarmult_vartime",
         "x25519_ge_frombytes_vartime",
         "x25519_ge_scalarmult_base",
@@ -736,14 +736,23 @@ static void x25519_ge_scalarmult_small_precomp(
   }
 }
 
-void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx) {
+// Every lookup into |k25519SmallPrecomp| already reads the whole table, so
+// |scan_all| has no effect.
+void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx,
+                               int scan_all) {
   (void)use_adx;
+  (void)scan_all;
   x25519_ge_scalarmult_small_precomp(h, a, k25519SmallPrecomp);
 }
 
 #else
 
-static void table_select(ge_precomp *t, const int pos, const signed char b) {
+// table_select sets |t| to |b|*256^|pos|*B. Only the entries of row |pos| of
+// |k25519Precomp| are read, unless |scan_all| is non-zero, in which case every
+// entry of every row is read. Either way, which entries are read doesn't
+// depend on |b|.
+static void table_select(ge_precomp *t, const int pos, const signed char b,
+                         int scan_all) {
   uint8_t bnegative = constant_time_msb_w(b);
   uint8_t babs = b - ((bnegative & b) << 1);
 
@@ -753,10 +762,16 @@ static void table_select(ge_precomp *t, const int pos, const signed char b) {
   __asm__("" : "+m" (t_bytes) : /*no inputs*/);
 #endif
   OPENSSL_STATIC_ASSERT(sizeof(t_bytes) == sizeof(k25519Precomp[pos][0]), "");
-  for (int i = 0; i < 8; i++) {
-    constant_time_conditional_memxor(t_bytes, k25519Precomp[pos][i],
-                                     sizeof(t_bytes),
-                                     constant_time_eq_w(babs, 1 + i));
+  int first_row = scan_all ? 0 : pos;
+  int last_row = scan_all ? 31 : pos;
+  for (int row = first_row; row <= last_row; row++) {
+    crypto_word_t in_row =
+        constant_time_eq_w((crypto_word_t)row, (crypto_word_t)pos);
+    for (int i = 0; i < 8; i++) {
+      constant_time_conditional_memxor(t_bytes, k25519Precomp[row][i],
+                                       sizeof(t_bytes),
+                                       in_row & constant_time_eq_w(babs, 1 + i));
+    }
   }
 
   fe yplusx, yminusx, xy2d;
@@ -781,11 +796,12 @@ static void table_select(ge_precomp *t, const int pos, const signed char b) {
 //
 // Preconditions:
 //   a[31] <= 127
-void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx) {
+void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx,
+                               int scan_all) {
 #if defined(BORINGSSL_FE25519_ADX)
   if (use_adx) {
     uint8_t t[4][32];
-    x25519_ge_scalarmult_base_adx(t, a);
+    x25519_ge_scalarmult_base_adx(t, a, scan_all);
     fiat_25519_from_bytes(h->X.v, t[0]);
     fiat_25519_from_bytes(h->Y.v, t[1]);
     fiat_25519_from_bytes(h->Z.v, t[2]);
@@ -821,7 +837,7 @@ void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx) {
 
   ge_p3_0(h);
   for (i = 1; i < 64; i += 2) {
-    table_select(&t, i / 2, e[i]);
+    table_select(&t, i / 2, e[i], scan_all);
     ge_madd(&r, h, &t);
     x25519_ge_p1p1_to_p3(h, &r);
   }
@@ -836,7 +852,7 @@ void x25519_ge_scalarmult_base(ge_p3 *h, const uint8_t a[32], int use_adx) {
   x25519_ge_p1p1_to_p3(h, &r);
 
   for (i = 0; i < 64; i += 2) {
-    table_select(&t, i / 2, e[i]);
+    table_select(&t, i / 2, e[i], scan_all);
     ge_madd(&r, h, &t);
     x25519_ge_p1p1_to_p3(h, &r);
   }
@@ -951,6 +967,67 @@ static void ge_double_scalarmult_vartime(ge_p2 *r, const uint8_t *a,
   }
 }
 
+static void cmov_cached(ge_cached *t, const ge_cached *u, fe_limb_t b) {
+  fe_cmov(&t->YplusX, &u->YplusX, b);
+  fe_cmov(&t->YminusX, &u->YminusX, b);
+  fe_cmov(&t->Z, &u->Z, b);
+  fe_cmov(&t->T2d, &u->T2d, b);
+}
+
+// r = a * A + b * B, like |ge_double_scalarmult_vartime|, except that the
+// sequence of operations and memory accesses doesn't depend on |a|, |b|, or
+// |A|. |a * A| is computed with a fixed 4-bit window, reading every entry of
+// the table of multiples of |A| for each window, and |b * B| is computed by
+// |x25519_ge_scalarmult_base| reading the whole precomputed table.
+//
+// Preconditions:
+//   b[31] <= 127
+static void ge_double_scalarmult_consttime(ge_p2 *r, const uint8_t *a,
+                                           const ge_p3 *A, const uint8_t *b,
+                                           int use_adx) {
+  ge_cached Ai[16];  // 0,A,2A,...,15A
+  ge_p1p1 t;
+  ge_p3 u;
+  int i;
+
+  ge_p3_0(&u);
+  x25519_ge_p3_to_cached(&Ai[0], &u);
+  x25519_ge_p3_to_cached(&Ai[1], A);
+  for (i = 2; i < 16; ++i) {
+    x25519_ge_add(&t, A, &Ai[i - 1]);
+    x25519_ge_p1p1_to_p3(&u, &t);
+    x25519_ge_p3_to_cached(&Ai[i], &u);
+  }
+
+  ge_p3_0(&u);
+  for (i = 63; i >= 0; --i) {
+    ge_p3_dbl(&t, &u);
+    x25519_ge_p1p1_to_p3(&u, &t);
+    ge_p3_dbl(&t, &u);
+    x25519_ge_p1p1_to_p3(&u, &t);
+    ge_p3_dbl(&t, &u);
+    x25519_ge_p1p1_to_p3(&u, &t);
+    ge_p3_dbl(&t, &u);
+    x25519_ge_p1p1_to_p3(&u, &t);
+
+    crypto_word_t window = (a[i / 2] >> (4 * (i & 1))) & 15;
+    ge_cached selected = Ai[0];
+    for (int j = 1; j < 16; ++j) {
+      cmov_cached(&selected, &Ai[j],
+                  constant_time_eq_w(window, (crypto_word_t)j) & 1);
+    }
+    x25519_ge_add(&t, &u, &selected);
+    x25519_ge_p1p1_to_p3(&u, &t);
+  }
+
+  ge_p3 bB;
+  x25519_ge_scalarmult_base(&bB, b, use_adx, 1);
+  ge_cached bB_cached;
+  x25519_ge_p3_to_cached(&bB_cached, &bB);
+  x25519_ge_add(&t, &u, &bB_cached);
+  x25519_ge_p1p1_to_p2(r, &t);
+}
+
 // int64_lshift21 returns |a << 21| but is defined when shifting bits into the
 // sign bit. This works around a language flaw in C.
 static inline int64_t int64_lshift21(int64_t a) {
@@ -1870,12 +1947,13 @@ void x25519_scalar_mult_generic_masked(uint8_t out[32],
 
 void x25519_public_from_private_generic_masked(uint8_t out_public_value[32],
                                                const uint8_t private_key_masked[32],
-                                               int use_adx) {
+                                               int use_adx,
+                                               int scan_all) {
   uint8_t e[32];
   OPENSSL_memcpy(e, private_key_masked, 32);
 
   ge_p3 A;
-  x25519_ge_scalarmult_base(&A, e, use_adx);
+  x25519_ge_scalarmult_base(&A, e, use_adx, scan_all);
 
   // We only need the u-coordinate of the curve25519 point. The map is
   // u=(y+1)/(1-y). Since y=Y/Z, this gives u=(Z+Y)/(Z-Y).
@@ -1916,6 +1994,12 @@ void x25519_ge_double_scalarmult_vartime(ge_p2 *r, const uint8_t *a,
   ge_double_scalarmult_vartime(r, a, A, b);
 }
 
+void x25519_ge_double_scalarmult_consttime(ge_p2 *r, const uint8_t *a,
+                                           const ge_p3 *A, const uint8_t *b,
+                                           int use_adx) {
+  ge_double_scalarmult_consttime(r, a, A, b, use_adx);
+}
+
 void x25519_sc_mask(uint8_t a[32]) {
   a[0] &= 248;
   a[31] &= 127;
@@ -46,7 +46,8 @@ fiat_curve25519_adx_square(uint64_t out[4], const uint64_t in[4]);
 // x25519_scalar_mult_adx is defined in third_party/fiat/curve25519_64_adx.h
 void x25519_scalar_mult_adx(uint8_t out[32], const uint8_t scalar[32],
                             const uint8_t point[32]);
-void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32]);
+void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32],
+                                   int scan_all);
 #endif
 
 #if defined(OPENSSL_64_BIT)
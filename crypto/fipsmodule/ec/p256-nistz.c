@@ -243,7 +243,30 @@ void p256_point_mul(Limb r[3][P256_LIMBS], const Limb p_scalar[P256_LIMBS],
   limbs_copy(r[2], out.Z, P256_LIMBS);
 }
 
-void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
+// select_w7_precomputed sets |*t| to entry |index| of window |window| of
+// |ecp_nistz256_precomputed|, as |ecp_nistz256_select_w7| does. Only the
+// entries of window |window| are read, unless |scan_all| is non-zero, in which
+// case the entries of every window are read.
+static void select_w7_precomputed(P256_POINT_AFFINE *t, int window, int index,
+                                  int scan_all) {
+  if (!scan_all) {
+    ecp_nistz256_select_w7(t, ecp_nistz256_precomputed[window], index);
+    return;
+  }
+  limbs_zero(t->X, P256_LIMBS);
+  limbs_zero(t->Y, P256_LIMBS);
+  for (int i = 0; i < 37; i++) {
+    alignas(32) P256_POINT_AFFINE candidate;
+    ecp_nistz256_select_w7(&candidate, ecp_nistz256_precomputed[i], index);
+    BN_ULONG in_window =
+        constant_time_eq_w((crypto_word_t)i, (crypto_word_t)window) & 1;
+    copy_conditional(t->X, candidate.X, in_window);
+    copy_conditional(t->Y, candidate.Y, in_window);
+  }
+}
+
+void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS],
+                         int scan_all) {
   P256_SCALAR_BYTES p_str;
   p256_scalar_bytes_from_limbs(p_str, scalar);
 
@@ -253,7 +276,7 @@ void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
 
   alignas(32) P256_POINT_AFFINE t;
   alignas(32) P256_POINT p;
-  ecp_nistz256_select_w7(&t, ecp_nistz256_precomputed[0], (int)(wvalue >> 1));
+  select_w7_precomputed(&t, 0, (int)(wvalue >> 1), scan_all);
   ecp_nistz256_neg(p.Z, t.Y);
   copy_conditional(t.Y, p.Z, wvalue & 1);
 
@@ -268,7 +291,7 @@ void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
   for (int i = 1; i < 37; i++) {
     wvalue = calc_wvalue(&index, p_str);
 
-    ecp_nistz256_select_w7(&t, ecp_nistz256_precomputed[i], (int)(wvalue >> 1));
+    select_w7_precomputed(&t, i, (int)(wvalue >> 1), scan_all);
 
     alignas(32) BN_ULONG neg_Y[P256_LIMBS];
     ecp_nistz256_neg(neg_Y, t.Y);
@@ -427,7 +427,30 @@ void p256_point_mul(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS],
   fiat_p256_to_words(r[2], nq[2]);
 }
 
-void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
+// fiat_p256_select_g_pre_comp selects the |idx-1|th point of
+// |fiat_p256_g_pre_comp[table]| as |fiat_p256_select_point_affine| does. Only
+// the entries of |fiat_p256_g_pre_comp[table]| are read, unless |scan_all| is
+// non-zero, in which case the entries of both tables are read.
+static void fiat_p256_select_g_pre_comp(const fiat_p256_limb_t idx,
+                                        size_t table, int scan_all,
+                                        fiat_p256_felem out[3]) {
+  if (!scan_all) {
+    fiat_p256_select_point_affine(idx, 15, fiat_p256_g_pre_comp[table], out);
+    return;
+  }
+  OPENSSL_memset(out, 0, sizeof(fiat_p256_felem) * 3);
+  for (size_t i = 0; i < 2; i++) {
+    fiat_p256_felem candidate[3];
+    fiat_p256_select_point_affine(idx, 15, fiat_p256_g_pre_comp[i], candidate);
+    fiat_p256_limb_t mismatch = i ^ table;
+    fiat_p256_cmovznz(out[0], mismatch, candidate[0], out[0]);
+    fiat_p256_cmovznz(out[1], mismatch, candidate[1], out[1]);
+    fiat_p256_cmovznz(out[2], mismatch, candidate[2], out[2]);
+  }
+}
+
+void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS],
+                         int scan_all) {
   // Set nq to the point at infinity.
   fiat_p256_felem nq[3] = {{0}, {0}, {0}}, tmp[3];
 
@@ -443,8 +466,7 @@ void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
     bits |= fiat_p256_get_bit(scalar, i + 96) << 1;
     bits |= fiat_p256_get_bit(scalar, i + 32);
     // Select the point to add, in constant time.
-    fiat_p256_select_point_affine((fiat_p256_limb_t)bits, 15,
-                                  fiat_p256_g_pre_comp[1], tmp);
+    fiat_p256_select_g_pre_comp((fiat_p256_limb_t)bits, 1, scan_all, tmp);
 
     if (!skip) {
       fiat_p256_point_add(nq[0], nq[1], nq[2], nq[0], nq[1], nq[2],
@@ -462,8 +484,7 @@ void p256_point_mul_base(Limb r[3][P256_LIMBS], const Limb scalar[P256_LIMBS]) {
     bits |= fiat_p256_get_bit(scalar, i + 64) << 1;
     bits |= fiat_p256_get_bit(scalar, i);
     // Select the point to add, in constant time.
-    fiat_p256_select_point_affine((fiat_p256_limb_t)bits, 15,
-                                  fiat_p256_g_pre_comp[0], tmp);
+    fiat_p256_select_g_pre_comp((fiat_p256_limb_t)bits, 0, scan_all, tmp);
     fiat_p256_point_add(nq[0], nq[1], nq[2], nq[0], nq[1], nq[2], 1 /* mixed */,
                         tmp[0], tmp[1], tmp[2]);
   }
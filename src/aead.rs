@@ -18,6 +18,37 @@
 //! generic composition paradigm][AEAD] for an introduction to the concept of
 //! AEADs.
 //!
+//! On targets without AES instructions or a usable vector unit, AES-GCM
+//! uses a constant-time, bitsliced AES implementation without lookup
+//! tables, so its timing doesn't depend on the key or data through the
+//! cache. It is considerably slower than the hardware-accelerated and
+//! vector implementations.
+//!
+//! # Authentication failures
+//!
+//! Except for [`OpeningContext`], no opening function ever leaves the
+//! would-be plaintext of a message that fails authentication where the
+//! caller can see it:
+//!
+//! * AES-GCM and ChaCha20-Poly1305, through [`OpeningKey`], [`LessSafeKey`],
+//!   `RandomNonceKey`, [`StrictOpeningKey`], and [`CommittedOpeningKey`],
+//!   authenticate while they decrypt, for performance. When the tag doesn't
+//!   match, the plaintext part of `in_out`, i.e. the part that the returned
+//!   slice would have covered, is overwritten with zeros before the error is
+//!   returned. The rest of `in_out` holds what it held before, or ciphertext
+//!   moved by `open_within`.
+//! * [`CommittedOpeningKey`] checks the key commitment before anything else,
+//!   and [`StrictOpeningKey`] checks the AAD before anything else, so those
+//!   failures leave `in_out` unmodified.
+//! * [`chacha20_poly1305_openssh::OpeningKey`] verifies the tag before
+//!   decrypting, so a failure leaves the packet unmodified.
+//! * `aes_siv` must decrypt before it can authenticate; it decrypts into a
+//!   buffer of its own, which is zeroed and freed when authentication fails.
+//!
+//! [`OpeningContext`] opens a message a part at a time, so it has to return
+//! the plaintext of each part before the tag, which comes last, can be
+//! checked. The caller must discard all of it if authentication fails.
+//!
 //! [AEAD]: https://eprint.iacr.org/2000/025.pdf
 //! [`crypto.cipher.AEAD`]: https://golang.org/pkg/crypto/cipher/#AEAD
 
@@ -27,13 +58,29 @@ use core::ops::RangeFrom;
 pub use self::{
     aes_gcm::{AES_128_GCM, AES_256_GCM},
     chacha20_poly1305::CHACHA20_POLY1305,
+    committed::{
+        CommittedAlgorithm, CommittedOpeningKey, CommittedSealingKey, COMMITTED_CHACHA20_POLY1305,
+    },
     less_safe_key::LessSafeKey,
     nonce::{Nonce, NONCE_LEN},
     opening_key::OpeningKey,
+    rotating_key::{RotatingOpeningKey, RotatingSealingKey},
     sealing_key::SealingKey,
-    unbound_key::UnboundKey,
+    streaming::{OpeningContext, SealingContext},
+    strict_aad::{StrictAad, StrictOpeningKey, StrictSealingKey},
+    traffic_keys::TrafficKeys,
+    unbound_key::{derive_key, UnboundKey, UnboundOpeningKey, UnboundSealingKey},
 };
 
+#[cfg(feature = "std")]
+pub use self::copy::{copy_encrypt, CopyError};
+
+#[cfg(target_has_atomic = "64")]
+pub use self::random_nonce_key::RandomNonceKey;
+
+#[cfg(feature = "nonce_audit")]
+pub use self::audited_nonce_sequence::{AuditedNonceSequence, NonceReuseDetected};
+
 /// A sequences of unique nonces.
 ///
 /// A given `NonceSequence` must never return the same `Nonce` twice from
@@ -43,6 +90,16 @@ pub use self::{
 ///
 /// Intentionally not `Clone` or `Copy` since cloning would allow duplication
 /// of the sequence.
+///
+/// A nonce sequence is moved into the key it is bound to with
+/// [`BoundKey::new()`], so the type of a [`SealingKey`] or [`OpeningKey`]
+/// records which kind of sequence its nonces come from, e.g.
+/// `SealingKey<Counter>`. The key owns the sequence from then on; use
+/// `nonce_sequence()` and `nonce_sequence_mut()` to inspect it. A
+/// [`RotatingSealingKey`] or [`RotatingOpeningKey`] binds each new key to a
+/// fresh sequence when it is rotated. Each sealing or opening operation calls
+/// `advance()` at most once, and a nonce it returns is never used for another
+/// operation, even if the operation fails.
 pub trait NonceSequence {
     /// Returns the next nonce in the sequence.
     ///
@@ -55,9 +112,63 @@ pub trait NonceSequence {
 }
 
 /// An AEAD key bound to a nonce sequence.
+///
+/// This is implemented by [`SealingKey`], [`OpeningKey`],
+/// [`StrictSealingKey`], and [`StrictOpeningKey`], so code that only needs to
+/// construct a key or know its algorithm can be generic over all of them:
+///
+/// ```
+/// use ring::{aead::{self, BoundKey}, error};
+///
+/// struct Counter(u32);
+///
+/// impl aead::NonceSequence for Counter {
+///     fn advance(&mut self) -> Result<aead::Nonce, error::Unspecified> {
+///         let mut nonce = [0; aead::NONCE_LEN];
+///         nonce[8..].copy_from_slice(&self.0.to_be_bytes());
+///         self.0 = self.0.checked_add(1).ok_or(error::Unspecified)?;
+///         Ok(aead::Nonce::assume_unique_for_key(nonce))
+///     }
+/// }
+///
+/// /// A record layer that is generic over the kind of key.
+/// struct RecordLayer<K> {
+///     key: K,
+/// }
+///
+/// impl<K: BoundKey<Counter>> RecordLayer<K> {
+///     fn new(algorithm: &'static aead::Algorithm, key_bytes: &[u8])
+///         -> Result<Self, error::Unspecified>
+///     {
+///         let key = aead::UnboundKey::new(algorithm, key_bytes)?;
+///         Ok(Self { key: K::new(key, Counter(0)) })
+///     }
+///
+///     fn tag_len(&self) -> usize {
+///         self.key.algorithm().tag_len()
+///     }
+/// }
+///
+/// // `BoundKey` can also be used as a trait object.
+/// fn algorithm_of(key: &dyn BoundKey<Counter>) -> &'static aead::Algorithm {
+///     key.algorithm()
+/// }
+///
+/// # fn main() -> Result<(), error::Unspecified> {
+/// let sealer = RecordLayer::<aead::SealingKey<Counter>>::new(&aead::AES_128_GCM, &[0; 16])?;
+/// let opener = RecordLayer::<aead::OpeningKey<Counter>>::new(&aead::AES_128_GCM, &[0; 16])?;
+/// assert_eq!(sealer.tag_len(), aead::MAX_TAG_LEN);
+/// assert_eq!(algorithm_of(&opener.key), &aead::AES_128_GCM);
+/// # Ok(())
+/// # }
+/// ```
 pub trait BoundKey<N: NonceSequence>: core::fmt::Debug {
     /// Constructs a new key from the given `UnboundKey` and `NonceSequence`.
-    fn new(key: UnboundKey, nonce_sequence: N) -> Self;
+    ///
+    /// This isn't available through a `dyn BoundKey<N>`.
+    fn new(key: UnboundKey, nonce_sequence: N) -> Self
+    where
+        Self: Sized;
 
     /// The key's AEAD algorithm.
     fn algorithm(&self) -> &'static Algorithm;
@@ -189,6 +300,18 @@ enum AlgorithmID {
     CHACHA20_POLY1305,
 }
 
+impl AlgorithmID {
+    // These names are used for domain separation in `derive_key()` so they
+    // must never change.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::AES_128_GCM => "AES_128_GCM",
+            Self::AES_256_GCM => "AES_256_GCM",
+            Self::CHACHA20_POLY1305 => "CHACHA20_POLY1305",
+        }
+    }
+}
+
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -235,16 +358,37 @@ pub const MAX_TAG_LEN: usize = TAG_LEN;
 
 mod aes;
 mod aes_gcm;
+#[cfg(feature = "alloc")]
+pub mod aes_siv;
+#[cfg(feature = "nonce_audit")]
+mod audited_nonce_sequence;
 mod block;
 mod chacha;
 mod chacha20_poly1305;
 pub mod chacha20_poly1305_openssh;
+mod committed;
+#[cfg(feature = "std")]
+mod copy;
 mod gcm;
 mod less_safe_key;
 mod nonce;
 mod opening_key;
 mod poly1305;
+pub mod polyval;
 pub mod quic;
+#[cfg(target_has_atomic = "64")]
+mod random_nonce_key;
+mod rotating_key;
 mod sealing_key;
 mod shift;
+mod streaming;
+mod strict_aad;
+mod traffic_keys;
 mod unbound_key;
+
+inventory! { Aead, Always;
+    AES_128_GCM => 128..=128,
+    AES_256_GCM => 256..=256,
+    CHACHA20_POLY1305 => 256..=256,
+    COMMITTED_CHACHA20_POLY1305 => 256..=256,
+}
@@ -29,7 +29,7 @@ use core::ops::RangeFrom;
 #[derive(Clone)]
 pub(super) struct Key {
     inner: AES_KEY,
-    cpu_features: cpu::Features,
+    implementation: Implementation,
 }
 
 macro_rules! set_encrypt_key {
@@ -131,6 +131,14 @@ impl Key {
         bytes: &[u8],
         variant: Variant,
         cpu_features: cpu::Features,
+    ) -> Result<Self, error::Unspecified> {
+        Self::with_implementation(bytes, variant, detect_implementation(cpu_features))
+    }
+
+    pub(super) fn with_implementation(
+        bytes: &[u8],
+        variant: Variant,
+        implementation: Implementation,
     ) -> Result<Self, error::Unspecified> {
         let key_bits = match variant {
             Variant::AES_128 => BitLength::from_usize_bits(128),
@@ -145,7 +153,7 @@ impl Key {
             rounds: 0,
         };
 
-        match detect_implementation(cpu_features) {
+        match implementation {
             #[cfg(any(
                 target_arch = "aarch64",
                 target_arch = "arm",
@@ -174,13 +182,13 @@ impl Key {
 
         Ok(Self {
             inner: key,
-            cpu_features,
+            implementation,
         })
     }
 
     #[inline]
     pub fn encrypt_block(&self, a: Block) -> Block {
-        match detect_implementation(self.cpu_features) {
+        match self.implementation {
             #[cfg(any(
                 target_arch = "aarch64",
                 target_arch = "arm",
@@ -219,7 +227,7 @@ impl Key {
 
         assert_eq!(in_out_len % BLOCK_LEN, 0);
 
-        match detect_implementation(self.cpu_features) {
+        match self.implementation {
             #[cfg(any(
                 target_arch = "aarch64",
                 target_arch = "arm",
@@ -294,10 +302,7 @@ impl Key {
     #[cfg(target_arch = "x86_64")]
     #[must_use]
     pub fn is_aes_hw(&self) -> bool {
-        matches!(
-            detect_implementation(self.cpu_features),
-            Implementation::HWAES
-        )
+        matches!(self.implementation, Implementation::HWAES)
     }
 
     #[cfg(target_arch = "x86_64")]
@@ -318,6 +323,7 @@ pub(super) struct AES_KEY {
 // Keep this in sync with `AES_MAXNR` in aes.h.
 const MAX_ROUNDS: usize = 14;
 
+#[derive(Clone, Copy)]
 pub enum Variant {
     AES_128,
     AES_256,
@@ -387,6 +393,9 @@ pub enum Implementation {
     ))]
     VPAES_BSAES = 2,
 
+    // A constant-time, bitsliced implementation without lookup tables, for
+    // targets without AES instructions or a usable vector unit. It is much
+    // slower than the others.
     #[cfg(not(target_arch = "aarch64"))]
     NOHW = 3,
 }
@@ -438,6 +447,21 @@ fn detect_implementation(cpu_features: cpu::Features) -> Implementation {
     }
 }
 
+/// The detected implementation and then the constant-time fallback, which is
+/// tested even when a faster implementation is available.
+#[cfg(test)]
+pub(super) fn test_implementations() -> impl Iterator<Item = Implementation> {
+    let detected = detect_implementation(cpu::features());
+
+    #[cfg(not(target_arch = "aarch64"))]
+    let fallback = Some(Implementation::NOHW);
+
+    #[cfg(target_arch = "aarch64")]
+    let fallback = None;
+
+    core::iter::once(detected).chain(fallback)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,28 +469,73 @@ mod tests {
 
     #[test]
     pub fn test_aes() {
-        test::run(test_file!("aes_tests.txt"), |section, test_case| {
-            assert_eq!(section, "");
-            let key = consume_key(test_case, "Key");
-            let input = test_case.consume_bytes("Input");
-            let input: &[u8; BLOCK_LEN] = input.as_slice().try_into()?;
-            let expected_output = test_case.consume_bytes("Output");
-
-            let block = Block::from(input);
-            let output = key.encrypt_block(block);
-            assert_eq!(output.as_ref(), &expected_output[..]);
-
-            Ok(())
-        })
+        for implementation in test_implementations() {
+            test::run(test_file!("aes_tests.txt"), |section, test_case| {
+                assert_eq!(section, "");
+                let key = consume_key(test_case, "Key", implementation);
+                let input = test_case.consume_bytes("Input");
+                let input: &[u8; BLOCK_LEN] = input.as_slice().try_into()?;
+                let expected_output = test_case.consume_bytes("Output");
+
+                let block = Block::from(input);
+                let output = key.encrypt_block(block);
+                assert_eq!(output.as_ref(), &expected_output[..]);
+
+                Ok(())
+            })
+        }
+    }
+
+    // Compare each implementation's CTR mode against block-at-a-time
+    // encryption with the detected implementation.
+    #[test]
+    pub fn test_aes_ctr32_differential() {
+        let key_bytes = [0x2a; 32];
+        let reference = Key::new(&key_bytes, Variant::AES_256, cpu::features()).unwrap();
+        let nonce = [0x5c; 12];
+
+        let mut input = [0u8; 33 * BLOCK_LEN];
+        for (i, b) in input.iter_mut().enumerate() {
+            *b = i.to_le_bytes()[0];
+        }
+
+        for implementation in test_implementations() {
+            let key =
+                Key::with_implementation(&key_bytes, Variant::AES_256, implementation).unwrap();
+            for blocks in 1..=33 {
+                let input = &input[..(blocks * BLOCK_LEN)];
+
+                let mut actual = [0u8; 33 * BLOCK_LEN];
+                let actual = &mut actual[..input.len()];
+                actual.copy_from_slice(input);
+                let mut ctr = Counter::one(Nonce::assume_unique_for_key(nonce));
+                key.ctr32_encrypt_within(actual, 0.., &mut ctr);
+
+                let mut ctr = Counter::one(Nonce::assume_unique_for_key(nonce));
+                for (actual, input) in actual
+                    .chunks_exact(BLOCK_LEN)
+                    .zip(input.chunks_exact(BLOCK_LEN))
+                {
+                    let input: &[u8; BLOCK_LEN] = input.try_into().unwrap();
+                    let expected =
+                        reference.encrypt_iv_xor_block(ctr.increment(), Block::from(input));
+                    assert_eq!(actual, expected.as_ref());
+                }
+            }
+        }
     }
 
-    fn consume_key(test_case: &mut test::TestCase, name: &str) -> Key {
+    fn consume_key(
+        test_case: &mut test::TestCase,
+        name: &str,
+        implementation: Implementation,
+    ) -> Key {
         let key = test_case.consume_bytes(name);
         let variant = match key.len() {
             16 => Variant::AES_128,
             32 => Variant::AES_256,
             _ => unreachable!(),
         };
-        Key::new(&key[..], variant, cpu::features()).unwrap()
+        Key::with_implementation(&key[..], variant, implementation).unwrap()
     }
 }
@@ -15,7 +15,9 @@
 use super::{
     aes::{self, Counter},
     block::{Block, BLOCK_LEN},
-    gcm, shift, Aad, Nonce, Tag,
+    gcm, shift,
+    streaming::Direction,
+    Aad, Nonce, Tag,
 };
 use crate::{
     aead, cpu, error,
@@ -63,8 +65,14 @@ fn init(
     cpu_features: cpu::Features,
 ) -> Result<aead::KeyInner, error::Unspecified> {
     let aes_key = aes::Key::new(key, variant, cpu_features)?;
-    let gcm_key = gcm::Key::new(aes_key.encrypt_block(Block::zero()), cpu_features);
-    Ok(aead::KeyInner::AesGcm(Key { gcm_key, aes_key }))
+    Ok(aead::KeyInner::AesGcm(Key::new(aes_key, cpu_features)))
+}
+
+impl Key {
+    fn new(aes_key: aes::Key, cpu_features: cpu::Features) -> Self {
+        let gcm_key = gcm::Key::new(aes_key.encrypt_block(Block::zero()), cpu_features);
+        Self { gcm_key, aes_key }
+    }
 }
 
 const CHUNK_BLOCKS: usize = 3 * 1024 / 16;
@@ -137,7 +145,13 @@ fn aes_gcm_seal(key: &aead::KeyInner, nonce: Nonce, aad: Aad<&[u8]>, in_out: &mu
         remainder.copy_from_slice(&output.as_ref()[..remainder.len()]);
     }
 
-    finish(aes_key, auth, tag_iv, aad_len, total_in_out_len)
+    finish(
+        aes_key,
+        auth,
+        tag_iv,
+        aad_len,
+        polyfill::u64_from_usize(total_in_out_len),
+    )
 }
 
 fn aes_gcm_open(
@@ -232,7 +246,134 @@ fn aes_gcm_open(
         aes_key.encrypt_iv_xor_block(ctr.into(), input)
     });
 
-    finish(aes_key, auth, tag_iv, aad_len, total_in_out_len)
+    finish(
+        aes_key,
+        auth,
+        tag_iv,
+        aad_len,
+        polyfill::u64_from_usize(total_in_out_len),
+    )
+}
+
+/// Incremental AES-GCM sealing or opening, for inputs that aren't all in
+/// memory at once. The input may be split anywhere, not only at block
+/// boundaries.
+pub(super) struct StreamingContext<'k> {
+    aes_key: &'k aes::Key,
+    direction: Direction,
+    ctr: Counter,
+    tag_iv: aes::Iv,
+    auth: gcm::Context,
+    aad_len: usize,
+    in_out_len: u64,
+
+    // The key stream and ciphertext of the block that the previous input
+    // ended in the middle of, and how much of that block was used.
+    partial_key_stream: Block,
+    partial_ciphertext: [u8; BLOCK_LEN],
+    partial_len: usize,
+}
+
+impl<'k> StreamingContext<'k> {
+    pub(super) fn new(key: &'k Key, direction: Direction, nonce: Nonce, aad: Aad<&[u8]>) -> Self {
+        let Key { gcm_key, aes_key } = key;
+        let mut ctr = Counter::one(nonce);
+        let tag_iv = ctr.increment();
+        Self {
+            aes_key,
+            direction,
+            ctr,
+            tag_iv,
+            auth: gcm::Context::new(gcm_key, aad),
+            aad_len: aad.0.len(),
+            in_out_len: 0,
+            partial_key_stream: Block::zero(),
+            partial_ciphertext: [0; BLOCK_LEN],
+            partial_len: 0,
+        }
+    }
+
+    /// Encrypts or decrypts `in_out`, which may be any length.
+    pub(super) fn update(&mut self, in_out: &mut [u8]) {
+        self.in_out_len += polyfill::u64_from_usize(in_out.len());
+
+        // Finish the block that the previous input ended in, if any.
+        let in_out = if self.partial_len > 0 {
+            let (partial, rest) =
+                in_out.split_at_mut(core::cmp::min(BLOCK_LEN - self.partial_len, in_out.len()));
+            self.update_partial_block(partial);
+            rest
+        } else {
+            in_out
+        };
+
+        let (whole, remainder) = {
+            let in_out_len = in_out.len();
+            let whole_len = in_out_len - (in_out_len % BLOCK_LEN);
+            in_out.split_at_mut(whole_len)
+        };
+
+        for chunk in whole.chunks_mut(CHUNK_BLOCKS * BLOCK_LEN) {
+            match self.direction {
+                Direction::Sealing => {
+                    self.aes_key.ctr32_encrypt_within(chunk, 0.., &mut self.ctr);
+                    self.auth.update_blocks(chunk);
+                }
+                Direction::Opening => {
+                    self.auth.update_blocks(chunk);
+                    self.aes_key.ctr32_encrypt_within(chunk, 0.., &mut self.ctr);
+                }
+            }
+        }
+
+        if !remainder.is_empty() {
+            self.partial_key_stream = self
+                .aes_key
+                .encrypt_iv_xor_block(self.ctr.increment(), Block::zero());
+            self.update_partial_block(remainder);
+        }
+    }
+
+    // `in_out` must fit within the rest of the partial block.
+    fn update_partial_block(&mut self, in_out: &mut [u8]) {
+        let start = self.partial_len;
+        let key_stream = &self.partial_key_stream.as_ref()[start..];
+        let ciphertext = &mut self.partial_ciphertext[start..];
+        for ((b, k), c) in in_out.iter_mut().zip(key_stream).zip(ciphertext) {
+            match self.direction {
+                Direction::Sealing => {
+                    *b ^= k;
+                    *c = *b;
+                }
+                Direction::Opening => {
+                    *c = *b;
+                    *b ^= k;
+                }
+            }
+        }
+        self.partial_len += in_out.len();
+        if self.partial_len == BLOCK_LEN {
+            self.auth
+                .update_block(Block::from(&self.partial_ciphertext));
+            self.partial_len = 0;
+        }
+    }
+
+    /// Returns the tag of everything that was processed.
+    pub(super) fn finish(mut self) -> Tag {
+        if self.partial_len > 0 {
+            let mut last = Block::from(&self.partial_ciphertext);
+            last.zero_from(self.partial_len);
+            self.auth.update_block(last);
+        }
+        finish(
+            self.aes_key,
+            self.auth,
+            self.tag_iv,
+            self.aad_len,
+            self.in_out_len,
+        )
+    }
 }
 
 fn finish(
@@ -240,11 +381,11 @@ fn finish(
     mut gcm_ctx: gcm::Context,
     tag_iv: aes::Iv,
     aad_len: usize,
-    in_out_len: usize,
+    in_out_len: u64,
 ) -> Tag {
     // Authenticate the final block containing the input lengths.
     let aad_bits = polyfill::u64_from_usize(aad_len) << 3;
-    let ciphertext_bits = polyfill::u64_from_usize(in_out_len) << 3;
+    let ciphertext_bits = in_out_len << 3;
     gcm_ctx.update_block(Block::from(
         [aad_bits, ciphertext_bits].map(u64::to_be_bytes),
     ));
@@ -261,6 +402,64 @@ const AES_GCM_MAX_INPUT_LEN: u64 = super::max_input_len(BLOCK_LEN, 2);
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::test;
+
+    // The public API always uses the detected AES implementation; this also
+    // runs the AES-GCM test vectors with the constant-time fallback.
+    #[test]
+    fn test_aes_gcm_implementations() {
+        for implementation in aes::test_implementations() {
+            for file in [
+                test_file!("../../tests/aead_aes_128_gcm_tests.txt"),
+                test_file!("../../tests/aead_aes_256_gcm_tests.txt"),
+            ] {
+                test::run(file, |section, test_case| {
+                    assert_eq!(section, "");
+                    let key = test_case.consume_bytes("KEY");
+                    let nonce = test_case.consume_bytes("NONCE");
+                    let plaintext = test_case.consume_bytes("IN");
+                    let ad = test_case.consume_bytes("AD");
+                    let ct = test_case.consume_bytes("CT");
+                    let tag = test_case.consume_bytes("TAG");
+                    if test_case.consume_optional_string("FAILS").is_some() {
+                        return Ok(());
+                    }
+
+                    let variant = match key.len() {
+                        16 => aes::Variant::AES_128,
+                        32 => aes::Variant::AES_256,
+                        _ => unreachable!(),
+                    };
+                    let aes_key = aes::Key::with_implementation(&key, variant, implementation)?;
+                    let key = aead::KeyInner::AesGcm(Key::new(aes_key, cpu::features()));
+
+                    let mut in_out = plaintext.clone();
+                    let actual_tag = aes_gcm_seal(
+                        &key,
+                        Nonce::try_assume_unique_for_key(&nonce)?,
+                        Aad::from(&ad[..]),
+                        &mut in_out,
+                    );
+                    assert_eq!(in_out, ct);
+                    assert_eq!(actual_tag.as_ref(), &tag[..]);
+
+                    let actual_tag = aes_gcm_open(
+                        &key,
+                        Nonce::try_assume_unique_for_key(&nonce)?,
+                        Aad::from(&ad[..]),
+                        &mut in_out,
+                        0..,
+                    );
+                    assert_eq!(in_out, plaintext);
+                    assert_eq!(actual_tag.as_ref(), &tag[..]);
+
+                    Ok(())
+                });
+            }
+        }
+    }
+
     #[test]
     fn max_input_len_test() {
         // [NIST SP800-38D] Section 5.2.1.1. Note that [RFC 5116 Section 5.1] and
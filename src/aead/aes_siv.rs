@@ -0,0 +1,298 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! AES-SIV ([RFC 5297]), the deterministic, misuse-resistant AEAD built from
+//! AES-CMAC and AES-CTR.
+//!
+//! AES-SIV authenticates a vector of associated data components, each of
+//! which is authenticated separately rather than concatenated. To use
+//! AES-SIV with a nonce, pass the nonce as the last component.
+//!
+//! The sealed output is the 16-byte synthetic IV followed by the ciphertext.
+//!
+//! [RFC 5297]: https://tools.ietf.org/html/rfc5297
+
+use super::{
+    aes,
+    block::{Block, BLOCK_LEN},
+};
+use crate::{constant_time, cpu, error, polyfill};
+use alloc::vec::Vec;
+
+/// The length of the synthetic IV that precedes the ciphertext.
+pub const SIV_LEN: usize = BLOCK_LEN;
+
+/// The maximum number of associated data components (RFC 5297 Section 7).
+pub const MAX_AD_COMPONENTS: usize = 126;
+
+macro_rules! aes_siv_key {
+    ( $Key:ident, $name:expr, $variant:expr, $key_len:expr ) => {
+        #[doc = concat!("An ", $name, " key.")]
+        pub struct $Key(Key);
+
+        impl $Key {
+            /// The length of the key, in bytes: the CMAC key followed by the
+            /// CTR key.
+            pub const KEY_LEN: usize = $key_len;
+
+            #[doc = concat!("Constructs an ", $name, " key.")]
+            ///
+            /// Fails if `key` isn't `KEY_LEN` bytes long.
+            pub fn new(key: &[u8]) -> Result<Self, error::Unspecified> {
+                if key.len() != Self::KEY_LEN {
+                    return Err(error::Unspecified);
+                }
+                Key::new(key, $variant).map(Self)
+            }
+
+            /// Encrypts `plaintext` and authenticates it and each of
+            /// `ad_components`, returning the synthetic IV followed by the
+            /// ciphertext.
+            ///
+            /// Fails if there are more than [`MAX_AD_COMPONENTS`]
+            /// components.
+            pub fn seal_multi_ad(
+                &self,
+                ad_components: &[&[u8]],
+                plaintext: &[u8],
+            ) -> Result<Vec<u8>, error::Unspecified> {
+                self.0.seal(ad_components, plaintext)
+            }
+
+            /// Authenticates and decrypts the output of
+            /// [`Self::seal_multi_ad`] given the same `ad_components`,
+            /// returning the plaintext.
+            ///
+            /// The plaintext is decrypted into a new buffer before it can be
+            /// authenticated; if authentication fails, the buffer is zeroed
+            /// before it's freed.
+            pub fn open_multi_ad(
+                &self,
+                ad_components: &[&[u8]],
+                sealed: &[u8],
+            ) -> Result<Vec<u8>, error::Unspecified> {
+                self.0.open(ad_components, sealed)
+            }
+        }
+
+        impl core::fmt::Debug for $Key {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+                f.debug_struct(stringify!($Key)).finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+aes_siv_key!(AesSiv128Key, "AES-SIV (AES-128)", aes::Variant::AES_128, 32);
+aes_siv_key!(AesSiv256Key, "AES-SIV (AES-256)", aes::Variant::AES_256, 64);
+
+struct Key {
+    mac: CmacKey,
+    ctr: aes::Key,
+}
+
+impl Key {
+    fn new(key: &[u8], variant: aes::Variant) -> Result<Self, error::Unspecified> {
+        let cpu_features = cpu::features();
+        let (mac_key, ctr_key) = key.split_at(key.len() / 2);
+        Ok(Self {
+            mac: CmacKey::new(aes::Key::new(mac_key, variant, cpu_features)?),
+            ctr: aes::Key::new(ctr_key, variant, cpu_features)?,
+        })
+    }
+
+    fn seal(&self, ad: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+        if ad.len() > MAX_AD_COMPONENTS {
+            return Err(error::Unspecified);
+        }
+        let v = s2v(&self.mac, ad, plaintext);
+
+        let mut out = Vec::with_capacity(SIV_LEN + plaintext.len());
+        out.extend_from_slice(v.as_ref());
+        out.extend_from_slice(plaintext);
+        ctr(&self.ctr, v, &mut out[SIV_LEN..]);
+        Ok(out)
+    }
+
+    fn open(&self, ad: &[&[u8]], sealed: &[u8]) -> Result<Vec<u8>, error::Unspecified> {
+        if ad.len() > MAX_AD_COMPONENTS || sealed.len() < SIV_LEN {
+            return Err(error::Unspecified);
+        }
+        let (v, ciphertext) = sealed.split_at(SIV_LEN);
+        let v: &[u8; SIV_LEN] = v.try_into()?;
+
+        let mut plaintext = Vec::from(ciphertext);
+        ctr(&self.ctr, Block::from(v), &mut plaintext);
+
+        let t = s2v(&self.mac, ad, &plaintext);
+        if constant_time::verify_slices_are_equal(t.as_ref(), v).is_err() {
+            // SIV can only authenticate the plaintext, so don't leave it
+            // behind in freed memory.
+            polyfill::zeroize(&mut plaintext);
+            return Err(error::Unspecified);
+        }
+        Ok(plaintext)
+    }
+}
+
+// RFC 5297 Section 2.4.
+fn s2v(mac: &CmacKey, ad: &[&[u8]], plaintext: &[u8]) -> Block {
+    let mut d = mac.cmac(&[0; BLOCK_LEN]);
+    for ad in ad {
+        d = dbl(d) ^ mac.cmac(ad);
+    }
+
+    let mut ctx = Cmac::new(mac);
+    if plaintext.len() >= BLOCK_LEN {
+        // xorend
+        let (head, tail) = plaintext.split_at(plaintext.len() - BLOCK_LEN);
+        let tail: &[u8; BLOCK_LEN] = tail.try_into().unwrap();
+        ctx.update(head);
+        ctx.update((Block::from(tail) ^ d).as_ref());
+    } else {
+        let mut padded = [0; BLOCK_LEN];
+        padded[..plaintext.len()].copy_from_slice(plaintext);
+        padded[plaintext.len()] = 0x80;
+        ctx.update((dbl(d) ^ Block::from(&padded)).as_ref());
+    }
+    ctx.finish()
+}
+
+// RFC 5297 Section 2.5, with the counter incremented modulo 2^128.
+fn ctr(key: &aes::Key, v: Block, in_out: &mut [u8]) {
+    // Clear the 31st and 63rd bits (counting from the right) so that
+    // implementations using 32- or 64-bit counters interoperate.
+    let mut q = *v.as_ref();
+    q[8] &= 0x7f;
+    q[12] &= 0x7f;
+
+    let mut counter = u128::from_be_bytes(q);
+    for chunk in in_out.chunks_mut(BLOCK_LEN) {
+        let key_stream = key.encrypt_block(Block::from(&counter.to_be_bytes()));
+        chunk
+            .iter_mut()
+            .zip(key_stream.as_ref())
+            .for_each(|(b, k)| *b ^= k);
+        counter = counter.wrapping_add(1);
+    }
+}
+
+// Doubling in GF(2^128) with the CMAC polynomial, in constant time.
+fn dbl(a: Block) -> Block {
+    let a = u128::from_be_bytes(*a.as_ref());
+    let mask = 0u128.wrapping_sub(a >> 127);
+    Block::from(&((a << 1) ^ (mask & 0x87)).to_be_bytes())
+}
+
+// AES-CMAC (NIST SP 800-38B).
+struct CmacKey {
+    aes: aes::Key,
+    k1: Block,
+    k2: Block,
+}
+
+impl CmacKey {
+    fn new(aes: aes::Key) -> Self {
+        let l = aes.encrypt_block(Block::zero());
+        let k1 = dbl(l);
+        let k2 = dbl(k1);
+        Self { aes, k1, k2 }
+    }
+
+    fn cmac(&self, data: &[u8]) -> Block {
+        let mut ctx = Cmac::new(self);
+        ctx.update(data);
+        ctx.finish()
+    }
+}
+
+struct Cmac<'a> {
+    key: &'a CmacKey,
+    state: Block,
+    // The last block is processed by `finish`, so up to a full block is
+    // buffered.
+    pending: [u8; BLOCK_LEN],
+    pending_len: usize,
+}
+
+impl<'a> Cmac<'a> {
+    fn new(key: &'a CmacKey) -> Self {
+        Self {
+            key,
+            state: Block::zero(),
+            pending: [0; BLOCK_LEN],
+            pending_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.pending_len == BLOCK_LEN {
+                self.state = self
+                    .key
+                    .aes
+                    .encrypt_block(self.state ^ Block::from(&self.pending));
+                self.pending_len = 0;
+            }
+            let n = core::cmp::min(BLOCK_LEN - self.pending_len, data.len());
+            let (chunk, rest) = data.split_at(n);
+            self.pending[self.pending_len..][..n].copy_from_slice(chunk);
+            self.pending_len += n;
+            data = rest;
+        }
+    }
+
+    fn finish(mut self) -> Block {
+        let last = if self.pending_len == BLOCK_LEN {
+            Block::from(&self.pending) ^ self.key.k1
+        } else {
+            self.pending[self.pending_len..].fill(0);
+            self.pending[self.pending_len] = 0x80;
+            Block::from(&self.pending) ^ self.key.k2
+        };
+        self.key.aes.encrypt_block(self.state ^ last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    #[test]
+    fn test_cmac() {
+        // NIST SP 800-38B Appendix D.1 (AES-128), examples 1, 2, and 4.
+        let key = test::from_hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let key =
+            CmacKey::new(aes::Key::new(&key, aes::Variant::AES_128, cpu::features()).unwrap());
+        let msg = test::from_hex(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .unwrap();
+        for (len, expected) in [
+            (0, "bb1d6929e95937287fa37d129b756746"),
+            (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+            (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+        ] {
+            let expected = test::from_hex(expected).unwrap();
+            assert_eq!(&key.cmac(&msg[..len]).as_ref()[..], &expected[..]);
+
+            // The same, a byte at a time.
+            let mut ctx = Cmac::new(&key);
+            msg[..len].chunks(1).for_each(|b| ctx.update(b));
+            assert_eq!(&ctx.finish().as_ref()[..], &expected[..]);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+extern crate std;
+
+use super::{Nonce, NonceSequence, NONCE_LEN};
+use crate::error;
+use std::collections::HashSet;
+
+/// A [`NonceSequence`] that records every nonce it returns and fails if the
+/// sequence it wraps returns a nonce twice.
+///
+/// This is a tool for finding nonce-reuse bugs in tests; it keeps every nonce
+/// in memory, so it isn't suitable for long-lived keys.
+///
+/// `NonceSequence::advance` can only return `error::Unspecified`, so use
+/// [`Self::check`] to distinguish nonce reuse from other failures. A mutable
+/// reference to an `AuditedNonceSequence` is also a `NonceSequence`, so that
+/// it can be inspected after a key that uses it is dropped:
+///
+/// ```
+/// use ring::aead::{self, AuditedNonceSequence, Nonce, NonceSequence, NONCE_LEN};
+/// use ring::error;
+///
+/// struct Broken;
+///
+/// impl NonceSequence for Broken {
+///     fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+///         Ok(Nonce::assume_unique_for_key([0; NONCE_LEN]))
+///     }
+/// }
+///
+/// let mut nonces = AuditedNonceSequence::new(Broken);
+/// {
+///     let key = aead::UnboundKey::new(&aead::AES_128_GCM, &[0; 16]).unwrap();
+///     let mut key = aead::SealingKey::new(key, &mut nonces);
+///     let mut in_out = *b"hello";
+///     assert!(key.seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out).is_ok());
+///     assert!(key.seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out).is_err());
+/// }
+/// assert_eq!(nonces.used_count(), 1);
+/// assert!(nonces.check().is_err());
+/// ```
+pub struct AuditedNonceSequence<NS> {
+    inner: NS,
+    used: HashSet<[u8; NONCE_LEN]>,
+    reused: Option<NonceReuseDetected>,
+}
+
+impl<NS: NonceSequence> AuditedNonceSequence<NS> {
+    /// Wraps `inner`.
+    pub fn new(inner: NS) -> Self {
+        Self {
+            inner,
+            used: HashSet::new(),
+            reused: None,
+        }
+    }
+
+    /// The number of distinct nonces returned so far.
+    #[inline]
+    pub fn used_count(&self) -> usize {
+        self.used.len()
+    }
+
+    /// Fails if the wrapped sequence has returned a nonce twice.
+    ///
+    /// Once reuse has been detected, every call to `advance` fails.
+    pub fn check(&self) -> Result<(), NonceReuseDetected> {
+        match self.reused {
+            Some(reused) => Err(reused),
+            None => Ok(()),
+        }
+    }
+
+    /// Forgets the recorded nonces and any detected reuse, e.g. when the
+    /// sequence is reused with a new key.
+    pub fn reset(&mut self) {
+        self.used.clear();
+        self.reused = None;
+    }
+
+    /// Returns the wrapped sequence.
+    #[inline]
+    pub fn into_inner(self) -> NS {
+        self.inner
+    }
+}
+
+impl<NS: NonceSequence> NonceSequence for AuditedNonceSequence<NS> {
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+        self.check()?;
+        let nonce = self.inner.advance()?;
+        if !self.used.insert(*nonce.as_ref()) {
+            self.reused = Some(NonceReuseDetected {
+                nonce: *nonce.as_ref(),
+            });
+            return Err(error::Unspecified);
+        }
+        Ok(nonce)
+    }
+}
+
+impl<NS: NonceSequence> NonceSequence for &mut AuditedNonceSequence<NS> {
+    #[inline]
+    fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+        (**self).advance()
+    }
+}
+
+impl<NS> core::fmt::Debug for AuditedNonceSequence<NS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AuditedNonceSequence")
+            .field("used_count", &self.used.len())
+            .field("reused", &self.reused)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error returned by [`AuditedNonceSequence::check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceReuseDetected {
+    nonce: [u8; NONCE_LEN],
+}
+
+impl NonceReuseDetected {
+    /// The first nonce that was returned twice.
+    #[inline]
+    pub fn nonce(&self) -> &[u8; NONCE_LEN] {
+        &self.nonce
+    }
+}
+
+impl core::fmt::Display for NonceReuseDetected {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("nonce reused")
+    }
+}
+
+impl std::error::Error for NonceReuseDetected {}
+
+impl From<NonceReuseDetected> for error::Unspecified {
+    fn from(_: NonceReuseDetected) -> Self {
+        Self
+    }
+}
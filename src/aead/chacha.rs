@@ -158,7 +158,7 @@ impl Counter {
         Self::from_nonce_and_ctr(nonce, 0)
     }
 
-    fn from_nonce_and_ctr(nonce: Nonce, ctr: u32) -> Self {
+    pub(super) fn from_nonce_and_ctr(nonce: Nonce, ctr: u32) -> Self {
         let [n0, n1, n2] = nonce.as_ref().array_split_map(u32::from_le_bytes);
         Self([ctr, n0, n1, n2])
     }
@@ -202,7 +202,7 @@ impl Iv {
 
 pub const KEY_LEN: usize = 32;
 
-const BLOCK_LEN: usize = 64;
+pub(super) const BLOCK_LEN: usize = 64;
 
 #[cfg(test)]
 mod tests {
@@ -14,7 +14,9 @@
 
 use super::{
     chacha::{self, Counter, Iv},
-    poly1305, Aad, Nonce, Tag,
+    poly1305,
+    streaming::Direction,
+    Aad, Nonce, Tag,
 };
 use crate::{
     aead, cpu, error,
@@ -127,7 +129,11 @@ fn chacha20_poly1305_seal(
     poly1305_update_padded_16(&mut auth, aad.as_ref());
     chacha20_key.encrypt_in_place(counter, in_out);
     poly1305_update_padded_16(&mut auth, in_out);
-    finish(auth, aad.as_ref().len(), in_out.len())
+    finish(
+        auth,
+        aad.as_ref().len(),
+        polyfill::u64_from_usize(in_out.len()),
+    )
 }
 
 fn chacha20_poly1305_open(
@@ -206,13 +212,135 @@ fn chacha20_poly1305_open(
     poly1305_update_padded_16(&mut auth, aad.as_ref());
     poly1305_update_padded_16(&mut auth, &in_out[src.clone()]);
     chacha20_key.encrypt_within(counter, in_out, src.clone());
-    finish(auth, aad.as_ref().len(), in_out[src].len())
+    finish(
+        auth,
+        aad.as_ref().len(),
+        polyfill::u64_from_usize(in_out[src].len()),
+    )
 }
 
-fn finish(mut auth: poly1305::Context, aad_len: usize, in_out_len: usize) -> Tag {
-    let block: [[u8; 8]; 2] = [aad_len, in_out_len]
-        .map(polyfill::u64_from_usize)
-        .map(u64::to_le_bytes);
+/// Incremental ChaCha20-Poly1305 sealing or opening, for inputs that aren't
+/// all in memory at once. The input may be split anywhere, not only at block
+/// boundaries.
+pub(super) struct StreamingContext<'k> {
+    key: &'k Key,
+    direction: Direction,
+    nonce: [u8; super::NONCE_LEN],
+    next_block: u32,
+    auth: poly1305::Context,
+    aad_len: usize,
+    in_out_len: u64,
+
+    // The key stream of the block that the previous input ended in the
+    // middle of, and how much of that block was used.
+    partial_key_stream: [u8; chacha::BLOCK_LEN],
+    partial_len: usize,
+}
+
+impl<'k> StreamingContext<'k> {
+    pub(super) fn new(key: &'k Key, direction: Direction, nonce: Nonce, aad: Aad<&[u8]>) -> Self {
+        let nonce = *nonce.as_ref();
+        let mut counter = Counter::zero(Nonce::assume_unique_for_key(nonce));
+        let mut auth = poly1305::Context::from_key(derive_poly1305_key(key, counter.increment()));
+        poly1305_update_padded_16(&mut auth, aad.as_ref());
+        Self {
+            key,
+            direction,
+            nonce,
+            next_block: 1,
+            auth,
+            aad_len: aad.as_ref().len(),
+            in_out_len: 0,
+            partial_key_stream: [0; chacha::BLOCK_LEN],
+            partial_len: 0,
+        }
+    }
+
+    /// Encrypts or decrypts `in_out`, which may be any length.
+    pub(super) fn update(&mut self, in_out: &mut [u8]) {
+        self.in_out_len += polyfill::u64_from_usize(in_out.len());
+
+        // Finish the block that the previous input ended in, if any.
+        let in_out = if self.partial_len > 0 {
+            let len = core::cmp::min(chacha::BLOCK_LEN - self.partial_len, in_out.len());
+            let (partial, rest) = in_out.split_at_mut(len);
+            self.update_partial_block(partial);
+            rest
+        } else {
+            in_out
+        };
+
+        let (whole, remainder) = {
+            let in_out_len = in_out.len();
+            let whole_len = in_out_len - (in_out_len % chacha::BLOCK_LEN);
+            in_out.split_at_mut(whole_len)
+        };
+
+        if !whole.is_empty() {
+            match self.direction {
+                Direction::Sealing => {
+                    self.key.encrypt_in_place(self.counter(), whole);
+                    self.auth.update(whole);
+                }
+                Direction::Opening => {
+                    self.auth.update(whole);
+                    self.key.encrypt_in_place(self.counter(), whole);
+                }
+            }
+            self.advance(whole.len() / chacha::BLOCK_LEN);
+        }
+
+        if !remainder.is_empty() {
+            self.partial_key_stream = [0; chacha::BLOCK_LEN];
+            self.key
+                .encrypt_in_place(self.counter(), &mut self.partial_key_stream);
+            self.advance(1);
+            self.update_partial_block(remainder);
+        }
+    }
+
+    // `in_out` must fit within the rest of the partial block.
+    fn update_partial_block(&mut self, in_out: &mut [u8]) {
+        if self.direction == Direction::Opening {
+            self.auth.update(in_out);
+        }
+        let key_stream = &self.partial_key_stream[self.partial_len..];
+        in_out.iter_mut().zip(key_stream).for_each(|(b, k)| *b ^= k);
+        if self.direction == Direction::Sealing {
+            self.auth.update(in_out);
+        }
+        self.partial_len = (self.partial_len + in_out.len()) % chacha::BLOCK_LEN;
+    }
+
+    /// Returns the tag of everything that was processed.
+    pub(super) fn finish(mut self) -> Tag {
+        // The ciphertext is padded as a whole, not each input separately.
+        // Since a ChaCha20 block is a whole number of Poly1305 blocks, the
+        // ciphertext ends in the middle of a Poly1305 block only if it ends
+        // in the middle of a ChaCha20 block.
+        let remainder_len = self.partial_len % poly1305::BLOCK_LEN;
+        if remainder_len != 0 {
+            const ZEROES: [u8; poly1305::BLOCK_LEN] = [0; poly1305::BLOCK_LEN];
+            self.auth
+                .update(&ZEROES[..(poly1305::BLOCK_LEN - remainder_len)]);
+        }
+        finish(self.auth, self.aad_len, self.in_out_len)
+    }
+
+    fn counter(&self) -> Counter {
+        // The nonce is reused only with a counter that hasn't been used yet.
+        Counter::from_nonce_and_ctr(Nonce::assume_unique_for_key(self.nonce), self.next_block)
+    }
+
+    fn advance(&mut self, blocks: usize) {
+        // The caller enforces the maximum input length, so this only wraps
+        // after the final block, when it is no longer used.
+        self.next_block = self.next_block.wrapping_add(u32::try_from(blocks).unwrap());
+    }
+}
+
+fn finish(mut auth: poly1305::Context, aad_len: usize, in_out_len: u64) -> Tag {
+    let block: [[u8; 8]; 2] = [polyfill::u64_from_usize(aad_len), in_out_len].map(u64::to_le_bytes);
     auth.update(&block.array_flatten());
     auth.finish()
 }
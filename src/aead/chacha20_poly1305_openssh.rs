@@ -116,9 +116,10 @@ impl OpeningKey {
     /// `encrypted_packet_length||ciphertext` where `ciphertext` is the
     /// encrypted `plaintext`. When the function succeeds the ciphertext is
     /// replaced by the plaintext and the result is `Ok(plaintext)`, where
-    /// `plaintext` is `&ciphertext_in_plaintext_out[PACKET_LENGTH_LEN..]`;
-    /// otherwise the contents of `ciphertext_in_plaintext_out` are unspecified
-    /// and must not be used.
+    /// `plaintext` is `&ciphertext_in_plaintext_out[PACKET_LENGTH_LEN..]`.
+    ///
+    /// The tag is verified before anything is decrypted, so when the function
+    /// fails `ciphertext_in_plaintext_out` is unmodified.
     pub fn open_in_place<'a>(
         &self,
         sequence_number: u32,
@@ -129,7 +130,6 @@ impl OpeningKey {
 
         // We must verify the tag before decrypting so that
         // `ciphertext_in_plaintext_out` is unmodified if verification fails.
-        // This is beyond what we guarantee.
         let poly_key = derive_poly1305_key(&self.key.k_2, counter.increment());
         verify(poly_key, ciphertext_in_plaintext_out, tag)?;
 
@@ -0,0 +1,265 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Key-committing AEADs.
+//!
+//! An ordinary AEAD like ChaCha20-Poly1305 doesn't commit to its key: an
+//! attacker can construct a single ciphertext that successfully opens under
+//! several keys, which breaks protocols that assume a ciphertext identifies
+//! the key it was sealed with. This implements the UtC transform of
+//! [Bellare and Hoang], which prepends a commitment to the key to every
+//! ciphertext.
+//!
+//! [Bellare and Hoang]: https://eprint.iacr.org/2022/268
+
+use super::{
+    Aad, Algorithm, NonceSequence, OpeningKey, SealingKey, UnboundKey, CHACHA20_POLY1305, TAG_LEN,
+};
+use crate::{constant_time, error, hkdf};
+use core::ops::RangeFrom;
+
+/// A key-committing AEAD algorithm.
+pub struct CommittedAlgorithm {
+    aead: &'static Algorithm,
+    name: &'static str,
+}
+
+/// ChaCha20-Poly1305 with a 32-byte key commitment prepended to each
+/// ciphertext.
+///
+/// The key is 32 bytes. Both the commitment and the ChaCha20-Poly1305 key
+/// are derived from it with HKDF-SHA-256:
+///
+/// ```ascii-art
+/// prk = HKDF-Extract(salt = "", IKM = key)
+/// commitment = HKDF-Expand(prk, "ring committed aead commitment", 32)
+/// chacha20_poly1305_key = HKDF-Expand(prk, "ring committed aead key", 32)
+/// ```
+///
+/// A sealed message is `commitment || ciphertext || tag`.
+pub static COMMITTED_CHACHA20_POLY1305: CommittedAlgorithm = CommittedAlgorithm {
+    aead: &CHACHA20_POLY1305,
+    name: "COMMITTED_CHACHA20_POLY1305",
+};
+
+impl CommittedAlgorithm {
+    /// The length of the key.
+    #[inline]
+    pub fn key_len(&self) -> usize {
+        self.aead.key_len()
+    }
+
+    /// The length of the commitment prepended to each ciphertext.
+    #[inline]
+    pub fn commitment_len(&self) -> usize {
+        COMMITMENT_LEN
+    }
+
+    /// The length of a tag.
+    #[inline]
+    pub fn tag_len(&self) -> usize {
+        TAG_LEN
+    }
+
+    /// The length of the nonces.
+    #[inline]
+    pub fn nonce_len(&self) -> usize {
+        self.aead.nonce_len()
+    }
+
+    fn derive(&self, key_bytes: &[u8]) -> Result<(UnboundKey, Commitment), error::Unspecified> {
+        const COMMITMENT_LABEL: &[u8] = b"ring committed aead commitment";
+        const KEY_LABEL: &[u8] = b"ring committed aead key";
+
+        if key_bytes.len() != self.key_len() {
+            return Err(error::Unspecified);
+        }
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(key_bytes);
+        let mut commitment = [0; COMMITMENT_LEN];
+        prk.expand(&[COMMITMENT_LABEL], CommitmentLen)?
+            .fill(&mut commitment)?;
+        let key = UnboundKey::from(prk.expand(&[KEY_LABEL], self.aead)?);
+        Ok((key, Commitment(commitment)))
+    }
+}
+
+impl core::fmt::Debug for CommittedAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str(self.name)
+    }
+}
+
+impl PartialEq for CommittedAlgorithm {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CommittedAlgorithm {}
+
+const COMMITMENT_LEN: usize = 32;
+
+struct Commitment([u8; COMMITMENT_LEN]);
+
+struct CommitmentLen;
+
+impl hkdf::KeyType for CommitmentLen {
+    fn len(&self) -> usize {
+        COMMITMENT_LEN
+    }
+}
+
+/// A key-committing AEAD key for sealing, bound to a nonce sequence.
+pub struct CommittedSealingKey<N: NonceSequence> {
+    algorithm: &'static CommittedAlgorithm,
+    key: SealingKey<N>,
+    commitment: Commitment,
+}
+
+impl<N: NonceSequence> CommittedSealingKey<N> {
+    /// Constructs a new key from the given key bytes and `NonceSequence`.
+    ///
+    /// Fails if `key_bytes.len() != algorithm.key_len()`.
+    pub fn new(
+        algorithm: &'static CommittedAlgorithm,
+        key_bytes: &[u8],
+        nonce_sequence: N,
+    ) -> Result<Self, error::Unspecified> {
+        let (key, commitment) = algorithm.derive(key_bytes)?;
+        Ok(Self {
+            algorithm,
+            key: SealingKey::new(key, nonce_sequence),
+            commitment,
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static CommittedAlgorithm {
+        self.algorithm
+    }
+
+    /// Encrypts and signs (“seals”) data in place, prepending the key
+    /// commitment and appending the tag.
+    ///
+    /// On success `in_out` is `commitment || ciphertext || tag`, which is
+    /// `algorithm.commitment_len() + algorithm.tag_len()` bytes longer than
+    /// the plaintext.
+    pub fn seal_in_place_append_tag<A, InOut>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut InOut,
+    ) -> Result<(), error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+        InOut: AsMut<[u8]> + for<'in_out> Extend<&'in_out u8>,
+    {
+        let tag = self.key.seal_in_place_separate_tag(aad, in_out.as_mut())?;
+        in_out.extend(&self.commitment.0);
+        in_out.as_mut().rotate_right(COMMITMENT_LEN);
+        in_out.extend(tag.as_ref());
+        Ok(())
+    }
+}
+
+impl<N: NonceSequence> core::fmt::Debug for CommittedSealingKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("CommittedSealingKey")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+/// A key-committing AEAD key for opening, bound to a nonce sequence.
+pub struct CommittedOpeningKey<N: NonceSequence> {
+    algorithm: &'static CommittedAlgorithm,
+    key: OpeningKey<N>,
+    commitment: Commitment,
+}
+
+impl<N: NonceSequence> CommittedOpeningKey<N> {
+    /// Constructs a new key from the given key bytes and `NonceSequence`.
+    ///
+    /// Fails if `key_bytes.len() != algorithm.key_len()`.
+    pub fn new(
+        algorithm: &'static CommittedAlgorithm,
+        key_bytes: &[u8],
+        nonce_sequence: N,
+    ) -> Result<Self, error::Unspecified> {
+        let (key, commitment) = algorithm.derive(key_bytes)?;
+        Ok(Self {
+            algorithm,
+            key: OpeningKey::new(key, nonce_sequence),
+            commitment,
+        })
+    }
+
+    /// The key's algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static CommittedAlgorithm {
+        self.algorithm
+    }
+
+    /// Authenticates and decrypts (“opens”) data in place.
+    ///
+    /// `in_out` must be `commitment || ciphertext || tag`. The commitment is
+    /// checked before the tag, so a ciphertext sealed under a different key
+    /// is rejected without decrypting anything, and without advancing the
+    /// nonce sequence.
+    ///
+    /// On success, returns the plaintext, which is at the start of `in_out`.
+    /// If the commitment doesn't match, `in_out` is unmodified. If the tag
+    /// doesn't match, the would-be plaintext at the start of `in_out` is
+    /// overwritten with zeros, as with [`OpeningKey::open_in_place`].
+    #[inline]
+    pub fn open_in_place<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        self.open_within(aad, in_out, 0..)
+    }
+
+    /// Like [`Self::open_in_place`], except the commitment, ciphertext, and
+    /// tag are at `in_out[src]` and the plaintext is moved to the start of
+    /// `in_out`, as in [`OpeningKey::open_within`].
+    pub fn open_within<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+        src: RangeFrom<usize>,
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        let commitment = in_out
+            .get(src.clone())
+            .and_then(|input| input.get(..COMMITMENT_LEN))
+            .ok_or(error::Unspecified)?;
+        constant_time::verify_slices_are_equal(commitment, &self.commitment.0)?;
+        self.key
+            .open_within(aad, in_out, (src.start + COMMITMENT_LEN)..)
+    }
+}
+
+impl<N: NonceSequence> core::fmt::Debug for CommittedOpeningKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("CommittedOpeningKey")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Sealing data streamed from a `std::io::Read` to a `std::io::Write`.
+
+extern crate std;
+
+use super::{Aad, LessSafeKey, Nonce, SealingContext};
+use crate::{error, io::read, polyfill};
+use std::io::{self, Read, Write};
+
+/// Encrypts and signs ("seals") all the data read from `r` until the end of
+/// the stream, writing the ciphertext and then the tag to `w`.
+///
+/// The output is the same as if all of the input had been sealed at once with
+/// [`LessSafeKey::seal_in_place_append_tag()`], so it can be opened with any of
+/// the usual opening functions. Unlike those, `copy_encrypt` never holds more
+/// than an internal 64 KiB buffer of the input in memory. Reads interrupted
+/// with `std::io::ErrorKind::Interrupted` are retried, and short reads and
+/// partial writes are handled.
+///
+/// Returns the number of bytes read from `r`.
+///
+/// Ciphertext is written as it is produced, so on failure `w` may have been
+/// given some ciphertext without a tag; that output must be discarded. Fails
+/// with [`CopyError::Crypto`] if the input is longer than the algorithm
+/// allows for a single nonce.
+pub fn copy_encrypt<A>(
+    key: &LessSafeKey,
+    nonce: Nonce,
+    aad: Aad<A>,
+    mut r: impl Read,
+    mut w: impl Write,
+) -> Result<u64, CopyError>
+where
+    A: AsRef<[u8]>,
+{
+    let mut ctx = SealingContext::new(key, nonce, aad);
+
+    let mut buf = read::new_buffer();
+    let mut total: u64 = 0;
+    loop {
+        let len = read::read_full(&mut r, &mut buf)?;
+        let in_out = &mut buf[..len];
+        ctx.update(in_out)?;
+        w.write_all(in_out)?;
+        total += polyfill::u64_from_usize(len);
+        if len < buf.len() {
+            // End of stream.
+            w.write_all(ctx.finish().as_ref())?;
+            return Ok(total);
+        }
+    }
+}
+
+/// An error from [`copy_encrypt()`].
+#[derive(Debug)]
+pub enum CopyError {
+    /// Reading the input or writing the output failed.
+    Io(io::Error),
+
+    /// The input couldn't be sealed, e.g. because it is too long.
+    Crypto(error::Unspecified),
+}
+
+impl From<io::Error> for CopyError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<error::Unspecified> for CopyError {
+    fn from(e: error::Unspecified) -> Self {
+        Self::Crypto(e)
+    }
+}
+
+impl core::fmt::Display for CopyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Crypto(e) => write!(f, "cryptographic error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CopyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Crypto(e) => Some(e),
+        }
+    }
+}
@@ -243,6 +243,11 @@ impl Context {
         f(self.inner.Xi.0)
     }
 
+    /// The raw GHASH state, for POLYVAL.
+    pub(super) fn into_block(self) -> Block {
+        self.inner.Xi.0
+    }
+
     #[cfg(target_arch = "x86_64")]
     pub(super) fn is_avx2(&self) -> bool {
         match detect_implementation(self.cpu_features) {
@@ -143,12 +143,66 @@ impl LessSafeKey {
         seal_in_place_separate_tag_(self, nonce, Aad::from(aad.as_ref()), in_out)
     }
 
+    /// Seals `plaintext`, writing `tag || ciphertext` to `out`, for wire
+    /// formats that put the tag before the ciphertext.
+    ///
+    /// `out` must be exactly `plaintext.len() + TAG_LEN` bytes long. The
+    /// plaintext is copied into `out` once and then encrypted in place.
+    ///
+    /// `nonce` must be unique for every use of the key to seal data.
+    pub fn seal_from_slice_tag_first<A>(
+        &self,
+        nonce: Nonce,
+        aad: Aad<A>,
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<(), error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        if plaintext.len().checked_add(TAG_LEN) != Some(out.len()) {
+            return Err(error::Unspecified);
+        }
+        let (tag_out, in_out) = out.split_at_mut(TAG_LEN);
+        in_out.copy_from_slice(plaintext);
+        let tag = seal_in_place_separate_tag_(self, nonce, Aad::from(aad.as_ref()), in_out)?;
+        tag_out.copy_from_slice(tag.as_ref());
+        Ok(())
+    }
+
+    /// Opens `in_out`, which is `tag || ciphertext`, as written by
+    /// [`Self::seal_from_slice_tag_first()`].
+    ///
+    /// The plaintext is decrypted into the front of `in_out`, over the tag,
+    /// without a separate copy, and the plaintext part of `in_out` is
+    /// returned.
+    ///
+    /// `nonce` must be unique for every use of the key to open data.
+    pub fn open_tag_first<'in_out, A>(
+        &self,
+        nonce: Nonce,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        let received_tag = in_out.get(..TAG_LEN).ok_or(error::Unspecified)?;
+        let received_tag = received_tag.try_into()?;
+        self.open_in_place_separate_tag(nonce, aad, received_tag, in_out, TAG_LEN..)
+    }
+
     /// The key's AEAD algorithm.
     #[inline]
     pub fn algorithm(&self) -> &'static Algorithm {
         self.algorithm
     }
 
+    #[inline]
+    pub(super) fn inner(&self) -> &KeyInner {
+        &self.inner
+    }
+
     pub(super) fn fmt_debug(
         &self,
         type_name: &'static str,
@@ -169,6 +223,12 @@ fn open_within_<'in_out>(
     src: RangeFrom<usize>,
 ) -> Result<&'in_out mut [u8], error::Unspecified> {
     let ciphertext_len = in_out.get(src.clone()).ok_or(error::Unspecified)?.len();
+    trace_span!(
+        crate::trace::AEAD_OPEN,
+        algorithm = ?key.algorithm,
+        ciphertext_len,
+        aad_len = aad.as_ref().len()
+    );
     check_per_nonce_max_bytes(key.algorithm, ciphertext_len)?;
 
     let Tag(calculated_tag) = (key.algorithm.open)(&key.inner, nonce, aad, in_out, src);
@@ -197,6 +257,12 @@ pub(super) fn seal_in_place_separate_tag_(
     aad: Aad<&[u8]>,
     in_out: &mut [u8],
 ) -> Result<Tag, error::Unspecified> {
+    trace_span!(
+        crate::trace::AEAD_SEAL,
+        algorithm = ?key.algorithm,
+        plaintext_len = in_out.len(),
+        aad_len = aad.as_ref().len()
+    );
     check_per_nonce_max_bytes(key.algorithm(), in_out.len())?;
     Ok((key.algorithm.seal)(&key.inner, nonce, aad, in_out))
 }
@@ -21,7 +21,7 @@
 //! [AEAD]: https://eprint.iacr.org/2000/025.pdf
 //! [`crypto.cipher.AEAD`]: https://golang.org/pkg/crypto/cipher/#AEAD
 
-use super::{Aad, Algorithm, BoundKey, LessSafeKey, NonceSequence, UnboundKey};
+use super::{Aad, Algorithm, BoundKey, LessSafeKey, NonceSequence, UnboundKey, UnboundOpeningKey};
 use crate::error;
 use core::ops::RangeFrom;
 
@@ -37,10 +37,7 @@ pub struct OpeningKey<N: NonceSequence> {
 
 impl<N: NonceSequence> BoundKey<N> for OpeningKey<N> {
     fn new(key: UnboundKey, nonce_sequence: N) -> Self {
-        Self {
-            key: key.into_inner(),
-            nonce_sequence,
-        }
+        Self::new(key, nonce_sequence)
     }
 
     #[inline]
@@ -56,6 +53,43 @@ impl<N: NonceSequence> core::fmt::Debug for OpeningKey<N> {
 }
 
 impl<N: NonceSequence> OpeningKey<N> {
+    /// Constructs a new key from the given key and `NonceSequence`.
+    ///
+    /// `key` may be an `UnboundKey` or an `UnboundOpeningKey`, but not
+    /// an `UnboundSealingKey`. This is the same as `BoundKey::new` except for
+    /// the types of key it accepts.
+    pub fn new(key: impl Into<UnboundOpeningKey>, nonce_sequence: N) -> Self {
+        Self {
+            key: key.into().into_inner(),
+            nonce_sequence,
+        }
+    }
+
+    /// The key's AEAD algorithm.
+    ///
+    /// This is the same as `BoundKey::algorithm()`, but doesn't need the
+    /// trait to be in scope.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// The nonce sequence the key is bound to.
+    #[inline]
+    pub fn nonce_sequence(&self) -> &N {
+        &self.nonce_sequence
+    }
+
+    /// The nonce sequence the key is bound to, e.g. for inspecting or
+    /// adjusting a counter in tests.
+    ///
+    /// Making the sequence return a nonce that it has already returned breaks
+    /// the security of the key; see [`NonceSequence`].
+    #[inline]
+    pub fn nonce_sequence_mut(&mut self) -> &mut N {
+        &mut self.nonce_sequence
+    }
+
     /// Authenticates and decrypts (“opens”) data in place.
     ///
     /// `aad` is the additional authenticated data (AAD), if any.
@@ -65,8 +99,10 @@ impl<N: NonceSequence> OpeningKey<N> {
     /// has been overwritten by the plaintext; `plaintext` will refer to the
     /// plaintext without the tag.
     ///
-    /// When `open_in_place()` returns `Err(..)`, `in_out` may have been
-    /// overwritten in an unspecified way.
+    /// When `open_in_place()` returns `Err(..)` because authentication
+    /// failed, the would-be plaintext has been overwritten with zeros, so
+    /// `in_out[..(in_out.len() - TAG_LEN)]` is all zeros. See
+    /// [Authentication failures](super#authentication-failures).
     #[inline]
     pub fn open_in_place<'in_out, A>(
         &mut self,
@@ -104,8 +140,10 @@ impl<N: NonceSequence> OpeningKey<N> {
     /// Similarly, `key.open_within(aad, in_out, 0..)` is equivalent to
     /// `key.open_in_place(aad, in_out)`.
     ///
-    ///  When `open_in_place()` returns `Err(..)`, `in_out` may have been
-    /// overwritten in an unspecified way.
+    /// When `open_within()` returns `Err(..)` because authentication failed,
+    /// `in_out[0..plaintext_len]`, where the plaintext would have been, is all
+    /// zeros, and the rest of `in_out` holds ciphertext or what it held
+    /// before.
     ///
     /// The shifting feature is useful in the case where multiple packets are
     /// being reassembled in place. Consider this example where the peer has
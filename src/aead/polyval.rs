@@ -0,0 +1,203 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! POLYVAL, the universal hash function of AES-GCM-SIV ([RFC 8452]).
+//!
+//! POLYVAL is the little-endian counterpart of GHASH. It is computed with the
+//! same carry-less multiplication implementations as AES-GCM (PMULL on
+//! AArch64, CLMUL on x86 and x86-64, NEON on ARM, and a portable fallback)
+//! using the transformation of [RFC 8452 Appendix A]:
+//!
+//! ```text
+//! POLYVAL(H, X_1, ..., X_n) =
+//!     ByteReverse(GHASH(mulX_GHASH(ByteReverse(H)),
+//!                       ByteReverse(X_1), ..., ByteReverse(X_n)))
+//! ```
+//!
+//! [RFC 8452]: https://tools.ietf.org/html/rfc8452
+//! [RFC 8452 Appendix A]: https://tools.ietf.org/html/rfc8452#appendix-A
+
+use super::{block::Block, gcm, Aad};
+use crate::cpu;
+
+/// The length of a POLYVAL key, input block, and output, in bytes.
+pub const BLOCK_LEN: usize = super::block::BLOCK_LEN;
+
+/// A POLYVAL key, *H*.
+#[derive(Clone)]
+pub struct Key {
+    inner: gcm::Key,
+}
+
+impl Key {
+    /// Constructs a new `Key`.
+    pub fn new(h: &[u8; BLOCK_LEN]) -> Self {
+        let h = mul_x_ghash(byte_reverse(*h));
+        Self {
+            inner: gcm::Key::new(Block::from(&h), cpu::features()),
+        }
+    }
+}
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Key").finish_non_exhaustive()
+    }
+}
+
+/// A context for computing POLYVAL incrementally.
+pub struct Context {
+    inner: gcm::Context,
+}
+
+impl Context {
+    /// Starts computing POLYVAL with `key`.
+    pub fn new(key: &Key) -> Self {
+        Self {
+            inner: gcm::Context::new(&key.inner, Aad::from(&[][..])),
+        }
+    }
+
+    /// Updates the hash with `input`.
+    ///
+    /// If the length of `input` isn't a multiple of the block length then
+    /// its last block is padded with zeros. Thus, only the last call to
+    /// `update_blocks` for a message may have a non-block-aligned input.
+    pub fn update_blocks(&mut self, input: &[u8]) {
+        // Reverse the bytes of each block in a buffer of several blocks so
+        // that the GHASH implementation can process them in bulk.
+        const CHUNK_BLOCKS: usize = 16;
+        let mut buf = [0u8; CHUNK_BLOCKS * BLOCK_LEN];
+
+        for chunk in input.chunks(buf.len()) {
+            let padded_len = (chunk.len() + BLOCK_LEN - 1) / BLOCK_LEN * BLOCK_LEN;
+            let buf = &mut buf[..padded_len];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            buf[chunk.len()..].fill(0);
+            buf.chunks_exact_mut(BLOCK_LEN)
+                .for_each(|block| block.reverse());
+            self.inner.update_blocks(buf);
+        }
+    }
+
+    /// Returns the POLYVAL of the input.
+    pub fn finish(self) -> [u8; BLOCK_LEN] {
+        byte_reverse(*self.inner.into_block().as_ref())
+    }
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("Context").finish_non_exhaustive()
+    }
+}
+
+#[inline]
+fn byte_reverse(mut a: [u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+    a.reverse();
+    a
+}
+
+// RFC 8452 Appendix A: Multiplies `a` by x in GHASH's field, in constant
+// time.
+fn mul_x_ghash(a: [u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+    let v = u128::from_be_bytes(a);
+    let mask = 0u128.wrapping_sub(v & 1);
+    ((v >> 1) ^ (mask & (0xe1 << 120))).to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    // RFC 8452 Appendix A: Multiplies `a` by x in POLYVAL's field.
+    fn mul_x_polyval(a: [u8; BLOCK_LEN]) -> [u8; BLOCK_LEN] {
+        let v = u128::from_le_bytes(a);
+        let carry = v >> 127;
+        let reduction = (1 << 127) | (1 << 126) | (1 << 121) | 1;
+        ((v << 1) ^ (0u128.wrapping_sub(carry) & reduction)).to_le_bytes()
+    }
+
+    fn ghash(h: &[u8; BLOCK_LEN], input: &[u8]) -> [u8; BLOCK_LEN] {
+        let key = gcm::Key::new(Block::from(h), cpu::features());
+        let mut ctx = gcm::Context::new(&key, Aad::from(&[][..]));
+        if !input.is_empty() {
+            ctx.update_blocks(input);
+        }
+        *ctx.into_block().as_ref()
+    }
+
+    #[test]
+    fn test_mul_x_ghash() {
+        // RFC 8452 Appendix A.
+        let h = test::from_hex("25629347589242761d31f826ba4b757b").unwrap();
+        let expected = test::from_hex("dcbaa5dd137c188ebb21492c23c9b112").unwrap();
+        let h: [u8; BLOCK_LEN] = h.try_into().unwrap();
+        assert_eq!(&mul_x_ghash(byte_reverse(h))[..], &expected[..]);
+    }
+
+    // GHASH(H, X_1, ..., X_n) =
+    //     ByteReverse(POLYVAL(mulX_POLYVAL(ByteReverse(H)),
+    //                         ByteReverse(X_1), ..., ByteReverse(X_n)))
+    //
+    // This is the inverse of the transformation that `Key` uses, so it checks
+    // POLYVAL against GHASH independently of it.
+    #[test]
+    fn test_differential_ghash() {
+        let mut h = [0u8; BLOCK_LEN];
+        let mut input = [0u8; 37 * BLOCK_LEN];
+        for (i, b) in h.iter_mut().chain(input.iter_mut()).enumerate() {
+            *b = (i.wrapping_mul(0x9d) ^ (i >> 3)).to_le_bytes()[0];
+        }
+
+        for num_blocks in [0, 1, 2, 15, 16, 17, 37] {
+            let ghash_input = &input[..(num_blocks * BLOCK_LEN)];
+            let expected = ghash(&h, ghash_input);
+
+            let mut polyval_input = [0u8; 37 * BLOCK_LEN];
+            let polyval_input = &mut polyval_input[..ghash_input.len()];
+            polyval_input.copy_from_slice(ghash_input);
+            polyval_input
+                .chunks_exact_mut(BLOCK_LEN)
+                .for_each(|block| block.reverse());
+
+            let key = Key::new(&mul_x_polyval(byte_reverse(h)));
+            let mut ctx = Context::new(&key);
+            ctx.update_blocks(polyval_input);
+            assert_eq!(byte_reverse(ctx.finish()), expected);
+        }
+    }
+
+    #[test]
+    fn test_unaligned_is_zero_padded() {
+        let h = [0x5a; BLOCK_LEN];
+        let key = Key::new(&h);
+        let input = [0xa5u8; 3 * BLOCK_LEN];
+
+        for len in 1..input.len() {
+            let mut padded = [0u8; 3 * BLOCK_LEN];
+            padded[..len].copy_from_slice(&input[..len]);
+            let padded_len = (len + BLOCK_LEN - 1) / BLOCK_LEN * BLOCK_LEN;
+
+            let mut expected = Context::new(&key);
+            expected.update_blocks(&padded[..padded_len]);
+
+            let mut actual = Context::new(&key);
+            actual.update_blocks(&input[..len]);
+
+            assert_eq!(actual.finish(), expected.finish());
+        }
+    }
+}
@@ -22,12 +22,14 @@ use crate::{
 };
 
 /// A key for generating QUIC Header Protection masks.
+#[derive(Clone)]
 pub struct HeaderProtectionKey {
     inner: KeyInner,
     algorithm: &'static Algorithm,
 }
 
 #[allow(clippy::large_enum_variant, variant_size_differences)]
+#[derive(Clone)]
 enum KeyInner {
     Aes(aes::Key),
     ChaCha20(chacha::Key),
@@ -0,0 +1,177 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Aad, Algorithm, AlgorithmID, LessSafeKey, Nonce, UnboundKey, NONCE_LEN};
+use crate::{error, rand};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// A key that seals with randomly-generated nonces and limits how many
+/// messages it seals.
+///
+/// With random 96-bit nonces, the probability that two messages sealed with
+/// the same key share a nonce reaches the 2**-32 bound of
+/// [NIST SP 800-38D Section 8.3] after 2**32 messages. A `RandomNonceKey`
+/// counts the messages it seals and refuses to seal any more once it reaches
+/// its limit, which is 2**32 by default for every algorithm in this module.
+///
+/// The count is atomic, so a `RandomNonceKey` can be shared between threads
+/// when `R` is `Sync`.
+///
+/// Generating a fresh key for each message doesn't avoid the need for a
+/// long-term key: a per-message key that is stored alongside the ciphertext
+/// in the clear lets anybody who has the ciphertext decrypt it. A per-message
+/// key must itself be protected, e.g. sealed with a long-term key or derived
+/// with `agreement`, which is what a `RandomNonceKey` does more cheaply.
+///
+/// ```
+/// use ring::{aead, rand};
+///
+/// let key = aead::UnboundKey::new(&aead::AES_256_GCM, &[0; 32]).unwrap();
+/// let key = aead::RandomNonceKey::new(key, rand::SystemRandom::new());
+/// let mut in_out = b"hello".to_vec();
+/// let nonce = key.seal_append(aead::Aad::empty(), &mut in_out).unwrap();
+/// assert_eq!(key.invocations(), 1);
+///
+/// let plaintext = key.open_in_place(nonce, aead::Aad::empty(), &mut in_out).unwrap();
+/// assert_eq!(plaintext, b"hello");
+/// ```
+///
+/// [NIST SP 800-38D Section 8.3]:
+///     https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+pub struct RandomNonceKey<R: rand::SecureRandom> {
+    key: LessSafeKey,
+    rng: R,
+    invocations: AtomicU64,
+    limit: u64,
+}
+
+impl<R: rand::SecureRandom> RandomNonceKey<R> {
+    /// Constructs a key that generates nonces with `rng`, with the default
+    /// limit for its algorithm.
+    pub fn new(key: UnboundKey, rng: R) -> Self {
+        let limit = default_limit(key.algorithm());
+        Self::new_(key, rng, limit)
+    }
+
+    /// Constructs a key that generates nonces with `rng` and seals at most
+    /// `limit` messages.
+    ///
+    /// Fails if `limit` is larger than the default limit for the key's
+    /// algorithm.
+    pub fn with_limit(key: UnboundKey, rng: R, limit: u64) -> Result<Self, error::Unspecified> {
+        if limit > default_limit(key.algorithm()) {
+            return Err(error::Unspecified);
+        }
+        Ok(Self::new_(key, rng, limit))
+    }
+
+    fn new_(key: UnboundKey, rng: R, limit: u64) -> Self {
+        Self {
+            key: LessSafeKey::new(key),
+            rng,
+            invocations: AtomicU64::new(0),
+            limit,
+        }
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// The number of messages the key may seal.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// The number of times `seal_append` has been called, not counting calls
+    /// that were refused because the limit was reached.
+    #[inline]
+    pub fn invocations(&self) -> u64 {
+        self.invocations.load(Ordering::Relaxed)
+    }
+
+    /// Like [`LessSafeKey::seal_in_place_append_tag()`], except the nonce is
+    /// generated randomly and returned.
+    ///
+    /// The nonce isn't part of the output; the caller must send it along with
+    /// the ciphertext.
+    ///
+    /// Fails without sealing anything if the key has reached its limit. Each
+    /// call counts towards the limit, even if it fails for another reason.
+    pub fn seal_append<A, InOut>(
+        &self,
+        aad: Aad<A>,
+        in_out: &mut InOut,
+    ) -> Result<Nonce, error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+        InOut: AsMut<[u8]> + for<'in_out> Extend<&'in_out u8>,
+    {
+        let limit = self.limit;
+        let _: u64 = self
+            .invocations
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                if n < limit {
+                    Some(n + 1)
+                } else {
+                    None
+                }
+            })
+            .map_err(|_| error::Unspecified)?;
+
+        let mut nonce = [0; NONCE_LEN];
+        self.rng.fill(&mut nonce)?;
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), aad, in_out)?;
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+
+    /// Like [`LessSafeKey::open_in_place()`]. Opening doesn't count towards
+    /// the limit.
+    #[inline]
+    pub fn open_in_place<'in_out, A>(
+        &self,
+        nonce: Nonce,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        self.key.open_in_place(nonce, aad, in_out)
+    }
+}
+
+impl<R: rand::SecureRandom> core::fmt::Debug for RandomNonceKey<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("RandomNonceKey")
+            .field("algorithm", &self.algorithm())
+            .field("invocations", &self.invocations())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+fn default_limit(algorithm: &'static Algorithm) -> u64 {
+    // Every algorithm here has 96-bit nonces. An extended-nonce algorithm
+    // such as XChaCha20-Poly1305 could have an effectively unlimited default.
+    match algorithm.id {
+        AlgorithmID::AES_128_GCM | AlgorithmID::AES_256_GCM | AlgorithmID::CHACHA20_POLY1305 => {
+            1 << 32
+        }
+    }
+}
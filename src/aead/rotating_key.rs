@@ -0,0 +1,335 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Aad, Algorithm, NonceSequence, OpeningKey, SealingKey, Tag, UnboundKey, MAX_KEY_LEN};
+use crate::{error, hkdf, polyfill};
+use core::ops::RangeFrom;
+
+/// A [`SealingKey`] that can be replaced with the next key in its key
+/// sequence.
+///
+/// Rotating a key requires its raw bytes, so unlike a `SealingKey`, a
+/// `RotatingSealingKey` keeps a copy of them, which is zeroized when the key
+/// is dropped or rotated.
+///
+/// This is intended for long-lived connections that periodically update
+/// their traffic keys, as TLS 1.3 does with its `KeyUpdate` message
+/// ([RFC 8446 Section 4.6.3]). Since each key is used with a new nonce
+/// sequence, rotation also resets any limit the nonce sequence places on the
+/// number of records that can be sealed.
+///
+/// ```
+/// use ring::{aead, error};
+///
+/// struct Counter(u64);
+///
+/// impl aead::NonceSequence for Counter {
+///     fn advance(&mut self) -> Result<aead::Nonce, error::Unspecified> {
+///         let mut nonce = [0; aead::NONCE_LEN];
+///         nonce[4..].copy_from_slice(&self.0.to_be_bytes());
+///         self.0 = self.0.checked_add(1).ok_or(error::Unspecified)?;
+///         Ok(aead::Nonce::assume_unique_for_key(nonce))
+///     }
+/// }
+///
+/// # fn main() -> Result<(), error::Unspecified> {
+/// let key_bytes = [0x42; 32];
+/// let mut sealing_key =
+///     aead::RotatingSealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes, Counter(0))?;
+/// let mut opening_key =
+///     aead::RotatingOpeningKey::new(&aead::CHACHA20_POLY1305, &key_bytes, Counter(0))?;
+///
+/// for epoch in 0u32..3 {
+///     let mut record = b"hello".to_vec();
+///     sealing_key.seal_in_place_append_tag(aead::Aad::empty(), &mut record)?;
+///     let plaintext = opening_key.open_in_place(aead::Aad::empty(), &mut record)?;
+///     assert_eq!(plaintext, b"hello");
+///
+///     // Both sides agree to rotate, e.g. after a `KeyUpdate` message.
+///     let new_key_material = epoch.to_be_bytes();
+///     sealing_key = sealing_key.rotate(&new_key_material, Counter(0))?;
+///     opening_key = opening_key.rotate(&new_key_material, Counter(0))?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [RFC 8446 Section 4.6.3]: https://tools.ietf.org/html/rfc8446#section-4.6.3
+pub struct RotatingSealingKey<N: NonceSequence> {
+    key: SealingKey<N>,
+    key_bytes: KeyBytes,
+}
+
+impl<N: NonceSequence> core::fmt::Debug for RotatingSealingKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("RotatingSealingKey")
+            .field("algorithm", &self.key.algorithm())
+            .finish()
+    }
+}
+
+impl<N: NonceSequence> RotatingSealingKey<N> {
+    /// Constructs a new key from `key_bytes`, bound to `nonce_sequence`.
+    ///
+    /// Fails if `key_bytes.len() != algorithm.key_len()`.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+        nonce_sequence: N,
+    ) -> Result<Self, error::Unspecified> {
+        let key = UnboundKey::new(algorithm, key_bytes)?;
+        Ok(Self {
+            key: SealingKey::new(key, nonce_sequence),
+            key_bytes: KeyBytes::new(key_bytes),
+        })
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// Replaces this key with the next key in its key sequence, bound to the
+    /// fresh nonce sequence `nonce_sequence`.
+    ///
+    /// The next key is derived from the current key and `new_key_material`
+    /// using HKDF-SHA-256. `self` is consumed, so the current key can no
+    /// longer be used once it has been rotated; if the current key is later
+    /// compromised, records sealed under a rotated key are still protected
+    /// as long as `new_key_material` is secret. The peer must rotate its
+    /// `RotatingOpeningKey` with the same `new_key_material` at the same
+    /// point in the stream of records.
+    pub fn rotate<N2: NonceSequence>(
+        self,
+        new_key_material: &[u8],
+        nonce_sequence: N2,
+    ) -> Result<RotatingSealingKey<N2>, error::Unspecified> {
+        let algorithm = self.algorithm();
+        let key_bytes = self.key_bytes.derive_next(algorithm, new_key_material)?;
+        let key = UnboundKey::new(algorithm, key_bytes.as_ref())?;
+        Ok(RotatingSealingKey {
+            key: SealingKey::new(key, nonce_sequence),
+            key_bytes,
+        })
+    }
+
+    /// See [`SealingKey::seal_in_place_append_tag`].
+    #[inline]
+    pub fn seal_in_place_append_tag<A, InOut>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut InOut,
+    ) -> Result<(), error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+        InOut: AsMut<[u8]> + for<'in_out> Extend<&'in_out u8>,
+    {
+        self.key.seal_in_place_append_tag(aad, in_out)
+    }
+
+    /// See [`SealingKey::seal_in_place_separate_tag`].
+    #[inline]
+    pub fn seal_in_place_separate_tag<A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+    ) -> Result<Tag, error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        self.key.seal_in_place_separate_tag(aad, in_out)
+    }
+}
+
+/// An [`OpeningKey`] that can be replaced with the next key in its key
+/// sequence.
+///
+/// This is the counterpart of [`RotatingSealingKey`]; see its documentation
+/// for details and an example.
+pub struct RotatingOpeningKey<N: NonceSequence> {
+    key: OpeningKey<N>,
+    key_bytes: KeyBytes,
+}
+
+impl<N: NonceSequence> core::fmt::Debug for RotatingOpeningKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("RotatingOpeningKey")
+            .field("algorithm", &self.key.algorithm())
+            .finish()
+    }
+}
+
+impl<N: NonceSequence> RotatingOpeningKey<N> {
+    /// Constructs a new key from `key_bytes`, bound to `nonce_sequence`.
+    ///
+    /// Fails if `key_bytes.len() != algorithm.key_len()`.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        key_bytes: &[u8],
+        nonce_sequence: N,
+    ) -> Result<Self, error::Unspecified> {
+        let key = UnboundKey::new(algorithm, key_bytes)?;
+        Ok(Self {
+            key: OpeningKey::new(key, nonce_sequence),
+            key_bytes: KeyBytes::new(key_bytes),
+        })
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// Replaces this key with the next key in its key sequence, bound to the
+    /// fresh nonce sequence `nonce_sequence`.
+    ///
+    /// See [`RotatingSealingKey::rotate`].
+    pub fn rotate<N2: NonceSequence>(
+        self,
+        new_key_material: &[u8],
+        nonce_sequence: N2,
+    ) -> Result<RotatingOpeningKey<N2>, error::Unspecified> {
+        let algorithm = self.algorithm();
+        let key_bytes = self.key_bytes.derive_next(algorithm, new_key_material)?;
+        let key = UnboundKey::new(algorithm, key_bytes.as_ref())?;
+        Ok(RotatingOpeningKey {
+            key: OpeningKey::new(key, nonce_sequence),
+            key_bytes,
+        })
+    }
+
+    /// See [`OpeningKey::open_in_place`].
+    #[inline]
+    pub fn open_in_place<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        self.key.open_in_place(aad, in_out)
+    }
+
+    /// See [`OpeningKey::open_within`].
+    #[inline]
+    pub fn open_within<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+        ciphertext_and_tag: RangeFrom<usize>,
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        self.key.open_within(aad, in_out, ciphertext_and_tag)
+    }
+}
+
+/// The raw bytes of a key, zeroized when dropped.
+///
+/// Intentionally not `Clone` or `Copy`.
+struct KeyBytes {
+    bytes: [u8; MAX_KEY_LEN],
+    len: usize,
+}
+
+impl KeyBytes {
+    fn new(key_bytes: &[u8]) -> Self {
+        // `key_bytes` was already checked to have the correct length.
+        let mut bytes = [0; MAX_KEY_LEN];
+        bytes[..key_bytes.len()].copy_from_slice(key_bytes);
+        Self {
+            bytes,
+            len: key_bytes.len(),
+        }
+    }
+
+    /// Derives the next key in the sequence of keys for `algorithm`:
+    ///
+    /// ```ascii-art
+    /// prk = HKDF-Extract(salt = new_key_material, IKM = current key)
+    /// next key = HKDF-Expand(prk, "ring aead key update", algorithm.key_len())
+    /// ```
+    ///
+    /// using HKDF-SHA-256.
+    fn derive_next(
+        &self,
+        algorithm: &'static Algorithm,
+        new_key_material: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        const KEY_UPDATE_LABEL: &[u8] = b"ring aead key update";
+
+        let salt = hkdf::Salt::new(hkdf::HKDF_SHA256, new_key_material);
+        let prk = salt.extract(self.as_ref());
+        let okm = prk.expand(&[KEY_UPDATE_LABEL], algorithm)?;
+        let mut next = Self {
+            bytes: [0; MAX_KEY_LEN],
+            len: algorithm.key_len(),
+        };
+        okm.fill(&mut next.bytes[..next.len])?;
+        Ok(next)
+    }
+}
+
+impl AsRef<[u8]> for KeyBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl Drop for KeyBytes {
+    fn drop(&mut self) {
+        polyfill::zeroize(&mut self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::{
+        aead::{Nonce, AES_256_GCM, CHACHA20_POLY1305, NONCE_LEN},
+        polyfill::zeroize_hook,
+    };
+    use std::boxed::Box;
+
+    struct Zeros;
+
+    impl NonceSequence for Zeros {
+        fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+            Ok(Nonce::assume_unique_for_key([0; NONCE_LEN]))
+        }
+    }
+
+    #[test]
+    fn test_rotating_key_zeroized_on_drop() {
+        let key = Box::new(RotatingSealingKey::new(&AES_256_GCM, &[1; 32], Zeros).unwrap());
+        let key_bytes = zeroize_hook::address_and_len(&key.key_bytes.bytes[..]);
+        zeroize_hook::clear();
+        drop(key);
+        assert!(zeroize_hook::was_zeroized(key_bytes));
+    }
+
+    #[test]
+    fn test_rotating_key_wrong_length() {
+        for len in [0, 31, 33, MAX_KEY_LEN + 1, 100] {
+            let key_bytes = [1; 100];
+            assert!(RotatingOpeningKey::new(&CHACHA20_POLY1305, &key_bytes[..len], Zeros).is_err());
+        }
+    }
+}
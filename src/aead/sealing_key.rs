@@ -21,7 +21,9 @@
 //! [AEAD]: https://eprint.iacr.org/2000/025.pdf
 //! [`crypto.cipher.AEAD`]: https://golang.org/pkg/crypto/cipher/#AEAD
 
-use super::{Aad, Algorithm, BoundKey, LessSafeKey, NonceSequence, Tag, UnboundKey};
+use super::{
+    Aad, Algorithm, BoundKey, LessSafeKey, NonceSequence, Tag, UnboundKey, UnboundSealingKey,
+};
 use crate::error;
 
 /// An AEAD key for encrypting and signing ("sealing"), bound to a nonce
@@ -36,10 +38,7 @@ pub struct SealingKey<N: NonceSequence> {
 
 impl<N: NonceSequence> BoundKey<N> for SealingKey<N> {
     fn new(key: UnboundKey, nonce_sequence: N) -> Self {
-        Self {
-            key: key.into_inner(),
-            nonce_sequence,
-        }
+        Self::new(key, nonce_sequence)
     }
 
     #[inline]
@@ -55,6 +54,43 @@ impl<N: NonceSequence> core::fmt::Debug for SealingKey<N> {
 }
 
 impl<N: NonceSequence> SealingKey<N> {
+    /// Constructs a new key from the given key and `NonceSequence`.
+    ///
+    /// `key` may be an `UnboundKey` or an `UnboundSealingKey`, but not
+    /// an `UnboundOpeningKey`. This is the same as `BoundKey::new` except for
+    /// the types of key it accepts.
+    pub fn new(key: impl Into<UnboundSealingKey>, nonce_sequence: N) -> Self {
+        Self {
+            key: key.into().into_inner(),
+            nonce_sequence,
+        }
+    }
+
+    /// The key's AEAD algorithm.
+    ///
+    /// This is the same as `BoundKey::algorithm()`, but doesn't need the
+    /// trait to be in scope.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.key.algorithm()
+    }
+
+    /// The nonce sequence the key is bound to.
+    #[inline]
+    pub fn nonce_sequence(&self) -> &N {
+        &self.nonce_sequence
+    }
+
+    /// The nonce sequence the key is bound to, e.g. for inspecting or
+    /// adjusting a counter in tests.
+    ///
+    /// Making the sequence return a nonce that it has already returned breaks
+    /// the security of the key; see [`NonceSequence`].
+    #[inline]
+    pub fn nonce_sequence_mut(&mut self) -> &mut N {
+        &mut self.nonce_sequence
+    }
+
     /// Encrypts and signs (“seals”) data in place, appending the tag to the
     /// resulting ciphertext.
     ///
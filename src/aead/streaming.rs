@@ -0,0 +1,245 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{aes_gcm, chacha20_poly1305, Aad, Algorithm, KeyInner, LessSafeKey, Nonce, Tag};
+use crate::{constant_time, error, polyfill};
+
+/// Seals a message that isn't all in memory at once, a part at a time.
+///
+/// The ciphertext and tag are the same as if the whole message had been
+/// sealed at once with [`LessSafeKey::seal_in_place_separate_tag()`], so they
+/// can be opened with any of the usual opening functions, or with
+/// [`OpeningContext`].
+///
+/// ```
+/// use ring::aead;
+///
+/// let key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &[0x42; 32])?;
+/// let key = aead::LessSafeKey::new(key);
+///
+/// let mut message = *b"a message that arrives in parts";
+/// let mut ctx = aead::SealingContext::new(
+///     &key,
+///     aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+///     aead::Aad::empty(),
+/// );
+/// for part in message.chunks_mut(5) {
+///     ctx.update(part)?;
+/// }
+/// let tag = ctx.finish();
+///
+/// let mut ciphertext = message.to_vec();
+/// ciphertext.extend_from_slice(tag.as_ref());
+/// let plaintext = key.open_in_place(
+///     aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+///     aead::Aad::empty(),
+///     &mut ciphertext,
+/// )?;
+/// assert_eq!(plaintext, b"a message that arrives in parts");
+/// # Ok::<(), ring::error::Unspecified>(())
+/// ```
+pub struct SealingContext<'k>(Inner<'k>);
+
+impl<'k> SealingContext<'k> {
+    /// Starts sealing a message.
+    ///
+    /// `nonce` must be unique for every use of the key to seal data.
+    pub fn new<A>(key: &'k LessSafeKey, nonce: Nonce, aad: Aad<A>) -> Self
+    where
+        A: AsRef<[u8]>,
+    {
+        Self(Inner::new(key, Direction::Sealing, nonce, aad))
+    }
+
+    /// Encrypts the next part of the message in place.
+    ///
+    /// The message may be split anywhere, not only at block boundaries, and
+    /// `in_out` may be empty.
+    ///
+    /// Fails, leaving `in_out` and the context unmodified, if the message
+    /// would be longer than the algorithm allows for a single nonce.
+    pub fn update(&mut self, in_out: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.0.update(in_out)
+    }
+
+    /// Returns the tag of the whole message.
+    pub fn finish(self) -> Tag {
+        self.0.context.finish()
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm
+    }
+}
+
+impl core::fmt::Debug for SealingContext<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        self.0.fmt_debug("SealingContext", f)
+    }
+}
+
+/// Opens a message that isn't all in memory at once, a part at a time.
+///
+/// **The plaintext of each part is returned before the message is
+/// authenticated**, unlike with every other opening function. Nothing may be
+/// done with any of it until [`Self::finish()`] has succeeded; if it fails,
+/// all of the plaintext must be discarded. Where the whole message fits in
+/// memory, use [`LessSafeKey::open_in_place()`] instead.
+pub struct OpeningContext<'k>(Inner<'k>);
+
+impl<'k> OpeningContext<'k> {
+    /// Starts opening a message.
+    ///
+    /// `nonce` must be the nonce that the message was sealed with.
+    pub fn new<A>(key: &'k LessSafeKey, nonce: Nonce, aad: Aad<A>) -> Self
+    where
+        A: AsRef<[u8]>,
+    {
+        Self(Inner::new(key, Direction::Opening, nonce, aad))
+    }
+
+    /// Decrypts the next part of the ciphertext, without its tag, in place.
+    ///
+    /// The ciphertext may be split anywhere, not only at block boundaries,
+    /// and `in_out` may be empty.
+    ///
+    /// Fails, leaving `in_out` and the context unmodified, if the message
+    /// would be longer than the algorithm allows for a single nonce.
+    pub fn update(&mut self, in_out: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.0.update(in_out)
+    }
+
+    /// Verifies, in constant time, that `received_tag` is the tag of the
+    /// whole message.
+    pub fn finish(self, received_tag: Tag) -> Result<(), error::Unspecified> {
+        let calculated_tag = self.0.context.finish();
+        constant_time::verify_slices_are_equal(calculated_tag.as_ref(), received_tag.as_ref())
+    }
+
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm
+    }
+}
+
+impl core::fmt::Debug for OpeningContext<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        self.0.fmt_debug("OpeningContext", f)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Direction {
+    Sealing,
+    Opening,
+}
+
+struct Inner<'k> {
+    context: Context<'k>,
+    algorithm: &'static Algorithm,
+    in_out_len: u64,
+}
+
+impl<'k> Inner<'k> {
+    fn new<A>(key: &'k LessSafeKey, direction: Direction, nonce: Nonce, aad: Aad<A>) -> Self
+    where
+        A: AsRef<[u8]>,
+    {
+        let aad = Aad::from(aad.as_ref());
+        let context = match key.inner() {
+            KeyInner::AesGcm(key) => {
+                Context::AesGcm(aes_gcm::StreamingContext::new(key, direction, nonce, aad))
+            }
+            KeyInner::ChaCha20Poly1305(key) => Context::ChaCha20Poly1305(
+                chacha20_poly1305::StreamingContext::new(key, direction, nonce, aad),
+            ),
+        };
+        Self {
+            context,
+            algorithm: key.algorithm(),
+            in_out_len: 0,
+        }
+    }
+
+    fn update(&mut self, in_out: &mut [u8]) -> Result<(), error::Unspecified> {
+        self.in_out_len = self
+            .in_out_len
+            .checked_add(polyfill::u64_from_usize(in_out.len()))
+            .filter(|len| *len <= self.algorithm.max_input_len)
+            .ok_or(error::Unspecified)?;
+        match &mut self.context {
+            Context::AesGcm(ctx) => ctx.update(in_out),
+            Context::ChaCha20Poly1305(ctx) => ctx.update(in_out),
+        }
+        Ok(())
+    }
+
+    fn fmt_debug(
+        &self,
+        type_name: &'static str,
+        f: &mut core::fmt::Formatter,
+    ) -> Result<(), core::fmt::Error> {
+        f.debug_struct(type_name)
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+#[allow(clippy::large_enum_variant, variant_size_differences)]
+enum Context<'k> {
+    AesGcm(aes_gcm::StreamingContext<'k>),
+    ChaCha20Poly1305(chacha20_poly1305::StreamingContext<'k>),
+}
+
+impl Context<'_> {
+    fn finish(self) -> Tag {
+        match self {
+            Self::AesGcm(ctx) => ctx.finish(),
+            Self::ChaCha20Poly1305(ctx) => ctx.finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aead::{UnboundKey, AES_256_GCM, CHACHA20_POLY1305, NONCE_LEN};
+
+    #[test]
+    fn test_max_input_len() {
+        for alg in [&AES_256_GCM, &CHACHA20_POLY1305] {
+            let key = LessSafeKey::new(UnboundKey::new(alg, &[0; 32]).unwrap());
+            let nonce = || Nonce::assume_unique_for_key([0; NONCE_LEN]);
+
+            let mut sealing = SealingContext::new(&key, nonce(), Aad::empty());
+            let mut opening = OpeningContext::new(&key, nonce(), Aad::empty());
+            // Act as though all but the last two bytes of the maximum were
+            // already processed.
+            sealing.0.in_out_len = alg.max_input_len - 2;
+            opening.0.in_out_len = alg.max_input_len - 2;
+
+            for ctx in [&mut sealing.0, &mut opening.0] {
+                let mut in_out = [1u8; 3];
+                assert!(ctx.update(&mut in_out).is_err());
+                assert_eq!(in_out, [1; 3]);
+                assert!(ctx.update(&mut in_out[..2]).is_ok());
+                assert!(ctx.update(&mut []).is_ok());
+                assert!(ctx.update(&mut in_out[..1]).is_err());
+            }
+        }
+    }
+}
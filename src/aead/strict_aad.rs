@@ -0,0 +1,208 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Keys that reject empty associated data.
+//!
+//! Empty AAD is perfectly secure for every AEAD in *ring*; these types are a
+//! defense-in-depth measure for applications that always bind ciphertexts to
+//! some context (a session ID, header bytes, etc.) and want a missing context
+//! to be an error rather than a silently weaker binding.
+
+use super::{
+    Aad, Algorithm, BoundKey, NonceSequence, OpeningKey, SealingKey, Tag, UnboundKey,
+    UnboundOpeningKey, UnboundSealingKey,
+};
+use crate::error;
+use core::ops::RangeFrom;
+
+/// Associated data that is known to be non-empty.
+pub struct StrictAad<A>(Aad<A>);
+
+impl<A: AsRef<[u8]>> StrictAad<A> {
+    /// Fails if `aad` is empty.
+    pub fn new(aad: Aad<A>) -> Result<Self, error::Unspecified> {
+        if aad.as_ref().is_empty() {
+            return Err(error::Unspecified);
+        }
+        Ok(Self(aad))
+    }
+
+    /// The validated associated data.
+    #[inline]
+    pub fn into_aad(self) -> Aad<A> {
+        self.0
+    }
+}
+
+impl<A: AsRef<[u8]>> AsRef<[u8]> for StrictAad<A> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl<A: core::fmt::Debug> core::fmt::Debug for StrictAad<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("StrictAad").field(&self.0).finish()
+    }
+}
+
+/// A [`SealingKey`] that fails if the associated data is empty.
+///
+/// The nonce sequence is not advanced when the associated data is rejected.
+///
+/// ```
+/// use ring::aead::{self, BoundKey, Nonce, NonceSequence, NONCE_LEN};
+/// use ring::error;
+///
+/// struct Counter(u64);
+///
+/// impl NonceSequence for Counter {
+///     fn advance(&mut self) -> Result<Nonce, error::Unspecified> {
+///         let mut nonce = [0; NONCE_LEN];
+///         nonce[4..].copy_from_slice(&self.0.to_be_bytes());
+///         self.0 += 1;
+///         Ok(Nonce::assume_unique_for_key(nonce))
+///     }
+/// }
+///
+/// let key = aead::UnboundKey::new(&aead::AES_128_GCM, &[0; 16]).unwrap();
+/// let mut key = aead::StrictSealingKey::new(key, Counter(0));
+/// let mut in_out = *b"hello";
+/// assert!(key.seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out).is_err());
+/// assert!(key.seal_in_place_separate_tag(aead::Aad::from(b"session 1"), &mut in_out).is_ok());
+/// ```
+pub struct StrictSealingKey<N: NonceSequence>(SealingKey<N>);
+
+impl<N: NonceSequence> BoundKey<N> for StrictSealingKey<N> {
+    fn new(key: UnboundKey, nonce_sequence: N) -> Self {
+        Self::new(key, nonce_sequence)
+    }
+
+    #[inline]
+    fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm()
+    }
+}
+
+impl<N: NonceSequence> core::fmt::Debug for StrictSealingKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("StrictSealingKey")
+            .field("algorithm", &self.0.algorithm())
+            .finish()
+    }
+}
+
+impl<N: NonceSequence> StrictSealingKey<N> {
+    /// Constructs a new key from the given key and `NonceSequence`.
+    ///
+    /// See [`SealingKey::new`].
+    pub fn new(key: impl Into<UnboundSealingKey>, nonce_sequence: N) -> Self {
+        Self(SealingKey::new(key, nonce_sequence))
+    }
+
+    /// Like [`SealingKey::seal_in_place_append_tag`], except it fails if
+    /// `aad` is empty.
+    #[inline]
+    pub fn seal_in_place_append_tag<A, InOut>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut InOut,
+    ) -> Result<(), error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+        InOut: AsMut<[u8]> + for<'in_out> Extend<&'in_out u8>,
+    {
+        let aad = StrictAad::new(aad)?;
+        self.0.seal_in_place_append_tag(aad.into_aad(), in_out)
+    }
+
+    /// Like [`SealingKey::seal_in_place_separate_tag`], except it fails if
+    /// `aad` is empty.
+    #[inline]
+    pub fn seal_in_place_separate_tag<A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &mut [u8],
+    ) -> Result<Tag, error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        let aad = StrictAad::new(aad)?;
+        self.0.seal_in_place_separate_tag(aad.into_aad(), in_out)
+    }
+}
+
+/// An [`OpeningKey`] that fails if the associated data is empty.
+///
+/// The nonce sequence is not advanced when the associated data is rejected.
+pub struct StrictOpeningKey<N: NonceSequence>(OpeningKey<N>);
+
+impl<N: NonceSequence> BoundKey<N> for StrictOpeningKey<N> {
+    fn new(key: UnboundKey, nonce_sequence: N) -> Self {
+        Self::new(key, nonce_sequence)
+    }
+
+    #[inline]
+    fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm()
+    }
+}
+
+impl<N: NonceSequence> core::fmt::Debug for StrictOpeningKey<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("StrictOpeningKey")
+            .field("algorithm", &self.0.algorithm())
+            .finish()
+    }
+}
+
+impl<N: NonceSequence> StrictOpeningKey<N> {
+    /// Constructs a new key from the given key and `NonceSequence`.
+    ///
+    /// See [`OpeningKey::new`].
+    pub fn new(key: impl Into<UnboundOpeningKey>, nonce_sequence: N) -> Self {
+        Self(OpeningKey::new(key, nonce_sequence))
+    }
+
+    /// Like [`OpeningKey::open_in_place`], except it fails if `aad` is
+    /// empty.
+    #[inline]
+    pub fn open_in_place<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        let aad = StrictAad::new(aad)?;
+        self.0.open_in_place(aad.into_aad(), in_out)
+    }
+
+    /// Like [`OpeningKey::open_within`], except it fails if `aad` is empty.
+    #[inline]
+    pub fn open_within<'in_out, A>(
+        &mut self,
+        aad: Aad<A>,
+        in_out: &'in_out mut [u8],
+        ciphertext_and_tag: RangeFrom<usize>,
+    ) -> Result<&'in_out mut [u8], error::Unspecified>
+    where
+        A: AsRef<[u8]>,
+    {
+        let aad = StrictAad::new(aad)?;
+        self.0
+            .open_within(aad.into_aad(), in_out, ciphertext_and_tag)
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{quic, Algorithm, AlgorithmID, LessSafeKey, Nonce, MAX_KEY_LEN, NONCE_LEN};
+use crate::{digest, error, hkdf};
+
+/// The packet protection keys for one direction of one QUIC encryption
+/// level, derived from a traffic secret as specified in [RFC 9001 Section
+/// 5.1].
+///
+/// [RFC 9001 Section 5.1]: https://www.rfc-editor.org/rfc/rfc9001#section-5.1
+pub struct TrafficKeys {
+    secret: hkdf::Prk,
+    key: LessSafeKey,
+    iv: [u8; NONCE_LEN],
+    hp: quic::HeaderProtectionKey,
+}
+
+impl TrafficKeys {
+    /// Derives the AEAD key ("quic key"), IV ("quic iv"), and header
+    /// protection key ("quic hp") for `aead_alg` from `traffic_secret`.
+    ///
+    /// The HKDF hash function is the one `traffic_secret` was created with.
+    /// The header protection algorithm is the one corresponding to
+    /// `aead_alg`.
+    pub fn derive(
+        traffic_secret: &hkdf::Prk,
+        aead_alg: &'static Algorithm,
+    ) -> Result<Self, error::Unspecified> {
+        let hp_alg = match aead_alg.id {
+            AlgorithmID::AES_128_GCM => &quic::AES_128,
+            AlgorithmID::AES_256_GCM => &quic::AES_256,
+            AlgorithmID::CHACHA20_POLY1305 => &quic::CHACHA20,
+        };
+        let mut hp_bytes = [0u8; MAX_KEY_LEN];
+        let hp_bytes = &mut hp_bytes[..hp_alg.key_len()];
+        expand_label(traffic_secret, b"quic hp", hp_bytes)?;
+        let hp = quic::HeaderProtectionKey::new(hp_alg, hp_bytes)?;
+
+        Self::new(traffic_secret.clone(), aead_alg, hp)
+    }
+
+    fn new(
+        secret: hkdf::Prk,
+        aead_alg: &'static Algorithm,
+        hp: quic::HeaderProtectionKey,
+    ) -> Result<Self, error::Unspecified> {
+        let mut key_bytes = [0u8; MAX_KEY_LEN];
+        let key_bytes = &mut key_bytes[..aead_alg.key_len()];
+        expand_label(&secret, b"quic key", key_bytes)?;
+        let key = LessSafeKey::new_(aead_alg, key_bytes)?;
+
+        let mut iv = [0u8; NONCE_LEN];
+        expand_label(&secret, b"quic iv", &mut iv)?;
+
+        Ok(Self {
+            secret,
+            key,
+            iv,
+            hp,
+        })
+    }
+
+    /// Derives the keys for the next key phase, as specified in [RFC 9001
+    /// Section 6.1].
+    ///
+    /// The new traffic secret is derived from the current one with the label
+    /// "quic ku", and the packet protection key and IV are derived from it.
+    /// The header protection key is not updated.
+    ///
+    /// [RFC 9001 Section 6.1]: https://www.rfc-editor.org/rfc/rfc9001#section-6.1
+    pub fn next_generation(&self) -> Result<Self, error::Unspecified> {
+        let hkdf_alg = self.secret.algorithm();
+        let mut secret = [0u8; digest::MAX_OUTPUT_LEN];
+        let secret = &mut secret[..hkdf::KeyType::len(&hkdf_alg)];
+        expand_label(&self.secret, b"quic ku", secret)?;
+        let secret = hkdf::Prk::new_less_safe(hkdf_alg, secret);
+
+        Self::new(secret, self.key.algorithm(), self.hp.clone())
+    }
+
+    /// The packet protection key.
+    #[inline]
+    pub fn key(&self) -> &LessSafeKey {
+        &self.key
+    }
+
+    /// The packet protection IV.
+    #[inline]
+    pub fn iv(&self) -> &[u8; NONCE_LEN] {
+        &self.iv
+    }
+
+    /// The header protection key.
+    #[inline]
+    pub fn header_protection_key(&self) -> &quic::HeaderProtectionKey {
+        &self.hp
+    }
+
+    /// The nonce for the packet with number `packet_number`: the IV XORed
+    /// with the left-padded packet number, as specified in [RFC 9001 Section
+    /// 5.3].
+    ///
+    /// [RFC 9001 Section 5.3]: https://www.rfc-editor.org/rfc/rfc9001#section-5.3
+    pub fn nonce(&self, packet_number: u64) -> Nonce {
+        let mut nonce = self.iv;
+        nonce[(NONCE_LEN - 8)..]
+            .iter_mut()
+            .zip(packet_number.to_be_bytes())
+            .for_each(|(n, pn)| *n ^= pn);
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+impl core::fmt::Debug for TrafficKeys {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("TrafficKeys")
+            .field("algorithm", self.key.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+struct Len(usize);
+
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+// HKDF-Expand-Label with an empty context, from [RFC 8446 Section 7.1], filling
+// all of `out`. `label` doesn't include the "tls13 " prefix.
+//
+// [RFC 8446 Section 7.1]: https://www.rfc-editor.org/rfc/rfc8446#section-7.1
+fn expand_label(
+    secret: &hkdf::Prk,
+    label: &[u8],
+    out: &mut [u8],
+) -> Result<(), error::Unspecified> {
+    const PREFIX: &[u8] = b"tls13 ";
+    let out_len = u16::try_from(out.len())
+        .map_err(|_| error::Unspecified)?
+        .to_be_bytes();
+    let label_len = [u8::try_from(PREFIX.len() + label.len()).map_err(|_| error::Unspecified)?];
+    let context_len = [0u8];
+    let info = [&out_len[..], &label_len, PREFIX, label, &context_len];
+    secret.expand(&info, Len(out.len()))?.fill(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    // The handshake traffic keys from the "Simple 1-RTT Handshake" of
+    // [RFC 8448 Section 3], which uses the TLS 1.3 labels rather than the QUIC
+    // ones.
+    //
+    // [RFC 8448 Section 3]: https://www.rfc-editor.org/rfc/rfc8448#section-3
+    #[test]
+    fn test_expand_label_rfc8448() {
+        struct TrafficSecret {
+            secret: &'static str,
+            key: &'static str,
+            iv: &'static str,
+        }
+        for TrafficSecret { secret, key, iv } in [
+            // Server handshake traffic secret.
+            TrafficSecret {
+                secret: "b67b7d690cc16c4e75e54213cb2d37b4e9c912bcded9105d42befd59d391ad38",
+                key: "3fce516009c21727d0f2e4e86ee403bc",
+                iv: "5d313eb2671276ee13000b30",
+            },
+            // Client handshake traffic secret.
+            TrafficSecret {
+                secret: "b3eddb126e067f35a780b3abf45e2d8f3b1a950738f52e9600746a0e27a55a21",
+                key: "dbfaa693d1762c5b666af5d950258d01",
+                iv: "5bd3c71b836e0b76bb73265f",
+            },
+        ] {
+            let secret = test::from_hex(secret).unwrap();
+            let secret = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &secret);
+
+            let mut actual_key = [0u8; 16];
+            expand_label(&secret, b"key", &mut actual_key).unwrap();
+            assert_eq!(&actual_key[..], &test::from_hex(key).unwrap()[..]);
+
+            let mut actual_iv = [0u8; NONCE_LEN];
+            expand_label(&secret, b"iv", &mut actual_iv).unwrap();
+            assert_eq!(&actual_iv[..], &test::from_hex(iv).unwrap()[..]);
+        }
+    }
+}
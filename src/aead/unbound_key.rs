@@ -49,6 +49,24 @@ impl UnboundKey {
         self.inner.algorithm()
     }
 
+    /// Restricts the key to sealing.
+    ///
+    /// The result can only be used to construct a `SealingKey`; there is no
+    /// way to convert it back into an `UnboundKey`.
+    #[inline]
+    pub fn split_sealing(self) -> UnboundSealingKey {
+        UnboundSealingKey(self)
+    }
+
+    /// Restricts the key to opening.
+    ///
+    /// The result can only be used to construct an `OpeningKey`; there is no
+    /// way to convert it back into an `UnboundKey`.
+    #[inline]
+    pub fn split_opening(self) -> UnboundOpeningKey {
+        UnboundOpeningKey(self)
+    }
+
     #[inline]
     pub(super) fn into_inner(self) -> LessSafeKey {
         self.inner
@@ -61,6 +79,84 @@ impl core::fmt::Debug for UnboundKey {
     }
 }
 
+/// An AEAD key that can only be used to construct a `SealingKey`.
+///
+/// Construct one with [`UnboundKey::split_sealing`].
+///
+/// ```compile_fail
+/// use ring::aead::{self, NonceSequence};
+///
+/// fn open<N: NonceSequence>(key: aead::UnboundSealingKey, n: N) -> aead::OpeningKey<N> {
+///     aead::OpeningKey::new(key, n) // Error: a sealing key can't open.
+/// }
+/// ```
+pub struct UnboundSealingKey(UnboundKey);
+
+impl UnboundSealingKey {
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm()
+    }
+
+    #[inline]
+    pub(super) fn into_inner(self) -> LessSafeKey {
+        self.0.into_inner()
+    }
+}
+
+impl From<UnboundKey> for UnboundSealingKey {
+    #[inline]
+    fn from(key: UnboundKey) -> Self {
+        key.split_sealing()
+    }
+}
+
+impl core::fmt::Debug for UnboundSealingKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        self.0.inner.fmt_debug("UnboundSealingKey", f)
+    }
+}
+
+/// An AEAD key that can only be used to construct an `OpeningKey`.
+///
+/// Construct one with [`UnboundKey::split_opening`].
+///
+/// ```compile_fail
+/// use ring::aead::{self, NonceSequence};
+///
+/// fn seal<N: NonceSequence>(key: aead::UnboundOpeningKey, n: N) -> aead::SealingKey<N> {
+///     aead::SealingKey::new(key, n) // Error: an opening key can't seal.
+/// }
+/// ```
+pub struct UnboundOpeningKey(UnboundKey);
+
+impl UnboundOpeningKey {
+    /// The key's AEAD algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.0.algorithm()
+    }
+
+    #[inline]
+    pub(super) fn into_inner(self) -> LessSafeKey {
+        self.0.into_inner()
+    }
+}
+
+impl From<UnboundKey> for UnboundOpeningKey {
+    #[inline]
+    fn from(key: UnboundKey) -> Self {
+        key.split_opening()
+    }
+}
+
+impl core::fmt::Debug for UnboundOpeningKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        self.0.inner.fmt_debug("UnboundOpeningKey", f)
+    }
+}
+
 impl From<hkdf::Okm<'_, &'static Algorithm>> for UnboundKey {
     fn from(okm: hkdf::Okm<&'static Algorithm>) -> Self {
         let mut key_bytes = [0; MAX_KEY_LEN];
@@ -72,3 +168,28 @@ impl From<hkdf::Okm<'_, &'static Algorithm>> for UnboundKey {
         }
     }
 }
+
+/// Derives the key identified by `key_id` for `algorithm` from `master_key`:
+///
+/// ```ascii-art
+/// prk = HKDF-Extract(salt = "", IKM = master_key)
+/// key = HKDF-Expand(prk, algorithm_name || key_id, algorithm.key_len())
+/// ```
+///
+/// using HKDF-SHA-256, where `algorithm_name` is the name of `algorithm`'s
+/// constant, e.g. `"AES_256_GCM"`. Including the name means that keys derived
+/// for different algorithms are independent even when `key_id` is the same.
+///
+/// `key_id` is typically a unique identifier such as a 16-byte UUID. Keys for
+/// different `key_id` values are independent.
+pub fn derive_key(
+    master_key: &[u8; 32],
+    algorithm: &'static Algorithm,
+    key_id: &[u8],
+) -> Result<UnboundKey, error::Unspecified> {
+    let salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]);
+    let prk = salt.extract(master_key);
+    let info = [algorithm.id.name().as_bytes(), key_id];
+    let okm = prk.expand(&info, algorithm)?;
+    Ok(UnboundKey::from(okm))
+}
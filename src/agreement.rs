@@ -56,17 +56,62 @@
 //!
 //! # Ok::<(), ring::error::Unspecified>(())
 //! ```
+//!
+//! # Key usage
+//!
+//! A private key for key agreement can't be imported; an
+//! `EphemeralPrivateKey` is only ever generated, or derived with
+//! [`crate::derive::derive_x25519_static()`], whose HKDF `info` differs from
+//! that of every signing key type. Conversely, the key pairs that can be
+//! imported from PKCS#8 or SEC1, such as `EcdsaKeyPair`, can only sign. So a
+//! key can't be used for both ECDH and signing, and there is no usage to
+//! check when a key is used:
+//!
+//! ```compile_fail
+//! use ring::{agreement, error, signature};
+//!
+//! fn agree(
+//!     key_pair: signature::EcdsaKeyPair,
+//!     peer: &agreement::UnparsedPublicKey<&[u8]>,
+//! ) -> Result<(), error::Unspecified> {
+//!     // Error: a signing key pair isn't an `EphemeralPrivateKey`.
+//!     agreement::agree_ephemeral(key_pair, peer, |_key_material| ())
+//! }
+//! ```
+//!
+//! An application that imports key material in other formats, e.g. JWK, and
+//! converts it to a *ring* key must check the format's usage fields itself.
 
 // The "NSA Guide" steps here are from from section 3.1, "Ephemeral Unified
 // Model."
 
-use crate::{cpu, debug, ec, error, rand};
+use crate::{cpu, debug, ec, error, fingerprint, rand};
 
 pub use crate::ec::{
-    curve25519::x25519::X25519,
-    suite_b::ecdh::{ECDH_P256, ECDH_P384},
+    curve25519::x25519::{X25519, X25519_PUBLIC_KEY_LEN},
+    suite_b::ecdh::{
+        ECDH_BRAINPOOLP384R1, ECDH_BRAINPOOLP384R1_PUBLIC_KEY_LEN, ECDH_P256, ECDH_P256_COMPACT,
+        ECDH_P256_COMPACT_PUBLIC_KEY_LEN, ECDH_P256_PUBLIC_KEY_LEN, ECDH_P384,
+        ECDH_P384_PUBLIC_KEY_LEN,
+    },
+};
+
+pub use self::{
+    cascade::{cascade_ecdh, CASCADE_KEY_LEN},
+    pinned::{PinnedKeyError, PinnedPublicKey},
 };
 
+mod cascade;
+mod pinned;
+
+/// The maximum length of a public key of any key agreement algorithm.
+///
+/// A buffer of this length is large enough for
+/// [`EphemeralPrivateKey::compute_public_key_into()`] regardless of the
+/// algorithm. The exact length for each algorithm is given by its
+/// `*_PUBLIC_KEY_LEN` constant, e.g. [`X25519_PUBLIC_KEY_LEN`].
+pub const MAX_PUBLIC_KEY_LEN: usize = ec::PUBLIC_KEY_MAX_LEN;
+
 /// A key agreement algorithm.
 pub struct Algorithm {
     pub(crate) curve: &'static ec::Curve,
@@ -75,10 +120,44 @@ pub struct Algorithm {
         private_key: &ec::Seed,
         peer_public_key: untrusted::Input,
     ) -> Result<(), error::Unspecified>,
+
+    // Does the same validation of the peer's public key as `ecdh`, given a
+    // public key of the correct length.
+    pub(crate) validate_public_key: fn(untrusted::Input) -> Result<(), error::Unspecified>,
 }
 
 derive_debug_via_field!(Algorithm, curve);
 
+impl Algorithm {
+    // The value of the `AlgorithmIdentifier` of the algorithm's
+    // `SubjectPublicKeyInfo`, not including the tag and length.
+    fn spki_alg_id_value(&self) -> &'static [u8] {
+        match self.curve.id {
+            // id-X25519 (RFC 8410).
+            ec::CurveID::Curve25519 => &[0x06, 0x03, 0x2b, 0x65, 0x6e],
+            // id-ecPublicKey, secp256r1 (RFC 5480).
+            ec::CurveID::P256 | ec::CurveID::P256Compact => &[
+                0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48,
+                0xce, 0x3d, 0x03, 0x01, 0x07,
+            ],
+            // id-ecPublicKey, secp384r1 (RFC 5480).
+            ec::CurveID::P384 => &[
+                0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b, 0x81, 0x04,
+                0x00, 0x22,
+            ],
+            // id-ecPublicKey, brainpoolP384r1 (RFC 5639).
+            ec::CurveID::BrainpoolP384r1 => &[
+                0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x09, 0x2b, 0x24, 0x03,
+                0x03, 0x02, 0x08, 0x01, 0x01, 0x0b,
+            ],
+        }
+    }
+
+    fn spki_fingerprint(&self, public_key: &[u8]) -> fingerprint::PublicKeyFingerprint {
+        fingerprint::spki_fingerprint(self.spki_alg_id_value(), public_key)
+    }
+}
+
 impl Eq for Algorithm {}
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Self) -> bool {
@@ -106,6 +185,11 @@ impl EphemeralPrivateKey {
         alg: &'static Algorithm,
         rng: &dyn rand::SecureRandom,
     ) -> Result<Self, error::Unspecified> {
+        trace_span!(
+            crate::trace::KEY_GENERATION,
+            algorithm = ?alg,
+            key_bits = alg.curve.elem_scalar_seed_len * 8
+        );
         let cpu_features = cpu::features();
 
         // NSA Guide Step 1.
@@ -119,6 +203,23 @@ impl EphemeralPrivateKey {
         })
     }
 
+    // `private_key` must be for the curve of `alg`.
+    pub(crate) fn from_seed(alg: &'static Algorithm, private_key: ec::Seed) -> Self {
+        Self {
+            private_key,
+            algorithm: alg,
+        }
+    }
+
+    /// Like [`Self::generate()`], except `rng` is consumed, so a generator
+    /// that is only meant to produce one key can't be used again.
+    pub fn generate_once<R>(alg: &'static Algorithm, rng: R) -> Result<Self, error::Unspecified>
+    where
+        R: rand::SecureRandom + rand::ConsumedAfterUse,
+    {
+        Self::generate(alg, &rng)
+    }
+
     /// Computes the public key from the private key.
     #[inline(always)]
     pub fn compute_public_key(&self) -> Result<PublicKey, error::Unspecified> {
@@ -135,6 +236,15 @@ impl EphemeralPrivateKey {
             })
     }
 
+    /// Computes the public key from the private key, writing it into the
+    /// start of `out` and returning its length.
+    ///
+    /// Fails if `out` is shorter than the algorithm's public key. A buffer of
+    /// [`MAX_PUBLIC_KEY_LEN`] bytes is always long enough.
+    pub fn compute_public_key_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        self.private_key.compute_public_key_into(out)
+    }
+
     /// The algorithm for the private key.
     #[inline]
     pub fn algorithm(&self) -> &'static Algorithm {
@@ -175,6 +285,18 @@ impl PublicKey {
     pub fn algorithm(&self) -> &'static Algorithm {
         self.algorithm
     }
+
+    /// Returns the fingerprint of the public key, computed over its
+    /// `SubjectPublicKeyInfo` as described in [`fingerprint`]. X25519 keys
+    /// use the `id-X25519` algorithm identifier ([RFC 8410]); P-256 and P-384
+    /// keys are encoded as for ECDSA. `ECDH_P256_COMPACT` keys are encoded in
+    /// their compact form, so their fingerprints differ from those of the same
+    /// keys used with `ECDH_P256`.
+    ///
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    pub fn spki_fingerprint(&self) -> fingerprint::PublicKeyFingerprint {
+        self.algorithm.spki_fingerprint(self.as_ref())
+    }
 }
 
 /// An unparsed, possibly malformed, public key for key agreement.
@@ -224,6 +346,38 @@ impl<B> UnparsedPublicKey<B> {
     }
 }
 
+impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
+    /// Validates the public key the same way [`agree_ephemeral`] does, without
+    /// doing a key agreement.
+    ///
+    /// `new` doesn't validate the key, and key agreement always does, so this
+    /// is only needed to reject an invalid key early, e.g. when it is
+    /// received rather than when it is used.
+    ///
+    /// For the NIST P-256 and P-384 curves and brainpoolP384r1, the point is
+    /// checked to be on the curve, not on its quadratic twist or another
+    /// curve, and not the point at infinity. These curves have cofactor 1, so
+    /// no further subgroup check is needed. For `ECDH_P256_COMPACT`, there
+    /// must be a point on the curve with the given x-coordinate.
+    ///
+    /// For `X25519`, every value of the correct length is a valid encoding
+    /// of a point on the curve or its twist, and Curve25519 is secure on its
+    /// twist, so only points of small order, for which key agreement fails,
+    /// are rejected.
+    ///
+    /// Fails with `KeyRejected::invalid_encoding()` if the key isn't the
+    /// correct length for the algorithm, and with
+    /// `KeyRejected::invalid_component()` if it is otherwise invalid.
+    pub fn validate_point(&self) -> Result<(), error::KeyRejected> {
+        let bytes = self.bytes.as_ref();
+        if bytes.len() != self.algorithm.curve.public_key_len {
+            return Err(error::KeyRejected::invalid_encoding());
+        }
+        (self.algorithm.validate_public_key)(untrusted::Input::from(bytes))
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_component())
+    }
+}
+
 /// Performs a key agreement with an ephemeral private key and the given public
 /// key.
 ///
@@ -269,6 +423,7 @@ fn agree_ephemeral_<R>(
     }
 
     let alg = &my_private_key.algorithm;
+    trace_span!(crate::trace::AGREEMENT, algorithm = ?alg);
 
     // NSA Guide Prerequisite 2, regarding which KDFs are allowed, is delegated
     // to the caller.
@@ -299,3 +454,11 @@ fn agree_ephemeral_<R>(
     // "Destroy" that doesn't meet the NSA requirement to "zeroize."
     Ok(kdf(shared_key))
 }
+
+inventory! { Agreement, Always;
+    X25519 => 256..=256,
+    ECDH_P256 => 256..=256,
+    ECDH_P256_COMPACT => 256..=256,
+    ECDH_P384 => 384..=384,
+    ECDH_BRAINPOOLP384R1 => 384..=384,
+}
@@ -0,0 +1,80 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+use crate::{error, hkdf, hmac};
+
+/// The length of the key derived by [`cascade_ecdh`].
+pub const CASCADE_KEY_LEN: usize = 32;
+
+/// Performs a chain of X25519 agreements and derives a single key from all of
+/// the shared secrets.
+///
+/// `parties` is a sequence of `(sk_i, pk_i)` pairs, usually with `pk_i` being
+/// the public key of the party whose private key is `sk_(i+1)`, so that
+/// `ss_i = X25519(sk_i, pk_i)`. The result is
+///
+/// ```ascii-art
+/// prk = HKDF-Extract(salt = "", IKM = ss_1 || ss_2 || ... || ss_n)
+/// key = HKDF-Expand(prk, shared_info, 32)
+/// ```
+///
+/// using HKDF-SHA256. The private keys are consumed, as in
+/// [`agree_ephemeral`], so each is used for only one agreement.
+///
+/// Fails if `parties` is empty, if any key isn't an X25519 key, or if any of
+/// the agreements fails. Nothing is returned on failure, not even the
+/// secrets of the agreements that succeeded.
+///
+/// # Security
+///
+/// The derived key is secret as long as at least one of the shared secrets
+/// is, so it can combine agreements with keys of different provenance.
+/// However, this is only a building block: it provides no authentication of
+/// any party, no forward secrecy beyond that of the individual agreements,
+/// and no agreement on group membership; `shared_info` should bind the
+/// derived key to the context, e.g. to the identities of the parties and a
+/// transcript of the public keys. It isn't the [RFC 9420] (MLS) key schedule
+/// of Section 8, which derives each epoch's secrets from a ratchet tree and
+/// the previous epoch rather than from a flat list of agreements.
+///
+/// [RFC 9420]: https://www.rfc-editor.org/rfc/rfc9420
+pub fn cascade_ecdh<'a, B: AsRef<[u8]> + 'a>(
+    parties: impl IntoIterator<Item = (EphemeralPrivateKey, &'a UnparsedPublicKey<B>)>,
+    shared_info: &[u8],
+) -> Result<[u8; CASCADE_KEY_LEN], error::Unspecified> {
+    // HKDF-Extract is HMAC keyed with the salt, so the shared secrets can be
+    // absorbed one at a time instead of being concatenated.
+    let salt = hmac::Key::new(hmac::HMAC_SHA256, &[]);
+    let mut extract = hmac::Context::with_key(&salt);
+    let mut agreements = 0usize;
+    for (my_private_key, peer_public_key) in parties {
+        if my_private_key.algorithm() != &X25519 {
+            return Err(error::Unspecified);
+        }
+        agree_ephemeral(my_private_key, peer_public_key, |shared_secret| {
+            extract.update(shared_secret)
+        })?;
+        agreements += 1;
+    }
+    if agreements == 0 {
+        return Err(error::Unspecified);
+    }
+
+    let prk = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, extract.sign().as_ref());
+    let mut key = [0; CASCADE_KEY_LEN];
+    prk.expand(&[shared_info], hkdf::HKDF_SHA256)?
+        .fill(&mut key)?;
+    Ok(key)
+}
@@ -0,0 +1,104 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::{agree_ephemeral, Algorithm, EphemeralPrivateKey, UnparsedPublicKey};
+use crate::{error, fingerprint};
+
+/// A pinned peer public key: the algorithm and SPKI fingerprint of the only
+/// public key a peer is allowed to use.
+///
+/// The fingerprint is the SHA-256 digest of the DER-encoded
+/// `SubjectPublicKeyInfo` of the key, as returned by
+/// [`super::PublicKey::spki_fingerprint`] and described in [`fingerprint`].
+#[derive(Clone, Debug)]
+pub struct PinnedPublicKey {
+    algorithm: &'static Algorithm,
+    fingerprint: [u8; fingerprint::FINGERPRINT_LEN],
+}
+
+impl PinnedPublicKey {
+    /// Pins the `algorithm` public key with SPKI fingerprint `fingerprint`.
+    pub fn new(
+        algorithm: &'static Algorithm,
+        fingerprint: &[u8; fingerprint::FINGERPRINT_LEN],
+    ) -> Self {
+        Self {
+            algorithm,
+            fingerprint: *fingerprint,
+        }
+    }
+
+    /// The algorithm of the pinned key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Like [`agree_ephemeral`], but first checks that `peer_public_key` is
+    /// the pinned key.
+    ///
+    /// Fails with [`PinnedKeyError::KeyMismatch`] if `peer_public_key` has a
+    /// different algorithm or fingerprint, without using `my_private_key`.
+    pub fn agree_ephemeral<B: AsRef<[u8]>, R>(
+        &self,
+        my_private_key: EphemeralPrivateKey,
+        peer_public_key: &UnparsedPublicKey<B>,
+        kdf: impl FnOnce(&[u8]) -> R,
+    ) -> Result<R, PinnedKeyError> {
+        if peer_public_key.algorithm != self.algorithm {
+            return Err(PinnedKeyError::KeyMismatch);
+        }
+        let actual = self
+            .algorithm
+            .spki_fingerprint(peer_public_key.bytes.as_ref());
+        if actual.as_ref() != self.fingerprint {
+            return Err(PinnedKeyError::KeyMismatch);
+        }
+        agree_ephemeral(my_private_key, peer_public_key, kdf)
+            .map_err(|error::Unspecified| PinnedKeyError::Unspecified)
+    }
+}
+
+/// The error returned by [`PinnedPublicKey::agree_ephemeral`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PinnedKeyError {
+    /// The peer's public key isn't the pinned key.
+    KeyMismatch,
+
+    /// The key agreement failed, e.g. because the private key is for a
+    /// different algorithm.
+    Unspecified,
+}
+
+impl core::fmt::Display for PinnedKeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::KeyMismatch => "peer public key doesn't match the pinned key",
+            Self::Unspecified => "key agreement failed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PinnedKeyError {}
+
+impl From<PinnedKeyError> for error::Unspecified {
+    fn from(_: PinnedKeyError) -> Self {
+        Self
+    }
+}
@@ -18,12 +18,28 @@ mod constant;
 pub mod bigint;
 
 pub mod montgomery;
-mod n0;
+pub(crate) mod n0;
 
 #[cfg(feature = "alloc")]
 mod nonnegative;
 
+/// The largest supported modulus size, in bits, as configured by
+/// `$RING_MAX_RSA_BITS` at build time; see build.rs.
+pub(crate) const MODULUS_MAX_BITS: usize = parse_decimal(env!("RING_MAX_RSA_BITS"));
+
 #[allow(dead_code)]
-const BIGINT_MODULUS_MAX_LIMBS: usize = 8192 / crate::limb::LIMB_BITS;
+const BIGINT_MODULUS_MAX_LIMBS: usize = MODULUS_MAX_BITS / crate::limb::LIMB_BITS;
+
+// build.rs has already validated `s`.
+const fn parse_decimal(s: &str) -> usize {
+    let s = s.as_bytes();
+    let mut value = 0;
+    let mut i = 0;
+    while i < s.len() {
+        value = (value * 10) + ((s[i] - b'0') as usize);
+        i += 1;
+    }
+    value
+}
 
 pub use constant::limbs_from_hex;
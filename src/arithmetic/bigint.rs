@@ -38,6 +38,7 @@
 
 use self::boxed_limbs::BoxedLimbs;
 pub(crate) use self::{
+    exp_consttime::{Budget, ElemExpConsttime},
     modulus::{Modulus, OwnedModulusWithOne, MODULUS_MAX_LIMBS},
     private_exponent::PrivateExponent,
 };
@@ -47,12 +48,14 @@ use crate::{
     arithmetic::montgomery::*,
     c, cpu, error,
     limb::{self, Limb, LimbMask, LIMB_BITS},
-    polyfill::u64_from_usize,
+    polyfill::{self, u64_from_usize},
 };
-use alloc::vec;
-use core::{marker::PhantomData, num::NonZeroU64};
+use alloc::{collections::TryReserveError, vec::Vec};
+use core::{marker::PhantomData, num::NonZeroU64, task::Poll};
 
 mod boxed_limbs;
+mod exp_consttime;
+pub(crate) mod keygen;
 mod modulus;
 mod private_exponent;
 
@@ -80,19 +83,6 @@ pub unsafe trait Prime {}
 /// trait preemptively.)
 pub unsafe trait SmallerModulus<L> {}
 
-/// A modulus *s* where s < l < 2*s for the given larger modulus *l*. This is
-/// the precondition for reduction by conditional subtraction,
-/// `elem_reduce_once()`.
-///
-/// # Safety
-///
-/// Some logic may assume that the invariant holds when accessing limbs within
-/// a value, e.g. by assuming that the smaller modulus is at most one limb
-/// smaller than the larger modulus. TODO: Any such logic should be
-/// encapsulated here, or this trait should be made non-`unsafe`. (In retrospect,
-/// this shouldn't have been made an `unsafe` trait preemptively.)
-pub unsafe trait SlightlySmallerModulus<L>: SmallerModulus<L> {}
-
 /// A modulus *s* where √l <= s < l for the given larger modulus *l*. This is
 /// the precondition for the more general Montgomery reduction from ℤ/lℤ to
 /// ℤ/sℤ.
@@ -105,6 +95,13 @@ pub unsafe trait SlightlySmallerModulus<L>: SmallerModulus<L> {}
 /// an `unsafe` trait preemptively.)
 pub unsafe trait NotMuchSmallerModulus<L>: SmallerModulus<L> {}
 
+/// A modulus whose value is public, so it may be cloned, formatted, and
+/// serialized (see `OwnedModulusWithOne::be_bytes`).
+///
+/// Like the other modulus traits, this is implemented by type-level
+/// representations of moduli (uninhabited `enum`s), not by the moduli
+/// themselves, so it has no methods; queries about a modulus's value belong
+/// on `OwnedModulusWithOne`.
 pub trait PublicModulus {}
 
 /// Elements of ℤ/mℤ for some modulus *m*.
@@ -136,6 +133,52 @@ impl<M, E> Elem<M, E> {
     pub fn is_zero(&self) -> bool {
         self.limbs.is_zero()
     }
+
+    /// Like `clone`, except allocation failure is reported instead of
+    /// aborting.
+    pub fn try_clone(&self) -> Result<Self, TryReserveError> {
+        Ok(Self {
+            limbs: self.limbs.try_clone()?,
+            encoding: self.encoding,
+        })
+    }
+
+    /// Overwrites the value with zeros, e.g. before dropping a secret value.
+    pub fn zeroize(&mut self) {
+        polyfill::zeroize(&mut self.limbs);
+    }
+
+    /// Moves a long-lived secret value into locked memory.
+    #[cfg(feature = "locked-memory")]
+    pub fn into_locked(self) -> Self {
+        Self {
+            limbs: self.limbs.into_locked(),
+            encoding: self.encoding,
+        }
+    }
+
+    #[cfg(feature = "locked-memory")]
+    pub fn was_locked(&self) -> bool {
+        self.limbs.was_locked()
+    }
+
+    #[cfg(test)]
+    pub fn limbs(&self) -> &[Limb] {
+        &self.limbs
+    }
+}
+
+/// Only unencoded elements can be compared. The same value has a different
+/// representation in each Montgomery encoding, and two elements that are both
+/// `R`-encoded might not be fully reduced, so comparing their limbs wouldn't
+/// tell whether their values are equal. Convert them with `into_unencoded`
+/// first.
+impl<M> subtle::ConstantTimeEq for Elem<M, Unencoded> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        let mask = limb::limbs_equal_limbs_consttime(&self.limbs, &other.limbs);
+        #[allow(clippy::cast_possible_truncation)]
+        subtle::Choice::from((mask as Limb & 1) as u8)
+    }
 }
 
 /// Does a Montgomery reduction on `limbs` assuming they are Montgomery-encoded ('R') and assuming
@@ -145,17 +188,21 @@ fn from_montgomery_amm<M>(limbs: BoxedLimbs<M>, m: &Modulus<M>) -> Elem<M, Unenc
     debug_assert_eq!(limbs.len(), m.limbs().len());
 
     let mut limbs = limbs;
-    let mut one = [0; MODULUS_MAX_LIMBS];
-    one[0] = 1;
-    let one = &one[..m.limbs().len()];
-    limbs_mont_mul(&mut limbs, one, m.limbs(), m.n0(), m.cpu_features());
+    limbs_from_montgomery_amm(&mut limbs, m.limbs(), m.n0(), m.cpu_features());
     Elem {
         limbs,
         encoding: PhantomData,
     }
 }
 
-#[cfg(any(test, not(target_arch = "x86_64")))]
+// Like `from_montgomery_amm`, but not generic over the modulus type.
+fn limbs_from_montgomery_amm(r: &mut [Limb], m: &[Limb], n0: &N0, cpu_features: cpu::Features) {
+    let mut one = [0; MODULUS_MAX_LIMBS];
+    one[0] = 1;
+    let one = &one[..m.len()];
+    limbs_mont_mul(r, one, m, n0, cpu_features);
+}
+
 impl<M> Elem<M, R> {
     #[inline]
     pub fn into_unencoded(self, m: &Modulus<M>) -> Elem<M, Unencoded> {
@@ -174,6 +221,22 @@ impl<M> Elem<M, Unencoded> {
         })
     }
 
+    /// Like `from_be_bytes_padded`, for key components: an invalid value is
+    /// rejected with `KeyRejected::invalid_component()` and allocation failure
+    /// is reported as `KeyRejected::out_of_memory()`.
+    pub fn try_from_be_bytes_padded(
+        input: untrusted::Input,
+        m: &Modulus<M>,
+    ) -> Result<Self, error::KeyRejected> {
+        let mut r = m
+            .try_zero()
+            .map_err(|_| error::KeyRejected::out_of_memory())?;
+        r.limbs
+            .set_from_be_bytes_padded_less_than(input, m)
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_component())?;
+        Ok(r)
+    }
+
     #[inline]
     pub fn fill_be_bytes(&self, out: &mut [u8]) {
         // See Falko Strenzke, "Manger's Attack revisited", ICICS 2010.
@@ -214,19 +277,6 @@ fn elem_mul_by_2<M, AF>(a: &mut Elem<M, AF>, m: &Modulus<M>) {
     }
 }
 
-pub fn elem_reduced_once<Larger, Smaller: SlightlySmallerModulus<Larger>>(
-    a: &Elem<Larger, Unencoded>,
-    m: &Modulus<Smaller>,
-) -> Elem<Smaller, Unencoded> {
-    let mut r = a.limbs.clone();
-    assert!(r.len() <= m.limbs().len());
-    limb::limbs_reduce_once_constant_time(&mut r, m.limbs());
-    Elem {
-        limbs: BoxedLimbs::new_unchecked(r.into_limbs()),
-        encoding: PhantomData,
-    }
-}
-
 #[inline]
 pub fn elem_reduced<Larger, Smaller: NotMuchSmallerModulus<Larger>>(
     a: &Elem<Larger, Unencoded>,
@@ -294,6 +344,14 @@ pub fn elem_sub<M, E>(mut a: Elem<M, E>, b: &Elem<M, E>, m: &Modulus<M>) -> Elem
     a
 }
 
+/// Returns `-a (mod m)`, i.e. `m - a` for nonzero `a` and zero for zero `a`,
+/// in constant time.
+pub fn elem_neg<M, E>(a: Elem<M, E>, m: &Modulus<M>) -> Elem<M, E> {
+    // `LIMBS_sub_mod` only adds `m` when the subtraction borrows, which it
+    // doesn't when `a` is zero.
+    elem_sub(m.zero(), &a, m)
+}
+
 // The value 1, Montgomery-encoded some number of times.
 pub struct One<M, E>(Elem<M, E>);
 
@@ -305,13 +363,13 @@ impl<M> One<M, RR> {
     // values, using `LIMB_BITS` here, rather than `N0::LIMBS_USED * LIMB_BITS`,
     // is correct because R**2 will still be a multiple of the latter as
     // `N0::LIMBS_USED` is either one or two.
-    fn newRR(m: &Modulus<M>) -> Self {
+    fn newRR(m: &Modulus<M>) -> Result<Self, TryReserveError> {
         let m_bits = m.len_bits().as_usize_bits();
         let r = (m_bits + (LIMB_BITS - 1)) / LIMB_BITS * LIMB_BITS;
 
         // base = 2**(lg m - 1).
         let bit = m_bits - 1;
-        let mut base = m.zero();
+        let mut base = m.try_zero()?;
         base.limbs[bit / LIMB_BITS] = 1 << (bit % LIMB_BITS);
 
         // Double `base` so that base == R == 2**r (mod m). For normal moduli
@@ -332,8 +390,8 @@ impl<M> One<M, RR> {
         debug_assert_eq!(LG_BASE.count_ones(), 1); // Must be 2**n for n >= 0.
         let shifts = r - bit + LG_BASE;
         // `m_bits >= LG_BASE` (for the currently chosen value of `LG_BASE`)
-        // since we require the modulus to have at least `MODULUS_MIN_LIMBS`
-        // limbs. `r >= m_bits` as seen above. So `r >= LG_BASE` and thus
+        // since we require the modulus to have at least `MODULUS_MIN_BITS`
+        // bits. `r >= m_bits` as seen above. So `r >= LG_BASE` and thus
         // `r / LG_BASE` is non-zero.
         //
         // The maximum value of `r` is determined by
@@ -346,12 +404,15 @@ impl<M> One<M, RR> {
         for _ in 0..shifts {
             elem_mul_by_2(&mut base, m)
         }
-        let RR = elem_exp_vartime(base, exponent, m);
+        #[cfg(not(feature = "ct-everything"))]
+        let RR = try_elem_exp_vartime(base, exponent, m)?;
+        #[cfg(feature = "ct-everything")]
+        let RR = try_elem_exp_public_consttime(base, exponent, m)?;
 
-        Self(Elem {
+        Ok(Self(Elem {
             limbs: RR.limbs,
             encoding: PhantomData, // PhantomData<RR>
-        })
+        }))
     }
 }
 
@@ -376,10 +437,35 @@ impl<M: PublicModulus, E> Clone for One<M, E> {
 /// weight of `exponent`.
 // TODO: The test coverage needs to be expanded, e.g. test with the largest
 // accepted exponent and with the most common values of 65537 and 3.
+#[cfg(not(feature = "ct-everything"))]
 pub(crate) fn elem_exp_vartime<M>(
     base: Elem<M, R>,
     exponent: NonZeroU64,
     m: &Modulus<M>,
+) -> Elem<M, R> {
+    let acc = base.clone();
+    elem_exp_vartime_(base, acc, exponent, m)
+}
+
+// Like `elem_exp_vartime`, except allocation failure is reported instead of
+// aborting.
+#[cfg(not(feature = "ct-everything"))]
+fn try_elem_exp_vartime<M>(
+    base: Elem<M, R>,
+    exponent: NonZeroU64,
+    m: &Modulus<M>,
+) -> Result<Elem<M, R>, TryReserveError> {
+    let acc = base.try_clone()?;
+    Ok(elem_exp_vartime_(base, acc, exponent, m))
+}
+
+// `acc` must be a copy of `base`.
+#[cfg(not(feature = "ct-everything"))]
+fn elem_exp_vartime_<M>(
+    base: Elem<M, R>,
+    mut acc: Elem<M, R>,
+    exponent: NonZeroU64,
+    m: &Modulus<M>,
 ) -> Elem<M, R> {
     // Use what [Knuth] calls the "S-and-X binary method", i.e. variable-time
     // square-and-multiply that scans the exponent from the most significant
@@ -399,7 +485,6 @@ pub(crate) fn elem_exp_vartime<M>(
     // [Knuth]: The Art of Computer Programming, Volume 2: Seminumerical
     //          Algorithms (3rd Edition), Section 4.6.3.
     let exponent = exponent.get();
-    let mut acc = base.clone();
     let mut bit = 1 << (64 - 1 - exponent.leading_zeros());
     debug_assert!((exponent & bit) != 0);
     while bit > 1 {
@@ -412,305 +497,111 @@ pub(crate) fn elem_exp_vartime<M>(
     acc
 }
 
-/// Uses Fermat's Little Theorem to calculate modular inverse in constant time.
-pub fn elem_inverse_consttime<M: Prime>(
-    a: Elem<M, R>,
-    m: &OwnedModulusWithOne<M>,
-) -> Result<Elem<M, Unencoded>, error::Unspecified> {
-    elem_exp_consttime(a, &PrivateExponent::for_flt(&m.modulus()), m)
+/// Calculates base**exponent (mod m), like `elem_exp_vartime`, which it
+/// replaces when the `ct-everything` feature is enabled.
+///
+/// The run time is a function of the number of limbs in `m` only: every bit
+/// of the 64-bit `exponent` costs a squaring and a multiplication, whether
+/// it is set or not. For the common RSA public exponent 65537 this is about
+/// seven times as many multiplications as `elem_exp_vartime` does.
+#[cfg(feature = "ct-everything")]
+pub(crate) fn elem_exp_public_consttime<M>(
+    base: Elem<M, R>,
+    exponent: NonZeroU64,
+    m: &Modulus<M>,
+) -> Elem<M, R> {
+    let acc = base.clone();
+    let squared = base.clone();
+    let product = base.clone();
+    elem_exp_public_consttime_(base, acc, squared, product, exponent, m)
 }
 
-#[cfg(not(target_arch = "x86_64"))]
-pub fn elem_exp_consttime<M>(
+// Like `elem_exp_public_consttime`, except allocation failure is reported
+// instead of aborting.
+#[cfg(feature = "ct-everything")]
+fn try_elem_exp_public_consttime<M>(
     base: Elem<M, R>,
-    exponent: &PrivateExponent,
-    m: &OwnedModulusWithOne<M>,
-) -> Result<Elem<M, Unencoded>, error::Unspecified> {
-    let oneRR = m.oneRR();
-    let m = &m.modulus();
-
-    use crate::{bssl, limb::Window};
-
-    const WINDOW_BITS: usize = 5;
-    const TABLE_ENTRIES: usize = 1 << WINDOW_BITS;
-
-    let num_limbs = m.limbs().len();
-
-    let mut table = vec![0; TABLE_ENTRIES * num_limbs];
-
-    fn gather<M>(table: &[Limb], acc: &mut Elem<M, R>, i: Window) {
-        prefixed_extern! {
-            fn LIMBS_select_512_32(
-                r: *mut Limb,
-                table: *const Limb,
-                num_limbs: c::size_t,
-                i: Window,
-            ) -> bssl::Result;
-        }
-        Result::from(unsafe {
-            LIMBS_select_512_32(acc.limbs.as_mut_ptr(), table.as_ptr(), acc.limbs.len(), i)
-        })
-        .unwrap();
-    }
-
-    fn power<M>(
-        table: &[Limb],
-        mut acc: Elem<M, R>,
-        m: &Modulus<M>,
-        i: Window,
-        mut tmp: Elem<M, R>,
-    ) -> (Elem<M, R>, Elem<M, R>) {
-        for _ in 0..WINDOW_BITS {
-            acc = elem_squared(acc, m);
-        }
-        gather(table, &mut tmp, i);
-        let acc = elem_mul(&tmp, acc, m);
-        (acc, tmp)
-    }
+    exponent: NonZeroU64,
+    m: &Modulus<M>,
+) -> Result<Elem<M, R>, TryReserveError> {
+    let acc = base.try_clone()?;
+    let squared = base.try_clone()?;
+    let product = base.try_clone()?;
+    Ok(elem_exp_public_consttime_(
+        base, acc, squared, product, exponent, m,
+    ))
+}
 
-    fn entry(table: &[Limb], i: usize, num_limbs: usize) -> &[Limb] {
-        &table[(i * num_limbs)..][..num_limbs]
-    }
-    fn entry_mut(table: &mut [Limb], i: usize, num_limbs: usize) -> &mut [Limb] {
-        &mut table[(i * num_limbs)..][..num_limbs]
+// `acc`, `squared`, and `product` must be copies of `base`.
+#[cfg(feature = "ct-everything")]
+fn elem_exp_public_consttime_<M>(
+    base: Elem<M, R>,
+    mut acc: Elem<M, R>,
+    mut squared: Elem<M, R>,
+    mut product: Elem<M, R>,
+    exponent: NonZeroU64,
+    m: &Modulus<M>,
+) -> Elem<M, R> {
+    // Left-to-right square-and-multiply like `elem_exp_vartime_`, except
+    // every step is done for every bit below the most significant bit of a
+    // `u64`, and the results are selected with masks instead of branches.
+    // Until the most significant set bit of `exponent` is reached, `acc`
+    // stays equal to `base`.
+    fn mask(bit: u64) -> Limb {
+        Limb::from(bit == 1).wrapping_neg()
     }
-
-    // table[0] = base**0 (i.e. 1).
-    {
-        let acc = entry_mut(&mut table, 0, num_limbs);
-        // `table` was initialized to zero and hasn't changed.
-        debug_assert!(acc.iter().all(|&value| value == 0));
-        acc[0] = 1;
-        limbs_mont_mul(acc, &oneRR.0.limbs, m.limbs(), m.n0(), m.cpu_features());
+    fn cmov(r: &mut [Limb], a: &[Limb], mask: Limb) {
+        r.iter_mut()
+            .zip(a)
+            .for_each(|(r, a)| *r ^= (*r ^ *a) & mask);
     }
 
-    entry_mut(&mut table, 1, num_limbs).copy_from_slice(&base.limbs);
-    for i in 2..TABLE_ENTRIES {
-        let (src1, src2) = if i % 2 == 0 {
-            (i / 2, i / 2)
-        } else {
-            (i - 1, 1)
-        };
-        let (previous, rest) = table.split_at_mut(num_limbs * i);
-        let src1 = entry(previous, src1, num_limbs);
-        let src2 = entry(previous, src2, num_limbs);
-        let dst = entry_mut(rest, 0, num_limbs);
-        limbs_mont_product(dst, src1, src2, m.limbs(), m.n0(), m.cpu_features());
+    let exponent = exponent.get();
+    let mut started = mask(exponent >> 63);
+    for i in (0..63).rev() {
+        squared.limbs.copy_from_slice(&acc.limbs);
+        squared = elem_squared(squared, m);
+        product.limbs.copy_from_slice(&squared.limbs);
+        product = elem_mul(&base, product, m);
+
+        let bit = mask((exponent >> i) & 1);
+        cmov(&mut squared.limbs, &product.limbs, bit);
+        cmov(&mut acc.limbs, &squared.limbs, started);
+        started |= bit;
     }
+    acc
+}
 
-    let tmp = m.zero();
-    let mut acc = Elem {
-        limbs: base.limbs,
-        encoding: PhantomData,
-    };
-    let (acc, _) = limb::fold_5_bit_windows(
-        exponent.limbs(),
-        |initial_window| {
-            gather(&table, &mut acc, initial_window);
-            (acc, tmp)
-        },
-        |(acc, tmp), window| power(&table, acc, m, window, tmp),
-    );
+// Allocation failure is the only way `elem_exp_consttime` can fail.
+fn try_zeroed_limbs(len: usize) -> Result<Vec<Limb>, error::Unspecified> {
+    let mut r = Vec::new();
+    r.try_reserve_exact(len)
+        .map_err(|_: TryReserveError| error::Unspecified)?;
+    r.resize(len, 0);
+    Ok(r)
+}
 
-    Ok(acc.into_unencoded(m))
+/// Uses Fermat's Little Theorem to calculate modular inverse in constant time.
+pub fn elem_inverse_consttime<M: Prime>(
+    a: Elem<M, R>,
+    m: &OwnedModulusWithOne<M>,
+) -> Result<Elem<M, Unencoded>, error::Unspecified> {
+    let exponent =
+        PrivateExponent::for_flt(&m.modulus()).map_err(|_: TryReserveError| error::Unspecified)?;
+    elem_exp_consttime(a, &exponent, m)
 }
 
-#[cfg(target_arch = "x86_64")]
 pub fn elem_exp_consttime<M>(
     base: Elem<M, R>,
     exponent: &PrivateExponent,
     m: &OwnedModulusWithOne<M>,
 ) -> Result<Elem<M, Unencoded>, error::Unspecified> {
-    use crate::limb::LIMB_BYTES;
-
-    let oneRR = m.oneRR();
-    let m = &m.modulus();
-
-    // Pretty much all the math here requires CPU feature detection to have
-    // been done. `cpu_features` isn't threaded through all the internal
-    // functions, so just make it clear that it has been done at this point.
-    let cpu_features = m.cpu_features();
-
-    // The x86_64 assembly was written under the assumption that the input data
-    // is aligned to `MOD_EXP_CTIME_ALIGN` bytes, which was/is 64 in OpenSSL.
-    // Similarly, OpenSSL uses the x86_64 assembly functions by giving it only
-    // inputs `tmp`, `am`, and `np` that immediately follow the table. All the
-    // awkwardness here stems from trying to use the assembly code like OpenSSL
-    // does.
-
-    use crate::limb::Window;
-
-    const WINDOW_BITS: usize = 5;
-    const TABLE_ENTRIES: usize = 1 << WINDOW_BITS;
-
-    let num_limbs = m.limbs().len();
-
-    const ALIGNMENT: usize = 64;
-    assert_eq!(ALIGNMENT % LIMB_BYTES, 0);
-    let mut table = vec![0; ((TABLE_ENTRIES + 3) * num_limbs) + ALIGNMENT];
-    let (table, state) = {
-        let misalignment = (table.as_ptr() as usize) % ALIGNMENT;
-        let table = &mut table[((ALIGNMENT - misalignment) / LIMB_BYTES)..];
-        assert_eq!((table.as_ptr() as usize) % ALIGNMENT, 0);
-        table.split_at_mut(TABLE_ENTRIES * num_limbs)
-    };
-
-    fn scatter(table: &mut [Limb], acc: &[Limb], i: Window, num_limbs: usize) {
-        prefixed_extern! {
-            fn bn_scatter5(a: *const Limb, a_len: c::size_t, table: *mut Limb, i: Window);
-        }
-        unsafe { bn_scatter5(acc.as_ptr(), num_limbs, table.as_mut_ptr(), i) }
-    }
-
-    fn gather(table: &[Limb], acc: &mut [Limb], i: Window, num_limbs: usize) {
-        prefixed_extern! {
-            fn bn_gather5(r: *mut Limb, a_len: c::size_t, table: *const Limb, i: Window);
-        }
-        unsafe { bn_gather5(acc.as_mut_ptr(), num_limbs, table.as_ptr(), i) }
-    }
-
-    fn limbs_mul_mont_gather5_amm(
-        table: &[Limb],
-        acc: &mut [Limb],
-        base: &[Limb],
-        m: &[Limb],
-        n0: &N0,
-        i: Window,
-        num_limbs: usize,
-    ) {
-        prefixed_extern! {
-            fn bn_mul_mont_gather5(
-                rp: *mut Limb,
-                ap: *const Limb,
-                table: *const Limb,
-                np: *const Limb,
-                n0: &N0,
-                num: c::size_t,
-                power: Window,
-            );
-        }
-        unsafe {
-            bn_mul_mont_gather5(
-                acc.as_mut_ptr(),
-                base.as_ptr(),
-                table.as_ptr(),
-                m.as_ptr(),
-                n0,
-                num_limbs,
-                i,
-            );
+    let mut exp = ElemExpConsttime::new(base, exponent, m)?;
+    loop {
+        if let Poll::Ready(r) = exp.step(&mut Budget::new(u32::MAX)) {
+            return Ok(r);
         }
     }
-
-    fn power_amm(
-        table: &[Limb],
-        acc: &mut [Limb],
-        m_cached: &[Limb],
-        n0: &N0,
-        i: Window,
-        num_limbs: usize,
-    ) {
-        prefixed_extern! {
-            fn bn_power5(
-                r: *mut Limb,
-                a: *const Limb,
-                table: *const Limb,
-                n: *const Limb,
-                n0: &N0,
-                num: c::size_t,
-                i: Window,
-            );
-        }
-        unsafe {
-            bn_power5(
-                acc.as_mut_ptr(),
-                acc.as_ptr(),
-                table.as_ptr(),
-                m_cached.as_ptr(),
-                n0,
-                num_limbs,
-                i,
-            );
-        }
-    }
-
-    // These are named `(tmp, am, np)` in BoringSSL.
-    let (acc, base_cached, m_cached): (&mut [Limb], &[Limb], &[Limb]) = {
-        let (acc, rest) = state.split_at_mut(num_limbs);
-        let (base_cached, rest) = rest.split_at_mut(num_limbs);
-
-        // Upstream, the input `base` is not Montgomery-encoded, so they compute a
-        // Montgomery-encoded copy and store it here.
-        base_cached.copy_from_slice(&base.limbs);
-
-        let m_cached = &mut rest[..num_limbs];
-        // "To improve cache locality" according to upstream.
-        m_cached.copy_from_slice(m.limbs());
-
-        (acc, base_cached, m_cached)
-    };
-
-    let n0 = m.n0();
-
-    // Fill in all the powers of 2 of `acc` into the table using only squaring and without any
-    // gathering, storing the last calculated power into `acc`.
-    fn scatter_powers_of_2(
-        table: &mut [Limb],
-        acc: &mut [Limb],
-        m_cached: &[Limb],
-        n0: &N0,
-        mut i: Window,
-        num_limbs: usize,
-        cpu_features: cpu::Features,
-    ) {
-        loop {
-            scatter(table, acc, i, num_limbs);
-            i *= 2;
-            if i >= (TABLE_ENTRIES as Window) {
-                break;
-            }
-            limbs_mont_square(acc, m_cached, n0, cpu_features);
-        }
-    }
-
-    // All entries in `table` will be Montgomery encoded.
-
-    // acc = table[0] = base**0 (i.e. 1).
-    // `acc` was initialized to zero and hasn't changed. Change it to 1 and then Montgomery
-    // encode it.
-    debug_assert!(acc.iter().all(|&value| value == 0));
-    acc[0] = 1;
-    limbs_mont_mul(acc, &oneRR.0.limbs, m_cached, n0, cpu_features);
-    scatter(table, acc, 0, num_limbs);
-
-    // acc = base**1 (i.e. base).
-    acc.copy_from_slice(base_cached);
-
-    // Fill in entries 1, 2, 4, 8, 16.
-    scatter_powers_of_2(table, acc, m_cached, n0, 1, num_limbs, cpu_features);
-    // Fill in entries 3, 6, 12, 24; 5, 10, 20, 30; 7, 14, 28; 9, 18; 11, 22; 13, 26; 15, 30;
-    // 17; 19; 21; 23; 25; 27; 29; 31.
-    for i in (3..(TABLE_ENTRIES as Window)).step_by(2) {
-        limbs_mul_mont_gather5_amm(table, acc, base_cached, m_cached, n0, i - 1, num_limbs);
-        scatter_powers_of_2(table, acc, m_cached, n0, i, num_limbs, cpu_features);
-    }
-
-    let acc = limb::fold_5_bit_windows(
-        exponent.limbs(),
-        |initial_window| {
-            gather(table, acc, initial_window, num_limbs);
-            acc
-        },
-        |acc, window| {
-            power_amm(table, acc, m_cached, n0, window, num_limbs);
-            acc
-        },
-    );
-
-    let mut r_amm = base.limbs;
-    r_amm.copy_from_slice(acc);
-
-    Ok(from_montgomery_amm(r_amm, m))
 }
 
 /// Verified a == b**-1 (mod m), i.e. a**-1 == b (mod m).
@@ -757,16 +648,7 @@ impl Nonnegative {
 fn limbs_mont_mul(r: &mut [Limb], a: &[Limb], m: &[Limb], n0: &N0, _cpu_features: cpu::Features) {
     debug_assert_eq!(r.len(), m.len());
     debug_assert_eq!(a.len(), m.len());
-    unsafe {
-        bn_mul_mont(
-            r.as_mut_ptr(),
-            r.as_ptr(),
-            a.as_ptr(),
-            m.as_ptr(),
-            n0,
-            r.len(),
-        )
-    }
+    unsafe { mul_mont(r.as_mut_ptr(), r.as_ptr(), a.as_ptr(), m, n0) }
 }
 
 /// r = a * b
@@ -783,30 +665,25 @@ fn limbs_mont_product(
     debug_assert_eq!(a.len(), m.len());
     debug_assert_eq!(b.len(), m.len());
 
-    unsafe {
-        bn_mul_mont(
-            r.as_mut_ptr(),
-            a.as_ptr(),
-            b.as_ptr(),
-            m.as_ptr(),
-            n0,
-            r.len(),
-        )
-    }
+    unsafe { mul_mont(r.as_mut_ptr(), a.as_ptr(), b.as_ptr(), m, n0) }
 }
 
 /// r = r**2
 fn limbs_mont_square(r: &mut [Limb], m: &[Limb], n0: &N0, _cpu_features: cpu::Features) {
     debug_assert_eq!(r.len(), m.len());
-    unsafe {
-        bn_mul_mont(
-            r.as_mut_ptr(),
-            r.as_ptr(),
-            r.as_ptr(),
-            m.as_ptr(),
-            n0,
-            r.len(),
-        )
+    unsafe { mul_mont(r.as_mut_ptr(), r.as_ptr(), r.as_ptr(), m, n0) }
+}
+
+/// The assembly implementations of `bn_mul_mont` require at least this many
+/// limbs; the x86 one, at least, doesn't compute anything for fewer.
+const BN_MUL_MONT_MIN_LIMBS: usize = 4;
+
+// `r` and/or `a` and/or `b` may alias. Each is `m.len()` limbs long.
+unsafe fn mul_mont(r: *mut Limb, a: *const Limb, b: *const Limb, m: &[Limb], n0: &N0) {
+    if m.len() < BN_MUL_MONT_MIN_LIMBS {
+        unsafe { limbs_mul_mont_portable(r, a, b, m.as_ptr(), n0, m.len()) }
+    } else {
+        unsafe { bn_mul_mont(r, a, b, m.as_ptr(), n0, m.len()) }
     }
 }
 
@@ -824,8 +701,8 @@ prefixed_extern! {
 
 #[cfg(test)]
 mod tests {
-    use super::{modulus::MODULUS_MIN_LIMBS, *};
-    use crate::{limb::LIMB_BYTES, test};
+    use super::{modulus::MODULUS_MIN_BITS, *};
+    use crate::test;
     use alloc::format;
 
     // Type-level representation of an arbitrary modulus.
@@ -859,6 +736,46 @@ mod tests {
         )
     }
 
+    #[cfg(feature = "ct-everything")]
+    #[test]
+    fn test_elem_exp_public_consttime() {
+        let cpu_features = cpu::features();
+        test::run(
+            test_file!("../../crypto/fipsmodule/bn/test/mod_exp_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let m_ = consume_modulus::<M>(test_case, "M", cpu_features);
+                let m = m_.modulus();
+                let base = consume_elem(test_case, "A", &m);
+                let _ = test_case.consume_bytes("E");
+                let _ = test_case.consume_bytes("ModExp");
+
+                for e in [1, 2, 3, 65537, (1 << 33) - 1, 1 << 63, u64::MAX] {
+                    let expected_result = {
+                        let bytes = e.to_be_bytes();
+                        let leading_zero_bytes = usize::try_from(e.leading_zeros() / 8).unwrap();
+                        let e = PrivateExponent::from_be_bytes_for_test_only(
+                            untrusted::Input::from(&bytes[leading_zero_bytes..]),
+                            &m,
+                        )
+                        .unwrap();
+                        elem_exp_consttime(into_encoded(base.clone(), &m_), &e, &m_).unwrap()
+                    };
+                    let actual_result = elem_exp_public_consttime(
+                        into_encoded(base.clone(), &m_),
+                        NonZeroU64::new(e).unwrap(),
+                        &m,
+                    );
+                    let actual_result = elem_mul(&m.one(), actual_result, &m);
+                    assert_elem_eq(&actual_result, &expected_result);
+                }
+
+                Ok(())
+            },
+        )
+    }
+
     // TODO: fn test_elem_exp_vartime() using
     // "src/rsa/bigint_elem_exp_vartime_tests.txt". See that file for details.
     // In the meantime, the function is tested indirectly via the RSA
@@ -888,6 +805,62 @@ mod tests {
         )
     }
 
+    // `elem_mul_by_2` must agree with `elem_add(a, a)`.
+    #[test]
+    fn test_elem_mul_by_2() {
+        let cpu_features = cpu::features();
+        test::run(
+            test_file!("../../crypto/fipsmodule/bn/test/mod_mul_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let m_ = consume_modulus::<M>(test_case, "M", cpu_features);
+                let m = m_.modulus();
+                let _ = test_case.consume_bytes("ModMul");
+                let a = consume_elem(test_case, "A", &m);
+                let b = consume_elem(test_case, "B", &m);
+
+                for a in [a, b] {
+                    let expected_result = elem_add(a.clone(), a.clone(), &m);
+                    let mut actual_result = a;
+                    elem_mul_by_2(&mut actual_result, &m);
+                    assert_elem_eq(&actual_result, &expected_result);
+                }
+
+                Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_elem_neg() {
+        let cpu_features = cpu::features();
+        test::run(
+            test_file!("../../crypto/fipsmodule/bn/test/mod_mul_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let m_ = consume_modulus::<M>(test_case, "M", cpu_features);
+                let m = m_.modulus();
+                let _ = test_case.consume_bytes("ModMul");
+                let a = consume_elem(test_case, "A", &m);
+                let b = consume_elem(test_case, "B", &m);
+                let zero = m.zero::<Unencoded>();
+
+                assert_elem_eq(&elem_neg(zero.clone(), &m), &zero);
+
+                for (a, b) in [(a.clone(), b.clone()), (b, a), (zero.clone(), m.one())] {
+                    let neg_a = elem_neg(a.clone(), &m);
+                    assert_elem_eq(&elem_neg(neg_a.clone(), &m), &a);
+                    assert_elem_eq(&elem_add(a.clone(), neg_a.clone(), &m), &zero);
+                    assert_elem_eq(&elem_add(b.clone(), neg_a, &m), &elem_sub(b, &a, &m));
+                }
+
+                Ok(())
+            },
+        )
+    }
+
     #[test]
     fn test_elem_squared() {
         let cpu_features = cpu::features();
@@ -939,40 +912,141 @@ mod tests {
         )
     }
 
+    // The same moduli must be accepted on every target, regardless of how
+    // many limbs they take.
     #[test]
-    fn test_elem_reduced_once() {
+    fn test_modulus_min_bits() {
+        let cpu_features = cpu::features();
+        for bits in [
+            MODULUS_MIN_BITS - 1,
+            MODULUS_MIN_BITS,
+            MODULUS_MIN_BITS + 1,
+            192,
+            255,
+            256,
+            257,
+        ] {
+            // 2**(bits - 1) + 1.
+            let mut bytes = [0u8; 257 / 8 + 1];
+            let bytes = &mut bytes[..((bits + 7) / 8)];
+            bytes[0] = 1 << ((bits - 1) % 8);
+            *bytes.last_mut().unwrap() |= 1;
+
+            let modulus = OwnedModulusWithOne::<M>::from_be_bytes(
+                untrusted::Input::from(bytes),
+                cpu_features,
+            );
+            assert_eq!(modulus.is_ok(), bits >= MODULUS_MIN_BITS, "{}", bits);
+            if let Ok(modulus) = modulus {
+                assert!(modulus.be_bytes().eq(bytes.iter().copied()), "{}", bits);
+                assert_eq!(modulus.len_bits().as_usize_bits(), bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_elem_small_modulus() {
         let cpu_features = cpu::features();
         test::run(
-            test_file!("bigint_elem_reduced_once_tests.txt"),
+            test_file!("bigint_small_modulus_tests.txt"),
             |section, test_case| {
                 assert_eq!(section, "");
 
-                struct N {}
-                struct QQ {}
-                unsafe impl SmallerModulus<N> for QQ {}
-                unsafe impl SlightlySmallerModulus<N> for QQ {}
+                let m_ = consume_modulus::<M>(test_case, "M", cpu_features);
+                let m = m_.modulus();
+                let expected_product = consume_elem(test_case, "ModMul", &m);
+                let expected_power = consume_elem(test_case, "ModExp", &m);
+                let a = consume_elem(test_case, "A", &m);
+                let b = consume_elem(test_case, "B", &m);
+                let e = test_case.consume_bytes("E");
+                let e =
+                    NonZeroU64::new(e.iter().fold(0, |acc, &b| (acc << 8) | u64::from(b))).unwrap();
 
-                let qq = consume_modulus::<QQ>(test_case, "QQ", cpu_features);
-                let expected_result = consume_elem::<QQ>(test_case, "R", &qq.modulus());
-                let n = consume_modulus::<N>(test_case, "N", cpu_features);
-                let a = consume_elem::<N>(test_case, "A", &n.modulus());
+                let a = into_encoded(a, &m_);
+                let b = into_encoded(b, &m_);
 
-                let actual_result = elem_reduced_once(&a, &qq.modulus());
-                assert_elem_eq(&actual_result, &expected_result);
+                // The portable fallback must agree with `bn_mul_mont` whenever
+                // both can be used.
+                if m.limbs().len() >= BN_MUL_MONT_MIN_LIMBS {
+                    let mut asm = b.limbs.clone();
+                    let mut portable = b.limbs.clone();
+                    unsafe {
+                        bn_mul_mont(
+                            asm.as_mut_ptr(),
+                            a.limbs.as_ptr(),
+                            b.limbs.as_ptr(),
+                            m.limbs().as_ptr(),
+                            m.n0(),
+                            m.limbs().len(),
+                        );
+                        limbs_mul_mont_portable(
+                            portable.as_mut_ptr(),
+                            a.limbs.as_ptr(),
+                            b.limbs.as_ptr(),
+                            m.limbs().as_ptr(),
+                            m.n0(),
+                            m.limbs().len(),
+                        );
+                    }
+                    assert_eq!(&*asm, &*portable);
+                }
+
+                let actual_product = elem_mul(&a, b, &m).into_unencoded(&m);
+                assert_elem_eq(&actual_product, &expected_product);
+
+                #[cfg(not(feature = "ct-everything"))]
+                let actual_power = elem_exp_vartime(a, e, &m);
+                #[cfg(feature = "ct-everything")]
+                let actual_power = elem_exp_public_consttime(a, e, &m);
+                let actual_power = actual_power.into_unencoded(&m);
+                assert_elem_eq(&actual_power, &expected_power);
 
                 Ok(())
             },
         )
     }
 
+    #[test]
+    fn test_elem_ct_eq() {
+        use subtle::ConstantTimeEq;
+
+        let m_ = OwnedModulusWithOne::<M>::from_be_bytes(
+            untrusted::Input::from(&[0xff; MODULUS_MIN_BITS / 8]),
+            cpu::features(),
+        )
+        .unwrap();
+        let m = m_.modulus();
+        let elem = |bytes: &[u8]| {
+            Elem::<M, Unencoded>::from_be_bytes_padded(untrusted::Input::from(bytes), &m).unwrap()
+        };
+
+        let zero = m.zero::<Unencoded>();
+        let one = elem(&[1]);
+        let one_hi = elem(&[1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let max = elem(&[0xfe; MODULUS_MIN_BITS / 8]);
+        let values = [&zero, &one, &one_hi, &max];
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                assert_eq!(bool::from(a.ct_eq(b)), i == j);
+            }
+        }
+        assert!(bool::from(one.ct_eq(&elem(&[0, 0, 1]))));
+        assert!(bool::from(one.ct_eq(&m.one())));
+        let encoded = into_encoded(max.clone(), &m_);
+        assert!(bool::from(max.ct_eq(&encoded.into_unencoded(&m))));
+    }
+
     #[test]
     fn test_modulus_debug() {
         let modulus = OwnedModulusWithOne::<M>::from_be_bytes(
-            untrusted::Input::from(&[0xff; LIMB_BYTES * MODULUS_MIN_LIMBS]),
+            untrusted::Input::from(&[0xff; MODULUS_MIN_BITS / 8]),
             cpu::features(),
         )
         .unwrap();
-        assert_eq!("Modulus", format!("{:?}", modulus));
+        assert_eq!(
+            format!("Modulus(0x{})", "ff".repeat(MODULUS_MIN_BITS / 8)),
+            format!("{:?}", modulus)
+        );
     }
 
     fn consume_elem<M>(
@@ -17,15 +17,22 @@ use crate::{
     error,
     limb::{self, Limb, LimbMask, LIMB_BYTES},
 };
-use alloc::{borrow::ToOwned, boxed::Box, vec};
+use alloc::{collections::TryReserveError, vec, vec::Vec};
 use core::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
 };
 
+/// The storage of `BoxedLimbs`. With the `locked-memory` feature, long-lived
+/// values can be moved into locked memory; see `crate::locked`.
+#[cfg(not(feature = "locked-memory"))]
+pub(super) type Storage = alloc::boxed::Box<[Limb]>;
+#[cfg(feature = "locked-memory")]
+pub(super) type Storage = crate::locked::SecretSlice<Limb>;
+
 /// All `BoxedLimbs<M>` are stored in the same number of limbs.
 pub(super) struct BoxedLimbs<M> {
-    limbs: Box<[Limb]>,
+    limbs: Storage,
 
     /// The modulus *m* that determines the size of `limbx`.
     m: PhantomData<M>,
@@ -60,9 +67,9 @@ impl<M> Clone for BoxedLimbs<M> {
 impl<M> BoxedLimbs<M> {
     // The caller must ensure that `limbs.len()` is the same width as the
     // modulus.
-    pub(super) fn new_unchecked(limbs: Box<[Limb]>) -> Self {
+    pub(super) fn new_unchecked(limbs: impl Into<Storage>) -> Self {
         Self {
-            limbs,
+            limbs: limbs.into(),
             m: PhantomData,
         }
     }
@@ -76,18 +83,15 @@ impl<M> BoxedLimbs<M> {
             return Err(error::KeyRejected::invalid_encoding());
         }
         let num_limbs = (input.len() + LIMB_BYTES - 1) / LIMB_BYTES;
-        let mut r = Self::zero(num_limbs);
+        let mut r = Self::try_zero(num_limbs).map_err(|_| error::KeyRejected::out_of_memory())?;
         limb::parse_big_endian_and_pad_consttime(input, &mut r)
             .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())?;
         Ok(r)
     }
 
-    pub(super) fn minimal_width_from_unpadded(limbs: &[Limb]) -> Self {
+    pub(super) fn minimal_width_from_unpadded(limbs: &[Limb]) -> Result<Self, TryReserveError> {
         debug_assert_ne!(limbs.last(), Some(&0));
-        Self {
-            limbs: limbs.to_owned().into_boxed_slice(),
-            m: PhantomData,
-        }
+        Self::try_copy_from(limbs)
     }
 
     pub(super) fn from_be_bytes_padded_less_than(
@@ -95,11 +99,21 @@ impl<M> BoxedLimbs<M> {
         m: &Modulus<M>,
     ) -> Result<Self, error::Unspecified> {
         let mut r = Self::zero(m.limbs().len());
-        limb::parse_big_endian_and_pad_consttime(input, &mut r)?;
-        if limb::limbs_less_than_limbs_consttime(&r, m.limbs()) != LimbMask::True {
+        r.set_from_be_bytes_padded_less_than(input, m)?;
+        Ok(r)
+    }
+
+    // The caller must ensure that `self` is the same width as `m`.
+    pub(super) fn set_from_be_bytes_padded_less_than(
+        &mut self,
+        input: untrusted::Input,
+        m: &Modulus<M>,
+    ) -> Result<(), error::Unspecified> {
+        limb::parse_big_endian_and_pad_consttime(input, self)?;
+        if limb::limbs_less_than_limbs_consttime(self, m.limbs()) != LimbMask::True {
             return Err(error::Unspecified);
         }
-        Ok(r)
+        Ok(())
     }
 
     #[inline]
@@ -108,13 +122,41 @@ impl<M> BoxedLimbs<M> {
     }
 
     pub(super) fn zero(len: usize) -> Self {
-        Self {
-            limbs: vec![0; len].into_boxed_slice(),
-            m: PhantomData,
-        }
+        Self::new_unchecked(vec![0; len].into_boxed_slice())
+    }
+
+    /// Like `zero`, except allocation failure is reported instead of aborting.
+    pub(super) fn try_zero(len: usize) -> Result<Self, TryReserveError> {
+        let mut limbs = Vec::new();
+        limbs.try_reserve_exact(len)?;
+        limbs.resize(len, 0);
+        Ok(Self::new_unchecked(limbs.into_boxed_slice()))
     }
 
-    pub(super) fn into_limbs(self) -> Box<[Limb]> {
+    /// Like `clone`, except allocation failure is reported instead of
+    /// aborting.
+    pub(super) fn try_clone(&self) -> Result<Self, TryReserveError> {
+        Self::try_copy_from(&self.limbs)
+    }
+
+    fn try_copy_from(limbs: &[Limb]) -> Result<Self, TryReserveError> {
+        let mut r = Vec::new();
+        r.try_reserve_exact(limbs.len())?;
+        r.extend_from_slice(limbs);
+        Ok(Self::new_unchecked(r.into_boxed_slice()))
+    }
+
+    pub(super) fn into_limbs(self) -> Storage {
         self.limbs
     }
+
+    #[cfg(feature = "locked-memory")]
+    pub(super) fn into_locked(self) -> Self {
+        Self::new_unchecked(self.limbs.into_locked())
+    }
+
+    #[cfg(feature = "locked-memory")]
+    pub(super) fn was_locked(&self) -> bool {
+        self.limbs.was_locked()
+    }
 }
@@ -0,0 +1,587 @@
+// Copyright 2015-2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Constant-time modular exponentiation as a state machine that can be
+//! suspended between steps, so that long exponentiations can be interleaved
+//! with other work.
+//!
+//! The work is divided into units of at most six Montgomery multiplications:
+//! one per entry (or, on x86_64, per group of entries) of the precomputed
+//! table, one per 5-bit window of the exponent, and the final conversion out
+//! of the Montgomery domain. Which units are done in a step
+//! depends only on the budget and on public values (the lengths of the
+//! modulus and exponent), so stepping doesn't leak the exponent.
+
+use super::{
+    boxed_limbs::{BoxedLimbs, Storage},
+    limbs_from_montgomery_amm, limbs_mont_mul, limbs_mont_square, try_zeroed_limbs, Elem,
+    OwnedModulusWithOne, PrivateExponent, Unencoded, N0, R,
+};
+use crate::{
+    c, cpu, error,
+    limb::{FiveBitWindows, Limb, Window},
+    polyfill,
+};
+use alloc::vec::Vec;
+use core::{marker::PhantomData, task::Poll};
+
+#[cfg(not(target_arch = "x86_64"))]
+use super::limbs_mont_product;
+
+const WINDOW_BITS: u32 = 5;
+const TABLE_ENTRIES: usize = 1 << WINDOW_BITS;
+
+// The cost of the most expensive unit of work: five squarings and a
+// multiplication for each window of the exponent.
+const MAX_UNIT_COST: u32 = WINDOW_BITS + 1;
+
+/// A limit on the number of Montgomery multiplications done in a step of a
+/// suspendable computation.
+pub struct Budget {
+    remaining: u32,
+    progressed: bool,
+}
+
+impl Budget {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            remaining: limit,
+            progressed: false,
+        }
+    }
+
+    /// Deducts `cost` from the budget if it is within the remaining budget,
+    /// or if nothing has been spent yet so that every step makes progress
+    /// even when the budget is smaller than a single unit of work.
+    pub fn try_spend(&mut self, cost: u32) -> bool {
+        if self.progressed && cost > self.remaining {
+            return false;
+        }
+        self.remaining = self.remaining.saturating_sub(cost);
+        self.progressed = true;
+        true
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Next {
+    /// Fill in the table, starting with the given entry.
+    Table(Window),
+    /// Load the accumulator from the table using the leading window.
+    InitialWindow,
+    /// Fold the next window into the accumulator.
+    Window,
+    /// Convert the accumulator out of the Montgomery domain.
+    Finish,
+    Done,
+}
+
+/// Computes `base**exponent (mod m)` in constant time, a step at a time.
+///
+/// All secret intermediate values are zeroized when this is dropped, whether
+/// or not the computation has finished.
+///
+/// This is a typed wrapper around `ExpConsttime`, which does all the work
+/// without being generic over `M`, so that the exponentiation code isn't
+/// duplicated for every modulus type it is used with.
+pub struct ElemExpConsttime<'a, M> {
+    inner: ExpConsttime<'a>,
+    m: PhantomData<M>,
+}
+
+impl<'a, M> ElemExpConsttime<'a, M> {
+    /// Fails only if allocation fails.
+    pub fn new(
+        base: Elem<M, R>,
+        exponent: &'a PrivateExponent,
+        m: &'a OwnedModulusWithOne<M>,
+    ) -> Result<Self, error::Unspecified> {
+        let modulus = m.modulus();
+        let inner = ExpConsttime::new(
+            base.limbs.into_limbs(),
+            exponent,
+            m.limbs(),
+            modulus.n0().clone(),
+            m.oneRR_limbs(),
+            modulus.cpu_features(),
+        )?;
+        Ok(Self {
+            inner,
+            m: PhantomData,
+        })
+    }
+
+    /// Does as many units of work as `budget` allows, returning the result
+    /// once all the work is done.
+    ///
+    /// Panics if called after the result has been returned.
+    #[inline]
+    pub fn step(&mut self, budget: &mut Budget) -> Poll<Elem<M, Unencoded>> {
+        self.inner.step(budget).map(|limbs| Elem {
+            limbs: BoxedLimbs::new_unchecked(limbs),
+            encoding: PhantomData,
+        })
+    }
+
+    /// The secret intermediate state, for verifying that it is zeroized.
+    #[cfg(test)]
+    pub fn secret_limbs(&self) -> impl Iterator<Item = &[Limb]> {
+        self.inner.secret_limbs()
+    }
+}
+
+struct ExpConsttime<'a> {
+    m: &'a [Limb],
+    n0: N0,
+    one_rr: &'a [Limb],
+    cpu_features: cpu::Features,
+    windows: FiveBitWindows<'a>,
+
+    // The table, the accumulator, and temporaries. On x86_64, this is
+    // over-allocated so that the table can be aligned to `ALIGNMENT` bytes,
+    // starting at `offset`.
+    state: Vec<Limb>,
+    #[cfg(target_arch = "x86_64")]
+    offset: usize,
+
+    // The input, which is reused for the output.
+    base: Option<Storage>,
+
+    next: Next,
+}
+
+impl<'a> ExpConsttime<'a> {
+    fn new(
+        base: Storage,
+        exponent: &'a PrivateExponent,
+        m: &'a [Limb],
+        n0: N0,
+        one_rr: &'a [Limb],
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::Unspecified> {
+        let num_limbs = m.len();
+        #[cfg(target_arch = "x86_64")]
+        let (state, offset) = {
+            use crate::limb::LIMB_BYTES;
+            assert_eq!(ALIGNMENT % LIMB_BYTES, 0);
+            let state = try_zeroed_limbs(((TABLE_ENTRIES + 3) * num_limbs) + ALIGNMENT)?;
+            let misalignment = (state.as_ptr() as usize) % ALIGNMENT;
+            let offset = (ALIGNMENT - misalignment) / LIMB_BYTES;
+            (state, offset)
+        };
+        #[cfg(not(target_arch = "x86_64"))]
+        let state = try_zeroed_limbs((TABLE_ENTRIES + 2) * num_limbs)?;
+
+        let mut r = Self {
+            m,
+            n0,
+            one_rr,
+            cpu_features,
+            windows: FiveBitWindows::new(exponent.limbs()),
+            state,
+            #[cfg(target_arch = "x86_64")]
+            offset,
+            base: Some(base),
+            next: Next::Table(0),
+        };
+        r.init();
+        Ok(r)
+    }
+
+    /// Does as many units of work as `budget` allows, returning the result
+    /// once all the work is done.
+    ///
+    /// Panics if called after the result has been returned.
+    fn step(&mut self, budget: &mut Budget) -> Poll<Storage> {
+        loop {
+            let next = self.next;
+            let cost = match next {
+                Next::Table(i) => table_unit_cost(i),
+                Next::InitialWindow => 0,
+                Next::Window => MAX_UNIT_COST,
+                Next::Finish => 1,
+                Next::Done => panic!("`ElemExpConsttime` stepped after completion"),
+            };
+            if !budget.try_spend(cost) {
+                return Poll::Pending;
+            }
+            self.next = match next {
+                Next::Table(i) => self.fill_table(i),
+                Next::InitialWindow => {
+                    let window = self.windows.next().unwrap();
+                    self.initial_window(window);
+                    self.after_window()
+                }
+                Next::Window => {
+                    let window = self.windows.next().unwrap();
+                    self.window(window);
+                    self.after_window()
+                }
+                Next::Finish => {
+                    let r = self.finish();
+                    self.next = Next::Done;
+                    return Poll::Ready(r);
+                }
+                Next::Done => unreachable!(),
+            };
+        }
+    }
+
+    fn after_window(&self) -> Next {
+        if self.windows.remaining() > 0 {
+            Next::Window
+        } else {
+            Next::Finish
+        }
+    }
+
+    #[cfg(test)]
+    fn secret_limbs(&self) -> impl Iterator<Item = &[Limb]> {
+        core::iter::once(&self.state[..]).chain(self.base.as_deref())
+    }
+
+    // Copies the accumulator into the output buffer and converts it out of
+    // the (almost) Montgomery domain.
+    fn finish(&mut self) -> Storage {
+        let mut r = self.base.take().unwrap();
+        r.copy_from_slice(self.acc());
+        limbs_from_montgomery_amm(&mut r, self.m, &self.n0, self.cpu_features);
+        r
+    }
+}
+
+impl Drop for ExpConsttime<'_> {
+    fn drop(&mut self) {
+        polyfill::zeroize(&mut self.state);
+        if let Some(base) = &mut self.base {
+            polyfill::zeroize(base);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+impl ExpConsttime<'_> {
+    // Layout of `state`: the table, then the accumulator, then a temporary.
+    fn parts(&mut self) -> (&mut [Limb], &mut [Limb], &mut [Limb]) {
+        let num_limbs = self.m.len();
+        let (table, rest) = self.state.split_at_mut(TABLE_ENTRIES * num_limbs);
+        let (acc, tmp) = rest.split_at_mut(num_limbs);
+        (table, acc, tmp)
+    }
+
+    fn acc(&mut self) -> &[Limb] {
+        let (_, acc, _) = self.parts();
+        acc
+    }
+
+    fn init(&mut self) {
+        let num_limbs = self.m.len();
+        let base = self.base.as_deref().unwrap();
+        self.state[num_limbs..][..num_limbs].copy_from_slice(base);
+    }
+
+    fn fill_table(&mut self, i: Window) -> Next {
+        let m = self.m;
+        let num_limbs = m.len();
+        let i = i as usize;
+        if i == 0 {
+            // table[0] = base**0 (i.e. 1).
+            let acc = entry_mut(&mut self.state, 0, num_limbs);
+            // `table` was initialized to zero and hasn't changed.
+            debug_assert!(acc.iter().all(|&value| value == 0));
+            acc[0] = 1;
+            limbs_mont_mul(acc, self.one_rr, m, &self.n0, self.cpu_features);
+            // table[1] = base**1 was filled in by `init`.
+            return Next::Table(2);
+        }
+
+        let (src1, src2) = if i % 2 == 0 {
+            (i / 2, i / 2)
+        } else {
+            (i - 1, 1)
+        };
+        let (previous, rest) = self.state.split_at_mut(num_limbs * i);
+        let src1 = entry(previous, src1, num_limbs);
+        let src2 = entry(previous, src2, num_limbs);
+        let dst = entry_mut(rest, 0, num_limbs);
+        limbs_mont_product(dst, src1, src2, m, &self.n0, self.cpu_features);
+
+        if i + 1 < TABLE_ENTRIES {
+            Next::Table((i + 1) as Window)
+        } else {
+            Next::InitialWindow
+        }
+    }
+
+    fn initial_window(&mut self, window: Window) {
+        let (table, acc, _) = self.parts();
+        gather(table, acc, window);
+    }
+
+    fn window(&mut self, window: Window) {
+        let m = self.m;
+        let n0 = self.n0.clone();
+        let cpu_features = self.cpu_features;
+        let (table, acc, tmp) = self.parts();
+        for _ in 0..WINDOW_BITS {
+            limbs_mont_square(acc, m, &n0, cpu_features);
+        }
+        gather(table, tmp, window);
+        limbs_mont_mul(acc, tmp, m, &n0, cpu_features);
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn table_unit_cost(_i: Window) -> u32 {
+    1
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn gather(table: &[Limb], acc: &mut [Limb], i: Window) {
+    prefixed_extern! {
+        fn LIMBS_select_512_32(
+            r: *mut Limb,
+            table: *const Limb,
+            num_limbs: c::size_t,
+            i: Window,
+        ) -> crate::bssl::Result;
+    }
+    Result::from(unsafe { LIMBS_select_512_32(acc.as_mut_ptr(), table.as_ptr(), acc.len(), i) })
+        .unwrap();
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn entry(table: &[Limb], i: usize, num_limbs: usize) -> &[Limb] {
+    &table[(i * num_limbs)..][..num_limbs]
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn entry_mut(table: &mut [Limb], i: usize, num_limbs: usize) -> &mut [Limb] {
+    &mut table[(i * num_limbs)..][..num_limbs]
+}
+
+// The x86_64 assembly was written under the assumption that the input data
+// is aligned to `MOD_EXP_CTIME_ALIGN` bytes, which was/is 64 in OpenSSL.
+// Similarly, OpenSSL uses the x86_64 assembly functions by giving it only
+// inputs `tmp`, `am`, and `np` that immediately follow the table. All the
+// awkwardness here stems from trying to use the assembly code like OpenSSL
+// does.
+#[cfg(target_arch = "x86_64")]
+const ALIGNMENT: usize = 64;
+
+#[cfg(target_arch = "x86_64")]
+impl ExpConsttime<'_> {
+    // Layout of `state` after `offset`: the table, then what BoringSSL calls
+    // `(tmp, am, np)`: the accumulator, the base, and a copy of the modulus.
+    fn parts(&mut self) -> (&mut [Limb], &mut [Limb], &mut [Limb], &mut [Limb]) {
+        let num_limbs = self.m.len();
+        let state = &mut self.state[self.offset..];
+        debug_assert_eq!((state.as_ptr() as usize) % ALIGNMENT, 0);
+        let (table, rest) = state.split_at_mut(TABLE_ENTRIES * num_limbs);
+        let (acc, rest) = rest.split_at_mut(num_limbs);
+        let (base_cached, rest) = rest.split_at_mut(num_limbs);
+        let m_cached = &mut rest[..num_limbs];
+        (table, acc, base_cached, m_cached)
+    }
+
+    fn acc(&mut self) -> &[Limb] {
+        let (_, acc, _, _) = self.parts();
+        acc
+    }
+
+    fn init(&mut self) {
+        let base = self.base.take().unwrap();
+        let m = self.m;
+        let (_, _, base_cached, m_cached) = self.parts();
+
+        // Upstream, the input `base` is not Montgomery-encoded, so they compute a
+        // Montgomery-encoded copy and store it here.
+        base_cached.copy_from_slice(&base);
+
+        // "To improve cache locality" according to upstream.
+        m_cached.copy_from_slice(m);
+
+        self.base = Some(base);
+    }
+
+    // All entries in `table` will be Montgomery encoded.
+    fn fill_table(&mut self, i: Window) -> Next {
+        let n0 = self.n0.clone();
+        let cpu_features = self.cpu_features;
+        let one_rr = self.one_rr;
+        let num_limbs = self.m.len();
+        let (table, acc, base_cached, m_cached) = self.parts();
+
+        if i == 0 {
+            // acc = table[0] = base**0 (i.e. 1).
+            // `acc` was initialized to zero and hasn't changed. Change it to 1 and then Montgomery
+            // encode it.
+            debug_assert!(acc.iter().all(|&value| value == 0));
+            acc[0] = 1;
+            limbs_mont_mul(acc, one_rr, m_cached, &n0, cpu_features);
+            scatter(table, acc, 0, num_limbs);
+
+            // acc = base**1 (i.e. base).
+            acc.copy_from_slice(base_cached);
+
+            // Fill in entries 1, 2, 4, 8, 16.
+            scatter_powers_of_2(table, acc, m_cached, &n0, 1, num_limbs, cpu_features);
+            return Next::Table(3);
+        }
+
+        // Fill in entries 3, 6, 12, 24; 5, 10, 20, 30; 7, 14, 28; 9, 18; 11, 22; 13, 26; 15, 30;
+        // 17; 19; 21; 23; 25; 27; 29; 31.
+        limbs_mul_mont_gather5_amm(table, acc, base_cached, m_cached, &n0, i - 1, num_limbs);
+        scatter_powers_of_2(table, acc, m_cached, &n0, i, num_limbs, cpu_features);
+
+        if i + 2 < (TABLE_ENTRIES as Window) {
+            Next::Table(i + 2)
+        } else {
+            Next::InitialWindow
+        }
+    }
+
+    fn initial_window(&mut self, window: Window) {
+        let num_limbs = self.m.len();
+        let (table, acc, _, _) = self.parts();
+        gather(table, acc, window, num_limbs);
+    }
+
+    fn window(&mut self, window: Window) {
+        let n0 = self.n0.clone();
+        let num_limbs = self.m.len();
+        let (table, acc, _, m_cached) = self.parts();
+        power_amm(table, acc, m_cached, &n0, window, num_limbs);
+    }
+}
+
+// The number of multiplications done by `fill_table(i)`: one to compute
+// entry `i` (or, for entry 0, to Montgomery-encode 1), and then the squarings
+// in `scatter_powers_of_2`.
+#[cfg(target_arch = "x86_64")]
+fn table_unit_cost(i: Window) -> u32 {
+    let first = if i == 0 { 1 } else { i };
+    let mut cost = 1;
+    let mut i = first * 2;
+    while i < (TABLE_ENTRIES as Window) {
+        cost += 1;
+        i *= 2;
+    }
+    cost
+}
+
+#[cfg(target_arch = "x86_64")]
+fn scatter(table: &mut [Limb], acc: &[Limb], i: Window, num_limbs: usize) {
+    prefixed_extern! {
+        fn bn_scatter5(a: *const Limb, a_len: c::size_t, table: *mut Limb, i: Window);
+    }
+    unsafe { bn_scatter5(acc.as_ptr(), num_limbs, table.as_mut_ptr(), i) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn gather(table: &[Limb], acc: &mut [Limb], i: Window, num_limbs: usize) {
+    prefixed_extern! {
+        fn bn_gather5(r: *mut Limb, a_len: c::size_t, table: *const Limb, i: Window);
+    }
+    unsafe { bn_gather5(acc.as_mut_ptr(), num_limbs, table.as_ptr(), i) }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn limbs_mul_mont_gather5_amm(
+    table: &[Limb],
+    acc: &mut [Limb],
+    base: &[Limb],
+    m: &[Limb],
+    n0: &N0,
+    i: Window,
+    num_limbs: usize,
+) {
+    prefixed_extern! {
+        fn bn_mul_mont_gather5(
+            rp: *mut Limb,
+            ap: *const Limb,
+            table: *const Limb,
+            np: *const Limb,
+            n0: &N0,
+            num: c::size_t,
+            power: Window,
+        );
+    }
+    unsafe {
+        bn_mul_mont_gather5(
+            acc.as_mut_ptr(),
+            base.as_ptr(),
+            table.as_ptr(),
+            m.as_ptr(),
+            n0,
+            num_limbs,
+            i,
+        );
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn power_amm(
+    table: &[Limb],
+    acc: &mut [Limb],
+    m_cached: &[Limb],
+    n0: &N0,
+    i: Window,
+    num_limbs: usize,
+) {
+    prefixed_extern! {
+        fn bn_power5(
+            r: *mut Limb,
+            a: *const Limb,
+            table: *const Limb,
+            n: *const Limb,
+            n0: &N0,
+            num: c::size_t,
+            i: Window,
+        );
+    }
+    unsafe {
+        bn_power5(
+            acc.as_mut_ptr(),
+            acc.as_ptr(),
+            table.as_ptr(),
+            m_cached.as_ptr(),
+            n0,
+            num_limbs,
+            i,
+        );
+    }
+}
+
+// Fill in all the powers of 2 of `acc` into the table using only squaring and without any
+// gathering, storing the last calculated power into `acc`.
+#[cfg(target_arch = "x86_64")]
+fn scatter_powers_of_2(
+    table: &mut [Limb],
+    acc: &mut [Limb],
+    m_cached: &[Limb],
+    n0: &N0,
+    mut i: Window,
+    num_limbs: usize,
+    cpu_features: crate::cpu::Features,
+) {
+    loop {
+        scatter(table, acc, i, num_limbs);
+        i *= 2;
+        if i >= (TABLE_ENTRIES as Window) {
+            break;
+        }
+        limbs_mont_square(acc, m_cached, n0, cpu_features);
+    }
+}
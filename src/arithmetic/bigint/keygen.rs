@@ -0,0 +1,744 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Arithmetic for generating private keys: random probable primes, products
+//! of them, and inverses of small numbers modulo such products.
+//!
+//! Unlike the rest of `bigint`, the values here are plain integers, not
+//! elements of ℤ/mℤ, so they are passed around as unpadded `Limb` slices.
+
+use super::{
+    elem_exp_consttime, elem_mul, elem_neg, elem_squared, elem_verify_equal_consttime, BoxedLimbs,
+    Elem, Modulus, OwnedModulusWithOne, PrivateExponent, Unencoded, R,
+};
+use crate::{
+    arithmetic::montgomery::limbs_mul,
+    bits::BitLength,
+    c, cpu, error,
+    limb::{self, Limb, LIMB_BITS, LIMB_BYTES},
+    polyfill::{self, Zeroable},
+    rand,
+};
+use alloc::{vec, vec::Vec};
+
+// Type-level representation of a candidate prime. It isn't `Prime` since it
+// is only used to test whether the candidate is (probably) prime.
+enum Candidate {}
+
+/// Returns a random probable prime *p* of exactly `bits` bits, as described
+/// in FIPS 186-4 Appendix B.3.3, such that *p* - 1 is coprime to the odd
+/// number `e`. If `other` is given, *p* is also at least `2**(bits - 99)` away
+/// from it, so that it can be used as the second prime factor of an RSA
+/// modulus whose first prime factor is `other`.
+///
+/// The two most significant bits of *p* are set, so that the product of two
+/// such primes has exactly `2 * bits` bits. This is stricter than the lower
+/// bound of `(√2)(2**(bits - 1))` required by FIPS 186-4.
+///
+/// `bits` must be a multiple of 512.
+///
+/// At most `5 * bits` candidates are tried, as FIPS 186-4 specifies, so the
+/// running time is bounded. Failure to find a prime within that many tries is
+/// overwhelmingly unlikely unless `rng` is broken, and is reported as an error.
+///
+/// The number of Miller-Rabin rounds doesn't come from FIPS 186-4's Table C.3;
+/// see `miller_rabin_rounds`.
+pub(crate) fn generate_prime(
+    bits: BitLength,
+    e: u64,
+    other: Option<&[Limb]>,
+    rng: &dyn rand::SecureRandom,
+    cpu_features: cpu::Features,
+) -> Result<Zeroizing<Limb>, error::Unspecified> {
+    let bits = bits.as_usize_bits();
+    // `elem_exp_consttime` requires this.
+    assert_eq!(bits % 512, 0);
+    let e = SmallDivisor::new(e);
+    let rounds = miller_rabin_rounds(bits);
+
+    let mut bytes = Zeroizing::new(vec![0; (bits + 7) / 8]);
+    let mut candidate = Zeroizing::new(vec![0; (bits + LIMB_BITS - 1) / LIMB_BITS]);
+    for _ in 0..(5 * bits) {
+        // Step 4.2/5.2.
+        rng.fill(&mut bytes)?;
+        limb::parse_big_endian_and_pad_consttime(untrusted::Input::from(&bytes), &mut candidate)?;
+        let top = candidate.len() - 1;
+        candidate[top] &= Limb::MAX >> ((candidate.len() * LIMB_BITS) - bits);
+        set_bit(&mut candidate, bits - 1);
+        set_bit(&mut candidate, bits - 2);
+        candidate[0] |= 1; // Step 4.3/5.3.
+
+        // Step 5.4. The check is stricter than needed so that the bound is
+        // a power of two.
+        if let Some(other) = other {
+            if limbs_abs_diff_bits(&candidate, other) <= bits - 99 {
+                continue;
+            }
+        }
+
+        // Not part of FIPS 186-4, but cheaply eliminates most candidates
+        // before the much more expensive Miller-Rabin test.
+        if SMALL_PRIMES
+            .iter()
+            .any(|&p| limbs_mod_small(&candidate, &SmallDivisor::new(u64::from(p))) == 0)
+        {
+            continue;
+        }
+
+        // Step 4.5/5.5: GCD(p - 1, e) == 1. `candidate` is odd so
+        // `p - 1 (mod e)` is `(p mod e) - 1 (mod e)`.
+        let p_minus_1_mod_e = e.sub_mod(limbs_mod_small(&candidate, &e), 1);
+        if small_inverse(p_minus_1_mod_e, e.d).is_none() {
+            continue;
+        }
+
+        if is_probably_prime(&candidate, rounds, rng, cpu_features)? {
+            return Ok(candidate);
+        }
+    }
+
+    Err(error::Unspecified)
+}
+
+/// Returns `a * b`. `a` and `b` must have the same length.
+pub(crate) fn limbs_product(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    assert_eq!(a.len(), b.len());
+    let mut r = vec![0; 2 * a.len()];
+    limbs_mul(&mut r, a, b);
+    r
+}
+
+/// Returns `e**-1 (mod m)`, padded to the length of `m`, or an error if `e`
+/// and `m` aren't coprime. `e` must be odd and less than `2**34`, and `m` may
+/// be even. This is constant-time with respect to `m`.
+///
+/// This is how an RSA private exponent is calculated from the public exponent
+/// `e` and (a multiple of) the order of the multiplicative group.
+pub(crate) fn small_inverse_mod(e: u64, m: &[Limb]) -> Result<Vec<Limb>, error::Unspecified> {
+    prefixed_extern! {
+        // `r` must not alias `a`
+        #[must_use]
+        fn limbs_mul_add_limb(r: *mut Limb, a: *const Limb, b: Limb, num_limbs: c::size_t)
+            -> Limb;
+    }
+
+    assert!(e < (1 << 34));
+    let e = SmallDivisor::new(e);
+
+    // Find `k` such that `k*m == -1 (mod e)`. Then `e` divides `k*m + 1`, and
+    // `x = (k*m + 1) / e` satisfies `e*x == 1 (mod m)`. Since `0 < k < e`,
+    // `0 < x < m`.
+    let m_mod_e = limbs_mod_small(m, &e);
+    let k = e.d - small_inverse(m_mod_e, e.d).ok_or(error::Unspecified)?;
+
+    // `k` may be wider than a limb on 32-bit targets, so it is split into two
+    // limbs. On 64-bit targets, the second limb is zero.
+    #[allow(clippy::cast_possible_truncation)]
+    let k = [k as Limb, ((k >> 32) >> (LIMB_BITS - 32)) as Limb];
+
+    let mut x = vec![0; m.len() + k.len() + 1];
+    for (i, &k_i) in k.iter().enumerate() {
+        let carry = unsafe { limbs_mul_add_limb(x[i..].as_mut_ptr(), m.as_ptr(), k_i, m.len()) };
+        limbs_add_limb(&mut x[(i + m.len())..], carry);
+    }
+    limbs_add_limb(&mut x, 1);
+
+    if limbs_div_small_in_place(&mut x, &e) != 0 {
+        return Err(error::Unspecified);
+    }
+    debug_assert!(x[m.len()..].iter().all(|&limb| limb == 0));
+    x.truncate(m.len());
+    Ok(x)
+}
+
+// The Miller-Rabin probabilistic primality test of FIPS 186-4 Appendix C.3.1.
+//
+// `w` is secret if it turns out to be prime, so for primes this is
+// constant-time with respect to `w`: the number `a` of trailing zero bits of
+// `w - 1` is calculated and removed in constant time, and every round does
+// the `m.len_bits() - 1` squarings that `a` could at most need, masking off
+// the ones after the first `a - 1`. It returns early only for composites,
+// which are discarded, and so it may leak how a composite was detected.
+//
+// The one exception is `random_base`, whose number of attempts depends on how
+// close `w` is to a power of two. Since the two most significant bits of `w`
+// are set, each attempt fails with probability less than a quarter, and the
+// number of attempts says very little about `w`.
+fn is_probably_prime(
+    w: &[Limb],
+    rounds: usize,
+    rng: &dyn rand::SecureRandom,
+    cpu_features: cpu::Features,
+) -> Result<bool, error::Unspecified> {
+    let w =
+        BoxedLimbs::<Candidate>::minimal_width_from_unpadded(w).map_err(|_| error::Unspecified)?;
+    let w = OwnedModulusWithOne::from_boxed_limbs(w, cpu_features)
+        .map_err(|_: error::KeyRejected| error::Unspecified)?;
+    let m = &w.modulus();
+    let w_bits = m.len_bits().as_usize_bits();
+    let w_bytes = m.len_bits().as_usize_bytes_rounded_up();
+
+    // Steps 1 and 2: `w - 1 == 2**a * odd`. `w` is odd so `a >= 1`.
+    let mut odd = Zeroizing::new(w.limbs().to_vec());
+    odd[0] &= !1;
+    let a = limbs_trailing_zeros_consttime(&odd);
+    limbs_shr_consttime(&mut odd, a);
+    let odd = {
+        let mut bytes = Zeroizing::new(vec![0; odd.len() * LIMB_BYTES]);
+        limb::big_endian_from_limbs(&odd, &mut bytes);
+        PrivateExponent::from_be_bytes_padded(untrusted::Input::from(&bytes), m)
+            .map_err(|_: error::KeyRejected| error::Unspecified)?
+    };
+
+    let minus_one_unencoded = elem_neg(m.one(), m);
+    let one: Elem<Candidate, R> = elem_mul(w.oneRR().as_ref(), m.one(), m);
+    let minus_one = elem_neg(one.clone(), m);
+
+    let mut b_bytes = vec![0; w_bytes];
+    for _ in 0..rounds {
+        // Steps 4.1 and 4.2.
+        let b = random_base(&mut b_bytes, m, &minus_one_unencoded, rng)?;
+
+        // Step 4.3.
+        let z = elem_exp_consttime(elem_mul(w.oneRR().as_ref(), b, m), &odd, &w)?;
+        let mut z = elem_mul(w.oneRR().as_ref(), z, m);
+
+        // Step 4.4. Instead of continuing with the next round when `z` is
+        // `1` or `w - 1`, remember that `w` passed this round.
+        let mut passed = elem_equal_mask(&z, &one) | elem_equal_mask(&z, &minus_one);
+
+        // Step 4.5. If `z == 1` before `z == w - 1` (step 4.5.3) then `w`
+        // never passes the round, so that is detected when `j == a`.
+        for j in 1..w_bits {
+            if (usize_equal_mask(j, a) & !passed) != 0 {
+                return Ok(false); // Step 4.6.
+            }
+            z = elem_squared(z, m);
+            passed |= usize_less_than_mask(j, a) & elem_equal_mask(&z, &minus_one);
+        }
+    }
+
+    // Step 5.
+    Ok(true)
+}
+
+// Returns a random `b` with `1 < b < w - 1`, where `w` is `m`'s value, per
+// steps 4.1 and 4.2 of FIPS 186-4 Appendix C.3.1. `bytes` is scratch space
+// of the length of `w`.
+fn random_base(
+    bytes: &mut [u8],
+    m: &Modulus<Candidate>,
+    minus_one: &Elem<Candidate, Unencoded>,
+    rng: &dyn rand::SecureRandom,
+) -> Result<Elem<Candidate, Unencoded>, error::Unspecified> {
+    // The two most significant bits of `w` are set, so at least three quarters
+    // of the candidates are accepted; failing this many times means that `rng`
+    // is broken.
+    const MAX_ATTEMPTS: usize = 100;
+
+    let excess_bits = (bytes.len() * 8) - m.len_bits().as_usize_bits();
+    for _ in 0..MAX_ATTEMPTS {
+        rng.fill(bytes)?;
+        bytes[0] &= 0xff >> excess_bits;
+        let b = match Elem::from_be_bytes_padded(untrusted::Input::from(bytes), m) {
+            Ok(b) => b,
+            Err(error::Unspecified) => continue,
+        };
+        if b.is_zero() || b.is_one() || elem_verify_equal_consttime(&b, minus_one).is_ok() {
+            continue;
+        }
+        return Ok(b);
+    }
+
+    Err(error::Unspecified)
+}
+
+// The number of Miller-Rabin rounds needed for a random candidate of `bits`
+// bits to have a false positive rate below 2**-80, from Damgård, Landrock, and
+// Pomerance, "Average Case Error Estimates for the Strong Probable Prime
+// Test". These are the thresholds that BoringSSL's `BN_prime_checks_for_size`
+// uses; they are not taken from the minimum numbers of rounds in FIPS 186-4
+// Table C.3.
+fn miller_rabin_rounds(bits: usize) -> usize {
+    if bits >= 3747 {
+        3
+    } else if bits >= 1345 {
+        4
+    } else if bits >= 476 {
+        5
+    } else if bits >= 400 {
+        6
+    } else if bits >= 347 {
+        7
+    } else if bits >= 308 {
+        8
+    } else if bits >= 55 {
+        27
+    } else {
+        34
+    }
+}
+
+// The first odd primes, for trial division of candidates.
+const SMALL_PRIMES: [u16; 512] = small_primes();
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_lossless)]
+const fn small_primes<const N: usize>() -> [u16; N] {
+    let mut primes = [0; N];
+    let mut len = 0;
+    let mut candidate: u32 = 3;
+    while len < N {
+        let mut is_prime = true;
+        let mut i = 0;
+        while i < len && (primes[i] as u32) * (primes[i] as u32) <= candidate {
+            if candidate % (primes[i] as u32) == 0 {
+                is_prime = false;
+                break;
+            }
+            i += 1;
+        }
+        if is_prime {
+            primes[len] = candidate as u16;
+            len += 1;
+        }
+        candidate += 2;
+    }
+    primes
+}
+
+/// A small, public divisor `d`, for dividing by it in constant time using
+/// Barrett reduction, 16 bits at a time.
+struct SmallDivisor {
+    d: u64,
+    // floor((2**64 - 1) / d)
+    m: u64,
+}
+
+impl SmallDivisor {
+    fn new(d: u64) -> Self {
+        // `div_rem` requires `d * 2**16 < 2**63`.
+        assert!(d > 1 && d < (1 << 47));
+        Self { d, m: u64::MAX / d }
+    }
+
+    // Returns `(x / d, x % d)`, for `x < d * 2**16`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn div_rem(&self, x: u64) -> (u64, u64) {
+        debug_assert!(x < (self.d << 16));
+        // `q` is either the quotient or one less than it, since the error in
+        // `self.m` is less than 2 and `x < 2**63`.
+        let q = ((u128::from(x) * u128::from(self.m)) >> 64) as u64;
+        let r = x - (q * self.d);
+        // `r < 2*d`; if `r >= d` then `r - d` doesn't wrap around and its most
+        // significant bit is clear.
+        let r_ge_d = (r.wrapping_sub(self.d) >> 63) ^ 1;
+        let mask = 0u64.wrapping_sub(r_ge_d);
+        (q + r_ge_d, r - (self.d & mask))
+    }
+
+    // Returns `a - b (mod d)` for `a, b < d`.
+    fn sub_mod(&self, a: u64, b: u64) -> u64 {
+        let (r, borrow) = a.overflowing_sub(b);
+        r.wrapping_add(self.d & 0u64.wrapping_sub(u64::from(borrow)))
+    }
+}
+
+// The 16-bit digits of a limb, most significant first.
+const DIGITS_PER_LIMB: usize = LIMB_BITS / 16;
+
+// Returns `a (mod d)`.
+#[allow(clippy::useless_conversion)]
+fn limbs_mod_small(a: &[Limb], d: &SmallDivisor) -> u64 {
+    a.iter().rev().fold(0, |r, &limb| {
+        (0..DIGITS_PER_LIMB).rev().fold(r, |r, i| {
+            let digit = u64::from((limb >> (16 * i)) & 0xffff);
+            d.div_rem((r << 16) | digit).1
+        })
+    })
+}
+
+// Sets `a = a / d`, returning `a (mod d)`.
+#[allow(clippy::useless_conversion, clippy::cast_possible_truncation)]
+fn limbs_div_small_in_place(a: &mut [Limb], d: &SmallDivisor) -> u64 {
+    let mut r = 0;
+    for limb in a.iter_mut().rev() {
+        let mut q = 0;
+        for i in (0..DIGITS_PER_LIMB).rev() {
+            let digit = u64::from((*limb >> (16 * i)) & 0xffff);
+            let (q_digit, r_) = d.div_rem((r << 16) | digit);
+            q |= (q_digit as Limb) << (16 * i);
+            r = r_;
+        }
+        *limb = q;
+    }
+    r
+}
+
+// Returns `a**-1 (mod m)`, or `None` if `a` and `m` aren't coprime, for odd
+// `m < 2**62` and `a < m`.
+//
+// This is a binary extended Euclidean algorithm with a fixed number of
+// iterations, and is constant-time with respect to `a`. It maintains
+// `x1*a == u (mod m)` and `x2*a == v (mod m)`, with `v` odd; each iteration
+// reduces the total bit length of `u` and `v` by at least one, so when it
+// is done `u == 0` and `v == gcd(a, m)`.
+fn small_inverse(a: u64, m: u64) -> Option<u64> {
+    debug_assert!(m & 1 == 1 && m < (1 << 62) && a < m);
+    let (mut u, mut v, mut x1, mut x2) = (a, m, 1, 0);
+    for _ in 0..(2 * (64 - m.leading_zeros())) {
+        let u_is_odd = 0u64.wrapping_sub(u & 1);
+
+        // If `u` is odd and `u < v`, swap them, so that `u >= v` below.
+        let swap = u_is_odd & 0u64.wrapping_sub(u.wrapping_sub(v) >> 63);
+        let t = (u ^ v) & swap;
+        u ^= t;
+        v ^= t;
+        let t = (x1 ^ x2) & swap;
+        x1 ^= t;
+        x2 ^= t;
+
+        // If `u` is odd, subtract `v`, making `u` even.
+        u -= v & u_is_odd;
+        let (x, borrow) = x1.overflowing_sub(x2 & u_is_odd);
+        x1 = x.wrapping_add(m & 0u64.wrapping_sub(u64::from(borrow)));
+
+        // Halve `u`, and `x1 (mod m)`.
+        u >>= 1;
+        x1 = (x1 + (m & 0u64.wrapping_sub(x1 & 1))) >> 1;
+    }
+    if v == 1 {
+        Some(x2)
+    } else {
+        None
+    }
+}
+
+fn set_bit(a: &mut [Limb], bit: usize) {
+    a[bit / LIMB_BITS] |= 1 << (bit % LIMB_BITS);
+}
+
+// Adds `b` to `a`, ignoring any carry out of `a`.
+fn limbs_add_limb(a: &mut [Limb], b: Limb) {
+    let mut carry = b;
+    for limb in a.iter_mut() {
+        let (sum, overflow) = limb.overflowing_add(carry);
+        *limb = sum;
+        carry = Limb::from(overflow);
+    }
+}
+
+// Returns the bit length of `|a - b|`, for `a` and `b` of the same length.
+fn limbs_abs_diff_bits(a: &[Limb], b: &[Limb]) -> usize {
+    assert_eq!(a.len(), b.len());
+    let mut diff = vec![0; a.len()];
+    let mut borrow = 0;
+    for ((d, &a), &b) in diff.iter_mut().zip(a).zip(b) {
+        let (x, b1) = a.overflowing_sub(b);
+        let (x, b2) = x.overflowing_sub(borrow);
+        *d = x;
+        borrow = Limb::from(b1 | b2);
+    }
+    // If `a < b` then negate the two's-complement result.
+    let mask = 0 as Limb;
+    let mask = mask.wrapping_sub(borrow);
+    diff.iter_mut().for_each(|d| *d ^= mask);
+    limbs_add_limb(&mut diff, borrow);
+    limb::limbs_minimal_bits(&diff).as_usize_bits()
+}
+
+// Returns the number of trailing zero bits of the nonzero value `a`, in
+// constant time.
+fn limbs_trailing_zeros_consttime(a: &[Limb]) -> usize {
+    let mut seen_one: Limb = 0;
+    let mut count: usize = 0;
+    for &limb in a {
+        for i in 0..LIMB_BITS {
+            seen_one |= (limb >> i) & 1;
+            count += usize_from_limb_bit(seen_one ^ 1);
+        }
+    }
+    count
+}
+
+// Sets `a = a >> shift` for `shift < a.len() * LIMB_BITS`, in constant time
+// with respect to `shift`, by shifting by each power of two or not.
+fn limbs_shr_consttime(a: &mut [Limb], shift: usize) {
+    let mut shifted = Zeroizing::new(vec![0; a.len()]);
+    let mut i = 0;
+    while (1 << i) < a.len() * LIMB_BITS {
+        shifted.copy_from_slice(a);
+        limbs_shr_in_place(&mut shifted, 1 << i);
+        let mask = limb_mask_from_bit((shift >> i) & 1);
+        for (a, &s) in a.iter_mut().zip(shifted.iter()) {
+            *a ^= (*a ^ s) & mask;
+        }
+        i += 1;
+    }
+}
+
+// Sets `a = a >> shift`.
+fn limbs_shr_in_place(a: &mut [Limb], shift: usize) {
+    let (limbs, bits) = (shift / LIMB_BITS, shift % LIMB_BITS);
+    for i in 0..a.len() {
+        let lo = a.get(i + limbs).copied().unwrap_or(0);
+        let hi = a.get(i + limbs + 1).copied().unwrap_or(0);
+        a[i] = if bits == 0 {
+            lo
+        } else {
+            (lo >> bits) | (hi << (LIMB_BITS - bits))
+        };
+    }
+}
+
+// Returns all ones if `a == b`, and zero otherwise.
+fn elem_equal_mask<E>(a: &Elem<Candidate, E>, b: &Elem<Candidate, E>) -> Limb {
+    limb::limbs_equal_limbs_consttime(&a.limbs, &b.limbs) as Limb
+}
+
+// Returns all ones if `a == b`, and zero otherwise, for `a, b < 2**31`.
+fn usize_equal_mask(a: usize, b: usize) -> Limb {
+    let diff = a ^ b;
+    limb_mask_from_bit(((diff | diff.wrapping_neg()) >> (usize::BITS - 1)) ^ 1)
+}
+
+// Returns all ones if `a < b`, and zero otherwise, for `a, b < 2**31`.
+fn usize_less_than_mask(a: usize, b: usize) -> Limb {
+    limb_mask_from_bit(a.wrapping_sub(b) >> (usize::BITS - 1))
+}
+
+// Returns all ones if `bit` is 1, and zero if it is 0.
+#[allow(clippy::cast_possible_truncation)]
+fn limb_mask_from_bit(bit: usize) -> Limb {
+    (0 as Limb).wrapping_sub(bit as Limb)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn usize_from_limb_bit(bit: Limb) -> usize {
+    bit as usize
+}
+
+/// A vector of secret values that is zeroized when it is dropped.
+pub(crate) struct Zeroizing<T: Zeroable>(Vec<T>);
+
+impl<T: Zeroable> Zeroizing<T> {
+    pub(crate) fn new(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroable> core::ops::Deref for Zeroizing<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: Zeroable> core::ops::DerefMut for Zeroizing<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroable> Drop for Zeroizing<T> {
+    fn drop(&mut self) {
+        polyfill::zeroize(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::rand::DeterministicRandom;
+
+    fn to_limbs(value: u128) -> Vec<Limb> {
+        #[allow(clippy::cast_possible_truncation)]
+        (0..(128 / LIMB_BITS))
+            .map(|i| (value >> (i * LIMB_BITS)) as Limb)
+            .collect()
+    }
+
+    fn from_limbs(limbs: &[Limb]) -> u128 {
+        limbs
+            .iter()
+            .rev()
+            .fold(0, |acc, &limb| (acc << LIMB_BITS) | u128::from(limb))
+    }
+
+    fn gcd(mut a: u128, mut b: u128) -> u128 {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+
+    #[test]
+    fn test_small_primes() {
+        assert_eq!(SMALL_PRIMES[..8], [3, 5, 7, 11, 13, 17, 19, 23]);
+        assert_eq!(SMALL_PRIMES[511], 3673);
+    }
+
+    #[test]
+    fn test_small_divisor() {
+        for d in [2, 3, 7, 251, 65521, 65537, (1 << 33) - 9, (1 << 47) - 1] {
+            let d_ = SmallDivisor::new(d);
+            for x in [
+                0,
+                1,
+                d - 1,
+                d,
+                d + 1,
+                2 * d - 1,
+                (d << 16) - 1,
+                (d << 15) + 12345,
+            ] {
+                assert_eq!(d_.div_rem(x), (x / d, x % d), "{} / {}", x, d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_limbs_div_small() {
+        let value = 0x7654_3210_fedc_ba98_89ab_cdef_0123_4567;
+        let a = to_limbs(value);
+        for d in [3, 65537, (1 << 33) - 9] {
+            let d_ = SmallDivisor::new(d);
+            let d = u128::from(d);
+            assert_eq!(u128::from(limbs_mod_small(&a, &d_)), value % d);
+
+            let mut q = a.clone();
+            assert_eq!(u128::from(limbs_div_small_in_place(&mut q, &d_)), value % d);
+            assert_eq!(from_limbs(&q), value / d);
+        }
+    }
+
+    #[test]
+    fn test_small_inverse() {
+        for m in [3, 15, 65537, 65539 * 3, (1 << 33) - 9] {
+            for a in [1, 2, 3, 5, 12345 % m, m - 1, m - 2] {
+                let a = a % m;
+                match small_inverse(a, m) {
+                    Some(inv) => {
+                        assert_eq!(gcd(a.into(), m.into()), 1);
+                        assert_eq!((u128::from(a) * u128::from(inv)) % u128::from(m), 1);
+                    }
+                    None => assert_ne!(gcd(a.into(), m.into()), 1),
+                }
+            }
+        }
+        assert_eq!(small_inverse(0, 65537), None);
+    }
+
+    #[test]
+    fn test_small_inverse_mod() {
+        // Even moduli less than 2**94, so that `e * d` fits in a `u128`.
+        for m in [
+            0x0fed_cba9_1234_5678_ffff_fff0,
+            (1 << 93) + 2,
+            (1 << 94) - 2,
+        ] {
+            for e in [3, 15, 65537, (1 << 33) - 9] {
+                let d = small_inverse_mod(e, &to_limbs(m));
+                if gcd(u128::from(e), m) != 1 {
+                    assert!(d.is_err());
+                    continue;
+                }
+                let d = d.unwrap();
+                assert_eq!(d.len(), 128 / LIMB_BITS);
+                let d = from_limbs(&d);
+                assert!(d < m);
+                assert_eq!((u128::from(e) * d) % m, 1);
+            }
+        }
+        assert!(small_inverse_mod(65537, &to_limbs(65537 * 2)).is_err());
+    }
+
+    #[test]
+    fn test_limbs_abs_diff_bits() {
+        let a = to_limbs(1 << 100);
+        let b = to_limbs((1 << 100) + (1 << 70) + 1);
+        assert_eq!(limbs_abs_diff_bits(&a, &b), 71);
+        assert_eq!(limbs_abs_diff_bits(&b, &a), 71);
+        assert_eq!(limbs_abs_diff_bits(&a, &a), 0);
+    }
+
+    #[test]
+    fn test_limbs_shr_consttime() {
+        let value = 0x7654_3210_fedc_ba98_89ab_cdef_0123_4000;
+        assert_eq!(limbs_trailing_zeros_consttime(&to_limbs(value)), 14);
+        assert_eq!(limbs_trailing_zeros_consttime(&to_limbs(1 << 127)), 127);
+        for shift in [0, 1, 14, 63, 64, 65, 100, 127] {
+            let mut a = to_limbs(value);
+            limbs_shr_consttime(&mut a, shift);
+            assert_eq!(from_limbs(&a), value >> shift, "{}", shift);
+        }
+    }
+
+    #[test]
+    fn test_usize_masks() {
+        for (a, b) in [(0, 0), (1, 0), (0, 1), (5, 5), (4, 5), (1 << 30, 3)] {
+            let expected = |condition: bool| if condition { Limb::MAX } else { 0 };
+            assert_eq!(usize_equal_mask(a, b), expected(a == b));
+            assert_eq!(usize_less_than_mask(a, b), expected(a < b));
+        }
+    }
+
+    // Returns `2**bits - c` with `bits` a multiple of `LIMB_BITS`.
+    fn power_of_two_minus(bits: usize, c: Limb) -> Vec<Limb> {
+        let mut r = vec![Limb::MAX; bits / LIMB_BITS];
+        r[0] -= c - 1;
+        r
+    }
+
+    #[test]
+    fn test_is_probably_prime() {
+        let rng = DeterministicRandom::new(0);
+        let cpu_features = cpu::features();
+
+        // The largest prime below 2**512.
+        let p = power_of_two_minus(512, 569);
+        assert!(is_probably_prime(&p, 5, &rng, cpu_features).unwrap());
+
+        // Its even neighbour's neighbour, which is divisible by 3.
+        let mut not_p = p.clone();
+        not_p[0] -= 2;
+        assert!(!is_probably_prime(&not_p, 5, &rng, cpu_features).unwrap());
+
+        // A composite without small factors: the product of the primes
+        // 2**256 - 189 and 2**255 - 19.
+        let q = power_of_two_minus(256, 189);
+        let mut r = power_of_two_minus(256, 19);
+        let top = r.len() - 1;
+        r[top] >>= 1;
+        let qr = limbs_product(&q, &r);
+        assert!(!is_probably_prime(&qr, 5, &rng, cpu_features).unwrap());
+    }
+
+    #[test]
+    fn test_generate_prime() {
+        let rng = DeterministicRandom::new(0);
+        let cpu_features = cpu::features();
+        let bits = BitLength::from_usize_bits(512);
+
+        let p = generate_prime(bits, 65537, None, &rng, cpu_features).unwrap();
+        assert_eq!(p[p.len() - 1] >> (LIMB_BITS - 2), 0b11);
+        assert_ne!(limbs_mod_small(&p, &SmallDivisor::new(65537)), 1);
+        for &small in SMALL_PRIMES.iter() {
+            assert_ne!(limbs_mod_small(&p, &SmallDivisor::new(u64::from(small))), 0);
+        }
+
+        let q = generate_prime(bits, 65537, Some(&p), &rng, cpu_features).unwrap();
+        assert!(limbs_abs_diff_bits(&p, &q) > 512 - 99);
+    }
+}
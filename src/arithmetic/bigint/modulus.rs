@@ -17,7 +17,7 @@ use super::{
         montgomery::{Unencoded, RR},
         n0::N0,
     },
-    BoxedLimbs, Elem, Nonnegative, One, PublicModulus, SlightlySmallerModulus, SmallerModulus,
+    BoxedLimbs, Elem, Nonnegative, One, PublicModulus, SmallerModulus,
 };
 use crate::{
     bits::BitLength,
@@ -25,14 +25,14 @@ use crate::{
     limb::{self, Limb, LimbMask, LIMB_BITS},
     polyfill::LeadingZerosStripped,
 };
+use alloc::{boxed::Box, collections::TryReserveError};
 use core::marker::PhantomData;
 
-/// The x86 implementation of `bn_mul_mont`, at least, requires at least 4
-/// limbs. For a long time we have required 4 limbs for all targets, though
-/// this may be unnecessary. TODO: Replace this with
-/// `n.len() < 256 / LIMB_BITS` so that 32-bit and 64-bit platforms behave the
-/// same.
-pub const MODULUS_MIN_LIMBS: usize = 4;
+/// The minimum length of a modulus, in bits. This is independent of the limb
+/// size so that all targets accept the same moduli; moduli that are too short
+/// for the `bn_mul_mont` assembly code are handled by the portable fallback in
+/// `limbs_mont_mul` and friends.
+pub const MODULUS_MIN_BITS: usize = 128;
 
 pub const MODULUS_MAX_LIMBS: usize = super::super::BIGINT_MODULUS_MAX_LIMBS;
 
@@ -99,14 +99,47 @@ impl<M: PublicModulus> Clone for OwnedModulusWithOne<M> {
     }
 }
 
+/// Formats the modulus as `Modulus(0x...)`, where the value is written in
+/// lowercase hex without leading zeros.
+///
+/// The type isn't public, so this example can't be run as a doc-test;
+/// `test_debug` and `test_debug_strips_leading_zeros` check the same thing.
+///
+/// ```ignore
+/// let n = OwnedModulusWithOne::<N>::from_be_bytes(
+///     untrusted::Input::from(&[0x0a, 0xbc, /* ... */ 0xff]),
+///     cpu::features(),
+/// )?;
+/// assert_eq!(format!("{:?}", n), "Modulus(0xabc...ff)");
+/// ```
+///
+/// Only public moduli implement `Debug`; without specialization there is no
+/// way to also provide a redacted implementation for private moduli, and
+/// leaving it out entirely makes it impossible to print one by accident.
 impl<M: PublicModulus> core::fmt::Debug for OwnedModulusWithOne<M> {
     fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> Result<(), ::core::fmt::Error> {
-        fmt.debug_struct("Modulus")
-            // TODO: Print modulus value.
+        fmt.debug_tuple("Modulus")
+            .field(&HexValue(self.be_bytes()))
             .finish()
     }
 }
 
+/// The big-endian bytes of a value, with the leading zero bytes already
+/// stripped, formatted as a `0x`-prefixed hex number.
+struct HexValue<I>(I);
+
+impl<I: Iterator<Item = u8> + Clone> core::fmt::Debug for HexValue<I> {
+    fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> Result<(), ::core::fmt::Error> {
+        let mut bytes = self.0.clone();
+        fmt.write_str("0x")?;
+        // Only the first byte may have a leading zero nibble to strip.
+        if let Some(first) = bytes.next() {
+            write!(fmt, "{:x}", first)?;
+        }
+        bytes.try_for_each(|b| write!(fmt, "{:02x}", b))
+    }
+}
+
 impl<M> OwnedModulusWithOne<M> {
     pub(crate) fn from_be_bytes(
         input: untrusted::Input,
@@ -124,35 +157,78 @@ impl<M> OwnedModulusWithOne<M> {
         Self::from_boxed_limbs(limbs, cpu_features)
     }
 
-    pub(crate) fn from_elem<L>(
-        elem: Elem<L, Unencoded>,
+    /// Reconstructs a modulus from the values previously returned by
+    /// `limbs()`, `n0_u64()`, and `oneRR()`, avoiding the expensive
+    /// computation of `oneRR`.
+    ///
+    /// The cheap invariants of the modulus are re-validated, and `rr` is
+    /// checked to be reduced modulo `n` and `n0` is checked to be consistent
+    /// with `n`. The caller is responsible for ensuring the integrity of
+    /// `rr`'s value beyond that.
+    #[allow(clippy::useless_conversion)]
+    pub(crate) fn from_precomputed(
+        n: &[Limb],
+        n0: u64,
+        rr: &[Limb],
         cpu_features: cpu::Features,
-    ) -> Result<Self, error::KeyRejected>
-    where
-        M: SlightlySmallerModulus<L>,
-    {
-        Self::from_boxed_limbs(
-            BoxedLimbs::minimal_width_from_unpadded(&elem.limbs),
+    ) -> Result<Self, error::KeyRejected> {
+        // Only minimal-width encodings are accepted.
+        if n.last() == Some(&0) {
+            return Err(error::KeyRejected::invalid_encoding());
+        }
+        let n = BoxedLimbs::minimal_width_from_unpadded(n)
+            .map_err(|_| error::KeyRejected::out_of_memory())?;
+        Self::check_limbs(&n)?;
+
+        // n0 * n == -1 (mod r); see the documentation for `n0`.
+        let mut n_mod_r = u64::from(n[0]);
+        if N0::LIMBS_USED == 2 {
+            debug_assert_eq!(LIMB_BITS, 32);
+            n_mod_r |= u64::from(n[1]) << 32;
+        }
+        if n0.wrapping_mul(n_mod_r) != u64::MAX {
+            return Err(error::KeyRejected::inconsistent_components());
+        }
+
+        if rr.len() != n.len() || limb::limbs_less_than_limbs_consttime(rr, &n) != LimbMask::True {
+            return Err(error::KeyRejected::inconsistent_components());
+        }
+        let oneRR = One(Elem {
+            limbs: BoxedLimbs::new_unchecked(Box::<[Limb]>::from(rr)),
+            encoding: PhantomData,
+        });
+
+        let len_bits = limb::limbs_minimal_bits(&n);
+        Ok(Self {
+            limbs: n,
+            n0: N0::from(n0),
+            oneRR,
+            len_bits,
             cpu_features,
-        )
+        })
     }
 
-    fn from_boxed_limbs(
-        n: BoxedLimbs<M>,
-        cpu_features: cpu::Features,
-    ) -> Result<Self, error::KeyRejected> {
+    fn check_limbs(n: &BoxedLimbs<M>) -> Result<(), error::KeyRejected> {
         if n.len() > MODULUS_MAX_LIMBS {
             return Err(error::KeyRejected::too_large());
         }
-        if n.len() < MODULUS_MIN_LIMBS {
+        if limb::limbs_minimal_bits(n).as_usize_bits() < MODULUS_MIN_BITS {
             return Err(error::KeyRejected::unexpected_error());
         }
-        if limb::limbs_are_even_constant_time(&n) != LimbMask::False {
+        if limb::limbs_are_even_constant_time(n) != LimbMask::False {
             return Err(error::KeyRejected::invalid_component());
         }
-        if limb::limbs_less_than_limb_constant_time(&n, 3) != LimbMask::False {
+        if limb::limbs_less_than_limb_constant_time(n, 3) != LimbMask::False {
             return Err(error::KeyRejected::unexpected_error());
         }
+        Ok(())
+    }
+
+    pub(super) fn from_boxed_limbs(
+        n: BoxedLimbs<M>,
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::KeyRejected> {
+        Self::check_limbs(&n)?;
 
         // n_mod_r = n % r. As explained in the documentation for `n0`, this is
         // done by taking the lowest `N0::LIMBS_USED` limbs of `n`.
@@ -184,7 +260,7 @@ impl<M> OwnedModulusWithOne<M> {
                 cpu_features,
             };
 
-            One::newRR(&partial)
+            One::newRR(&partial).map_err(|_| error::KeyRejected::out_of_memory())?
         };
 
         Ok(Self {
@@ -200,16 +276,34 @@ impl<M> OwnedModulusWithOne<M> {
         &self.oneRR
     }
 
-    pub fn to_elem<L>(&self, l: &Modulus<L>) -> Elem<L, Unencoded>
+    /// The limbs of the modulus, least significant limb first, without any
+    /// padding.
+    #[inline]
+    pub(crate) fn limbs(&self) -> &[Limb] {
+        &self.limbs
+    }
+
+    #[inline]
+    pub(crate) fn n0_u64(&self) -> u64 {
+        self.n0.to_u64()
+    }
+
+    /// The limbs of `oneRR`, in the same form as `limbs()`.
+    #[inline]
+    pub(crate) fn oneRR_limbs(&self) -> &[Limb] {
+        &self.oneRR.0.limbs
+    }
+
+    pub fn to_elem<L>(&self, l: &Modulus<L>) -> Result<Elem<L, Unencoded>, TryReserveError>
     where
         M: SmallerModulus<L>,
     {
-        let mut limbs = BoxedLimbs::zero(l.limbs.len());
+        let mut limbs = BoxedLimbs::try_zero(l.limbs.len())?;
         limbs[..self.limbs.len()].copy_from_slice(&self.limbs);
-        Elem {
+        Ok(Elem {
             limbs,
             encoding: PhantomData,
-        }
+        })
     }
     pub fn modulus(&self) -> Modulus<M> {
         Modulus {
@@ -224,6 +318,41 @@ impl<M> OwnedModulusWithOne<M> {
     pub fn len_bits(&self) -> BitLength {
         self.len_bits
     }
+
+    /// Moves a secret modulus, and `oneRR`, which reveals it, into locked
+    /// memory.
+    #[cfg(feature = "locked-memory")]
+    pub(crate) fn into_locked(self) -> Self {
+        Self {
+            limbs: self.limbs.into_locked(),
+            oneRR: One(self.oneRR.0.into_locked()),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "locked-memory")]
+    pub(crate) fn was_locked(&self) -> bool {
+        self.limbs.was_locked() && self.oneRR.0.was_locked()
+    }
+}
+
+/// The limbs are compared in constant time, without branching on their
+/// values; only their number affects the timing. A modulus is equal to one
+/// with the same value and more (zero) limbs.
+impl<M> subtle::ConstantTimeEq for OwnedModulusWithOne<M> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        let (shorter, longer) = if self.limbs.len() <= other.limbs.len() {
+            (&self.limbs, &other.limbs)
+        } else {
+            (&other.limbs, &self.limbs)
+        };
+        let padded = shorter.iter().chain(core::iter::repeat(&0));
+        let diff = longer
+            .iter()
+            .zip(padded)
+            .fold(0, |diff, (a, b)| diff | (a ^ b));
+        diff.ct_eq(&0)
+    }
 }
 
 impl<M: PublicModulus> OwnedModulusWithOne<M> {
@@ -249,6 +378,13 @@ impl<M> Modulus<'_, M> {
         }
     }
 
+    pub(super) fn try_zero<E>(&self) -> Result<Elem<M, E>, TryReserveError> {
+        Ok(Elem {
+            limbs: BoxedLimbs::try_zero(self.limbs.len())?,
+            encoding: PhantomData,
+        })
+    }
+
     // TODO: Get rid of this
     pub(super) fn one(&self) -> Elem<M, Unencoded> {
         let mut r = self.zero();
@@ -256,6 +392,12 @@ impl<M> Modulus<'_, M> {
         r
     }
 
+    pub(super) fn try_one(&self) -> Result<Elem<M, Unencoded>, TryReserveError> {
+        let mut r = self.try_zero()?;
+        r.limbs[0] = 1;
+        Ok(r)
+    }
+
     #[inline]
     pub(super) fn limbs(&self) -> &[Limb] {
         self.limbs
@@ -275,3 +417,90 @@ impl<M> Modulus<'_, M> {
         self.cpu_features
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use alloc::{format, vec};
+
+    // Type-level representation of an arbitrary modulus.
+    struct M {}
+    impl PublicModulus for M {}
+
+    fn modulus(limbs: &[Limb]) -> OwnedModulusWithOne<M> {
+        let limbs = BoxedLimbs::new_unchecked(Box::<[Limb]>::from(limbs));
+        OwnedModulusWithOne::from_boxed_limbs(limbs, cpu::features()).unwrap()
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        use subtle::ConstantTimeEq;
+
+        const MAX: Limb = Limb::MAX;
+        let a = modulus(&[MAX, MAX, MAX, MAX, 1]);
+        let b = modulus(&[MAX - 2, MAX, MAX, MAX, 1]);
+        let c = modulus(&[MAX, MAX, MAX, MAX, 1, 0, 0]);
+        let d = modulus(&[MAX, MAX, MAX, MAX, 1, 1]);
+
+        assert!(bool::from(a.ct_eq(&a)));
+        assert!(bool::from(a.ct_eq(&modulus(&[MAX, MAX, MAX, MAX, 1]))));
+        assert!(!bool::from(a.ct_eq(&b)));
+        assert!(!bool::from(b.ct_eq(&a)));
+
+        // Different lengths, same value.
+        assert!(bool::from(a.ct_eq(&c)));
+        assert!(bool::from(c.ct_eq(&a)));
+
+        // Different lengths, different values.
+        assert!(!bool::from(a.ct_eq(&d)));
+        assert!(!bool::from(d.ct_eq(&c)));
+    }
+
+    #[test]
+    fn test_debug() {
+        const N: &str = concat!(
+            "cf3feeac9c76b4c69e4c7b7504ade8cbbe1505af4d2ea8fb72948a8181912423",
+            "80fff446f3ac78404032f0333db188fc089430994e342e15be0417a649370324",
+            "4165087b9720e046e14796c8d7964e9f49b002e8adc083bb8e34b4dee6199274",
+            "d629de0c49427add77d0b3896090e8325e89cc77b51b9649e677ecec75d9213f",
+            "309efabb2a06d2ae5787fb6c17cb2cb51491b19a9099ae131203fcc678db6c69",
+            "2290f4642a2ec2a8767bcd311393c3cab0843c70fe2566c199905897d6981e80",
+            "ca6e3f4b2959a2b965a4527d2545f52dbb640a970c02eaf4d47d00a3474d5f2e",
+            "ff2ce6864a1a61f54bcd0baca79713bec058f9732cefeddb5ef1f821c8a709c5",
+        );
+        let n_bytes = test::from_hex(N).unwrap();
+        let n = OwnedModulusWithOne::<M>::from_be_bytes(
+            untrusted::Input::from(&n_bytes),
+            cpu::features(),
+        )
+        .unwrap();
+
+        let formatted = format!("{:?}", n);
+        assert_eq!(formatted, format!("Modulus(0x{})", N));
+
+        let hex = formatted
+            .strip_prefix("Modulus(0x")
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap();
+        assert_eq!(test::from_hex(hex).unwrap(), n_bytes);
+    }
+
+    #[test]
+    fn test_debug_strips_leading_zeros() {
+        // A 140-bit value whose most significant byte has a leading zero
+        // nibble, and whose most significant limb has leading zero bytes on
+        // every target. It is given as bytes so that it is the same value on
+        // every target.
+        let mut n_bytes = vec![0x0a, 0xbc];
+        n_bytes.extend_from_slice(&[0xff; 16]);
+        let n = OwnedModulusWithOne::<M>::from_be_bytes(
+            untrusted::Input::from(&n_bytes),
+            cpu::features(),
+        )
+        .unwrap();
+        assert_eq!(n.len_bits().as_usize_bits(), 140);
+        let expected = format!("Modulus(0xabc{})", "ff".repeat(16));
+        assert_eq!(format!("{:?}", n), expected);
+    }
+}
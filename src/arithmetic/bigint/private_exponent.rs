@@ -12,21 +12,29 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use super::{elem_add, elem_sub, limb, BoxedLimbs, Limb, LimbMask, Modulus, Prime};
+use super::{
+    boxed_limbs::Storage, elem_add, elem_sub, limb, BoxedLimbs, Limb, LimbMask, Modulus, Prime,
+};
 use crate::error;
-use alloc::boxed::Box;
+use alloc::collections::TryReserveError;
 
 pub struct PrivateExponent {
-    limbs: Box<[Limb]>,
+    limbs: Storage,
 }
 
 impl PrivateExponent {
     // `p` is the modulus for which the exponent is in the interval [1, `p` - 1).
+    //
+    // An invalid value is rejected with
+    // `KeyRejected::inconsistent_components()`.
     pub fn from_be_bytes_padded<M>(
         input: untrusted::Input,
         p: &Modulus<M>,
-    ) -> Result<Self, error::Unspecified> {
-        let dP = BoxedLimbs::from_be_bytes_padded_less_than(input, p)?;
+    ) -> Result<Self, error::KeyRejected> {
+        let mut dP = BoxedLimbs::try_zero(p.limbs().len())
+            .map_err(|_| error::KeyRejected::out_of_memory())?;
+        dP.set_from_be_bytes_padded_less_than(input, p)
+            .map_err(|error::Unspecified| error::KeyRejected::inconsistent_components())?;
 
         // Proof that `dP < p - 1`:
         //
@@ -37,7 +45,7 @@ impl PrivateExponent {
         //
         // Further we know `dP != 0` because `dP` is not even.
         if limb::limbs_are_even_constant_time(&dP) != LimbMask::False {
-            return Err(error::Unspecified);
+            return Err(error::KeyRejected::inconsistent_components());
         }
 
         Ok(Self {
@@ -51,8 +59,8 @@ impl PrivateExponent {
         p: &Modulus<M>,
     ) -> Result<Self, error::Unspecified> {
         // Do exactly what `from_be_bytes_padded` does for any inputs it accepts.
-        if let r @ Ok(_) = Self::from_be_bytes_padded(input, p) {
-            return r;
+        if let Ok(r) = Self::from_be_bytes_padded(input, p) {
+            return Ok(r);
         }
 
         let dP = BoxedLimbs::<M>::positive_minimal_width_from_be_bytes(input)?;
@@ -63,16 +71,28 @@ impl PrivateExponent {
     }
 
     #[inline]
-    pub(super) fn limbs(&self) -> &[Limb] {
+    pub(crate) fn limbs(&self) -> &[Limb] {
         &self.limbs
     }
 
-    // Returns `p - 2`.
-    pub(super) fn for_flt<P: Prime>(p: &Modulus<P>) -> Self {
-        let two = elem_add(p.one(), p.one(), p);
-        let p_minus_2 = elem_sub(p.zero(), &two, p);
+    #[cfg(feature = "locked-memory")]
+    pub(crate) fn into_locked(self) -> Self {
         Self {
-            limbs: p_minus_2.limbs.into_limbs(),
+            limbs: self.limbs.into_locked(),
         }
     }
+
+    #[cfg(feature = "locked-memory")]
+    pub(crate) fn was_locked(&self) -> bool {
+        self.limbs.was_locked()
+    }
+
+    // Returns `p - 2`.
+    pub(super) fn for_flt<P: Prime>(p: &Modulus<P>) -> Result<Self, TryReserveError> {
+        let two = elem_add(p.try_one()?, p.try_one()?, p);
+        let p_minus_2 = elem_sub(p.try_zero()?, &two, p);
+        Ok(Self {
+            limbs: p_minus_2.limbs.into_limbs(),
+        })
+    }
 }
@@ -108,27 +108,48 @@ prefixed_export! {
         n0: &N0,
         num_limbs: c::size_t,
     ) {
-        // The mutable pointer `r` may alias `a` and/or `b`, so the lifetimes of
-        // any slices for `a` or `b` must not overlap with the lifetime of any
-        // mutable for `r`.
-
-        // Nothing aliases `n`
-        let n = unsafe { core::slice::from_raw_parts(n, num_limbs) };
-
-        let mut tmp = [0; 2 * super::BIGINT_MODULUS_MAX_LIMBS];
-        let tmp = &mut tmp[..(2 * num_limbs)];
-        {
-            let a: &[Limb] = unsafe { core::slice::from_raw_parts(a, num_limbs) };
-            let b: &[Limb] = unsafe { core::slice::from_raw_parts(b, num_limbs) };
-            limbs_mul(tmp, a, b);
-        }
-        let r: &mut [Limb] = unsafe { core::slice::from_raw_parts_mut(r, num_limbs) };
-        limbs_from_mont_in_place(r, tmp, n, n0);
+        unsafe { limbs_mul_mont_portable(r, a, b, n, n0, num_limbs) }
+    }
+}
+
+/// The portable implementation of `bn_mul_mont`. `bigint` uses this for moduli
+/// that are too small for the assembly implementations.
+#[cfg(any(
+    feature = "alloc",
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "x86",
+        target_arch = "x86_64"
+    ))
+))]
+pub(super) unsafe fn limbs_mul_mont_portable(
+    r: *mut Limb,
+    a: *const Limb,
+    b: *const Limb,
+    n: *const Limb,
+    n0: &N0,
+    num_limbs: c::size_t,
+) {
+    // The mutable pointer `r` may alias `a` and/or `b`, so the lifetimes of
+    // any slices for `a` or `b` must not overlap with the lifetime of any
+    // mutable for `r`.
+
+    // Nothing aliases `n`
+    let n = unsafe { core::slice::from_raw_parts(n, num_limbs) };
+
+    let mut tmp = [0; 2 * super::BIGINT_MODULUS_MAX_LIMBS];
+    let tmp = &mut tmp[..(2 * num_limbs)];
+    {
+        let a: &[Limb] = unsafe { core::slice::from_raw_parts(a, num_limbs) };
+        let b: &[Limb] = unsafe { core::slice::from_raw_parts(b, num_limbs) };
+        limbs_mul(tmp, a, b);
     }
+    let r: &mut [Limb] = unsafe { core::slice::from_raw_parts_mut(r, num_limbs) };
+    limbs_from_mont_in_place(r, tmp, n, n0);
 }
 
-// `bigint` needs then when the `alloc` feature is enabled. `bn_mul_mont` above needs this when
-// we are using the platforms for which we don't have `bn_mul_mont` in assembly.
+// `bigint` needs this when the `alloc` feature is enabled.
 #[cfg(any(
     feature = "alloc",
     not(any(
@@ -164,13 +185,16 @@ pub(super) fn limbs_from_mont_in_place(r: &mut [Limb], tmp: &mut [Limb], m: &[Li
     .unwrap()
 }
 
-#[cfg(not(any(
-    target_arch = "aarch64",
-    target_arch = "arm",
-    target_arch = "x86",
-    target_arch = "x86_64"
-)))]
-fn limbs_mul(r: &mut [Limb], a: &[Limb], b: &[Limb]) {
+#[cfg(any(
+    feature = "alloc",
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "x86",
+        target_arch = "x86_64"
+    ))
+))]
+pub(super) fn limbs_mul(r: &mut [Limb], a: &[Limb], b: &[Limb]) {
     debug_assert_eq!(r.len(), 2 * a.len());
     debug_assert_eq!(a.len(), b.len());
     let ab_len = a.len();
@@ -178,18 +202,14 @@ fn limbs_mul(r: &mut [Limb], a: &[Limb], b: &[Limb]) {
     r[..ab_len].fill(0);
     for (i, &b_limb) in b.iter().enumerate() {
         r[ab_len + i] = unsafe {
-            limbs_mul_add_limb(
-                (&mut r[i..][..ab_len]).as_mut_ptr(),
-                a.as_ptr(),
-                b_limb,
-                ab_len,
-            )
+            limbs_mul_add_limb(r[i..][..ab_len].as_mut_ptr(), a.as_ptr(), b_limb, ab_len)
         };
     }
 }
 
 #[cfg(any(
     test,
+    feature = "alloc",
     not(any(
         target_arch = "aarch64",
         target_arch = "arm",
@@ -23,6 +23,22 @@ impl N0 {
     pub(super) const LIMBS_USED: usize = 64 / crate::limb::LIMB_BITS;
 }
 
+impl N0 {
+    #[cfg(feature = "alloc")]
+    #[allow(clippy::useless_conversion)]
+    pub(super) fn to_u64(&self) -> u64 {
+        #[cfg(target_pointer_width = "64")]
+        {
+            u64::from(self.0[0])
+        }
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            u64::from(self.0[0]) | (u64::from(self.0[1]) << crate::limb::LIMB_BITS)
+        }
+    }
+}
+
 impl From<u64> for N0 {
     #[inline]
     fn from(n0: u64) -> Self {
@@ -14,9 +14,9 @@
 
 use crate::{
     bits, error,
-    limb::{self, Limb, LimbMask, LIMB_BYTES},
+    limb::{self, Limb, LimbMask, LIMB_BITS, LIMB_BYTES},
 };
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, vec::Vec};
 
 /// Nonnegative integers.
 pub(crate) struct Nonnegative {
@@ -24,12 +24,19 @@ pub(crate) struct Nonnegative {
 }
 
 impl Nonnegative {
+    // An invalid encoding is rejected with `KeyRejected::invalid_encoding()`.
     pub fn from_be_bytes_with_bit_length(
         input: untrusted::Input,
-    ) -> Result<(Self, bits::BitLength), error::Unspecified> {
-        let mut limbs = vec![0; (input.len() + LIMB_BYTES - 1) / LIMB_BYTES];
+    ) -> Result<(Self, bits::BitLength), error::KeyRejected> {
+        let num_limbs = (input.len() + LIMB_BYTES - 1) / LIMB_BYTES;
+        let mut limbs = Vec::new();
+        limbs
+            .try_reserve_exact(num_limbs)
+            .map_err(|_| error::KeyRejected::out_of_memory())?;
+        limbs.resize(num_limbs, 0);
         // Rejects empty inputs.
-        limb::parse_big_endian_and_pad_consttime(input, &mut limbs)?;
+        limb::parse_big_endian_and_pad_consttime(input, &mut limbs)
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
         while limbs.last() == Some(&0) {
             let _ = limbs.pop();
         }
@@ -42,6 +49,44 @@ impl Nonnegative {
         limb::limbs_are_even_constant_time(&self.limbs) != LimbMask::True
     }
 
+    // The methods below are like OpenSSL's `BN_num_bits`, `BN_is_bit_set`, and
+    // `BN_rshift`. They are not constant-time.
+
+    /// Returns the number of bits needed to represent the value; zero for zero.
+    #[allow(dead_code)]
+    pub fn bit_length(&self) -> usize {
+        limb::limbs_minimal_bits(&self.limbs).as_usize_bits()
+    }
+
+    /// Returns whether bit `index` (counting from the least significant bit)
+    /// is set. Bits at or beyond `bit_length()` are never set.
+    #[allow(dead_code)]
+    pub fn bit(&self, index: usize) -> bool {
+        match self.limbs.get(index / LIMB_BITS) {
+            Some(limb) => (limb >> (index % LIMB_BITS)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    /// Returns the value divided by 2**`bits`, rounded down.
+    #[allow(dead_code)]
+    pub fn shift_right(&self, bits: usize) -> Self {
+        let mut limbs = self.limbs.get((bits / LIMB_BITS)..).unwrap_or(&[]).to_vec();
+        let bit_shift = bits % LIMB_BITS;
+        if bit_shift != 0 {
+            let mut carry = 0;
+            for limb in limbs.iter_mut().rev() {
+                let value = *limb;
+                *limb = (value >> bit_shift) | carry;
+                carry = value << (LIMB_BITS - bit_shift);
+            }
+        }
+        while limbs.last() == Some(&0) {
+            let _ = limbs.pop();
+        }
+        Self { limbs }
+    }
+
     pub fn verify_less_than(&self, other: &Self) -> Result<(), error::Unspecified> {
         if !greater_than(other, self) {
             return Err(error::Unspecified);
@@ -68,3 +113,64 @@ fn greater_than(a: &Nonnegative, b: &Nonnegative) -> bool {
         a.limbs.len() > b.limbs.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_be_bytes(bytes: &[u8]) -> Nonnegative {
+        let (r, _) =
+            Nonnegative::from_be_bytes_with_bit_length(untrusted::Input::from(bytes)).unwrap();
+        r
+    }
+
+    #[test]
+    fn test_bit_length() {
+        assert_eq!(from_be_bytes(&[0]).bit_length(), 0);
+        assert_eq!(from_be_bytes(&[1]).bit_length(), 1);
+        assert_eq!(from_be_bytes(&[0, 0, 0x80]).bit_length(), 8);
+        assert_eq!(from_be_bytes(&[0xff; 8]).bit_length(), 64);
+        assert_eq!(from_be_bytes(&[1, 0, 0, 0, 0, 0, 0, 0, 0]).bit_length(), 65);
+    }
+
+    #[test]
+    fn test_bit() {
+        let even = from_be_bytes(&[0x12, 0x34]);
+        assert!(!even.bit(0));
+        assert!(even.bit(2));
+        assert!(!even.is_odd());
+
+        let top = from_be_bytes(&[0x80, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(top.bit(63));
+        assert!(!top.bit(62));
+        assert!(!top.bit(64));
+        assert!(!top.bit(usize::MAX));
+
+        assert!(!from_be_bytes(&[0]).bit(0));
+        assert!(from_be_bytes(&[3]).is_odd());
+    }
+
+    #[test]
+    fn test_shift_right() {
+        let value = from_be_bytes(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+        assert_eq!(value.bit_length(), 72);
+
+        let shifted = value.shift_right(1);
+        assert_eq!(shifted.bit_length(), 71);
+        assert!(shifted.bit(70));
+        assert!(!shifted.bit(0));
+
+        let shifted = value.shift_right(64);
+        assert_eq!(shifted.bit_length(), 8);
+        assert!(shifted.bit(7));
+
+        // Shifts that clear every limb.
+        for bits in [72, 73, 128, 1000] {
+            let shifted = value.shift_right(bits);
+            assert_eq!(shifted.bit_length(), 0);
+            assert!(shifted.limbs().is_empty());
+        }
+
+        assert_eq!(value.shift_right(0).limbs(), value.limbs());
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Unpadded base64, as used in password hash strings and key text.
+
+use crate::error;
+
+pub(crate) struct Alphabet([u8; 64]);
+
+/// passlib's "adapted base64", which uses `.` in place of `+`.
+pub(crate) static AB64: Alphabet =
+    Alphabet(*b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789./");
+
+/// The "B64" encoding of the PHC string format, which uses the standard
+/// base64 alphabet.
+pub(crate) static PHC: Alphabet =
+    Alphabet(*b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/");
+
+/// The standard alphabet of [RFC 4648 Section 4].
+///
+/// [RFC 4648 Section 4]: https://www.rfc-editor.org/rfc/rfc4648#section-4
+pub(crate) static STANDARD: Alphabet =
+    Alphabet(*b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/");
+
+/// The URL- and filename-safe alphabet of [RFC 4648 Section 5].
+///
+/// [RFC 4648 Section 5]: https://www.rfc-editor.org/rfc/rfc4648#section-5
+pub(crate) static URL_SAFE: Alphabet =
+    Alphabet(*b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_");
+
+impl Alphabet {
+    pub(crate) fn contains(&self, c: u8) -> bool {
+        self.0.contains(&c)
+    }
+
+    /// Returns the value of the character `c`.
+    ///
+    /// Every entry of the alphabet is compared with `c`, without any branches
+    /// or table lookups that depend on `c`, since the encoded data is often a
+    /// secret.
+    pub(crate) fn decode_char(&self, c: u8) -> Result<u8, error::Unspecified> {
+        let mut value = 0;
+        let mut found = 0;
+        for (i, &a) in (0u8..).zip(self.0.iter()) {
+            let m = mask(a == c);
+            value |= m & i;
+            found |= m;
+        }
+        if found == 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(value)
+    }
+}
+
+// Returns 0xff if `b` is true and 0 otherwise.
+#[inline]
+fn mask(b: bool) -> u8 {
+    0u8.wrapping_sub(u8::from(b))
+}
+
+/// Returns the three bytes encoded by four characters with the values
+/// `[a, b, c, d]`.
+pub(crate) fn decode_quantum([a, b, c, d]: [u8; 4]) -> [u8; 3] {
+    [(a << 2) | (b >> 4), (b << 4) | (c >> 2), (c << 6) | d]
+}
+
+pub(crate) fn encode(
+    alphabet: &Alphabet,
+    input: &[u8],
+    f: &mut dyn core::fmt::Write,
+) -> core::fmt::Result {
+    for chunk in input.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let indices = [
+            buf[0] >> 2,
+            ((buf[0] & 0x03) << 4) | (buf[1] >> 4),
+            ((buf[1] & 0x0f) << 2) | (buf[2] >> 6),
+            buf[2] & 0x3f,
+        ];
+        for &index in &indices[..=chunk.len()] {
+            f.write_char(char::from(alphabet.0[usize::from(index)]))?;
+        }
+    }
+    Ok(())
+}
+
+// Decodes `input` into the start of `out`, returning the decoded length.
+// Non-canonical encodings, where the unused bits of the last character aren't
+// zero, are rejected.
+pub(crate) fn decode(
+    alphabet: &Alphabet,
+    input: &str,
+    out: &mut [u8],
+) -> Result<usize, error::Unspecified> {
+    let input = input.as_bytes();
+    if input.len() % 4 == 1 {
+        return Err(error::Unspecified);
+    }
+    let len = input.len() / 4 * 3 + (input.len() % 4).saturating_sub(1);
+    let out = out.get_mut(..len).ok_or(error::Unspecified)?;
+
+    for (chunk, out) in input.chunks(4).zip(out.chunks_mut(3)) {
+        let mut values = [0u8; 4];
+        for (value, &c) in values.iter_mut().zip(chunk) {
+            *value = alphabet.decode_char(c)?;
+        }
+        let bytes = decode_quantum(values);
+        let unused = match chunk.len() {
+            2 => values[1] & 0x0f,
+            3 => values[2] & 0x03,
+            _ => 0,
+        };
+        if unused != 0 {
+            return Err(error::Unspecified);
+        }
+        out.copy_from_slice(&bytes[..out.len()]);
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_char() {
+        for alphabet in [&AB64, &PHC, &STANDARD, &URL_SAFE] {
+            for c in 0..=u8::MAX {
+                let expected = alphabet.0.iter().position(|&a| a == c);
+                let actual = alphabet.decode_char(c).ok().map(usize::from);
+                assert_eq!(actual, expected, "{:?}", char::from(c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut out = [0u8; 4];
+        assert_eq!(decode(&AB64, "", &mut out), Ok(0));
+        assert_eq!(decode(&AB64, "AA", &mut out), Ok(1));
+        assert_eq!(decode(&AB64, "AAA", &mut out), Ok(2));
+        assert_eq!(decode(&AB64, "AAAA", &mut out), Ok(3));
+        assert_eq!(decode(&AB64, "//8", &mut out), Ok(2));
+        assert_eq!(&out[..2], &[0xff, 0xff]);
+        assert_eq!(decode(&AB64, "..8", &mut out), Ok(2));
+        assert_eq!(&out[..2], &[0xfb, 0xef]);
+        assert_eq!(decode(&PHC, "++8", &mut out), Ok(2));
+        assert_eq!(&out[..2], &[0xfb, 0xef]);
+
+        // Wrong length.
+        assert!(decode(&AB64, "A", &mut out).is_err());
+        assert!(decode(&AB64, "AAAAAAAA", &mut out).is_err());
+
+        // Unused bits set.
+        assert!(decode(&AB64, "AB", &mut out).is_err());
+        assert!(decode(&AB64, "AAB", &mut out).is_err());
+
+        // The alphabets aren't interchangeable, and padding isn't accepted.
+        assert!(decode(&AB64, "+A", &mut out).is_err());
+        assert!(decode(&PHC, ".A", &mut out).is_err());
+        assert!(decode(&AB64, "AA==", &mut out).is_err());
+        assert!(decode(&PHC, "AA==", &mut out).is_err());
+    }
+}
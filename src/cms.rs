@@ -0,0 +1,526 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Verification of detached CMS `SignedData` signatures.
+//!
+//! [`verify_detached`] verifies a DER-encoded CMS `ContentInfo` containing a
+//! `SignedData` ([RFC 5652 Section 5]) whose content is carried separately,
+//! like the output of `openssl cms -sign -binary -outform DER`. The content
+//! is read from a `std::io::Read` and digested as it is read, so it is never
+//! held in memory all at once.
+//!
+//! The `SignedData` must have exactly one `SignerInfo`, and it must have
+//! signed attributes that include the `contentType` and `messageDigest`
+//! attributes. The signature is over the DER encoding of the signed
+//! attributes as a `SET OF`, as RFC 5652 Section 5.4 requires; the
+//! attributes are sorted into DER order before being verified, so signatures
+//! are accepted even if the signer encoded the attributes in another order.
+//!
+//! Signers may use RSA PKCS#1 1.5, RSA-PSS, ECDSA, or Ed25519 signatures,
+//! with the algorithms supported by [`AlgorithmIdentifier`]. The content must
+//! be digested with SHA-256, SHA-384, or SHA-512.
+//!
+//! Only the signature is verified. Any certificates in the `SignedData` are
+//! ignored: the caller supplies the signer's public key, and is responsible
+//! for deciding whether it is trusted.
+//!
+//! Only available in `std` mode.
+//!
+//! [RFC 5652 Section 5]: https://tools.ietf.org/html/rfc5652#section-5
+//! [`AlgorithmIdentifier`]: crate::signature::AlgorithmIdentifier
+
+extern crate std;
+
+use crate::{
+    digest, error,
+    io::{der, der_writer},
+    signature::{
+        algorithm_identifier::parse_hash_algorithm, AlgorithmIdentifier, AlgorithmIdentifierError,
+        VerificationAlgorithm,
+    },
+};
+use std::{
+    io::{self, Read},
+    vec::Vec,
+};
+
+/// An error from [`verify_detached`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading the content failed with an error of the given kind.
+    Io(io::ErrorKind),
+
+    /// The input isn't a DER-encoded `ContentInfo` containing a
+    /// `SignedData`, or the signed attributes are malformed.
+    Malformed,
+
+    /// The `SignedData` contains the content, so it isn't detached.
+    EmbeddedContent,
+
+    /// The `SignedData` doesn't have exactly one `SignerInfo`.
+    UnsupportedSignerCount,
+
+    /// The `SignerInfo` has no signed attributes, or they don't include a
+    /// `contentType` or `messageDigest` attribute.
+    MissingSignedAttributes,
+
+    /// The digest algorithm, signature algorithm, or signer's public key
+    /// algorithm isn't supported.
+    UnsupportedAlgorithm,
+
+    /// The signer's public key isn't a valid DER-encoded
+    /// `SubjectPublicKeyInfo`, or can't be used with the signature
+    /// algorithm.
+    InvalidSignerKey,
+
+    /// The `contentType` attribute doesn't match the content type of the
+    /// `SignedData`.
+    ContentTypeMismatch,
+
+    /// The `messageDigest` attribute doesn't match the digest of the content.
+    MessageDigestMismatch,
+
+    /// The signature is invalid.
+    BadSignature,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "I/O error: {}", kind),
+            Self::Malformed => f.write_str("malformed SignedData"),
+            Self::EmbeddedContent => f.write_str("content isn't detached"),
+            Self::UnsupportedSignerCount => f.write_str("not exactly one SignerInfo"),
+            Self::MissingSignedAttributes => f.write_str("missing signed attributes"),
+            Self::UnsupportedAlgorithm => f.write_str("unsupported algorithm"),
+            Self::InvalidSignerKey => f.write_str("invalid signer public key"),
+            Self::ContentTypeMismatch => f.write_str("contentType attribute mismatch"),
+            Self::MessageDigestMismatch => f.write_str("messageDigest attribute mismatch"),
+            Self::BadSignature => f.write_str("bad signature"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e.kind())
+    }
+}
+
+/// The result of a successful [`verify_detached`].
+#[derive(Clone, Debug)]
+pub struct VerifiedInfo {
+    content_type: Vec<u8>,
+    signature_algorithm: AlgorithmIdentifier,
+    message_digest: digest::Digest,
+}
+
+impl VerifiedInfo {
+    /// The contents of the signed content type's OID, without the tag and
+    /// length; e.g. `[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01]`
+    /// for `id-data`.
+    pub fn content_type(&self) -> &[u8] {
+        &self.content_type
+    }
+
+    /// The algorithm the content was digested with.
+    #[inline]
+    pub fn digest_algorithm(&self) -> &'static digest::Algorithm {
+        self.message_digest.algorithm()
+    }
+
+    /// The signature algorithm. A signature algorithm of `rsaEncryption`,
+    /// which is what OpenSSL uses for PKCS#1 1.5 signatures, is reported as
+    /// the PKCS#1 1.5 algorithm with the content's digest algorithm.
+    pub fn signature_algorithm(&self) -> AlgorithmIdentifier {
+        self.signature_algorithm
+    }
+
+    /// The digest of the content.
+    pub fn message_digest(&self) -> &digest::Digest {
+        &self.message_digest
+    }
+}
+
+/// Verifies the DER-encoded CMS `ContentInfo` `signed_data` over `content`,
+/// with the signer public key `trusted_signer_spki`, a DER-encoded
+/// `SubjectPublicKeyInfo`.
+///
+/// The signature is verified before `content` is read, so `content` isn't
+/// read at all if the signature is invalid. `content` is read to its end.
+/// A byte slice can be passed as `content` since `&[u8]` implements `Read`.
+pub fn verify_detached<R: Read>(
+    signed_data: &[u8],
+    mut content: R,
+    trusted_signer_spki: &[u8],
+) -> Result<VerifiedInfo, Error> {
+    let (public_key_alg, public_key) = parse_spki(untrusted::Input::from(trusted_signer_spki))?;
+    let signed_data = untrusted::Input::from(signed_data).read_all(Error::Malformed, |input| {
+        der::nested(
+            input,
+            der::Tag::Sequence,
+            Error::Malformed,
+            parse_content_info,
+        )
+    })?;
+    let signer_info = signed_data.signer_info;
+
+    let attributes = parse_signed_attributes(signer_info.signed_attributes)?;
+    if attributes.content_type != signed_data.content_type.as_slice_less_safe() {
+        return Err(Error::ContentTypeMismatch);
+    }
+    if attributes.message_digest.len() != signer_info.digest_alg.output_len() {
+        return Err(Error::MessageDigestMismatch);
+    }
+
+    let signature_alg = match signer_info.signature_alg {
+        AlgorithmIdentifier::RsaEncryption => rsa_pkcs1_for_digest(signer_info.digest_alg),
+        alg => alg,
+    };
+    let verification_alg = verification_alg(signature_alg, public_key_alg)?;
+    let signed_attributes = reencode_signed_attributes(signer_info.signed_attributes)?;
+    verification_alg
+        .verify(
+            public_key,
+            untrusted::Input::from(&signed_attributes),
+            signer_info.signature,
+        )
+        .map_err(|error::Unspecified| Error::BadSignature)?;
+
+    let message_digest = digest_content(signer_info.digest_alg, &mut content)?;
+    if message_digest.as_ref() != attributes.message_digest {
+        return Err(Error::MessageDigestMismatch);
+    }
+
+    Ok(VerifiedInfo {
+        content_type: signed_data.content_type.as_slice_less_safe().to_vec(),
+        signature_algorithm: signature_alg,
+        message_digest,
+    })
+}
+
+// 1.2.840.113549.1.7.2
+const SIGNED_DATA_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+// 1.2.840.113549.1.9.3
+const CONTENT_TYPE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x03];
+
+// 1.2.840.113549.1.9.4
+const MESSAGE_DIGEST_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+
+struct SignedData<'a> {
+    content_type: untrusted::Input<'a>,
+    signer_info: SignerInfo<'a>,
+}
+
+struct SignerInfo<'a> {
+    digest_alg: &'static digest::Algorithm,
+    // The contents of the `[0] IMPLICIT` tagged `signedAttrs`.
+    signed_attributes: untrusted::Input<'a>,
+    signature_alg: AlgorithmIdentifier,
+    signature: untrusted::Input<'a>,
+}
+
+// ContentInfo ::= SEQUENCE {
+//     contentType ContentType,
+//     content [0] EXPLICIT ANY DEFINED BY contentType }
+fn parse_content_info<'a>(input: &mut untrusted::Reader<'a>) -> Result<SignedData<'a>, Error> {
+    let content_type = der::expect_tag_and_get_value(input, der::Tag::OID)
+        .map_err(|error::Unspecified| Error::Malformed)?;
+    if content_type.as_slice_less_safe() != SIGNED_DATA_OID {
+        return Err(Error::Malformed);
+    }
+    der::nested(
+        input,
+        der::Tag::ContextSpecificConstructed0,
+        Error::Malformed,
+        |input| {
+            der::nested(
+                input,
+                der::Tag::Sequence,
+                Error::Malformed,
+                parse_signed_data,
+            )
+        },
+    )
+}
+
+// SignedData ::= SEQUENCE {
+//     version CMSVersion,
+//     digestAlgorithms DigestAlgorithmIdentifiers,
+//     encapContentInfo EncapsulatedContentInfo,
+//     certificates [0] IMPLICIT CertificateSet OPTIONAL,
+//     crls [1] IMPLICIT RevocationInfoChoices OPTIONAL,
+//     signerInfos SignerInfos }
+fn parse_signed_data<'a>(input: &mut untrusted::Reader<'a>) -> Result<SignedData<'a>, Error> {
+    let _version =
+        der::small_nonnegative_integer(input).map_err(|error::Unspecified| Error::Malformed)?;
+    let _digest_algorithms = der::expect_tag_and_get_value(input, der::Tag::Set)
+        .map_err(|error::Unspecified| Error::Malformed)?;
+
+    // EncapsulatedContentInfo ::= SEQUENCE {
+    //     eContentType ContentType,
+    //     eContent [0] EXPLICIT OCTET STRING OPTIONAL }
+    let content_type = der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+        let content_type = der::expect_tag_and_get_value(input, der::Tag::OID)
+            .map_err(|error::Unspecified| Error::Malformed)?;
+        if !input.at_end() {
+            let _ = der::expect_tag_and_get_value(input, der::Tag::ContextSpecificConstructed0)
+                .map_err(|error::Unspecified| Error::Malformed)?;
+            return Err(Error::EmbeddedContent);
+        }
+        Ok(content_type)
+    })?;
+
+    for tag in [
+        der::Tag::ContextSpecificConstructed0,
+        der::Tag::ContextSpecificConstructed1,
+    ] {
+        if input.peek(tag.into()) {
+            let _ = der::expect_tag_and_get_value(input, tag)
+                .map_err(|error::Unspecified| Error::Malformed)?;
+        }
+    }
+
+    let signer_info = der::nested(input, der::Tag::Set, Error::Malformed, |input| {
+        let signer_info = der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+            parse_signer_info(input)
+        })?;
+        if !input.at_end() {
+            let _ = input.read_bytes_to_end();
+            return Err(Error::UnsupportedSignerCount);
+        }
+        Ok(signer_info)
+    })?;
+
+    Ok(SignedData {
+        content_type,
+        signer_info,
+    })
+}
+
+// SignerInfo ::= SEQUENCE {
+//     version CMSVersion,
+//     sid SignerIdentifier,
+//     digestAlgorithm DigestAlgorithmIdentifier,
+//     signedAttrs [0] IMPLICIT SignedAttributes OPTIONAL,
+//     signatureAlgorithm SignatureAlgorithmIdentifier,
+//     signature SignatureValue,
+//     unsignedAttrs [1] IMPLICIT UnsignedAttributes OPTIONAL }
+fn parse_signer_info<'a>(input: &mut untrusted::Reader<'a>) -> Result<SignerInfo<'a>, Error> {
+    let _version =
+        der::small_nonnegative_integer(input).map_err(|error::Unspecified| Error::Malformed)?;
+    // The signer is identified by the public key the caller supplies.
+    let _sid = der::read_tag_and_get_value(input).map_err(|error::Unspecified| Error::Malformed)?;
+
+    let digest_alg = parse_hash_algorithm(input)
+        .map_err(|_: AlgorithmIdentifierError| Error::Malformed)?
+        .map_err(|_: AlgorithmIdentifierError| Error::UnsupportedAlgorithm)?;
+    if digest_alg == &digest::SHA1_FOR_LEGACY_USE_ONLY {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+
+    let signed_attributes = if input.peek(der::Tag::ContextSpecificConstructed0.into()) {
+        Some(
+            der::expect_tag_and_get_value(input, der::Tag::ContextSpecificConstructed0)
+                .map_err(|error::Unspecified| Error::Malformed)?,
+        )
+    } else {
+        None
+    };
+
+    let signature_alg = der::nested(input, der::Tag::Sequence, Error::Malformed, |value| {
+        AlgorithmIdentifier::from_der_value(value.read_bytes_to_end()).map_err(|e| match e {
+            AlgorithmIdentifierError::Malformed => Error::Malformed,
+            _ => Error::UnsupportedAlgorithm,
+        })
+    })?;
+
+    let signature = der::expect_tag_and_get_value(input, der::Tag::OctetString)
+        .map_err(|error::Unspecified| Error::Malformed)?;
+
+    if input.peek(der::Tag::ContextSpecificConstructed1.into()) {
+        let _unsigned_attributes =
+            der::expect_tag_and_get_value(input, der::Tag::ContextSpecificConstructed1)
+                .map_err(|error::Unspecified| Error::Malformed)?;
+    }
+
+    Ok(SignerInfo {
+        digest_alg,
+        signed_attributes: signed_attributes.ok_or(Error::MissingSignedAttributes)?,
+        signature_alg,
+        signature,
+    })
+}
+
+struct SignedAttributes<'a> {
+    content_type: &'a [u8],
+    message_digest: &'a [u8],
+}
+
+// SignedAttributes ::= SET SIZE (1..MAX) OF Attribute
+//
+// Attribute ::= SEQUENCE {
+//     attrType OBJECT IDENTIFIER,
+//     attrValues SET OF AttributeValue }
+//
+// The `contentType` and `messageDigest` attributes must each occur exactly
+// once, with exactly one value (RFC 5652 Sections 11.1 and 11.2).
+fn parse_signed_attributes(signed_attributes: untrusted::Input) -> Result<SignedAttributes, Error> {
+    let mut content_type = None;
+    let mut message_digest = None;
+
+    signed_attributes.read_all(Error::Malformed, |input| {
+        loop {
+            der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+                let attr_type = der::expect_tag_and_get_value(input, der::Tag::OID)
+                    .map_err(|error::Unspecified| Error::Malformed)?;
+                let (found, value_tag) = match attr_type.as_slice_less_safe() {
+                    CONTENT_TYPE_OID => (&mut content_type, der::Tag::OID),
+                    MESSAGE_DIGEST_OID => (&mut message_digest, der::Tag::OctetString),
+                    _ => {
+                        let _ = der::expect_tag_and_get_value(input, der::Tag::Set)
+                            .map_err(|error::Unspecified| Error::Malformed)?;
+                        return Ok(());
+                    }
+                };
+                if found.is_some() {
+                    return Err(Error::Malformed);
+                }
+                let value = der::nested(input, der::Tag::Set, Error::Malformed, |values| {
+                    der::expect_tag_and_get_value(values, value_tag)
+                        .map_err(|error::Unspecified| Error::Malformed)
+                })?;
+                *found = Some(value.as_slice_less_safe());
+                Ok(())
+            })?;
+            if input.at_end() {
+                break;
+            }
+        }
+        Ok(())
+    })?;
+
+    match (content_type, message_digest) {
+        (Some(content_type), Some(message_digest)) => Ok(SignedAttributes {
+            content_type,
+            message_digest,
+        }),
+        _ => Err(Error::MissingSignedAttributes),
+    }
+}
+
+// Returns the DER encoding of the signed attributes with an explicit
+// `SET OF` tag, which is what is signed (RFC 5652 Section 5.4). The elements
+// of a DER `SET OF` are sorted by their encodings (X.690 Section 11.6).
+fn reencode_signed_attributes(signed_attributes: untrusted::Input) -> Result<Vec<u8>, Error> {
+    let mut attributes = signed_attributes.read_all(Error::Malformed, |input| {
+        let mut attributes = Vec::new();
+        while !input.at_end() {
+            let (attribute, _) = input
+                .read_partial(der::read_tag_and_get_value)
+                .map_err(|error::Unspecified| Error::Malformed)?;
+            attributes.push(attribute.as_slice_less_safe());
+        }
+        Ok(attributes)
+    })?;
+    attributes.sort_unstable();
+
+    let encoded = der_writer::write_all(der::Tag::Set, &|output| {
+        for attribute in &attributes {
+            output.write_bytes(attribute);
+        }
+    })
+    .map_err(|_| Error::Malformed)?;
+    Ok(encoded.into())
+}
+
+// SubjectPublicKeyInfo ::= SEQUENCE {
+//     algorithm AlgorithmIdentifier,
+//     subjectPublicKey BIT STRING }
+fn parse_spki(spki: untrusted::Input) -> Result<(AlgorithmIdentifier, untrusted::Input), Error> {
+    spki.read_all(Error::InvalidSignerKey, |input| {
+        der::nested(
+            input,
+            der::Tag::Sequence,
+            Error::InvalidSignerKey,
+            |input| {
+                let alg = der::nested(
+                    input,
+                    der::Tag::Sequence,
+                    Error::InvalidSignerKey,
+                    |value| {
+                        AlgorithmIdentifier::from_der_value(value.read_bytes_to_end()).map_err(
+                            |e| match e {
+                                AlgorithmIdentifierError::UnsupportedAlgorithm => {
+                                    Error::UnsupportedAlgorithm
+                                }
+                                _ => Error::InvalidSignerKey,
+                            },
+                        )
+                    },
+                )?;
+                let public_key = der::bit_string_with_no_unused_bits(input)
+                    .map_err(|error::Unspecified| Error::InvalidSignerKey)?;
+                Ok((alg, public_key))
+            },
+        )
+    })
+}
+
+fn rsa_pkcs1_for_digest(digest_alg: &'static digest::Algorithm) -> AlgorithmIdentifier {
+    if digest_alg == &digest::SHA256 {
+        AlgorithmIdentifier::RsaPkcs1Sha256
+    } else if digest_alg == &digest::SHA384 {
+        AlgorithmIdentifier::RsaPkcs1Sha384
+    } else {
+        debug_assert!(digest_alg == &digest::SHA512);
+        AlgorithmIdentifier::RsaPkcs1Sha512
+    }
+}
+
+fn verification_alg(
+    signature_alg: AlgorithmIdentifier,
+    public_key_alg: AlgorithmIdentifier,
+) -> Result<&'static dyn VerificationAlgorithm, Error> {
+    if signature_alg == AlgorithmIdentifier::RsaPkcs1Sha1ForLegacyUseOnly {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+    signature_alg
+        .to_verification_params(public_key_alg)
+        .map_err(|e| match e {
+            AlgorithmIdentifierError::IncompatiblePublicKey => Error::InvalidSignerKey,
+            _ => Error::UnsupportedAlgorithm,
+        })
+}
+
+fn digest_content(
+    digest_alg: &'static digest::Algorithm,
+    content: &mut dyn Read,
+) -> Result<digest::Digest, Error> {
+    let mut ctx = digest::Context::new(digest_alg);
+    let mut buf = [0u8; 4096];
+    loop {
+        match content.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => ctx.update(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(ctx.finish())
+}
@@ -70,5 +70,104 @@ pub(crate) fn features() -> Features {
     Features(())
 }
 
-pub mod arm;
-pub mod intel;
+/// The CPU features detected by *ring*, for diagnostics.
+///
+/// *ring* chooses its implementations based on these, e.g. AES-GCM uses the
+/// AES and carry-less multiplication instructions when they're available.
+/// A feature is reported as available only if both the CPU and the operating
+/// system support it; e.g. on x86-64, AVX2 is unavailable if the operating
+/// system doesn't save the YMM registers.
+///
+/// ```
+/// let features = ring::cpu::CpuFeatures::current();
+/// # #[cfg(target_arch = "x86_64")]
+/// println!("AES-NI: {}", features.has_aes_ni());
+/// println!("{:?}", features);
+/// ```
+#[derive(Clone, Copy)]
+pub struct CpuFeatures {
+    features: Features,
+}
+
+impl CpuFeatures {
+    /// Detects the features of the CPU, if they haven't been detected yet.
+    #[inline]
+    pub fn current() -> Self {
+        Self {
+            features: features(),
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl CpuFeatures {
+    /// Whether the AES-NI instructions are available.
+    #[inline]
+    pub fn has_aes_ni(&self) -> bool {
+        intel::AES.available(self.features)
+    }
+
+    /// Whether the SHA extensions (SHA-NI) are available.
+    #[inline]
+    pub fn has_sha_ni(&self) -> bool {
+        intel::SHA.available(self.features)
+    }
+
+    /// Whether AVX2 is available.
+    #[inline]
+    pub fn has_avx2(&self) -> bool {
+        intel::AVX2.available(self.features)
+    }
+
+    /// Whether the PCLMULQDQ instruction is available.
+    #[inline]
+    pub fn has_pclmulqdq(&self) -> bool {
+        intel::PCLMULQDQ.available(self.features)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl CpuFeatures {
+    /// Whether NEON (Advanced SIMD) is available.
+    #[inline]
+    pub fn has_neon(&self) -> bool {
+        arm::NEON.available(self.features)
+    }
+
+    /// Whether the PMULL instructions are available.
+    #[inline]
+    pub fn has_pmull(&self) -> bool {
+        arm::PMULL.available(self.features)
+    }
+
+    /// Whether the SHA-256 instructions are available.
+    #[inline]
+    pub fn has_sha2(&self) -> bool {
+        arm::SHA256.available(self.features)
+    }
+}
+
+impl core::fmt::Debug for CpuFeatures {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mut s = f.debug_struct("CpuFeatures");
+        #[cfg(target_arch = "x86_64")]
+        {
+            let _ = s
+                .field("aes_ni", &self.has_aes_ni())
+                .field("sha_ni", &self.has_sha_ni())
+                .field("avx2", &self.has_avx2())
+                .field("pclmulqdq", &self.has_pclmulqdq());
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            let _ = s
+                .field("neon", &self.has_neon())
+                .field("pmull", &self.has_pmull())
+                .field("sha2", &self.has_sha2());
+        }
+        s.finish()
+    }
+}
+
+pub(crate) mod arm;
+pub(crate) mod intel;
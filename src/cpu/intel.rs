@@ -93,6 +93,20 @@ pub(crate) const AVX: Feature = Feature {
     mask: 1 << 28,
 };
 
+// Only reported by `CpuFeatures`; *ring* doesn't use AVX2.
+#[cfg(target_arch = "x86_64")]
+pub(crate) const AVX2: Feature = Feature {
+    word: 2,
+    mask: 1 << 5,
+};
+
+// Only reported by `CpuFeatures`; *ring* doesn't use the SHA extensions.
+#[cfg(target_arch = "x86_64")]
+pub(crate) const SHA: Feature = Feature {
+    word: 2,
+    mask: 1 << 29,
+};
+
 #[cfg(all(target_arch = "x86_64", test))]
 mod x86_64_tests {
     use super::*;
@@ -0,0 +1,151 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Deterministic derivation of key pairs from a master secret.
+//!
+//! Each function derives a private key from a master [`hkdf::Prk`] and a
+//! `label` using HKDF-Expand, so any number of independent keys can be
+//! derived from one secret and recreated from it later. The same master and
+//! label always give the same key, and different labels or key types give
+//! independent keys.
+//!
+//! The constructions are frozen: they will never change for a given
+//! function, in any version of *ring*, and they're pinned by test vectors.
+//! If a construction ever has to change, it will be with a new function and
+//! a new version in the `info` prefix.
+//!
+//! | Function               | HKDF-Expand `info`                        | Output   |
+//! |------------------------|-------------------------------------------|----------|
+//! | `derive_ed25519`       | `"ring-derive-v1 ed25519"` ‖ label        | 32 bytes |
+//! | `derive_x25519_static` | `"ring-derive-v1 x25519"` ‖ label         | 32 bytes |
+//! | `derive_ecdsa_p256`    | `"ring-derive-v1 ecdsa-p256"` ‖ label ‖ i | 32 bytes |
+//!
+//! The prefixes are ASCII without a terminator; none is a prefix of another,
+//! so the `info` of one key type never equals that of another. `i` is a
+//! single byte.
+//!
+//! * The Ed25519 output is the RFC 8032 seed, which is hashed and clamped as
+//!   usual, so it's the same as `Ed25519KeyPair::from_seed_unchecked()` of
+//!   the output.
+//! * The X25519 output is the RFC 7748 private key, which is clamped when
+//!   it's used.
+//! * The P-256 output is the big-endian private scalar. Candidates for
+//!   `i = 0, 1, ...` are tested until one is in [1, n - 1], as in
+//!   "Key Pair Generation by Testing Candidates" in the NSA Suite B
+//!   Implementer's Guide, which `EcdsaKeyPair::generate_pkcs8()` also uses.
+//!   Rejection sampling doesn't bias the result, unlike a modular reduction.
+//!   n is larger than 2<sup>256</sup> - 2<sup>224</sup>, so a candidate is
+//!   rejected with probability less than 2<sup>-32</sup>, and in practice
+//!   the first candidate is always used. Derivation fails if all 256
+//!   candidates are rejected.
+//!
+//! The security of the derived keys depends on the master key having full
+//! entropy, e.g. being the output of `hkdf::Salt::extract()` over a secret
+//! with enough entropy. Labels don't need to be secret, but each key must
+//! have its own label.
+//!
+//! ```
+//! use ring::{derive, hkdf, signature::KeyPair};
+//!
+//! let master = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"master secret");
+//! let key_pair = derive::derive_ed25519(&master, b"service-a/signing")?;
+//! let again = derive::derive_ed25519(&master, b"service-a/signing")?;
+//! assert_eq!(key_pair.public_key().as_ref(), again.public_key().as_ref());
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+
+use crate::{
+    agreement, cpu,
+    ec::{self, CurveID},
+    error, hkdf, rand, signature,
+};
+
+const ED25519_INFO_PREFIX: &[u8] = b"ring-derive-v1 ed25519";
+const X25519_INFO_PREFIX: &[u8] = b"ring-derive-v1 x25519";
+const ECDSA_P256_INFO_PREFIX: &[u8] = b"ring-derive-v1 ecdsa-p256";
+
+const SEED_LEN: usize = 32;
+
+/// Derives the Ed25519 key pair for `label` from `master`.
+pub fn derive_ed25519(
+    master: &hkdf::Prk,
+    label: &[u8],
+) -> Result<signature::Ed25519KeyPair, error::Unspecified> {
+    let seed = expand(master, &[ED25519_INFO_PREFIX, label])?;
+    signature::Ed25519KeyPair::from_seed_unchecked(&seed).map_err(|_| error::Unspecified)
+}
+
+/// Derives the X25519 private key for `label` from `master`.
+///
+/// *ring* doesn't have static agreement keys; an `EphemeralPrivateKey` can be
+/// used for only one agreement. Since the derivation is deterministic, a
+/// static key is used by deriving it again for each agreement; the public key
+/// is the same every time.
+pub fn derive_x25519_static(
+    master: &hkdf::Prk,
+    label: &[u8],
+) -> Result<agreement::EphemeralPrivateKey, error::Unspecified> {
+    let alg = &agreement::X25519;
+    let private_key = expand(master, &[X25519_INFO_PREFIX, label])?;
+    let seed = ec::Seed::from_bytes(
+        alg.curve,
+        untrusted::Input::from(&private_key),
+        cpu::features(),
+    )?;
+    Ok(agreement::EphemeralPrivateKey::from_seed(alg, seed))
+}
+
+/// Derives the P-256 ECDSA key pair for `label` from `master`.
+///
+/// `alg` is the signing algorithm of the key pair, and must be
+/// `ECDSA_P256_SHA256_ASN1_SIGNING` or `ECDSA_P256_SHA256_FIXED_SIGNING`;
+/// the derived key doesn't depend on which. As with
+/// `EcdsaKeyPair::from_pkcs8()`, `rng` is only used to protect the nonces of
+/// signatures; it doesn't affect the derived key.
+pub fn derive_ecdsa_p256(
+    master: &hkdf::Prk,
+    label: &[u8],
+    alg: &'static signature::EcdsaSigningAlgorithm,
+    rng: &dyn rand::SecureRandom,
+) -> Result<signature::EcdsaKeyPair, error::Unspecified> {
+    let curve = alg.curve();
+    if curve.id != CurveID::P256 {
+        return Err(error::Unspecified);
+    }
+    let cpu_features = cpu::features();
+    for i in 0..=u8::MAX {
+        let candidate = expand(master, &[ECDSA_P256_INFO_PREFIX, label, &[i]])?;
+        let seed =
+            match ec::Seed::from_bytes(curve, untrusted::Input::from(&candidate), cpu_features) {
+                Ok(seed) => seed,
+                Err(error::Unspecified) => continue,
+            };
+        return signature::EcdsaKeyPair::from_seed(alg, seed, rng).map_err(|_| error::Unspecified);
+    }
+    Err(error::Unspecified)
+}
+
+fn expand(master: &hkdf::Prk, info: &[&[u8]]) -> Result<[u8; SEED_LEN], error::Unspecified> {
+    let mut out = [0; SEED_LEN];
+    master.expand(info, Len(SEED_LEN))?.fill(&mut out)?;
+    Ok(out)
+}
+
+struct Len(usize);
+
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
@@ -24,24 +24,32 @@
 // The goal for this implementation is to drive the overhead as close to zero
 // as possible.
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use crate::{
-    c, cpu, debug,
+    c, constant_time, cpu, debug,
     endian::{ArrayEncoding, BigEndian},
-    polyfill,
+    error, polyfill, tagged,
 };
 use core::num::Wrapping;
 
+#[cfg(feature = "external-digest")]
+mod external;
 mod sha1;
 mod sha2;
+mod sm3;
+
+#[cfg(feature = "external-digest")]
+pub use external::{ExternalBlockFn, ExternalFinalizeFn, ExternalState, EXTERNAL_STATE_WORDS};
 
 #[derive(Clone)]
 pub(crate) struct BlockContext {
     state: State,
 
-    // Note that SHA-512 has a 128-bit input bit counter, but this
-    // implementation only supports up to 2^64-1 input bits for all algorithms,
-    // so a 64-bit counter is more than sufficient.
-    completed_data_blocks: u64,
+    // SHA-384 and SHA-512 have a 128-bit input bit counter, so a 64-bit
+    // block counter isn't sufficient for them.
+    completed_data_blocks: u128,
 
     /// The context's algorithm.
     pub algorithm: &'static Algorithm,
@@ -65,18 +73,154 @@ impl BlockContext {
         assert_eq!(num_blocks * self.algorithm.block_len, input.len());
 
         if num_blocks > 0 {
-            let _cpu_features = self.cpu_features;
-            unsafe {
-                (self.algorithm.block_data_order)(&mut self.state, input.as_ptr(), num_blocks);
+            match self.algorithm.block_function {
+                BlockFunction::MerkleDamgard {
+                    block_data_order, ..
+                } => {
+                    let _cpu_features = self.cpu_features;
+                    unsafe {
+                        block_data_order(&mut self.state, input.as_ptr(), num_blocks);
+                    }
+                }
+                #[cfg(feature = "external-digest")]
+                BlockFunction::External(functions) => {
+                    (functions.block_fn)(unsafe { &mut self.state.external }, input);
+                }
             }
             self.completed_data_blocks = self
                 .completed_data_blocks
-                .checked_add(polyfill::u64_from_usize(num_blocks))
+                .checked_add(u128::from(polyfill::u64_from_usize(num_blocks)))
                 .unwrap();
         }
     }
 
+    /// Writes the big-endian encoding of the current chaining value into
+    /// `out` and returns it.
+    fn chaining_value<'o>(
+        &self,
+        format_output: fn(input: State) -> Output,
+        out: &'o mut [u8; MAX_CHAINING_LEN],
+    ) -> &'o [u8] {
+        let output = format_output(self.state);
+        let as64 = unsafe { &output.as64 };
+        let out = &mut out[..self.algorithm.chaining_len];
+        out.copy_from_slice(&as64.as_byte_array()[..out.len()]);
+        out
+    }
+
+    /// Whether `self` and `other`, which must use the same algorithm, are in
+    /// the same state. The comparison is done in constant time with respect
+    /// to the states.
+    pub(crate) fn state_equals(&self, other: &Self) -> bool {
+        assert_eq!(self.algorithm, other.algorithm);
+        if self.completed_data_blocks != other.completed_data_blocks {
+            return false;
+        }
+        match self.algorithm.block_function {
+            BlockFunction::MerkleDamgard { format_output, .. } => {
+                let mut a = [0; MAX_CHAINING_LEN];
+                let mut b = [0; MAX_CHAINING_LEN];
+                let a = self.chaining_value(format_output, &mut a);
+                let b = other.chaining_value(format_output, &mut b);
+                constant_time::verify_slices_are_equal(a, b).is_ok()
+            }
+            #[cfg(feature = "external-digest")]
+            BlockFunction::External(_) => {
+                let (a, b) = unsafe { (&self.state.external, &other.state.external) };
+                let a = external::state_bytes(a);
+                let b = external::state_bytes(b);
+                constant_time::verify_slices_are_equal(&a, &b).is_ok()
+            }
+        }
+    }
+
+    /// The length, in bits, of the input processed so far followed by
+    /// `num_pending` more bytes.
+    ///
+    /// Fails if the length doesn't fit in the algorithm's length field.
+    fn input_bits(&self, num_pending: usize) -> Result<u128, error::Unspecified> {
+        let bits = self
+            .completed_data_blocks
+            .checked_mul(u128::from(polyfill::u64_from_usize(
+                self.algorithm.block_len,
+            )))
+            .and_then(|bytes| bytes.checked_add(u128::from(polyfill::u64_from_usize(num_pending))))
+            .and_then(|bytes| bytes.checked_mul(8))
+            .ok_or(error::Unspecified)?;
+        let len_bits = u32::try_from(self.algorithm.len_len * 8).unwrap();
+        if bits.checked_shr(len_bits).unwrap_or(0) != 0 {
+            return Err(error::Unspecified);
+        }
+        Ok(bits)
+    }
+
     pub(crate) fn finish(mut self, pending: &mut [u8], num_pending: usize) -> Digest {
+        let value = match self.algorithm.block_function {
+            BlockFunction::MerkleDamgard {
+                block_data_order,
+                format_output,
+            } => {
+                self.process_final_blocks(block_data_order, pending, num_pending);
+                format_output(self.state)
+            }
+            #[cfg(feature = "external-digest")]
+            BlockFunction::External(functions) => {
+                let mut out = [0u8; MAX_OUTPUT_LEN];
+                self.finalize_external(functions, pending, num_pending, &mut out);
+                Output::from_bytes(out)
+            }
+        };
+        Digest {
+            algorithm: self.algorithm,
+            value,
+        }
+    }
+
+    /// Like `finish`, except the digest value is written to `out`, which must
+    /// be exactly `self.algorithm.output_len` bytes long.
+    pub(crate) fn finish_into(mut self, pending: &mut [u8], num_pending: usize, out: &mut [u8]) {
+        assert_eq!(out.len(), self.algorithm.output_len);
+        match self.algorithm.block_function {
+            BlockFunction::MerkleDamgard {
+                block_data_order,
+                format_output,
+            } => {
+                self.process_final_blocks(block_data_order, pending, num_pending);
+                let output = format_output(self.state);
+                let as64 = unsafe { &output.as64 };
+                out.copy_from_slice(&as64.as_byte_array()[..out.len()]);
+            }
+            #[cfg(feature = "external-digest")]
+            BlockFunction::External(functions) => {
+                self.finalize_external(functions, pending, num_pending, out);
+            }
+        }
+    }
+
+    #[cfg(feature = "external-digest")]
+    fn finalize_external(
+        &mut self,
+        functions: external::Functions,
+        pending: &[u8],
+        num_pending: usize,
+        out: &mut [u8],
+    ) {
+        let _ = self.input_bits(num_pending).unwrap();
+        (functions.finalize)(
+            unsafe { &mut self.state.external },
+            &pending[..num_pending],
+            &mut out[..self.algorithm.output_len],
+        );
+    }
+
+    /// Pads the `num_pending` bytes of input at the start of `pending`, which
+    /// must be `block_len` bytes long, and processes the final block(s).
+    fn process_final_blocks(
+        &mut self,
+        block_data_order: unsafe extern "C" fn(state: &mut State, data: *const u8, num: c::size_t),
+        pending: &mut [u8],
+        num_pending: usize,
+    ) {
         let block_len = self.algorithm.block_len;
         assert_eq!(pending.len(), block_len);
         assert!(num_pending <= pending.len());
@@ -88,33 +232,23 @@ impl BlockContext {
         if padding_pos > block_len - self.algorithm.len_len {
             pending[padding_pos..block_len].fill(0);
             unsafe {
-                (self.algorithm.block_data_order)(&mut self.state, pending.as_ptr(), 1);
+                block_data_order(&mut self.state, pending.as_ptr(), 1);
             }
             // We don't increase |self.completed_data_blocks| because the
             // padding isn't data, and so it isn't included in the data length.
             padding_pos = 0;
         }
 
-        pending[padding_pos..(block_len - 8)].fill(0);
+        let len_len = self.algorithm.len_len;
+        pending[padding_pos..(block_len - len_len)].fill(0);
 
         // Output the length, in bits, in big endian order.
-        let completed_data_bits = self
-            .completed_data_blocks
-            .checked_mul(polyfill::u64_from_usize(block_len))
-            .unwrap()
-            .checked_add(polyfill::u64_from_usize(num_pending))
-            .unwrap()
-            .checked_mul(8)
-            .unwrap();
-        pending[(block_len - 8)..block_len].copy_from_slice(&u64::to_be_bytes(completed_data_bits));
+        let completed_data_bits = self.input_bits(num_pending).unwrap();
+        pending[(block_len - len_len)..block_len]
+            .copy_from_slice(&u128::to_be_bytes(completed_data_bits)[(16 - len_len)..]);
 
         unsafe {
-            (self.algorithm.block_data_order)(&mut self.state, pending.as_ptr(), 1);
-        }
-
-        Digest {
-            algorithm: self.algorithm,
-            value: (self.algorithm.format_output)(self.state),
+            block_data_order(&mut self.state, pending.as_ptr(), 1);
         }
     }
 }
@@ -163,12 +297,49 @@ impl Context {
     }
 
     /// Updates the digest with all the data in `data`.
+    ///
+    /// # Panics
+    ///
+    /// `update` panics if the total input would exceed the algorithm's
+    /// maximum input length: 2<sup>64</sup> - 1 bits for SHA-1 and SHA-256,
+    /// and 2<sup>128</sup> - 1 bits for the SHA-512-based algorithms. Use
+    /// [`Self::try_update`] to handle that case.
     pub fn update(&mut self, data: &[u8]) {
+        self.try_update(data).unwrap()
+    }
+
+    /// Updates the digest with all the data in `bufs`, in order.
+    ///
+    /// This is equivalent to calling `update` with each buffer in turn. The
+    /// buffers don't need to be aligned to the algorithm's block length;
+    /// partial blocks are carried over from one buffer to the next, so
+    /// buffers can be passed as they are, e.g. straight from a vectored read.
+    ///
+    /// # Panics
+    ///
+    /// Like `update`, `update_vectored` panics if the total input would exceed
+    /// the algorithm's maximum input length.
+    #[cfg(feature = "std")]
+    pub fn update_vectored(&mut self, bufs: &[std::io::IoSlice]) {
+        for buf in bufs {
+            self.update(buf);
+        }
+    }
+
+    /// Like [`Self::update`], except it fails, without changing the context,
+    /// if the total input would exceed the algorithm's maximum input length.
+    pub fn try_update(&mut self, data: &[u8]) -> Result<(), error::Unspecified> {
+        let total_pending = self
+            .num_pending
+            .checked_add(data.len())
+            .ok_or(error::Unspecified)?;
+        let _ = self.block.input_bits(total_pending)?;
+
         let block_len = self.block.algorithm.block_len;
         if data.len() < block_len - self.num_pending {
             self.pending[self.num_pending..(self.num_pending + data.len())].copy_from_slice(data);
             self.num_pending += data.len();
-            return;
+            return Ok(());
         }
 
         let mut remaining = data;
@@ -188,6 +359,7 @@ impl Context {
                 .copy_from_slice(&remaining[(remaining.len() - num_to_save_for_later)..]);
             self.num_pending = num_to_save_for_later;
         }
+        Ok(())
     }
 
     /// Finalizes the digest calculation and returns the digest value.
@@ -200,6 +372,59 @@ impl Context {
             .finish(&mut self.pending[..block_len], self.num_pending)
     }
 
+    /// Finalizes the digest calculation and writes the digest value to the
+    /// start of `out`, returning its length.
+    ///
+    /// Fails, without writing anything, if `out` is shorter than the
+    /// algorithm's output length.
+    #[inline]
+    pub fn finish_into(mut self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        let out = out
+            .get_mut(..self.algorithm().output_len)
+            .ok_or(error::Unspecified)?;
+        let block_len = self.block.algorithm.block_len;
+        self.block
+            .finish_into(&mut self.pending[..block_len], self.num_pending, out);
+        Ok(out.len())
+    }
+
+    /// Like [`Self::finish_into`], but finalizes a copy of the context so
+    /// that more data can be added to it afterwards, e.g. to compute the hash
+    /// of a transcript so far.
+    #[inline]
+    pub fn finish_copy_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        self.clone().finish_into(out)
+    }
+
+    /// Resets the context to its initial state, discarding all the data
+    /// added to it, as though it had just been constructed with
+    /// `Context::new(self.algorithm())`.
+    ///
+    /// A `Context` never allocates, so this is no cheaper than constructing
+    /// a new one; it's useful when the context is kept in a place that can
+    /// only be borrowed mutably, e.g. a field of a struct that computes one
+    /// digest after another.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::new(self.algorithm());
+    }
+
+    /// Like [`Self::reset`], except the context switches to `algorithm`.
+    ///
+    /// Fails, without changing the context, if the context can't hold the
+    /// state of `algorithm`. Every `Context` has room for the state of every
+    /// algorithm in this module, so this never fails for them.
+    pub fn reset_with_algorithm(
+        &mut self,
+        algorithm: &'static Algorithm,
+    ) -> Result<(), error::Unspecified> {
+        if algorithm.block_len > self.pending.len() {
+            return Err(error::Unspecified);
+        }
+        *self = Self::new(algorithm);
+        Ok(())
+    }
+
     /// The algorithm that this context is using.
     #[inline(always)]
     pub fn algorithm(&self) -> &'static Algorithm {
@@ -228,6 +453,22 @@ pub fn digest(algorithm: &'static Algorithm, data: &[u8]) -> Digest {
     ctx.finish()
 }
 
+/// Returns the digest of all the data read from `r`, until the end of the
+/// stream.
+///
+/// The data is read into an internal 64 KiB buffer, so `r` doesn't need to be
+/// buffered. Reads interrupted with `std::io::ErrorKind::Interrupted` are
+/// retried; any other error is returned as-is.
+#[cfg(feature = "std")]
+pub fn digest_reader(
+    algorithm: &'static Algorithm,
+    r: impl std::io::Read,
+) -> std::io::Result<Digest> {
+    let mut ctx = Context::new(algorithm);
+    crate::io::read::for_each_chunk(r, |chunk| ctx.update(chunk))?;
+    Ok(ctx.finish())
+}
+
 /// A calculated digest value.
 ///
 /// Use [`Self::as_ref`] to get the value as a `&[u8]`.
@@ -243,6 +484,15 @@ impl Digest {
     pub fn algorithm(&self) -> &'static Algorithm {
         self.algorithm
     }
+
+    /// Encodes the digest value along with the id of its algorithm, as
+    /// described in [`crate::tagged`].
+    ///
+    /// Fails if the algorithm has no id, which is only possible for
+    /// algorithms constructed with the `external-digest` feature.
+    pub fn to_tagged_bytes(&self) -> Result<tagged::TaggedBytes, error::Unspecified> {
+        tagged::TaggedBytes::from_digest(self)
+    }
 }
 
 impl AsRef<[u8]> for Digest {
@@ -269,14 +519,26 @@ pub struct Algorithm {
     /// The length of the length in the padding.
     len_len: usize,
 
-    block_data_order: unsafe extern "C" fn(state: &mut State, data: *const u8, num: c::size_t),
-    format_output: fn(input: State) -> Output,
+    block_function: BlockFunction,
 
     initial_state: State,
 
     id: AlgorithmID,
 }
 
+/// How an algorithm processes blocks and produces its output.
+#[derive(Clone, Copy)]
+enum BlockFunction {
+    /// The Merkle–Damgård construction with the padding of FIPS 180-4.
+    MerkleDamgard {
+        block_data_order: unsafe extern "C" fn(state: &mut State, data: *const u8, num: c::size_t),
+        format_output: fn(input: State) -> Output,
+    },
+
+    #[cfg(feature = "external-digest")]
+    External(external::Functions),
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum AlgorithmID {
     SHA1,
@@ -284,11 +546,18 @@ enum AlgorithmID {
     SHA384,
     SHA512,
     SHA512_256,
+    SM3,
+    #[cfg(feature = "external-digest")]
+    External,
 }
 
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        match self.id {
+            #[cfg(feature = "external-digest")]
+            AlgorithmID::External => core::ptr::eq(self, other),
+            _ => self.id == other.id,
+        }
     }
 }
 
@@ -327,8 +596,10 @@ pub static SHA1_FOR_LEGACY_USE_ONLY: Algorithm = Algorithm {
     chaining_len: sha1::CHAINING_LEN,
     block_len: sha1::BLOCK_LEN,
     len_len: 64 / 8,
-    block_data_order: sha1::block_data_order,
-    format_output: sha256_format_output,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sha1::block_data_order,
+        format_output: sha256_format_output,
+    },
     initial_state: State {
         as32: [
             Wrapping(0x67452301u32),
@@ -346,14 +617,23 @@ pub static SHA1_FOR_LEGACY_USE_ONLY: Algorithm = Algorithm {
 
 /// SHA-256 as specified in [FIPS 180-4].
 ///
+/// When interoperability doesn't require SHA-256, consider [`SHA512_256`]
+/// instead. On 64-bit CPUs without SHA-256 instructions it is usually
+/// considerably faster than SHA-256, since it processes twice as much input
+/// per block using 64-bit arithmetic. On CPUs that do have SHA-256
+/// instructions (e.g. x86-64 with the SHA extensions, or AArch64), SHA-256 is
+/// faster; `cargo bench --bench digest` in the `bench` directory compares them.
+///
 /// [FIPS 180-4]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
 pub static SHA256: Algorithm = Algorithm {
     output_len: SHA256_OUTPUT_LEN,
     chaining_len: SHA256_OUTPUT_LEN,
     block_len: 512 / 8,
     len_len: 64 / 8,
-    block_data_order: sha2::sha256_block_data_order,
-    format_output: sha256_format_output,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sha2::sha256_block_data_order,
+        format_output: sha256_format_output,
+    },
     initial_state: State {
         as32: [
             Wrapping(0x6a09e667u32),
@@ -377,8 +657,10 @@ pub static SHA384: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: sha2::sha512_block_data_order,
-    format_output: sha512_format_output,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sha2::sha512_block_data_order,
+        format_output: sha512_format_output,
+    },
     initial_state: State {
         as64: [
             Wrapping(0xcbbb9d5dc1059ed8),
@@ -402,8 +684,10 @@ pub static SHA512: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: sha2::sha512_block_data_order,
-    format_output: sha512_format_output,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sha2::sha512_block_data_order,
+        format_output: sha512_format_output,
+    },
     initial_state: State {
         as64: [
             Wrapping(0x6a09e667f3bcc908),
@@ -431,8 +715,10 @@ pub static SHA512_256: Algorithm = Algorithm {
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
     len_len: SHA512_LEN_LEN,
-    block_data_order: sha2::sha512_block_data_order,
-    format_output: sha512_format_output,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sha2::sha512_block_data_order,
+        format_output: sha512_format_output,
+    },
     initial_state: State {
         as64: [
             Wrapping(0x22312194fc2bf72c),
@@ -448,11 +734,43 @@ pub static SHA512_256: Algorithm = Algorithm {
     id: AlgorithmID::SHA512_256,
 };
 
+/// SM3 as specified in [GB/T 32905-2016].
+///
+/// SM3 is only provided for interoperability with systems that require the
+/// Chinese national standard algorithms, e.g. for SM2 signatures.
+///
+/// [GB/T 32905-2016]: https://datatracker.ietf.org/doc/html/draft-sca-cfrg-sm3-02
+pub static SM3: Algorithm = Algorithm {
+    output_len: SM3_OUTPUT_LEN,
+    chaining_len: sm3::CHAINING_LEN,
+    block_len: sm3::BLOCK_LEN,
+    len_len: 64 / 8,
+    block_function: BlockFunction::MerkleDamgard {
+        block_data_order: sm3::block_data_order,
+        format_output: sha256_format_output,
+    },
+    initial_state: State {
+        as32: [
+            Wrapping(0x7380166fu32),
+            Wrapping(0x4914b2b9u32),
+            Wrapping(0x172442d7u32),
+            Wrapping(0xda8a0600u32),
+            Wrapping(0xa96f30bcu32),
+            Wrapping(0x163138aau32),
+            Wrapping(0xe38dee4du32),
+            Wrapping(0xb0fb0e4eu32),
+        ],
+    },
+    id: AlgorithmID::SM3,
+};
+
 #[derive(Clone, Copy)] // XXX: Why do we need to be `Copy`?
 #[repr(C)]
 union State {
     as64: [Wrapping<u64>; sha2::CHAINING_WORDS],
     as32: [Wrapping<u32>; sha2::CHAINING_WORDS],
+    #[cfg(feature = "external-digest")]
+    external: ExternalState,
 }
 
 #[derive(Clone, Copy)]
@@ -462,6 +780,17 @@ union Output {
     as32: [BigEndian<u32>; 256 / 8 / core::mem::size_of::<BigEndian<u32>>()],
 }
 
+#[cfg(feature = "external-digest")]
+impl Output {
+    fn from_bytes(bytes: [u8; MAX_OUTPUT_LEN]) -> Self {
+        let mut as64 = [BigEndian::from(0u64); MAX_OUTPUT_LEN / 8];
+        for (word, chunk) in as64.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = BigEndian::from(<[u8; 8]>::try_from(chunk).unwrap());
+        }
+        Self { as64 }
+    }
+}
+
 /// The maximum block length ([`Algorithm::block_len()`]) of all the algorithms
 /// in this module.
 pub const MAX_BLOCK_LEN: usize = 1024 / 8;
@@ -503,12 +832,24 @@ pub const SHA512_OUTPUT_LEN: usize = 512 / 8;
 /// The length of the output of SHA-512/256, in bytes.
 pub const SHA512_256_OUTPUT_LEN: usize = 256 / 8;
 
+/// The length of the output of SM3, in bytes.
+pub const SM3_OUTPUT_LEN: usize = sm3::OUTPUT_LEN;
+
 /// The length of a block for SHA-512-based algorithms, in bytes.
 const SHA512_BLOCK_LEN: usize = 1024 / 8;
 
 /// The length of the length field for SHA-512-based algorithms, in bytes.
 const SHA512_LEN_LEN: usize = 128 / 8;
 
+inventory! { Digest, Always;
+    SHA1_FOR_LEGACY_USE_ONLY,
+    SHA256,
+    SHA384,
+    SHA512,
+    SHA512_256,
+    SM3,
+}
+
 #[cfg(test)]
 mod tests {
     mod max_input {
@@ -538,6 +879,11 @@ mod tests {
                     fn too_long_input_test_byte() {
                         super::too_long_input_test_byte(&digest::$algorithm_name);
                     }
+
+                    #[test]
+                    fn try_update_at_boundary_test() {
+                        super::try_update_at_boundary_test(&digest::$algorithm_name);
+                    }
                 }
             };
         }
@@ -564,16 +910,53 @@ mod tests {
             let _ = context.finish(); // should panic
         }
 
+        fn try_update_at_boundary_test(alg: &'static digest::Algorithm) {
+            let mut context = nearly_full_context(alg);
+            assert!(context.try_update(&vec![0u8; alg.block_len]).is_err());
+            assert!(context.try_update(&vec![0u8; alg.block_len - 2]).is_ok());
+            let expected = context.clone().finish();
+
+            // The last byte that fits.
+            assert!(context.try_update(&[0]).is_ok());
+            let full = context.clone();
+
+            // Failures leave the context unchanged.
+            assert!(context.try_update(&[0]).is_err());
+            assert!(context.try_update(&vec![0u8; alg.block_len * 2]).is_err());
+            assert!(context.try_update(&[]).is_ok());
+            assert_eq!(context.finish().as_ref(), full.clone().finish().as_ref());
+            assert_ne!(full.finish().as_ref(), expected.as_ref());
+
+            // Accounting is done on the total length, not per call.
+            let mut context = context_with_completed_blocks(alg, max_blocks(alg) - 2);
+            let next_input = vec![0u8; alg.block_len];
+            assert!(context.try_update(&next_input).is_ok());
+            assert!(context
+                .try_update(&next_input[..(alg.block_len - 1)])
+                .is_ok());
+            assert!(context.try_update(&[0]).is_err());
+        }
+
+        // The number of blocks in the maximum input length plus one bit;
+        // i.e. 2^64 bits for SHA-1 and SHA-256 and 2^128 bits for the
+        // SHA-512-based algorithms.
+        fn max_blocks(alg: &'static digest::Algorithm) -> u128 {
+            let max_bytes = 1u128 << (alg.len_len * 8 - 3);
+            max_bytes / u128::from(polyfill::u64_from_usize(alg.block_len))
+        }
+
         fn nearly_full_context(alg: &'static digest::Algorithm) -> digest::Context {
-            // All implementations currently support up to 2^64-1 bits
-            // of input; according to the spec, SHA-384 and SHA-512
-            // support up to 2^128-1, but that's not implemented yet.
-            let max_bytes = 1u64 << (64 - 3);
-            let max_blocks = max_bytes / polyfill::u64_from_usize(alg.block_len);
+            context_with_completed_blocks(alg, max_blocks(alg) - 1)
+        }
+
+        fn context_with_completed_blocks(
+            alg: &'static digest::Algorithm,
+            completed_data_blocks: u128,
+        ) -> digest::Context {
             digest::Context {
                 block: digest::BlockContext {
                     state: alg.initial_state,
-                    completed_data_blocks: max_blocks - 1,
+                    completed_data_blocks,
                     algorithm: alg,
                     cpu_features: crate::cpu::features(),
                 },
@@ -586,5 +969,7 @@ mod tests {
         max_input_tests!(SHA256);
         max_input_tests!(SHA384);
         max_input_tests!(SHA512);
+        max_input_tests!(SHA512_256);
+        max_input_tests!(SM3);
     }
 }
@@ -0,0 +1,110 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Digest algorithms implemented outside of *ring*.
+
+use super::{Algorithm, AlgorithmID, BlockFunction, State, MAX_BLOCK_LEN, MAX_OUTPUT_LEN};
+
+/// The number of 64-bit words in an [`ExternalState`].
+pub const EXTERNAL_STATE_WORDS: usize = 32;
+
+/// The state of an external digest algorithm.
+///
+/// *ring* never interprets the state; it is only passed to the algorithm's
+/// functions, and copied when a context is cloned.
+pub type ExternalState = [u64; EXTERNAL_STATE_WORDS];
+
+/// Processes whole blocks of input for an external digest algorithm; see
+/// [`Algorithm::new_external()`].
+pub type ExternalBlockFn = fn(state: &mut ExternalState, blocks: &[u8]);
+
+/// Pads the remaining input and writes the digest for an external digest
+/// algorithm; see [`Algorithm::new_external()`].
+pub type ExternalFinalizeFn = fn(state: &mut ExternalState, remainder: &[u8], out: &mut [u8]);
+
+pub(super) fn state_bytes(state: &ExternalState) -> [u8; 8 * EXTERNAL_STATE_WORDS] {
+    let mut bytes = [0; 8 * EXTERNAL_STATE_WORDS];
+    for (bytes, word) in bytes.chunks_exact_mut(8).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct Functions {
+    pub(super) block_fn: ExternalBlockFn,
+    pub(super) finalize: ExternalFinalizeFn,
+}
+
+impl Algorithm {
+    /// Constructs a digest algorithm from functions implemented outside of
+    /// *ring*, e.g. for a national standard that *ring* doesn't implement.
+    ///
+    /// The result can be used anywhere a built-in algorithm can, as long as
+    /// it has a `'static` lifetime: declare it as a `static`, or leak it with
+    /// `Box::leak` when the parameters are only known at runtime. Use
+    /// [`crate::hmac::Algorithm::from_digest_algorithm()`] to use it with
+    /// `hmac`, `hkdf`, and `pbkdf2`. External algorithms have no id in
+    /// [`crate::tagged`] and aren't listed in [`crate::inventory`].
+    ///
+    /// Each context starts with the state `init`. As input is added, `block_fn`
+    /// is called with whole blocks; `blocks` is always a non-empty multiple of
+    /// `block_len` bytes long. When the context is finished, `finalize` is
+    /// called with the remaining input, which is shorter than `block_len`
+    /// (and may be empty), and must do the algorithm's padding and write the
+    /// digest to `out`, which is exactly `output_len` bytes long. The state is
+    /// discarded afterwards.
+    ///
+    /// # Contract for `block_fn` and `finalize`
+    ///
+    /// Both are safe functions, so an incorrect implementation can't cause
+    /// undefined behavior in *ring*. The security of everything built on the
+    /// algorithm does depend on them, though, so they must:
+    ///
+    /// * be deterministic, depending only on their arguments;
+    /// * not panic; a panic would unwind out of whichever *ring* function
+    ///   was processing the input;
+    /// * take time and access memory independently of the values in `state`,
+    ///   `blocks` and `remainder`, since those include HMAC keys, PBKDF2
+    ///   passwords and other secrets;
+    /// * not keep or leak copies of their inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics (at compile time, when used in a constant context) if
+    /// `output_len` is zero or larger than [`super::MAX_OUTPUT_LEN`],
+    /// `block_len` is larger than [`super::MAX_BLOCK_LEN`], or `block_len` is
+    /// smaller than `output_len`; HMAC requires a digest to fit in a block.
+    pub const fn new_external(
+        output_len: usize,
+        block_len: usize,
+        init: ExternalState,
+        block_fn: ExternalBlockFn,
+        finalize: ExternalFinalizeFn,
+    ) -> Self {
+        assert!(output_len > 0 && output_len <= MAX_OUTPUT_LEN);
+        assert!(block_len >= output_len && block_len <= MAX_BLOCK_LEN);
+        Self {
+            output_len,
+            chaining_len: output_len,
+            block_len,
+            // External algorithms do their own padding; this only limits the
+            // total input length.
+            len_len: 128 / 8,
+            block_function: BlockFunction::External(Functions { block_fn, finalize }),
+            initial_state: State { external: init },
+            id: AlgorithmID::External,
+        }
+    }
+}
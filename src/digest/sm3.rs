@@ -0,0 +1,131 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SM3, as specified in GB/T 32905-2016.
+
+use super::sha2::{ch, maj, Word};
+use crate::c;
+use core::num::Wrapping;
+
+pub const BLOCK_LEN: usize = 512 / 8;
+pub const CHAINING_LEN: usize = 256 / 8;
+pub const OUTPUT_LEN: usize = 256 / 8;
+const CHAINING_WORDS: usize = CHAINING_LEN / 4;
+
+type W32 = Wrapping<u32>;
+
+type State = [W32; CHAINING_WORDS];
+const ROUNDS: usize = 64;
+
+// GB/T 32905-2016 4.3
+#[inline(always)]
+fn parity(x: W32, y: W32, z: W32) -> W32 {
+    x ^ y ^ z
+}
+
+// GB/T 32905-2016 4.4
+#[inline(always)]
+fn p0(x: W32) -> W32 {
+    x ^ rotl(x, 9) ^ rotl(x, 17)
+}
+
+#[inline(always)]
+fn p1(x: W32) -> W32 {
+    x ^ rotl(x, 15) ^ rotl(x, 23)
+}
+
+pub(super) extern "C" fn block_data_order(
+    state: &mut super::State,
+    data: *const u8,
+    num: c::size_t,
+) {
+    let state = unsafe { &mut state.as32 };
+    let state: &mut State = (&mut state[..CHAINING_WORDS]).try_into().unwrap();
+    let data = data.cast::<[<W32 as Word>::InputBytes; 16]>();
+    let blocks = unsafe { core::slice::from_raw_parts(data, num) };
+    *state = block_data_order_(*state, blocks)
+}
+
+#[inline]
+#[rustfmt::skip]
+fn block_data_order_(mut V: State, B: &[[<W32 as Word>::InputBytes; 16]]) -> State {
+    for B in B {
+        // GB/T 32905-2016 5.3.2
+        let mut W: [W32; ROUNDS + 4] = [W32::ZERO; ROUNDS + 4];
+        for j in 0..16 {
+            W[j] = W32::from_be_bytes(B[j]);
+        }
+        for j in 16..(ROUNDS + 4) {
+            W[j] = p1(W[j - 16] ^ W[j - 9] ^ rotl(W[j - 3], 15))
+                ^ rotl(W[j - 13], 7)
+                ^ W[j - 6];
+        }
+
+        // GB/T 32905-2016 5.3.3, with the constants and functions of 4.2 and
+        // 4.3.
+        let [a, b, c, d, e, f, g, h] = V;
+        let s = (a, b, c, d, e, f, g, h);
+        let s = compress(s, &W, 0, Wrapping(0x79cc4519), parity, parity);
+        let (a, b, c, d, e, f, g, h) =
+            compress(s, &W, 16, Wrapping(0x7a879d8a), maj, ch);
+
+        V[0] ^= a;
+        V[1] ^= b;
+        V[2] ^= c;
+        V[3] ^= d;
+        V[4] ^= e;
+        V[5] ^= f;
+        V[6] ^= g;
+        V[7] ^= h;
+    }
+
+    V
+}
+
+type Registers = (W32, W32, W32, W32, W32, W32, W32, W32);
+
+#[inline(always)]
+fn compress(
+    (mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h): Registers,
+    W: &[W32; ROUNDS + 4],
+    start: usize,
+    T: W32,
+    ff: impl Fn(W32, W32, W32) -> W32,
+    gg: impl Fn(W32, W32, W32) -> W32,
+) -> Registers {
+    let end = if start == 0 { 16 } else { ROUNDS };
+    for j in start..end {
+        #[allow(clippy::cast_possible_truncation)]
+        let t_j = rotl(T, (j % 32) as u32);
+        let a12 = rotl(a, 12);
+        let ss1 = rotl(a12 + e + t_j, 7);
+        let ss2 = ss1 ^ a12;
+        let tt1 = ff(a, b, c) + d + ss2 + (W[j] ^ W[j + 4]);
+        let tt2 = gg(e, f, g) + h + ss1 + W[j];
+        d = c;
+        c = rotl(b, 9);
+        b = a;
+        a = tt1;
+        h = g;
+        g = rotl(f, 19);
+        f = e;
+        e = p0(tt2);
+    }
+    (a, b, c, d, e, f, g, h)
+}
+
+#[inline(always)]
+fn rotl(x: W32, n: u32) -> W32 {
+    Wrapping(x.0.rotate_left(n))
+}
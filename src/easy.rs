@@ -0,0 +1,147 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! One-shot functions for the simplest uses of hashing, HMAC, and AEADs.
+//!
+//! Each is a thin wrapper around the corresponding API in [`digest`],
+//! [`hmac`], or [`aead`]; use those directly for incremental processing,
+//! other algorithms, or control over nonces.
+//!
+//! ```
+//! use ring::{aead, easy};
+//!
+//! let digest = easy::sha256(b"hello, world");
+//!
+//! let tag = easy::hmac_sha256(b"key", b"message");
+//! assert!(easy::hmac_verify_sha256(b"key", b"message", &tag));
+//!
+//! # #[cfg(feature = "alloc")]
+//! # {
+//! let key = [0x42; 32];
+//! let sealed = easy::aead_seal(&aead::AES_256_GCM, &key, b"header", b"secret")?;
+//! let opened = easy::aead_open(&aead::AES_256_GCM, &key, b"header", &sealed)?;
+//! assert_eq!(opened, b"secret");
+//! # }
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [`digest`]: crate::digest
+//! [`hmac`]: crate::hmac
+//! [`aead`]: crate::aead
+
+use crate::{digest, hmac};
+
+#[cfg(feature = "alloc")]
+use crate::{aead, error, policy, rand};
+
+/// Returns the SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    digest_array(&digest::SHA256, data)
+}
+
+/// Returns the SHA-512 digest of `data`.
+pub fn sha512(data: &[u8]) -> [u8; 64] {
+    digest_array(&digest::SHA512, data)
+}
+
+fn digest_array<const N: usize>(algorithm: &'static digest::Algorithm, data: &[u8]) -> [u8; N] {
+    let digest = digest::digest(algorithm, data);
+    digest.as_ref().try_into().unwrap()
+}
+
+/// Returns the HMAC-SHA256 tag of `data` with `key`.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data).as_ref().try_into().unwrap()
+}
+
+/// Returns whether `tag` is the HMAC-SHA256 tag of `data` with `key`.
+///
+/// The comparison is done in constant time.
+pub fn hmac_verify_sha256(key: &[u8], data: &[u8], tag: &[u8]) -> bool {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::verify(&key, data, tag).is_ok()
+}
+
+/// Encrypts and authenticates `plaintext` and authenticates `aad`, with a
+/// nonce generated randomly by [`rand::SystemRandom`].
+///
+/// Returns the nonce, followed by the ciphertext and the tag.
+///
+/// # Limits on the number of invocations
+///
+/// The AEADs in *ring* have 96-bit nonces. If a nonce ever repeats for a key,
+/// both confidentiality and authenticity are lost for the messages that
+/// share it. With random nonces, NIST SP 800-38D Section 8.3 limits the
+/// number of messages sealed with a single key to 2<sup>32</sup>, which keeps
+/// the probability of a collision below 2<sup>-32</sup>; the same bound is
+/// appropriate for ChaCha20-Poly1305. Rotate keys well before that. Use
+/// [`aead::SealingKey`] with a counter-based [`aead::NonceSequence`] if more
+/// messages are needed.
+///
+/// Fails if a default [`crate::policy::Policy`] is installed that doesn't
+/// allow `algorithm`.
+#[cfg(feature = "alloc")]
+pub fn aead_seal(
+    algorithm: &'static aead::Algorithm,
+    key: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<alloc::vec::Vec<u8>, error::Unspecified> {
+    policy::check_default_aead(algorithm)?;
+    let key = aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, key)?);
+
+    let nonce: [u8; aead::NONCE_LEN] = rand::generate(&rand::SystemRandom::new())?.expose();
+
+    let mut out =
+        alloc::vec::Vec::with_capacity(aead::NONCE_LEN + plaintext.len() + algorithm.tag_len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(plaintext);
+    let tag = key.seal_in_place_separate_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(aad),
+        &mut out[aead::NONCE_LEN..],
+    )?;
+    out.extend_from_slice(tag.as_ref());
+    Ok(out)
+}
+
+/// Authenticates and decrypts the output of [`aead_seal`], and authenticates
+/// `aad`. Returns the plaintext.
+///
+/// Fails if a default [`crate::policy::Policy`] is installed that doesn't
+/// allow `algorithm`.
+#[cfg(feature = "alloc")]
+pub fn aead_open(
+    algorithm: &'static aead::Algorithm,
+    key: &[u8],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<alloc::vec::Vec<u8>, error::Unspecified> {
+    policy::check_default_aead(algorithm)?;
+    let key = aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, key)?);
+
+    if sealed.len() < aead::NONCE_LEN {
+        return Err(error::Unspecified);
+    }
+    let (nonce, ciphertext_and_tag) = sealed.split_at(aead::NONCE_LEN);
+    let nonce = aead::Nonce::try_assume_unique_for_key(nonce)?;
+
+    let mut in_out = alloc::vec::Vec::from(ciphertext_and_tag);
+    let plaintext_len = key
+        .open_in_place(nonce, aead::Aad::from(aad), &mut in_out)?
+        .len();
+    in_out.truncate(plaintext_len);
+    Ok(in_out)
+}
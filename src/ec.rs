@@ -12,9 +12,9 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use crate::{error, rand};
+use crate::{c, error, rand};
 
-pub use self::keys::{KeyPair, PublicKey, Seed};
+pub use self::keys::{KeyPair, PublicKey, Seed, PUBLIC_KEY_MAX_LEN};
 
 pub struct Curve {
     pub public_key_len: usize,
@@ -38,7 +38,9 @@ derive_debug_via_id!(Curve);
 pub enum CurveID {
     Curve25519,
     P256,
+    P256Compact,
     P384,
+    BrainpoolP384r1,
 }
 
 const ELEM_MAX_BITS: usize = 384;
@@ -57,6 +59,52 @@ const SEED_MAX_BYTES: usize = ELEM_MAX_BYTES;
 /// longer.
 pub const PKCS8_DOCUMENT_MAX_LEN: usize = 40 + SCALAR_MAX_BYTES + keys::PUBLIC_KEY_MAX_LEN;
 
+/// How constant-time fixed-base scalar multiplication looks up multiples of the
+/// base point in a precomputed table.
+///
+/// The table is divided into parts, one per window of the scalar. Either way,
+/// every entry of the part being read is read, so the memory accessed never
+/// depends on the secret scalar; the part itself is chosen by the position of
+/// the window, which is public.
+///
+/// | Curve      | Target                 | Lookup                                            |
+/// |------------|------------------------|---------------------------------------------------|
+/// | P-256      | x86_64, AArch64        | `ecp_nistz256_select_w7`: 64 entries of 37 parts  |
+/// | P-256      | Others                 | `fiat_p256_select_point_affine`: 15 entries of 2 parts |
+/// | P-384      | All                    | `p384_point_select_w5`: 16 entries of a table computed for each multiplication; always the whole table |
+/// | brainpoolP384r1 | All                | `point_select` in `bp384`: 15 entries of a table computed for each multiplication; always the whole table |
+/// | Curve25519 | x86_64, AArch64        | `table_select`: 8 entries of 32 parts             |
+/// | Curve25519 | Others (`OPENSSL_SMALL`) | All 15 entries of `k25519SmallPrecomp`; always the whole table |
+///
+/// Variable-time multiplications, which are only done with public scalars
+/// during signature verification, don't use this.
+#[derive(Clone, Copy)]
+pub(crate) enum TableScan {
+    /// Read every entry of the part of the table for the current window.
+    Window,
+
+    /// Read every entry of the whole table for every lookup.
+    Full,
+}
+
+impl TableScan {
+    /// `Full` when the `ct-table-scan` feature is enabled, `Window` otherwise.
+    pub(crate) const DEFAULT: Self = if cfg!(feature = "ct-table-scan") {
+        Self::Full
+    } else {
+        Self::Window
+    };
+}
+
+impl From<TableScan> for c::int {
+    fn from(scan: TableScan) -> Self {
+        match scan {
+            TableScan::Window => 0,
+            TableScan::Full => 1,
+        }
+    }
+}
+
 pub mod curve25519;
 mod keys;
 pub mod suite_b;
@@ -16,8 +16,9 @@
 
 use super::{super::ops::*, eddsa_digest, ED25519_PUBLIC_KEY_LEN};
 use crate::{
-    cpu, digest, error,
+    cpu, digest, error, fingerprint,
     io::der,
+    locked::Secret,
     pkcs8, rand,
     signature::{self, KeyPair as SigningKeyPair},
 };
@@ -25,10 +26,10 @@ use crate::{
 /// An Ed25519 key pair, for signing.
 pub struct Ed25519KeyPair {
     // RFC 8032 Section 5.1.6 calls this *s*.
-    private_scalar: Scalar,
+    private_scalar: Secret<Scalar>,
 
     // RFC 8032 Section 5.1.6 calls this *prefix*.
-    private_prefix: Prefix,
+    private_prefix: Secret<Prefix>,
 
     // RFC 8032 Section 5.1.5 calls this *A*.
     public_key: PublicKey,
@@ -37,6 +38,10 @@ pub struct Ed25519KeyPair {
 derive_debug_via_field!(Ed25519KeyPair, stringify!(Ed25519KeyPair), public_key);
 
 impl Ed25519KeyPair {
+    /// The length of the public key, which is always exactly
+    /// `ED25519_PUBLIC_KEY_LEN` bytes.
+    pub const MAX_PUBLIC_KEY_LEN: usize = ED25519_PUBLIC_KEY_LEN;
+
     /// Generates a new key pair and returns the key pair serialized as a
     /// PKCS#8 document.
     ///
@@ -49,6 +54,11 @@ impl Ed25519KeyPair {
     pub fn generate_pkcs8(
         rng: &dyn rand::SecureRandom,
     ) -> Result<pkcs8::Document, error::Unspecified> {
+        trace_span!(
+            crate::trace::KEY_GENERATION,
+            algorithm = "Ed25519",
+            key_bits = 256
+        );
         let seed: [u8; SEED_LEN] = rand::generate(rng)?.expose();
         let key_pair = Self::from_seed_(&seed);
         Ok(pkcs8::wrap_key(
@@ -58,6 +68,25 @@ impl Ed25519KeyPair {
         ))
     }
 
+    /// Generates a new key pair without serializing it.
+    ///
+    /// This is for keys that are used and then thrown away, such as a key
+    /// that signs a single attestation token. The key pair is the same as the
+    /// one `Ed25519KeyPair::from_pkcs8()` would construct from the document
+    /// that `Ed25519KeyPair::generate_pkcs8()` would generate with the same
+    /// random seed, but there's no PKCS#8 document to encode and parse, and
+    /// the public key is only computed once. Use `generate_pkcs8()` instead
+    /// for keys that need to be stored.
+    pub fn generate(rng: &dyn rand::SecureRandom) -> Result<Self, error::Unspecified> {
+        trace_span!(
+            crate::trace::KEY_GENERATION,
+            algorithm = "Ed25519",
+            key_bits = 256
+        );
+        let seed: [u8; SEED_LEN] = rand::generate(rng)?.expose();
+        Ok(Self::from_seed_(&seed))
+    }
+
     /// Constructs an Ed25519 key pair by parsing an unencrypted PKCS#8 v2
     /// Ed25519 private key.
     ///
@@ -125,7 +154,10 @@ impl Ed25519KeyPair {
     /// Constructs an Ed25519 key pair from the private key seed `seed` and its
     /// public key `public_key`.
     ///
-    /// It is recommended to use `Ed25519KeyPair::from_pkcs8()` instead.
+    /// It is recommended to use `Ed25519KeyPair::from_pkcs8()` instead. This
+    /// is intended for cases where the seed and the public key are stored
+    /// separately, such as keys exported from some HSMs. `&[u8; 32]` arrays
+    /// may be passed directly.
     ///
     /// The private and public keys will be verified to be consistent with each
     /// other. This helps avoid misuse of the key (e.g. accidentally swapping
@@ -179,14 +211,70 @@ impl Ed25519KeyPair {
         let a = ExtPoint::from_scalarmult_base_consttime(&private_scalar, cpu::features());
 
         Self {
-            private_scalar,
-            private_prefix: private_prefix.try_into().unwrap(),
+            private_scalar: Secret::new(private_scalar),
+            private_prefix: Secret::new(private_prefix.try_into().unwrap()),
             public_key: PublicKey(a.into_encoded_point()),
         }
     }
 
     /// Returns the signature of the message `msg`.
     pub fn sign(&self, msg: &[u8]) -> signature::Signature {
+        self.sign_(&[], msg)
+    }
+
+    /// Returns the signature of the message `msg`, using a nonce derived from
+    /// fresh randomness from `rng` in addition to the private key and `msg`.
+    ///
+    /// Deterministic signing, as done by `sign()`, is vulnerable to fault
+    /// attacks that induce an error while signing the same message twice.
+    /// Here the nonce is derived from `prefix || Z || msg` instead of
+    /// `prefix || msg`, where `Z` is 32 random bytes. The result is an
+    /// ordinary Ed25519 signature that is verified exactly like any other,
+    /// but signing the same message twice gives different signatures.
+    pub fn sign_hedged(
+        &self,
+        rng: &dyn rand::SecureRandom,
+        msg: &[u8],
+    ) -> Result<signature::Signature, error::Unspecified> {
+        let z: [u8; 32] = rand::generate(rng)?.expose();
+        Ok(self.sign_(&z, msg))
+    }
+
+    /// Returns the signatures of `messages`, in the same order.
+    ///
+    /// Each signature is the same as the one `sign()` would return for the
+    /// same message. The messages are currently signed one after another;
+    /// *ring* has no multi-buffer SHA-512 implementation that could hash
+    /// several messages at once.
+    #[cfg(feature = "alloc")]
+    pub fn sign_batch(&self, messages: &[&[u8]]) -> alloc::vec::Vec<signature::Signature> {
+        trace_span!(
+            crate::trace::SIGN,
+            algorithm = "Ed25519",
+            message_count = messages.len()
+        );
+        let cpu_features = cpu::features();
+        messages
+            .iter()
+            .map(|msg| self.sign_with(cpu_features, &[], msg))
+            .collect()
+    }
+
+    fn sign_(&self, nonce_randomness: &[u8], msg: &[u8]) -> signature::Signature {
+        trace_span!(
+            crate::trace::SIGN,
+            algorithm = "Ed25519",
+            message_len = msg.len()
+        );
+        self.sign_with(cpu::features(), nonce_randomness, msg)
+    }
+
+    fn sign_with(
+        &self,
+        cpu_features: cpu::Features,
+        nonce_randomness: &[u8],
+        msg: &[u8],
+    ) -> signature::Signature {
         signature::Signature::new(|signature_bytes| {
             prefixed_extern! {
                 fn x25519_sc_muladd(
@@ -201,13 +289,14 @@ impl Ed25519KeyPair {
             let (signature_r, signature_s) = signature_bytes.split_at_mut(ELEM_LEN);
             let nonce = {
                 let mut ctx = digest::Context::new(&digest::SHA512);
-                ctx.update(&self.private_prefix);
+                ctx.update(&*self.private_prefix);
+                ctx.update(nonce_randomness);
                 ctx.update(msg);
                 ctx.finish()
             };
             let nonce = Scalar::from_sha512_digest_reduced(nonce);
 
-            let r = ExtPoint::from_scalarmult_base_consttime(&nonce, cpu::features());
+            let r = ExtPoint::from_scalarmult_base_consttime(&nonce, cpu_features);
             signature_r.copy_from_slice(&r.into_encoded_point());
             let hram_digest = eddsa_digest(signature_r, self.public_key.as_ref(), msg);
             let hram = Scalar::from_sha512_digest_reduced(hram_digest);
@@ -223,6 +312,49 @@ impl Ed25519KeyPair {
             SIGNATURE_LEN
         })
     }
+
+    /// Writes the public key into the start of `out`, returning its length.
+    ///
+    /// Fails if `out` is shorter than [`Self::MAX_PUBLIC_KEY_LEN`].
+    pub fn public_key_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        signature::public_key_into(self.public_key.as_ref(), out)
+    }
+
+    /// Returns the fingerprint of the key pair's public key, computed over
+    /// its `SubjectPublicKeyInfo` as described in [`fingerprint`].
+    pub fn spki_fingerprint(&self) -> fingerprint::PublicKeyFingerprint {
+        fingerprint::spki_fingerprint(PKCS8_TEMPLATE.alg_id_value_(), self.public_key.as_ref())
+    }
+
+    /// Whether the private key is held in locked memory, which is never
+    /// written to swap.
+    ///
+    /// The key pair holds the private scalar and prefix derived from the
+    /// seed, not the seed itself. This is `false` if they couldn't be locked,
+    /// e.g. because `RLIMIT_MEMLOCK` was exhausted; the key is still usable.
+    #[cfg(feature = "locked-memory")]
+    pub fn was_locked(&self) -> bool {
+        self.private_scalar.was_locked() && self.private_prefix.was_locked()
+    }
+}
+
+/// Generates a new Ed25519 key pair, signs `msg` with it, and returns the
+/// public key and the signature, discarding the private key.
+///
+/// This is equivalent to `Ed25519KeyPair::generate(rng)` followed by
+/// `sign(msg)`. The result can be verified with [`ed25519_verify`].
+///
+/// [`ed25519_verify`]: crate::signature::ed25519_verify
+pub fn ed25519_keygen_sign(
+    rng: &dyn rand::SecureRandom,
+    msg: &[u8],
+) -> Result<([u8; ED25519_PUBLIC_KEY_LEN], [u8; SIGNATURE_LEN]), error::Unspecified> {
+    let key_pair = Ed25519KeyPair::generate(rng)?;
+    let signature = key_pair.sign(msg);
+    Ok((
+        key_pair.public_key.0,
+        signature.as_ref().try_into().unwrap(),
+    ))
 }
 
 impl signature::KeyPair for Ed25519KeyPair {
@@ -265,7 +397,7 @@ const SIGNATURE_LEN: usize = ELEM_LEN + SCALAR_LEN;
 type Seed = [u8; SEED_LEN];
 const SEED_LEN: usize = 32;
 
-static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ed25519_pkcs8_v2_template.der"),
     alg_id_range: core::ops::Range { start: 7, end: 12 },
     curve_id_index: 0,
@@ -15,7 +15,13 @@
 //! EdDSA Signatures.
 
 use super::{super::ops::*, eddsa_digest};
-use crate::{error, sealed, signature};
+use crate::{
+    error::{self, VerifyFailure},
+    sealed, signature,
+};
+
+#[cfg(feature = "ct-everything")]
+use crate::{c, constant_time, cpu};
 
 /// Parameters for EdDSA signing and verification.
 pub struct EdDSAParameters;
@@ -40,39 +46,28 @@ impl signature::VerificationAlgorithm for EdDSAParameters {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified> {
-        let public_key: &[u8; ELEM_LEN] = public_key.as_slice_less_safe().try_into()?;
-        let (signature_r, signature_s) = signature.read_all(error::Unspecified, |input| {
-            let signature_r: &[u8; ELEM_LEN] = input
-                .read_bytes(ELEM_LEN)?
-                .as_slice_less_safe()
-                .try_into()?;
-            let signature_s: &[u8; SCALAR_LEN] = input
-                .read_bytes(SCALAR_LEN)?
-                .as_slice_less_safe()
-                .try_into()?;
-            Ok((signature_r, signature_s))
-        })?;
-
-        let signature_s = Scalar::from_bytes_checked(*signature_s)?;
-
-        let mut a = ExtPoint::from_encoded_point_vartime(public_key)?;
-        a.invert_vartime();
-
-        let h_digest = eddsa_digest(signature_r, public_key, msg.as_slice_less_safe());
-        let h = Scalar::from_sha512_digest_reduced(h_digest);
-
-        let mut r = Point::new_at_infinity();
-        unsafe { x25519_ge_double_scalarmult_vartime(&mut r, &h, &a, &signature_s) };
-        let r_check = r.into_encoded_point();
-        if *signature_r != r_check {
-            return Err(error::Unspecified);
-        }
-        Ok(())
+        verify_unparsed(public_key, msg, signature).map_err(error::Unspecified::from)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        verify_unparsed(public_key, msg, signature)
+    }
+
+    #[inline]
+    fn signature_len_hint(&self) -> signature::SignatureLen {
+        signature::SignatureLen::Exact(SIGNATURE_LEN)
     }
 }
 
 impl sealed::Sealed for EdDSAParameters {}
 
+#[cfg(not(feature = "ct-everything"))]
 prefixed_extern! {
     fn x25519_ge_double_scalarmult_vartime(
         r: &mut Point,
@@ -81,3 +76,89 @@ prefixed_extern! {
         b_coeff: &Scalar,
     );
 }
+
+#[cfg(feature = "ct-everything")]
+prefixed_extern! {
+    fn x25519_ge_double_scalarmult_consttime(
+        r: &mut Point,
+        a_coeff: &Scalar,
+        a: &ExtPoint,
+        b_coeff: &Scalar,
+        use_adx: c::int,
+    );
+}
+
+const SIGNATURE_LEN: usize = ELEM_LEN + SCALAR_LEN;
+
+/// Verifies an Ed25519 signature given fixed-size references to the public
+/// key and signature.
+///
+/// This is equivalent to
+/// `UnparsedPublicKey::new(&ED25519, public_key).verify(msg, signature)`,
+/// for callers that already have the public key and signature in arrays and
+/// so don't need to handle length errors.
+#[inline]
+pub fn ed25519_verify(
+    public_key: &[u8; ELEM_LEN],
+    msg: &[u8],
+    signature: &[u8; SIGNATURE_LEN],
+) -> Result<(), error::Unspecified> {
+    verify_(public_key, msg, signature).map_err(error::Unspecified::from)
+}
+
+fn verify_unparsed(
+    public_key: untrusted::Input,
+    msg: untrusted::Input,
+    signature: untrusted::Input,
+) -> Result<(), VerifyFailure> {
+    let signature: &[u8; SIGNATURE_LEN] = signature
+        .as_slice_less_safe()
+        .try_into()
+        .map_err(|_| VerifyFailure::MalformedSignature)?;
+    let public_key: &[u8; ELEM_LEN] = public_key
+        .as_slice_less_safe()
+        .try_into()
+        .map_err(|_| VerifyFailure::MalformedKey)?;
+    verify_(public_key, msg.as_slice_less_safe(), signature)
+}
+
+fn verify_(
+    public_key: &[u8; ELEM_LEN],
+    msg: &[u8],
+    signature: &[u8; SIGNATURE_LEN],
+) -> Result<(), VerifyFailure> {
+    let (signature_r, signature_s) = signature.split_at(ELEM_LEN);
+    let signature_r: &[u8; ELEM_LEN] = signature_r.try_into().unwrap();
+    let signature_s: &[u8; SCALAR_LEN] = signature_s.try_into().unwrap();
+
+    let signature_s = Scalar::from_bytes_checked(*signature_s)
+        .map_err(|error::Unspecified| VerifyFailure::OutOfRangeComponent)?;
+
+    let mut a = ExtPoint::from_encoded_point_vartime(public_key)
+        .map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
+    a.invert_vartime();
+
+    let h_digest = eddsa_digest(signature_r, public_key, msg);
+    let h = Scalar::from_sha512_digest_reduced(h_digest);
+
+    let mut r = Point::new_at_infinity();
+
+    #[cfg(not(feature = "ct-everything"))]
+    {
+        unsafe { x25519_ge_double_scalarmult_vartime(&mut r, &h, &a, &signature_s) };
+        let r_check = r.into_encoded_point();
+        if *signature_r != r_check {
+            return Err(VerifyFailure::Mismatch);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "ct-everything")]
+    {
+        let use_adx = has_fe25519_adx(cpu::features()).into();
+        unsafe { x25519_ge_double_scalarmult_consttime(&mut r, &h, &a, &signature_s, use_adx) };
+        let r_check = r.into_encoded_point();
+        constant_time::verify_slices_are_equal(signature_r, &r_check)
+            .map_err(|error::Unspecified| VerifyFailure::Mismatch)
+    }
+}
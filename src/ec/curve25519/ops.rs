@@ -17,7 +17,7 @@
 
 pub use super::scalar::{MaskedScalar, Scalar, SCALAR_LEN};
 use crate::{
-    bssl, c, cpu, error,
+    bssl, c, cpu, ec, error,
     limb::{Limb, LIMB_BITS},
 };
 use core::marker::PhantomData;
@@ -75,6 +75,14 @@ pub struct ExtPoint {
 impl ExtPoint {
     // Returns the result of multiplying the base point by the scalar in constant time.
     pub(super) fn from_scalarmult_base_consttime(scalar: &Scalar, cpu: cpu::Features) -> Self {
+        Self::from_scalarmult_base_consttime_(scalar, cpu, ec::TableScan::DEFAULT)
+    }
+
+    fn from_scalarmult_base_consttime_(
+        scalar: &Scalar,
+        cpu: cpu::Features,
+        table_scan: ec::TableScan,
+    ) -> Self {
         let mut r = Self {
             x: Elem::zero(),
             y: Elem::zero(),
@@ -82,10 +90,20 @@ impl ExtPoint {
             t: Elem::zero(),
         };
         prefixed_extern! {
-            fn x25519_ge_scalarmult_base(h: &mut ExtPoint, a: &Scalar, has_fe25519_adx: c::int);
+            fn x25519_ge_scalarmult_base(
+                h: &mut ExtPoint,
+                a: &Scalar,
+                has_fe25519_adx: c::int,
+                scan_all: c::int,
+            );
         }
         unsafe {
-            x25519_ge_scalarmult_base(&mut r, scalar, has_fe25519_adx(cpu).into());
+            x25519_ge_scalarmult_base(
+                &mut r,
+                scalar,
+                has_fe25519_adx(cpu).into(),
+                table_scan.into(),
+            );
         }
         r
     }
@@ -173,3 +191,40 @@ prefixed_extern! {
     fn x25519_fe_tobytes(bytes: &mut EncodedPoint, elem: &Elem<T>);
     fn x25519_ge_frombytes_vartime(h: &mut ExtPoint, s: &EncodedPoint) -> bssl::Result;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand;
+
+    #[test]
+    fn test_scalarmult_base_table_scan() {
+        fn check(bytes: [u8; SCALAR_LEN]) {
+            let scalar = Scalar::from_bytes_checked(bytes).unwrap();
+            let cpu = cpu::features();
+            let window =
+                ExtPoint::from_scalarmult_base_consttime_(&scalar, cpu, ec::TableScan::Window);
+            let full = ExtPoint::from_scalarmult_base_consttime_(&scalar, cpu, ec::TableScan::Full);
+            assert_eq!(window.into_encoded_point(), full.into_encoded_point());
+        }
+
+        for k in [0, 1, 2] {
+            let mut bytes = [0; SCALAR_LEN];
+            bytes[0] = k;
+            check(bytes);
+        }
+        // The order of the group, minus one, in little-endian order.
+        check([
+            0xec, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x10,
+        ]);
+
+        let rng = rand::SystemRandom::new();
+        for _ in 0..100 {
+            let mut bytes: [u8; SCALAR_LEN] = rand::generate(&rng).unwrap().expose();
+            bytes[SCALAR_LEN - 1] &= 0x0f; // Less than 2^252, so less than the order.
+            check(bytes);
+        }
+    }
+}
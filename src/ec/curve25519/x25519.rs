@@ -37,6 +37,7 @@ static CURVE25519: ec::Curve = ec::Curve {
 pub static X25519: agreement::Algorithm = agreement::Algorithm {
     curve: &CURVE25519,
     ecdh: x25519_ecdh,
+    validate_public_key: x25519_validate_public_key,
 };
 
 #[allow(clippy::unnecessary_wraps)]
@@ -80,6 +81,7 @@ fn x25519_public_from_private(
             public_key_out: &mut PublicKey,
             private_key: &PrivateKey,
             use_adx: c::int,
+            scan_all: c::int,
         );
     }
     unsafe {
@@ -87,6 +89,7 @@ fn x25519_public_from_private(
             public_out,
             &private_key,
             ops::has_fe25519_adx(cpu_features).into(),
+            ec::TableScan::DEFAULT.into(),
         );
     }
 
@@ -103,45 +106,6 @@ fn x25519_ecdh(
     let my_private_key = ops::MaskedScalar::from_bytes_masked(*my_private_key);
     let peer_public_key: &[u8; PUBLIC_KEY_LEN] = peer_public_key.as_slice_less_safe().try_into()?;
 
-    fn scalar_mult(
-        out: &mut ops::EncodedPoint,
-        scalar: &ops::MaskedScalar,
-        point: &ops::EncodedPoint,
-        #[allow(unused_variables)] cpu_features: cpu::Features,
-    ) {
-        #[cfg(all(not(target_os = "ios"), target_arch = "arm"))]
-        {
-            if cpu::arm::NEON.available(cpu_features) {
-                return x25519_neon(out, scalar, point);
-            }
-        }
-
-        #[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
-        {
-            if ops::has_fe25519_adx(cpu_features) {
-                prefixed_extern! {
-                    fn x25519_scalar_mult_adx(
-                        out: &mut ops::EncodedPoint,
-                        scalar: &ops::MaskedScalar,
-                        point: &ops::EncodedPoint,
-                    );
-                }
-                return unsafe { x25519_scalar_mult_adx(out, scalar, point) };
-            }
-        }
-
-        prefixed_extern! {
-            fn x25519_scalar_mult_generic_masked(
-                out: &mut ops::EncodedPoint,
-                scalar: &ops::MaskedScalar,
-                point: &ops::EncodedPoint,
-            );
-        }
-        unsafe {
-            x25519_scalar_mult_generic_masked(out, scalar, point);
-        }
-    }
-
     scalar_mult(
         out.try_into()?,
         &my_private_key,
@@ -158,6 +122,69 @@ fn x25519_ecdh(
     Ok(())
 }
 
+fn x25519_validate_public_key(public_key: untrusted::Input) -> Result<(), error::Unspecified> {
+    // Every 32-byte value is the encoding of a point on the curve or on its
+    // twist, and both are safe to use, so only reject the points of small
+    // order, exactly when `x25519_ecdh` would. Masking makes the scalar a
+    // multiple of the cofactor, and it isn't a multiple of the order of the
+    // prime-order subgroup of the curve or of the twist, so the result is
+    // zero exactly for the small-order points.
+    let public_key: &[u8; PUBLIC_KEY_LEN] = public_key.as_slice_less_safe().try_into()?;
+    let scalar = ops::MaskedScalar::from_bytes_masked({
+        let mut bytes = [0; SCALAR_LEN];
+        bytes[0] = 8;
+        bytes
+    });
+    let mut out: SharedSecret = [0; SHARED_SECRET_LEN];
+    scalar_mult(&mut out, &scalar, public_key, cpu::features());
+
+    let zeros: SharedSecret = [0; SHARED_SECRET_LEN];
+    if constant_time::verify_slices_are_equal(&out, &zeros).is_ok() {
+        return Err(error::Unspecified);
+    }
+
+    Ok(())
+}
+
+fn scalar_mult(
+    out: &mut ops::EncodedPoint,
+    scalar: &ops::MaskedScalar,
+    point: &ops::EncodedPoint,
+    #[allow(unused_variables)] cpu_features: cpu::Features,
+) {
+    #[cfg(all(not(target_os = "ios"), target_arch = "arm"))]
+    {
+        if cpu::arm::NEON.available(cpu_features) {
+            return x25519_neon(out, scalar, point);
+        }
+    }
+
+    #[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+    {
+        if ops::has_fe25519_adx(cpu_features) {
+            prefixed_extern! {
+                fn x25519_scalar_mult_adx(
+                    out: &mut ops::EncodedPoint,
+                    scalar: &ops::MaskedScalar,
+                    point: &ops::EncodedPoint,
+                );
+            }
+            return unsafe { x25519_scalar_mult_adx(out, scalar, point) };
+        }
+    }
+
+    prefixed_extern! {
+        fn x25519_scalar_mult_generic_masked(
+            out: &mut ops::EncodedPoint,
+            scalar: &ops::MaskedScalar,
+            point: &ops::EncodedPoint,
+        );
+    }
+    unsafe {
+        x25519_scalar_mult_generic_masked(out, scalar, point);
+    }
+}
+
 #[cfg(all(not(target_os = "ios"), target_arch = "arm"))]
 fn x25519_neon(out: &mut ops::EncodedPoint, scalar: &ops::MaskedScalar, point: &ops::EncodedPoint) {
     prefixed_extern! {
@@ -177,7 +204,10 @@ const PRIVATE_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
 
 // An X25519 public key as an encoded Curve25519 point.
 type PublicKey = [u8; PUBLIC_KEY_LEN];
-const PUBLIC_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
+const PUBLIC_KEY_LEN: usize = X25519_PUBLIC_KEY_LEN;
+
+/// The length of an X25519 public key.
+pub const X25519_PUBLIC_KEY_LEN: usize = ELEM_AND_SCALAR_LEN;
 
 // An X25519 shared secret as an encoded Curve25519 point.
 type SharedSecret = [u8; SHARED_SECRET_LEN];
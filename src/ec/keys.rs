@@ -72,6 +72,14 @@ impl Seed {
         (self.curve.public_from_private)(&mut public_key.bytes[..public_key.len], self)?;
         Ok(public_key)
     }
+
+    /// Writes the public key into the start of `out`, returning its length.
+    pub fn compute_public_key_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        let len = self.curve.public_key_len;
+        let out = out.get_mut(..len).ok_or(error::Unspecified)?;
+        (self.curve.public_from_private)(out, self)?;
+        Ok(len)
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -15,7 +15,13 @@
 //! Elliptic curve operations on P-256 & P-384.
 
 use self::ops::*;
-use crate::{arithmetic::montgomery::*, cpu, ec, error, io::der, limb::LimbMask, pkcs8};
+use crate::{
+    arithmetic::montgomery::*,
+    cpu, ec, error,
+    io::{der, der_writer, writer::Accumulator},
+    limb::LimbMask,
+    pkcs8,
+};
 
 // NIST SP 800-56A Step 3: "If q is an odd prime p, verify that
 // yQ**2 = xQ**3 + axQ + b in GF(p), where the arithmetic is performed modulo
@@ -160,23 +166,53 @@ pub(crate) fn key_pair_from_pkcs8(
     cpu_features: cpu::Features,
 ) -> Result<ec::KeyPair, error::KeyRejected> {
     let (ec_private_key, _) = pkcs8::unwrap_key(template, pkcs8::Version::V1Only, input)?;
-    let (private_key, public_key) =
-        ec_private_key.read_all(error::KeyRejected::invalid_encoding(), |input| {
-            // https://tools.ietf.org/html/rfc5915#section-3
-            der::nested(
-                input,
-                der::Tag::Sequence,
-                error::KeyRejected::invalid_encoding(),
-                |input| key_pair_from_pkcs8_(template, input),
-            )
-        })?;
+    let (private_key, public_key) = parse_ec_private_key(template, ec_private_key)?;
+    // The RFC says the public key is optional, but we require it to be
+    // present.
+    let public_key = public_key.ok_or_else(error::KeyRejected::invalid_encoding)?;
     key_pair_from_bytes(curve, private_key, public_key, cpu_features)
 }
 
-fn key_pair_from_pkcs8_<'a>(
+/// Parses a SEC1 `ECPrivateKey`. If the public key is present then it must be
+/// consistent with the private key; otherwise it is computed from the
+/// private key.
+pub(crate) fn key_pair_from_sec1(
+    curve: &'static ec::Curve,
+    template: &pkcs8::Template,
+    input: untrusted::Input,
+    cpu_features: cpu::Features,
+) -> Result<ec::KeyPair, error::KeyRejected> {
+    let (private_key, public_key) = parse_ec_private_key(template, input)?;
+    match public_key {
+        Some(public_key) => key_pair_from_bytes(curve, private_key, public_key, cpu_features),
+        None => {
+            let seed = ec::Seed::from_bytes(curve, private_key, cpu_features)
+                .map_err(|error::Unspecified| error::KeyRejected::invalid_component())?;
+            ec::KeyPair::derive(seed)
+                .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())
+        }
+    }
+}
+
+fn parse_ec_private_key<'a>(
+    template: &pkcs8::Template,
+    input: untrusted::Input<'a>,
+) -> Result<(untrusted::Input<'a>, Option<untrusted::Input<'a>>), error::KeyRejected> {
+    input.read_all(error::KeyRejected::invalid_encoding(), |input| {
+        // https://tools.ietf.org/html/rfc5915#section-3
+        der::nested(
+            input,
+            der::Tag::Sequence,
+            error::KeyRejected::invalid_encoding(),
+            |input| parse_ec_private_key_(template, input),
+        )
+    })
+}
+
+fn parse_ec_private_key_<'a>(
     template: &pkcs8::Template,
     input: &mut untrusted::Reader<'a>,
-) -> Result<(untrusted::Input<'a>, untrusted::Input<'a>), error::KeyRejected> {
+) -> Result<(untrusted::Input<'a>, Option<untrusted::Input<'a>>), error::KeyRejected> {
     let version = der::small_nonnegative_integer(input)
         .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
     if version != 1 {
@@ -196,19 +232,56 @@ fn key_pair_from_pkcs8_<'a>(
         }
     }
 
-    // [1] publicKey. The RFC says it is optional, but we require it
-    // to be present.
-    let public_key = der::nested(
-        input,
-        der::Tag::ContextSpecificConstructed1,
-        error::Unspecified,
-        der::bit_string_with_no_unused_bits,
-    )
-    .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+    // [1] publicKey (optional).
+    let public_key = if input.at_end() {
+        None
+    } else {
+        let public_key = der::nested(
+            input,
+            der::Tag::ContextSpecificConstructed1,
+            error::Unspecified,
+            der::bit_string_with_no_unused_bits,
+        )
+        .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+        Some(public_key)
+    };
 
     Ok((private_key, public_key))
 }
 
+/// Writes a SEC1 `ECPrivateKey` with the fixed-length big-endian
+/// `private_key`, optionally with the `parameters` and `publicKey` fields.
+///
+/// The output depends only on the arguments, and its length depends only on
+/// the curve and on which optional fields are included.
+pub(crate) fn write_ec_private_key(
+    output: &mut dyn Accumulator,
+    template: &pkcs8::Template,
+    private_key: &[u8],
+    include_parameters: bool,
+    public_key: Option<&[u8]>,
+) {
+    der_writer::write_tlv(output, der::Tag::Sequence, |output| {
+        der_writer::write_tlv(output, der::Tag::Integer, |output| output.write_byte(1));
+        der_writer::write_tlv(output, der::Tag::OctetString, |output| {
+            output.write_bytes(private_key)
+        });
+        if include_parameters {
+            der_writer::write_tlv(output, der::Tag::ContextSpecificConstructed0, |output| {
+                output.write_bytes(template.curve_oid().as_slice_less_safe())
+            });
+        }
+        if let Some(public_key) = public_key {
+            der_writer::write_tlv(output, der::Tag::ContextSpecificConstructed1, |output| {
+                der_writer::write_tlv(output, der::Tag::BitString, |output| {
+                    output.write_byte(0); // No unused bits.
+                    output.write_bytes(public_key);
+                })
+            });
+        }
+    })
+}
+
 pub(crate) fn key_pair_from_bytes(
     curve: &'static ec::Curve,
     private_key_bytes: untrusted::Input,
@@ -237,3 +310,5 @@ mod ops;
 
 mod private_key;
 mod public_key;
+
+pub mod sm2;
@@ -81,3 +81,35 @@ suite_b_curve!(
     p384_generate_private_key,
     p384_public_from_private
 );
+
+suite_b_curve!(
+    BRAINPOOL_P384R1,
+    384,
+    &ec::suite_b::ops::bp384::PRIVATE_KEY_OPS,
+    ec::CurveID::BrainpoolP384r1,
+    bp384_check_private_key_bytes,
+    bp384_generate_private_key,
+    bp384_public_from_private
+);
+
+/// P-256, with public keys encoded as just their big-endian x-coordinate. See
+/// `agreement::ECDH_P256_COMPACT`.
+pub static P256_COMPACT: ec::Curve = ec::Curve {
+    public_key_len: 256 / 8,
+    elem_scalar_seed_len: 256 / 8,
+    id: ec::CurveID::P256Compact,
+    check_private_key_bytes: p256_check_private_key_bytes,
+    generate_private_key: p256_generate_private_key,
+    public_from_private: p256_compact_public_from_private,
+};
+
+fn p256_compact_public_from_private(
+    public_out: &mut [u8],
+    private_key: &ec::Seed,
+) -> Result<(), error::Unspecified> {
+    ec::suite_b::private_key::public_x_from_private(
+        &ec::suite_b::ops::p256::PRIVATE_KEY_OPS,
+        public_out,
+        private_key,
+    )
+}
@@ -15,12 +15,12 @@
 //! ECDH key agreement using the P-256 and P-384 curves.
 
 use super::{ops::*, private_key::*, public_key::*};
-use crate::{agreement, ec, error};
+use crate::{agreement, arithmetic::montgomery::R, ec, error};
 
 /// A key agreement algorithm.
 macro_rules! ecdh {
     ( $NAME:ident, $curve:expr, $name_str:expr, $private_key_ops:expr,
-      $public_key_ops:expr, $ecdh:ident ) => {
+      $public_key_ops:expr, $ecdh:ident, $validate_public_key:ident ) => {
         #[doc = "ECDH using the NSA Suite B"]
         #[doc=$name_str]
         #[doc = "curve."]
@@ -41,6 +41,7 @@ macro_rules! ecdh {
         pub static $NAME: agreement::Algorithm = agreement::Algorithm {
             curve: $curve,
             ecdh: $ecdh,
+            validate_public_key: $validate_public_key,
         };
 
         fn $ecdh(
@@ -56,6 +57,12 @@ macro_rules! ecdh {
                 peer_public_key,
             )
         }
+
+        fn $validate_public_key(
+            peer_public_key: untrusted::Input,
+        ) -> Result<(), error::Unspecified> {
+            parse_uncompressed_point($public_key_ops, peer_public_key).map(|_| ())
+        }
     };
 }
 
@@ -65,18 +72,106 @@ ecdh!(
     "P-256 (secp256r1)",
     &p256::PRIVATE_KEY_OPS,
     &p256::PUBLIC_KEY_OPS,
-    p256_ecdh
+    p256_ecdh,
+    p256_validate_public_key
 );
 
+/// The length of an `ECDH_P256` public key.
+pub const ECDH_P256_PUBLIC_KEY_LEN: usize = 1 + (2 * (256 / 8));
+
+/// ECDH using the P-256 (secp256r1) curve, with public keys encoded as just
+/// their 32-byte big-endian x-coordinate.
+///
+/// This is the compact representation of [RFC 6090] Section 4.2, used by
+/// some IoT protocols. A peer's public key is decompressed to the point with
+/// that x-coordinate and an even y-coordinate; since only the x-coordinate
+/// of the shared point is output, the result is the same regardless of which
+/// of the two points the peer's private key corresponds to. The peer's public
+/// key is otherwise validated like it is for [`ECDH_P256`], and is rejected
+/// if there is no point with that x-coordinate.
+///
+/// The output is the same as that of `ECDH_P256` given the peer's
+/// uncompressed public key.
+///
+/// [RFC 6090]: https://tools.ietf.org/html/rfc6090#section-4.2
+pub static ECDH_P256_COMPACT: agreement::Algorithm = agreement::Algorithm {
+    curve: &ec::suite_b::curve::P256_COMPACT,
+    ecdh: p256_compact_ecdh,
+    validate_public_key: p256_compact_validate_public_key,
+};
+
+/// The length of an `ECDH_P256_COMPACT` public key.
+pub const ECDH_P256_COMPACT_PUBLIC_KEY_LEN: usize = 256 / 8;
+
+fn p256_compact_validate_public_key(
+    peer_public_key: untrusted::Input,
+) -> Result<(), error::Unspecified> {
+    parse_p256_x_only_point(peer_public_key).map(|_| ())
+}
+
+fn p256_compact_ecdh(
+    out: &mut [u8],
+    my_private_key: &ec::Seed,
+    peer_public_key: untrusted::Input,
+) -> Result<(), error::Unspecified> {
+    // `parse_p256_x_only_point` validates the point like
+    // `parse_uncompressed_point` does in `ecdh`.
+    let peer_public_key = parse_p256_x_only_point(peer_public_key)?;
+    ecdh_(
+        &p256::PRIVATE_KEY_OPS,
+        out,
+        my_private_key,
+        &peer_public_key,
+    )
+}
+
 ecdh!(
     ECDH_P384,
     &ec::suite_b::curve::P384,
     "P-384 (secp384r1)",
     &p384::PRIVATE_KEY_OPS,
     &p384::PUBLIC_KEY_OPS,
-    p384_ecdh
+    p384_ecdh,
+    p384_validate_public_key
 );
 
+/// The length of an `ECDH_P384` public key.
+pub const ECDH_P384_PUBLIC_KEY_LEN: usize = 1 + (2 * (384 / 8));
+
+/// ECDH using the brainpoolP384r1 curve of [RFC 5639].
+///
+/// Public keys are encoded and validated as for `ECDH_P384`. brainpoolP384r1
+/// isn't a Suite B curve and is much slower than P-384; use it only when a
+/// protocol requires it.
+///
+/// [RFC 5639]: https://tools.ietf.org/html/rfc5639
+pub static ECDH_BRAINPOOLP384R1: agreement::Algorithm = agreement::Algorithm {
+    curve: &ec::suite_b::curve::BRAINPOOL_P384R1,
+    ecdh: bp384_ecdh,
+    validate_public_key: bp384_validate_public_key,
+};
+
+/// The length of an `ECDH_BRAINPOOLP384R1` public key.
+pub const ECDH_BRAINPOOLP384R1_PUBLIC_KEY_LEN: usize = 1 + (2 * (384 / 8));
+
+fn bp384_ecdh(
+    out: &mut [u8],
+    my_private_key: &ec::Seed,
+    peer_public_key: untrusted::Input,
+) -> Result<(), error::Unspecified> {
+    ecdh(
+        &bp384::PRIVATE_KEY_OPS,
+        &bp384::PUBLIC_KEY_OPS,
+        out,
+        my_private_key,
+        peer_public_key,
+    )
+}
+
+fn bp384_validate_public_key(peer_public_key: untrusted::Input) -> Result<(), error::Unspecified> {
+    parse_uncompressed_point(&bp384::PUBLIC_KEY_OPS, peer_public_key).map(|_| ())
+}
+
 fn ecdh(
     private_key_ops: &PrivateKeyOps,
     public_key_ops: &PublicKeyOps,
@@ -100,6 +195,15 @@ fn ecdh(
     // Routine.
     let peer_public_key = parse_uncompressed_point(public_key_ops, peer_public_key)?;
 
+    ecdh_(private_key_ops, out, my_private_key, &peer_public_key)
+}
+
+fn ecdh_(
+    private_key_ops: &PrivateKeyOps,
+    out: &mut [u8],
+    my_private_key: &ec::Seed,
+    peer_public_key: &(Elem<R>, Elem<R>),
+) -> Result<(), error::Unspecified> {
     // NIST SP 800-56Ar2 Step 1.
     // NSA Guide Step 3 (except point at infinity check).
     //
@@ -121,7 +225,7 @@ fn ecdh(
     // NSA guide's explicit requirement to "zeroize" them though.
     // TODO: this only needs common scalar ops
     let my_private_key = private_key_as_scalar(private_key_ops, my_private_key);
-    let product = private_key_ops.point_mul(&my_private_key, &peer_public_key);
+    let product = private_key_ops.point_mul(&my_private_key, peer_public_key);
 
     // NIST SP 800-56Ar2 Steps 2, 3, 4, and 5.
     // NSA Guide Steps 3 (point at infinity check) and 4.
@@ -143,7 +247,7 @@ mod tests {
     use super::super::ops;
     use crate::{agreement, ec, limb, test};
 
-    static SUPPORTED_SUITE_B_ALGS: [(&str, &agreement::Algorithm, &ec::Curve, &ops::CommonOps); 2] = [
+    static SUPPORTED_SUITE_B_ALGS: [(&str, &agreement::Algorithm, &ec::Curve, &ops::CommonOps); 3] = [
         (
             "P-256",
             &agreement::ECDH_P256,
@@ -156,6 +260,12 @@ mod tests {
             &super::super::curve::P384,
             &super::super::ops::p384::COMMON_OPS,
         ),
+        (
+            "brainpoolP384r1",
+            &agreement::ECDH_BRAINPOOLP384R1,
+            &super::super::curve::BRAINPOOL_P384R1,
+            &super::super::ops::bp384::COMMON_OPS,
+        ),
     ];
 
     #[test]
@@ -233,4 +343,23 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_agreement_suite_b_public_key_len() {
+        for (alg, len) in [
+            (&agreement::ECDH_P256, agreement::ECDH_P256_PUBLIC_KEY_LEN),
+            (
+                &agreement::ECDH_P256_COMPACT,
+                agreement::ECDH_P256_COMPACT_PUBLIC_KEY_LEN,
+            ),
+            (&agreement::ECDH_P384, agreement::ECDH_P384_PUBLIC_KEY_LEN),
+            (
+                &agreement::ECDH_BRAINPOOLP384R1,
+                agreement::ECDH_BRAINPOOLP384R1_PUBLIC_KEY_LEN,
+            ),
+        ] {
+            assert_eq!(alg.curve.public_key_len, len);
+            assert!(len <= agreement::MAX_PUBLIC_KEY_LEN);
+        }
+    }
 }
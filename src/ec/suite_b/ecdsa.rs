@@ -1,3 +1,5 @@
 mod digest_scalar;
+pub mod low_s;
+pub mod parsed_signature;
 pub mod signing;
 pub mod verification;
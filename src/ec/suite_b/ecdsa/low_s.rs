@@ -0,0 +1,103 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! "Low-s" ECDSA signatures.
+//!
+//! If (r, s) is a valid signature then so is (r, n - s), so protocols that
+//! need signatures to be non-malleable require s <= n/2.
+
+use super::verification::EcdsaVerificationAlgorithm;
+use crate::{
+    ec::suite_b::ops::*,
+    error,
+    limb::{self, Limb, LimbMask, LIMB_BITS},
+    signature::Signature,
+};
+
+/// Returns `true` if `sig` is a well-formed fixed-length (r || s) signature
+/// for `alg`'s curve with s <= floor(n/2).
+///
+/// Only the curve of `alg` is used; `sig` is in the fixed-length format even
+/// if `alg` is one of the ASN.1 algorithms. Returns `false` if `sig` isn't
+/// well-formed.
+///
+/// The comparison is done in constant time.
+pub fn ecdsa_is_low_s(alg: &EcdsaVerificationAlgorithm, sig: &[u8]) -> bool {
+    let ops = alg.scalar_ops();
+    match parse_s(ops, sig) {
+        Ok(s) => is_high_s(ops, &s) == LimbMask::False,
+        Err(error::Unspecified) => false,
+    }
+}
+
+/// Returns `sig`, a fixed-length (r || s) signature for `alg`'s curve, with
+/// s replaced by n - s if s > floor(n/2).
+///
+/// Only the curve of `alg` is used; `sig` and the result are in the
+/// fixed-length format even if `alg` is one of the ASN.1 algorithms. Fails if
+/// `sig` isn't well-formed.
+///
+/// The result is computed in constant time.
+pub fn ecdsa_normalize_s(
+    alg: &EcdsaVerificationAlgorithm,
+    sig: &[u8],
+) -> Result<Signature, error::Unspecified> {
+    let ops = alg.scalar_ops();
+    let mut s = parse_s(ops, sig)?;
+
+    let num_limbs = ops.common.num_limbs;
+    let n = &ops.common.n.limbs[..num_limbs];
+    let mut neg_s = [0; MAX_LIMBS];
+    limb::limbs_sub_assign_mod(&mut neg_s[..num_limbs], &s.limbs[..num_limbs], n);
+
+    let high = is_high_s(ops, &s) as Limb;
+    s.limbs[..num_limbs]
+        .iter_mut()
+        .zip(&neg_s[..num_limbs])
+        .for_each(|(s, neg_s)| *s = (neg_s & high) | (*s & !high));
+
+    let scalar_len = ops.scalar_bytes_len();
+    Ok(Signature::new(|out| {
+        let (r_out, rest) = out.split_at_mut(scalar_len);
+        r_out.copy_from_slice(&sig[..scalar_len]);
+        limb::big_endian_from_limbs(&s.limbs[..num_limbs], &mut rest[..scalar_len]);
+        2 * scalar_len
+    }))
+}
+
+// Validates that both r and s are in [1, n) and returns s.
+fn parse_s(ops: &ScalarOps, sig: &[u8]) -> Result<Scalar, error::Unspecified> {
+    let scalar_len = ops.scalar_bytes_len();
+    if sig.len() != 2 * scalar_len {
+        return Err(error::Unspecified);
+    }
+    let (r, s) = sig.split_at(scalar_len);
+    let _ = scalar_parse_big_endian_fixed_consttime(ops.common, untrusted::Input::from(r))?;
+    scalar_parse_big_endian_fixed_consttime(ops.common, untrusted::Input::from(s))
+}
+
+// Returns `LimbMask::True` if floor(n/2) < s.
+pub(super) fn is_high_s(ops: &ScalarOps, s: &Scalar) -> LimbMask {
+    let num_limbs = ops.common.num_limbs;
+    let n = &ops.common.n.limbs[..num_limbs];
+    let mut half_n = [0; MAX_LIMBS];
+    half_n[..num_limbs]
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, half_n)| {
+            let next = n.get(i + 1).copied().unwrap_or(0);
+            *half_n = (n[i] >> 1) | (next << (LIMB_BITS - 1));
+        });
+    limb::limbs_less_than_limbs_consttime(&half_n[..num_limbs], &s.limbs[..num_limbs])
+}
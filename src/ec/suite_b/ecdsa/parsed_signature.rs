@@ -0,0 +1,221 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! ECDSA signatures parsed into their (r, s) components.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::signing::{format_rs_asn1, format_rs_fixed};
+use crate::{ec::suite_b::ops::*, error, io::der, limb::AllowZero, signature::Signature};
+
+/// The curve of an ECDSA key or signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EcdsaCurve {
+    /// NIST P-256 (secp256r1).
+    P256,
+
+    /// NIST P-384 (secp384r1).
+    P384,
+
+    /// brainpoolP384r1 (RFC 5639).
+    BrainpoolP384r1,
+}
+
+impl EcdsaCurve {
+    /// The length of the curve's order *n*, in bytes, which is also the
+    /// length of each of *r* and *s* in the fixed-length format.
+    #[inline]
+    pub fn scalar_len(self) -> usize {
+        self.scalar_ops().scalar_bytes_len()
+    }
+
+    pub(super) fn public_scalar_ops(self) -> &'static PublicScalarOps {
+        match self {
+            Self::P256 => &p256::PUBLIC_SCALAR_OPS,
+            Self::P384 => &p384::PUBLIC_SCALAR_OPS,
+            Self::BrainpoolP384r1 => &bp384::PUBLIC_SCALAR_OPS,
+        }
+    }
+
+    fn scalar_ops(self) -> &'static ScalarOps {
+        self.public_scalar_ops().scalar_ops
+    }
+}
+
+/// An encoding of an ECDSA signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EcdsaSignatureFormat {
+    /// An ASN.1 DER-encoded `Ecdsa-Sig-Value`, as used by the `ECDSA_*_ASN1`
+    /// algorithms; see "`ECDSA_*_ASN1` Details" in `ring::signature`.
+    Asn1,
+
+    /// The fixed-length `r || s` encoding of IEEE P1363, where *r* and *s*
+    /// are each exactly [`EcdsaCurve::scalar_len()`] bytes, as used by the
+    /// `ECDSA_*_FIXED` algorithms; see "`ECDSA_*_FIXED` Details" in
+    /// `ring::signature`.
+    FixedP1363,
+}
+
+/// An error parsing an ECDSA signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EcdsaSignatureError {
+    /// A fixed-length signature has the wrong length for the curve.
+    WrongLength {
+        /// The length of a fixed-length signature for the curve.
+        expected: usize,
+
+        /// The length of the input.
+        actual: usize,
+    },
+
+    /// An ASN.1 signature isn't a valid DER-encoded `Ecdsa-Sig-Value`.
+    InvalidEncoding,
+
+    /// *r* or *s* is zero.
+    ZeroScalar,
+
+    /// *r* or *s* is at least the order *n* of the curve, e.g. because the
+    /// signature is for a larger curve.
+    ScalarOutOfRange,
+}
+
+impl core::fmt::Display for EcdsaSignatureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::WrongLength { expected, actual } => write!(
+                f,
+                "signature is {} bytes; expected {} bytes",
+                actual, expected
+            ),
+            Self::InvalidEncoding => f.write_str("invalid ASN.1 signature encoding"),
+            Self::ZeroScalar => f.write_str("signature scalar is zero"),
+            Self::ScalarOutOfRange => f.write_str("signature scalar is out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EcdsaSignatureError {}
+
+impl From<EcdsaSignatureError> for error::Unspecified {
+    fn from(_: EcdsaSignatureError) -> Self {
+        Self
+    }
+}
+
+/// An ECDSA signature whose *r* and *s* are known to be in [1, n - 1] for its
+/// curve.
+///
+/// A signature can be parsed once and then verified with
+/// [`super::verification::EcdsaVerificationAlgorithm::verify_parsed()`],
+/// and converted between formats with [`Self::serialize()`].
+#[derive(Clone, Copy)]
+pub struct EcdsaSignature {
+    curve: EcdsaCurve,
+    r: Scalar,
+    s: Scalar,
+}
+
+impl EcdsaSignature {
+    /// Parses `bytes` as a `curve` signature in the given format.
+    ///
+    /// Leading zeros of *r* and *s* aren't significant in the ASN.1 format,
+    /// so an ASN.1 signature for one curve may parse successfully for the
+    /// other curve; verification with the other curve's algorithm will then
+    /// fail. The fixed-length format has no such ambiguity.
+    pub fn parse(
+        format: EcdsaSignatureFormat,
+        curve: EcdsaCurve,
+        bytes: &[u8],
+    ) -> Result<Self, EcdsaSignatureError> {
+        let (r, s) = match format {
+            EcdsaSignatureFormat::FixedP1363 => {
+                let scalar_len = curve.scalar_len();
+                if bytes.len() != 2 * scalar_len {
+                    return Err(EcdsaSignatureError::WrongLength {
+                        expected: 2 * scalar_len,
+                        actual: bytes.len(),
+                    });
+                }
+                bytes.split_at(scalar_len)
+            }
+            EcdsaSignatureFormat::Asn1 => untrusted::Input::from(bytes)
+                .read_all(error::Unspecified, |input| {
+                    der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
+                        let r = der::nonnegative_integer(input)?;
+                        let s = der::nonnegative_integer(input)?;
+                        Ok((r.as_slice_less_safe(), s.as_slice_less_safe()))
+                    })
+                })
+                .map_err(|error::Unspecified| EcdsaSignatureError::InvalidEncoding)?,
+        };
+        let ops = curve.scalar_ops();
+        Ok(Self {
+            curve,
+            r: parse_scalar(ops, r)?,
+            s: parse_scalar(ops, s)?,
+        })
+    }
+
+    /// Encodes the signature in the given format.
+    pub fn serialize(&self, format: EcdsaSignatureFormat) -> Signature {
+        let format_rs = match format {
+            EcdsaSignatureFormat::Asn1 => format_rs_asn1,
+            EcdsaSignatureFormat::FixedP1363 => format_rs_fixed,
+        };
+        let ops = self.curve.scalar_ops();
+        Signature::new(|out| format_rs(ops, &self.r, &self.s, out))
+    }
+
+    /// The curve of the signature.
+    #[inline]
+    pub fn curve(&self) -> EcdsaCurve {
+        self.curve
+    }
+
+    pub(super) fn rs(&self) -> (&Scalar, &Scalar) {
+        (&self.r, &self.s)
+    }
+}
+
+impl core::fmt::Debug for EcdsaSignature {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(f, "EcdsaSignature {{ curve: {:?}, rs: ", self.curve)?;
+        let fixed = self.serialize(EcdsaSignatureFormat::FixedP1363);
+        crate::debug::write_hex_bytes(f, fixed.as_ref())?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialEq for EcdsaSignature {
+    fn eq(&self, other: &Self) -> bool {
+        let num_limbs = self.curve.scalar_ops().common.num_limbs;
+        self.curve == other.curve
+            && self.r.limbs[..num_limbs] == other.r.limbs[..num_limbs]
+            && self.s.limbs[..num_limbs] == other.s.limbs[..num_limbs]
+    }
+}
+
+impl Eq for EcdsaSignature {}
+
+// NSA Guide Step 1 of verification: "If r and s are not both integers in the
+// interval [1, n − 1], output INVALID."
+fn parse_scalar(ops: &ScalarOps, bytes: &[u8]) -> Result<Scalar, EcdsaSignatureError> {
+    if bytes.iter().all(|&b| b == 0) {
+        return Err(EcdsaSignatureError::ZeroScalar);
+    }
+    scalar_parse_big_endian_variable(ops.common, AllowZero::No, untrusted::Input::from(bytes))
+        .map_err(|error::Unspecified| EcdsaSignatureError::ScalarOutOfRange)
+}
@@ -24,16 +24,18 @@ use crate::{
         self,
         suite_b::{ops::*, private_key},
     },
-    error,
-    io::der,
+    error, fingerprint, hmac,
+    io::{self, der},
     limb, pkcs8, rand, sealed, signature,
 };
+use core::cell::RefCell;
 /// An ECDSA signing algorithm.
 pub struct EcdsaSigningAlgorithm {
     curve: &'static ec::Curve,
     private_scalar_ops: &'static PrivateScalarOps,
     private_key_ops: &'static PrivateKeyOps,
     digest_alg: &'static digest::Algorithm,
+    hmac_alg: &'static hmac::Algorithm,
     pkcs8_template: &'static pkcs8::Template,
     format_rs: fn(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u8]) -> usize,
     id: AlgorithmID,
@@ -59,10 +61,50 @@ impl Eq for EcdsaSigningAlgorithm {}
 
 impl sealed::Sealed for EcdsaSigningAlgorithm {}
 
+impl EcdsaSigningAlgorithm {
+    #[inline]
+    pub(crate) fn curve(&self) -> &'static ec::Curve {
+        self.curve
+    }
+
+    // The X.509 signature algorithm of the ASN.1 algorithms. The fixed-length
+    // `r || s` format has no `AlgorithmIdentifier`.
+    pub(crate) fn algorithm_identifier(&self) -> Option<signature::AlgorithmIdentifier> {
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_ASN1_SIGNING => {
+                Some(signature::AlgorithmIdentifier::EcdsaSha256)
+            }
+            AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING => {
+                Some(signature::AlgorithmIdentifier::EcdsaSha384)
+            }
+            AlgorithmID::ECDSA_P256_SHA256_FIXED_SIGNING
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED_SIGNING => None,
+        }
+    }
+}
+
+/// Whether a SEC1 `ECPrivateKey` serialized by [`EcdsaKeyPair::to_sec1()`]
+/// includes the public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EcdsaPublicKeyInclusion {
+    /// The public key is included.
+    Include,
+
+    /// The public key is omitted, for smaller output. `EcdsaKeyPair::from_sec1()`
+    /// recomputes it from the private key.
+    Omit,
+}
+
 /// An ECDSA key pair, used for signing.
+///
+/// `EcdsaKeyPair` is `Send` and `Sync`, so it can be shared between threads,
+/// e.g. with `Arc<EcdsaKeyPair>`. Signing doesn't modify the key pair; the
+/// nonce for each signature is derived from the private key and the `rng` and
+/// message passed to [`EcdsaKeyPair::sign`], and there is no interior
+/// mutability.
 pub struct EcdsaKeyPair {
+    seed: ec::Seed,
     d: Scalar<R>,
-    nonce_key: NonceRandomKey,
     alg: &'static EcdsaSigningAlgorithm,
     public_key: PublicKey,
 }
@@ -70,6 +112,12 @@ pub struct EcdsaKeyPair {
 derive_debug_via_field!(EcdsaKeyPair, stringify!(EcdsaKeyPair), public_key);
 
 impl EcdsaKeyPair {
+    /// The maximum length of the public key of any ECDSA signing algorithm.
+    ///
+    /// This is the length of an uncompressed P-384 public key; P-256 public
+    /// keys are 65 bytes.
+    pub const MAX_PUBLIC_KEY_LEN: usize = ec::PUBLIC_KEY_MAX_LEN;
+
     /// Generates a new key pair and returns the key pair serialized as a
     /// PKCS#8 document.
     ///
@@ -85,6 +133,11 @@ impl EcdsaKeyPair {
         alg: &'static EcdsaSigningAlgorithm,
         rng: &dyn rand::SecureRandom,
     ) -> Result<pkcs8::Document, error::Unspecified> {
+        trace_span!(
+            crate::trace::KEY_GENERATION,
+            algorithm = ?alg,
+            key_bits = alg.curve.elem_scalar_seed_len * 8
+        );
         let private_key = ec::Seed::generate(alg.curve, rng, cpu::features())?;
         let public_key = private_key.compute_public_key()?;
         Ok(pkcs8::wrap_key(
@@ -149,10 +202,101 @@ impl EcdsaKeyPair {
         Self::new(alg, key_pair, rng)
     }
 
+    /// Constructs an ECDSA key pair by parsing a SEC1 `ECPrivateKey` as
+    /// described in [RFC 5915], e.g. the output of [`Self::to_sec1()`].
+    ///
+    /// Unlike `from_pkcs8()`, the public key is optional; if it is present
+    /// then it must be consistent with the private key. The `parameters`
+    /// field, if present, must name the curve of `alg`.
+    ///
+    /// [RFC 5915]: https://tools.ietf.org/html/rfc5915
+    pub fn from_sec1(
+        alg: &'static EcdsaSigningAlgorithm,
+        sec1: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::KeyRejected> {
+        let key_pair = ec::suite_b::key_pair_from_sec1(
+            alg.curve,
+            alg.pkcs8_template,
+            untrusted::Input::from(sec1),
+            cpu::features(),
+        )?;
+        Self::new(alg, key_pair, rng)
+    }
+
+    // `seed` must be for the curve of `alg`.
+    pub(crate) fn from_seed(
+        alg: &'static EcdsaSigningAlgorithm,
+        seed: ec::Seed,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::KeyRejected> {
+        let key_pair = ec::KeyPair::derive(seed)
+            .map_err(|error::Unspecified| error::KeyRejected::unexpected_error())?;
+        Self::new(alg, key_pair, rng)
+    }
+
+    /// Serializes the key pair as a PKCS#8 v1 document, of the same form as
+    /// those produced by `generate_pkcs8()`, which `from_pkcs8()` parses.
+    ///
+    /// The public key is always included since `from_pkcs8()` requires it;
+    /// use [`Self::to_sec1()`] for output without it.
+    ///
+    /// The output is deterministic: it depends only on the key, not on the
+    /// RNG, the target, or the crate features. The private key is always
+    /// encoded at the full width of the curve's scalars, so the length of the
+    /// output doesn't depend on the key either.
+    pub fn to_pkcs8(&self) -> pkcs8::Document {
+        pkcs8::wrap_key_with(self.alg.pkcs8_template, &|output| {
+            // The `parameters` are omitted because they'd duplicate the
+            // PKCS#8 algorithm identifier, as in `generate_pkcs8()`.
+            self.write_ec_private_key(output, false, EcdsaPublicKeyInclusion::Include)
+        })
+    }
+
+    /// Serializes the key pair as a SEC1 `ECPrivateKey`, as described in
+    /// [RFC 5915], with the `parameters` field naming the curve.
+    ///
+    /// With [`EcdsaPublicKeyInclusion::Omit`] the output is smaller, and
+    /// [`Self::from_sec1()`] recomputes the public key when parsing it.
+    ///
+    /// The output is deterministic in the same way as that of
+    /// [`Self::to_pkcs8()`], with `public_key` as the only other input.
+    ///
+    /// [RFC 5915]: https://tools.ietf.org/html/rfc5915
+    #[cfg(feature = "alloc")]
+    pub fn to_sec1(&self, public_key: EcdsaPublicKeyInclusion) -> alloc::boxed::Box<[u8]> {
+        let mut bytes = [0; ec::PKCS8_DOCUMENT_MAX_LEN];
+        let mut output = io::writer::SliceWriter::new(&mut bytes);
+        self.write_ec_private_key(&mut output, true, public_key);
+        let len = output.len();
+        alloc::boxed::Box::from(&bytes[..len])
+    }
+
+    fn write_ec_private_key(
+        &self,
+        output: &mut dyn io::writer::Accumulator,
+        include_parameters: bool,
+        public_key: EcdsaPublicKeyInclusion,
+    ) {
+        let public_key = match public_key {
+            EcdsaPublicKeyInclusion::Include => Some(self.public_key.as_ref()),
+            EcdsaPublicKeyInclusion::Omit => None,
+        };
+        ec::suite_b::write_ec_private_key(
+            output,
+            self.alg.pkcs8_template,
+            self.seed.bytes_less_safe(),
+            include_parameters,
+            public_key,
+        );
+    }
+
+    // `_rng` isn't used since nonces are derived when signing, but the public
+    // constructors still take one for compatibility.
     fn new(
         alg: &'static EcdsaSigningAlgorithm,
         key_pair: ec::KeyPair,
-        rng: &dyn rand::SecureRandom,
+        _rng: &dyn rand::SecureRandom,
     ) -> Result<Self, error::KeyRejected> {
         let (seed, public_key) = key_pair.split();
         let d = private_key::private_key_as_scalar(alg.private_key_ops, &seed);
@@ -161,16 +305,24 @@ impl EcdsaKeyPair {
             .scalar_ops
             .scalar_product(&d, &alg.private_scalar_ops.oneRR_mod_n);
 
-        let nonce_key = NonceRandomKey::new(alg, &seed, rng)?;
         Ok(Self {
+            seed,
             d,
-            nonce_key,
             alg,
             public_key: PublicKey(public_key),
         })
     }
 
-    /// Returns the signature of the `message` using a random nonce generated by `rng`.
+    /// Returns the signature of the `message` using a nonce derived from the
+    /// private key, the message, and random bytes generated by `rng`.
+    ///
+    /// The nonce is generated as described in [RFC 6979], with the random
+    /// bytes as the additional data `k'` of Section 3.6. It is unpredictable
+    /// if either the private key or the output of `rng` is, so even if `rng`
+    /// is completely broken, e.g. it always returns zeros, no nonce is reused
+    /// for different messages; signing just becomes deterministic.
+    ///
+    /// [RFC 6979]: https://www.rfc-editor.org/rfc/rfc6979
     pub fn sign(
         &self,
         rng: &dyn rand::SecureRandom,
@@ -178,19 +330,56 @@ impl EcdsaKeyPair {
     ) -> Result<signature::Signature, error::Unspecified> {
         // Step 4 (out of order).
         let h = digest::digest(self.alg.digest_alg, message);
+        trace_span!(crate::trace::SIGN, algorithm = ?self.alg, message_digest = ?h);
 
-        // Incorporate `h` into the nonce to hedge against faulty RNGs. (This
-        // is not an approved random number generator that is mandated in
-        // the spec.)
-        let nonce_rng = NonceRandom {
-            key: &self.nonce_key,
-            message_digest: &h,
-            rng,
-        };
+        let mut entropy = [0; ec::SCALAR_MAX_BYTES];
+        let entropy = &mut entropy[..self.alg.curve.elem_scalar_seed_len];
+        rng.fill(entropy)?;
+
+        self.sign_digest_hedged(h, entropy)
+    }
 
+    /// Returns the signature of message digest `h` using a nonce generated
+    /// as described in RFC 6979, with `entropy` as the additional data `k'`.
+    /// With empty `entropy`, this is exactly RFC 6979.
+    fn sign_digest_hedged(
+        &self,
+        h: digest::Digest,
+        entropy: &[u8],
+    ) -> Result<signature::Signature, error::Unspecified> {
+        let nonce_rng = NonceHmacDrbg::new(self.alg, &self.seed, h, entropy);
         self.sign_digest(h, &nonce_rng)
     }
 
+    /// Returns just the big-endian x-coordinate of the public key, i.e. the
+    /// part of the uncompressed public key after the encoding indicator and
+    /// before the y-coordinate.
+    ///
+    /// This is the compact representation used by
+    /// [`crate::agreement::ECDH_P256_COMPACT`].
+    pub fn public_key_x_only(&self) -> &[u8] {
+        let public_key = self.public_key.as_ref();
+        let elem_len = (public_key.len() - 1) / 2;
+        &public_key[1..][..elem_len]
+    }
+
+    /// Writes the public key into the start of `out`, returning its length.
+    ///
+    /// Fails if `out` is too short. A buffer of
+    /// [`Self::MAX_PUBLIC_KEY_LEN`] bytes is always long enough.
+    pub fn public_key_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        signature::public_key_into(self.public_key.as_ref(), out)
+    }
+
+    /// Returns the fingerprint of the key pair's public key, computed over
+    /// its `SubjectPublicKeyInfo` as described in [`fingerprint`].
+    pub fn spki_fingerprint(&self) -> fingerprint::PublicKeyFingerprint {
+        fingerprint::spki_fingerprint(
+            self.alg.pkcs8_template.alg_id_value_(),
+            self.public_key.as_ref(),
+        )
+    }
+
     #[cfg(test)]
     fn sign_with_fixed_nonce_during_test(
         &self,
@@ -283,86 +472,110 @@ impl EcdsaKeyPair {
     }
 }
 
-/// Generates an ECDSA nonce in a way that attempts to protect against a faulty
-/// `SecureRandom`.
-struct NonceRandom<'a> {
-    key: &'a NonceRandomKey,
-    message_digest: &'a digest::Digest,
-    rng: &'a dyn rand::SecureRandom,
+/// Generates ECDSA nonces with HMAC_DRBG, as described in [RFC 6979] Section
+/// 3.2 step h and Section 3.6.
+///
+/// Each call to `fill()` produces the next candidate `k`; a candidate that is
+/// rejected, either because it isn't in [1, n) or because `r` or `s` is zero,
+/// is followed by the next one as in step h.3. This relies on the candidates
+/// being exactly `hlen` bytes, which they are since each curve is only used
+/// with the digest algorithm with the same length as its order.
+///
+/// [RFC 6979]: https://www.rfc-editor.org/rfc/rfc6979
+struct NonceHmacDrbg {
+    state: RefCell<NonceHmacDrbgState>,
 }
 
-impl core::fmt::Debug for NonceRandom<'_> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("NonceRandom").finish()
-    }
+struct NonceHmacDrbgState {
+    k: hmac::Key,
+    v: [u8; digest::MAX_OUTPUT_LEN],
+    started: bool,
 }
 
-impl rand::sealed::SecureRandom for NonceRandom<'_> {
-    fn fill_impl(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
-        // Use the same digest algorithm that will be used to digest the
-        // message. The digest algorithm's output is exactly the right size;
-        // this is checked below.
-        //
-        // XXX(perf): The single iteration will require two digest block
-        // operations because the amount of data digested is larger than one
-        // block.
-        let digest_alg = self.key.0.algorithm();
-        let mut ctx = digest::Context::new(digest_alg);
-
-        // Digest the randomized digest of the private key.
-        let key = self.key.0.as_ref();
-        ctx.update(key);
-
-        // The random value is digested between the key and the message so that
-        // the key and the message are not directly digested in the same digest
-        // block.
-        assert!(key.len() <= digest_alg.block_len() / 2);
-        {
-            let mut rand = [0u8; digest::MAX_BLOCK_LEN];
-            let rand = &mut rand[..digest_alg.block_len() - key.len()];
-            assert!(rand.len() >= dest.len());
-            self.rng.fill(rand)?;
-            ctx.update(rand);
+impl NonceHmacDrbg {
+    fn new(
+        alg: &EcdsaSigningAlgorithm,
+        seed: &ec::Seed,
+        h: digest::Digest,
+        entropy: &[u8],
+    ) -> Self {
+        let hmac_alg = *alg.hmac_alg;
+        let hlen = hmac_alg.digest_algorithm().output_len();
+
+        // bits2octets(h1), which is just `h mod n` since `h` is as long as
+        // `n`.
+        let scalar_ops = alg.private_scalar_ops.scalar_ops;
+        let e = digest_scalar(scalar_ops, h);
+        let mut h_octets = [0; ec::SCALAR_MAX_BYTES];
+        let h_octets = &mut h_octets[..scalar_ops.scalar_bytes_len()];
+        limb::big_endian_from_limbs(&e.limbs[..scalar_ops.common.num_limbs], h_octets);
+
+        // Steps b through g.
+        let mut v = [0; digest::MAX_OUTPUT_LEN];
+        v[..hlen].fill(0x01);
+        let mut k = hmac::Key::new(hmac_alg, &[0; digest::MAX_OUTPUT_LEN][..hlen]);
+        for separator in [0x00, 0x01] {
+            let mut ctx = hmac::Context::with_key(&k);
+            ctx.update(&v[..hlen]);
+            ctx.update(&[separator]);
+            ctx.update(seed.bytes_less_safe());
+            ctx.update(h_octets);
+            ctx.update(entropy);
+            k = hmac::Key::new(hmac_alg, ctx.sign().as_ref());
+            update_v(&k, &mut v[..hlen]);
         }
 
-        ctx.update(self.message_digest.as_ref());
-
-        let nonce = ctx.finish();
+        Self {
+            state: RefCell::new(NonceHmacDrbgState {
+                k,
+                v,
+                started: false,
+            }),
+        }
+    }
+}
 
-        // `copy_from_slice()` panics if the lengths differ, so we don't have
-        // to separately assert that the lengths are the same.
-        dest.copy_from_slice(nonce.as_ref());
+// V = HMAC_K(V)
+fn update_v(k: &hmac::Key, v: &mut [u8]) {
+    let tag = hmac::sign(k, v);
+    v.copy_from_slice(tag.as_ref());
+}
 
-        Ok(())
+impl core::fmt::Debug for NonceHmacDrbg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NonceHmacDrbg").finish()
     }
 }
 
-impl<'a> sealed::Sealed for NonceRandom<'a> {}
+impl rand::sealed::SecureRandom for NonceHmacDrbg {
+    fn fill_impl(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let hmac_alg = state.k.algorithm();
+        let v = &mut state.v[..hmac_alg.digest_algorithm().output_len()];
+
+        // Step h.3: the previous candidate was rejected.
+        if state.started {
+            let mut ctx = hmac::Context::with_key(&state.k);
+            ctx.update(v);
+            ctx.update(&[0x00]);
+            state.k = hmac::Key::new(hmac_alg, ctx.sign().as_ref());
+            update_v(&state.k, v);
+        }
+        state.started = true;
 
-struct NonceRandomKey(digest::Digest);
+        // Steps h.1 and h.2. `copy_from_slice()` panics if the lengths
+        // differ, so we don't have to separately assert that `dest` is
+        // exactly `hlen` bytes long.
+        update_v(&state.k, v);
+        dest.copy_from_slice(v);
 
-impl NonceRandomKey {
-    fn new(
-        alg: &EcdsaSigningAlgorithm,
-        seed: &ec::Seed,
-        rng: &dyn rand::SecureRandom,
-    ) -> Result<Self, error::KeyRejected> {
-        let mut rand = [0; digest::MAX_OUTPUT_LEN];
-        let rand = &mut rand[0..alg.curve.elem_scalar_seed_len];
-
-        // XXX: `KeyRejected` isn't the right way to model  failure of the RNG,
-        // but to fix that we'd need to break the API by changing the result type.
-        // TODO: Fix the API in the next breaking release.
-        rng.fill(rand)
-            .map_err(|error::Unspecified| error::KeyRejected::rng_failed())?;
-
-        let mut ctx = digest::Context::new(alg.digest_alg);
-        ctx.update(rand);
-        ctx.update(seed.bytes_less_safe());
-        Ok(Self(ctx.finish()))
+        Ok(())
     }
 }
 
+impl sealed::Sealed for NonceHmacDrbg {}
+
 impl signature::KeyPair for EcdsaKeyPair {
     type PublicKey = PublicKey;
 
@@ -382,7 +595,12 @@ impl AsRef<[u8]> for PublicKey {
     }
 }
 
-fn format_rs_fixed(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u8]) -> usize {
+pub(super) fn format_rs_fixed(
+    ops: &'static ScalarOps,
+    r: &Scalar,
+    s: &Scalar,
+    out: &mut [u8],
+) -> usize {
     let scalar_len = ops.scalar_bytes_len();
 
     let (r_out, rest) = out.split_at_mut(scalar_len);
@@ -394,7 +612,12 @@ fn format_rs_fixed(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u
     2 * scalar_len
 }
 
-fn format_rs_asn1(ops: &'static ScalarOps, r: &Scalar, s: &Scalar, out: &mut [u8]) -> usize {
+pub(super) fn format_rs_asn1(
+    ops: &'static ScalarOps,
+    r: &Scalar,
+    s: &Scalar,
+    out: &mut [u8],
+) -> usize {
     // This assumes `a` is not zero since neither `r` or `s` is allowed to be
     // zero.
     fn format_integer_tlv(ops: &ScalarOps, a: &Scalar, out: &mut [u8]) -> usize {
@@ -450,6 +673,7 @@ pub static ECDSA_P256_SHA256_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigning
     private_scalar_ops: &p256::PRIVATE_SCALAR_OPS,
     private_key_ops: &p256::PRIVATE_KEY_OPS,
     digest_alg: &digest::SHA256,
+    hmac_alg: &hmac::HMAC_SHA256,
     pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_fixed,
     id: AlgorithmID::ECDSA_P256_SHA256_FIXED_SIGNING,
@@ -465,6 +689,7 @@ pub static ECDSA_P384_SHA384_FIXED_SIGNING: EcdsaSigningAlgorithm = EcdsaSigning
     private_scalar_ops: &p384::PRIVATE_SCALAR_OPS,
     private_key_ops: &p384::PRIVATE_KEY_OPS,
     digest_alg: &digest::SHA384,
+    hmac_alg: &hmac::HMAC_SHA384,
     pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_fixed,
     id: AlgorithmID::ECDSA_P384_SHA384_FIXED_SIGNING,
@@ -480,6 +705,7 @@ pub static ECDSA_P256_SHA256_ASN1_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningA
     private_scalar_ops: &p256::PRIVATE_SCALAR_OPS,
     private_key_ops: &p256::PRIVATE_KEY_OPS,
     digest_alg: &digest::SHA256,
+    hmac_alg: &hmac::HMAC_SHA256,
     pkcs8_template: &EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_asn1,
     id: AlgorithmID::ECDSA_P256_SHA256_ASN1_SIGNING,
@@ -495,19 +721,20 @@ pub static ECDSA_P384_SHA384_ASN1_SIGNING: EcdsaSigningAlgorithm = EcdsaSigningA
     private_scalar_ops: &p384::PRIVATE_SCALAR_OPS,
     private_key_ops: &p384::PRIVATE_KEY_OPS,
     digest_alg: &digest::SHA384,
+    hmac_alg: &hmac::HMAC_SHA384,
     pkcs8_template: &EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
     format_rs: format_rs_asn1,
     id: AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING,
 };
 
-static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ecPublicKey_p256_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 27 },
     curve_id_index: 9,
     private_key_index: 0x24,
 };
 
-static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ecPublicKey_p384_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 24 },
     curve_id_index: 9,
@@ -516,7 +743,47 @@ static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
 
 #[cfg(test)]
 mod tests {
-    use crate::{rand, signature, test};
+    use crate::{digest, rand, signature, test};
+
+    // With no additional entropy, the nonces are exactly those of RFC 6979.
+    #[test]
+    fn signature_ecdsa_sign_rfc6979_test() {
+        let rng = rand::SystemRandom::new();
+
+        test::run(
+            test_file!("ecdsa_sign_rfc6979_tests.txt"),
+            |section, test_case| {
+                assert_eq!(section, "");
+
+                let curve_name = test_case.consume_string("Curve");
+                let digest_name = test_case.consume_string("Digest");
+                let msg = test_case.consume_bytes("Msg");
+                let d = test_case.consume_bytes("d");
+                let q = test_case.consume_bytes("Q");
+
+                let expected_result = test_case.consume_bytes("Sig");
+
+                let alg = match (curve_name.as_str(), digest_name.as_str()) {
+                    ("P-256", "SHA256") => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                    _ => {
+                        panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
+                    }
+                };
+
+                let private_key =
+                    signature::EcdsaKeyPair::from_private_key_and_public_key(alg, &d, &q, &rng)
+                        .unwrap();
+                let h = digest::digest(alg.digest_alg, &msg);
+
+                let actual_result = private_key.sign_digest_hedged(h, &[]).unwrap();
+
+                assert_eq!(actual_result.as_ref(), &expected_result[..]);
+
+                Ok(())
+            },
+        );
+    }
 
     #[test]
     fn signature_ecdsa_sign_fixed_test() {
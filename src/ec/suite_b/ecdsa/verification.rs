@@ -14,12 +14,16 @@
 
 //! ECDSA Signatures using the P-256 and P-384 curves.
 
-use super::digest_scalar::digest_scalar;
+use super::{
+    digest_scalar::digest_scalar,
+    low_s::is_high_s,
+    parsed_signature::{EcdsaCurve, EcdsaSignature},
+};
 use crate::{
     arithmetic::montgomery::*,
     digest,
     ec::suite_b::{ops::*, public_key::*, verify_jacobian_point_is_on_the_curve},
-    error,
+    error::{self, VerifyFailure},
     io::der,
     limb, sealed, signature,
 };
@@ -45,6 +49,11 @@ enum AlgorithmID {
     ECDSA_P384_SHA256_ASN1,
     ECDSA_P384_SHA384_ASN1,
     ECDSA_P384_SHA384_FIXED,
+    ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
+    ECDSA_P256_SHA256_ASN1_LOW_S,
+    ECDSA_P256_SHA256_FIXED_LOW_S,
+    ECDSA_P384_SHA384_ASN1_LOW_S,
+    ECDSA_P384_SHA384_FIXED_LOW_S,
 }
 
 derive_debug_via_id!(EcdsaVerificationAlgorithm);
@@ -56,6 +65,119 @@ impl signature::VerificationAlgorithm for EcdsaVerificationAlgorithm {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified> {
+        self.verify_(public_key, msg, signature)
+            .map_err(error::Unspecified::from)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        self.verify_(public_key, msg, signature)
+    }
+
+    fn signature_len_hint(&self) -> signature::SignatureLen {
+        let scalar_len = self.ops.scalar_ops.scalar_bytes_len();
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_FIXED
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED_LOW_S
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED_LOW_S => {
+                signature::SignatureLen::Exact(2 * scalar_len)
+            }
+            AlgorithmID::ECDSA_P256_SHA256_ASN1
+            | AlgorithmID::ECDSA_P256_SHA256_ASN1_LOW_S
+            | AlgorithmID::ECDSA_P256_SHA384_ASN1
+            | AlgorithmID::ECDSA_P384_SHA256_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1_LOW_S
+            | AlgorithmID::ECDSA_BRAINPOOLP384R1_SHA384_ASN1 => {
+                // SEQUENCE { INTEGER r, INTEGER s }, where each INTEGER is
+                // one byte up to `scalar_len` bytes plus a leading zero.
+                const MIN_LEN: usize = 2 + (2 * (2 + 1));
+                let max_contents_len = 2 * (2 + scalar_len + 1);
+                let max_len = if max_contents_len < 0x80 {
+                    2 + max_contents_len
+                } else {
+                    3 + max_contents_len
+                };
+                signature::SignatureLen::Range(MIN_LEN, max_len)
+            }
+        }
+    }
+}
+
+impl EcdsaVerificationAlgorithm {
+    #[inline]
+    pub(super) fn scalar_ops(&self) -> &'static ScalarOps {
+        self.ops.scalar_ops
+    }
+
+    /// The curve of the algorithm.
+    pub fn curve(&self) -> EcdsaCurve {
+        match self.id {
+            AlgorithmID::ECDSA_P256_SHA256_ASN1
+            | AlgorithmID::ECDSA_P256_SHA256_ASN1_LOW_S
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED
+            | AlgorithmID::ECDSA_P256_SHA256_FIXED_LOW_S
+            | AlgorithmID::ECDSA_P256_SHA384_ASN1 => EcdsaCurve::P256,
+            AlgorithmID::ECDSA_P384_SHA256_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1
+            | AlgorithmID::ECDSA_P384_SHA384_ASN1_LOW_S
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED
+            | AlgorithmID::ECDSA_P384_SHA384_FIXED_LOW_S => EcdsaCurve::P384,
+            AlgorithmID::ECDSA_BRAINPOOLP384R1_SHA384_ASN1 => EcdsaCurve::BrainpoolP384r1,
+        }
+    }
+
+    /// Whether the algorithm rejects signatures with s > floor(n/2).
+    #[inline]
+    pub fn requires_low_s(&self) -> bool {
+        matches!(
+            self.id,
+            AlgorithmID::ECDSA_P256_SHA256_ASN1_LOW_S
+                | AlgorithmID::ECDSA_P256_SHA256_FIXED_LOW_S
+                | AlgorithmID::ECDSA_P384_SHA384_ASN1_LOW_S
+                | AlgorithmID::ECDSA_P384_SHA384_FIXED_LOW_S
+        )
+    }
+
+    /// Verifies the already-parsed `signature` of `msg` with `public_key`.
+    ///
+    /// This is like `VerificationAlgorithm::verify()`, except that the
+    /// signature isn't parsed again, and the algorithm's signature format is
+    /// ignored; e.g. a signature parsed from the fixed-length format can be
+    /// verified with `ECDSA_P256_SHA256_ASN1`. Fails if `signature` is for a
+    /// different curve than the algorithm.
+    pub fn verify_parsed(
+        &self,
+        public_key: &[u8],
+        msg: &[u8],
+        signature: &EcdsaSignature,
+    ) -> Result<(), error::Unspecified> {
+        if signature.curve() != self.curve() {
+            return Err(error::Unspecified);
+        }
+        let h = digest::digest(self.digest_alg, msg);
+        let e = digest_scalar(self.ops.scalar_ops, h);
+        let (r, s) = signature.rs();
+        self.verify_digest_rs(untrusted::Input::from(public_key), e, r, s)
+            .map_err(error::Unspecified::from)
+    }
+
+    fn verify_(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        if !signature::VerificationAlgorithm::signature_len_hint(self).contains(signature.len()) {
+            return Err(VerifyFailure::MalformedSignature);
+        }
+
         let e = {
             // NSA Guide Step 2: "Use the selected hash function to compute H =
             // Hash(M)."
@@ -68,16 +190,14 @@ impl signature::VerificationAlgorithm for EcdsaVerificationAlgorithm {
 
         self.verify_digest(public_key, e, signature)
     }
-}
 
-impl EcdsaVerificationAlgorithm {
     /// This is intentionally not public.
     fn verify_digest(
         &self,
         public_key: untrusted::Input,
         e: Scalar,
         signature: untrusted::Input,
-    ) -> Result<(), error::Unspecified> {
+    ) -> Result<(), VerifyFailure> {
         // NSA Suite B Implementer's Guide to ECDSA Section 3.4.2.
 
         let public_key_ops = self.ops.public_key_ops;
@@ -100,77 +220,145 @@ impl EcdsaVerificationAlgorithm {
         // can do. Prerequisite #2 is handled implicitly as the domain
         // parameters are hard-coded into the source. Prerequisite #3 is
         // handled by `parse_uncompressed_point`.
-        let peer_pub_key = parse_uncompressed_point(public_key_ops, public_key)?;
-
-        let (r, s) = signature.read_all(error::Unspecified, |input| {
-            (self.split_rs)(scalar_ops, input)
-        })?;
+        let (r, s) = signature
+            .read_all(error::Unspecified, |input| {
+                (self.split_rs)(scalar_ops, input)
+            })
+            .map_err(|error::Unspecified| VerifyFailure::MalformedSignature)?;
 
         // NSA Guide Step 1: "If r and s are not both integers in the interval
         // [1, n − 1], output INVALID."
-        let r = scalar_parse_big_endian_variable(public_key_ops.common, limb::AllowZero::No, r)?;
-        let s = scalar_parse_big_endian_variable(public_key_ops.common, limb::AllowZero::No, s)?;
+        let parse_scalar = |value| {
+            scalar_parse_big_endian_variable(public_key_ops.common, limb::AllowZero::No, value)
+                .map_err(|error::Unspecified| VerifyFailure::OutOfRangeComponent)
+        };
+        let r = parse_scalar(r)?;
+        let s = parse_scalar(s)?;
+
+        self.verify_digest_rs(public_key, e, &r, &s)
+    }
+
+    // `r` and `s` must be in [1, n - 1].
+    fn verify_digest_rs(
+        &self,
+        public_key: untrusted::Input,
+        e: Scalar,
+        r: &Scalar,
+        s: &Scalar,
+    ) -> Result<(), VerifyFailure> {
+        let public_key_ops = self.ops.public_key_ops;
+        let scalar_ops = self.ops.scalar_ops;
+
+        if self.requires_low_s() && is_high_s(scalar_ops, s) == limb::LimbMask::True {
+            return Err(VerifyFailure::OutOfRangeComponent);
+        }
+
+        let peer_pub_key = parse_uncompressed_point(public_key_ops, public_key)
+            .map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
 
         // NSA Guide Step 4: "Compute w = s**−1 mod n, using the routine in
         // Appendix B.1."
-        let w = scalar_ops.scalar_inv_to_mont(&s);
+        let w = scalar_ops.scalar_inv_to_mont(s);
 
         // NSA Guide Step 5: "Compute u1 = (e * w) mod n, and compute
         // u2 = (r * w) mod n."
         let u1 = scalar_ops.scalar_product(&e, &w);
-        let u2 = scalar_ops.scalar_product(&r, &w);
+        let u2 = scalar_ops.scalar_product(r, &w);
 
         // NSA Guide Step 6: "Compute the elliptic curve point
         // R = (xR, yR) = u1*G + u2*Q, using EC scalar multiplication and EC
         // addition. If R is equal to the point at infinity, output INVALID."
         let product = (self.ops.twin_mul)(&u1, &u2, &peer_pub_key);
 
-        // Verify that the point we computed is on the curve; see
-        // `verify_affine_point_is_on_the_curve_scaled` for details on why. It
-        // would be more secure to do the check on the affine coordinates if we
-        // were going to convert to affine form (again, see
-        // `verify_affine_point_is_on_the_curve_scaled` for details on why).
-        // But, we're going to avoid converting to affine for performance
-        // reasons, so we do the verification using the Jacobian coordinates.
-        let z2 = verify_jacobian_point_is_on_the_curve(public_key_ops.common, &product)?;
-
         // NSA Guide Step 7: "Compute v = xR mod n."
         // NSA Guide Step 8: "Compare v and r0. If v = r0, output VALID;
         // otherwise, output INVALID."
-        //
-        // Instead, we use Greg Maxwell's trick to avoid the inversion mod `q`
-        // that would be necessary to compute the affine X coordinate.
-        let x = public_key_ops.common.point_x(&product);
-        fn sig_r_equals_x(
-            ops: &PublicScalarOps,
-            r: &Elem<Unencoded>,
-            x: &Elem<R>,
-            z2: &Elem<R>,
-        ) -> bool {
-            let cops = ops.public_key_ops.common;
-            let r_jacobian = cops.elem_product(z2, r);
-            let x = cops.elem_unencoded(x);
-            ops.elem_equals_vartime(&r_jacobian, &x)
-        }
-        let mut r = self.ops.scalar_as_elem(&r);
-        if sig_r_equals_x(self.ops, &r, &x, &z2) {
+        verify_x_mod_n_equals(self.ops, &product, r)
+            .map_err(|error::Unspecified| VerifyFailure::Mismatch)
+    }
+}
+
+impl sealed::Sealed for EcdsaVerificationAlgorithm {}
+
+/// Verifies that `product` is on the curve and that its affine X coordinate,
+/// reduced mod `n`, is `r`. This is the final step of both ECDSA and SM2
+/// verification.
+pub(in crate::ec::suite_b) fn verify_x_mod_n_equals(
+    ops: &PublicScalarOps,
+    product: &Point,
+    r: &Scalar,
+) -> Result<(), error::Unspecified> {
+    // Verify that the point we computed is on the curve; see
+    // `verify_affine_point_is_on_the_curve_scaled` for details on why. It
+    // would be more secure to do the check on the affine coordinates if we
+    // were going to convert to affine form (again, see
+    // `verify_affine_point_is_on_the_curve_scaled` for details on why).
+    // But, we're going to avoid converting to affine for performance
+    // reasons, so we do the verification using the Jacobian coordinates.
+    let z2 = verify_jacobian_point_is_on_the_curve(ops.public_key_ops.common, product)?;
+
+    // We use Greg Maxwell's trick to avoid the inversion mod `q` that
+    // would be necessary to compute the affine X coordinate.
+    let x = ops.public_key_ops.common.point_x(product);
+    #[cfg(not(feature = "ct-everything"))]
+    fn sig_r_equals_x(
+        ops: &PublicScalarOps,
+        r: &Elem<Unencoded>,
+        x: &Elem<R>,
+        z2: &Elem<R>,
+    ) -> bool {
+        let cops = ops.public_key_ops.common;
+        let r_jacobian = cops.elem_product(z2, r);
+        let x = cops.elem_unencoded(x);
+        ops.elem_equals_vartime(&r_jacobian, &x)
+    }
+    #[cfg(feature = "ct-everything")]
+    fn sig_r_equals_x(
+        ops: &PublicScalarOps,
+        r: &Elem<Unencoded>,
+        x: &Elem<R>,
+        z2: &Elem<R>,
+    ) -> limb::Limb {
+        let cops = ops.public_key_ops.common;
+        let r_jacobian = cops.elem_product(z2, r);
+        let x = cops.elem_unencoded(x);
+        ops.elem_equals_consttime(&r_jacobian, &x) as limb::Limb
+    }
+    let mut r = ops.scalar_as_elem(r);
+
+    #[cfg(not(feature = "ct-everything"))]
+    {
+        if sig_r_equals_x(ops, &r, &x, &z2) {
             return Ok(());
         }
-        if self.ops.elem_less_than(&r, &self.ops.q_minus_n) {
-            self.ops
-                .scalar_ops
+        if ops.elem_less_than(&r, &ops.q_minus_n) {
+            ops.scalar_ops
                 .common
-                .elem_add(&mut r, &public_key_ops.common.n);
-            if sig_r_equals_x(self.ops, &r, &x, &z2) {
+                .elem_add(&mut r, &ops.public_key_ops.common.n);
+            if sig_r_equals_x(ops, &r, &x, &z2) {
                 return Ok(());
             }
         }
+    }
 
-        Err(error::Unspecified)
+    // Do both comparisons unconditionally, and combine the results
+    // without branching. `r + n` is only a candidate if it is less than
+    // `q`.
+    #[cfg(feature = "ct-everything")]
+    {
+        let equals_r = sig_r_equals_x(ops, &r, &x, &z2);
+        let r_plus_n_in_range = ops.elem_less_than_consttime(&r, &ops.q_minus_n) as limb::Limb;
+        ops.scalar_ops
+            .common
+            .elem_add(&mut r, &ops.public_key_ops.common.n);
+        let equals_r_plus_n = sig_r_equals_x(ops, &r, &x, &z2);
+        if equals_r | (r_plus_n_in_range & equals_r_plus_n) != 0 {
+            return Ok(());
+        }
     }
-}
 
-impl sealed::Sealed for EcdsaVerificationAlgorithm {}
+    Err(error::Unspecified)
+}
 
 fn split_rs_fixed<'a>(
     ops: &'static ScalarOps,
@@ -186,9 +374,11 @@ fn split_rs_asn1<'a>(
     _ops: &'static ScalarOps,
     input: &mut untrusted::Reader<'a>,
 ) -> Result<(untrusted::Input<'a>, untrusted::Input<'a>), error::Unspecified> {
+    // Zero is well-formed here; it is rejected as out of range when the
+    // scalars are parsed.
     der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
-        let r = der::positive_integer(input)?.big_endian_without_leading_zero_as_input();
-        let s = der::positive_integer(input)?.big_endian_without_leading_zero_as_input();
+        let r = der::nonnegative_integer(input)?;
+        let s = der::nonnegative_integer(input)?;
         Ok((r, s))
     })
 }
@@ -205,6 +395,30 @@ pub static ECDSA_P256_SHA256_FIXED: EcdsaVerificationAlgorithm = EcdsaVerificati
     id: AlgorithmID::ECDSA_P256_SHA256_FIXED,
 };
 
+/// Verifies a fixed-length (PKCS#11 style) ECDSA P-256 SHA-256 signature given
+/// fixed-size references to the uncompressed public key and the signature.
+///
+/// This is equivalent to
+/// `UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, public_key).verify(msg, signature)`,
+/// for callers that already have the public key and signature in arrays and
+/// so don't need to handle length errors. The public key and the signature's
+/// scalars are still validated.
+#[inline]
+pub fn ecdsa_p256_fixed_verify(
+    public_key: &[u8; 1 + (2 * 32)],
+    msg: &[u8],
+    signature: &[u8; 2 * 32],
+) -> Result<(), error::Unspecified> {
+    let alg = &ECDSA_P256_SHA256_FIXED;
+    let e = digest_scalar(alg.ops.scalar_ops, digest::digest(alg.digest_alg, msg));
+    alg.verify_digest(
+        untrusted::Input::from(public_key),
+        e,
+        untrusted::Input::from(signature),
+    )
+    .map_err(error::Unspecified::from)
+}
+
 /// Verification of fixed-length (PKCS#11 style) ECDSA signatures using the
 /// P-384 curve and SHA-384.
 ///
@@ -217,6 +431,30 @@ pub static ECDSA_P384_SHA384_FIXED: EcdsaVerificationAlgorithm = EcdsaVerificati
     id: AlgorithmID::ECDSA_P384_SHA384_FIXED,
 };
 
+/// Like `ECDSA_P256_SHA256_FIXED`, except signatures with s > floor(n/2) are
+/// rejected.
+///
+/// See "`ECDSA_*_LOW_S` Details" in `ring::signature`'s module-level documentation
+/// for more details.
+pub static ECDSA_P256_SHA256_FIXED_LOW_S: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p256::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA256,
+    split_rs: split_rs_fixed,
+    id: AlgorithmID::ECDSA_P256_SHA256_FIXED_LOW_S,
+};
+
+/// Like `ECDSA_P384_SHA384_FIXED`, except signatures with s > floor(n/2) are
+/// rejected.
+///
+/// See "`ECDSA_*_LOW_S` Details" in `ring::signature`'s module-level documentation
+/// for more details.
+pub static ECDSA_P384_SHA384_FIXED_LOW_S: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p384::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA384,
+    split_rs: split_rs_fixed,
+    id: AlgorithmID::ECDSA_P384_SHA384_FIXED_LOW_S,
+};
+
 /// Verification of ASN.1 DER-encoded ECDSA signatures using the P-256 curve
 /// and SHA-256.
 ///
@@ -275,6 +513,45 @@ pub static ECDSA_P384_SHA384_ASN1: EcdsaVerificationAlgorithm = EcdsaVerificatio
     id: AlgorithmID::ECDSA_P384_SHA384_ASN1,
 };
 
+/// Like `ECDSA_P256_SHA256_ASN1`, except signatures with s > floor(n/2) are
+/// rejected.
+///
+/// See "`ECDSA_*_LOW_S` Details" in `ring::signature`'s module-level documentation
+/// for more details.
+pub static ECDSA_P256_SHA256_ASN1_LOW_S: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p256::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA256,
+    split_rs: split_rs_asn1,
+    id: AlgorithmID::ECDSA_P256_SHA256_ASN1_LOW_S,
+};
+
+/// Like `ECDSA_P384_SHA384_ASN1`, except signatures with s > floor(n/2) are
+/// rejected.
+///
+/// See "`ECDSA_*_LOW_S` Details" in `ring::signature`'s module-level documentation
+/// for more details.
+pub static ECDSA_P384_SHA384_ASN1_LOW_S: EcdsaVerificationAlgorithm = EcdsaVerificationAlgorithm {
+    ops: &p384::PUBLIC_SCALAR_OPS,
+    digest_alg: &digest::SHA384,
+    split_rs: split_rs_asn1,
+    id: AlgorithmID::ECDSA_P384_SHA384_ASN1_LOW_S,
+};
+
+/// Verification of ASN.1 DER-encoded ECDSA signatures using the
+/// brainpoolP384r1 curve of [RFC 5639] and SHA-384.
+///
+/// See "`ECDSA_*_ASN1` Details" in `ring::signature`'s module-level
+/// documentation for more details.
+///
+/// [RFC 5639]: https://tools.ietf.org/html/rfc5639
+pub static ECDSA_BRAINPOOLP384R1_SHA384_ASN1: EcdsaVerificationAlgorithm =
+    EcdsaVerificationAlgorithm {
+        ops: &bp384::PUBLIC_SCALAR_OPS,
+        digest_alg: &digest::SHA384,
+        split_rs: split_rs_asn1,
+        id: AlgorithmID::ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
+    };
+
 #[cfg(test)]
 mod tests {
     extern crate alloc;
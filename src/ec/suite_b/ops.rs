@@ -56,7 +56,7 @@ pub struct CommonOps {
     q: Modulus,
     pub n: Elem<Unencoded>,
 
-    pub a: Elem<R>, // Must be -3 mod q
+    pub a: Elem<R>, // Must be -3 mod q, except for brainpoolP384r1
     pub b: Elem<R>,
 
     // In all cases, `r`, `a`, and `b` may all alias each other.
@@ -88,6 +88,18 @@ impl CommonOps {
         self.elem_product(a, &ONE)
     }
 
+    #[inline]
+    pub fn elem_negated(&self, a: &Elem<R>) -> Elem<R> {
+        let num_limbs = self.num_limbs;
+        let mut r = Elem::zero();
+        limbs_sub_assign_mod(
+            &mut r.limbs[..num_limbs],
+            &a.limbs[..num_limbs],
+            &self.q.p[..num_limbs],
+        );
+        r
+    }
+
     #[inline]
     pub fn elem_mul(&self, a: &mut Elem<R>, b: &Elem<R>) {
         binary_op_assign(self.elem_mul_mont, a, b)
@@ -283,15 +295,29 @@ impl PublicScalarOps {
         }
     }
 
+    #[cfg(not(feature = "ct-everything"))]
     pub fn elem_equals_vartime(&self, a: &Elem<Unencoded>, b: &Elem<Unencoded>) -> bool {
         a.limbs[..self.public_key_ops.common.num_limbs]
             == b.limbs[..self.public_key_ops.common.num_limbs]
     }
 
+    #[cfg(not(feature = "ct-everything"))]
     pub fn elem_less_than(&self, a: &Elem<Unencoded>, b: &Elem<Unencoded>) -> bool {
         let num_limbs = self.public_key_ops.common.num_limbs;
         limbs_less_than_limbs_vartime(&a.limbs[..num_limbs], &b.limbs[..num_limbs])
     }
+
+    #[cfg(feature = "ct-everything")]
+    pub fn elem_equals_consttime(&self, a: &Elem<Unencoded>, b: &Elem<Unencoded>) -> LimbMask {
+        let num_limbs = self.public_key_ops.common.num_limbs;
+        limbs_equal_limbs_consttime(&a.limbs[..num_limbs], &b.limbs[..num_limbs])
+    }
+
+    #[cfg(feature = "ct-everything")]
+    pub fn elem_less_than_consttime(&self, a: &Elem<Unencoded>, b: &Elem<Unencoded>) -> LimbMask {
+        let num_limbs = self.public_key_ops.common.num_limbs;
+        limbs_less_than_limbs_consttime(&a.limbs[..num_limbs], &b.limbs[..num_limbs])
+    }
 }
 
 #[allow(non_snake_case)]
@@ -1168,6 +1194,9 @@ mod tests {
     }
 }
 
+pub mod bp384;
 mod elem;
+mod generic;
 pub mod p256;
 pub mod p384;
+pub mod sm2p256;
@@ -0,0 +1,151 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! brainpoolP384r1 ([RFC 5639] Section 3.6).
+//!
+//! Unlike P-256 and P-384, the prime has no special form and `a` isn't -3,
+//! so nothing here is specialized: the arithmetic is the shared, generic
+//! arithmetic of the `generic` module.
+//!
+//! [RFC 5639]: https://tools.ietf.org/html/rfc5639#section-3.6
+
+use super::{elem::binary_op, generic, Modulus, *};
+
+const NUM_LIMBS: usize = 384 / LIMB_BITS;
+
+pub static COMMON_OPS: CommonOps = CommonOps {
+    num_limbs: NUM_LIMBS,
+
+    q: Modulus {
+        p: limbs_from_hex("8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b412b1da197fb71123acd3a729901d1a71874700133107ec53"),
+        rr: limbs_from_hex("36bf6883178df842d5c6ef3ba57e052c621401919918d5af8e28f99cc9940899535283343d7fd965087cefff40b64bde"),
+    },
+    n: Elem::from_hex("8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b31f166e6cac0425a7cf3ab6af6b7fc3103b883202e9046565"),
+
+    a: Elem::from_hex("7c338021a2e8c0d1400a8fdf42b00c60e7ffe9e535529374936771b9d7f10db475d7f3fef157b07bdb26b895466c3c99"),
+    b: Elem::from_hex("453dcefae84686aa2b335681d1cd255d17413827fe77fed8cdb456c3f7216eda362ef7c8205a0fe31f05fdea00c8e16d"),
+
+    elem_mul_mont: bp384_elem_mul_mont,
+    elem_sqr_mont: bp384_elem_sqr_mont,
+
+    point_add_jacobian_impl: bp384_point_add,
+};
+
+const Q_N0: u64 = 0x9a6ea96cea9ec825;
+const N_N0: u64 = 0x5cfedd2a5cb5bb93;
+
+// 1 * R (mod q).
+const ONE: Elem<R> = Elem::from_hex(
+    "7346e17d5cc792d7f0a29081af19be20ead08ef612aba94bed4e25e68048eedc532c58d66fe2e58e78b8ffeccef813ad",
+);
+
+pub(super) static GENERATOR: (Elem<R>, Elem<R>) = (
+    Elem::from_hex("8500753388f53fc19cdd0dcfbacd0099068b264ef95c216494c378e99d202f2366fc80e8d5a886bfa189deebd438fbc1"),
+    Elem::from_hex("2cf4a062458968b5c61625664f21ddb6a180acd4d5719217f88309a38f0737fcf5e0d246c7996f55e738b3310de140a5"),
+);
+
+pub static PRIVATE_KEY_OPS: PrivateKeyOps = PrivateKeyOps {
+    common: &COMMON_OPS,
+    elem_inv_squared: bp384_elem_inv_squared,
+    point_mul_base_impl: bp384_point_mul_base_impl,
+    point_mul_impl: bp384_point_mul,
+};
+
+fn bp384_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
+    // Calculate a**-2 (mod q) == a**(q - 3) (mod q)
+    static Q_MINUS_3: [Limb; MAX_LIMBS] = limbs_from_hex("8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b412b1da197fb71123acd3a729901d1a71874700133107ec50");
+    generic::exp_public(&COMMON_OPS, bp384_elem_mul_mont, a, &Q_MINUS_3)
+}
+
+fn bp384_point_mul_base_impl(a: &Scalar) -> Point {
+    // XXX: Not efficient. TODO: Precompute multiples of the generator.
+    PRIVATE_KEY_OPS.point_mul(a, &GENERATOR)
+}
+
+pub static PUBLIC_KEY_OPS: PublicKeyOps = PublicKeyOps {
+    common: &COMMON_OPS,
+};
+
+pub static SCALAR_OPS: ScalarOps = ScalarOps {
+    common: &COMMON_OPS,
+    scalar_inv_to_mont_impl: bp384_scalar_inv_to_mont,
+    scalar_mul_mont: bp384_scalar_mul_mont,
+};
+
+pub static PUBLIC_SCALAR_OPS: PublicScalarOps = PublicScalarOps {
+    scalar_ops: &SCALAR_OPS,
+    public_key_ops: &PUBLIC_KEY_OPS,
+    twin_mul: |g_scalar, p_scalar, p_xy| {
+        twin_mul_inefficient(&PRIVATE_KEY_OPS, g_scalar, p_scalar, p_xy)
+    },
+
+    q_minus_n: Elem::from_hex("f39b6bacd3b2eb7bdd98f07a249d57614bbece10480386ee"),
+};
+
+pub static PRIVATE_SCALAR_OPS: PrivateScalarOps = PrivateScalarOps {
+    scalar_ops: &SCALAR_OPS,
+
+    oneRR_mod_n: Scalar::from_hex("ce8941a614e97c28f886dc965165fdb574a74cb52d748ff2a927e3b9802688a37264e202f2b6b6eac4ed3a2de771c8e"),
+};
+
+fn bp384_scalar_inv_to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+    // Calculate the modular inverse of scalar |a| using Fermat's Little
+    // Theorem:
+    //
+    //    a**-1 (mod n) == a**(n - 2) (mod n)
+    static N_MINUS_2: [Limb; MAX_LIMBS] = limbs_from_hex("8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b31f166e6cac0425a7cf3ab6af6b7fc3103b883202e9046563");
+    let a: Scalar<R> = binary_op(bp384_scalar_mul_mont, a, &PRIVATE_SCALAR_OPS.oneRR_mod_n);
+    generic::exp_public(&COMMON_OPS, bp384_scalar_mul_mont, &a, &N_MINUS_2)
+}
+
+unsafe extern "C" fn bp384_elem_mul_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+    b: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    generic::mul_mont(r, a, b, &COMMON_OPS.q.p, Q_N0, NUM_LIMBS);
+}
+
+unsafe extern "C" fn bp384_elem_sqr_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    // XXX: Inefficient. TODO: Make a dedicated squaring routine.
+    bp384_elem_mul_mont(r, a, a);
+}
+
+unsafe extern "C" fn bp384_scalar_mul_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+    b: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    generic::mul_mont(r, a, b, &COMMON_OPS.n.limbs, N_N0, NUM_LIMBS);
+}
+
+unsafe extern "C" fn bp384_point_add(
+    r: *mut Limb,   // [3][COMMON_OPS.num_limbs]
+    a: *const Limb, // [3][COMMON_OPS.num_limbs]
+    b: *const Limb, // [3][COMMON_OPS.num_limbs]
+) {
+    generic::point_add_jacobian(&COMMON_OPS, r, a, b);
+}
+
+unsafe extern "C" fn bp384_point_mul(
+    r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
+    p_scalar: *const Limb, // [COMMON_OPS.num_limbs]
+    p_x: *const Limb,      // [COMMON_OPS.num_limbs]
+    p_y: *const Limb,      // [COMMON_OPS.num_limbs]
+) {
+    generic::point_mul(&COMMON_OPS, &ONE, r, p_scalar, p_x, p_y);
+}
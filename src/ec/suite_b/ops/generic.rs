@@ -0,0 +1,291 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Arithmetic for curves that have no specialized implementation, shared by
+//! `bp384` and `sm2p256`.
+//!
+//! Field and scalar multiplication use generic Montgomery multiplication
+//! (`bn_mul_mont`), and the point formulas are the ones for arbitrary `a`.
+//! Everything is parameterized by the curve's `CommonOps`; each curve
+//! provides the `extern "C"` functions that `CommonOps` and `PrivateKeyOps`
+//! point to as thin wrappers around these.
+
+use super::{elem::binary_op, *};
+use crate::{
+    arithmetic::n0::N0,
+    limb::{limbs_are_zero_constant_time, limbs_sub_assign_mod},
+};
+
+// Returns `a**exponent`, where `exponent` is public. The time taken depends
+// only on `exponent`, never on `a`.
+pub(super) fn exp_public<M: Copy>(
+    ops: &CommonOps,
+    mul_mont: unsafe extern "C" fn(r: *mut Limb, a: *const Limb, b: *const Limb),
+    a: &elem::Elem<M, R>,
+    exponent: &[Limb; MAX_LIMBS],
+) -> elem::Elem<M, R> {
+    let bits = exponent[..ops.num_limbs]
+        .iter()
+        .rev()
+        .flat_map(|limb| (0..LIMB_BITS).rev().map(move |i| (limb >> i) & 1 == 1))
+        .skip_while(|bit| !bit);
+    let mut acc = *a;
+    for bit in bits.skip(1) {
+        acc = binary_op(mul_mont, &acc, &acc);
+        if bit {
+            acc = binary_op(mul_mont, &acc, a);
+        }
+    }
+    acc
+}
+
+// Sets `r = a * b * R**-1 (mod m)`, where `m` is `num_limbs` limbs and `n0`
+// is its Montgomery constant.
+pub(super) unsafe fn mul_mont(
+    r: *mut Limb,   // [num_limbs]
+    a: *const Limb, // [num_limbs]
+    b: *const Limb, // [num_limbs]
+    m: &[Limb],
+    n0: u64,
+    num_limbs: usize,
+) {
+    bn_mul_mont(r, a, b, m.as_ptr(), &N0::from(n0), num_limbs);
+}
+
+pub(super) unsafe fn point_add_jacobian(
+    ops: &CommonOps,
+    r: *mut Limb,   // [3][ops.num_limbs]
+    a: *const Limb, // [3][ops.num_limbs]
+    b: *const Limb, // [3][ops.num_limbs]
+) {
+    let a = Jacobian::load(ops, a);
+    let b = Jacobian::load(ops, b);
+    point_add(ops, &a, &b).store(ops, r);
+}
+
+// `one` is `1 * R (mod q)`.
+pub(super) unsafe fn point_mul(
+    ops: &CommonOps,
+    one: &Elem<R>,
+    r: *mut Limb,          // [3][ops.num_limbs]
+    p_scalar: *const Limb, // [ops.num_limbs]
+    p_x: *const Limb,      // [ops.num_limbs]
+    p_y: *const Limb,      // [ops.num_limbs]
+) {
+    const WINDOW_BITS: usize = 4;
+    const TABLE_LEN: usize = (1 << WINDOW_BITS) - 1;
+
+    let num_limbs = ops.num_limbs;
+    let scalar = core::slice::from_raw_parts(p_scalar, num_limbs);
+    let p = Jacobian {
+        x: load_elem(ops, p_x),
+        y: load_elem(ops, p_y),
+        z: *one,
+    };
+
+    // table[i] = (i + 1) * p.
+    let mut table = [p; TABLE_LEN];
+    table[1] = point_double(ops, &p);
+    for i in 2..TABLE_LEN {
+        table[i] = point_add(ops, &table[i - 1], &p);
+    }
+
+    let mut acc = Jacobian::at_infinity();
+    for i in (0..(num_limbs * LIMB_BITS / WINDOW_BITS)).rev() {
+        for _ in 0..WINDOW_BITS {
+            acc = point_double(ops, &acc);
+        }
+        let bit = i * WINDOW_BITS;
+        let window = (scalar[bit / LIMB_BITS] >> (bit % LIMB_BITS)) & ((1 << WINDOW_BITS) - 1);
+        acc = point_add(ops, &acc, &point_select(&table, window));
+    }
+    acc.store(ops, r);
+}
+
+#[derive(Clone, Copy)]
+struct Jacobian {
+    x: Elem<R>,
+    y: Elem<R>,
+    z: Elem<R>,
+}
+
+impl Jacobian {
+    fn at_infinity() -> Self {
+        Self {
+            x: Elem::zero(),
+            y: Elem::zero(),
+            z: Elem::zero(),
+        }
+    }
+
+    unsafe fn load(ops: &CommonOps, p: *const Limb) -> Self {
+        Self {
+            x: load_elem(ops, p),
+            y: load_elem(ops, p.add(ops.num_limbs)),
+            z: load_elem(ops, p.add(2 * ops.num_limbs)),
+        }
+    }
+
+    unsafe fn store(&self, ops: &CommonOps, r: *mut Limb) {
+        let num_limbs = ops.num_limbs;
+        let r = core::slice::from_raw_parts_mut(r, 3 * num_limbs);
+        r[..num_limbs].copy_from_slice(&self.x.limbs[..num_limbs]);
+        r[num_limbs..(2 * num_limbs)].copy_from_slice(&self.y.limbs[..num_limbs]);
+        r[(2 * num_limbs)..].copy_from_slice(&self.z.limbs[..num_limbs]);
+    }
+
+    fn is_at_infinity(&self, ops: &CommonOps) -> Limb {
+        limbs_are_zero_constant_time(&self.z.limbs[..ops.num_limbs]) as Limb
+    }
+}
+
+unsafe fn load_elem(ops: &CommonOps, p: *const Limb) -> Elem<R> {
+    let mut r = Elem::zero();
+    r.limbs[..ops.num_limbs].copy_from_slice(core::slice::from_raw_parts(p, ops.num_limbs));
+    r
+}
+
+// dbl-1998-cmo-2 from the Explicit-Formulas Database, which works for any
+// `a`. The double of the point at infinity (z == 0) is at infinity. There
+// are no points of order 2 since the group order is prime.
+fn point_double(ops: &CommonOps, p: &Jacobian) -> Jacobian {
+    let add = |a: &Elem<R>, b: &Elem<R>| add(ops, a, b);
+    let sub = |a: &Elem<R>, b: &Elem<R>| sub(ops, a, b);
+    let mul = |a: &Elem<R>, b: &Elem<R>| ops.elem_product(a, b);
+    let sqr = |a: &Elem<R>| ops.elem_squared(a);
+
+    let xx = sqr(&p.x);
+    let yy = sqr(&p.y);
+    let zz = sqr(&p.z);
+
+    // s = 4*x*yy
+    let s = mul(&p.x, &yy);
+    let s = add(&s, &s);
+    let s = add(&s, &s);
+
+    // m = 3*xx + a*zz**2
+    let m = add(&add(&xx, &xx), &xx);
+    let m = add(&m, &mul(&ops.a, &sqr(&zz)));
+
+    // x3 = m**2 - 2*s
+    let x = sub(&sub(&sqr(&m), &s), &s);
+
+    // y3 = m*(s - x3) - 8*yy**2
+    let yyyy = sqr(&yy);
+    let yyyy_2 = add(&yyyy, &yyyy);
+    let yyyy_4 = add(&yyyy_2, &yyyy_2);
+    let yyyy_8 = add(&yyyy_4, &yyyy_4);
+    let y = sub(&mul(&m, &sub(&s, &x)), &yyyy_8);
+
+    // z3 = 2*y*z
+    let yz = mul(&p.y, &p.z);
+    let z = add(&yz, &yz);
+
+    Jacobian { x, y, z }
+}
+
+// add-1998-cmo-2 from the Explicit-Formulas Database, plus constant-time
+// handling of the cases it doesn't cover: either input at infinity, or
+// both inputs equal. When `a == -b`, `h` is zero so the result is at
+// infinity.
+fn point_add(ops: &CommonOps, a: &Jacobian, b: &Jacobian) -> Jacobian {
+    let sub = |a: &Elem<R>, b: &Elem<R>| sub(ops, a, b);
+    let mul = |a: &Elem<R>, b: &Elem<R>| ops.elem_product(a, b);
+    let sqr = |a: &Elem<R>| ops.elem_squared(a);
+
+    let z1z1 = sqr(&a.z);
+    let z2z2 = sqr(&b.z);
+    let u1 = mul(&a.x, &z2z2);
+    let u2 = mul(&b.x, &z1z1);
+    let s1 = mul(&mul(&a.y, &b.z), &z2z2);
+    let s2 = mul(&mul(&b.y, &a.z), &z1z1);
+    let h = sub(&u2, &u1);
+    let r = sub(&s2, &s1);
+    let hh = sqr(&h);
+    let hhh = mul(&h, &hh);
+    let v = mul(&u1, &hh);
+
+    let x = sub(&sub(&sub(&sqr(&r), &hhh), &v), &v);
+    let y = sub(&mul(&r, &sub(&v, &x)), &mul(&s1, &hhh));
+    let z = mul(&mul(&a.z, &b.z), &h);
+    let sum = Jacobian { x, y, z };
+
+    let num_limbs = ops.num_limbs;
+    let a_is_inf = a.is_at_infinity(ops);
+    let b_is_inf = b.is_at_infinity(ops);
+    let equal = (limbs_are_zero_constant_time(&h.limbs[..num_limbs]) as Limb)
+        & (limbs_are_zero_constant_time(&r.limbs[..num_limbs]) as Limb)
+        & !a_is_inf
+        & !b_is_inf;
+
+    let result = point_select_if(equal, &point_double(ops, a), &sum);
+    let result = point_select_if(a_is_inf, b, &result);
+    point_select_if(b_is_inf, a, &result)
+}
+
+// Returns `table[index - 1]`, or the point at infinity if `index` is zero,
+// reading every entry of `table`.
+fn point_select(table: &[Jacobian], index: Limb) -> Jacobian {
+    let mut r = Jacobian::at_infinity();
+    for (i, entry) in (1..).zip(table) {
+        let equal = limbs_are_zero_constant_time(&[index ^ i]) as Limb;
+        r = point_select_if(equal, entry, &r);
+    }
+    r
+}
+
+// Returns `a` if `mask` is all ones, or `b` if it's zero.
+fn point_select_if(mask: Limb, a: &Jacobian, b: &Jacobian) -> Jacobian {
+    fn select(mask: Limb, a: &Elem<R>, b: &Elem<R>) -> Elem<R> {
+        let mut r = Elem::zero();
+        for ((r, a), b) in r.limbs.iter_mut().zip(&a.limbs).zip(&b.limbs) {
+            *r = (a & mask) | (b & !mask);
+        }
+        r
+    }
+    Jacobian {
+        x: select(mask, &a.x, &b.x),
+        y: select(mask, &a.y, &b.y),
+        z: select(mask, &a.z, &b.z),
+    }
+}
+
+fn add(ops: &CommonOps, a: &Elem<R>, b: &Elem<R>) -> Elem<R> {
+    let mut r = *a;
+    ops.elem_add(&mut r, b);
+    r
+}
+
+fn sub(ops: &CommonOps, a: &Elem<R>, b: &Elem<R>) -> Elem<R> {
+    let num_limbs = ops.num_limbs;
+    let mut r = *a;
+    limbs_sub_assign_mod(
+        &mut r.limbs[..num_limbs],
+        &b.limbs[..num_limbs],
+        &ops.q.p[..num_limbs],
+    );
+    r
+}
+
+prefixed_extern! {
+    // `r` and/or 'a' and/or 'b' may alias.
+    fn bn_mul_mont(
+        r: *mut Limb,
+        a: *const Limb,
+        b: *const Limb,
+        n: *const Limb,
+        n0: &N0,
+        num_limbs: c::size_t,
+    );
+}
@@ -16,6 +16,7 @@ use super::{
     elem::{binary_op, binary_op_assign},
     elem_sqr_mul, elem_sqr_mul_acc, Modulus, *,
 };
+use crate::ec;
 
 pub static COMMON_OPS: CommonOps = CommonOps {
     num_limbs: 256 / LIMB_BITS,
@@ -93,17 +94,66 @@ fn p256_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
     acc
 }
 
+// Calculates a**((q + 1) / 4) (mod q). Since q == 3 (mod 4), this is a square
+// root of `a` if `a` is a square.
+pub(in super::super) fn p256_elem_sqrt_candidate(a: &Elem<R>) -> Elem<R> {
+    // The exponent (q + 1) / 4 is:
+    //
+    //    0x3fffffffc0000000400000000000000000000000400000000000000000000000
+    //
+    // which is ((((2**32 - 1) << 32) + 1) << 96 + 1) << 94.
+
+    #[inline]
+    fn sqr_mul(a: &Elem<R>, squarings: usize, b: &Elem<R>) -> Elem<R> {
+        elem_sqr_mul(&COMMON_OPS, a, squarings, b)
+    }
+
+    #[inline]
+    fn sqr_mul_acc(a: &mut Elem<R>, squarings: usize, b: &Elem<R>) {
+        elem_sqr_mul_acc(&COMMON_OPS, a, squarings, b)
+    }
+
+    let b_1 = &a;
+    let b_11 = sqr_mul(b_1, 1, b_1);
+    let f = sqr_mul(&b_11, 2, &b_11);
+    let ff = sqr_mul(&f, 4, &f);
+    let ffff = sqr_mul(&ff, 8, &ff);
+    let ffffffff = sqr_mul(&ffff, 16, &ffff);
+
+    // ffffffff00000001
+    let mut acc = sqr_mul(&ffffffff, 32, b_1);
+
+    // ffffffff00000001000000000000000000000001
+    sqr_mul_acc(&mut acc, 96, b_1);
+
+    // 3fffffffc0000000400000000000000000000000400000000000000000000000
+    for _ in 0..94 {
+        COMMON_OPS.elem_square(&mut acc);
+    }
+
+    acc
+}
+
 fn p256_point_mul_base_impl(g_scalar: &Scalar) -> Point {
+    p256_point_mul_base_(g_scalar, ec::TableScan::DEFAULT)
+}
+
+fn p256_point_mul_base_(g_scalar: &Scalar, table_scan: ec::TableScan) -> Point {
     prefixed_extern! {
         fn p256_point_mul_base(
             r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
             g_scalar: *const Limb, // [COMMON_OPS.num_limbs]
+            scan_all: c::int,
         );
     }
 
     let mut r = Point::new_at_infinity();
     unsafe {
-        p256_point_mul_base(r.xyz.as_mut_ptr(), g_scalar.limbs.as_ptr());
+        p256_point_mul_base(
+            r.xyz.as_mut_ptr(),
+            g_scalar.limbs.as_ptr(),
+            table_scan.into(),
+        );
     }
     r
 }
@@ -135,12 +185,18 @@ pub static PUBLIC_SCALAR_OPS: PublicScalarOps = PublicScalarOps {
 
 #[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
 fn twin_mul_nistz256(g_scalar: &Scalar, p_scalar: &Scalar, p_xy: &(Elem<R>, Elem<R>)) -> Point {
+    #[cfg(not(feature = "ct-everything"))]
     let scaled_g = point_mul_base_vartime(g_scalar);
+    #[cfg(feature = "ct-everything")]
+    let scaled_g = p256_point_mul_base_impl(g_scalar);
     let scaled_p = PRIVATE_KEY_OPS.point_mul(p_scalar, p_xy);
     PRIVATE_KEY_OPS.common.point_sum(&scaled_g, &scaled_p)
 }
 
-#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[cfg(all(
+    any(target_arch = "aarch64", target_arch = "x86_64"),
+    not(feature = "ct-everything")
+))]
 fn point_mul_base_vartime(g_scalar: &Scalar) -> Point {
     prefixed_extern! {
         fn p256_point_mul_base_vartime(r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
@@ -322,10 +378,50 @@ prefixed_extern! {
 
 #[cfg(test)]
 mod tests {
-    #[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+    use super::*;
+    use crate::rand;
+
+    #[test]
+    fn p256_point_mul_base_table_scan_test() {
+        fn check(scalar: &Scalar) {
+            let window = p256_point_mul_base_(scalar, ec::TableScan::Window);
+            let full = p256_point_mul_base_(scalar, ec::TableScan::Full);
+            assert_eq!(window.xyz, full.xyz);
+        }
+
+        for k in [0, 1, 2] {
+            let mut scalar = Scalar::zero();
+            scalar.limbs[0] = k;
+            check(&scalar);
+        }
+        let mut n_minus_1 = Scalar::zero();
+        n_minus_1.limbs[..COMMON_OPS.num_limbs]
+            .copy_from_slice(&COMMON_OPS.n.limbs[..COMMON_OPS.num_limbs]);
+        n_minus_1.limbs[0] -= 1;
+        check(&n_minus_1);
+
+        let rng = rand::SystemRandom::new();
+        let mut samples = 0;
+        while samples < 100 {
+            let bytes: [u8; 32] = rand::generate(&rng).unwrap().expose();
+            if let Ok(scalar) = scalar_parse_big_endian_variable(
+                &COMMON_OPS,
+                AllowZero::Yes,
+                untrusted::Input::from(&bytes),
+            ) {
+                check(&scalar);
+                samples += 1;
+            }
+        }
+    }
+
+    #[cfg(all(
+        any(target_arch = "aarch64", target_arch = "x86_64"),
+        not(feature = "ct-everything")
+    ))]
     #[test]
     fn p256_point_mul_base_vartime_test() {
-        use super::{super::tests::point_mul_base_tests, *};
+        use super::super::tests::point_mul_base_tests;
         point_mul_base_tests(
             &PRIVATE_KEY_OPS,
             point_mul_base_vartime,
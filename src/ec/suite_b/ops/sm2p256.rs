@@ -0,0 +1,158 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! sm2p256v1 ([GB/T 32918.5-2017]), the curve of the SM2 signature scheme.
+//!
+//! `a` is -3, but the prime isn't supported by any of the specialized field
+//! arithmetic, so like `bp384` this uses the arithmetic of the `generic`
+//! module.
+//!
+//! Only SM2 signature verification uses this curve. `PRIVATE_KEY_OPS` and
+//! `PRIVATE_SCALAR_OPS` exist because verification's `twin_mul` is built
+//! on `PrivateKeyOps::point_mul`, and scalar inversion needs
+//! `PRIVATE_SCALAR_OPS.oneRR_mod_n`; nothing creates SM2 private keys.
+//!
+//! [GB/T 32918.5-2017]: https://datatracker.ietf.org/doc/html/draft-shen-sm2-ecdsa-02
+
+use super::{elem::binary_op, generic, Modulus, *};
+
+const NUM_LIMBS: usize = 256 / LIMB_BITS;
+
+pub static COMMON_OPS: CommonOps = CommonOps {
+    num_limbs: NUM_LIMBS,
+
+    q: Modulus {
+        p: limbs_from_hex("fffffffeffffffffffffffffffffffffffffffff00000000ffffffffffffffff"),
+        rr: limbs_from_hex("400000002000000010000000100000002ffffffff0000000200000003"),
+    },
+    n: Elem::from_hex("fffffffeffffffffffffffffffffffff7203df6b21c6052b53bbf40939d54123"),
+
+    a: Elem::from_hex("fffffffbfffffffffffffffffffffffffffffffc00000003fffffffffffffffc"),
+    b: Elem::from_hex("240fe188ba20e2c8527981505ea51c3c71cf379ae9b537ab90d230632bc0dd42"),
+
+    elem_mul_mont: sm2p256_elem_mul_mont,
+    elem_sqr_mont: sm2p256_elem_sqr_mont,
+
+    point_add_jacobian_impl: sm2p256_point_add,
+};
+
+const Q_N0: u64 = 0x1;
+const N_N0: u64 = 0x327f9e8872350975;
+
+// 1 * R (mod q).
+const ONE: Elem<R> = Elem::from_hex("100000000000000000000000000000000ffffffff0000000000000001");
+
+pub(super) static GENERATOR: (Elem<R>, Elem<R>) = (
+    Elem::from_hex("91167a5ee1c13b05d6a1ed99ac24c3c33e7981eddca6c05061328990f418029e"),
+    Elem::from_hex("63cd65d481d735bd8d4cfb066e2a48f8c1f5e5788d3295fac1354e593c2d0ddd"),
+);
+
+pub static PRIVATE_KEY_OPS: PrivateKeyOps = PrivateKeyOps {
+    common: &COMMON_OPS,
+    elem_inv_squared: sm2p256_elem_inv_squared,
+    point_mul_base_impl: sm2p256_point_mul_base_impl,
+    point_mul_impl: sm2p256_point_mul,
+};
+
+fn sm2p256_elem_inv_squared(a: &Elem<R>) -> Elem<R> {
+    // Calculate a**-2 (mod q) == a**(q - 3) (mod q)
+    static Q_MINUS_3: [Limb; MAX_LIMBS] =
+        limbs_from_hex("fffffffeffffffffffffffffffffffffffffffff00000000fffffffffffffffc");
+    generic::exp_public(&COMMON_OPS, sm2p256_elem_mul_mont, a, &Q_MINUS_3)
+}
+
+fn sm2p256_point_mul_base_impl(a: &Scalar) -> Point {
+    // XXX: Not efficient. TODO: Precompute multiples of the generator.
+    PRIVATE_KEY_OPS.point_mul(a, &GENERATOR)
+}
+
+pub static PUBLIC_KEY_OPS: PublicKeyOps = PublicKeyOps {
+    common: &COMMON_OPS,
+};
+
+pub static SCALAR_OPS: ScalarOps = ScalarOps {
+    common: &COMMON_OPS,
+    scalar_inv_to_mont_impl: sm2p256_scalar_inv_to_mont,
+    scalar_mul_mont: sm2p256_scalar_mul_mont,
+};
+
+pub static PUBLIC_SCALAR_OPS: PublicScalarOps = PublicScalarOps {
+    scalar_ops: &SCALAR_OPS,
+    public_key_ops: &PUBLIC_KEY_OPS,
+    twin_mul: |g_scalar, p_scalar, p_xy| {
+        twin_mul_inefficient(&PRIVATE_KEY_OPS, g_scalar, p_scalar, p_xy)
+    },
+
+    q_minus_n: Elem::from_hex("8dfc2093de39fad5ac440bf6c62abedc"),
+};
+
+pub static PRIVATE_SCALAR_OPS: PrivateScalarOps = PrivateScalarOps {
+    scalar_ops: &SCALAR_OPS,
+
+    oneRR_mod_n: Scalar::from_hex(
+        "1eb5e412a22b3d3b620fc84c3affe0d43464504ade6fa2fa901192af7c114f20",
+    ),
+};
+
+fn sm2p256_scalar_inv_to_mont(a: &Scalar<Unencoded>) -> Scalar<R> {
+    // Calculate the modular inverse of scalar |a| using Fermat's Little
+    // Theorem:
+    //
+    //    a**-1 (mod n) == a**(n - 2) (mod n)
+    static N_MINUS_2: [Limb; MAX_LIMBS] =
+        limbs_from_hex("fffffffeffffffffffffffffffffffff7203df6b21c6052b53bbf40939d54121");
+    let a: Scalar<R> = binary_op(sm2p256_scalar_mul_mont, a, &PRIVATE_SCALAR_OPS.oneRR_mod_n);
+    generic::exp_public(&COMMON_OPS, sm2p256_scalar_mul_mont, &a, &N_MINUS_2)
+}
+
+unsafe extern "C" fn sm2p256_elem_mul_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+    b: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    generic::mul_mont(r, a, b, &COMMON_OPS.q.p, Q_N0, NUM_LIMBS);
+}
+
+unsafe extern "C" fn sm2p256_elem_sqr_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    // XXX: Inefficient. TODO: Make a dedicated squaring routine.
+    sm2p256_elem_mul_mont(r, a, a);
+}
+
+unsafe extern "C" fn sm2p256_scalar_mul_mont(
+    r: *mut Limb,   // [COMMON_OPS.num_limbs]
+    a: *const Limb, // [COMMON_OPS.num_limbs]
+    b: *const Limb, // [COMMON_OPS.num_limbs]
+) {
+    generic::mul_mont(r, a, b, &COMMON_OPS.n.limbs, N_N0, NUM_LIMBS);
+}
+
+unsafe extern "C" fn sm2p256_point_add(
+    r: *mut Limb,   // [3][COMMON_OPS.num_limbs]
+    a: *const Limb, // [3][COMMON_OPS.num_limbs]
+    b: *const Limb, // [3][COMMON_OPS.num_limbs]
+) {
+    generic::point_add_jacobian(&COMMON_OPS, r, a, b);
+}
+
+unsafe extern "C" fn sm2p256_point_mul(
+    r: *mut Limb,          // [3][COMMON_OPS.num_limbs]
+    p_scalar: *const Limb, // [COMMON_OPS.num_limbs]
+    p_x: *const Limb,      // [COMMON_OPS.num_limbs]
+    p_y: *const Limb,      // [COMMON_OPS.num_limbs]
+) {
+    generic::point_mul(&COMMON_OPS, &ONE, r, p_scalar, p_x, p_y);
+}
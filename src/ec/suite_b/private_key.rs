@@ -147,6 +147,18 @@ pub fn public_from_private(
     big_endian_affine_from_jacobian(ops, Some(x_out), Some(y_out), &my_public_key)
 }
 
+// Like `public_from_private`, except only the x-coordinate is output.
+pub fn public_x_from_private(
+    ops: &PrivateKeyOps,
+    public_out: &mut [u8],
+    my_private_key: &ec::Seed,
+) -> Result<(), error::Unspecified> {
+    debug_assert_eq!(public_out.len(), ops.common.num_limbs * LIMB_BYTES);
+    let my_private_key = private_key_as_scalar(ops, my_private_key);
+    let my_public_key = ops.point_mul_base(&my_private_key);
+    big_endian_affine_from_jacobian(ops, Some(public_out), None, &my_public_key)
+}
+
 pub fn affine_from_jacobian(
     ops: &PrivateKeyOps,
     p: &Point,
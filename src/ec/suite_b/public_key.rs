@@ -16,7 +16,7 @@
 //! ECDH agreement).
 
 use super::{ops::*, verify_affine_point_is_on_the_curve};
-use crate::{arithmetic::montgomery::*, error};
+use crate::{arithmetic::montgomery::*, error, limb::LimbMask};
 
 /// Parses a public key encoded in uncompressed form. The key is validated
 /// using the ECC Partial Public-Key Validation Routine from
@@ -64,6 +64,49 @@ pub fn parse_uncompressed_point(
     Ok((x, y))
 }
 
+/// Parses a P-256 public key encoded as just its big-endian x-coordinate, as
+/// in the compact representation of [RFC 6090] Section 4.2. Of the two points
+/// with that x-coordinate, the one with the even y-coordinate is chosen.
+///
+/// The key is validated like in `parse_uncompressed_point`; in particular,
+/// the encoding is rejected if `x` isn't less than q or if there is no point
+/// with x-coordinate `x`.
+///
+/// [RFC 6090]: https://tools.ietf.org/html/rfc6090#section-4.2
+pub fn parse_p256_x_only_point(
+    input: untrusted::Input,
+) -> Result<(Elem<R>, Elem<R>), error::Unspecified> {
+    let ops = &p256::PUBLIC_KEY_OPS;
+    let common = ops.common;
+
+    let x = input.read_all(error::Unspecified, |input| ops.elem_parse(input))?;
+
+    // y**2 == (x**2 + a)*x + b  (mod q)
+    let mut rhs = common.elem_squared(&x);
+    common.elem_add(&mut rhs, &common.a);
+    common.elem_mul(&mut rhs, &x);
+    common.elem_add(&mut rhs, &common.b);
+
+    // If `rhs` isn't a square then there's no point with x-coordinate `x`.
+    let y = p256::p256_elem_sqrt_candidate(&rhs);
+    if common.elems_are_equal(&common.elem_squared(&y), &rhs) != LimbMask::True {
+        return Err(error::Unspecified);
+    }
+
+    // The public key is public so the choice of `y` needn't be constant-time.
+    let y = if common.elem_unencoded(&y).limbs[0] & 1 == 0 {
+        y
+    } else {
+        common.elem_negated(&y)
+    };
+
+    // This was effectively done above, but do the same check as
+    // `parse_uncompressed_point` anyway.
+    verify_affine_point_is_on_the_curve(common, (&x, &y))?;
+
+    Ok((x, y))
+}
+
 #[cfg(test)]
 mod tests {
     use super::{super::ops, *};
@@ -95,6 +138,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_p256_x_only_point_test() {
+        // The generator's y-coordinate is odd, so decompressing its
+        // x-coordinate must yield -G.
+        const G: &str = "04\
+            6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296\
+            4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+        let g = test::from_hex(G).unwrap();
+        let ops = &ops::p256::PUBLIC_KEY_OPS;
+        let (gx, gy) = parse_uncompressed_point(ops, untrusted::Input::from(&g)).unwrap();
+
+        let (x, y) = parse_p256_x_only_point(untrusted::Input::from(&g[1..33])).unwrap();
+        assert_eq!(ops.common.elems_are_equal(&x, &gx), LimbMask::True);
+        assert_eq!(
+            ops.common
+                .elems_are_equal(&y, &ops.common.elem_negated(&gy)),
+            LimbMask::True
+        );
+    }
+
     fn public_key_ops_from_curve_name(curve_name: &str) -> &'static PublicKeyOps {
         if curve_name == "P-256" {
             &ops::p256::PUBLIC_KEY_OPS
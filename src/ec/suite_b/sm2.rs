@@ -0,0 +1,247 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SM2 signature verification ([GB/T 32918.2-2016]) with SM3.
+//!
+//! [GB/T 32918.2-2016]: https://datatracker.ietf.org/doc/html/draft-shen-sm2-ecdsa-02
+
+use super::{
+    ecdsa::verification::verify_x_mod_n_equals,
+    ops::{sm2p256, *},
+    public_key::parse_uncompressed_point,
+};
+use crate::{
+    digest,
+    error::{self, VerifyFailure},
+    io::der,
+    limb::{self, LIMB_BYTES},
+    sealed, signature,
+};
+
+/// An SM2 verification algorithm.
+///
+/// The message is hashed with SM3 together with `Z_A`, a hash of the
+/// signer's distinguishing identifier (user ID), the curve parameters and the
+/// public key. The verifier must use the same user ID as the signer.
+pub struct Sm2VerificationAlgorithm {
+    user_id: &'static [u8],
+}
+
+impl core::fmt::Debug for Sm2VerificationAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Sm2VerificationAlgorithm")
+            .field("user_id", &crate::debug::HexStr(self.user_id))
+            .finish()
+    }
+}
+
+/// The user ID used when none is specified, from GB/T 35276-2017.
+pub const SM2_DEFAULT_USER_ID: &[u8] = b"1234567812345678";
+
+/// Verification of ASN.1 DER-encoded SM2 signatures using the sm2p256v1 curve
+/// and SM3, with the default user ID [`SM2_DEFAULT_USER_ID`].
+///
+/// Signatures are encoded like ECDSA signatures, as
+/// `SEQUENCE { INTEGER r, INTEGER s }`.
+pub static SM2_SM3_ASN1: Sm2VerificationAlgorithm =
+    Sm2VerificationAlgorithm::with_user_id(SM2_DEFAULT_USER_ID);
+
+impl Sm2VerificationAlgorithm {
+    /// An algorithm like [`SM2_SM3_ASN1`] that uses `user_id` as the user
+    /// ID.
+    ///
+    /// Verification with this algorithm always fails if `user_id` is 8192
+    /// bytes or longer, since its length in bits must fit in 16 bits.
+    pub const fn with_user_id(user_id: &'static [u8]) -> Self {
+        Self { user_id }
+    }
+
+    /// The user ID used by [`signature::VerificationAlgorithm::verify()`].
+    pub fn user_id(&self) -> &'static [u8] {
+        self.user_id
+    }
+
+    /// Verifies `signature` of `msg` with `public_key` using `user_id`
+    /// instead of the algorithm's user ID.
+    pub fn verify_with_user_id(
+        &self,
+        public_key: &[u8],
+        user_id: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        self.verify_(
+            untrusted::Input::from(public_key),
+            user_id,
+            msg,
+            untrusted::Input::from(signature),
+        )
+        .map_err(error::Unspecified::from)
+    }
+
+    fn verify_(
+        &self,
+        public_key: untrusted::Input,
+        user_id: &[u8],
+        msg: &[u8],
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        if !signature::VerificationAlgorithm::signature_len_hint(self).contains(signature.len()) {
+            return Err(VerifyFailure::MalformedSignature);
+        }
+        verify(public_key, user_id, msg, signature)
+    }
+}
+
+impl signature::VerificationAlgorithm for Sm2VerificationAlgorithm {
+    fn verify(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        self.verify_(
+            public_key,
+            self.user_id,
+            msg.as_slice_less_safe(),
+            signature,
+        )
+        .map_err(error::Unspecified::from)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        self.verify_(
+            public_key,
+            self.user_id,
+            msg.as_slice_less_safe(),
+            signature,
+        )
+    }
+
+    fn signature_len_hint(&self) -> signature::SignatureLen {
+        // SEQUENCE { INTEGER r, INTEGER s }, where each INTEGER is one byte
+        // up to 32 bytes plus a leading zero.
+        const MIN_LEN: usize = 2 + (2 * (2 + 1));
+        const MAX_LEN: usize = 2 + (2 * (2 + SCALAR_LEN + 1));
+        signature::SignatureLen::Range(MIN_LEN, MAX_LEN)
+    }
+}
+
+impl sealed::Sealed for Sm2VerificationAlgorithm {}
+
+const SCALAR_LEN: usize = 256 / 8;
+
+// a || b || xG || yG, each as a 32-byte big-endian integer.
+static CURVE_PARAMS: [u8; 4 * SCALAR_LEN] = [
+    0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfc,
+    0x28, 0xe9, 0xfa, 0x9e, 0x9d, 0x9f, 0x5e, 0x34, 0x4d, 0x5a, 0x9e, 0x4b, 0xcf, 0x65, 0x09, 0xa7,
+    0xf3, 0x97, 0x89, 0xf5, 0x15, 0xab, 0x8f, 0x92, 0xdd, 0xbc, 0xbd, 0x41, 0x4d, 0x94, 0x0e, 0x93,
+    0x32, 0xc4, 0xae, 0x2c, 0x1f, 0x19, 0x81, 0x19, 0x5f, 0x99, 0x04, 0x46, 0x6a, 0x39, 0xc9, 0x94,
+    0x8f, 0xe3, 0x0b, 0xbf, 0xf2, 0x66, 0x0b, 0xe1, 0x71, 0x5a, 0x45, 0x89, 0x33, 0x4c, 0x74, 0xc7,
+    0xbc, 0x37, 0x36, 0xa2, 0xf4, 0xf6, 0x77, 0x9c, 0x59, 0xbd, 0xce, 0xe3, 0x6b, 0x69, 0x21, 0x53,
+    0xd0, 0xa9, 0x87, 0x7c, 0xc6, 0x2a, 0x47, 0x40, 0x02, 0xdf, 0x32, 0xe5, 0x21, 0x39, 0xf0, 0xa0,
+];
+
+// GB/T 32918.2-2016 Section 7.1.
+fn verify(
+    public_key: untrusted::Input,
+    user_id: &[u8],
+    msg: &[u8],
+    signature: untrusted::Input,
+) -> Result<(), VerifyFailure> {
+    let ops = &sm2p256::PUBLIC_SCALAR_OPS;
+    let public_key_ops = ops.public_key_ops;
+    let cops = public_key_ops.common;
+
+    let peer_pub_key = parse_uncompressed_point(public_key_ops, public_key)
+        .map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
+
+    // B1 and B2: r and s must be in [1, n - 1].
+    let (r, s) = signature
+        .read_all(error::Unspecified, |input| {
+            der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
+                let r = der::nonnegative_integer(input)?;
+                let s = der::nonnegative_integer(input)?;
+                Ok((r, s))
+            })
+        })
+        .map_err(|error::Unspecified| VerifyFailure::MalformedSignature)?;
+    let parse_scalar = |value| {
+        scalar_parse_big_endian_variable(cops, limb::AllowZero::No, value)
+            .map_err(|error::Unspecified| VerifyFailure::OutOfRangeComponent)
+    };
+    let r = parse_scalar(r)?;
+    let s = parse_scalar(s)?;
+
+    // B3 and B4: e = SM3(Z_A || M), as an integer reduced mod n. The user ID
+    // is part of the signer's identity, like the public key.
+    let za = za(user_id, public_key.as_slice_less_safe())
+        .map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
+    let e = {
+        let mut ctx = digest::Context::new(&digest::SM3);
+        ctx.update(za.as_ref());
+        ctx.update(msg);
+        let h = ctx.finish();
+        debug_assert_eq!(h.as_ref().len(), cops.num_limbs * LIMB_BYTES);
+        scalar_parse_big_endian_partially_reduced_variable_consttime(
+            cops,
+            limb::AllowZero::Yes,
+            untrusted::Input::from(h.as_ref()),
+        )
+        .map_err(|error::Unspecified| VerifyFailure::Mismatch)?
+    };
+
+    // B5: t = (r + s) mod n, which must not be zero.
+    let t = scalar_sum(cops, &r, &s);
+    if cops.is_zero(&t) {
+        return Err(VerifyFailure::OutOfRangeComponent);
+    }
+
+    // B6: (x1, y1) = s*G + t*P.
+    let product = (ops.twin_mul)(&s, &t, &peer_pub_key);
+
+    // B7: R = (e + x1) mod n must equal r; equivalently x1 mod n must equal
+    // (r - e) mod n.
+    let mut v = r;
+    limb::limbs_sub_assign_mod(
+        &mut v.limbs[..cops.num_limbs],
+        &e.limbs[..cops.num_limbs],
+        &cops.n.limbs[..cops.num_limbs],
+    );
+    verify_x_mod_n_equals(ops, &product, &v).map_err(|error::Unspecified| VerifyFailure::Mismatch)
+}
+
+// Z_A = SM3(ENTL_A || ID_A || a || b || x_G || y_G || x_A || y_A), where
+// ENTL_A is the length of ID_A in bits as a 16-bit big-endian integer.
+fn za(user_id: &[u8], public_key: &[u8]) -> Result<digest::Digest, error::Unspecified> {
+    let entl = user_id
+        .len()
+        .checked_mul(8)
+        .and_then(|bits| u16::try_from(bits).ok())
+        .ok_or(error::Unspecified)?;
+    let mut ctx = digest::Context::new(&digest::SM3);
+    ctx.update(&entl.to_be_bytes());
+    ctx.update(user_id);
+    ctx.update(&CURVE_PARAMS);
+    // Skip the leading 0x04; `public_key` was already validated.
+    ctx.update(&public_key[1..]);
+    Ok(ctx.finish())
+}
@@ -17,6 +17,14 @@
 #[cfg(feature = "std")]
 extern crate std;
 
+mod verify_failure;
+
+#[cfg(feature = "diagnostics")]
+pub use self::verify_failure::VerifyFailure;
+
+#[cfg(not(feature = "diagnostics"))]
+pub(crate) use self::verify_failure::VerifyFailure;
+
 /// An error with absolutely no details.
 ///
 /// *ring* uses this unit type as the error type in most of its results
@@ -127,6 +135,10 @@ impl From<core::array::TryFromSliceError> for Unspecified {
 ///  * Wrong algorithm: The key is not valid for the algorithm in which it was
 ///    being used.
 ///
+///  * Out of memory: Memory couldn't be allocated for the key. Only APIs
+///    that document this, such as [`crate::rsa::KeyPair::try_from_pkcs8()`],
+///    report allocation failure instead of aborting.
+///
 ///  * Unexpected errors: Report this as a bug.
 #[derive(Copy, Clone, Debug)]
 pub struct KeyRejected(&'static str);
@@ -145,16 +157,10 @@ impl KeyRejected {
         Self("InvalidEncoding")
     }
 
-    // XXX: See the comment at the call site.
-    pub(crate) fn rng_failed() -> Self {
-        Self("RNG failed")
-    }
-
     pub(crate) fn public_key_is_missing() -> Self {
         Self("PublicKeyIsMissing")
     }
 
-    #[cfg(feature = "alloc")]
     pub(crate) fn too_small() -> Self {
         Self("TooSmall")
     }
@@ -177,6 +183,11 @@ impl KeyRejected {
         Self("PrivateModulusLenNotMultipleOf512Bits")
     }
 
+    #[cfg(feature = "alloc")]
+    pub(crate) fn out_of_memory() -> Self {
+        Self("OutOfMemory")
+    }
+
     pub(crate) fn unexpected_error() -> Self {
         Self("UnexpectedError")
     }
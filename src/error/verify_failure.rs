@@ -0,0 +1,72 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::Unspecified;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// The reason a signature was rejected by
+/// [`crate::signature::VerificationAlgorithm::verify_diagnostic()`].
+///
+/// This is for counting and logging rejected signatures. Every input to
+/// signature verification is public, and the reason is determined only by
+/// which check failed first, in an order that is fixed for each algorithm;
+/// the verification equation is always checked last, and nothing computed
+/// from it other than its result is reported. So the reason reveals nothing
+/// that the inputs don't.
+///
+/// More variants may be added, and an input that fails several checks may be
+/// classified differently in future versions if the checks are reordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyFailure {
+    /// The signature has the wrong length or isn't encoded correctly; e.g.
+    /// it is BER but not DER.
+    MalformedSignature,
+
+    /// The public key isn't encoded correctly, isn't a valid key, or isn't
+    /// supported by the algorithm; e.g. it is a point that isn't on the curve
+    /// or an RSA key that is too small.
+    MalformedKey,
+
+    /// The signature is encoded correctly but one of its components is out of
+    /// range; e.g. an ECDSA `r` or `s` that is zero or at least the group
+    /// order, or an RSA signature that is at least the modulus.
+    OutOfRangeComponent,
+
+    /// The signature and public key are well-formed, but the signature isn't
+    /// valid for the message.
+    Mismatch,
+}
+
+impl core::fmt::Display for VerifyFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::MalformedSignature => "MalformedSignature",
+            Self::MalformedKey => "MalformedKey",
+            Self::OutOfRangeComponent => "OutOfRangeComponent",
+            Self::Mismatch => "Mismatch",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyFailure {}
+
+impl From<VerifyFailure> for Unspecified {
+    fn from(_: VerifyFailure) -> Self {
+        Self
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Public key fingerprints.
+//!
+//! A fingerprint is the SHA-256 digest of a canonical encoding of a public
+//! key. The canonicalization rules below are frozen; a given public key will
+//! always have the same fingerprint in every version of *ring*.
+//!
+//! * The SPKI fingerprint of a key is the SHA-256 digest of the DER encoding
+//!   of its X.509 `SubjectPublicKeyInfo` ([RFC 5280 Section 4.1]). This is
+//!   the same value as
+//!   `openssl pkey -pubout -outform DER | openssl dgst -sha256`.
+//!   - Ed25519 keys use the `id-Ed25519` algorithm identifier with absent
+//!     parameters ([RFC 8410]).
+//!   - ECDSA keys use the `id-ecPublicKey` algorithm identifier with the
+//!     named curve as its parameters, and the uncompressed public point
+//!     ([RFC 5480]).
+//!   - RSA keys use the `rsaEncryption` algorithm identifier with NULL
+//!     parameters, and the DER-encoded `RSAPublicKey` ([RFC 3279]).
+//!
+//! * The JWK thumbprint of a key is the SHA-256 digest of its canonical JSON
+//!   encoding as defined in [RFC 7638]: only the required members, in
+//!   lexicographic order, with no whitespace.
+//!
+//! Nothing is truncated. [`PublicKeyFingerprint`] formats as lowercase hex
+//! with `Display`, and as unpadded base64url with
+//! [`PublicKeyFingerprint::base64url()`].
+//!
+//! [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+//! [RFC 8410]: https://tools.ietf.org/html/rfc8410
+//! [RFC 5480]: https://tools.ietf.org/html/rfc5480
+//! [RFC 3279]: https://tools.ietf.org/html/rfc3279
+//! [RFC 7638]: https://tools.ietf.org/html/rfc7638
+
+use crate::{b64, digest, io::der};
+
+/// The length of a fingerprint, in bytes.
+pub const FINGERPRINT_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+/// Returns the SHA-256 digest of `spki_der`, the DER encoding of a
+/// `SubjectPublicKeyInfo`.
+///
+/// `spki_der` is not parsed or validated; it is hashed exactly as given.
+pub fn spki_sha256(spki_der: &[u8]) -> [u8; FINGERPRINT_LEN] {
+    sha256(digest::digest(&digest::SHA256, spki_der))
+}
+
+/// Returns the [RFC 7638] thumbprint of a JWK.
+///
+/// `canonical_jwk` must already be in the canonical form required by RFC 7638;
+/// it is hashed exactly as given.
+///
+/// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+pub fn jwk_thumbprint(canonical_jwk: &[u8]) -> [u8; FINGERPRINT_LEN] {
+    sha256(digest::digest(&digest::SHA256, canonical_jwk))
+}
+
+/// A public key fingerprint.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKeyFingerprint([u8; FINGERPRINT_LEN]);
+
+derive_debug_self_as_ref_hex_bytes!(PublicKeyFingerprint);
+
+impl PublicKeyFingerprint {
+    /// Returns a value that formats the fingerprint as unpadded base64url.
+    pub fn base64url(&self) -> Base64Url<'_> {
+        Base64Url(self)
+    }
+}
+
+impl AsRef<[u8]> for PublicKeyFingerprint {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<PublicKeyFingerprint> for [u8; FINGERPRINT_LEN] {
+    fn from(fingerprint: PublicKeyFingerprint) -> Self {
+        fingerprint.0
+    }
+}
+
+/// Formats the fingerprint as lowercase hex.
+impl core::fmt::Display for PublicKeyFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        crate::debug::write_hex_bytes(f, &self.0)
+    }
+}
+
+/// The unpadded base64url encoding of a [`PublicKeyFingerprint`].
+pub struct Base64Url<'a>(&'a PublicKeyFingerprint);
+
+impl core::fmt::Display for Base64Url<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        b64::encode(&b64::URL_SAFE, &self.0 .0, f)
+    }
+}
+
+/// Computes the SPKI fingerprint of a public key whose algorithm identifier
+/// has the value (not including the tag and length) `alg_id_value`.
+pub(crate) fn spki_fingerprint(alg_id_value: &[u8], public_key: &[u8]) -> PublicKeyFingerprint {
+    // The `SubjectPublicKeyInfo` is hashed as it is encoded, without being
+    // buffered.
+    let alg_id_len = tlv_len(alg_id_value.len());
+    let public_key_len = tlv_len(1 + public_key.len());
+
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    update_header(&mut ctx, der::Tag::Sequence, alg_id_len + public_key_len);
+    update_header(&mut ctx, der::Tag::Sequence, alg_id_value.len());
+    ctx.update(alg_id_value);
+    update_header(&mut ctx, der::Tag::BitString, 1 + public_key.len());
+    ctx.update(&[0]); // No unused bits.
+    ctx.update(public_key);
+    PublicKeyFingerprint(sha256(ctx.finish()))
+}
+
+fn sha256(digest: digest::Digest) -> [u8; FINGERPRINT_LEN] {
+    digest.as_ref().try_into().unwrap()
+}
+
+fn tlv_len(value_len: usize) -> usize {
+    let (_, header_len) = header(der::Tag::Sequence, value_len);
+    header_len + value_len
+}
+
+fn update_header(ctx: &mut digest::Context, tag: der::Tag, value_len: usize) {
+    let (header, header_len) = header(tag, value_len);
+    ctx.update(&header[..header_len]);
+}
+
+fn header(tag: der::Tag, value_len: usize) -> ([u8; 4], usize) {
+    let len = value_len.to_be_bytes();
+    let len = &len[(len.len() - 2)..];
+    match value_len {
+        0..=0x7f => ([tag.into(), len[1], 0, 0], 2),
+        0x80..=0xff => ([tag.into(), 0x81, len[1], 0], 3),
+        0x1_00..=0xff_ff => ([tag.into(), 0x82, len[0], len[1]], 4),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header() {
+        assert_eq!(header(der::Tag::Sequence, 0x7f), ([0x30, 0x7f, 0, 0], 2));
+        assert_eq!(header(der::Tag::Sequence, 0x80), ([0x30, 0x81, 0x80, 0], 3));
+        assert_eq!(
+            header(der::Tag::BitString, 0x1_0f),
+            ([0x03, 0x82, 0x01, 0x0f], 4)
+        );
+    }
+}
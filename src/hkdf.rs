@@ -18,13 +18,20 @@
 //!
 //! [RFC 5869]: https://tools.ietf.org/html/rfc5869
 
-use crate::{error, hmac};
+use crate::{digest, error, hmac};
 
 /// An HKDF algorithm.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Algorithm(hmac::Algorithm);
 
 impl Algorithm {
+    /// HKDF using `hmac_algorithm`, e.g. one based on a digest algorithm
+    /// constructed with [`digest::Algorithm::new_external()`].
+    #[cfg(feature = "external-digest")]
+    pub const fn from_hmac_algorithm(hmac_algorithm: hmac::Algorithm) -> Self {
+        Self(hmac_algorithm)
+    }
+
     /// The underlying HMAC algorithm.
     #[inline]
     pub fn hmac_algorithm(&self) -> hmac::Algorithm {
@@ -77,7 +84,7 @@ impl Salt {
         // zero-length string.
         let salt = &self.0;
         let prk = hmac::sign(salt, secret);
-        Prk(hmac::Key::new(salt.algorithm(), prk.as_ref()))
+        Prk::new_(salt.algorithm(), prk.as_ref())
     }
 
     /// The algorithm used to derive this salt.
@@ -105,8 +112,16 @@ pub trait KeyType {
 }
 
 /// A HKDF PRK (pseudorandom key).
-#[derive(Clone, Debug)]
-pub struct Prk(hmac::Key);
+///
+/// The `Debug` implementation doesn't show the value of the key unless the
+/// `debug-prk` feature is enabled.
+#[derive(Clone)]
+pub struct Prk {
+    key: hmac::Key,
+
+    #[cfg(feature = "debug-prk")]
+    value: alloc::boxed::Box<[u8]>,
+}
 
 impl Prk {
     /// Construct a new `Prk` directly with the given value.
@@ -115,7 +130,29 @@ impl Prk {
     /// intentionally wants to leak the PRK secret, e.g. to implement
     /// `SSLKEYLOGFILE` functionality.
     pub fn new_less_safe(algorithm: Algorithm, value: &[u8]) -> Self {
-        Self(hmac::Key::new(algorithm.hmac_algorithm(), value))
+        Self::new_(algorithm.hmac_algorithm(), value)
+    }
+
+    fn new_(algorithm: hmac::Algorithm, value: &[u8]) -> Self {
+        Self {
+            key: hmac::Key::new(algorithm, value),
+            #[cfg(feature = "debug-prk")]
+            value: value.into(),
+        }
+    }
+
+    /// The value of the PRK.
+    ///
+    /// This exposes the secret; it is meant for debugging and for comparing
+    /// against test vectors only, so it is only available with the
+    /// `debug-prk` feature.
+    #[cfg(feature = "debug-prk")]
+    pub fn as_bytes_dangerous(&self) -> &[u8] {
+        &self.value
+    }
+
+    pub(crate) fn algorithm(&self) -> Algorithm {
+        Algorithm(self.key.algorithm())
     }
 
     /// The [HKDF-Expand] operation.
@@ -130,7 +167,7 @@ impl Prk {
         len: L,
     ) -> Result<Okm<'a, L>, error::Unspecified> {
         let len_cached = len.len();
-        if len_cached > 255 * self.0.algorithm().digest_algorithm().output_len() {
+        if len_cached > 255 * self.key.algorithm().digest_algorithm().output_len() {
             return Err(error::Unspecified);
         }
         Ok(Okm {
@@ -144,12 +181,23 @@ impl Prk {
 
 impl From<Okm<'_, Algorithm>> for Prk {
     fn from(okm: Okm<Algorithm>) -> Self {
-        Self(hmac::Key::from(Okm {
-            prk: okm.prk,
-            info: okm.info,
-            len: okm.len().0,
-            len_cached: okm.len_cached,
-        }))
+        let algorithm = okm.len().0;
+        let mut value = [0; digest::MAX_OUTPUT_LEN];
+        let value = &mut value[..okm.len_cached];
+        okm.fill(value).unwrap();
+        Self::new_(algorithm, value)
+    }
+}
+
+impl core::fmt::Debug for Prk {
+    #[cfg(feature = "debug-prk")]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        crate::debug::write_hex_tuple(f, "Prk", &self.value)
+    }
+
+    #[cfg(not(feature = "debug-prk"))]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.write_str("Prk { [REDACTED] }")
     }
 }
 
@@ -195,10 +243,10 @@ fn fill_okm(
         return Err(error::Unspecified);
     }
 
-    let digest_alg = prk.0.algorithm().digest_algorithm();
+    let digest_alg = prk.key.algorithm().digest_algorithm();
     assert!(digest_alg.block_len() >= digest_alg.output_len());
 
-    let mut ctx = hmac::Context::with_key(&prk.0);
+    let mut ctx = hmac::Context::with_key(&prk.key);
 
     let mut n = 1u8;
     let mut out = out;
@@ -226,8 +274,15 @@ fn fill_okm(
             return Ok(());
         }
 
-        ctx = hmac::Context::with_key(&prk.0);
+        ctx = hmac::Context::with_key(&prk.key);
         ctx.update(t);
         n = n.checked_add(1).unwrap();
     }
 }
+
+inventory! { Kdf, Always;
+    HKDF_SHA1_FOR_LEGACY_USE_ONLY,
+    HKDF_SHA256,
+    HKDF_SHA384,
+    HKDF_SHA512,
+}
@@ -109,13 +109,26 @@
 //! [code for `ring::hkdf`]:
 //!     https://github.com/briansmith/ring/blob/main/src/hkdf.rs
 
-use crate::{constant_time, digest, error, hkdf, rand};
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::{constant_time, digest, error, hkdf, locked::Secret, rand, tagged};
 
 /// An HMAC algorithm.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Algorithm(&'static digest::Algorithm);
 
 impl Algorithm {
+    /// HMAC using `digest_algorithm`, e.g. an algorithm constructed with
+    /// [`digest::Algorithm::new_external()`].
+    ///
+    /// The result has no id in [`crate::tagged`] unless it is equal to one of
+    /// the `HMAC_*` algorithms in this module.
+    #[cfg(feature = "external-digest")]
+    pub const fn from_digest_algorithm(digest_algorithm: &'static digest::Algorithm) -> Self {
+        Self(digest_algorithm)
+    }
+
     /// The digest algorithm this HMAC algorithm is based on.
     #[inline]
     pub fn digest_algorithm(&self) -> &'static digest::Algorithm {
@@ -141,6 +154,18 @@ pub static HMAC_SHA512: Algorithm = Algorithm(&digest::SHA512);
 #[derive(Clone, Copy, Debug)]
 pub struct Tag(digest::Digest);
 
+impl Tag {
+    /// Encodes the tag value along with the id of its algorithm, as
+    /// described in [`crate::tagged`].
+    ///
+    /// Fails if the algorithm has no id. Only the `HMAC_*` algorithms in
+    /// this module have ids, so e.g. HMAC-SM3 and HMAC using an algorithm
+    /// constructed with the `external-digest` feature have none.
+    pub fn to_tagged_bytes(&self) -> Result<tagged::TaggedBytes, error::Unspecified> {
+        tagged::TaggedBytes::from_hmac_tag(Algorithm(self.0.algorithm()), self)
+    }
+}
+
 impl AsRef<[u8]> for Tag {
     #[inline]
     fn as_ref(&self) -> &[u8] {
@@ -151,8 +176,8 @@ impl AsRef<[u8]> for Tag {
 /// A key to use for HMAC signing.
 #[derive(Clone)]
 pub struct Key {
-    inner: digest::BlockContext,
-    outer: digest::BlockContext,
+    inner: Secret<digest::BlockContext>,
+    outer: Secret<digest::BlockContext>,
 }
 
 impl core::fmt::Debug for Key {
@@ -178,7 +203,7 @@ impl Key {
         Self::construct(algorithm, |buf| rng.fill(buf))
     }
 
-    fn construct<F>(algorithm: Algorithm, fill: F) -> Result<Self, error::Unspecified>
+    pub(crate) fn construct<F>(algorithm: Algorithm, fill: F) -> Result<Self, error::Unspecified>
     where
         F: FnOnce(&mut [u8]) -> Result<(), error::Unspecified>,
     {
@@ -210,8 +235,8 @@ impl Key {
     pub fn new(algorithm: Algorithm, key_value: &[u8]) -> Self {
         let digest_alg = algorithm.0;
         let mut key = Self {
-            inner: digest::BlockContext::new(digest_alg),
-            outer: digest::BlockContext::new(digest_alg),
+            inner: Secret::new(digest::BlockContext::new(digest_alg)),
+            outer: Secret::new(digest::BlockContext::new(digest_alg)),
         };
 
         let block_len = digest_alg.block_len();
@@ -254,8 +279,38 @@ impl Key {
     pub fn algorithm(&self) -> Algorithm {
         Algorithm(self.inner.algorithm)
     }
+
+    /// Whether the key is held in locked memory, which is never written to
+    /// swap.
+    ///
+    /// This is `false` if the key couldn't be locked, e.g. because
+    /// `RLIMIT_MEMLOCK` was exhausted; the key is still usable.
+    #[cfg(feature = "locked-memory")]
+    pub fn was_locked(&self) -> bool {
+        self.inner.was_locked() && self.outer.was_locked()
+    }
 }
 
+/// Keys are equal if they have the same algorithm and compute the same tags.
+///
+/// The comparison is done in constant time with respect to the key material.
+/// Since RFC 2104 pads short keys with zeros and hashes long ones, keys
+/// constructed from different key values may be equal; e.g. a key value and
+/// the same value followed by a zero byte. Keys for different algorithms are
+/// never equal, even when constructed from the same key value.
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        if self.algorithm() != other.algorithm() {
+            return false;
+        }
+        let inner = self.inner.state_equals(&other.inner);
+        let outer = self.outer.state_equals(&other.outer);
+        inner & outer
+    }
+}
+
+impl Eq for Key {}
+
 impl hkdf::KeyType for Algorithm {
     fn len(&self) -> usize {
         self.digest_algorithm().output_len()
@@ -291,7 +346,7 @@ impl Context {
     pub fn with_key(signing_key: &Key) -> Self {
         Self {
             inner: digest::Context::clone_from(&signing_key.inner),
-            outer: signing_key.outer.clone(),
+            outer: digest::BlockContext::clone(&signing_key.outer),
         }
     }
 
@@ -301,6 +356,15 @@ impl Context {
         self.inner.update(data);
     }
 
+    /// Updates the HMAC with all the data in `bufs`, in order.
+    ///
+    /// This is equivalent to calling `update` with each buffer in turn; see
+    /// `digest::Context::update_vectored()`.
+    #[cfg(feature = "std")]
+    pub fn update_vectored(&mut self, bufs: &[std::io::IoSlice]) {
+        self.inner.update_vectored(bufs);
+    }
+
     /// Finalizes the HMAC calculation and returns the HMAC value. `sign`
     /// consumes the context so it cannot be (mis-)used after `sign` has been
     /// called.
@@ -330,6 +394,17 @@ pub fn sign(key: &Key, data: &[u8]) -> Tag {
     ctx.sign()
 }
 
+/// Calculates the HMAC of all the data read from `r`, until the end of the
+/// stream, using the key `key`.
+///
+/// The data is read as described in [`digest::digest_reader()`].
+#[cfg(feature = "std")]
+pub fn sign_reader(key: &Key, r: impl std::io::Read) -> std::io::Result<Tag> {
+    let mut ctx = Context::with_key(key);
+    crate::io::read::for_each_chunk(r, |chunk| ctx.update(chunk))?;
+    Ok(ctx.sign())
+}
+
 /// Calculates the HMAC of `data` using the signing key `key`, and verifies
 /// whether the resultant value equals `tag`, in one step.
 ///
@@ -341,6 +416,13 @@ pub fn verify(key: &Key, data: &[u8], tag: &[u8]) -> Result<(), error::Unspecifi
     constant_time::verify_slices_are_equal(sign(key, data).as_ref(), tag)
 }
 
+inventory! { Hmac, Always;
+    HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+    HMAC_SHA256,
+    HMAC_SHA384,
+    HMAC_SHA512,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{hmac, rand};
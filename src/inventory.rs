@@ -0,0 +1,150 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! An inventory of the algorithms available in this build of *ring*.
+//!
+//! Each module that implements algorithms maintains its own list, guarded by
+//! the same `cfg`s as the algorithms themselves, so the inventory reflects
+//! the cargo features *ring* was built with.
+//!
+//! The name of each algorithm is the name of the public item that implements
+//! it, e.g. `"AES_128_GCM"` for `ring::aead::AES_128_GCM`. These names are
+//! stable, so inventories from different versions of *ring* can be diffed.
+
+use core::ops::RangeInclusive;
+
+/// The kind of an algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AlgorithmKind {
+    /// An AEAD algorithm, in `ring::aead`.
+    Aead,
+
+    /// A digest algorithm, in `ring::digest`.
+    Digest,
+
+    /// An HMAC algorithm, in `ring::hmac`.
+    Hmac,
+
+    /// A signing or signature verification algorithm, in `ring::signature`.
+    Signature,
+
+    /// A key agreement algorithm, in `ring::agreement`.
+    Agreement,
+
+    /// A key derivation algorithm, in `ring::hkdf` or `ring::pbkdf2`.
+    Kdf,
+}
+
+/// The build configuration an algorithm requires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Availability {
+    /// Available in every build.
+    Always,
+
+    /// Requires the `alloc` feature.
+    Alloc,
+}
+
+/// A description of an algorithm available in this build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlgorithmInfo {
+    kind: AlgorithmKind,
+    name: &'static str,
+    key_bits: Option<(usize, usize)>,
+    availability: Availability,
+}
+
+impl AlgorithmInfo {
+    pub(crate) const fn new(
+        kind: AlgorithmKind,
+        name: &'static str,
+        key_bits: Option<(usize, usize)>,
+        availability: Availability,
+    ) -> Self {
+        Self {
+            kind,
+            name,
+            key_bits,
+            availability,
+        }
+    }
+
+    /// The kind of algorithm.
+    #[inline]
+    pub fn kind(&self) -> AlgorithmKind {
+        self.kind
+    }
+
+    /// The stable name of the algorithm.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The supported key sizes, in bits, or `None` if the algorithm has no
+    /// key (digests) or accepts keys of any length (HMAC and the KDFs).
+    #[inline]
+    pub fn key_bits(&self) -> Option<RangeInclusive<usize>> {
+        self.key_bits.map(|(min, max)| min..=max)
+    }
+
+    /// The build configuration the algorithm requires.
+    #[inline]
+    pub fn availability(&self) -> Availability {
+        self.availability
+    }
+}
+
+// Defines a module's `INVENTORY`, naming each algorithm after its public item.
+//
+// ```ignore
+// inventory! { Aead, Always;
+//     AES_128_GCM => 128..=128,
+// }
+// ```
+macro_rules! inventory {
+    { $kind:ident, $availability:ident;
+      $( $name:ident $( => $min:literal ..= $max:expr )? ),+ $(,)? } => {
+        pub(crate) static INVENTORY: &[$crate::inventory::AlgorithmInfo] = &[
+            $(
+                $crate::inventory::AlgorithmInfo::new(
+                    $crate::inventory::AlgorithmKind::$kind,
+                    stringify!($name),
+                    inventory!(@key_bits $( $min, $max )?),
+                    $crate::inventory::Availability::$availability,
+                ),
+            )+
+        ];
+    };
+    (@key_bits) => { None };
+    (@key_bits $min:literal, $max:expr) => { Some(($min, $max)) };
+}
+
+/// Returns every algorithm available in this build of *ring*.
+pub fn inventory() -> impl Iterator<Item = &'static AlgorithmInfo> {
+    static REGISTRIES: &[&[AlgorithmInfo]] = &[
+        crate::aead::INVENTORY,
+        crate::digest::INVENTORY,
+        crate::hmac::INVENTORY,
+        crate::signature::INVENTORY,
+        #[cfg(feature = "alloc")]
+        crate::rsa::INVENTORY,
+        crate::agreement::INVENTORY,
+        crate::hkdf::INVENTORY,
+        crate::pbkdf2::INVENTORY,
+    ];
+    REGISTRIES.iter().flat_map(|registry| registry.iter())
+}
@@ -17,12 +17,13 @@
 #[doc(hidden)]
 pub mod der;
 
-#[cfg(feature = "alloc")]
-mod writer;
+pub(crate) mod writer;
 
-#[cfg(feature = "alloc")]
 pub(crate) mod der_writer;
 
 pub(crate) mod positive;
 
+#[cfg(feature = "std")]
+pub(crate) mod read;
+
 pub use self::positive::Positive;
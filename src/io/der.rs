@@ -19,6 +19,12 @@
 use super::Positive;
 use crate::error;
 
+#[cfg(feature = "debug-der")]
+mod offset_reader;
+
+#[cfg(feature = "debug-der")]
+pub use self::offset_reader::{parse_with_offset, DerError, OffsetReader};
+
 pub const CONSTRUCTED: u8 = 1 << 5;
 pub const CONTEXT_SPECIFIC: u8 = 2 << 6;
 
@@ -32,6 +38,7 @@ pub enum Tag {
     Null = 0x05,
     OID = 0x06,
     Sequence = CONSTRUCTED | 0x10, // 0x30
+    Set = CONSTRUCTED | 0x11,      // 0x31
     UTCTime = 0x17,
     GeneralizedTime = 0x18,
 
@@ -39,6 +46,7 @@ pub enum Tag {
 
     ContextSpecificConstructed0 = CONTEXT_SPECIFIC | CONSTRUCTED | 0,
     ContextSpecificConstructed1 = CONTEXT_SPECIFIC | CONSTRUCTED | 1,
+    ContextSpecificConstructed2 = CONTEXT_SPECIFIC | CONSTRUCTED | 2,
     ContextSpecificConstructed3 = CONTEXT_SPECIFIC | CONSTRUCTED | 3,
 }
 
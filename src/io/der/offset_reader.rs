@@ -0,0 +1,311 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! DER parsing that reports where in the input parsing failed.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::{Positive, Tag};
+use crate::error;
+
+/// A DER parsing error, with the offset into the outermost input at which
+/// the offending element starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DerError {
+    /// An element had tag `found` where tag `expected` was required.
+    UnexpectedTag {
+        /// The tag that was found.
+        found: u8,
+        /// The tag that was expected.
+        expected: u8,
+        /// The offset of the element's tag.
+        offset: usize,
+    },
+
+    /// An element's tag, length, or value was malformed or truncated.
+    Malformed {
+        /// The offset of the element's tag.
+        offset: usize,
+    },
+
+    /// There was unparsed input after the end of the value.
+    TrailingData {
+        /// The offset of the first unparsed byte.
+        offset: usize,
+    },
+}
+
+impl core::fmt::Display for DerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            Self::UnexpectedTag {
+                found,
+                expected,
+                offset,
+            } => write!(
+                f,
+                "unexpected tag 0x{:02x} (expected 0x{:02x}) at offset {}",
+                found, expected, offset
+            ),
+            Self::Malformed { offset } => write!(f, "malformed element at offset {}", offset),
+            Self::TrailingData { offset } => write!(f, "trailing data at offset {}", offset),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DerError {}
+
+impl From<DerError> for error::Unspecified {
+    fn from(_: DerError) -> Self {
+        Self
+    }
+}
+
+/// An `untrusted::Reader` that keeps track of its offset into the outermost
+/// input, for reporting in `DerError`s.
+pub struct OffsetReader<'a> {
+    reader: untrusted::Reader<'a>,
+    offset: usize,
+}
+
+/// Parses `data` with `f`, which must consume all of it.
+///
+/// ```
+/// use ring::io::der::{self, DerError};
+///
+/// // SEQUENCE { INTEGER 1, BOOLEAN TRUE }
+/// let data = [0x30, 0x06, 0x02, 0x01, 0x01, 0x01, 0x01, 0xff];
+/// let r = der::parse_with_offset(&data, |input| {
+///     input.nested(der::Tag::Sequence, |seq| {
+///         let _ = seq.positive_integer()?;
+///         seq.positive_integer()
+///     })
+/// });
+/// assert!(matches!(
+///     r,
+///     Err(DerError::UnexpectedTag { found: 0x01, expected: 0x02, offset: 5 })
+/// ));
+/// ```
+pub fn parse_with_offset<'a, T>(
+    data: &'a [u8],
+    f: impl FnOnce(&mut OffsetReader<'a>) -> Result<T, DerError>,
+) -> Result<T, DerError> {
+    read_all(untrusted::Input::from(data), 0, f)
+}
+
+fn read_all<'a, T>(
+    input: untrusted::Input<'a>,
+    offset: usize,
+    f: impl FnOnce(&mut OffsetReader<'a>) -> Result<T, DerError>,
+) -> Result<T, DerError> {
+    let mut reader = OffsetReader {
+        reader: untrusted::Reader::new(input),
+        offset,
+    };
+    let r = f(&mut reader)?;
+    if !reader.at_end() {
+        return Err(DerError::TrailingData {
+            offset: reader.offset,
+        });
+    }
+    Ok(r)
+}
+
+impl<'a> OffsetReader<'a> {
+    /// The offset of the next unread byte into the outermost input.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether all the input has been read.
+    #[inline]
+    pub fn at_end(&self) -> bool {
+        self.reader.at_end()
+    }
+
+    /// Like [`super::read_tag_and_get_value()`].
+    pub fn read_tag_and_get_value(&mut self) -> Result<(u8, untrusted::Input<'a>), DerError> {
+        self.read_tlv().map(|(tag, value, _)| (tag, value))
+    }
+
+    /// Like [`super::expect_tag_and_get_value()`].
+    pub fn expect_tag_and_get_value(&mut self, tag: Tag) -> Result<untrusted::Input<'a>, DerError> {
+        self.expect_tlv(tag).map(|(value, _)| value)
+    }
+
+    /// Like [`super::nested()`]; `decoder` must consume all of the value.
+    pub fn nested<R>(
+        &mut self,
+        tag: Tag,
+        decoder: impl FnOnce(&mut OffsetReader<'a>) -> Result<R, DerError>,
+    ) -> Result<R, DerError> {
+        let (value, value_offset) = self.expect_tlv(tag)?;
+        read_all(value, value_offset, decoder)
+    }
+
+    /// Like [`super::positive_integer()`].
+    pub fn positive_integer(&mut self) -> Result<Positive<'a>, DerError> {
+        self.parse_tagged(Tag::Integer, super::positive_integer)
+    }
+
+    /// Like [`super::small_nonnegative_integer()`].
+    pub fn small_nonnegative_integer(&mut self) -> Result<u8, DerError> {
+        self.parse_tagged(Tag::Integer, super::small_nonnegative_integer)
+    }
+
+    /// Like [`super::bit_string_with_no_unused_bits()`].
+    pub fn bit_string_with_no_unused_bits(&mut self) -> Result<untrusted::Input<'a>, DerError> {
+        self.parse_tagged(Tag::BitString, super::bit_string_with_no_unused_bits)
+    }
+
+    // Returns the value and the offset of the value.
+    fn expect_tlv(&mut self, tag: Tag) -> Result<(untrusted::Input<'a>, usize), DerError> {
+        let offset = self.offset;
+        let (found, value, value_offset) = self.read_tlv()?;
+        if found != u8::from(tag) {
+            return Err(DerError::UnexpectedTag {
+                found,
+                expected: tag.into(),
+                offset,
+            });
+        }
+        Ok((value, value_offset))
+    }
+
+    // Returns the tag, the value, and the offset of the value.
+    fn read_tlv(&mut self) -> Result<(u8, untrusted::Input<'a>, usize), DerError> {
+        let (tag, value) = self.parse_element(super::read_tag_and_get_value)?;
+        Ok((tag, value, self.offset - value.len()))
+    }
+
+    // Parses one element, which must have tag `tag`, with `parse`.
+    fn parse_tagged<R>(
+        &mut self,
+        tag: Tag,
+        parse: impl FnOnce(&mut untrusted::Reader<'a>) -> Result<R, error::Unspecified>,
+    ) -> Result<R, DerError> {
+        let offset = self.offset;
+        let (element, (found, _)) = self
+            .reader
+            .read_partial(super::read_tag_and_get_value)
+            .map_err(|error::Unspecified| DerError::Malformed { offset })?;
+        self.offset += element.len();
+        if found != u8::from(tag) {
+            return Err(DerError::UnexpectedTag {
+                found,
+                expected: tag.into(),
+                offset,
+            });
+        }
+        element
+            .read_all(error::Unspecified, parse)
+            .map_err(|error::Unspecified| DerError::Malformed { offset })
+    }
+
+    // Parses one element with `parse`, reporting failure at the element's
+    // offset.
+    fn parse_element<R>(
+        &mut self,
+        parse: impl FnOnce(&mut untrusted::Reader<'a>) -> Result<R, error::Unspecified>,
+    ) -> Result<R, DerError> {
+        let offset = self.offset;
+        let (consumed, r) = self
+            .reader
+            .read_partial(parse)
+            .map_err(|error::Unspecified| DerError::Malformed { offset })?;
+        self.offset += consumed.len();
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SEQUENCE { INTEGER 1, SEQUENCE { BIT STRING 0x05 } }
+    static VALID: &[u8] = &[
+        0x30, 0x09, 0x02, 0x01, 0x01, 0x30, 0x04, 0x03, 0x02, 0x00, 0x05,
+    ];
+
+    fn parse(data: &[u8]) -> Result<(u8, u8), DerError> {
+        parse_with_offset(data, |input| {
+            input.nested(Tag::Sequence, |seq| {
+                let version = seq.small_nonnegative_integer()?;
+                let bits = seq.nested(Tag::Sequence, |inner| {
+                    inner.bit_string_with_no_unused_bits()
+                })?;
+                Ok((version, bits.as_slice_less_safe()[0]))
+            })
+        })
+    }
+
+    #[test]
+    fn test_parse_with_offset() {
+        assert_eq!(parse(VALID), Ok((1, 5)));
+
+        // The inner SEQUENCE is an OCTET STRING.
+        let mut data = VALID.to_vec();
+        data[5] = 0x04;
+        assert_eq!(
+            parse(&data),
+            Err(DerError::UnexpectedTag {
+                found: 0x04,
+                expected: 0x30,
+                offset: 5
+            })
+        );
+
+        // The BIT STRING has unused bits.
+        let mut data = VALID.to_vec();
+        data[9] = 0x01;
+        assert_eq!(parse(&data), Err(DerError::Malformed { offset: 7 }));
+
+        // The INTEGER is too long for its SEQUENCE.
+        let mut data = VALID.to_vec();
+        data[3] = 0x0a;
+        assert_eq!(parse(&data), Err(DerError::Malformed { offset: 2 }));
+
+        // Truncated.
+        assert_eq!(
+            parse(&VALID[..VALID.len() - 1]),
+            Err(DerError::Malformed { offset: 0 })
+        );
+
+        // Trailing data, inside and outside of the outer SEQUENCE.
+        let mut data = VALID.to_vec();
+        data[1] += 1;
+        data.push(0);
+        assert_eq!(parse(&data), Err(DerError::TrailingData { offset: 11 }));
+        let mut data = VALID.to_vec();
+        data.push(0);
+        assert_eq!(parse(&data), Err(DerError::TrailingData { offset: 11 }));
+    }
+
+    #[test]
+    fn test_offset_reader_offset() {
+        let _ = parse_with_offset(VALID, |input| {
+            assert_eq!(input.offset(), 0);
+            let (tag, value) = input.read_tag_and_get_value()?;
+            assert_eq!(tag, u8::from(Tag::Sequence));
+            assert_eq!(value.len(), VALID.len() - 2);
+            assert_eq!(input.offset(), VALID.len());
+            assert!(input.at_end());
+            Ok(())
+        });
+    }
+}
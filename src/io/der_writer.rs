@@ -12,9 +12,13 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use super::{der::*, writer::*, *};
-use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use super::Positive;
+use super::{der::*, writer::*};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::TryReserveError};
 
+#[cfg(feature = "alloc")]
 pub(crate) fn write_positive_integer(output: &mut dyn Accumulator, value: &Positive) {
     let first_byte = value.first_byte();
     let value = value.big_endian_without_leading_zero_as_input();
@@ -26,21 +30,25 @@ pub(crate) fn write_positive_integer(output: &mut dyn Accumulator, value: &Posit
     })
 }
 
-pub(crate) fn write_all(tag: Tag, write_value: &dyn Fn(&mut dyn Accumulator)) -> Box<[u8]> {
+#[cfg(feature = "alloc")]
+pub(crate) fn write_all(
+    tag: Tag,
+    write_value: &dyn Fn(&mut dyn Accumulator),
+) -> Result<Box<[u8]>, TryReserveError> {
     let length = {
         let mut length = LengthMeasurement::zero();
         write_tlv(&mut length, tag, write_value);
         length
     };
 
-    let mut output = Writer::with_capacity(length);
+    let mut output = Writer::try_with_capacity(length)?;
     write_tlv(&mut output, tag, write_value);
 
-    output.into()
+    Ok(output.into())
 }
 
 #[allow(clippy::cast_possible_truncation)]
-fn write_tlv<F>(output: &mut dyn Accumulator, tag: Tag, write_value: F)
+pub(crate) fn write_tlv<F>(output: &mut dyn Accumulator, tag: Tag, write_value: F)
 where
     F: Fn(&mut dyn Accumulator),
 {
@@ -0,0 +1,55 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Helpers for consuming a `std::io::Read` in fixed-size chunks.
+
+extern crate std;
+
+use alloc::{vec, vec::Vec};
+use std::io::{self, Read};
+
+/// The size of the internal buffer used when reading from a stream.
+pub(crate) const BUFFER_LEN: usize = 64 * 1024;
+
+pub(crate) fn new_buffer() -> Vec<u8> {
+    vec![0; BUFFER_LEN]
+}
+
+/// Reads from `r` until `buf` is full or the end of the stream is reached,
+/// returning the number of bytes read. A short read does not imply the end
+/// of the stream; only a read of zero bytes does.
+pub(crate) fn read_full(mut r: impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Calls `f` with each chunk read from `r` until the end of the stream.
+pub(crate) fn for_each_chunk(mut r: impl Read, mut f: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buf = new_buffer();
+    loop {
+        let len = read_full(&mut r, &mut buf)?;
+        if len == 0 {
+            return Ok(());
+        }
+        f(&buf[..len]);
+    }
+}
@@ -12,7 +12,8 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::TryReserveError, vec::Vec};
 
 pub trait Accumulator {
     fn write_byte(&mut self, value: u8);
@@ -44,20 +45,25 @@ impl Accumulator for LengthMeasurement {
     }
 }
 
+#[cfg(feature = "alloc")]
 pub(super) struct Writer {
     bytes: Vec<u8>,
     requested_capacity: usize,
 }
 
+#[cfg(feature = "alloc")]
 impl Writer {
-    pub(super) fn with_capacity(capacity: LengthMeasurement) -> Self {
-        Self {
-            bytes: Vec::with_capacity(capacity.len),
+    pub(super) fn try_with_capacity(capacity: LengthMeasurement) -> Result<Self, TryReserveError> {
+        let mut bytes = Vec::new();
+        bytes.try_reserve_exact(capacity.len)?;
+        Ok(Self {
+            bytes,
             requested_capacity: capacity.len,
-        }
+        })
     }
 }
 
+#[cfg(feature = "alloc")]
 impl From<Writer> for Box<[u8]> {
     fn from(writer: Writer) -> Self {
         assert_eq!(writer.requested_capacity, writer.bytes.len());
@@ -65,6 +71,7 @@ impl From<Writer> for Box<[u8]> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl Accumulator for Writer {
     fn write_byte(&mut self, value: u8) {
         self.bytes.push(value);
@@ -74,6 +81,35 @@ impl Accumulator for Writer {
     }
 }
 
+/// Writes into a fixed-size buffer. Writing more than fits panics.
+pub(crate) struct SliceWriter<'a> {
+    bytes: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub(crate) fn new(bytes: &'a mut [u8]) -> Self {
+        Self { bytes, len: 0 }
+    }
+
+    /// The number of bytes written so far.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl Accumulator for SliceWriter<'_> {
+    fn write_byte(&mut self, value: u8) {
+        self.bytes[self.len] = value;
+        self.len += 1;
+    }
+    fn write_bytes(&mut self, value: &[u8]) {
+        self.bytes[self.len..][..value.len()].copy_from_slice(value);
+        self.len += value.len();
+    }
+}
+
+#[cfg(feature = "alloc")]
 pub fn write_copy(accumulator: &mut dyn Accumulator, to_copy: untrusted::Input) {
     accumulator.write_bytes(to_copy.as_slice_less_safe())
 }
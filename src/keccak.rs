@@ -0,0 +1,260 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The Keccak-p[1600, 24] permutation and sponge (FIPS 202), and the cSHAKE
+//! encodings of NIST SP 800-185.
+
+const LANES: usize = 25;
+
+// FIPS 202 Section 3.2.5.
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+// The rotation offsets of ρ and the lane order of π, following the lane
+// visited at step t of FIPS 202 Section 3.2.2 starting from (x, y) = (1, 0).
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_p1600(a: &mut [u64; LANES]) {
+    for rc in ROUND_CONSTANTS {
+        // θ
+        let mut c = [0u64; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            *c = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + (5 * y)] ^= d;
+            }
+        }
+
+        // ρ and π
+        let mut current = a[1];
+        for (rho, pi) in RHO.iter().zip(PI.iter()) {
+            let next = a[*pi];
+            a[*pi] = current.rotate_left(*rho);
+            current = next;
+        }
+
+        // χ
+        for y in 0..5 {
+            let row = [
+                a[5 * y],
+                a[(5 * y) + 1],
+                a[(5 * y) + 2],
+                a[(5 * y) + 3],
+                a[(5 * y) + 4],
+            ];
+            for x in 0..5 {
+                a[x + (5 * y)] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // ι
+        a[0] ^= rc;
+    }
+}
+
+/// A Keccak sponge with a rate of `rate` bytes.
+#[derive(Clone)]
+pub(crate) struct Sponge {
+    state: [u64; LANES],
+    rate: usize,
+    // The position of the next byte to absorb or squeeze, in `0..rate`.
+    pos: usize,
+    squeezing: bool,
+}
+
+impl Sponge {
+    /// `rate` must be a multiple of 8 less than 200.
+    pub(crate) fn new(rate: usize) -> Self {
+        debug_assert!(rate % 8 == 0 && rate < LANES * 8);
+        Self {
+            state: [0; LANES],
+            rate,
+            pos: 0,
+            squeezing: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn rate(&self) -> usize {
+        self.rate
+    }
+
+    #[inline]
+    fn xor_byte(&mut self, pos: usize, b: u8) {
+        self.state[pos / 8] ^= u64::from(b) << (8 * (pos % 8));
+    }
+
+    pub(crate) fn absorb(&mut self, data: &[u8]) {
+        debug_assert!(!self.squeezing);
+        for &b in data {
+            self.xor_byte(self.pos, b);
+            self.pos += 1;
+            if self.pos == self.rate {
+                keccak_p1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Absorbs zeros up to the next multiple of the rate.
+    pub(crate) fn absorb_zero_pad(&mut self) {
+        if self.pos != 0 {
+            keccak_p1600(&mut self.state);
+            self.pos = 0;
+        }
+    }
+
+    /// Pads the input with the domain separation bits `suffix`, which
+    /// already includes the first bit of the `pad10*1` padding (e.g. `0x04`
+    /// for cSHAKE), and switches to squeezing.
+    pub(crate) fn finish_absorbing(&mut self, suffix: u8) {
+        debug_assert!(!self.squeezing);
+        self.xor_byte(self.pos, suffix);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_p1600(&mut self.state);
+        self.pos = 0;
+        self.squeezing = true;
+    }
+
+    pub(crate) fn squeeze(&mut self, out: &mut [u8]) {
+        debug_assert!(self.squeezing);
+        for out in out {
+            if self.pos == self.rate {
+                keccak_p1600(&mut self.state);
+                self.pos = 0;
+            }
+            *out = self.state[self.pos / 8].to_le_bytes()[self.pos % 8];
+            self.pos += 1;
+        }
+    }
+}
+
+/// cSHAKE (SP 800-185 Section 3.3): The domain separation suffix `00`
+/// followed by the first bit of `pad10*1`.
+pub(crate) const CSHAKE_SUFFIX: u8 = 0x04;
+
+// The big-endian encoding of `x` without leading zeros, but at least one
+// byte, right-aligned in a 16-byte buffer; returns the buffer and the
+// number of bytes used.
+fn encode_be(x: u128) -> ([u8; 16], usize) {
+    let bytes = x.to_be_bytes();
+    let n = bytes.iter().take_while(|&&b| b == 0).count();
+    let n = core::cmp::min(n, 15);
+    (bytes, 16 - n)
+}
+
+/// Absorbs `left_encode(x)` (SP 800-185 Section 2.3.1).
+pub(crate) fn absorb_left_encode(sponge: &mut Sponge, x: u128) {
+    let (bytes, n) = encode_be(x);
+    #[allow(clippy::cast_possible_truncation)]
+    sponge.absorb(&[n as u8]);
+    sponge.absorb(&bytes[(16 - n)..]);
+}
+
+/// Absorbs `right_encode(x)` (SP 800-185 Section 2.3.1).
+pub(crate) fn absorb_right_encode(sponge: &mut Sponge, x: u128) {
+    let (bytes, n) = encode_be(x);
+    sponge.absorb(&bytes[(16 - n)..]);
+    #[allow(clippy::cast_possible_truncation)]
+    sponge.absorb(&[n as u8]);
+}
+
+/// Absorbs the `left_encode(w)` that starts `bytepad(X, w)` (SP 800-185
+/// Section 2.3.3), where `w` is the rate. Follow it with `X` and then
+/// `absorb_zero_pad`.
+pub(crate) fn absorb_bytepad_start(sponge: &mut Sponge) {
+    absorb_left_encode(sponge, bit_len(sponge.rate()) / 8);
+}
+
+/// Absorbs `encode_string(s)` (SP 800-185 Section 2.3.2).
+pub(crate) fn absorb_encode_string(sponge: &mut Sponge, s: &[u8]) {
+    absorb_left_encode(sponge, bit_len(s.len()));
+    sponge.absorb(s);
+}
+
+/// The length of `len` bytes, in bits. This never overflows.
+#[inline]
+pub(crate) fn bit_len(len: usize) -> u128 {
+    // `usize` is at most 64 bits on every supported target.
+    (len as u128) * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_p1600_zero() {
+        // The first and last lanes of Keccak-f[1600] applied to the all-zero
+        // state, from the Keccak team's `KeccakF-1600-IntermediateValues.txt`.
+        let mut state = [0; LANES];
+        keccak_p1600(&mut state);
+        assert_eq!(state[0], 0xf125_8f79_40e1_dde7);
+        assert_eq!(state[24], 0xeaf1_ff7b_5cec_a249);
+    }
+
+    #[test]
+    fn test_encode() {
+        for (x, left, right) in [
+            (0, &[0x01, 0x00][..], &[0x00, 0x01][..]),
+            (168, &[0x01, 0xa8], &[0xa8, 0x01]),
+            (256, &[0x02, 0x01, 0x00], &[0x01, 0x00, 0x02]),
+        ] {
+            let mut expected = Sponge::new(168);
+            expected.absorb(left);
+            let mut actual = Sponge::new(168);
+            absorb_left_encode(&mut actual, x);
+            assert_eq!(actual.state, expected.state);
+            assert_eq!(actual.pos, left.len());
+
+            let mut expected = Sponge::new(168);
+            expected.absorb(right);
+            let mut actual = Sponge::new(168);
+            absorb_right_encode(&mut actual, x);
+            assert_eq!(actual.state, expected.state);
+        }
+    }
+}
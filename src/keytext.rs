@@ -0,0 +1,317 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Text encodings of public keys, as found in configuration files.
+//!
+//! Each key type implements `FromStr` and `Display` using hex, and has
+//! `from_base64` and `to_base64` for base64. Parsing is strict: the decoded
+//! key must be exactly the right length for the algorithm, and surrounding
+//! whitespace isn't accepted.
+//!
+//! Hex input may be in either case, but not a mix of both; output is lower
+//! case. Base64 input may use either the standard or the URL-safe alphabet
+//! of [RFC 4648], but not a mix of both, and padding is optional.
+//!
+//! ```
+//! use ring::{keytext, signature};
+//!
+//! let key: keytext::Ed25519PublicKey =
+//!     "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+//!         .parse()
+//!         .unwrap();
+//! assert_eq!(
+//!     key.to_base64(keytext::Base64::Standard),
+//!     "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo="
+//! );
+//! assert_eq!(
+//!     keytext::Ed25519PublicKey::from_base64("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"),
+//!     Ok(key)
+//! );
+//!
+//! let unparsed: signature::UnparsedPublicKey<[u8; 32]> = key.unparsed();
+//! # let _ = unparsed;
+//! ```
+//!
+//! [RFC 4648]: https://www.rfc-editor.org/rfc/rfc4648
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::{agreement, b64, signature};
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// A base64 alphabet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Base64 {
+    /// The standard alphabet, with padding.
+    Standard,
+
+    /// The URL- and filename-safe alphabet, without padding.
+    UrlSafe,
+}
+
+/// An error parsing the text encoding of a key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The character starting at byte `offset` of the input isn't valid in
+    /// the encoding.
+    InvalidCharacter {
+        /// The offset, in bytes from the start of the input, of the invalid
+        /// character.
+        offset: usize,
+    },
+
+    /// Hex input contains both upper- and lower-case digits.
+    MixedCase,
+
+    /// Base64 input contains characters from both the standard and the
+    /// URL-safe alphabet.
+    MixedAlphabets,
+
+    /// The input ends partway through a byte.
+    Truncated,
+
+    /// Base64 input has the wrong amount of `=` padding, or padding that
+    /// isn't at the end.
+    InvalidPadding,
+
+    /// The input decodes to the wrong number of bytes for the algorithm.
+    WrongLength {
+        /// The length of a key for the algorithm.
+        expected: usize,
+
+        /// The decoded length of the input.
+        actual: usize,
+    },
+
+    /// Base64 input has nonzero bits after the last decoded byte.
+    NonCanonical,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::InvalidCharacter { offset } => {
+                write!(f, "invalid character at offset {}", offset)
+            }
+            Self::MixedCase => f.write_str("hex digits are in mixed case"),
+            Self::MixedAlphabets => f.write_str("base64 mixes the standard and URL-safe alphabets"),
+            Self::Truncated => f.write_str("input ends partway through a byte"),
+            Self::InvalidPadding => f.write_str("invalid base64 padding"),
+            Self::WrongLength { expected, actual } => {
+                write!(f, "key is {} bytes; expected {} bytes", actual, expected)
+            }
+            Self::NonCanonical => f.write_str("non-canonical base64"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+macro_rules! public_key_text {
+    ( $( #[$attr:meta] )* $Name:ident, $LEN:expr ) => {
+        $( #[$attr] )*
+        #[derive(Clone, Copy, Eq, PartialEq)]
+        pub struct $Name([u8; $LEN]);
+
+        impl $Name {
+            /// Constructs the key from its raw bytes.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+                let bytes = bytes.try_into().map_err(|_| Error::WrongLength {
+                    expected: $LEN,
+                    actual: bytes.len(),
+                })?;
+                Ok(Self(bytes))
+            }
+
+            /// Parses the base64 encoding of the key, in either alphabet,
+            /// with or without padding.
+            pub fn from_base64(s: &str) -> Result<Self, Error> {
+                let mut bytes = [0; $LEN];
+                decode_base64(s, &mut bytes)?;
+                Ok(Self(bytes))
+            }
+
+            /// Encodes the key as base64 using `alphabet`.
+            #[cfg(feature = "alloc")]
+            pub fn to_base64(&self, alphabet: Base64) -> String {
+                encode_base64(&self.0, alphabet)
+            }
+        }
+
+        impl AsRef<[u8]> for $Name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        derive_debug_self_as_ref_hex_bytes!($Name);
+
+        impl core::fmt::Display for $Name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                crate::debug::write_hex_bytes(f, &self.0)
+            }
+        }
+
+        impl core::str::FromStr for $Name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut bytes = [0; $LEN];
+                decode_hex(s, &mut bytes)?;
+                Ok(Self(bytes))
+            }
+        }
+    };
+}
+
+public_key_text!(
+    /// An Ed25519 public key, for verifying [`signature::ED25519`]
+    /// signatures.
+    Ed25519PublicKey,
+    signature::ED25519_PUBLIC_KEY_LEN
+);
+
+impl Ed25519PublicKey {
+    /// The key as a public key for [`signature::ED25519`].
+    pub fn unparsed(
+        &self,
+    ) -> signature::UnparsedPublicKey<[u8; signature::ED25519_PUBLIC_KEY_LEN]> {
+        signature::UnparsedPublicKey::new(&signature::ED25519, self.0)
+    }
+}
+
+const X25519_PUBLIC_KEY_LEN: usize = 32;
+
+public_key_text!(
+    /// An X25519 public key, for [`agreement::X25519`] key agreement.
+    X25519PublicKey,
+    X25519_PUBLIC_KEY_LEN
+);
+
+impl X25519PublicKey {
+    /// The key as a peer public key for [`agreement::X25519`].
+    pub fn unparsed(&self) -> agreement::UnparsedPublicKey<[u8; X25519_PUBLIC_KEY_LEN]> {
+        agreement::UnparsedPublicKey::new(&agreement::X25519, self.0)
+    }
+}
+
+fn decode_hex(s: &str, out: &mut [u8]) -> Result<(), Error> {
+    let input = s.as_bytes();
+    let mut has_lower = false;
+    let mut has_upper = false;
+    for (offset, c) in input.iter().enumerate() {
+        match c {
+            b'0'..=b'9' => {}
+            b'a'..=b'f' => has_lower = true,
+            b'A'..=b'F' => has_upper = true,
+            _ => return Err(Error::InvalidCharacter { offset }),
+        }
+    }
+    if has_lower && has_upper {
+        return Err(Error::MixedCase);
+    }
+    if input.len() % 2 != 0 {
+        return Err(Error::Truncated);
+    }
+    check_len(input.len() / 2, out)?;
+
+    for (out, digits) in out.iter_mut().zip(input.chunks(2)) {
+        *out = (hex_digit(digits[0]) << 4) | hex_digit(digits[1]);
+    }
+    Ok(())
+}
+
+// `c` was already checked to be a hex digit.
+fn hex_digit(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+fn decode_base64(s: &str, out: &mut [u8]) -> Result<(), Error> {
+    // Only the two alphabets differ in these characters; everything else in
+    // either alphabet is in both.
+    let mut standard = false;
+    let mut url_safe = false;
+    for (offset, &c) in s.as_bytes().iter().enumerate() {
+        match c {
+            b'+' | b'/' => standard = true,
+            b'-' | b'_' => url_safe = true,
+            b'=' => {}
+            _ if b64::STANDARD.contains(c) => {}
+            _ => return Err(Error::InvalidCharacter { offset }),
+        }
+    }
+    if standard && url_safe {
+        return Err(Error::MixedAlphabets);
+    }
+
+    let unpadded = s.trim_end_matches('=');
+    let padding = s.len() - unpadded.len();
+    if unpadded.contains('=') {
+        return Err(Error::InvalidPadding);
+    }
+    let alphabet = if url_safe {
+        &b64::URL_SAFE
+    } else {
+        &b64::STANDARD
+    };
+
+    let remainder = unpadded.len() % 4;
+    if remainder == 1 {
+        return Err(Error::Truncated);
+    }
+    if padding != 0 && (remainder == 0 || padding != 4 - remainder) {
+        return Err(Error::InvalidPadding);
+    }
+    check_len(unpadded.len() / 4 * 3 + remainder.saturating_sub(1), out)?;
+
+    // The input was validated above, so the only possible failure is
+    // nonzero unused bits.
+    let _: usize = b64::decode(alphabet, unpadded, out).map_err(|_| Error::NonCanonical)?;
+    Ok(())
+}
+
+fn check_len(actual: usize, out: &[u8]) -> Result<(), Error> {
+    if actual != out.len() {
+        return Err(Error::WrongLength {
+            expected: out.len(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+fn encode_base64(bytes: &[u8], alphabet: Base64) -> String {
+    let mut s = String::new();
+    match alphabet {
+        Base64::Standard => {
+            b64::encode(&b64::STANDARD, bytes, &mut s).unwrap();
+            while s.len() % 4 != 0 {
+                s.push('=');
+            }
+        }
+        Base64::UrlSafe => {
+            b64::encode(&b64::URL_SAFE, bytes, &mut s).unwrap();
+        }
+    }
+    s
+}
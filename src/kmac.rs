@@ -0,0 +1,218 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! KMAC, the Keccak-based message authentication code of [NIST SP 800-185].
+//!
+//! KMAC128 and KMAC256 take a key, an optional customization string that
+//! separates different uses of the same key, and a requested output length,
+//! which is part of the MAC's input, so a shorter tag isn't a prefix of a
+//! longer one.
+//!
+//! ```
+//! use ring::kmac;
+//!
+//! let key = kmac::Kmac256Key::new(&[0x42; 32], "example.com firmware")?;
+//!
+//! let mut ctx = key.start();
+//! ctx.update(b"hello, ");
+//! ctx.update(b"world");
+//! let mut tag = [0u8; 32];
+//! ctx.finish_into(&mut tag);
+//!
+//! key.verify(b"hello, world", &tag)?;
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [NIST SP 800-185]: https://doi.org/10.6028/NIST.SP.800-185
+
+use crate::{constant_time, error, keccak};
+
+// SP 800-185 Section 4.3.1: The function name string of KMAC.
+const FUNCTION_NAME: &[u8] = b"KMAC";
+
+// The shortest tag `verify` accepts, in bytes.
+const MIN_TAG_LEN: usize = 32 / 8;
+
+/// A KMAC128 key.
+#[derive(Clone)]
+pub struct Kmac128Key(Key);
+
+/// A KMAC256 key.
+#[derive(Clone)]
+pub struct Kmac256Key(Key);
+
+#[derive(Clone)]
+struct Key {
+    // The sponge after absorbing the customization and the key.
+    initial: keccak::Sponge,
+    name: &'static str,
+}
+
+impl Key {
+    // `rate` is the rate of KECCAK[2 * security_bits].
+    fn new(
+        name: &'static str,
+        rate: usize,
+        security_bits: usize,
+        key: &[u8],
+        customization: &str,
+    ) -> Result<Self, error::KeyRejected> {
+        // SP 800-185 Section 8.4.1: "The key K should be at least as long as
+        // the security strength."
+        if key.len() * 8 < security_bits {
+            return Err(error::KeyRejected::too_small());
+        }
+
+        let mut initial = keccak::Sponge::new(rate);
+
+        // cSHAKE: bytepad(encode_string(N) || encode_string(S), rate).
+        keccak::absorb_bytepad_start(&mut initial);
+        keccak::absorb_encode_string(&mut initial, FUNCTION_NAME);
+        keccak::absorb_encode_string(&mut initial, customization.as_bytes());
+        initial.absorb_zero_pad();
+
+        // KMAC: bytepad(encode_string(K), rate).
+        keccak::absorb_bytepad_start(&mut initial);
+        keccak::absorb_encode_string(&mut initial, key);
+        initial.absorb_zero_pad();
+
+        Ok(Self { initial, name })
+    }
+
+    fn start(&self) -> KmacContext {
+        KmacContext {
+            sponge: self.initial.clone(),
+            name: self.name,
+        }
+    }
+
+    fn verify(&self, msg: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+        let mut ctx = self.start();
+        ctx.update(msg);
+        ctx.verify(tag)
+    }
+}
+
+macro_rules! kmac_key {
+    ( $Key:ident, $name:expr, $rate:expr, $security_bits:expr ) => {
+        impl $Key {
+            #[doc = concat!("Constructs a ", $name, " key.")]
+            ///
+            /// `customization` is the customization string *S*; use `""`
+            /// for none.
+            ///
+            /// Fails if `key` is shorter than the security strength.
+            pub fn new(key: &[u8], customization: &str) -> Result<Self, error::KeyRejected> {
+                Key::new($name, $rate, $security_bits, key, customization).map(Self)
+            }
+
+            /// Starts computing a MAC incrementally.
+            #[inline]
+            pub fn start(&self) -> KmacContext {
+                self.0.start()
+            }
+
+            /// Computes the `output_len`-byte MAC of `msg`.
+            #[cfg(feature = "alloc")]
+            pub fn mac(&self, msg: &[u8], output_len: usize) -> alloc::vec::Vec<u8> {
+                let mut ctx = self.start();
+                ctx.update(msg);
+                ctx.finish(output_len)
+            }
+
+            /// Verifies that `tag` is the MAC of `msg` with the length of
+            /// `tag`, in constant time; see [`KmacContext::verify`].
+            pub fn verify(&self, msg: &[u8], tag: &[u8]) -> Result<(), error::Unspecified> {
+                self.0.verify(msg, tag)
+            }
+        }
+
+        impl core::fmt::Debug for $Key {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+                f.debug_struct(stringify!($Key)).finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+// The rates are those of KECCAK[256] and KECCAK[512] (SP 800-185 Section
+// 3.3).
+kmac_key!(Kmac128Key, "KMAC128", 168, 128);
+kmac_key!(Kmac256Key, "KMAC256", 136, 256);
+
+/// A context for computing a KMAC tag incrementally.
+#[derive(Clone)]
+pub struct KmacContext {
+    sponge: keccak::Sponge,
+    name: &'static str,
+}
+
+impl core::fmt::Debug for KmacContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("KmacContext")
+            .field("algorithm", &self.name)
+            .finish()
+    }
+}
+
+impl KmacContext {
+    /// Updates the MAC with all the data in `data`.
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data)
+    }
+
+    /// Finalizes the MAC, writing a tag of `out.len()` bytes to `out`.
+    ///
+    /// The length of `out` is an input to KMAC, so the first bytes of a
+    /// longer tag are not a shorter tag.
+    pub fn finish_into(mut self, out: &mut [u8]) {
+        keccak::absorb_right_encode(&mut self.sponge, keccak::bit_len(out.len()));
+        self.sponge.finish_absorbing(keccak::CSHAKE_SUFFIX);
+        self.sponge.squeeze(out);
+    }
+
+    /// Finalizes the MAC and returns a tag of `output_len` bytes.
+    #[cfg(feature = "alloc")]
+    pub fn finish(self, output_len: usize) -> alloc::vec::Vec<u8> {
+        let mut out = alloc::vec![0; output_len];
+        self.finish_into(&mut out);
+        out
+    }
+
+    /// Finalizes the MAC and verifies, in constant time, that `tag` is the
+    /// tag with the length of `tag`.
+    ///
+    /// Tags shorter than 32 bits are always rejected, following SP 800-185
+    /// Section 8.4.2.
+    pub fn verify(mut self, tag: &[u8]) -> Result<(), error::Unspecified> {
+        if tag.len() < MIN_TAG_LEN {
+            return Err(error::Unspecified);
+        }
+        keccak::absorb_right_encode(&mut self.sponge, keccak::bit_len(tag.len()));
+        self.sponge.finish_absorbing(keccak::CSHAKE_SUFFIX);
+
+        // Compare a block at a time so that no buffer as long as `tag` is
+        // needed.
+        let mut block = [0u8; 168];
+        let mut result = Ok(());
+        for tag in tag.chunks(self.sponge.rate()) {
+            let expected = &mut block[..tag.len()];
+            self.sponge.squeeze(expected);
+            let matches = constant_time::verify_slices_are_equal(expected, tag);
+            result = result.and(matches);
+        }
+        result
+    }
+}
@@ -22,6 +22,57 @@
 //!     <th>Description
 //! <tr><td><code>alloc (default)</code>
 //!     <td>Enable features that require use of the heap, RSA in particular.
+//! <tr><td><code>ct-table-scan</code>
+//!     <td>Make every lookup into a precomputed table of multiples of an
+//!         elliptic curve base point read the whole table, instead of only the
+//!         part of it for the current window. This is slower.
+//! <tr><td><code>ct-everything</code>
+//!     <td>Use the constant-time code paths even for operations on values
+//!         that are normally public: RSA signature verification computes
+//!         <i>s<sup>e</sup></i> without depending on <i>e</i>, ECDSA and
+//!         Ed25519 verification multiply and compare points without
+//!         variable-time algorithms or early exits, and every table lookup
+//!         reads the whole table. Implies <code>ct-table-scan</code>. The
+//!         results are the same. This makes RSA verification with
+//!         <i>e</i> = 65537 several times slower, and P-256 ECDSA and Ed25519
+//!         verification roughly twice as slow.
+//! <tr><td><code>debug-der</code>
+//!     <td>Enable <code>io::der::parse_with_offset()</code>, which reports
+//!         the offset at which parsing of DER-encoded input failed, for
+//!         debugging malformed keys.
+//! <tr><td><code>debug-prk</code>
+//!     <td>Show the value of an <code>hkdf::Prk</code> in its
+//!         <code>Debug</code> output and enable
+//!         <code>Prk::as_bytes_dangerous()</code>, for debugging and test
+//!         vector comparison. Implies `alloc`.
+//! <tr><td><code>diagnostics</code>
+//!     <td>Enable <code>signature::VerificationAlgorithm::verify_diagnostic()</code>,
+//!         which reports why a signature was rejected as an
+//!         <code>error::VerifyFailure</code>, for counting rejections.
+//! <tr><td><code>external-digest</code>
+//!     <td>Enable <code>digest::Algorithm::new_external()</code>, which
+//!         constructs a digest algorithm from functions implemented outside
+//!         of <i>ring</i>, and the constructors that use it with
+//!         <code>hmac</code>, <code>hkdf</code>, and <code>pbkdf2</code>. This
+//!         makes every digest and HMAC context larger, since it must have
+//!         room for an <code>ExternalState</code>.
+//! <tr><td><code>locked-memory</code>
+//!     <td>Keep the private keys of <code>hmac::Key</code>,
+//!         <code>signature::Ed25519KeyPair</code>, and
+//!         <code>rsa::KeyPair</code> in memory that is locked into RAM
+//!         (<code>mlock</code>, or <code>VirtualLock</code> on Windows) so
+//!         that it is never swapped out, and zeroize it when the key is
+//!         dropped. If memory can't be locked, the key is kept in unlocked
+//!         memory and its <code>was_locked()</code> method returns false.
+//!         Implies `std`.
+//! <tr><td><code>nonce_audit</code>
+//!     <td>Enable <code>aead::AuditedNonceSequence</code>, a tool for finding
+//!         nonce reuse in tests. Implies `std`.
+//! <tr><td><code>timing-tests</code>
+//!     <td>Enable <code>test::timing</code>, a dudect-style statistical
+//!         check that signing, key agreement, and tag verification take time
+//!         independent of their secrets, and the <code>timing_tests</code>
+//!         integration tests that use it. Implies `std`.
 //! <tr><td><code>std</code>
 //!     <td>Enable features that use libstd, in particular
 //!         <code>std::error::Error</code> integration. Implies `alloc`.
@@ -31,6 +82,9 @@
 //!         require an operating environment of some kind. This has no effect
 //!         for any other target. This enables the `getrandom` crate's `js`
 //!         feature.
+//! <tr><td><code>tracing</code>
+//!     <td>Emit <code>tracing</code> spans for cryptographic operations. See
+//!         the <code>trace</code> module for details.
 //! </table>
 
 // When running mk/package.sh, don't actually build any code.
@@ -88,33 +142,61 @@ mod bssl;
 #[macro_use]
 mod polyfill;
 
+#[macro_use]
+pub mod trace;
+
+#[macro_use]
+pub mod inventory;
+
 pub mod aead;
 
 pub mod agreement;
 
+mod b64;
 mod bits;
 
+#[cfg(feature = "std")]
+pub mod cms;
+
 pub(crate) mod c;
 pub mod constant_time;
+pub mod derive;
 
 pub mod io;
 
-mod cpu;
+pub mod cpu;
 pub mod digest;
+pub mod easy;
 mod ec;
 mod endian;
 pub mod error;
+pub mod fingerprint;
 pub mod hkdf;
 pub mod hmac;
+mod keccak;
+pub mod keytext;
+pub mod kmac;
 mod limb;
+mod locked;
+pub mod otp;
+pub mod password_hash;
 pub mod pbkdf2;
+
+#[cfg(feature = "std")]
+pub mod pem;
+
 pub mod pkcs8;
+pub mod policy;
 pub mod rand;
 
 #[cfg(feature = "alloc")]
 pub mod rsa;
 
 pub mod signature;
+pub mod tagged;
+
+#[cfg(feature = "alloc")]
+pub mod tls13;
 
 mod sealed {
     /// Traits that are designed to only be implemented internally in *ring*.
@@ -256,81 +256,80 @@ pub fn unstripped_be_bytes(limbs: &[Limb]) -> impl ExactSizeIterator<Item = u8>
 #[cfg(feature = "alloc")]
 pub type Window = Limb;
 
-/// Processes `limbs` as a sequence of 5-bit windows, folding the windows from
-/// most significant to least significant and returning the accumulated result.
-/// The first window will be mapped by `init` to produce the initial value for
-/// the accumulator. Then `f` will be called to fold the accumulator and the
-/// next window until all windows are processed. When the input's bit length
-/// isn't divisible by 5, the window passed to `init` will be partial; all
-/// windows passed to `fold` will be full.
+/// The 5-bit windows of `limbs`, from most significant to least significant.
+/// When the input's bit length isn't divisible by 5, the first window will be
+/// partial; all other windows will be full.
 ///
 /// This is designed to avoid leaking the contents of `limbs` through side
-/// channels as long as `init` and `fold` are side-channel free.
-///
-/// Panics if `limbs` is empty.
+/// channels; in particular, the position of each window depends only on the
+/// length of `limbs`.
 #[cfg(feature = "alloc")]
-pub fn fold_5_bit_windows<R, I: FnOnce(Window) -> R, F: Fn(R, Window) -> R>(
-    limbs: &[Limb],
-    init: I,
-    fold: F,
-) -> R {
-    #[derive(Clone, Copy)]
-    #[repr(transparent)]
-    struct BitIndex(Wrapping<c::size_t>);
+pub struct FiveBitWindows<'a> {
+    limbs: &'a [Limb],
+    remaining: usize,
+}
 
-    const WINDOW_BITS: Wrapping<c::size_t> = Wrapping(5);
+#[cfg(feature = "alloc")]
+impl<'a> FiveBitWindows<'a> {
+    const WINDOW_BITS: usize = 5;
+
+    /// Panics if `limbs` is empty.
+    pub fn new(limbs: &'a [Limb]) -> Self {
+        assert!(!limbs.is_empty());
+        let num_bits = limbs.len() * LIMB_BITS;
+        Self {
+            limbs,
+            remaining: (num_bits + Self::WINDOW_BITS - 1) / Self::WINDOW_BITS,
+        }
+    }
 
-    prefixed_extern! {
-        fn LIMBS_window5_split_window(
-            lower_limb: Limb,
-            higher_limb: Limb,
-            index_within_word: BitIndex,
-        ) -> Window;
-        fn LIMBS_window5_unsplit_window(limb: Limb, index_within_word: BitIndex) -> Window;
+    /// The number of windows that haven't been returned yet.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining
     }
+}
 
-    let num_limbs = limbs.len();
-    let mut window_low_bit = {
-        let num_whole_windows = (num_limbs * LIMB_BITS) / 5;
-        let mut leading_bits = (num_limbs * LIMB_BITS) - (num_whole_windows * 5);
-        if leading_bits == 0 {
-            leading_bits = WINDOW_BITS.0;
+#[cfg(feature = "alloc")]
+impl Iterator for FiveBitWindows<'_> {
+    type Item = Window;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
+        struct BitIndex(Wrapping<c::size_t>);
+
+        prefixed_extern! {
+            fn LIMBS_window5_split_window(
+                lower_limb: Limb,
+                higher_limb: Limb,
+                index_within_word: BitIndex,
+            ) -> Window;
+            fn LIMBS_window5_unsplit_window(limb: Limb, index_within_word: BitIndex) -> Window;
         }
-        BitIndex(Wrapping(LIMB_BITS - leading_bits))
-    };
-
-    let initial_value = {
-        let leading_partial_window =
-            unsafe { LIMBS_window5_split_window(*limbs.last().unwrap(), 0, window_low_bit) };
-        window_low_bit.0 -= WINDOW_BITS;
-        init(leading_partial_window)
-    };
-
-    let mut low_limb = 0;
-    limbs
-        .iter()
-        .rev()
-        .fold(initial_value, |mut acc, current_limb| {
-            let higher_limb = low_limb;
-            low_limb = *current_limb;
-
-            if window_low_bit.0 > Wrapping(LIMB_BITS) - WINDOW_BITS {
-                let window =
-                    unsafe { LIMBS_window5_split_window(low_limb, higher_limb, window_low_bit) };
-                window_low_bit.0 -= WINDOW_BITS;
-                acc = fold(acc, window);
-            };
-            while window_low_bit.0 < Wrapping(LIMB_BITS) {
-                let window = unsafe { LIMBS_window5_unsplit_window(low_limb, window_low_bit) };
-                // The loop exits when this subtraction underflows, causing `window_low_bit` to
-                // wrap around to a very large value.
-                window_low_bit.0 -= WINDOW_BITS;
-                acc = fold(acc, window);
-            }
-            window_low_bit.0 += Wrapping(LIMB_BITS); // "Fix" the underflow.
 
-            acc
-        })
+        self.remaining = self.remaining.checked_sub(1)?;
+
+        let window_low_bit = self.remaining * Self::WINDOW_BITS;
+        let lower_limb = self.limbs[window_low_bit / LIMB_BITS];
+        let index_within_word = BitIndex(Wrapping(window_low_bit % LIMB_BITS));
+        let window = if index_within_word.0 .0 > LIMB_BITS - Self::WINDOW_BITS {
+            // The leading window may extend past the last limb.
+            let higher_limb = self
+                .limbs
+                .get((window_low_bit / LIMB_BITS) + 1)
+                .copied()
+                .unwrap_or(0);
+            unsafe { LIMBS_window5_split_window(lower_limb, higher_limb, index_within_word) }
+        } else {
+            unsafe { LIMBS_window5_unsplit_window(lower_limb, index_within_word) }
+        };
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
 #[inline]
@@ -350,6 +349,23 @@ pub(crate) fn limbs_add_assign_mod(a: &mut [Limb], b: &[Limb], m: &[Limb]) {
     unsafe { LIMBS_add_mod(a.as_mut_ptr(), a.as_ptr(), b.as_ptr(), m.as_ptr(), m.len()) }
 }
 
+#[inline]
+pub(crate) fn limbs_sub_assign_mod(a: &mut [Limb], b: &[Limb], m: &[Limb]) {
+    debug_assert_eq!(a.len(), m.len());
+    debug_assert_eq!(b.len(), m.len());
+    prefixed_extern! {
+        // `r` and `a` may alias.
+        fn LIMBS_sub_mod(
+            r: *mut Limb,
+            a: *const Limb,
+            b: *const Limb,
+            m: *const Limb,
+            num_limbs: c::size_t,
+        );
+    }
+    unsafe { LIMBS_sub_mod(a.as_mut_ptr(), a.as_ptr(), b.as_ptr(), m.as_ptr(), m.len()) }
+}
+
 prefixed_extern! {
     fn LIMBS_are_zero(a: *const Limb, num_limbs: c::size_t) -> LimbMask;
     fn LIMBS_less_than(a: *const Limb, b: *const Limb, num_limbs: c::size_t) -> LimbMask;
@@ -600,6 +616,42 @@ mod tests {
         big_endian_from_limbs(&limbs[..], &mut out);
     }
 
+    // Round-trips byte strings whose bytes are all distinct through limbs, so
+    // that any byte swapping within or across limbs (e.g. a native-endian
+    // conversion that is only correct on little-endian targets) is detected.
+    #[test]
+    fn test_parse_big_endian_round_trip() {
+        const LIMBS: usize = 32 / LIMB_BYTES;
+        let mut input = [0u8; LIMBS * LIMB_BYTES];
+        for (b, i) in input.iter_mut().zip(1u8..) {
+            *b = i;
+        }
+
+        for len in 1..=input.len() {
+            let inp = &input[(input.len() - len)..];
+            let mut limbs = [0; LIMBS];
+            assert_eq!(
+                Ok(()),
+                parse_big_endian_and_pad_consttime(untrusted::Input::from(inp), &mut limbs)
+            );
+
+            // Compute the expected limbs arithmetically, independent of the
+            // target's byte order.
+            let mut expected = [0; LIMBS];
+            for (i, &b) in inp.iter().rev().enumerate() {
+                expected[i / LIMB_BYTES] |= Limb::from(b) << (8 * (i % LIMB_BYTES));
+            }
+            assert_eq!(limbs, expected);
+
+            let mut out = [0xab; LIMBS * LIMB_BYTES];
+            big_endian_from_limbs(&limbs, &mut out);
+            let (leading, rest) = out.split_at(out.len() - len);
+            assert!(leading.iter().all(|&b| b == 0));
+            assert_eq!(rest, inp);
+            assert!(unstripped_be_bytes(&limbs).eq(out.iter().copied()));
+        }
+    }
+
     #[test]
     fn test_limbs_minimal_bits() {
         const ALL_ONES: Limb = LimbMask::True as Limb;
@@ -623,4 +675,30 @@ mod tests {
             assert_eq!(limbs_minimal_bits(limbs).as_usize_bits(), *bits);
         }
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_five_bit_windows() {
+        let limbs: &[Limb] = &[0x0123_4567, MAX, 0, 0x89ab_cdef, 0x1f];
+        for len in 1..=limbs.len() {
+            let limbs = &limbs[..len];
+            let bit = |i: usize| -> Limb {
+                limbs
+                    .get(i / LIMB_BITS)
+                    .map_or(0, |limb| (limb >> (i % LIMB_BITS)) & 1)
+            };
+            let num_windows = (len * LIMB_BITS + 4) / 5;
+            let expected = (0..num_windows)
+                .rev()
+                .map(|w| (0..5).fold(0, |acc, i| acc | (bit(w * 5 + i) << i)));
+
+            let mut windows = FiveBitWindows::new(limbs);
+            assert_eq!(windows.remaining(), num_windows);
+            for expected in expected {
+                assert_eq!(windows.next(), Some(expected));
+            }
+            assert_eq!(windows.remaining(), 0);
+            assert_eq!(windows.next(), None);
+        }
+    }
 }
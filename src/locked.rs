@@ -0,0 +1,268 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Storage for long-lived secrets.
+//!
+//! With the `locked-memory` feature, `Secret` and `SecretSlice` keep their
+//! contents in memory that is locked into RAM (`mlock`, or `VirtualLock` on
+//! Windows) so that it is never written to swap, and zeroize it when they are
+//! dropped. Without the feature, a `Secret<T>` is just a `T`.
+//!
+//! Only the long-lived copy of a secret is protected this way; temporary
+//! copies made while constructing a key or while using it aren't.
+
+#[cfg(feature = "locked-memory")]
+mod pool;
+
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "locked-memory")]
+use {
+    crate::polyfill,
+    alloc::{alloc::Layout, boxed::Box},
+    core::marker::PhantomData,
+};
+
+/// A secret value of type `T`.
+pub(crate) struct Secret<T> {
+    #[cfg(not(feature = "locked-memory"))]
+    value: T,
+
+    #[cfg(feature = "locked-memory")]
+    block: pool::Block,
+    #[cfg(feature = "locked-memory")]
+    value: PhantomData<T>,
+}
+
+#[cfg(not(feature = "locked-memory"))]
+impl<T> Secret<T> {
+    #[inline]
+    pub(crate) fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+#[cfg(not(feature = "locked-memory"))]
+impl<T> Deref for Secret<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(not(feature = "locked-memory"))]
+impl<T> DerefMut for Secret<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T> Secret<T> {
+    pub(crate) fn new(value: T) -> Self {
+        let layout = Layout::new::<T>();
+        let block = match pool::allocate(layout) {
+            Some(block) => block,
+            None => alloc::alloc::handle_alloc_error(layout),
+        };
+        unsafe { core::ptr::write(block.as_ptr().cast::<T>(), value) };
+        Self {
+            block,
+            value: PhantomData,
+        }
+    }
+
+    /// Whether the value is in locked memory.
+    #[inline]
+    pub(crate) fn was_locked(&self) -> bool {
+        self.block.was_locked()
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T> Deref for Secret<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { &*self.block.as_ptr().cast::<T>() }
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T> DerefMut for Secret<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.block.as_ptr().cast::<T>() }
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T> Drop for Secret<T> {
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(self.block.as_ptr().cast::<T>()) };
+        // `Block` doesn't implement `Drop` and `self.block` isn't used again.
+        pool::free(unsafe { core::ptr::read(&self.block) });
+    }
+}
+
+impl<T: Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self::new(T::clone(self))
+    }
+}
+
+/// A boxed slice of secret values of type `T`, which may or may not be in
+/// locked memory.
+///
+/// Values are created in normal heap memory, like a `Box<[T]>`, and moved
+/// into locked memory with `into_locked` once they are known to be
+/// long-lived. A clone of a locked slice is also locked.
+#[cfg(feature = "locked-memory")]
+pub(crate) struct SecretSlice<T: Copy>(Slice<T>);
+
+#[cfg(feature = "locked-memory")]
+enum Slice<T> {
+    Heap(Box<[T]>),
+    Locked { block: pool::Block, len: usize },
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> SecretSlice<T> {
+    /// Moves the values into locked memory. If allocation fails, the values
+    /// are left where they are.
+    pub(crate) fn into_locked(mut self) -> Self {
+        if let Slice::Heap(values) = &mut self.0 {
+            if let Some(r) = Self::try_locked_copy(values) {
+                // Don't leave a copy behind in the heap.
+                let len = core::mem::size_of_val(&**values);
+                polyfill::zeroize(unsafe {
+                    core::slice::from_raw_parts_mut(values.as_mut_ptr().cast::<u8>(), len)
+                });
+                return r;
+            }
+        }
+        self
+    }
+
+    /// Whether the values are in locked memory.
+    pub(crate) fn was_locked(&self) -> bool {
+        match &self.0 {
+            Slice::Heap(_) => false,
+            Slice::Locked { block, .. } => block.was_locked(),
+        }
+    }
+
+    fn try_locked_copy(values: &[T]) -> Option<Self> {
+        let block = pool::allocate(Layout::array::<T>(values.len()).ok()?)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(values.as_ptr(), block.as_ptr().cast(), values.len())
+        };
+        Some(Self(Slice::Locked {
+            block,
+            len: values.len(),
+        }))
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> From<Box<[T]>> for SecretSlice<T> {
+    #[inline]
+    fn from(values: Box<[T]>) -> Self {
+        Self(Slice::Heap(values))
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> Deref for SecretSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match &self.0 {
+            Slice::Heap(values) => values,
+            Slice::Locked { block, len } => unsafe {
+                core::slice::from_raw_parts(block.as_ptr().cast(), *len)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> DerefMut for SecretSlice<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match &mut self.0 {
+            Slice::Heap(values) => values,
+            Slice::Locked { block, len } => unsafe {
+                core::slice::from_raw_parts_mut(block.as_ptr().cast(), *len)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> Clone for SecretSlice<T> {
+    fn clone(&self) -> Self {
+        match &self.0 {
+            Slice::Heap(values) => Self(Slice::Heap(values.clone())),
+            Slice::Locked { .. } => {
+                Self::try_locked_copy(self).unwrap_or_else(|| Self(Slice::Heap(Box::from(&**self))))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "locked-memory")]
+impl<T: Copy> Drop for SecretSlice<T> {
+    fn drop(&mut self) {
+        if let Slice::Locked { block, .. } = &self.0 {
+            // `Block` doesn't implement `Drop` and `block` isn't used again.
+            pool::free(unsafe { core::ptr::read(block) });
+        }
+    }
+}
+
+#[cfg(all(test, feature = "locked-memory"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret() {
+        let mut a = Secret::new([1u64, 2, 3]);
+        assert_eq!(*a, [1, 2, 3]);
+        a[1] = 4;
+        let b = a.clone();
+        assert_eq!(*b, [1, 4, 3]);
+        assert_eq!(a.was_locked(), b.was_locked());
+    }
+
+    #[test]
+    fn test_secret_slice() {
+        let heap = SecretSlice::from(alloc::vec![1u64, 2, 3].into_boxed_slice());
+        assert!(!heap.was_locked());
+        let mut locked = heap.into_locked();
+        assert_eq!(&*locked, &[1, 2, 3]);
+        locked[0] = 5;
+        let clone = locked.clone();
+        assert!(matches!(clone.0, Slice::Locked { .. }));
+        assert_eq!(&*clone, &[5, 2, 3]);
+
+        let empty = SecretSlice::<u64>::from(Box::from([])).into_locked();
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The allocator behind `Secret` and `SecretSlice`.
+//!
+//! Memory can only be locked a page at a time, and secrets are mostly much
+//! smaller than a page, so small allocations are carved out of locked pages
+//! that are shared between them. Each page is divided into `SLOTS` equally
+//! sized slots; an allocation takes as many consecutive slots as it needs. A
+//! page is unlocked and freed once all its slots are free. Allocations larger
+//! than half a page get pages of their own.
+//!
+//! When a page can't be locked, e.g. because `RLIMIT_MEMLOCK` is exhausted,
+//! it is used anyway and the allocations in it report that they aren't
+//! locked. Locking is tried again for every new page.
+
+extern crate std;
+
+use crate::polyfill;
+use alloc::{alloc::Layout, vec::Vec};
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use std::sync::{Mutex, MutexGuard};
+
+// The number of slots in a page, so that the free slots of a page can be
+// tracked in a `u64`.
+const SLOTS: usize = 64;
+
+/// Locks or unlocks the given pages, returning whether it succeeded.
+pub(super) type LockFn = fn(NonNull<u8>, usize) -> bool;
+
+/// Memory allocated from a `Pool`.
+pub(super) struct Block {
+    ptr: NonNull<u8>,
+    len: usize,
+    locked: bool,
+}
+
+impl Block {
+    #[inline]
+    pub(super) fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    pub(super) fn was_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+// `Block` is a uniquely-owned allocation.
+unsafe impl Send for Block {}
+unsafe impl Sync for Block {}
+
+struct Page {
+    base: NonNull<u8>,
+
+    // Bit `i` is set if slot `i` is in use.
+    used: u64,
+
+    locked: bool,
+}
+
+pub(super) struct Pool {
+    page_size: usize,
+    lock: LockFn,
+    unlock: LockFn,
+    pages: Vec<Page>,
+}
+
+// The pages are owned by the pool.
+unsafe impl Send for Pool {}
+
+impl Pool {
+    /// `page_size` must be a power of two that is a multiple of `SLOTS`.
+    pub(super) fn new(page_size: usize, lock: LockFn, unlock: LockFn) -> Self {
+        assert!(page_size.is_power_of_two() && page_size >= SLOTS);
+        Self {
+            page_size,
+            lock,
+            unlock,
+            pages: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn slot_size(&self) -> usize {
+        self.page_size / SLOTS
+    }
+
+    /// Returns zeroed memory for `layout`, or `None` if allocation fails.
+    pub(super) fn allocate(&mut self, layout: Layout) -> Option<Block> {
+        let slot_size = self.slot_size();
+        if layout.size() > self.page_size / 2 || layout.align() > slot_size {
+            return self.allocate_dedicated(layout);
+        }
+
+        // A zero-sized allocation still takes a slot, so that its address is
+        // unique and `free` can find its page.
+        let num_slots = core::cmp::max(1, (layout.size() + slot_size - 1) / slot_size);
+        let mask = u64::MAX >> (SLOTS - num_slots);
+
+        let found = self.pages.iter().enumerate().find_map(|(i, page)| {
+            (0..=(SLOTS - num_slots))
+                .find(|&slot| page.used & (mask << slot) == 0)
+                .map(|slot| (i, slot))
+        });
+        let (i, slot) = match found {
+            Some(found) => found,
+            None => {
+                self.pages.try_reserve(1).ok()?;
+                let (base, locked) = self.allocate_pages(self.page_layout(self.page_size))?;
+                self.pages.push(Page {
+                    base,
+                    used: 0,
+                    locked,
+                });
+                (self.pages.len() - 1, 0)
+            }
+        };
+
+        let page = &mut self.pages[i];
+        page.used |= mask << slot;
+        // Freed slots were zeroized by `free` and new pages are zeroed.
+        let ptr = unsafe { page.base.as_ptr().add(slot * slot_size) };
+        Some(Block {
+            ptr: NonNull::new(ptr).unwrap(),
+            len: num_slots * slot_size,
+            locked: page.locked,
+        })
+    }
+
+    /// Zeroizes `block` and returns it to the pool.
+    pub(super) fn free(&mut self, block: Block) {
+        polyfill::zeroize(unsafe { core::slice::from_raw_parts_mut(block.as_ptr(), block.len) });
+
+        let page_size = self.page_size;
+        let addr = block.ptr.as_ptr() as usize;
+        let found = self.pages.iter().position(|page| {
+            let base = page.base.as_ptr() as usize;
+            (base..(base + page_size)).contains(&addr)
+        });
+        let i = match found {
+            Some(i) => i,
+            None => {
+                let layout = self.page_layout(block.len);
+                self.free_pages(block.ptr, layout, block.locked);
+                return;
+            }
+        };
+
+        let slot_size = self.slot_size();
+        let page = &mut self.pages[i];
+        let slot = (addr - (page.base.as_ptr() as usize)) / slot_size;
+        let mask = u64::MAX >> (SLOTS - (block.len / slot_size));
+        debug_assert_eq!(page.used & (mask << slot), mask << slot);
+        page.used &= !(mask << slot);
+
+        if page.used == 0 {
+            let page = self.pages.swap_remove(i);
+            let layout = self.page_layout(page_size);
+            self.free_pages(page.base, layout, page.locked);
+        }
+    }
+
+    fn allocate_dedicated(&mut self, layout: Layout) -> Option<Block> {
+        let page_size = self.page_size;
+        assert!(layout.align() <= page_size);
+        let len = (layout.size() + page_size - 1) / page_size * page_size;
+        let (ptr, locked) = self.allocate_pages(self.page_layout(len))?;
+        Some(Block { ptr, len, locked })
+    }
+
+    fn page_layout(&self, len: usize) -> Layout {
+        Layout::from_size_align(len, self.page_size).unwrap()
+    }
+
+    fn allocate_pages(&mut self, layout: Layout) -> Option<(NonNull<u8>, bool)> {
+        let ptr = NonNull::new(unsafe { alloc::alloc::alloc_zeroed(layout) })?;
+        let locked = (self.lock)(ptr, layout.size());
+        Some((ptr, locked))
+    }
+
+    fn free_pages(&mut self, ptr: NonNull<u8>, layout: Layout, locked: bool) {
+        if locked {
+            let _: bool = (self.unlock)(ptr, layout.size());
+        }
+        unsafe { alloc::alloc::dealloc(ptr.as_ptr(), layout) }
+    }
+
+    #[cfg(test)]
+    fn num_pages(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+/// Allocates zeroed memory for `layout` from the process-wide pool, or
+/// returns `None` if allocation fails.
+pub(super) fn allocate(layout: Layout) -> Option<Block> {
+    global()?.allocate(layout)
+}
+
+/// Zeroizes `block` and returns it to the process-wide pool.
+pub(super) fn free(block: Block) {
+    // `block` came from the pool, so the pool exists.
+    global().unwrap().free(block)
+}
+
+fn global() -> Option<MutexGuard<'static, Pool>> {
+    // `Mutex::new` isn't `const` in our MSRV, so the pool is created on first
+    // use and never freed. It is allocated without `Box::new` so that
+    // allocation failure can be reported.
+    static POOL: AtomicPtr<Mutex<Pool>> = AtomicPtr::new(core::ptr::null_mut());
+
+    let mut pool = POOL.load(Ordering::Acquire);
+    if pool.is_null() {
+        let layout = Layout::new::<Mutex<Pool>>();
+        let new = unsafe { alloc::alloc::alloc(layout) }.cast::<Mutex<Pool>>();
+        if new.is_null() {
+            return None;
+        }
+        let value = Mutex::new(Pool::new(os::page_size(), os::lock, os::unlock));
+        unsafe { core::ptr::write(new, value) };
+        pool = match POOL.compare_exchange(
+            core::ptr::null_mut(),
+            new,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => new,
+            Err(existing) => {
+                unsafe {
+                    core::ptr::drop_in_place(new);
+                    alloc::alloc::dealloc(new.cast(), layout);
+                }
+                existing
+            }
+        };
+    }
+    // The pool's invariants hold even if a thread panicked while holding the
+    // lock, since none of its operations can panic part-way through.
+    Some(
+        unsafe { &*pool }
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    )
+}
+
+#[cfg(unix)]
+mod os {
+    use core::ptr::NonNull;
+
+    pub(super) fn page_size() -> usize {
+        match usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }) {
+            Ok(size) if size > 0 => size,
+            _ => 4096,
+        }
+    }
+
+    pub(super) fn lock(ptr: NonNull<u8>, len: usize) -> bool {
+        unsafe { libc::mlock(ptr.as_ptr().cast(), len) == 0 }
+    }
+
+    pub(super) fn unlock(ptr: NonNull<u8>, len: usize) -> bool {
+        unsafe { libc::munlock(ptr.as_ptr().cast(), len) == 0 }
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use core::{ffi::c_void, ptr::NonNull};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn VirtualLock(address: *const c_void, size: usize) -> i32;
+        fn VirtualUnlock(address: *const c_void, size: usize) -> i32;
+    }
+
+    // The page size of every architecture Windows supports.
+    pub(super) fn page_size() -> usize {
+        4096
+    }
+
+    pub(super) fn lock(ptr: NonNull<u8>, len: usize) -> bool {
+        unsafe { VirtualLock(ptr.as_ptr().cast(), len) != 0 }
+    }
+
+    pub(super) fn unlock(ptr: NonNull<u8>, len: usize) -> bool {
+        unsafe { VirtualUnlock(ptr.as_ptr().cast(), len) != 0 }
+    }
+}
+
+// Memory can't be locked on other targets; everything is allocated unlocked.
+#[cfg(not(any(unix, windows)))]
+mod os {
+    use core::ptr::NonNull;
+
+    pub(super) fn page_size() -> usize {
+        4096
+    }
+
+    pub(super) fn lock(_: NonNull<u8>, _: usize) -> bool {
+        false
+    }
+
+    pub(super) fn unlock(_: NonNull<u8>, _: usize) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: usize = 4096;
+
+    fn succeed(_: NonNull<u8>, _: usize) -> bool {
+        true
+    }
+
+    fn fail(_: NonNull<u8>, _: usize) -> bool {
+        false
+    }
+
+    fn bytes(block: &Block) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(block.as_ptr(), block.len) }
+    }
+
+    #[test]
+    fn test_slab_shares_pages() {
+        let mut pool = Pool::new(PAGE_SIZE, succeed, succeed);
+        let small = Layout::from_size_align(32, 8).unwrap();
+        let blocks = (0..SLOTS)
+            .map(|_| pool.allocate(small).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pool.num_pages(), 1);
+        assert!(blocks.iter().all(Block::was_locked));
+
+        let extra = pool.allocate(small).unwrap();
+        assert_eq!(pool.num_pages(), 2);
+        pool.free(extra);
+        assert_eq!(pool.num_pages(), 1);
+
+        blocks.into_iter().for_each(|block| pool.free(block));
+        assert_eq!(pool.num_pages(), 0);
+    }
+
+    #[test]
+    fn test_multi_slot_and_dedicated() {
+        let mut pool = Pool::new(PAGE_SIZE, succeed, succeed);
+        let slot_size = PAGE_SIZE / SLOTS;
+
+        let a = pool
+            .allocate(Layout::from_size_align(slot_size + 1, 8).unwrap())
+            .unwrap();
+        let b = pool
+            .allocate(Layout::from_size_align(PAGE_SIZE / 2, 8).unwrap())
+            .unwrap();
+        assert_eq!(a.len, 2 * slot_size);
+        assert_eq!(b.len, PAGE_SIZE / 2);
+        assert_eq!(pool.num_pages(), 1);
+
+        // Too large for the slab.
+        let c = pool
+            .allocate(Layout::from_size_align(PAGE_SIZE + 1, 8).unwrap())
+            .unwrap();
+        assert_eq!(c.len, 2 * PAGE_SIZE);
+        assert_eq!(c.as_ptr() as usize % PAGE_SIZE, 0);
+        assert!(c.was_locked());
+        assert_eq!(pool.num_pages(), 1);
+
+        [a, b, c].into_iter().for_each(|block| pool.free(block));
+        assert_eq!(pool.num_pages(), 0);
+    }
+
+    #[test]
+    fn test_lock_failure_falls_back() {
+        let mut pool = Pool::new(PAGE_SIZE, fail, fail);
+        let small = pool
+            .allocate(Layout::from_size_align(16, 8).unwrap())
+            .unwrap();
+        let large = pool
+            .allocate(Layout::from_size_align(2 * PAGE_SIZE, 8).unwrap())
+            .unwrap();
+        assert!(!small.was_locked());
+        assert!(!large.was_locked());
+        assert!(bytes(&small).iter().all(|&b| b == 0));
+        assert!(bytes(&large).iter().all(|&b| b == 0));
+        pool.free(small);
+        pool.free(large);
+        assert_eq!(pool.num_pages(), 0);
+    }
+
+    #[test]
+    fn test_lock_status_is_per_page() {
+        fn fail_once(_: NonNull<u8>, _: usize) -> bool {
+            use core::sync::atomic::AtomicBool;
+            static FAILED: AtomicBool = AtomicBool::new(false);
+            FAILED.swap(true, Ordering::Relaxed)
+        }
+
+        let mut pool = Pool::new(PAGE_SIZE, fail_once, succeed);
+        let layout = Layout::from_size_align(PAGE_SIZE / 2, 8).unwrap();
+        let blocks = (0..4)
+            .map(|_| pool.allocate(layout).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pool.num_pages(), 2);
+        let locked = blocks.iter().map(Block::was_locked).collect::<Vec<_>>();
+        assert_eq!(locked, [false, false, true, true]);
+        blocks.into_iter().for_each(|block| pool.free(block));
+    }
+
+    #[test]
+    fn test_free_zeroizes() {
+        let mut pool = Pool::new(PAGE_SIZE, succeed, succeed);
+        let layout = Layout::from_size_align(64, 8).unwrap();
+        let a = pool.allocate(layout).unwrap();
+        let b = pool.allocate(layout).unwrap();
+        let a_ptr = a.ptr;
+        unsafe { core::ptr::write_bytes(a.as_ptr(), 0xff, 64) };
+        pool.free(a);
+
+        // `b` keeps the page alive, so the freed slot can still be read.
+        let freed = unsafe { core::slice::from_raw_parts(a_ptr.as_ptr(), 64) };
+        assert!(freed.iter().all(|&b| b == 0));
+
+        // The freed slot is reused.
+        let c = pool.allocate(layout).unwrap();
+        assert_eq!(c.ptr, a_ptr);
+        pool.free(b);
+        pool.free(c);
+    }
+
+    #[test]
+    fn test_global() {
+        let layout = Layout::from_size_align(32, 8).unwrap();
+        let block = allocate(layout).unwrap();
+        assert_eq!(block.as_ptr() as usize % 8, 0);
+        free(block);
+    }
+}
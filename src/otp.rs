@@ -0,0 +1,145 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! HMAC-based one-time passwords: HOTP ([RFC 4226]) and TOTP ([RFC 6238]).
+//!
+//! The algorithm of the `hmac::Key` selects the hash function. RFC 6238
+//! allows HMAC-SHA1, HMAC-SHA256, and HMAC-SHA512; most authenticator apps
+//! only support HMAC-SHA1, for which the key should be constructed with
+//! [`hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY`].
+//!
+//! Codes have 6, 7, or 8 decimal digits. They're returned as integers, so a
+//! code with leading zeros must be formatted with [`format_code`] before
+//! being displayed.
+//!
+//! ```
+//! use ring::{hmac, otp};
+//!
+//! let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, b"12345678901234567890");
+//! let code = otp::totp(&key, 1111111109, 30, 8)?;
+//! assert_eq!(otp::format_code(code, 8)?, "07081804");
+//! assert!(otp::verify_totp(&key, 1111111109 + 30, 30, 8, 1, code).is_ok());
+//! # Ok::<(), ring::error::Unspecified>(())
+//! ```
+//!
+//! [RFC 4226]: https://tools.ietf.org/html/rfc4226
+//! [RFC 6238]: https://tools.ietf.org/html/rfc6238
+
+use crate::{constant_time, error, hmac};
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// The smallest supported number of digits.
+pub const MIN_DIGITS: u8 = 6;
+
+/// The largest supported number of digits.
+pub const MAX_DIGITS: u8 = 8;
+
+/// Computes the `digits`-digit HOTP code for `counter`.
+///
+/// Fails if `digits` isn't in `MIN_DIGITS..=MAX_DIGITS`.
+pub fn hotp(key: &hmac::Key, counter: u64, digits: u8) -> Result<u32, error::Unspecified> {
+    let modulus = modulus(digits)?;
+    let tag = hmac::sign(key, &counter.to_be_bytes());
+    let tag = tag.as_ref();
+
+    // RFC 4226 Section 5.3: The low four bits of the last byte are the offset
+    // of the four bytes to use, which are big endian and have their top bit
+    // cleared. Every digest is at least 20 bytes, so the offset is in range.
+    let offset = usize::from(tag[tag.len() - 1] & 0x0f);
+    let truncated = [
+        tag[offset],
+        tag[offset + 1],
+        tag[offset + 2],
+        tag[offset + 3],
+    ];
+    let truncated = u32::from_be_bytes(truncated) & 0x7fff_ffff;
+
+    Ok(truncated % modulus)
+}
+
+/// Computes the `digits`-digit TOTP code for the time step containing
+/// `unix_time`, with steps of `step` seconds starting at the Unix epoch.
+///
+/// Fails if `step` is zero or if `digits` isn't in `MIN_DIGITS..=MAX_DIGITS`.
+pub fn totp(
+    key: &hmac::Key,
+    unix_time: u64,
+    step: u64,
+    digits: u8,
+) -> Result<u32, error::Unspecified> {
+    hotp(key, time_step(unix_time, step)?, digits)
+}
+
+/// Verifies that `code` is the TOTP code for the time step containing
+/// `unix_time`, or for one of the `skew_steps` steps before or after it.
+///
+/// Each candidate code is compared in constant time, and every candidate in
+/// the window is compared even after a match is found, so the time taken
+/// doesn't reveal which step matched.
+///
+/// Callers must prevent replays themselves, e.g. by remembering the last
+/// accepted time step; a code remains valid for the whole window.
+///
+/// Fails if `code` doesn't match, if `step` is zero, or if `digits` isn't in
+/// `MIN_DIGITS..=MAX_DIGITS`.
+pub fn verify_totp(
+    key: &hmac::Key,
+    unix_time: u64,
+    step: u64,
+    digits: u8,
+    skew_steps: u8,
+    code: u32,
+) -> Result<(), error::Unspecified> {
+    let _: u32 = modulus(digits)?;
+    let current = time_step(unix_time, step)?;
+    let first = current.saturating_sub(u64::from(skew_steps));
+    let last = current.saturating_add(u64::from(skew_steps));
+
+    let mut matched = false;
+    for counter in first..=last {
+        let candidate = hotp(key, counter, digits)?;
+        matched |=
+            constant_time::verify_slices_are_equal(&candidate.to_be_bytes(), &code.to_be_bytes())
+                .is_ok();
+    }
+    if !matched {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+/// Formats `code` as exactly `digits` decimal digits, with leading zeros.
+///
+/// Fails if `digits` isn't in `MIN_DIGITS..=MAX_DIGITS` or if `code` has more
+/// than `digits` digits.
+#[cfg(feature = "alloc")]
+pub fn format_code(code: u32, digits: u8) -> Result<String, error::Unspecified> {
+    if code >= modulus(digits)? {
+        return Err(error::Unspecified);
+    }
+    Ok(alloc::format!("{:01$}", code, usize::from(digits)))
+}
+
+fn modulus(digits: u8) -> Result<u32, error::Unspecified> {
+    if !(MIN_DIGITS..=MAX_DIGITS).contains(&digits) {
+        return Err(error::Unspecified);
+    }
+    Ok(10u32.pow(u32::from(digits)))
+}
+
+fn time_step(unix_time: u64, step: u64) -> Result<u64, error::Unspecified> {
+    unix_time.checked_div(step).ok_or(error::Unspecified)
+}
@@ -0,0 +1,251 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Password hashing with PBKDF2-HMAC-SHA256.
+//!
+//! This is for deployments, such as some FIPS 140-3 profiles, that require
+//! PBKDF2 with HMAC-SHA-256 for password storage. Where there is no such
+//! requirement, a memory-hard password hash is a better choice.
+//!
+//! A [`PasswordHash`] is stored as a string in the format used by Python's
+//! passlib and other libraries:
+//!
+//! ```text
+//! $pbkdf2-sha256$<iterations>$<salt>$<hash>
+//! ```
+//!
+//! where `<iterations>` is in decimal and `<salt>` and `<hash>` are encoded
+//! in base64 without padding, using `.` in place of `+`.
+//!
+//! # Migrating from other password hashes
+//!
+//! Hashes in other formats, e.g. bcrypt's `$2b$...`, are rejected when
+//! parsed, so the scheme of a stored hash can be detected by trying to parse
+//! it. Such a hash can be replaced after the user's next successful login:
+//! verify the password with the old scheme, then store the result of
+//! [`Pbkdf2PasswordHasher::hash_password`].
+//!
+//! [`Pbkdf2PasswordHasher::verify_and_upgrade`] does the same for hashes
+//! created with weaker parameters than the hasher's, for example when the
+//! iteration count is raised.
+
+use crate::{b64, error, pbkdf2, rand};
+use core::num::NonZeroU32;
+
+/// The smallest iteration count a [`Pbkdf2PasswordHasher`] accepts.
+pub const MIN_ITERATIONS: u32 = 100_000;
+
+/// The shortest salt, in bytes, a [`Pbkdf2PasswordHasher`] accepts.
+///
+/// This is the 128-bit minimum of [NIST Special Publication 800-132].
+///
+/// [NIST Special Publication 800-132]:
+///     http://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-132.pdf
+pub const MIN_SALT_LEN: usize = 128 / 8;
+
+/// The longest salt, in bytes, of a [`PasswordHash`].
+pub const MAX_SALT_LEN: usize = 64;
+
+/// The length of the hash value of a [`PasswordHash`].
+pub const HASH_LEN: usize = 256 / 8;
+
+const ALGORITHM: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA256;
+
+const PREFIX: &str = "$pbkdf2-sha256$";
+
+/// Hashes and verifies passwords with PBKDF2-HMAC-SHA256.
+#[derive(Clone, Copy, Debug)]
+pub struct Pbkdf2PasswordHasher {
+    iterations: NonZeroU32,
+    salt_len: usize,
+}
+
+impl Pbkdf2PasswordHasher {
+    /// Constructs a hasher that hashes passwords with `iterations` iterations
+    /// of PBKDF2 and a random salt of `salt_len` bytes.
+    ///
+    /// Fails if `iterations` is less than [`MIN_ITERATIONS`] or if `salt_len`
+    /// is not in the range `MIN_SALT_LEN..=MAX_SALT_LEN`.
+    pub fn new(iterations: u32, salt_len: usize) -> Result<Self, error::Unspecified> {
+        if iterations < MIN_ITERATIONS {
+            return Err(error::Unspecified);
+        }
+        if !(MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt_len) {
+            return Err(error::Unspecified);
+        }
+        let iterations = NonZeroU32::new(iterations).ok_or(error::Unspecified)?;
+        Ok(Self {
+            iterations,
+            salt_len,
+        })
+    }
+
+    /// Hashes `password` with a new random salt from `rng`.
+    pub fn hash_password(
+        &self,
+        password: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<PasswordHash, error::Unspecified> {
+        let mut salt = [0u8; MAX_SALT_LEN];
+        rng.fill(&mut salt[..self.salt_len])?;
+        let mut hash = [0u8; HASH_LEN];
+        pbkdf2::derive(
+            ALGORITHM,
+            self.iterations,
+            &salt[..self.salt_len],
+            password,
+            &mut hash,
+        );
+        Ok(PasswordHash {
+            iterations: self.iterations,
+            salt,
+            salt_len: self.salt_len,
+            hash,
+        })
+    }
+
+    /// Verifies that `hash` is a hash of `password`.
+    ///
+    /// The iteration count and salt stored in `hash` are used, even if they
+    /// are weaker than this hasher's; use [`Self::needs_rehash`] or
+    /// [`Self::verify_and_upgrade`] to detect that.
+    pub fn verify_password(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash,
+    ) -> Result<(), error::Unspecified> {
+        pbkdf2::verify(
+            ALGORITHM,
+            hash.iterations,
+            hash.salt(),
+            password,
+            &hash.hash,
+        )
+    }
+
+    /// Returns `true` if `hash` was created with fewer iterations or a
+    /// shorter salt than this hasher uses.
+    pub fn needs_rehash(&self, hash: &PasswordHash) -> bool {
+        hash.iterations < self.iterations || hash.salt_len < self.salt_len
+    }
+
+    /// Verifies that `hash` is a hash of `password` and, if `hash` needs to
+    /// be rehashed, returns a new hash of `password` to replace it.
+    ///
+    /// This is for upgrading stored hashes at login, the only time the
+    /// password is available.
+    pub fn verify_and_upgrade(
+        &self,
+        password: &[u8],
+        hash: &PasswordHash,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Option<PasswordHash>, error::Unspecified> {
+        self.verify_password(password, hash)?;
+        if !self.needs_rehash(hash) {
+            return Ok(None);
+        }
+        self.hash_password(password, rng).map(Some)
+    }
+}
+
+/// A salted PBKDF2-HMAC-SHA256 password hash.
+///
+/// `PasswordHash` implements `Display` and `FromStr` for the
+/// `$pbkdf2-sha256$...` format described in the [module-level
+/// documentation](self).
+#[derive(Clone)]
+pub struct PasswordHash {
+    iterations: NonZeroU32,
+    salt: [u8; MAX_SALT_LEN],
+    salt_len: usize,
+    hash: [u8; HASH_LEN],
+}
+
+impl PasswordHash {
+    /// The PBKDF2 iteration count.
+    #[inline]
+    pub fn iterations(&self) -> NonZeroU32 {
+        self.iterations
+    }
+
+    /// The salt.
+    #[inline]
+    pub fn salt(&self) -> &[u8] {
+        &self.salt[..self.salt_len]
+    }
+
+    /// The hash value.
+    #[inline]
+    pub fn hash(&self) -> &[u8; HASH_LEN] {
+        &self.hash
+    }
+}
+
+impl core::fmt::Debug for PasswordHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("PasswordHash")
+            .field("iterations", &self.iterations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl core::fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}{}$", PREFIX, self.iterations)?;
+        b64::encode(&b64::AB64, self.salt(), f)?;
+        f.write_str("$")?;
+        b64::encode(&b64::AB64, &self.hash, f)
+    }
+}
+
+impl core::str::FromStr for PasswordHash {
+    type Err = error::Unspecified;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix(PREFIX).ok_or(error::Unspecified)?;
+        let mut fields = s.split('$');
+        let iterations = fields.next().ok_or(error::Unspecified)?;
+        let salt = fields.next().ok_or(error::Unspecified)?;
+        let hash = fields.next().ok_or(error::Unspecified)?;
+        if fields.next().is_some() {
+            return Err(error::Unspecified);
+        }
+
+        // Require the canonical decimal encoding so that `Display` round-trips.
+        if iterations.starts_with('0') || !iterations.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error::Unspecified);
+        }
+        let iterations = iterations
+            .parse::<NonZeroU32>()
+            .map_err(|_| error::Unspecified)?;
+
+        let mut salt_buf = [0u8; MAX_SALT_LEN];
+        let salt_len = b64::decode(&b64::AB64, salt, &mut salt_buf)?;
+        if salt_len == 0 {
+            return Err(error::Unspecified);
+        }
+
+        let mut hash_buf = [0u8; HASH_LEN];
+        if b64::decode(&b64::AB64, hash, &mut hash_buf)? != HASH_LEN {
+            return Err(error::Unspecified);
+        }
+
+        Ok(Self {
+            iterations,
+            salt: salt_buf,
+            salt_len,
+            hash: hash_buf,
+        })
+    }
+}
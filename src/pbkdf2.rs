@@ -17,6 +17,9 @@
 //! Use `derive` to derive PBKDF2 outputs. Use `verify` to verify secret
 //! against previously-derived outputs.
 //!
+//! Use `verify_phc` to verify a password against a hash stored in the PHC
+//! string format, and `format_phc` to create such strings.
+//!
 //! PBKDF2 is specified in [RFC 2898 Section 5.2] with test vectors given in
 //! [RFC 6070]. See also [NIST Special Publication 800-132].
 //!
@@ -115,10 +118,28 @@
 use crate::{constant_time, digest, error, hmac};
 use core::num::NonZeroU32;
 
+mod phc;
+
+#[cfg(feature = "alloc")]
+pub use phc::format_phc;
+pub use phc::{verify_phc, VerifyError};
+
 /// A PBKDF2 algorithm.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Algorithm(hmac::Algorithm);
 
+impl Algorithm {
+    /// PBKDF2 using `hmac_algorithm`, e.g. one based on a digest algorithm
+    /// constructed with [`digest::Algorithm::new_external()`].
+    ///
+    /// The result has no PHC identifier, so `format_phc()` panics if it is
+    /// given the result, and `verify_phc()` never uses it.
+    #[cfg(feature = "external-digest")]
+    pub const fn from_hmac_algorithm(hmac_algorithm: hmac::Algorithm) -> Self {
+        Self(hmac_algorithm)
+    }
+}
+
 /// PBKDF2 using HMAC-SHA1.
 pub static PBKDF2_HMAC_SHA1: Algorithm = Algorithm(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY);
 
@@ -180,6 +201,34 @@ pub fn derive(
     }
 }
 
+/// Derives an HMAC key using PBKDF2, for protocols that derive a MAC key
+/// from a password.
+///
+/// The key is `digest_alg.output_len` bytes long, as recommended in
+/// [RFC 2104 Section 3], and is used with the same HMAC algorithm as the
+/// PBKDF2 PRF. The derived key value is never exposed to the caller.
+///
+/// | Parameter   | RFC 2898 Section 5.2 Term
+/// |-------------|-------------------------------------------
+/// | digest_alg  | PRF (HMAC with the given digest algorithm)
+/// | iterations  | c (iteration count)
+/// | salt        | S (salt)
+/// | secret      | P (password)
+///
+/// [RFC 2104 Section 3]: https://tools.ietf.org/html/rfc2104#section-3
+pub fn derive_hmac_key(
+    algorithm: Algorithm,
+    iterations: NonZeroU32,
+    salt: &[u8],
+    secret: &[u8],
+) -> hmac::Key {
+    hmac::Key::construct(algorithm.0, |out| {
+        derive(algorithm, iterations, salt, secret, out);
+        Ok(())
+    })
+    .unwrap()
+}
+
 fn derive_block(secret: &hmac::Key, iterations: NonZeroU32, salt: &[u8], idx: u32, out: &mut [u8]) {
     let mut ctx = hmac::Context::with_key(secret);
     ctx.update(salt);
@@ -207,6 +256,13 @@ fn derive_block(secret: &hmac::Key, iterations: NonZeroU32, salt: &[u8], idx: u3
 /// comparison will fail if `previously_derived` is empty (has a length of
 /// zero).
 ///
+/// The time taken depends only on `algorithm`, `iterations`, and the lengths
+/// of `salt`, `secret`, and `previously_derived`; in particular, it doesn't
+/// depend on whether, or where, the derived value differs from
+/// `previously_derived`. Every block is computed with the full iteration
+/// count, and the blocks are compared only after all of them have been
+/// computed.
+///
 /// | Parameter                  | RFC 2898 Section 5.2 Term
 /// |----------------------------|--------------------------------------------
 /// | digest_alg                 | PRF (HMAC with the given digest algorithm).
@@ -233,13 +289,15 @@ pub fn verify(
         return Err(error::Unspecified);
     }
 
-    let mut derived_buf = [0u8; digest::MAX_OUTPUT_LEN];
-
     let output_len = digest_alg.output_len();
     let secret = hmac::Key::new(algorithm.0, secret);
     let mut idx: u32 = 0;
 
-    let mut matches = 1;
+    // The bitwise OR of the differences between each derived block and the
+    // corresponding block of `previously_derived`. Nothing is compared until
+    // every block has been derived.
+    let mut differences = [0u8; digest::MAX_OUTPUT_LEN];
+    let mut derived_buf = [0u8; digest::MAX_OUTPUT_LEN];
 
     for previously_derived_chunk in previously_derived.chunks(output_len) {
         idx = idx.checked_add(1).expect("derived key too long");
@@ -249,23 +307,21 @@ pub fn verify(
 
         derive_block(&secret, iterations, salt, idx, derived_chunk);
 
-        // XXX: This isn't fully constant-time-safe. TODO: Fix that.
-        #[allow(clippy::bool_to_int_with_if)]
-        let current_block_matches =
-            if constant_time::verify_slices_are_equal(derived_chunk, previously_derived_chunk)
-                .is_ok()
-            {
-                1
-            } else {
-                0
-            };
-
-        matches &= current_block_matches;
+        differences
+            .iter_mut()
+            .zip(derived_chunk.iter().zip(previously_derived_chunk))
+            .for_each(|(d, (a, b))| *d |= a ^ b);
     }
 
-    if matches == 0 {
-        return Err(error::Unspecified);
-    }
+    constant_time::verify_slices_are_equal(
+        &differences[..output_len],
+        &[0u8; digest::MAX_OUTPUT_LEN][..output_len],
+    )
+}
 
-    Ok(())
+inventory! { Kdf, Always;
+    PBKDF2_HMAC_SHA1,
+    PBKDF2_HMAC_SHA256,
+    PBKDF2_HMAC_SHA384,
+    PBKDF2_HMAC_SHA512,
 }
@@ -0,0 +1,201 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! PBKDF2 hashes in the [PHC string format].
+//!
+//! [PHC string format]:
+//!     https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::{
+    verify, Algorithm, PBKDF2_HMAC_SHA1, PBKDF2_HMAC_SHA256, PBKDF2_HMAC_SHA384, PBKDF2_HMAC_SHA512,
+};
+use crate::{b64, digest, error};
+use core::num::NonZeroU32;
+
+// The smallest iteration count `verify_phc` accepts.
+const MIN_ITERATIONS: u32 = 1000;
+
+// The shortest hash, in bytes, `verify_phc` accepts.
+const MIN_HASH_LEN: usize = 16;
+
+const MAX_HASH_LEN: usize = digest::MAX_OUTPUT_LEN;
+
+const MAX_SALT_LEN: usize = 64;
+
+static IDS: [(&str, &Algorithm); 4] = [
+    ("pbkdf2", &PBKDF2_HMAC_SHA1),
+    ("pbkdf2-sha256", &PBKDF2_HMAC_SHA256),
+    ("pbkdf2-sha384", &PBKDF2_HMAC_SHA384),
+    ("pbkdf2-sha512", &PBKDF2_HMAC_SHA512),
+];
+
+/// The error returned by [`verify_phc`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The stored string isn't a well-formed PHC string for PBKDF2.
+    Malformed,
+
+    /// The stored string is for an algorithm other than PBKDF2 with SHA-1,
+    /// SHA-256, SHA-384, or SHA-512.
+    UnsupportedAlgorithm,
+
+    /// The stored string has fewer than 1000 iterations or a hash shorter
+    /// than 16 bytes.
+    WeakParameters,
+
+    /// The password doesn't match.
+    Mismatch,
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Malformed => "malformed PHC string",
+            Self::UnsupportedAlgorithm => "unsupported password hash algorithm",
+            Self::WeakParameters => "password hash parameters are too weak",
+            Self::Mismatch => "password mismatch",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+impl From<VerifyError> for error::Unspecified {
+    fn from(_: VerifyError) -> Self {
+        Self
+    }
+}
+
+/// Verifies `password` against `stored`, a PBKDF2 hash in the PHC string
+/// format:
+///
+/// ```text
+/// $<id>$i=<iterations>[,l=<hash length>]$<salt>$<hash>
+/// ```
+///
+/// where `<id>` is `pbkdf2` (for HMAC-SHA1), `pbkdf2-sha256`,
+/// `pbkdf2-sha384`, or `pbkdf2-sha512`, and `<salt>` and `<hash>` are in
+/// base64 without padding.
+///
+/// `stored` is completely parsed and checked before `password` is used, so
+/// the errors other than [`VerifyError::Mismatch`] don't depend on
+/// `password`. The hash is compared in constant time.
+pub fn verify_phc(stored: &str, password: &[u8]) -> Result<(), VerifyError> {
+    let stored = stored.strip_prefix('$').ok_or(VerifyError::Malformed)?;
+    let mut fields = stored.split('$');
+    let mut next_field = || fields.next().ok_or(VerifyError::Malformed);
+    let id = next_field()?;
+    let algorithm = IDS
+        .iter()
+        .find(|(candidate, _)| *candidate == id)
+        .map(|(_, algorithm)| **algorithm)
+        .ok_or(VerifyError::UnsupportedAlgorithm)?;
+
+    let params = next_field()?;
+    let salt = next_field()?;
+    let hash = next_field()?;
+    if fields.next().is_some() {
+        return Err(VerifyError::Malformed);
+    }
+
+    let mut iterations = None;
+    let mut hash_len = None;
+    for param in params.split(',') {
+        let (name, value) = param.split_once('=').ok_or(VerifyError::Malformed)?;
+        let slot = match name {
+            "i" => &mut iterations,
+            "l" => &mut hash_len,
+            _ => {
+                return Err(VerifyError::Malformed);
+            }
+        };
+        if slot.replace(parse_decimal(value)?).is_some() {
+            return Err(VerifyError::Malformed);
+        }
+    }
+    let iterations = iterations.ok_or(VerifyError::Malformed)?;
+
+    let mut salt_buf = [0u8; MAX_SALT_LEN];
+    let salt_len =
+        b64::decode(&b64::PHC, salt, &mut salt_buf).map_err(|_| VerifyError::Malformed)?;
+    if salt_len == 0 {
+        return Err(VerifyError::Malformed);
+    }
+
+    let mut hash_buf = [0u8; MAX_HASH_LEN];
+    let decoded_hash_len =
+        b64::decode(&b64::PHC, hash, &mut hash_buf).map_err(|_| VerifyError::Malformed)?;
+    if hash_len.map_or(false, |hash_len| {
+        usize::try_from(hash_len) != Ok(decoded_hash_len)
+    }) {
+        return Err(VerifyError::Malformed);
+    }
+
+    if iterations < MIN_ITERATIONS || decoded_hash_len < MIN_HASH_LEN {
+        return Err(VerifyError::WeakParameters);
+    }
+    let iterations = NonZeroU32::new(iterations).ok_or(VerifyError::WeakParameters)?;
+
+    verify(
+        algorithm,
+        iterations,
+        &salt_buf[..salt_len],
+        password,
+        &hash_buf[..decoded_hash_len],
+    )
+    .map_err(|error::Unspecified| VerifyError::Mismatch)
+}
+
+/// Formats a PBKDF2 hash in the PHC string format accepted by [`verify_phc`].
+///
+/// `derived` is the output of [`super::derive`] for `algorithm`, `iterations`,
+/// and `salt`. The parameters aren't checked; in particular, `verify_phc`
+/// rejects hashes with fewer than 1000 iterations or shorter than 16 bytes.
+#[cfg(feature = "alloc")]
+pub fn format_phc(
+    algorithm: Algorithm,
+    iterations: NonZeroU32,
+    salt: &[u8],
+    derived: &[u8],
+) -> alloc::string::String {
+    use core::fmt::Write;
+
+    let (id, _) = IDS
+        .iter()
+        .find(|(_, candidate)| **candidate == algorithm)
+        .unwrap();
+    let mut s = alloc::string::String::new();
+    // Writing to a `String` never fails.
+    let _ = write!(s, "${}$i={}$", id, iterations);
+    let _ = b64::encode(&b64::PHC, salt, &mut s);
+    s.push('$');
+    let _ = b64::encode(&b64::PHC, derived, &mut s);
+    s
+}
+
+// PHC decimal values have no sign or leading zeros.
+fn parse_decimal(value: &str) -> Result<u32, VerifyError> {
+    if value.is_empty()
+        || (value.starts_with('0') && value != "0")
+        || !value.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(VerifyError::Malformed);
+    }
+    value.parse().map_err(|_| VerifyError::Malformed)
+}
@@ -16,7 +16,16 @@
 //!
 //! [RFC 5958]: https://tools.ietf.org/html/rfc5958
 
-use crate::{ec, error, io::der};
+use crate::{
+    ec, error,
+    io::{
+        der, der_writer,
+        writer::{Accumulator, SliceWriter},
+    },
+};
+
+#[cfg(feature = "alloc")]
+use crate::{aead, rand};
 
 pub(crate) struct PublicKeyOptions {
     /// Should the wrong public key ASN.1 tagging used by early implementations
@@ -57,7 +66,7 @@ impl Template {
         untrusted::Input::from(self.alg_id_value_())
     }
 
-    fn alg_id_value_(&self) -> &[u8] {
+    pub(crate) fn alg_id_value_(&self) -> &[u8] {
         &self.bytes[self.alg_id_range.start..self.alg_id_range.end]
     }
 
@@ -174,6 +183,86 @@ fn unwrap_key__<'a>(
     Ok((private_key, public_key))
 }
 
+/// The kind of key in a PKCS#8 document, as identified by its
+/// `privateKeyAlgorithm`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyKind {
+    /// An RSA key (`rsaEncryption`), for use with `signature::RsaKeyPair`.
+    Rsa,
+
+    /// An ECDSA key on the P-256 curve (`id-ecPublicKey` with `secp256r1`),
+    /// for use with `signature::EcdsaKeyPair`.
+    EcdsaP256,
+
+    /// An ECDSA key on the P-384 curve (`id-ecPublicKey` with `secp384r1`),
+    /// for use with `signature::EcdsaKeyPair`.
+    EcdsaP384,
+
+    /// An Ed25519 key (`id-Ed25519`), for use with
+    /// `signature::Ed25519KeyPair`.
+    Ed25519,
+}
+
+/// Determines what kind of key an unencrypted PKCS#8 document contains,
+/// without parsing the key itself.
+///
+/// This is useful for routing a PKCS#8 document of unknown type to the
+/// appropriate `from_pkcs8()` constructor. Only the version and the
+/// `privateKeyAlgorithm` of the document are examined, so success doesn't
+/// imply that the key is valid.
+///
+/// Documents with an algorithm other than those in `KeyKind` are rejected
+/// with `KeyRejected` "WrongAlgorithm".
+pub fn key_kind(pkcs8: &[u8]) -> Result<KeyKind, error::KeyRejected> {
+    use crate::ec::{curve25519::ed25519, suite_b::ecdsa};
+
+    const RSA_ENCRYPTION: &[u8] = include_bytes!("data/alg-rsa-encryption.der");
+    let known: [(&[u8], KeyKind); 4] = [
+        (RSA_ENCRYPTION, KeyKind::Rsa),
+        (
+            ecdsa::signing::EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE.alg_id_value_(),
+            KeyKind::EcdsaP256,
+        ),
+        (
+            ecdsa::signing::EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE.alg_id_value_(),
+            KeyKind::EcdsaP384,
+        ),
+        (
+            ed25519::signing::PKCS8_TEMPLATE.alg_id_value_(),
+            KeyKind::Ed25519,
+        ),
+    ];
+
+    let alg_id = untrusted::Input::from(pkcs8).read_all(
+        error::KeyRejected::invalid_encoding(),
+        |input| {
+            der::nested(
+                input,
+                der::Tag::Sequence,
+                error::KeyRejected::invalid_encoding(),
+                |input| {
+                    let version = der::small_nonnegative_integer(input)
+                        .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+                    if version > 1 {
+                        return Err(error::KeyRejected::version_not_supported());
+                    }
+                    let alg_id = der::expect_tag_and_get_value(input, der::Tag::Sequence)
+                        .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+                    // The rest of the document is parsed by `unwrap_key()`.
+                    let _ = input.read_bytes_to_end();
+                    Ok(alg_id)
+                },
+            )
+        },
+    )?;
+
+    known
+        .iter()
+        .find(|(known_alg_id, _)| *known_alg_id == alg_id.as_slice_less_safe())
+        .map(|&(_, kind)| kind)
+        .ok_or_else(error::KeyRejected::wrong_algorithm)
+}
+
 /// A generated PKCS#8 document.
 pub struct Document {
     bytes: [u8; ec::PKCS8_DOCUMENT_MAX_LEN],
@@ -187,6 +276,111 @@ impl AsRef<[u8]> for Document {
     }
 }
 
+/// The current version of the [`Document::seal`] envelope format.
+pub const SEALED_VERSION: u8 = 1;
+
+// The header is `version || alg_id`.
+#[cfg(feature = "alloc")]
+const SEALED_HEADER_LEN: usize = 2;
+
+impl Document {
+    /// Encrypts the document under the key-encryption key `kek`, for storage
+    /// at rest.
+    ///
+    /// The result is `version || alg_id || nonce || ciphertext || tag`, where
+    /// `version` is [`SEALED_VERSION`], `alg_id` identifies `kek`'s
+    /// algorithm, and the nonce is generated with `rng`. The header and `aad`
+    /// are authenticated; use `aad` to bind the document to its context,
+    /// e.g. the key's purpose or the tenant that owns it.
+    ///
+    /// The nonce is random, so no more than 2<sup>32</sup> documents should be
+    /// sealed with the same `kek`.
+    #[cfg(feature = "alloc")]
+    pub fn seal(
+        &self,
+        kek: &aead::LessSafeKey,
+        aad: &[u8],
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<alloc::vec::Vec<u8>, error::Unspecified> {
+        let header = [SEALED_VERSION, sealed_alg_id(kek.algorithm())];
+        let nonce: [u8; aead::NONCE_LEN] = rand::generate(rng)?.expose();
+
+        let mut out = alloc::vec::Vec::with_capacity(
+            SEALED_HEADER_LEN + aead::NONCE_LEN + self.len + aead::MAX_TAG_LEN,
+        );
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(self.as_ref());
+        let tag = kek.seal_in_place_separate_tag(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(sealed_aad(&header, aad)),
+            &mut out[(SEALED_HEADER_LEN + aead::NONCE_LEN)..],
+        )?;
+        out.extend_from_slice(tag.as_ref());
+        Ok(out)
+    }
+
+    /// Decrypts a document sealed by [`Self::seal`] with the same `kek` and
+    /// `aad`.
+    ///
+    /// Fails if `sealed` was sealed with a different key, algorithm, or
+    /// `aad`, if it has been modified or truncated, or if it uses a version
+    /// of the format other than [`SEALED_VERSION`].
+    #[cfg(feature = "alloc")]
+    pub fn open_sealed(
+        kek: &aead::LessSafeKey,
+        aad: &[u8],
+        sealed: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        if sealed.len() < SEALED_HEADER_LEN + aead::NONCE_LEN {
+            return Err(error::Unspecified);
+        }
+        let (header, rest) = sealed.split_at(SEALED_HEADER_LEN);
+        if header != [SEALED_VERSION, sealed_alg_id(kek.algorithm())] {
+            return Err(error::Unspecified);
+        }
+        let (nonce, ciphertext_and_tag) = rest.split_at(aead::NONCE_LEN);
+        let nonce = aead::Nonce::try_assume_unique_for_key(nonce)?;
+
+        let mut in_out = alloc::vec::Vec::from(ciphertext_and_tag);
+        let plaintext =
+            kek.open_in_place(nonce, aead::Aad::from(sealed_aad(header, aad)), &mut in_out)?;
+
+        let mut result = Self {
+            bytes: [0; ec::PKCS8_DOCUMENT_MAX_LEN],
+            len: plaintext.len(),
+        };
+        result
+            .bytes
+            .get_mut(..plaintext.len())
+            .ok_or(error::Unspecified)?
+            .copy_from_slice(plaintext);
+        Ok(result)
+    }
+}
+
+// These values are stored in sealed documents so they must never change.
+#[cfg(feature = "alloc")]
+fn sealed_alg_id(algorithm: &aead::Algorithm) -> u8 {
+    if algorithm == &aead::AES_128_GCM {
+        1
+    } else if algorithm == &aead::AES_256_GCM {
+        2
+    } else if algorithm == &aead::CHACHA20_POLY1305 {
+        3
+    } else {
+        unreachable!()
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn sealed_aad(header: &[u8], aad: &[u8]) -> alloc::vec::Vec<u8> {
+    let mut result = alloc::vec::Vec::with_capacity(header.len() + aad.len());
+    result.extend_from_slice(header);
+    result.extend_from_slice(aad);
+    result
+}
+
 pub(crate) fn wrap_key(template: &Template, private_key: &[u8], public_key: &[u8]) -> Document {
     let mut result = Document {
         bytes: [0; ec::PKCS8_DOCUMENT_MAX_LEN],
@@ -201,6 +395,28 @@ pub(crate) fn wrap_key(template: &Template, private_key: &[u8], public_key: &[u8
     result
 }
 
+/// Formats a PKCS#8 v1 document for `template`'s algorithm whose
+/// `privateKey` is written by `write_private_key`.
+pub(crate) fn wrap_key_with(
+    template: &Template,
+    write_private_key: &dyn Fn(&mut dyn Accumulator),
+) -> Document {
+    let mut result = Document {
+        bytes: [0; ec::PKCS8_DOCUMENT_MAX_LEN],
+        len: 0,
+    };
+    let mut output = SliceWriter::new(&mut result.bytes);
+    der_writer::write_tlv(&mut output, der::Tag::Sequence, |output| {
+        der_writer::write_tlv(output, der::Tag::Integer, |output| output.write_byte(0));
+        der_writer::write_tlv(output, der::Tag::Sequence, |output| {
+            output.write_bytes(template.alg_id_value_())
+        });
+        der_writer::write_tlv(output, der::Tag::OctetString, write_private_key);
+    });
+    result.len = output.len();
+    result
+}
+
 /// Formats a private key "prefix||private_key||middle||public_key" where
 /// `template` is "prefix||middle" split at position `private_key_index`.
 fn wrap_key_(template: &Template, private_key: &[u8], public_key: &[u8], bytes: &mut [u8]) {
@@ -0,0 +1,396 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Allow-lists of signature verification and AEAD algorithms.
+//!
+//! A [`Policy`] is built from the [`inventory`] names of the algorithms it
+//! allows. Checking an algorithm against a policy compares the algorithm's
+//! address with those of *ring*'s algorithms, so it is cheap enough to do on
+//! every use.
+//!
+//! A policy can also be installed as the process-wide default with
+//! [`Policy::install_default()`]. Once installed, it is enforced by
+//! `signature::UnparsedPublicKey::verify()`,
+//! `signature::UnparsedPublicKey::verify_cached()`, `easy::aead_seal()`,
+//! and `easy::aead_open()`, which then fail for algorithms it doesn't allow.
+//! Other APIs don't consult the default policy; use [`Policy::check_aead()`]
+//! and [`Policy::wrap_verification()`] with them. Algorithms implemented
+//! outside of *ring*, e.g. with `signature::ExternalAlgorithm`, are never
+//! allowed.
+//!
+//! ```
+//! use ring::{aead, policy::{Policy, PolicyError}, signature};
+//!
+//! let policy = Policy::from_allow_list(["ED25519", "RSA_PKCS1_3072_8192_SHA384", "AES_256_GCM"])?;
+//!
+//! assert!(policy.wrap_verification(&signature::ED25519).is_ok());
+//! assert_eq!(
+//!     policy.check_aead(&aead::AES_128_GCM),
+//!     Err(PolicyError::Disallowed)
+//! );
+//! # Ok::<(), PolicyError>(())
+//! ```
+//!
+//! [`inventory`]: crate::inventory
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::{aead, error, inventory, signature};
+use core::fmt::{self, Write};
+
+/// An allow-list of algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Policy {
+    verification: u32,
+    aead: u32,
+}
+
+impl Policy {
+    /// Constructs a policy that allows exactly the algorithms named in
+    /// `names`.
+    ///
+    /// The names are those of [`crate::inventory::inventory()`]. Names of
+    /// algorithms other than signature verification and AEAD algorithms are
+    /// accepted but have no effect.
+    ///
+    /// Fails with [`PolicyError::UnknownAlgorithm`] if a name isn't in the
+    /// inventory of this build of *ring*.
+    pub fn from_allow_list<'a>(
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, PolicyError> {
+        let mut policy = Self {
+            verification: 0,
+            aead: 0,
+        };
+        for name in names {
+            if !inventory::inventory().any(|info| info.name() == name) {
+                return Err(PolicyError::UnknownAlgorithm);
+            }
+            policy.verification |= mask_of_name(VERIFICATION_ALGORITHMS, name);
+            policy.aead |= mask_of_name(AEAD_ALGORITHMS, name);
+        }
+        Ok(policy)
+    }
+
+    /// Returns `algorithm` if the policy allows it.
+    pub fn wrap_verification(
+        &self,
+        algorithm: &'static dyn signature::VerificationAlgorithm,
+    ) -> Result<&'static dyn signature::VerificationAlgorithm, PolicyError> {
+        let mask = mask_of(VERIFICATION_ALGORITHMS, |&(_, allowed)| {
+            same_verification_algorithm(allowed, algorithm)
+        });
+        check(self.verification, mask)?;
+        Ok(algorithm)
+    }
+
+    /// Fails unless the policy allows `algorithm`.
+    pub fn check_aead(&self, algorithm: &'static aead::Algorithm) -> Result<(), PolicyError> {
+        let mask = mask_of(AEAD_ALGORITHMS, |&(_, allowed)| {
+            core::ptr::eq(allowed, algorithm)
+        });
+        check(self.aead, mask)
+    }
+
+    /// Installs `self` as the process-wide default policy.
+    ///
+    /// Fails with [`PolicyError::DefaultAlreadyInstalled`] if a default
+    /// policy was already installed; the default can't be changed once set.
+    pub fn install_default(self) -> Result<(), PolicyError> {
+        let mut installed = false;
+        let _: &Self = DEFAULT.call_once(|| {
+            installed = true;
+            self
+        });
+        if !installed {
+            return Err(PolicyError::DefaultAlreadyInstalled);
+        }
+        Ok(())
+    }
+
+    /// The process-wide default policy, if one was installed.
+    #[inline]
+    pub fn default_policy() -> Option<&'static Self> {
+        DEFAULT.get()
+    }
+}
+
+static DEFAULT: spin::Once<Policy> = spin::Once::new();
+
+/// Fails if a default policy is installed and it doesn't allow `algorithm`.
+pub(crate) fn check_default_verification(
+    algorithm: &'static dyn signature::VerificationAlgorithm,
+) -> Result<(), error::Unspecified> {
+    if let Some(policy) = Policy::default_policy() {
+        let _ = policy.wrap_verification(algorithm)?;
+    }
+    Ok(())
+}
+
+/// Fails if a default policy is installed and it doesn't allow `algorithm`.
+#[cfg(feature = "alloc")]
+pub(crate) fn check_default_aead(
+    algorithm: &'static aead::Algorithm,
+) -> Result<(), error::Unspecified> {
+    if let Some(policy) = Policy::default_policy() {
+        policy.check_aead(algorithm)?;
+    }
+    Ok(())
+}
+
+/// An error constructing, installing, or checking against a [`Policy`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PolicyError {
+    /// A name in the allow-list isn't the name of an algorithm in this build.
+    UnknownAlgorithm,
+
+    /// The policy doesn't allow the algorithm.
+    Disallowed,
+
+    /// A default policy was already installed.
+    DefaultAlreadyInstalled,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnknownAlgorithm => "unknown algorithm name",
+            Self::Disallowed => "algorithm is not allowed by the policy",
+            Self::DefaultAlreadyInstalled => "a default policy is already installed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyError {}
+
+impl From<PolicyError> for error::Unspecified {
+    fn from(_: PolicyError) -> Self {
+        Self
+    }
+}
+
+type VerificationEntry = (&'static str, &'static dyn signature::VerificationAlgorithm);
+
+// The names must be the `inventory` names of the algorithms. Each table must
+// have at most 32 entries so that a `u32` can be used as a set of them.
+static VERIFICATION_ALGORITHMS: &[VerificationEntry] = &[
+    ("ED25519", &signature::ED25519),
+    ("ECDSA_P256_SHA256_ASN1", &signature::ECDSA_P256_SHA256_ASN1),
+    (
+        "ECDSA_P256_SHA256_FIXED",
+        &signature::ECDSA_P256_SHA256_FIXED,
+    ),
+    ("ECDSA_P256_SHA384_ASN1", &signature::ECDSA_P256_SHA384_ASN1),
+    ("ECDSA_P384_SHA256_ASN1", &signature::ECDSA_P384_SHA256_ASN1),
+    ("ECDSA_P384_SHA384_ASN1", &signature::ECDSA_P384_SHA384_ASN1),
+    (
+        "ECDSA_P384_SHA384_FIXED",
+        &signature::ECDSA_P384_SHA384_FIXED,
+    ),
+    (
+        "ECDSA_BRAINPOOLP384R1_SHA384_ASN1",
+        &signature::ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
+    ),
+    (
+        "ECDSA_P256_SHA256_ASN1_LOW_S",
+        &signature::ECDSA_P256_SHA256_ASN1_LOW_S,
+    ),
+    (
+        "ECDSA_P256_SHA256_FIXED_LOW_S",
+        &signature::ECDSA_P256_SHA256_FIXED_LOW_S,
+    ),
+    (
+        "ECDSA_P384_SHA384_ASN1_LOW_S",
+        &signature::ECDSA_P384_SHA384_ASN1_LOW_S,
+    ),
+    (
+        "ECDSA_P384_SHA384_FIXED_LOW_S",
+        &signature::ECDSA_P384_SHA384_FIXED_LOW_S,
+    ),
+    ("SM2_SM3_ASN1", &signature::SM2_SM3_ASN1),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY",
+        &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY",
+        &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY",
+        &signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY",
+        &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_8192_SHA256",
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_8192_SHA384",
+        &signature::RSA_PKCS1_2048_8192_SHA384,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_8192_SHA512",
+        &signature::RSA_PKCS1_2048_8192_SHA512,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_3072_8192_SHA384",
+        &signature::RSA_PKCS1_3072_8192_SHA384,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_8192_SHA256",
+        &signature::RSA_PSS_2048_8192_SHA256,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_8192_SHA384",
+        &signature::RSA_PSS_2048_8192_SHA384,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_8192_SHA512",
+        &signature::RSA_PSS_2048_8192_SHA512,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_16384_SHA256",
+        &signature::RSA_PKCS1_2048_16384_SHA256,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_16384_SHA384",
+        &signature::RSA_PKCS1_2048_16384_SHA384,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PKCS1_2048_16384_SHA512",
+        &signature::RSA_PKCS1_2048_16384_SHA512,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_16384_SHA256",
+        &signature::RSA_PSS_2048_16384_SHA256,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_16384_SHA384",
+        &signature::RSA_PSS_2048_16384_SHA384,
+    ),
+    #[cfg(feature = "alloc")]
+    (
+        "RSA_PSS_2048_16384_SHA512",
+        &signature::RSA_PSS_2048_16384_SHA512,
+    ),
+];
+
+static AEAD_ALGORITHMS: &[(&str, &aead::Algorithm)] = &[
+    ("AES_128_GCM", &aead::AES_128_GCM),
+    ("AES_256_GCM", &aead::AES_256_GCM),
+    ("CHACHA20_POLY1305", &aead::CHACHA20_POLY1305),
+];
+
+const _: () = assert!(VERIFICATION_ALGORITHMS.len() <= 32 && AEAD_ALGORITHMS.len() <= 32);
+
+fn check(allowed: u32, mask: u32) -> Result<(), PolicyError> {
+    if allowed & mask == 0 {
+        return Err(PolicyError::Disallowed);
+    }
+    Ok(())
+}
+
+fn mask_of_name<A>(table: &[(&str, A)], name: &str) -> u32 {
+    mask_of(table, |&(entry_name, _)| entry_name == name)
+}
+
+fn mask_of<T>(table: &[T], f: impl Fn(&T) -> bool) -> u32 {
+    table.iter().position(f).map_or(0, |i| 1 << i)
+}
+
+fn same_verification_algorithm(
+    a: &'static dyn signature::VerificationAlgorithm,
+    b: &'static dyn signature::VerificationAlgorithm,
+) -> bool {
+    // The vtable parts of the pointers may differ for the same algorithm, so
+    // only the addresses are compared. Distinct zero-sized statics, like
+    // `ED25519` and a zero-sized `ExternalAlgorithm`, may have the same
+    // address, so those are also distinguished by their `Debug` output.
+    let address = |alg: &dyn signature::VerificationAlgorithm| {
+        (alg as *const dyn signature::VerificationAlgorithm).cast::<u8>()
+    };
+    address(a) == address(b) && (core::mem::size_of_val(a) != 0 || debug_eq(a, b))
+}
+
+fn debug_eq(a: &dyn fmt::Debug, b: &dyn fmt::Debug) -> bool {
+    // Large enough for the `Debug` output of any of *ring*'s algorithms.
+    let mut buffer = [0u8; 64];
+    let mut a_writer = SliceWriter {
+        buffer: &mut buffer,
+        len: 0,
+    };
+    if write!(a_writer, "{:?}", a).is_err() {
+        return false;
+    }
+    let a_len = a_writer.len;
+    let mut expected = Expect {
+        remaining: &buffer[..a_len],
+    };
+    write!(expected, "{:?}", b).is_ok() && expected.remaining.is_empty()
+}
+
+struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let dest = self
+            .buffer
+            .get_mut(self.len..)
+            .and_then(|rest| rest.get_mut(..s.len()))
+            .ok_or(fmt::Error)?;
+        dest.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+// Succeeds only while the output is a prefix of `remaining`.
+struct Expect<'a> {
+    remaining: &'a [u8],
+}
+
+impl Write for Expect<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.remaining = self
+            .remaining
+            .strip_prefix(s.as_bytes())
+            .ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
@@ -36,10 +36,18 @@ mod test;
 
 mod unwrap_const;
 
+mod zeroize;
+
 pub use self::{
     array_flat_map::ArrayFlatMap, array_flatten::ArrayFlatten, array_split_map::ArraySplitMap,
-    unwrap_const::unwrap_const,
+    unwrap_const::unwrap_const, zeroize::zeroize,
 };
 
+#[cfg(feature = "alloc")]
+pub use zeroize::Zeroable;
+
+#[cfg(test)]
+pub use zeroize::zeroize_hook;
+
 #[cfg(feature = "alloc")]
 pub use leading_zeros_skipped::LeadingZerosStripped;
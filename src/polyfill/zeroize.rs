@@ -0,0 +1,98 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+/// Integer types whose value zero is all zero bits.
+pub trait Zeroable: Copy {
+    const ZERO: Self;
+}
+
+impl Zeroable for u8 {
+    const ZERO: Self = 0;
+}
+
+impl Zeroable for u32 {
+    const ZERO: Self = 0;
+}
+
+impl Zeroable for u64 {
+    const ZERO: Self = 0;
+}
+
+/// Overwrites `values` with zeros in a way that the compiler won't optimize
+/// away even if `values` is about to be freed.
+///
+/// Use this instead of `fill(0)` to erase secrets, since a store to memory
+/// that is never read again may be removed.
+pub fn zeroize<T: Zeroable>(values: &mut [T]) {
+    for value in values.iter_mut() {
+        unsafe { core::ptr::write_volatile(value, T::ZERO) };
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+
+    #[cfg(test)]
+    zeroize_hook::zeroized(values);
+}
+
+/// Records which buffers this thread has zeroized, so that tests can verify
+/// that secret values are zeroized when they are dropped.
+#[cfg(test)]
+pub mod zeroize_hook {
+    extern crate std;
+
+    use core::cell::RefCell;
+    use std::{thread_local, vec::Vec};
+
+    thread_local! {
+        static ZEROIZED: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub(super) fn zeroized<T>(values: &[T]) {
+        ZEROIZED.with(|z| z.borrow_mut().push(address_and_len(values)));
+    }
+
+    /// Returns the address and length in bytes of `values`, as recorded.
+    pub fn address_and_len<T>(values: &[T]) -> (usize, usize) {
+        (values.as_ptr() as usize, core::mem::size_of_val(values))
+    }
+
+    /// Returns whether the buffer with the given address and length, from
+    /// `address_and_len`, was zeroized since `clear` was last called. The
+    /// buffer may have been freed since, so only its address is compared.
+    pub fn was_zeroized(values: (usize, usize)) -> bool {
+        ZEROIZED.with(|z| z.borrow().contains(&values))
+    }
+
+    pub fn clear() {
+        ZEROIZED.with(|z| z.borrow_mut().clear());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize() {
+        let mut bytes = [1u8, 2, 3];
+        zeroize(&mut bytes[1..]);
+        assert_eq!(bytes, [1, 0, 0]);
+        assert!(zeroize_hook::was_zeroized(zeroize_hook::address_and_len(
+            &bytes[1..]
+        )));
+
+        let mut words = [u64::MAX; 2];
+        zeroize(&mut words);
+        assert_eq!(words, [0; 2]);
+    }
+}
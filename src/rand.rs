@@ -92,6 +92,14 @@ pub(crate) mod sealed {
 pub trait RandomlyConstructable: self::sealed::RandomlyConstructable {}
 impl<T> RandomlyConstructable for T where T: self::sealed::RandomlyConstructable {}
 
+/// A marker for random number generators that are meant to be used for a
+/// single operation, e.g. a test generator seeded with one known value.
+///
+/// Functions that take such a generator by value, such as
+/// [`crate::agreement::EphemeralPrivateKey::generate_once()`], consume it, so
+/// the type system prevents it from being used again.
+pub trait ConsumedAfterUse {}
+
 /// A secure random number generator where the random values come directly
 /// from the operating system.
 ///
@@ -28,6 +28,14 @@ use crate::{
 
 pub(crate) mod padding;
 
+/// The largest RSA modulus, in bits, supported for signature verification.
+///
+/// This is 8192 unless *ring* was built with `$RING_MAX_RSA_BITS` set to a
+/// larger multiple of 64, up to 16384. Only the `*_16384` verification
+/// algorithms accept keys larger than 8192 bits; the ones named for 8192-bit
+/// keys never do. Signing is always limited to 4096-bit keys.
+pub const MAX_RSA_BITS: usize = crate::arithmetic::MODULUS_MAX_BITS;
+
 // Maximum RSA modulus size supported for signature verification (in bytes).
 const PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN: usize = bigint::MODULUS_MAX_LIMBS * limb::LIMB_BYTES;
 
@@ -39,6 +47,7 @@ const PRIVATE_KEY_PUBLIC_MODULUS_MAX_BITS: bits::BitLength = bits::BitLength::fr
 pub struct RsaParameters {
     padding_alg: &'static dyn padding::Verification,
     min_bits: bits::BitLength,
+    max_bits: bits::BitLength,
 }
 
 fn parse_public_key(
@@ -62,6 +71,7 @@ impl bigint::PublicModulus for N {}
 
 mod keypair;
 mod keypair_components;
+mod parsed_public_key;
 mod public_exponent;
 mod public_key;
 mod public_key_components;
@@ -72,6 +82,37 @@ pub(crate) mod verification;
 use self::{public_exponent::PublicExponent, public_modulus::PublicModulus};
 
 pub use self::{
-    keypair::KeyPair, keypair_components::KeyPairComponents, public_key::PublicKey,
+    keypair::{KeyPair, KeySize, SignOperation},
+    keypair_components::KeyPairComponents,
+    parsed_public_key::ParsedPublicKey,
+    public_key::PublicKey,
     public_key_components::PublicKeyComponents,
 };
+
+// Signing is limited by `PRIVATE_KEY_PUBLIC_MODULUS_MAX_BITS`; verification by
+// each algorithm's maximum, which is at most `MAX_RSA_BITS`.
+inventory! { Signature, Alloc;
+    RSA_PKCS1_SHA256 => 2048..=4096,
+    RSA_PKCS1_SHA384 => 2048..=4096,
+    RSA_PKCS1_SHA512 => 2048..=4096,
+    RSA_PSS_SHA256 => 2048..=4096,
+    RSA_PSS_SHA384 => 2048..=4096,
+    RSA_PSS_SHA512 => 2048..=4096,
+    RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY => 1024..=8192,
+    RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY => 1024..=8192,
+    RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY => 1024..=8192,
+    RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY => 2048..=8192,
+    RSA_PKCS1_2048_8192_SHA256 => 2048..=8192,
+    RSA_PKCS1_2048_8192_SHA384 => 2048..=8192,
+    RSA_PKCS1_2048_8192_SHA512 => 2048..=8192,
+    RSA_PKCS1_3072_8192_SHA384 => 3072..=8192,
+    RSA_PSS_2048_8192_SHA256 => 2048..=8192,
+    RSA_PSS_2048_8192_SHA384 => 2048..=8192,
+    RSA_PSS_2048_8192_SHA512 => 2048..=8192,
+    RSA_PKCS1_2048_16384_SHA256 => 2048..=MAX_RSA_BITS,
+    RSA_PKCS1_2048_16384_SHA384 => 2048..=MAX_RSA_BITS,
+    RSA_PKCS1_2048_16384_SHA512 => 2048..=MAX_RSA_BITS,
+    RSA_PSS_2048_16384_SHA256 => 2048..=MAX_RSA_BITS,
+    RSA_PSS_2048_16384_SHA384 => 2048..=MAX_RSA_BITS,
+    RSA_PSS_2048_16384_SHA512 => 2048..=MAX_RSA_BITS,
+}
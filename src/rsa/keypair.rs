@@ -25,9 +25,20 @@ use crate::{
     bits::BitLength,
     cpu, digest,
     error::{self, KeyRejected},
+    fingerprint,
     io::der,
     pkcs8, rand, signature,
 };
+use alloc::collections::TryReserveError;
+use core::task::Poll;
+
+use self::sign_operation::PrivateExponentiation;
+pub use self::{generate::KeySize, sign_operation::SignOperation};
+
+mod generate;
+mod sign_operation;
+
+const RSA_ENCRYPTION: &[u8] = include_bytes!("../data/alg-rsa-encryption.der");
 
 /// An RSA key pair, used for signing.
 pub struct KeyPair {
@@ -35,24 +46,27 @@ pub struct KeyPair {
     q: PrivatePrime<Q>,
     qInv: bigint::Elem<P, R>,
 
-    // XXX: qq's `oneRR` isn't used and thus this is about twice as large as
-    // it needs to be, according to how it is used. Further, it appears to be
-    // completely unnecessary since `elem_reduced` seems to be able to reduce
-    // an `Elem<N>` directly to an `Elem<Q>`. TODO: Verify that is true and
-    // eliminate this.
-    qq: bigint::OwnedModulusWithOne<QQ>,
-
-    // TODO: Eliminate `q_mod_n` entirely since it is a bad space:time trade-off.
-    // Also, this is the only non-temporary `Elem` so if we eliminate this, we
-    // can make all `Elem`s temporary (borrowed) values.
-    q_mod_n: bigint::Elem<N, R>,
-
+    // The public modulus is shared with the signing operation, which borrows
+    // it from here instead of keeping its own copy. Nothing else the same
+    // size as `n` is kept; e.g. `q mod n` is recalculated for each signature
+    // since doing so is cheap compared to the exponentiations.
     public: PublicKey,
 }
 
 derive_debug_via_field!(KeyPair, stringify!(RsaKeyPair), public);
 
 impl KeyPair {
+    /// The maximum length of the DER-encoded `RSAPublicKey` of a key pair.
+    ///
+    /// This is an upper bound, reached only by a 4096-bit modulus with the
+    /// largest supported public exponent; a 4096-bit key with *e* = 65537
+    /// has a 526-byte public key.
+    // Keep in sync with `PRIVATE_KEY_PUBLIC_MODULUS_MAX_BITS` and
+    // `PublicExponent::MAX`.
+    pub const MAX_PUBLIC_KEY_LEN: usize = 1/*tag:SEQUENCE*/ + 3/*len*/ +
+        (1/*tag:INTEGER*/ + 3/*len*/ + 1/*zero*/ + (4096 / 8)) +
+        (1/*tag:INTEGER*/ + 1/*len*/ + 5/*e*/);
+
     /// Parses an unencrypted PKCS#8-encoded RSA private key.
     ///
     /// This will generate a 2048-bit RSA private key of the correct form using
@@ -107,7 +121,6 @@ impl KeyPair {
     /// [RFC 5958]:
     ///     https://tools.ietf.org/html/rfc5958
     pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, KeyRejected> {
-        const RSA_ENCRYPTION: &[u8] = include_bytes!("../data/alg-rsa-encryption.der");
         let (der, _) = pkcs8::unwrap_key_(
             untrusted::Input::from(RSA_ENCRYPTION),
             pkcs8::Version::V1Only,
@@ -116,6 +129,18 @@ impl KeyPair {
         Self::from_der(der.as_slice_less_safe())
     }
 
+    /// Like [`Self::from_pkcs8()`], except that if memory for the key can't
+    /// be allocated, `KeyRejected::out_of_memory()` is returned instead of the
+    /// allocation error handler being invoked (which, by default, aborts the
+    /// process).
+    ///
+    /// The `Display` implementation of the error is `"OutOfMemory"` in that
+    /// case.
+    pub fn try_from_pkcs8(pkcs8: &[u8]) -> Result<Self, KeyRejected> {
+        // Every allocation done while constructing a key pair is fallible.
+        Self::from_pkcs8(pkcs8)
+    }
+
     /// Parses an RSA private key that is not inside a PKCS#8 wrapper.
     ///
     /// The private key must be encoded as a binary DER-encoded ASN.1
@@ -268,10 +293,8 @@ impl KeyPair {
         let dQ = untrusted::Input::from(dQ);
         let qInv = untrusted::Input::from(qInv);
 
-        let (p, _p_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(p)
-            .map_err(|error::Unspecified| KeyRejected::invalid_encoding())?;
-        let (q, _q_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(q)
-            .map_err(|error::Unspecified| KeyRejected::invalid_encoding())?;
+        let (p, _p_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(p)?;
+        let (q, _q_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(q)?;
 
         // Our implementation of CRT-based modular exponentiation used requires
         // that `p > q` so swap them if `p < q`. If swapped, `qInv` is
@@ -336,8 +359,6 @@ impl KeyPair {
         let p = PrivatePrime::new(p, dP, n_bits, cpu_features)?;
         let q = PrivatePrime::new(q, dQ, n_bits, cpu_features)?;
 
-        let q_mod_n_decoded = q.modulus.to_elem(n);
-
         // TODO: Step 5.i
         //
         // 3.b is unneeded since `n_bits` is derived here from `n`.
@@ -349,8 +370,9 @@ impl KeyPair {
         // 0 < q < p < n. We check that q and p are close to sqrt(n) and then
         // assume that these preconditions are enough to let us assume that
         // checking p * q == 0 (mod n) is equivalent to checking p * q == n.
-        let q_mod_n = bigint::elem_mul(n_one.as_ref(), q_mod_n_decoded.clone(), n);
-        let p_mod_n = p.modulus.to_elem(n);
+        let q_mod_n = q.modulus.to_elem(n).map_err(out_of_memory)?;
+        let q_mod_n = bigint::elem_mul(n_one.as_ref(), q_mod_n, n);
+        let p_mod_n = p.modulus.to_elem(n).map_err(out_of_memory)?;
         let pq_mod_n = bigint::elem_mul(&q_mod_n, p_mod_n, n);
         if !pq_mod_n.is_zero() {
             return Err(KeyRejected::inconsistent_components());
@@ -363,8 +385,7 @@ impl KeyPair {
         // First, validate `2**half_n_bits < d`. Since 2**half_n_bits has a bit
         // length of half_n_bits + 1, this check gives us 2**half_n_bits <= d,
         // and knowing d is odd makes the inequality strict.
-        let (d, d_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(d)
-            .map_err(|_| error::KeyRejected::invalid_encoding())?;
+        let (d, d_bits) = bigint::Nonnegative::from_be_bytes_with_bit_length(d)?;
         if !(n_bits.half_rounded_up() < d_bits) {
             return Err(KeyRejected::inconsistent_components());
         }
@@ -382,18 +403,22 @@ impl KeyPair {
 
         // 6.4.1.4.3 - Step 7.
 
-        let q_mod_p = q.modulus.to_elem(pm);
+        let q_mod_p = q.modulus.to_elem(pm).map_err(out_of_memory)?;
 
         // Step 7.c.
         let qInv = if let Some(qInv) = qInv {
-            bigint::Elem::from_be_bytes_padded(qInv, pm)
-                .map_err(|error::Unspecified| KeyRejected::invalid_component())?
+            bigint::Elem::try_from_be_bytes_padded(qInv, pm)?
         } else {
             // We swapped `p` and `q` above, so we need to calculate `qInv`.
             // Step 7.f below will verify `qInv` is correct.
-            let q_mod_p = bigint::elem_mul(p.modulus.oneRR().as_ref(), q_mod_p.clone(), pm);
+            let q_mod_p = bigint::elem_mul(
+                p.modulus.oneRR().as_ref(),
+                q_mod_p.try_clone().map_err(out_of_memory)?,
+                pm,
+            );
+            // Allocation failure is the only way this can fail.
             bigint::elem_inverse_consttime(q_mod_p, &p.modulus)
-                .map_err(|error::Unspecified| KeyRejected::unexpected_error())?
+                .map_err(|error::Unspecified| KeyRejected::out_of_memory())?
         };
 
         // Steps 7.d and 7.e are omitted per the documentation above, and
@@ -405,21 +430,37 @@ impl KeyPair {
         bigint::verify_inverses_consttime(&qInv, q_mod_p, pm)
             .map_err(|error::Unspecified| KeyRejected::inconsistent_components())?;
 
-        let qq = bigint::OwnedModulusWithOne::from_elem(
-            bigint::elem_mul(&q_mod_n, q_mod_n_decoded, n),
-            cpu_features,
-        )?;
-
         // This should never fail since `n` and `e` were validated above.
 
-        Ok(Self {
+        let r = Self {
             p,
             q,
             qInv,
-            q_mod_n,
-            qq,
             public: public_key,
-        })
+        };
+        #[cfg(feature = "locked-memory")]
+        let r = r.into_locked();
+        Ok(r)
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn into_locked(self) -> Self {
+        Self {
+            p: self.p.into_locked(),
+            q: self.q.into_locked(),
+            qInv: self.qInv.into_locked(),
+            public: self.public,
+        }
+    }
+
+    /// Whether the private key is held in locked memory, which is never
+    /// written to swap.
+    ///
+    /// This is `false` if any page of the private key couldn't be locked,
+    /// e.g. because `RLIMIT_MEMLOCK` was exhausted; the key is still usable.
+    #[cfg(feature = "locked-memory")]
+    pub fn was_locked(&self) -> bool {
+        self.p.was_locked() && self.q.was_locked() && self.qInv.was_locked()
     }
 
     /// Returns a reference to the public key.
@@ -427,14 +468,45 @@ impl KeyPair {
         &self.public
     }
 
+    /// Writes the DER-encoded `RSAPublicKey` into the start of `out`,
+    /// returning its length.
+    ///
+    /// Fails if `out` is too short. A buffer of
+    /// [`Self::MAX_PUBLIC_KEY_LEN`] bytes is always long enough.
+    pub fn public_key_into(&self, out: &mut [u8]) -> Result<usize, error::Unspecified> {
+        signature::public_key_into(self.public.as_ref(), out)
+    }
+
     /// Returns the length in bytes of the key pair's public modulus.
     ///
     /// A signature has the same length as the public modulus.
     #[deprecated = "Use `public().modulus_len()`"]
     #[inline]
     pub fn public_modulus_len(&self) -> usize {
+        trace_deprecated!("RsaKeyPair::public_modulus_len", "public().modulus_len()");
         self.public().modulus_len()
     }
+
+    /// The big-endian encoding of the public modulus, without leading zeros.
+    pub fn modulus(&self) -> &[u8] {
+        self.public.n_and_e_be_bytes().0
+    }
+
+    /// The big-endian encoding of the public exponent, without leading zeros.
+    pub fn public_exponent(&self) -> &[u8] {
+        self.public.n_and_e_be_bytes().1
+    }
+
+    /// The length of the public modulus in bits.
+    pub fn key_size_bits(&self) -> usize {
+        self.public.inner().n().len_bits().as_usize_bits()
+    }
+
+    /// Returns the fingerprint of the key pair's public key, computed over
+    /// its `SubjectPublicKeyInfo` as described in [`fingerprint`].
+    pub fn spki_fingerprint(&self) -> fingerprint::PublicKeyFingerprint {
+        fingerprint::spki_fingerprint(RSA_ENCRYPTION, self.public().as_ref())
+    }
 }
 
 impl signature::KeyPair for KeyPair {
@@ -445,6 +517,10 @@ impl signature::KeyPair for KeyPair {
     }
 }
 
+fn out_of_memory(_: TryReserveError) -> KeyRejected {
+    KeyRejected::out_of_memory()
+}
+
 struct PrivatePrime<M: Prime> {
     modulus: bigint::OwnedModulusWithOne<M>,
     exponent: bigint::PrivateExponent,
@@ -478,8 +554,7 @@ impl<M: Prime> PrivatePrime<M> {
         // Steps 5.e and 5.f are omitted as explained above.
 
         // [NIST SP-800-56B rev. 1] 6.4.1.4.3 - Steps 7.a & 7.b.
-        let dP = bigint::PrivateExponent::from_be_bytes_padded(dP, &p.modulus())
-            .map_err(|error::Unspecified| KeyRejected::inconsistent_components())?;
+        let dP = bigint::PrivateExponent::from_be_bytes_padded(dP, &p.modulus())?;
 
         // XXX: Steps 7.d and 7.e are omitted. We don't check that
         // `dP == d % (p - 1)` because we don't (in the long term) have a good
@@ -499,23 +574,19 @@ impl<M: Prime> PrivatePrime<M> {
             exponent: dP,
         })
     }
-}
 
-fn elem_exp_consttime<M, MM>(
-    c: &bigint::Elem<MM>,
-    p: &PrivatePrime<M>,
-) -> Result<bigint::Elem<M>, error::Unspecified>
-where
-    M: bigint::NotMuchSmallerModulus<MM>,
-    M: Prime,
-{
-    let m = &p.modulus.modulus();
-    let c_mod_m = bigint::elem_reduced(c, m);
-    // We could precompute `oneRRR = elem_squared(&p.oneRR`) as mentioned
-    // in the Smooth CRT-RSA paper.
-    let c_mod_m = bigint::elem_mul(p.modulus.oneRR().as_ref(), c_mod_m, m);
-    let c_mod_m = bigint::elem_mul(p.modulus.oneRR().as_ref(), c_mod_m, m);
-    bigint::elem_exp_consttime(c_mod_m, &p.exponent, &p.modulus)
+    #[cfg(feature = "locked-memory")]
+    fn into_locked(self) -> Self {
+        Self {
+            modulus: self.modulus.into_locked(),
+            exponent: self.exponent.into_locked(),
+        }
+    }
+
+    #[cfg(feature = "locked-memory")]
+    fn was_locked(&self) -> bool {
+        self.modulus.was_locked() && self.exponent.was_locked()
+    }
 }
 
 // Type-level representations of the different moduli used in RSA signing, in
@@ -527,29 +598,18 @@ unsafe impl Prime for P {}
 unsafe impl bigint::SmallerModulus<N> for P {}
 unsafe impl bigint::NotMuchSmallerModulus<N> for P {}
 
-#[derive(Copy, Clone)]
-enum QQ {}
-unsafe impl bigint::SmallerModulus<N> for QQ {}
-unsafe impl bigint::NotMuchSmallerModulus<N> for QQ {}
-
-// `q < p < 2*q` since `q` is slightly smaller than `p` (see below). Thus:
-//
-//                         q <  p  < 2*q
-//                       q*q < p*q < 2*q*q.
-//                      q**2 <  n  < 2*(q**2).
-unsafe impl bigint::SlightlySmallerModulus<N> for QQ {}
-
 #[derive(Copy, Clone)]
 enum Q {}
 unsafe impl Prime for Q {}
 unsafe impl bigint::SmallerModulus<N> for Q {}
 unsafe impl bigint::SmallerModulus<P> for Q {}
 
-// q < p && `p.bit_length() == q.bit_length()` implies `q < p < 2*q`.
-unsafe impl bigint::SlightlySmallerModulus<P> for Q {}
-
-unsafe impl bigint::SmallerModulus<QQ> for Q {}
-unsafe impl bigint::NotMuchSmallerModulus<QQ> for Q {}
+// `q < √n`, so `Q` doesn't meet the letter of `NotMuchSmallerModulus<N>`.
+// What `elem_reduced` actually requires is `c < q*R` for `c < n`, where `R`
+// is `q`'s Montgomery `R`. `p` and `q` have the same bit length, which is a
+// multiple of 512, so `R == 2**q.len_bits() > p` and thus
+// `c < n == p*q < q*R`.
+unsafe impl bigint::NotMuchSmallerModulus<N> for Q {}
 
 impl KeyPair {
     /// Computes the signature of `msg` and writes it into `signature`.
@@ -581,6 +641,7 @@ impl KeyPair {
         }
 
         let m_hash = digest::digest(padding_alg.digest_alg(), msg);
+        trace_span!(crate::trace::SIGN, algorithm = ?padding_alg, message_digest = ?m_hash);
 
         // Use the output buffer as the scratch space for the signature to
         // reduce the required stack space.
@@ -598,6 +659,35 @@ impl KeyPair {
         Ok(())
     }
 
+    /// Like [`Self::sign`], except the private key operation is done a step
+    /// at a time by [`SignOperation::step`], so that signing with a large key
+    /// doesn't monopolize the thread, e.g. in a single-threaded asynchronous
+    /// executor.
+    ///
+    /// The message is digested and padded, using `rng` if necessary, before
+    /// this returns. `signature` is written when the operation is finished;
+    /// until then, it contains the padded message digest. On failure,
+    /// `signature` may contain intermediate results, but won't contain
+    /// anything that would endanger the private key.
+    pub fn sign_stepwise<'a>(
+        &'a self,
+        padding_alg: &'static dyn RsaEncoding,
+        rng: &dyn rand::SecureRandom,
+        msg: &[u8],
+        signature: &'a mut [u8],
+    ) -> Result<SignOperation<'a>, error::Unspecified> {
+        if signature.len() != self.public().modulus_len() {
+            return Err(error::Unspecified);
+        }
+
+        let m_hash = digest::digest(padding_alg.digest_alg(), msg);
+        trace_span!(crate::trace::SIGN, algorithm = ?padding_alg, message_digest = ?m_hash);
+
+        padding_alg.encode(m_hash, signature, self.public().inner().n().len_bits(), rng)?;
+
+        SignOperation::new(self, signature)
+    }
+
     /// Returns base**d (mod n).
     ///
     /// This does not return or write any intermediate results into any buffers
@@ -606,58 +696,11 @@ impl KeyPair {
     ///
     /// Panics if `in_out` is not `self.public().modulus_len()`.
     fn private_exponentiate(&self, base: &[u8]) -> Result<bigint::Elem<N>, error::Unspecified> {
-        assert_eq!(base.len(), self.public().modulus_len());
-
-        // RFC 8017 Section 5.1.2: RSADP, using the Chinese Remainder Theorem
-        // with Garner's algorithm.
-
-        let n = &self.public.inner().n().value().modulus();
-
-        // Step 1. The value zero is also rejected.
-        let base = bigint::Elem::from_be_bytes_padded(untrusted::Input::from(base), n)?;
-
-        // Step 2
-        let c = base;
-
-        // Step 2.b.i.
-        let m_1 = elem_exp_consttime(&c, &self.p)?;
-        let c_mod_qq = bigint::elem_reduced_once(&c, &self.qq.modulus());
-        let m_2 = elem_exp_consttime(&c_mod_qq, &self.q)?;
-
-        // Step 2.b.ii isn't needed since there are only two primes.
-
-        // Step 2.b.iii.
-        let p = &self.p.modulus.modulus();
-        let m_2 = bigint::elem_widen(m_2, p);
-        let m_1_minus_m_2 = bigint::elem_sub(m_1, &m_2, p);
-        let h = bigint::elem_mul(&self.qInv, m_1_minus_m_2, p);
-
-        // Step 2.b.iv. The reduction in the modular multiplication isn't
-        // necessary because `h < p` and `p * q == n` implies `h * q < n`.
-        // Modular arithmetic is used simply to avoid implementing
-        // non-modular arithmetic.
-        let h = bigint::elem_widen(h, n);
-        let q_times_h = bigint::elem_mul(&self.q_mod_n, h, n);
-        let m_2 = bigint::elem_widen(m_2, n);
-        let m = bigint::elem_add(m_2, q_times_h, n);
-
-        // Step 2.b.v isn't needed since there are only two primes.
-
-        // Verify the result to protect against fault attacks as described
-        // in "On the Importance of Checking Cryptographic Protocols for
-        // Faults" by Dan Boneh, Richard A. DeMillo, and Richard J. Lipton.
-        // This check is cheap assuming `e` is small, which is ensured during
-        // `KeyPair` construction. Note that this is the only validation of `e`
-        // that is done other than basic checks on its size, oddness, and
-        // minimum value, since the relationship of `e` to `d`, `p`, and `q` is
-        // not verified during `KeyPair` construction.
-        {
-            let verify = self.public.inner().exponentiate_elem(&m);
-            bigint::elem_verify_equal_consttime(&verify, &c)?;
+        let mut exponentiation = PrivateExponentiation::new(self, base)?;
+        loop {
+            if let Poll::Ready(r) = exponentiation.step(&mut bigint::Budget::new(u32::MAX)) {
+                return r;
+            }
         }
-
-        // Step 3 will be done by the caller.
-
-        Ok(m)
     }
 }
@@ -0,0 +1,225 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{KeyPair, P, Q, RSA_ENCRYPTION};
+use crate::{
+    arithmetic::bigint::{
+        self,
+        keygen::{self, Zeroizing},
+    },
+    bits::BitLength,
+    cpu,
+    error::{self, KeyRejected},
+    io::{
+        self,
+        der::Tag,
+        der_writer::{write_all, write_positive_integer, write_tlv},
+        writer::Accumulator,
+    },
+    limb::{self, Limb},
+    polyfill::LeadingZerosStripped,
+    rand,
+    rsa::{KeyPairComponents, PublicExponent, PublicKeyComponents},
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// The size of an RSA key pair generated by [`KeyPair::generate()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeySize {
+    /// A 2048-bit public modulus.
+    Rsa2048,
+
+    /// A 3072-bit public modulus.
+    Rsa3072,
+
+    /// A 4096-bit public modulus.
+    Rsa4096,
+}
+
+impl KeySize {
+    /// The length of the public modulus, in bits.
+    pub fn len_bits(self) -> usize {
+        match self {
+            Self::Rsa2048 => 2048,
+            Self::Rsa3072 => 3072,
+            Self::Rsa4096 => 4096,
+        }
+    }
+}
+
+impl KeyPair {
+    /// Generates a new key pair with a public modulus of the given size and
+    /// the public exponent 65537.
+    ///
+    /// The prime factors are generated as described in
+    /// [FIPS 186-4 Appendix B.3.3]. The result is then validated exactly as
+    /// [`Self::from_components()`] validates a key pair given to it.
+    ///
+    /// Generation is slow, and how slow varies from one call to the next.
+    /// The number of candidate primes tried is bounded, so a failing `rng`
+    /// results in an error instead of a loop that never ends.
+    ///
+    /// Use [`Self::to_pkcs8()`] to serialize the key pair.
+    ///
+    /// [FIPS 186-4 Appendix B.3.3]:
+    ///     https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+    pub fn generate(
+        size: KeySize,
+        rng: &dyn rand::SecureRandom,
+    ) -> Result<Self, error::Unspecified> {
+        trace_span!(
+            crate::trace::KEY_GENERATION,
+            algorithm = "RSA",
+            key_bits = size.len_bits()
+        );
+        let cpu_features = cpu::features();
+        let e = PublicExponent::_65537.value().get();
+
+        let prime_bits = BitLength::from_usize_bits(size.len_bits() / 2);
+        let p = keygen::generate_prime(prime_bits, e, None, rng, cpu_features)?;
+        let q = keygen::generate_prime(prime_bits, e, Some(&p), rng, cpu_features)?;
+        // `qInv` is calculated for `p > q`, the order that `from_components_`
+        // wants. Which prime is the larger one isn't secret.
+        let (p, q) = if limb::limbs_less_than_limbs_vartime(&p, &q) {
+            (q, p)
+        } else {
+            (p, q)
+        };
+
+        let n = keygen::limbs_product(&p, &q);
+        let d = private_exponent(&p, &q, e)?;
+        let dP = Zeroizing::new(keygen::small_inverse_mod(e, &minus_one(&p))?);
+        let dQ = Zeroizing::new(keygen::small_inverse_mod(e, &minus_one(&q))?);
+
+        let p = be_bytes(&p);
+        let q = be_bytes(&q);
+        let qInv =
+            q_inverse_mod_p(&p, &q, cpu_features).map_err(|_: KeyRejected| error::Unspecified)?;
+
+        let n = be_bytes(&n);
+        let e: Vec<u8> = LeadingZerosStripped::new(e.to_be_bytes().into_iter()).collect();
+        let d = be_bytes(&d);
+        let dP = be_bytes(&dP);
+        let dQ = be_bytes(&dQ);
+
+        let components = KeyPairComponents {
+            public_key: PublicKeyComponents {
+                n: &n[..],
+                e: &e[..],
+            },
+            d: &d[..],
+            p: &p[..],
+            q: &q[..],
+            dP: &dP[..],
+            dQ: &dQ[..],
+            qInv: &qInv[..],
+        };
+        Self::from_components_(&components, cpu_features)
+            .map_err(|_: KeyRejected| error::Unspecified)
+    }
+
+    /// Serializes the key pair as an unencrypted PKCS#8 v1 document
+    /// containing an RSA `RSAPrivateKey`, the format that
+    /// [`Self::from_pkcs8()`] parses.
+    ///
+    /// The key pair doesn't keep the private exponent `d`, so it is
+    /// recalculated as `e**-1 (mod (p - 1)(q - 1))`. This is the `d` that
+    /// [`Self::generate()`] uses, but it may differ from the `d` of an
+    /// imported key, e.g. one that was calculated modulo
+    /// `LCM(p - 1, q - 1)`. Either value works with every other component.
+    ///
+    /// An error is returned if `e` isn't invertible, which is only possible
+    /// for an imported key since *ring* doesn't check that when importing.
+    pub fn to_pkcs8(&self) -> Result<Box<[u8]>, error::Unspecified> {
+        let (n, e) = self.public.n_and_e_be_bytes();
+        let p = self.p.modulus.limbs();
+        let q = self.q.modulus.limbs();
+        let d = private_exponent(p, q, self.public.inner().e().value().get())?;
+        let mut qInv = Zeroizing::new(vec![
+            0;
+            self.p.modulus.len_bits().as_usize_bytes_rounded_up()
+        ]);
+        self.qInv
+            .try_clone()
+            .map_err(|_| error::Unspecified)?
+            .into_unencoded(&self.p.modulus.modulus())
+            .fill_be_bytes(&mut qInv);
+
+        let d = be_bytes(&d);
+        let p = be_bytes(p);
+        let q = be_bytes(q);
+        let dP = be_bytes(self.p.exponent.limbs());
+        let dQ = be_bytes(self.q.exponent.limbs());
+        let qInv = Zeroizing::new(LeadingZerosStripped::new(qInv.iter().copied()).collect());
+
+        // RFC 3447 Appendix A.1.2.
+        let rsa_private_key = |output: &mut dyn Accumulator| {
+            write_tlv(output, Tag::Integer, |output| output.write_byte(0)); // two-prime
+            for value in [n, e, &d[..], &p[..], &q[..], &dP[..], &dQ[..], &qInv[..]] {
+                // Every component is nonzero and has no leading zeros.
+                let value = io::Positive::from_be_bytes(untrusted::Input::from(value)).unwrap();
+                write_positive_integer(output, &value);
+            }
+        };
+
+        // RFC 5958 Section 2.
+        write_all(Tag::Sequence, &|output| {
+            write_tlv(output, Tag::Integer, |output| output.write_byte(0)); // v1
+            write_tlv(output, Tag::Sequence, |output| {
+                output.write_bytes(RSA_ENCRYPTION);
+            });
+            write_tlv(output, Tag::OctetString, |output| {
+                write_tlv(output, Tag::Sequence, rsa_private_key);
+            });
+        })
+        .map_err(|_| error::Unspecified)
+    }
+}
+
+// Returns `e**-1 (mod (p - 1)(q - 1))`.
+fn private_exponent(p: &[Limb], q: &[Limb], e: u64) -> Result<Zeroizing<Limb>, error::Unspecified> {
+    let phi = Zeroizing::new(keygen::limbs_product(&minus_one(p), &minus_one(q)));
+    keygen::small_inverse_mod(e, &phi).map(Zeroizing::new)
+}
+
+// Returns `p - 1` for an odd `p`.
+fn minus_one(p: &[Limb]) -> Zeroizing<Limb> {
+    let mut r = Zeroizing::new(p.to_vec());
+    r[0] &= !1;
+    r
+}
+
+fn be_bytes(limbs: &[Limb]) -> Zeroizing<u8> {
+    Zeroizing::new(LeadingZerosStripped::new(limb::unstripped_be_bytes(limbs)).collect())
+}
+
+// Returns `q**-1 (mod p)`, padded to the length of `p`.
+fn q_inverse_mod_p(
+    p: &[u8],
+    q: &[u8],
+    cpu_features: cpu::Features,
+) -> Result<Zeroizing<u8>, KeyRejected> {
+    let (p, _) = bigint::Nonnegative::from_be_bytes_with_bit_length(untrusted::Input::from(p))?;
+    let (q, _) = bigint::Nonnegative::from_be_bytes_with_bit_length(untrusted::Input::from(q))?;
+    let p = bigint::OwnedModulusWithOne::<P>::from_nonnegative(p, cpu_features)?;
+    let q = bigint::OwnedModulusWithOne::<Q>::from_nonnegative(q, cpu_features)?;
+    let pm = &p.modulus();
+    let q_mod_p = q.to_elem(pm).map_err(|_| KeyRejected::out_of_memory())?;
+    let q_mod_p = bigint::elem_mul(p.oneRR().as_ref(), q_mod_p, pm);
+    let qInv = bigint::elem_inverse_consttime(q_mod_p, &p)
+        .map_err(|error::Unspecified| KeyRejected::out_of_memory())?;
+    let mut r = Zeroizing::new(vec![0; p.len_bits().as_usize_bytes_rounded_up()]);
+    qInv.fill_be_bytes(&mut r);
+    Ok(r)
+}
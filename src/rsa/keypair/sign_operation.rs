@@ -0,0 +1,375 @@
+// Copyright 2015-2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{KeyPair, PrivatePrime, N, P, Q};
+use crate::{
+    arithmetic::{
+        bigint::{self, Budget, ElemExpConsttime, Prime},
+        montgomery::R,
+    },
+    error,
+};
+use core::task::Poll;
+
+/// An RSA signing operation that is done a step at a time, so that it can
+/// be interleaved with other work, e.g. by an asynchronous executor.
+///
+/// Constructed by [`KeyPair::sign_stepwise`]. All secret intermediate values
+/// are zeroized when the operation is dropped, whether or not it has
+/// finished.
+pub struct SignOperation<'a> {
+    exponentiation: PrivateExponentiation<'a>,
+    signature: &'a mut [u8],
+}
+
+impl<'a> SignOperation<'a> {
+    pub(super) fn new(
+        key_pair: &'a KeyPair,
+        signature: &'a mut [u8],
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            exponentiation: PrivateExponentiation::new(key_pair, signature)?,
+            signature,
+        })
+    }
+
+    /// Does at most `budget` Montgomery multiplications of work, returning
+    /// `Poll::Ready` once the signature has been written.
+    ///
+    /// The work is divided into indivisible units. Each call does at least one
+    /// unit so that the operation always makes progress, even if that exceeds
+    /// `budget`. Most units are at most six multiplications; the final unit,
+    /// which recombines the CRT components and verifies the result using the
+    /// public key, is a few times that. How much work is done in each step
+    /// depends only on `budget` and on the sizes of the key's components, so
+    /// it doesn't leak the private key.
+    ///
+    /// Panics if called after `Poll::Ready` has been returned.
+    pub fn step(&mut self, budget: u32) -> Poll<Result<(), error::Unspecified>> {
+        self.exponentiation
+            .step(&mut Budget::new(budget))
+            .map(|m| m.map(|m| m.fill_be_bytes(self.signature)))
+    }
+
+    #[cfg(test)]
+    fn secret_limbs(&self) -> impl Iterator<Item = &[crate::limb::Limb]> {
+        self.exponentiation.secret_limbs()
+    }
+}
+
+impl core::fmt::Debug for SignOperation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("SignOperation").finish_non_exhaustive()
+    }
+}
+
+/// Computes base**d (mod n) a step at a time.
+///
+/// This does not return or write any intermediate results into any buffers
+/// that are provided by the caller so that no intermediate state will be
+/// leaked that would endanger the private key.
+pub(super) struct PrivateExponentiation<'a> {
+    key_pair: &'a KeyPair,
+    c: bigint::Elem<N>,
+    stage: Stage<'a>,
+}
+
+enum Stage<'a> {
+    Start,
+    M1(ElemExpConsttime<'a, P>),
+    StartM2 {
+        m_1: bigint::Elem<P>,
+    },
+    M2 {
+        m_1: bigint::Elem<P>,
+        exp: ElemExpConsttime<'a, Q>,
+    },
+    Finish {
+        m_1: bigint::Elem<P>,
+        m_2: bigint::Elem<Q>,
+    },
+    Done,
+}
+
+// `elem_reduced` and the two multiplications in `start_exp_consttime`.
+const START_EXP_COST: u32 = 3;
+
+impl<'a> PrivateExponentiation<'a> {
+    /// Fails if `base` isn't in [1, n).
+    ///
+    /// Panics if `base` is not `key_pair.public().modulus_len()` bytes long.
+    pub(super) fn new(key_pair: &'a KeyPair, base: &[u8]) -> Result<Self, error::Unspecified> {
+        assert_eq!(base.len(), key_pair.public().modulus_len());
+
+        // RFC 8017 Section 5.1.2: RSADP, using the Chinese Remainder Theorem
+        // with Garner's algorithm.
+
+        let n = &key_pair.public.inner().n().value().modulus();
+
+        // Step 1. The value zero is also rejected.
+        let base = bigint::Elem::from_be_bytes_padded(untrusted::Input::from(base), n)?;
+
+        // Step 2
+        let c = base;
+
+        Ok(Self {
+            key_pair,
+            c,
+            stage: Stage::Start,
+        })
+    }
+
+    pub(super) fn step(
+        &mut self,
+        budget: &mut Budget,
+    ) -> Poll<Result<bigint::Elem<N>, error::Unspecified>> {
+        let r = self.step_(budget);
+        if r.is_ready() {
+            self.stage = Stage::Done;
+        }
+        r
+    }
+
+    fn step_(&mut self, budget: &mut Budget) -> Poll<Result<bigint::Elem<N>, error::Unspecified>> {
+        let key_pair = self.key_pair;
+        loop {
+            match &mut self.stage {
+                Stage::Start => {
+                    if !budget.try_spend(START_EXP_COST) {
+                        return Poll::Pending;
+                    }
+                    // Step 2.b.i.
+                    match start_exp_consttime(&self.c, &key_pair.p) {
+                        Ok(exp) => self.stage = Stage::M1(exp),
+                        Err(e) => return Poll::Ready(Err(e)),
+                    }
+                }
+                Stage::M1(exp) => match exp.step(budget) {
+                    Poll::Ready(m_1) => self.stage = Stage::StartM2 { m_1 },
+                    Poll::Pending => return Poll::Pending,
+                },
+                Stage::StartM2 { .. } => {
+                    if !budget.try_spend(START_EXP_COST) {
+                        return Poll::Pending;
+                    }
+                    let exp = match start_exp_consttime(&self.c, &key_pair.q) {
+                        Ok(exp) => exp,
+                        Err(e) => return Poll::Ready(Err(e)),
+                    };
+                    self.stage = match core::mem::replace(&mut self.stage, Stage::Done) {
+                        Stage::StartM2 { m_1 } => Stage::M2 { m_1, exp },
+                        _ => unreachable!(),
+                    };
+                }
+                Stage::M2 { exp, .. } => match exp.step(budget) {
+                    Poll::Ready(m_2) => {
+                        self.stage = match core::mem::replace(&mut self.stage, Stage::Done) {
+                            Stage::M2 { m_1, .. } => Stage::Finish { m_1, m_2 },
+                            _ => unreachable!(),
+                        };
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                Stage::Finish { .. } => {
+                    if !budget.try_spend(self.finish_cost()) {
+                        return Poll::Pending;
+                    }
+                    return match core::mem::replace(&mut self.stage, Stage::Done) {
+                        Stage::Finish { m_1, m_2 } => Poll::Ready(self.finish(m_1, m_2)),
+                        _ => unreachable!(),
+                    };
+                }
+                Stage::Done => panic!("`PrivateExponentiation` stepped after completion"),
+            }
+        }
+    }
+
+    // The multiplications in `finish`, including those in
+    // `PublicKey::exponentiate_elem`, which are bounded by twice the bit
+    // length of the public exponent.
+    fn finish_cost(&self) -> u32 {
+        let e = self.key_pair.public.inner().e().value().get();
+        3 + 2 * (u64::BITS - e.leading_zeros())
+    }
+
+    fn finish(
+        &self,
+        m_1: bigint::Elem<P>,
+        m_2: bigint::Elem<Q>,
+    ) -> Result<bigint::Elem<N>, error::Unspecified> {
+        let key_pair = self.key_pair;
+        let n_one = key_pair.public.inner().n().value().oneRR();
+        let n = &key_pair.public.inner().n().value().modulus();
+
+        // Step 2.b.ii isn't needed since there are only two primes.
+
+        // Step 2.b.iii.
+        let p = &key_pair.p.modulus.modulus();
+        let m_2 = bigint::elem_widen(m_2, p);
+        let m_1_minus_m_2 = bigint::elem_sub(m_1, &m_2, p);
+        let h = bigint::elem_mul(&key_pair.qInv, m_1_minus_m_2, p);
+
+        // Step 2.b.iv. The reduction in the modular multiplication isn't
+        // necessary because `h < p` and `p * q == n` implies `h * q < n`.
+        // Modular arithmetic is used simply to avoid implementing
+        // non-modular arithmetic.
+        let h = bigint::elem_widen(h, n);
+        let q_mod_n = key_pair
+            .q
+            .modulus
+            .to_elem(n)
+            .map_err(|_| error::Unspecified)?;
+        let mut q_mod_n = bigint::elem_mul(n_one.as_ref(), q_mod_n, n);
+        let q_times_h = bigint::elem_mul(&q_mod_n, h, n);
+        q_mod_n.zeroize();
+        let m_2 = bigint::elem_widen(m_2, n);
+        let m = bigint::elem_add(m_2, q_times_h, n);
+
+        // Step 2.b.v isn't needed since there are only two primes.
+
+        // Verify the result to protect against fault attacks as described
+        // in "On the Importance of Checking Cryptographic Protocols for
+        // Faults" by Dan Boneh, Richard A. DeMillo, and Richard J. Lipton.
+        // This check is cheap assuming `e` is small, which is ensured during
+        // `KeyPair` construction. Note that this is the only validation of `e`
+        // that is done other than basic checks on its size, oddness, and
+        // minimum value, since the relationship of `e` to `d`, `p`, and `q` is
+        // not verified during `KeyPair` construction.
+        {
+            let verify = key_pair.public.inner().exponentiate_elem(&m);
+            bigint::elem_verify_equal_consttime(&verify, &self.c)?;
+        }
+
+        // Step 3 will be done by the caller.
+
+        Ok(m)
+    }
+
+    #[cfg(test)]
+    fn secret_limbs(&self) -> impl Iterator<Item = &[crate::limb::Limb]> {
+        let (m_1, exp_p, exp_q, m_2) = match &self.stage {
+            Stage::M1(exp) => (None, Some(exp), None, None),
+            Stage::StartM2 { m_1 } => (Some(m_1), None, None, None),
+            Stage::M2 { m_1, exp } => (Some(m_1), None, Some(exp), None),
+            Stage::Finish { m_1, m_2 } => (Some(m_1), None, None, Some(m_2)),
+            Stage::Start | Stage::Done => (None, None, None, None),
+        };
+        m_1.map(|m_1| m_1.limbs())
+            .into_iter()
+            .chain(exp_p.into_iter().flat_map(|exp| exp.secret_limbs()))
+            .chain(exp_q.into_iter().flat_map(|exp| exp.secret_limbs()))
+            .chain(m_2.map(|m_2| m_2.limbs()))
+    }
+}
+
+impl Drop for PrivateExponentiation<'_> {
+    fn drop(&mut self) {
+        // The exponentiations zeroize themselves.
+        match &mut self.stage {
+            Stage::StartM2 { m_1 } | Stage::M2 { m_1, .. } => m_1.zeroize(),
+            Stage::Finish { m_1, m_2 } => {
+                m_1.zeroize();
+                m_2.zeroize();
+            }
+            Stage::Start | Stage::M1(_) | Stage::Done => {}
+        }
+    }
+}
+
+fn start_exp_consttime<'a, M, MM>(
+    c: &bigint::Elem<MM>,
+    p: &'a PrivatePrime<M>,
+) -> Result<ElemExpConsttime<'a, M>, error::Unspecified>
+where
+    M: bigint::NotMuchSmallerModulus<MM>,
+    M: Prime,
+{
+    let m = &p.modulus.modulus();
+    let c_mod_m = bigint::elem_reduced(c, m);
+    // We could precompute `oneRRR = elem_squared(&p.oneRR`) as mentioned
+    // in the Smooth CRT-RSA paper.
+    let c_mod_m = bigint::elem_mul(p.modulus.oneRR().as_ref(), c_mod_m, m);
+    let c_mod_m: bigint::Elem<M, R> = bigint::elem_mul(p.modulus.oneRR().as_ref(), c_mod_m, m);
+    ElemExpConsttime::new(c_mod_m, &p.exponent, &p.modulus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{polyfill::zeroize_hook, rand, signature};
+    use alloc::{vec, vec::Vec};
+
+    const PRIVATE_KEY: &[u8] = include_bytes!("../signature_rsa_example_private_key.der");
+    const MESSAGE: &[u8] = b"hello, world";
+
+    #[test]
+    fn test_sign_stepwise_matches_sign() {
+        let key_pair = KeyPair::from_der(PRIVATE_KEY).unwrap();
+        let rng = rand::SystemRandom::new();
+        let mut expected = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut expected)
+            .unwrap();
+
+        for budget in [0, 1, 5, 6, 7, 100, u32::MAX] {
+            let mut actual = vec![0; expected.len()];
+            let mut op = key_pair
+                .sign_stepwise(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut actual)
+                .unwrap();
+            let mut steps = 1;
+            while op.step(budget).is_pending() {
+                steps += 1;
+            }
+            if budget == u32::MAX {
+                assert_eq!(steps, 1);
+            } else if budget <= 6 {
+                // At least one window is done per step.
+                assert!(steps > 2 * (1024 / 5));
+            }
+            drop(op);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_sign_stepwise_zeroized_on_drop() {
+        let key_pair = KeyPair::from_der(PRIVATE_KEY).unwrap();
+        let rng = rand::SystemRandom::new();
+
+        // Stop in the middle of the exponentiation modulo p, and then in the
+        // middle of the exponentiation modulo q, when `m_1` is also live.
+        for (steps, num_secrets) in [(100, 2), (300, 3)] {
+            let mut signature = vec![0; key_pair.public().modulus_len()];
+            let mut op = key_pair
+                .sign_stepwise(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut signature)
+                .unwrap();
+            for _ in 0..steps {
+                assert!(op.step(6).is_pending());
+            }
+
+            let secrets: Vec<(usize, usize)> = op
+                .secret_limbs()
+                .map(zeroize_hook::address_and_len)
+                .collect();
+            assert_eq!(secrets.len(), num_secrets);
+            assert!(op.secret_limbs().any(|limbs| limbs.iter().any(|&l| l != 0)));
+
+            zeroize_hook::clear();
+            drop(op);
+            for secret in secrets {
+                assert!(zeroize_hook::was_zeroized(secret));
+            }
+        }
+    }
+}
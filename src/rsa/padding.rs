@@ -43,6 +43,9 @@ pub trait RsaEncoding: Padding {
         mod_bits: bits::BitLength,
         rng: &dyn rand::SecureRandom,
     ) -> Result<(), error::Unspecified>;
+
+    #[doc(hidden)]
+    fn algorithm_identifier(&self) -> crate::signature::AlgorithmIdentifier;
 }
 
 /// Verification of an RSA signature encoding as described in
@@ -13,7 +13,7 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use super::{super::PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN, Padding, RsaEncoding, Verification};
-use crate::{bits, digest, error, io::der, rand};
+use crate::{bits, digest, error, io::der, rand, signature::AlgorithmIdentifier};
 
 /// PKCS#1 1.5 padding as described in [RFC 3447 Section 8.2].
 ///
@@ -46,6 +46,18 @@ impl RsaEncoding for PKCS1 {
         pkcs1_encode(self, m_hash, m_out);
         Ok(())
     }
+
+    fn algorithm_identifier(&self) -> AlgorithmIdentifier {
+        if self.digest_alg == &digest::SHA256 {
+            AlgorithmIdentifier::RsaPkcs1Sha256
+        } else if self.digest_alg == &digest::SHA384 {
+            AlgorithmIdentifier::RsaPkcs1Sha384
+        } else if self.digest_alg == &digest::SHA512 {
+            AlgorithmIdentifier::RsaPkcs1Sha512
+        } else {
+            AlgorithmIdentifier::RsaPkcs1Sha1ForLegacyUseOnly
+        }
+    }
 }
 
 impl Verification for PKCS1 {
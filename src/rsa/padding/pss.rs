@@ -13,7 +13,7 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use super::{super::PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN, mgf1, Padding, RsaEncoding, Verification};
-use crate::{bits, digest, error, rand};
+use crate::{bits, digest, error, rand, signature::AlgorithmIdentifier};
 
 /// RSA PSS padding as described in [RFC 3447 Section 8.1].
 ///
@@ -97,6 +97,16 @@ impl RsaEncoding for PSS {
 
         Ok(())
     }
+
+    fn algorithm_identifier(&self) -> AlgorithmIdentifier {
+        if self.digest_alg == &digest::SHA256 {
+            AlgorithmIdentifier::RsaPssSha256
+        } else if self.digest_alg == &digest::SHA384 {
+            AlgorithmIdentifier::RsaPssSha384
+        } else {
+            AlgorithmIdentifier::RsaPssSha512
+        }
+    }
 }
 
 impl Verification for PSS {
@@ -0,0 +1,404 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{
+    parse_public_key, public_key, verification, PublicExponent, PublicModulus, RsaParameters,
+    PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN,
+};
+use crate::{
+    bits, cpu, digest, error,
+    limb::{Limb, LIMB_BITS, LIMB_BYTES},
+};
+use alloc::vec::Vec;
+
+/// An RSA public key that has been parsed and validated once, so that it can
+/// be used to verify many signatures without repeating that work.
+///
+/// Parsing a public key includes computing the Montgomery constants of the
+/// public modulus, which dominates the cost of verifying a single signature
+/// with a small public exponent. `to_cached_bytes()` serializes the result of
+/// that work so that `from_cached_bytes()` can reload it cheaply, e.g. when
+/// loading a large set of pinned public keys at startup.
+///
+/// There is no EC counterpart: parsing an EC public key only decodes the point
+/// and checks that it is on the curve, which costs a small fraction of one
+/// signature verification, so there is nothing worth caching.
+#[derive(Clone)]
+pub struct ParsedPublicKey {
+    inner: public_key::Inner,
+}
+
+impl core::fmt::Debug for ParsedPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        f.debug_struct("ParsedPublicKey")
+            .field("modulus_len", &self.modulus_len())
+            .finish()
+    }
+}
+
+impl ParsedPublicKey {
+    /// Parses and validates an RSA public key in DER-encoded PKCS#1
+    /// `RSAPublicKey` form, the same form accepted by
+    /// `signature::UnparsedPublicKey` with the `RSA_*` verification
+    /// algorithms.
+    ///
+    /// Keys of 1024 to [`super::MAX_RSA_BITS`] bits are accepted; the minimum
+    /// and maximum key sizes of the verification algorithm are enforced by
+    /// `verify()`.
+    pub fn from_der(public_key: &[u8]) -> Result<Self, error::KeyRejected> {
+        let (n, e) = parse_public_key(untrusted::Input::from(public_key))
+            .map_err(|error::Unspecified| error::KeyRejected::invalid_encoding())?;
+        let inner = public_key::Inner::from_modulus_and_exponent(
+            n.big_endian_without_leading_zero_as_input(),
+            e.big_endian_without_leading_zero_as_input(),
+            MIN_BITS,
+            MAX_BITS,
+            PublicExponent::_3,
+            cpu::features(),
+        )?;
+        Ok(Self { inner })
+    }
+
+    /// The length, in bytes, of the public modulus.
+    ///
+    /// The modulus length is rounded up to a whole number of bytes if its
+    /// bit length isn't a multiple of 8.
+    pub fn modulus_len(&self) -> usize {
+        self.inner.n().len_bits().as_usize_bytes_rounded_up()
+    }
+
+    /// Verifies that `signature` is a valid signature of `message` using
+    /// `self` as the public key.
+    ///
+    /// This is equivalent to `signature::UnparsedPublicKey::verify()`, except
+    /// that the public key isn't re-parsed.
+    pub fn verify(
+        &self,
+        params: &RsaParameters,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        verification::verify_rsa_with_key(
+            params,
+            &self.inner,
+            untrusted::Input::from(message),
+            untrusted::Input::from(signature),
+        )
+        .map_err(error::Unspecified::from)
+    }
+
+    /// Serializes the parsed form of the key, including the precomputed
+    /// Montgomery constants, for use with `from_cached_bytes()`.
+    ///
+    /// The encoding is tied to the version of *ring* and to the limb size of
+    /// the target, and includes an integrity check. It is not a stable
+    /// interchange format; it is only suitable for caches that are rebuilt
+    /// from the original keys when they fail to load.
+    pub fn to_cached_bytes(&self) -> Vec<u8> {
+        let n = self.inner.n().value();
+        let num_limbs = n.limbs().len();
+
+        let mut out = Vec::with_capacity(
+            CACHE_HEADER_LEN + 8 + 2 + 8 + (2 * num_limbs * LIMB_BYTES) + CHECKSUM_LEN,
+        );
+        write_header(&mut out);
+        out.extend_from_slice(&self.inner.e().value().get().to_be_bytes());
+        // `num_limbs` is bounded by `MODULUS_MAX_LIMBS`.
+        out.extend_from_slice(&u16::try_from(num_limbs).unwrap().to_be_bytes());
+        out.extend_from_slice(&n.n0_u64().to_be_bytes());
+        write_limbs(&mut out, n.limbs());
+        write_limbs(&mut out, n.oneRR_limbs());
+        let checksum = digest::digest(&digest::SHA256, &out);
+        out.extend_from_slice(checksum.as_ref());
+        out
+    }
+
+    /// Reloads a key serialized with `to_cached_bytes()`.
+    ///
+    /// Serializations produced by a different version of *ring* or on a
+    /// target with a different limb size are rejected with
+    /// `KeyRejected` "VersionNotSupported"; the caller should then fall back
+    /// to `from_der()`. The integrity check is verified and the cheap
+    /// invariants of the key (the range of the public exponent and the
+    /// length, oddness, and Montgomery constants of the modulus) are
+    /// re-validated; only the expensive re-computation of the Montgomery
+    /// constants is skipped.
+    pub fn from_cached_bytes(bytes: &[u8]) -> Result<Self, error::KeyRejected> {
+        let input = untrusted::Input::from(bytes);
+        input.read_all(error::KeyRejected::invalid_encoding(), |input| {
+            read_header(input)?;
+
+            let body = untrusted::Input::from(
+                bytes
+                    .get(..bytes.len().saturating_sub(CHECKSUM_LEN))
+                    .ok_or_else(error::KeyRejected::invalid_encoding)?,
+            );
+            let expected_checksum = bytes
+                .get(body.len()..)
+                .ok_or_else(error::KeyRejected::invalid_encoding)?;
+            let actual_checksum = digest::digest(&digest::SHA256, body.as_slice_less_safe());
+            if actual_checksum.as_ref() != expected_checksum {
+                return Err(error::KeyRejected::invalid_encoding());
+            }
+
+            let e = read_u64(input)?;
+            let num_limbs = read_u16(input)?;
+            let n0 = read_u64(input)?;
+            let n = read_limbs(input, num_limbs.into())?;
+            let rr = read_limbs(input, num_limbs.into())?;
+
+            // The checksum was already verified above.
+            let _ = input.read_bytes(CHECKSUM_LEN);
+
+            let n =
+                PublicModulus::from_precomputed(&n, n0, &rr, MIN_BITS..=MAX_BITS, cpu::features())?;
+            let inner = public_key::Inner::from_precomputed(n, e, PublicExponent::_3)?;
+            Ok(Self { inner })
+        })
+    }
+}
+
+const MIN_BITS: bits::BitLength = bits::BitLength::from_usize_bits(1024);
+const MAX_BITS: bits::BitLength =
+    bits::BitLength::from_usize_bits(PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN * 8);
+
+// The cache format is:
+//
+//   magic           || format version || LIMB_BITS ||
+//   len(version)    || version ||
+//   e (u64)         || num_limbs (u16) || n0 (u64) ||
+//   n (num_limbs)   || RR (num_limbs) ||
+//   SHA-256(everything before the checksum)
+//
+// All integers are big-endian; each limb is `LIMB_BYTES` big-endian bytes,
+// and limbs are in order from least to most significant.
+const CACHE_MAGIC: &[u8; 8] = b"ring-rsa";
+const CACHE_FORMAT_VERSION: u8 = 1;
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+const CACHE_HEADER_LEN: usize = CACHE_MAGIC.len() + 1 + 1 + 1 + CRATE_VERSION.len();
+const CHECKSUM_LEN: usize = digest::SHA256_OUTPUT_LEN;
+
+fn write_header(out: &mut Vec<u8>) {
+    out.extend_from_slice(CACHE_MAGIC);
+    out.push(CACHE_FORMAT_VERSION);
+    // `LIMB_BITS` is either 32 or 64.
+    out.push(u8::try_from(LIMB_BITS).unwrap());
+    // The crate version is a short string.
+    out.push(u8::try_from(CRATE_VERSION.len()).unwrap());
+    out.extend_from_slice(CRATE_VERSION.as_bytes());
+}
+
+fn read_header(input: &mut untrusted::Reader) -> Result<(), error::KeyRejected> {
+    let magic = input
+        .read_bytes(CACHE_MAGIC.len())
+        .map_err(|untrusted::EndOfInput| error::KeyRejected::invalid_encoding())?;
+    if magic.as_slice_less_safe() != CACHE_MAGIC {
+        return Err(error::KeyRejected::invalid_encoding());
+    }
+    let format_version = read_u8(input)?;
+    let limb_bits = read_u8(input)?;
+    let version_len = read_u8(input)?;
+    let version = input
+        .read_bytes(version_len.into())
+        .map_err(|untrusted::EndOfInput| error::KeyRejected::invalid_encoding())?;
+    if format_version != CACHE_FORMAT_VERSION
+        || usize::from(limb_bits) != LIMB_BITS
+        || version.as_slice_less_safe() != CRATE_VERSION.as_bytes()
+    {
+        return Err(error::KeyRejected::version_not_supported());
+    }
+    Ok(())
+}
+
+fn write_limbs(out: &mut Vec<u8>, limbs: &[Limb]) {
+    limbs
+        .iter()
+        .for_each(|limb| out.extend_from_slice(&limb.to_be_bytes()));
+}
+
+fn read_limbs(
+    input: &mut untrusted::Reader,
+    num_limbs: usize,
+) -> Result<Vec<Limb>, error::KeyRejected> {
+    (0..num_limbs)
+        .map(|_| {
+            let bytes = input
+                .read_bytes(LIMB_BYTES)
+                .map_err(|untrusted::EndOfInput| error::KeyRejected::invalid_encoding())?;
+            let bytes: [u8; LIMB_BYTES] = bytes
+                .as_slice_less_safe()
+                .try_into()
+                .map_err(|_| error::KeyRejected::invalid_encoding())?;
+            Ok(Limb::from_be_bytes(bytes))
+        })
+        .collect()
+}
+
+fn read_u8(input: &mut untrusted::Reader) -> Result<u8, error::KeyRejected> {
+    input
+        .read_byte()
+        .map_err(|untrusted::EndOfInput| error::KeyRejected::invalid_encoding())
+}
+
+fn read_u16(input: &mut untrusted::Reader) -> Result<u16, error::KeyRejected> {
+    let hi = read_u8(input)?;
+    let lo = read_u8(input)?;
+    Ok(u16::from_be_bytes([hi, lo]))
+}
+
+fn read_u64(input: &mut untrusted::Reader) -> Result<u64, error::KeyRejected> {
+    let mut value = 0u64;
+    for _ in 0..8 {
+        value = (value << 8) | u64::from(read_u8(input)?);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature;
+
+    const PUBLIC_KEY: &[u8] = include_bytes!("signature_rsa_example_public_key.der");
+
+    // Replaces the checksum so that only the header check can fail.
+    fn with_fixed_checksum(mut bytes: Vec<u8>) -> Vec<u8> {
+        let body_len = bytes.len() - CHECKSUM_LEN;
+        let checksum = digest::digest(&digest::SHA256, &bytes[..body_len]);
+        bytes[body_len..].copy_from_slice(checksum.as_ref());
+        bytes
+    }
+
+    fn rejection(bytes: &[u8]) -> alloc::string::String {
+        alloc::format!("{}", ParsedPublicKey::from_cached_bytes(bytes).unwrap_err())
+    }
+
+    #[test]
+    fn test_cached_bytes_round_trip() {
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+        let cached = key.to_cached_bytes();
+        let reloaded = ParsedPublicKey::from_cached_bytes(&cached).unwrap();
+        assert_eq!(reloaded.modulus_len(), key.modulus_len());
+        assert_eq!(reloaded.to_cached_bytes(), cached);
+
+        // The reloaded key must compute the same Montgomery constants as a
+        // freshly-parsed key.
+        let original = key.inner.n().value();
+        let reloaded = reloaded.inner.n().value();
+        assert_eq!(original.oneRR_limbs(), reloaded.oneRR_limbs());
+        assert_eq!(original.n0_u64(), reloaded.n0_u64());
+    }
+
+    #[test]
+    fn test_cached_bytes_reject_other_limb_bits() {
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+        let mut cached = key.to_cached_bytes();
+        let other_limb_bits = if LIMB_BITS == 64 { 32 } else { 64 };
+        cached[CACHE_MAGIC.len() + 1] = other_limb_bits;
+        assert_eq!(
+            rejection(&with_fixed_checksum(cached)),
+            "VersionNotSupported"
+        );
+    }
+
+    #[test]
+    fn test_cached_bytes_reject_other_versions() {
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+
+        let mut cached = key.to_cached_bytes();
+        cached[CACHE_MAGIC.len()] = CACHE_FORMAT_VERSION + 1;
+        assert_eq!(
+            rejection(&with_fixed_checksum(cached)),
+            "VersionNotSupported"
+        );
+
+        let mut cached = key.to_cached_bytes();
+        let last_version_byte = CACHE_HEADER_LEN - 1;
+        cached[last_version_byte] ^= 1;
+        assert_eq!(
+            rejection(&with_fixed_checksum(cached)),
+            "VersionNotSupported"
+        );
+    }
+
+    #[test]
+    fn test_cached_bytes_reject_corruption() {
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+        let cached = key.to_cached_bytes();
+
+        // Flip each bit after the header in turn; the checksum must catch it.
+        for i in CACHE_HEADER_LEN..cached.len() {
+            let mut corrupted = cached.clone();
+            corrupted[i] ^= 0x80;
+            assert_eq!(rejection(&corrupted), "InvalidEncoding");
+        }
+
+        assert_eq!(rejection(&cached[..cached.len() - 1]), "InvalidEncoding");
+        assert_eq!(rejection(&[]), "InvalidEncoding");
+    }
+
+    #[test]
+    fn test_cached_bytes_revalidate_invariants() {
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+        let cached = key.to_cached_bytes();
+        let e_offset = CACHE_HEADER_LEN;
+        let n_offset = e_offset + 8 + 2 + 8;
+
+        // Even modulus.
+        let mut even = cached.clone();
+        even[n_offset + LIMB_BYTES - 1] &= !1;
+        assert!(ParsedPublicKey::from_cached_bytes(&with_fixed_checksum(even)).is_err());
+
+        // Even public exponent.
+        let mut even_e = cached.clone();
+        even_e[e_offset + 7] &= !1;
+        assert!(ParsedPublicKey::from_cached_bytes(&with_fixed_checksum(even_e)).is_err());
+
+        // Inconsistent n0.
+        let mut bad_n0 = cached.clone();
+        bad_n0[n_offset - 1] ^= 2;
+        assert!(ParsedPublicKey::from_cached_bytes(&with_fixed_checksum(bad_n0)).is_err());
+
+        // RR not reduced modulo n.
+        let mut bad_rr = cached;
+        let rr_top = bad_rr.len() - CHECKSUM_LEN - LIMB_BYTES;
+        bad_rr[rr_top..][..LIMB_BYTES].fill(0xff);
+        assert!(ParsedPublicKey::from_cached_bytes(&with_fixed_checksum(bad_rr)).is_err());
+    }
+
+    #[test]
+    fn test_cached_key_verifies() {
+        const PRIVATE_KEY: &[u8] = include_bytes!("signature_rsa_example_private_key.der");
+        const MESSAGE: &[u8] = b"hello, world";
+
+        let key_pair = super::super::KeyPair::from_der(PRIVATE_KEY).unwrap();
+        let rng = crate::rand::SystemRandom::new();
+        let mut sig = alloc::vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut sig)
+            .unwrap();
+
+        let key = ParsedPublicKey::from_der(PUBLIC_KEY).unwrap();
+        let key = ParsedPublicKey::from_cached_bytes(&key.to_cached_bytes()).unwrap();
+        assert!(key
+            .verify(&signature::RSA_PKCS1_2048_8192_SHA256, MESSAGE, &sig)
+            .is_ok());
+        assert!(key
+            .verify(&signature::RSA_PKCS1_2048_8192_SHA256, b"goodbye", &sig)
+            .is_err());
+        assert!(key
+            .verify(&signature::RSA_PKCS1_3072_8192_SHA384, MESSAGE, &sig)
+            .is_err());
+    }
+}
@@ -54,6 +54,10 @@ impl PublicExponent {
             }
         })?;
 
+        Self::from_u64(value, min_value)
+    }
+
+    pub(super) fn from_u64(value: u64, min_value: Self) -> Result<Self, error::KeyRejected> {
         // Step 2 / Step b. NIST SP800-89 defers to FIPS 186-3, which requires
         // `e >= 65537`. We enforce this when signing, but are more flexible in
         // verification, for compatibility. Only small public exponents are
@@ -21,6 +21,7 @@ use crate::{
 };
 use alloc::boxed::Box;
 use core::num::NonZeroU64;
+use subtle::ConstantTimeEq;
 
 /// An RSA Public Key.
 #[derive(Clone)]
@@ -63,7 +64,8 @@ impl PublicKey {
         let serialized = der_writer::write_all(der::Tag::Sequence, &|output| {
             der_writer::write_positive_integer(output, &n_bytes);
             der_writer::write_positive_integer(output, &e_bytes);
-        });
+        })
+        .map_err(|_| error::KeyRejected::out_of_memory())?;
 
         Ok(Self { inner, serialized })
     }
@@ -79,6 +81,24 @@ impl PublicKey {
     pub(super) fn inner(&self) -> &Inner {
         &self.inner
     }
+
+    /// The big-endian encodings of the public modulus and the public exponent,
+    /// without leading zeros, borrowed from the serialized key.
+    pub(super) fn n_and_e_be_bytes(&self) -> (&[u8], &[u8]) {
+        untrusted::Input::from(&self.serialized)
+            .read_all(error::Unspecified, |input| {
+                der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
+                    let n = der::positive_integer(input)?;
+                    let e = der::positive_integer(input)?;
+                    Ok((
+                        n.big_endian_without_leading_zero(),
+                        e.big_endian_without_leading_zero(),
+                    ))
+                })
+            })
+            // `serialized` was encoded by `from_modulus_and_exponent`.
+            .unwrap()
+    }
 }
 
 /// `PublicKey` but without any superfluous allocations, optimized for one-shot
@@ -121,6 +141,15 @@ impl Inner {
         Ok(Self { n, e })
     }
 
+    pub(super) fn from_precomputed(
+        n: PublicModulus,
+        e: u64,
+        e_min_value: PublicExponent,
+    ) -> Result<Self, error::KeyRejected> {
+        let e = PublicExponent::from_u64(e, e_min_value)?;
+        Ok(Self { n, e })
+    }
+
     /// The public modulus.
     #[inline]
     pub(super) fn n(&self) -> &PublicModulus {
@@ -170,7 +199,9 @@ impl Inner {
 
     /// Calculates base**e (mod n).
     ///
-    /// This is constant-time with respect to `base` only.
+    /// This is constant-time with respect to `base` only, unless the
+    /// `ct-everything` feature is enabled, in which case it is also
+    /// constant-time with respect to `e`.
     pub(super) fn exponentiate_elem(&self, base: &bigint::Elem<N>) -> bigint::Elem<N> {
         // The exponent was already checked to be at least 3.
         let exponent_without_low_bit = NonZeroU64::try_from(self.e.value().get() & !1).unwrap();
@@ -186,13 +217,34 @@ impl Inner {
         // 65537 (0b10000000000000001) or 3 (0b11), both of which have a Hamming
         // weight of 2. The maximum bit length and maximum Hamming weight of the
         // exponent is bounded by the value of `PublicExponent::MAX`.
+        //
+        // For those two exponents square-and-multiply is already an optimal
+        // addition chain: any chain for 65537 needs at least 17 steps and
+        // any chain for 3 needs at least 2, which is what we do (16 or 1
+        // squarings, plus the final multiplication below, which also converts
+        // the result out of the Montgomery domain). So there's nothing to gain
+        // from special-casing them.
+        #[cfg(not(feature = "ct-everything"))]
         let acc = bigint::elem_exp_vartime(base_r, exponent_without_low_bit, n);
+        #[cfg(feature = "ct-everything")]
+        let acc = bigint::elem_exp_public_consttime(base_r, exponent_without_low_bit, n);
 
         // Now do the multiplication for the low bit and convert out of the Montgomery domain.
         bigint::elem_mul(base, acc, n)
     }
 }
 
+/// Public keys are equal if they have the same modulus and exponent. The
+/// moduli are compared in constant time.
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        let n_equal = self.inner.n().value().ct_eq(other.inner.n().value());
+        bool::from(n_equal) & (self.inner.e().value() == other.inner.e().value())
+    }
+}
+
+impl Eq for PublicKey {}
+
 // XXX: Refactor `signature::KeyPair` to get rid of this.
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
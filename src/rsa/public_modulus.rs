@@ -1,4 +1,4 @@
-use crate::{arithmetic::bigint, bits, cpu, error, rsa::N};
+use crate::{arithmetic::bigint, bits, cpu, error, limb::Limb, rsa::N};
 use core::ops::RangeInclusive;
 
 /// The modulus (n) of an RSA public key.
@@ -23,6 +23,29 @@ impl PublicModulus {
         // See `PublicKey::from_modulus_and_exponent` for background on the step
         // numbering.
 
+        // Step 3 / Step c for `n` (out of order).
+        let value = bigint::OwnedModulusWithOne::from_be_bytes(n, cpu_features)?;
+        Self::from_value(value, allowed_bit_lengths)
+    }
+
+    /// Reconstructs a modulus previously decomposed with `value()`, without
+    /// recomputing the Montgomery constants. See
+    /// `bigint::OwnedModulusWithOne::from_precomputed`.
+    pub(super) fn from_precomputed(
+        n: &[Limb],
+        n0: u64,
+        rr: &[Limb],
+        allowed_bit_lengths: RangeInclusive<bits::BitLength>,
+        cpu_features: cpu::Features,
+    ) -> Result<Self, error::KeyRejected> {
+        let value = bigint::OwnedModulusWithOne::from_precomputed(n, n0, rr, cpu_features)?;
+        Self::from_value(value, allowed_bit_lengths)
+    }
+
+    fn from_value(
+        value: bigint::OwnedModulusWithOne<N>,
+        allowed_bit_lengths: RangeInclusive<bits::BitLength>,
+    ) -> Result<Self, error::KeyRejected> {
         let min_bits = *allowed_bit_lengths.start();
         let max_bits = *allowed_bit_lengths.end();
 
@@ -31,8 +54,6 @@ impl PublicModulus {
         // bytes) for very small keys.
         const MIN_BITS: bits::BitLength = bits::BitLength::from_usize_bits(1024);
 
-        // Step 3 / Step c for `n` (out of order).
-        let value = bigint::OwnedModulusWithOne::from_be_bytes(n, cpu_features)?;
         let bits = value.len_bits();
 
         // Step 1 / Step a. XXX: SP800-56Br1 and SP800-89 require the length of
@@ -17,7 +17,11 @@
 use super::{
     parse_public_key, public_key, PublicExponent, RsaParameters, PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN,
 };
-use crate::{bits, cpu, digest, error, sealed, signature};
+use crate::{
+    bits, cpu, digest,
+    error::{self, VerifyFailure},
+    sealed, signature,
+};
 
 impl signature::VerificationAlgorithm for RsaParameters {
     fn verify(
@@ -26,15 +30,23 @@ impl signature::VerificationAlgorithm for RsaParameters {
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified> {
-        let (n, e) = parse_public_key(public_key)?;
-        verify_rsa_(
-            self,
-            (
-                n.big_endian_without_leading_zero_as_input(),
-                e.big_endian_without_leading_zero_as_input(),
-            ),
-            msg,
-            signature,
+        verify_rsa_unparsed(self, public_key, msg, signature).map_err(error::Unspecified::from)
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), VerifyFailure> {
+        verify_rsa_unparsed(self, public_key, msg, signature)
+    }
+
+    fn signature_len_hint(&self) -> signature::SignatureLen {
+        signature::SignatureLen::Range(
+            self.min_bits.as_usize_bytes_rounded_up(),
+            self.max_bits.as_usize_bytes_rounded_up(),
         )
     }
 }
@@ -42,7 +54,7 @@ impl signature::VerificationAlgorithm for RsaParameters {
 impl sealed::Sealed for RsaParameters {}
 
 macro_rules! rsa_params {
-    ( $VERIFY_ALGORITHM:ident, $min_bits:expr, $PADDING_ALGORITHM:expr,
+    ( $VERIFY_ALGORITHM:ident, $min_bits:expr, $max_bits:expr, $PADDING_ALGORITHM:expr,
       $doc_str:expr ) => {
         #[doc=$doc_str]
         ///
@@ -50,6 +62,7 @@ macro_rules! rsa_params {
         pub static $VERIFY_ALGORITHM: RsaParameters = RsaParameters {
             padding_alg: $PADDING_ALGORITHM,
             min_bits: bits::BitLength::from_usize_bits($min_bits),
+            max_bits: bits::BitLength::from_usize_bits($max_bits),
         };
     };
 }
@@ -57,6 +70,7 @@ macro_rules! rsa_params {
 rsa_params!(
     RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
     1024,
+    8192,
     &super::padding::RSA_PKCS1_SHA1_FOR_LEGACY_USE_ONLY,
     "Verification of signatures using RSA keys of 1024-8192 bits,
              PKCS#1.5 padding, and SHA-1.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -65,6 +79,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
     2048,
+    8192,
     &super::padding::RSA_PKCS1_SHA1_FOR_LEGACY_USE_ONLY,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PKCS#1.5 padding, and SHA-1.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -73,6 +88,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
     1024,
+    8192,
     &super::padding::RSA_PKCS1_SHA256,
     "Verification of signatures using RSA keys of 1024-8192 bits,
              PKCS#1.5 padding, and SHA-256.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -81,6 +97,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_2048_8192_SHA256,
     2048,
+    8192,
     &super::padding::RSA_PKCS1_SHA256,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PKCS#1.5 padding, and SHA-256.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -89,6 +106,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_2048_8192_SHA384,
     2048,
+    8192,
     &super::padding::RSA_PKCS1_SHA384,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PKCS#1.5 padding, and SHA-384.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -97,6 +115,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_2048_8192_SHA512,
     2048,
+    8192,
     &super::padding::RSA_PKCS1_SHA512,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PKCS#1.5 padding, and SHA-512.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -105,6 +124,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY,
     1024,
+    8192,
     &super::padding::RSA_PKCS1_SHA512,
     "Verification of signatures using RSA keys of 1024-8192 bits,
              PKCS#1.5 padding, and SHA-512.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -113,6 +133,7 @@ rsa_params!(
 rsa_params!(
     RSA_PKCS1_3072_8192_SHA384,
     3072,
+    8192,
     &super::padding::RSA_PKCS1_SHA384,
     "Verification of signatures using RSA keys of 3072-8192 bits,
              PKCS#1.5 padding, and SHA-384.\n\nSee \"`RSA_PKCS1_*` Details\" in
@@ -122,6 +143,7 @@ rsa_params!(
 rsa_params!(
     RSA_PSS_2048_8192_SHA256,
     2048,
+    8192,
     &super::padding::RSA_PSS_SHA256,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PSS padding, and SHA-256.\n\nSee \"`RSA_PSS_*` Details\" in
@@ -130,6 +152,7 @@ rsa_params!(
 rsa_params!(
     RSA_PSS_2048_8192_SHA384,
     2048,
+    8192,
     &super::padding::RSA_PSS_SHA384,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PSS padding, and SHA-384.\n\nSee \"`RSA_PSS_*` Details\" in
@@ -138,12 +161,87 @@ rsa_params!(
 rsa_params!(
     RSA_PSS_2048_8192_SHA512,
     2048,
+    8192,
     &super::padding::RSA_PSS_SHA512,
     "Verification of signatures using RSA keys of 2048-8192 bits,
              PSS padding, and SHA-512.\n\nSee \"`RSA_PSS_*` Details\" in
              `ring::signature`'s module-level documentation for more details."
 );
 
+rsa_params!(
+    RSA_PKCS1_2048_16384_SHA256,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PKCS1_SHA256,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PKCS#1.5 padding, and SHA-256.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PKCS1_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+rsa_params!(
+    RSA_PKCS1_2048_16384_SHA384,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PKCS1_SHA384,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PKCS#1.5 padding, and SHA-384.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PKCS1_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+rsa_params!(
+    RSA_PKCS1_2048_16384_SHA512,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PKCS1_SHA512,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PKCS#1.5 padding, and SHA-512.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PKCS1_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+
+rsa_params!(
+    RSA_PSS_2048_16384_SHA256,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PSS_SHA256,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PSS padding, and SHA-256.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PSS_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+rsa_params!(
+    RSA_PSS_2048_16384_SHA384,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PSS_SHA384,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PSS padding, and SHA-384.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PSS_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+rsa_params!(
+    RSA_PSS_2048_16384_SHA512,
+    2048,
+    super::MAX_RSA_BITS,
+    &super::padding::RSA_PSS_SHA512,
+    "Verification of signatures using RSA keys of 2048-16384 bits,
+             PSS padding, and SHA-512.\n\nKeys larger than
+             [`MAX_RSA_BITS`](crate::rsa::MAX_RSA_BITS) bits are rejected, so
+             keys larger than 8192 bits are only accepted if *ring* was built
+             with a larger `$RING_MAX_RSA_BITS`.\n\nSee \"`RSA_PSS_*` Details\" in
+             `ring::signature`'s module-level documentation for more details."
+);
+
 pub use super::PublicKeyComponents as RsaPublicKeyComponents;
 
 impl<B> super::PublicKeyComponents<B>
@@ -185,7 +283,31 @@ where
             untrusted::Input::from(message),
             untrusted::Input::from(signature),
         )
+        .map_err(error::Unspecified::from)
+    }
+}
+
+fn verify_rsa_unparsed(
+    params: &RsaParameters,
+    public_key: untrusted::Input,
+    msg: untrusted::Input,
+    signature: untrusted::Input,
+) -> Result<(), VerifyFailure> {
+    use signature::VerificationAlgorithm;
+    if !params.signature_len_hint().contains(signature.len()) {
+        return Err(VerifyFailure::MalformedSignature);
     }
+    let (n, e) =
+        parse_public_key(public_key).map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
+    verify_rsa_(
+        params,
+        (
+            n.big_endian_without_leading_zero_as_input(),
+            e.big_endian_without_leading_zero_as_input(),
+        ),
+        msg,
+        signature,
+    )
 }
 
 pub(crate) fn verify_rsa_(
@@ -193,9 +315,11 @@ pub(crate) fn verify_rsa_(
     (n, e): (untrusted::Input, untrusted::Input),
     msg: untrusted::Input,
     signature: untrusted::Input,
-) -> Result<(), error::Unspecified> {
-    let max_bits: bits::BitLength =
-        bits::BitLength::from_usize_bytes(PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN)?;
+) -> Result<(), VerifyFailure> {
+    use signature::VerificationAlgorithm;
+    if !params.signature_len_hint().contains(signature.len()) {
+        return Err(VerifyFailure::MalformedSignature);
+    }
 
     // XXX: FIPS 186-4 seems to indicate that the minimum
     // exponent value is 2**16 + 1, but it isn't clear if this is just for
@@ -205,18 +329,50 @@ pub(crate) fn verify_rsa_(
         n,
         e,
         params.min_bits,
-        max_bits,
+        params.max_bits,
         PublicExponent::_3,
         cpu::features(),
-    )?;
+    )
+    .map_err(|_| VerifyFailure::MalformedKey)?;
+
+    verify_rsa_with_key(params, &key, msg, signature)
+}
+
+/// Verifies `signature` using an already-parsed public key.
+///
+/// `key` must have been constructed with a minimum public exponent of 3
+/// and a maximum modulus length no larger than `PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN`;
+/// the modulus length is checked against `params` here.
+pub(super) fn verify_rsa_with_key(
+    params: &RsaParameters,
+    key: &public_key::Inner,
+    msg: untrusted::Input,
+    signature: untrusted::Input,
+) -> Result<(), VerifyFailure> {
+    let n_bits_rounded_up =
+        bits::BitLength::from_usize_bytes(key.n().len_bits().as_usize_bytes_rounded_up())
+            .map_err(|error::Unspecified| VerifyFailure::MalformedKey)?;
+    if n_bits_rounded_up < params.min_bits || key.n().len_bits() > params.max_bits {
+        return Err(VerifyFailure::MalformedKey);
+    }
+
+    // The signature must be exactly as long as the modulus.
+    if signature.len() != n_bits_rounded_up.as_usize_bytes_rounded_up() {
+        return Err(VerifyFailure::MalformedSignature);
+    }
 
-    // RFC 8017 Section 5.2.2: RSAVP1.
+    // RFC 8017 Section 5.2.2: RSAVP1. Given the length check above, this only
+    // fails if the signature is zero or not less than the modulus.
     let mut decoded = [0u8; PUBLIC_KEY_PUBLIC_MODULUS_MAX_LEN];
-    let decoded = key.exponentiate(signature, &mut decoded)?;
+    let decoded = key
+        .exponentiate(signature, &mut decoded)
+        .map_err(|error::Unspecified| VerifyFailure::OutOfRangeComponent)?;
 
     // Verify the padded message is correct.
     let m_hash = digest::digest(params.padding_alg.digest_alg(), msg.as_slice_less_safe());
-    untrusted::Input::from(decoded).read_all(error::Unspecified, |m| {
-        params.padding_alg.verify(m_hash, m, key.n().len_bits())
-    })
+    untrusted::Input::from(decoded)
+        .read_all(error::Unspecified, |m| {
+            params.padding_alg.verify(m_hash, m, key.n().len_bits())
+        })
+        .map_err(|error::Unspecified| VerifyFailure::Mismatch)
 }
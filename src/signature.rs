@@ -41,6 +41,12 @@
 //! [RFC 3279 Section 2.2.3]. This is the form of ECDSA signature used in
 //! X.509-related structures and in TLS's `ServerKeyExchange` messages.
 //!
+//! Only DER is accepted; every other BER encoding of the same *r* and *s* is
+//! rejected. This includes long-form or indefinite lengths where the short
+//! form would do, integers with unnecessary leading zero bytes, negative
+//! integers, and data after the `Ecdsa-Sig-Value`. So a signature has exactly
+//! one accepted encoding of a given (*r*, *s*).
+//!
 //! The public key is encoding in uncompressed form using the
 //! Octet-String-to-Elliptic-Curve-Point algorithm in
 //! [SEC 1: Elliptic Curve Cryptography, Version 2.0].
@@ -71,6 +77,17 @@
 //! in the NSA guide, ECC Partial Public-Key Validation is equivalent to ECC
 //! Full Public-Key Validation for prime-order curves like this one.
 //!
+//! ## `ECDSA_*_LOW_S` Details: Low-S Variants
+//!
+//! If (*r*, *s*) is a valid signature then so is (*r*, *n* - *s*). The
+//! `ECDSA_*_LOW_S` algorithms are like the algorithms without the suffix,
+//! except they also reject signatures with *s* > floor(*n*/2), so that a
+//! third party can't produce a second valid signature from a given one.
+//! Together with the DER-only parsing of the `ECDSA_*_ASN1` algorithms, this
+//! means each signature the signer produces has exactly one accepted
+//! encoding. `ecdsa_normalize_s()` converts a fixed-length signature into the
+//! form these algorithms accept.
+//!
 //! ## `RSA_PKCS1_*` Details: RSA PKCS#1 1.5 Signatures
 //!
 //! The signature is an RSASSA-PKCS1-v1_5 signature as described in
@@ -255,26 +272,38 @@
 //! # }
 //! ```
 
-use crate::{cpu, debug, ec, error, sealed};
+use crate::{cpu, debug, ec, error, policy, sealed};
+
+pub(crate) mod algorithm_identifier;
+mod external;
+mod multi;
+mod scheme;
+#[cfg(feature = "std")]
+mod verification_cache;
 
 pub use crate::ec::{
     curve25519::ed25519::{
-        signing::Ed25519KeyPair,
-        verification::{EdDSAParameters, ED25519},
+        signing::{ed25519_keygen_sign, Ed25519KeyPair},
+        verification::{ed25519_verify, EdDSAParameters, ED25519},
         ED25519_PUBLIC_KEY_LEN,
     },
     suite_b::ecdsa::{
+        low_s::{ecdsa_is_low_s, ecdsa_normalize_s},
+        parsed_signature::{EcdsaCurve, EcdsaSignature, EcdsaSignatureError, EcdsaSignatureFormat},
         signing::{
-            EcdsaKeyPair, EcdsaSigningAlgorithm, ECDSA_P256_SHA256_ASN1_SIGNING,
-            ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_ASN1_SIGNING,
-            ECDSA_P384_SHA384_FIXED_SIGNING,
+            EcdsaKeyPair, EcdsaPublicKeyInclusion, EcdsaSigningAlgorithm,
+            ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P256_SHA256_FIXED_SIGNING,
+            ECDSA_P384_SHA384_ASN1_SIGNING, ECDSA_P384_SHA384_FIXED_SIGNING,
         },
         verification::{
-            EcdsaVerificationAlgorithm, ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_FIXED,
-            ECDSA_P256_SHA384_ASN1, ECDSA_P384_SHA256_ASN1, ECDSA_P384_SHA384_ASN1,
-            ECDSA_P384_SHA384_FIXED,
+            ecdsa_p256_fixed_verify, EcdsaVerificationAlgorithm, ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
+            ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_LOW_S, ECDSA_P256_SHA256_FIXED,
+            ECDSA_P256_SHA256_FIXED_LOW_S, ECDSA_P256_SHA384_ASN1, ECDSA_P384_SHA256_ASN1,
+            ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_ASN1_LOW_S, ECDSA_P384_SHA384_FIXED,
+            ECDSA_P384_SHA384_FIXED_LOW_S,
         },
     },
+    suite_b::sm2::{Sm2VerificationAlgorithm, SM2_DEFAULT_USER_ID, SM2_SM3_ASN1},
 };
 
 #[cfg(feature = "alloc")]
@@ -286,18 +315,34 @@ pub use crate::rsa::{
     verification::{
         RsaPublicKeyComponents, RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
         RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
-        RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY,
+        RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY, RSA_PKCS1_2048_16384_SHA256,
+        RSA_PKCS1_2048_16384_SHA384, RSA_PKCS1_2048_16384_SHA512,
         RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY, RSA_PKCS1_2048_8192_SHA256,
         RSA_PKCS1_2048_8192_SHA384, RSA_PKCS1_2048_8192_SHA512, RSA_PKCS1_3072_8192_SHA384,
+        RSA_PSS_2048_16384_SHA256, RSA_PSS_2048_16384_SHA384, RSA_PSS_2048_16384_SHA512,
         RSA_PSS_2048_8192_SHA256, RSA_PSS_2048_8192_SHA384, RSA_PSS_2048_8192_SHA512,
     },
     RsaParameters,
 };
 
+pub use self::{
+    algorithm_identifier::{AlgorithmIdentifier, AlgorithmIdentifierError},
+    external::{ExternalAlgorithm, ExternalVerificationAlgorithm},
+    multi::{verify_all, verify_any, VerificationEntry, VerifyAllError},
+    scheme::SignatureScheme,
+};
+
+#[cfg(feature = "std")]
+pub use self::verification_cache::VerificationCache;
+
 /// An RSA key pair, used for signing.
 #[cfg(feature = "alloc")]
 pub type RsaKeyPair = crate::rsa::KeyPair;
 
+/// The size of an RSA key pair to generate with [`RsaKeyPair::generate()`].
+#[cfg(feature = "alloc")]
+pub type RsaKeySize = crate::rsa::KeySize;
+
 /// A public key signature returned from a signing operation.
 #[derive(Clone, Copy)]
 pub struct Signature {
@@ -335,6 +380,17 @@ pub trait KeyPair: core::fmt::Debug + Send + Sized + Sync {
     fn public_key(&self) -> &Self::PublicKey;
 }
 
+// Copies `public_key` into the start of `out`, for the `public_key_into`
+// methods of the key pair types.
+pub(crate) fn public_key_into(
+    public_key: &[u8],
+    out: &mut [u8],
+) -> Result<usize, error::Unspecified> {
+    let out = out.get_mut(..public_key.len()).ok_or(error::Unspecified)?;
+    out.copy_from_slice(public_key);
+    Ok(public_key.len())
+}
+
 /// The longest signature is an ASN.1 P-384 signature where *r* and *s* are of
 /// maximum length with the leading high bit set on each. Then each component
 /// will have a tag, a one-byte length, and a one-byte “I'm not negative”
@@ -343,15 +399,64 @@ pub(crate) const MAX_LEN: usize = 1/*tag:SEQUENCE*/ + 2/*len*/ +
     (2 * (1/*tag:INTEGER*/ + 1/*len*/ + 1/*zero*/ + ec::SCALAR_MAX_BYTES));
 
 /// A signature verification algorithm.
+///
+/// This trait is sealed; to use an algorithm implemented outside of *ring*,
+/// implement [`ExternalVerificationAlgorithm`] and wrap it in an
+/// [`ExternalAlgorithm`].
 pub trait VerificationAlgorithm: core::fmt::Debug + Sync + sealed::Sealed {
     /// Verify the signature `signature` of message `msg` with the public key
     /// `public_key`.
+    ///
+    /// Signatures with a length outside `self.signature_len_hint()` are
+    /// rejected before any other work is done.
     fn verify(
         &self,
         public_key: untrusted::Input,
         msg: untrusted::Input,
         signature: untrusted::Input,
     ) -> Result<(), error::Unspecified>;
+
+    /// Like [`Self::verify()`], but reports why the signature was rejected.
+    ///
+    /// This succeeds exactly when `verify()` does. The reason is meant for
+    /// metrics and logs, not for deciding whether to accept a signature; see
+    /// [`error::VerifyFailure`] for how it is determined.
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::VerifyFailure>;
+
+    /// The lengths of the signatures this algorithm can verify, so callers can
+    /// cheaply reject signatures that can't possibly be valid.
+    ///
+    /// For RSA the exact length is the length of the key's modulus, so the
+    /// hint is the range of supported modulus lengths.
+    fn signature_len_hint(&self) -> SignatureLen;
+}
+
+/// The possible lengths, in bytes, of a signature; see
+/// [`VerificationAlgorithm::signature_len_hint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureLen {
+    /// Exactly this many bytes.
+    Exact(usize),
+
+    /// From `.0` to `.1` bytes, inclusive.
+    Range(usize, usize),
+}
+
+impl SignatureLen {
+    /// Returns `true` if a signature may be `len` bytes long.
+    #[inline]
+    pub fn contains(self, len: usize) -> bool {
+        match self {
+            Self::Exact(n) => len == n,
+            Self::Range(min, max) => (min..=max).contains(&len),
+        }
+    }
 }
 
 /// An unparsed, possibly malformed, public key for signature verification.
@@ -395,15 +500,56 @@ impl<B> UnparsedPublicKey<B> {
     /// `message` using it.
     ///
     /// See the [crate::signature] module-level documentation for examples.
+    ///
+    /// Fails if a default [`policy::Policy`] is installed that doesn't allow
+    /// the algorithm.
     pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), error::Unspecified>
     where
         B: AsRef<[u8]>,
     {
         let _ = cpu::features();
+        policy::check_default_verification(self.algorithm)?;
         self.algorithm.verify(
             untrusted::Input::from(self.bytes.as_ref()),
             untrusted::Input::from(message),
             untrusted::Input::from(signature),
         )
     }
+
+    /// Like [`Self::verify()`], except the result is looked up in, or
+    /// recorded in, `cache`.
+    #[cfg(feature = "std")]
+    pub fn verify_cached(
+        &self,
+        cache: &VerificationCache,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified>
+    where
+        B: AsRef<[u8]>,
+    {
+        let _ = cpu::features();
+        policy::check_default_verification(self.algorithm)?;
+        cache.verify(self.algorithm, self.bytes.as_ref(), message, signature)
+    }
+}
+
+inventory! { Signature, Always;
+    ED25519 => 256..=256,
+    ECDSA_P256_SHA256_ASN1_SIGNING => 256..=256,
+    ECDSA_P256_SHA256_FIXED_SIGNING => 256..=256,
+    ECDSA_P384_SHA384_ASN1_SIGNING => 384..=384,
+    ECDSA_P384_SHA384_FIXED_SIGNING => 384..=384,
+    ECDSA_P256_SHA256_ASN1 => 256..=256,
+    ECDSA_P256_SHA256_FIXED => 256..=256,
+    ECDSA_P256_SHA384_ASN1 => 256..=256,
+    ECDSA_P384_SHA256_ASN1 => 384..=384,
+    ECDSA_P384_SHA384_ASN1 => 384..=384,
+    ECDSA_P384_SHA384_FIXED => 384..=384,
+    ECDSA_BRAINPOOLP384R1_SHA384_ASN1 => 384..=384,
+    ECDSA_P256_SHA256_ASN1_LOW_S => 256..=256,
+    ECDSA_P256_SHA256_FIXED_LOW_S => 256..=256,
+    ECDSA_P384_SHA384_ASN1_LOW_S => 384..=384,
+    ECDSA_P384_SHA384_FIXED_LOW_S => 384..=384,
+    SM2_SM3_ASN1 => 256..=256,
 }
@@ -0,0 +1,441 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::{EcdsaSigningAlgorithm, VerificationAlgorithm};
+use crate::{digest, error, io::der};
+
+/// An X.509 `AlgorithmIdentifier` ([RFC 5280 Section 4.1.1.2]) of a public
+/// key algorithm or signature algorithm that *ring* supports.
+///
+/// The public key algorithms are those of a `SubjectPublicKeyInfo`; they are
+/// combined with a signature algorithm by [`Self::to_verification_params`].
+/// Ed25519 is both.
+///
+/// The `RsaPss*` variants are `id-RSASSA-PSS` with `RSASSA-PSS-params`
+/// ([RFC 4055 Section 3.1]) that use the same hash function for the message
+/// digest and MGF1, and a salt as long as the digest, which are the only
+/// parameters *ring* supports.
+///
+/// [RFC 5280 Section 4.1.1.2]: https://tools.ietf.org/html/rfc5280#section-4.1.1.2
+/// [RFC 4055 Section 3.1]: https://tools.ietf.org/html/rfc4055#section-3.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AlgorithmIdentifier {
+    /// `id-ecPublicKey` with the named curve `secp256r1` ([RFC 5480]).
+    ///
+    /// [RFC 5480]: https://tools.ietf.org/html/rfc5480
+    EcPublicKeyP256,
+
+    /// `id-ecPublicKey` with the named curve `secp384r1` ([RFC 5480]).
+    ///
+    /// [RFC 5480]: https://tools.ietf.org/html/rfc5480
+    EcPublicKeyP384,
+
+    /// `rsaEncryption` ([RFC 3279]).
+    ///
+    /// [RFC 3279]: https://tools.ietf.org/html/rfc3279
+    RsaEncryption,
+
+    /// `id-Ed25519` ([RFC 8410]).
+    ///
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    Ed25519,
+
+    /// `ecdsa-with-SHA256` ([RFC 5758]).
+    ///
+    /// [RFC 5758]: https://tools.ietf.org/html/rfc5758
+    EcdsaSha256,
+
+    /// `ecdsa-with-SHA384` ([RFC 5758]).
+    ///
+    /// [RFC 5758]: https://tools.ietf.org/html/rfc5758
+    EcdsaSha384,
+
+    /// `sha1WithRSAEncryption` ([RFC 3279]).
+    ///
+    /// [RFC 3279]: https://tools.ietf.org/html/rfc3279
+    RsaPkcs1Sha1ForLegacyUseOnly,
+
+    /// `sha256WithRSAEncryption` ([RFC 4055]).
+    ///
+    /// [RFC 4055]: https://tools.ietf.org/html/rfc4055
+    RsaPkcs1Sha256,
+
+    /// `sha384WithRSAEncryption` ([RFC 4055]).
+    ///
+    /// [RFC 4055]: https://tools.ietf.org/html/rfc4055
+    RsaPkcs1Sha384,
+
+    /// `sha512WithRSAEncryption` ([RFC 4055]).
+    ///
+    /// [RFC 4055]: https://tools.ietf.org/html/rfc4055
+    RsaPkcs1Sha512,
+
+    /// `id-RSASSA-PSS` with SHA-256, MGF1 with SHA-256, and a 32-byte salt.
+    RsaPssSha256,
+
+    /// `id-RSASSA-PSS` with SHA-384, MGF1 with SHA-384, and a 48-byte salt.
+    RsaPssSha384,
+
+    /// `id-RSASSA-PSS` with SHA-512, MGF1 with SHA-512, and a 64-byte salt.
+    RsaPssSha512,
+}
+
+/// The error returned when an [`AlgorithmIdentifier`] can't be parsed or
+/// used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AlgorithmIdentifierError {
+    /// The input isn't a DER-encoded `AlgorithmIdentifier`, or the parameters
+    /// aren't valid for the algorithm.
+    Malformed,
+
+    /// The algorithm, or the curve of an `id-ecPublicKey`, isn't supported or
+    /// isn't available in this configuration.
+    UnsupportedAlgorithm,
+
+    /// The `RSASSA-PSS-params` use different hash functions for the message
+    /// digest and MGF1.
+    InconsistentPssParameters,
+
+    /// The `RSASSA-PSS-params` are consistent but not supported: e.g. SHA-1,
+    /// a mask generation function other than MGF1, or a salt that isn't as
+    /// long as the digest.
+    UnsupportedPssParameters,
+
+    /// The signature algorithm can't be used with the public key algorithm,
+    /// or one of them is the wrong kind of algorithm.
+    IncompatiblePublicKey,
+}
+
+impl core::fmt::Display for AlgorithmIdentifierError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Malformed => "malformed AlgorithmIdentifier",
+            Self::UnsupportedAlgorithm => "unsupported algorithm",
+            Self::InconsistentPssParameters => "inconsistent RSASSA-PSS parameters",
+            Self::UnsupportedPssParameters => "unsupported RSASSA-PSS parameters",
+            Self::IncompatiblePublicKey => "signature algorithm incompatible with public key",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AlgorithmIdentifierError {}
+
+type Error = AlgorithmIdentifierError;
+
+const ALL: [AlgorithmIdentifier; 13] = [
+    AlgorithmIdentifier::EcPublicKeyP256,
+    AlgorithmIdentifier::EcPublicKeyP384,
+    AlgorithmIdentifier::RsaEncryption,
+    AlgorithmIdentifier::Ed25519,
+    AlgorithmIdentifier::EcdsaSha256,
+    AlgorithmIdentifier::EcdsaSha384,
+    AlgorithmIdentifier::RsaPkcs1Sha1ForLegacyUseOnly,
+    AlgorithmIdentifier::RsaPkcs1Sha256,
+    AlgorithmIdentifier::RsaPkcs1Sha384,
+    AlgorithmIdentifier::RsaPkcs1Sha512,
+    AlgorithmIdentifier::RsaPssSha256,
+    AlgorithmIdentifier::RsaPssSha384,
+    AlgorithmIdentifier::RsaPssSha512,
+];
+
+// 1.2.840.10045.2.1
+const EC_PUBLIC_KEY_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+// 1.2.840.113549.1.1.10
+const RSASSA_PSS_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a];
+
+// 1.2.840.113549.1.1.8
+const MGF1_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x08];
+
+// 1.3.14.3.2.26
+const SHA1_OID: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x1a];
+
+// 2.16.840.1.101.3.4.2.{1, 2, 3}
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const SHA384_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+const SHA512_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+
+impl AlgorithmIdentifier {
+    /// Parses a DER-encoded `AlgorithmIdentifier`, including its outer
+    /// `SEQUENCE` tag and length.
+    ///
+    /// For `id-RSASSA-PSS`, equivalent encodings of the parameters are
+    /// accepted, e.g. with the hash function's `NULL` parameters omitted.
+    /// Otherwise the encoding must be exactly the one returned by
+    /// [`Self::der_value`].
+    pub fn from_der(input: &[u8]) -> Result<Self, AlgorithmIdentifierError> {
+        untrusted::Input::from(input).read_all(Error::Malformed, |input| {
+            der::nested(input, der::Tag::Sequence, Error::Malformed, |value| {
+                Self::from_der_value(value.read_bytes_to_end())
+            })
+        })
+    }
+
+    pub(crate) fn from_der_value(value: untrusted::Input) -> Result<Self, Error> {
+        if let Some(alg) = ALL
+            .iter()
+            .find(|alg| alg.der_value() == value.as_slice_less_safe())
+        {
+            return Ok(*alg);
+        }
+
+        value.read_all(Error::Malformed, |input| {
+            let oid = der::expect_tag_and_get_value(input, der::Tag::OID)
+                .map_err(|error::Unspecified| Error::Malformed)?;
+            let oid = oid.as_slice_less_safe();
+            if oid == RSASSA_PSS_OID {
+                return parse_pss_params(input);
+            }
+            if oid == EC_PUBLIC_KEY_OID {
+                // A curve we don't support.
+                let _ = der::expect_tag_and_get_value(input, der::Tag::OID)
+                    .map_err(|error::Unspecified| Error::Malformed)?;
+                return Err(Error::UnsupportedAlgorithm);
+            }
+            if ALL.iter().any(|alg| oid_of(alg.der_value()) == oid) {
+                // A supported algorithm with the wrong parameters.
+                return Err(Error::Malformed);
+            }
+            let _ = input.read_bytes_to_end();
+            Err(Error::UnsupportedAlgorithm)
+        })
+    }
+
+    /// The DER encoding of the contents of the `AlgorithmIdentifier`, i.e.
+    /// without the outer `SEQUENCE` tag and length.
+    ///
+    /// Every value is shorter than 128 bytes, so the complete encoding is the
+    /// value prefixed with `[0x30, len]`.
+    pub fn der_value(self) -> &'static [u8] {
+        match self {
+            Self::EcPublicKeyP256 => include_bytes!("../data/alg-ecdsa-p256.der"),
+            Self::EcPublicKeyP384 => include_bytes!("../data/alg-ecdsa-p384.der"),
+            Self::RsaEncryption => include_bytes!("../data/alg-rsa-encryption.der"),
+            Self::Ed25519 => include_bytes!("../data/alg-ed25519.der"),
+            Self::EcdsaSha256 => include_bytes!("../data/alg-ecdsa-sha256.der"),
+            Self::EcdsaSha384 => include_bytes!("../data/alg-ecdsa-sha384.der"),
+            Self::RsaPkcs1Sha1ForLegacyUseOnly => include_bytes!("../data/alg-rsa-pkcs1-sha1.der"),
+            Self::RsaPkcs1Sha256 => include_bytes!("../data/alg-rsa-pkcs1-sha256.der"),
+            Self::RsaPkcs1Sha384 => include_bytes!("../data/alg-rsa-pkcs1-sha384.der"),
+            Self::RsaPkcs1Sha512 => include_bytes!("../data/alg-rsa-pkcs1-sha512.der"),
+            Self::RsaPssSha256 => include_bytes!("../data/alg-rsa-pss-sha256.der"),
+            Self::RsaPssSha384 => include_bytes!("../data/alg-rsa-pss-sha384.der"),
+            Self::RsaPssSha512 => include_bytes!("../data/alg-rsa-pss-sha512.der"),
+        }
+    }
+
+    /// Returns the verification algorithm for signatures with this signature
+    /// algorithm by a key with the public key algorithm `public_key_alg`.
+    ///
+    /// The public key algorithm is needed because ECDSA signature algorithms
+    /// don't identify the curve. The RSA algorithms accept keys of 2048 to
+    /// 8192 bits and require the `alloc` feature.
+    pub fn to_verification_params(
+        self,
+        public_key_alg: Self,
+    ) -> Result<&'static dyn VerificationAlgorithm, AlgorithmIdentifierError> {
+        let alg: &'static dyn VerificationAlgorithm = match (self, public_key_alg) {
+            (Self::EcdsaSha256, Self::EcPublicKeyP256) => &super::ECDSA_P256_SHA256_ASN1,
+            (Self::EcdsaSha384, Self::EcPublicKeyP256) => &super::ECDSA_P256_SHA384_ASN1,
+            (Self::EcdsaSha256, Self::EcPublicKeyP384) => &super::ECDSA_P384_SHA256_ASN1,
+            (Self::EcdsaSha384, Self::EcPublicKeyP384) => &super::ECDSA_P384_SHA384_ASN1,
+            (Self::Ed25519, Self::Ed25519) => &super::ED25519,
+
+            #[cfg(feature = "alloc")]
+            (Self::RsaPkcs1Sha1ForLegacyUseOnly, Self::RsaEncryption) => {
+                &super::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY
+            }
+            #[cfg(feature = "alloc")]
+            (Self::RsaPkcs1Sha256, Self::RsaEncryption) => &super::RSA_PKCS1_2048_8192_SHA256,
+            #[cfg(feature = "alloc")]
+            (Self::RsaPkcs1Sha384, Self::RsaEncryption) => &super::RSA_PKCS1_2048_8192_SHA384,
+            #[cfg(feature = "alloc")]
+            (Self::RsaPkcs1Sha512, Self::RsaEncryption) => &super::RSA_PKCS1_2048_8192_SHA512,
+            #[cfg(feature = "alloc")]
+            (Self::RsaPssSha256, Self::RsaEncryption) => &super::RSA_PSS_2048_8192_SHA256,
+            #[cfg(feature = "alloc")]
+            (Self::RsaPssSha384, Self::RsaEncryption) => &super::RSA_PSS_2048_8192_SHA384,
+            #[cfg(feature = "alloc")]
+            (Self::RsaPssSha512, Self::RsaEncryption) => &super::RSA_PSS_2048_8192_SHA512,
+
+            #[cfg(not(feature = "alloc"))]
+            (
+                Self::RsaPkcs1Sha1ForLegacyUseOnly
+                | Self::RsaPkcs1Sha256
+                | Self::RsaPkcs1Sha384
+                | Self::RsaPkcs1Sha512
+                | Self::RsaPssSha256
+                | Self::RsaPssSha384
+                | Self::RsaPssSha512,
+                Self::RsaEncryption,
+            ) => {
+                return Err(Error::UnsupportedAlgorithm);
+            }
+
+            _ => {
+                return Err(Error::IncompatiblePublicKey);
+            }
+        };
+        Ok(alg)
+    }
+
+    /// Returns the signature algorithm of signatures produced with `alg`, or
+    /// `None` for the `*_FIXED_SIGNING` algorithms, whose signatures aren't
+    /// in the format X.509 uses.
+    pub fn from_ecdsa_signing_params(alg: &EcdsaSigningAlgorithm) -> Option<Self> {
+        alg.algorithm_identifier()
+    }
+
+    /// Returns the signature algorithm of signatures produced with
+    /// `encoding`.
+    #[cfg(feature = "alloc")]
+    pub fn from_rsa_signing_params(encoding: &dyn super::RsaEncoding) -> Self {
+        encoding.algorithm_identifier()
+    }
+}
+
+// The contents of the OID that `der_value` starts with.
+fn oid_of(der_value: &[u8]) -> &[u8] {
+    &der_value[2..][..usize::from(der_value[1])]
+}
+
+// RSASSA-PSS-params ::= SEQUENCE {
+//     hashAlgorithm      [0] HashAlgorithm DEFAULT sha1,
+//     maskGenAlgorithm   [1] MaskGenAlgorithm DEFAULT mgf1SHA1,
+//     saltLength         [2] INTEGER DEFAULT 20,
+//     trailerField       [3] TrailerField DEFAULT trailerFieldBC }
+fn parse_pss_params(input: &mut untrusted::Reader) -> Result<AlgorithmIdentifier, Error> {
+    if input.at_end() {
+        // An `id-RSASSA-PSS` public key that may be used with any parameters.
+        return Err(Error::UnsupportedPssParameters);
+    }
+    let (hash, mgf1_hash, salt_len, trailer) =
+        der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+            let hash = if input.peek(der::Tag::ContextSpecificConstructed0.into()) {
+                der::nested(
+                    input,
+                    der::Tag::ContextSpecificConstructed0,
+                    Error::Malformed,
+                    parse_hash_algorithm,
+                )?
+            } else {
+                Ok(&digest::SHA1_FOR_LEGACY_USE_ONLY)
+            };
+
+            let mgf1_hash = if input.peek(der::Tag::ContextSpecificConstructed1.into()) {
+                der::nested(
+                    input,
+                    der::Tag::ContextSpecificConstructed1,
+                    Error::Malformed,
+                    |input| {
+                        der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+                            let oid = der::expect_tag_and_get_value(input, der::Tag::OID)
+                                .map_err(|error::Unspecified| Error::Malformed)?;
+                            if oid.as_slice_less_safe() != MGF1_OID {
+                                let _ = input.read_bytes_to_end();
+                                return Ok(Err(Error::UnsupportedPssParameters));
+                            }
+                            parse_hash_algorithm(input)
+                        })
+                    },
+                )?
+            } else {
+                Ok(&digest::SHA1_FOR_LEGACY_USE_ONLY)
+            };
+
+            let salt_len = if input.peek(der::Tag::ContextSpecificConstructed2.into()) {
+                der::nested(
+                    input,
+                    der::Tag::ContextSpecificConstructed2,
+                    Error::Malformed,
+                    |input| {
+                        let salt_len = der::nonnegative_integer(input)
+                            .map_err(|error::Unspecified| Error::Malformed)?;
+                        // Salts longer than 255 bytes are never supported.
+                        Ok(match *salt_len.as_slice_less_safe() {
+                            [salt_len] => usize::from(salt_len),
+                            _ => usize::MAX,
+                        })
+                    },
+                )?
+            } else {
+                20
+            };
+
+            let trailer = if input.peek(der::Tag::ContextSpecificConstructed3.into()) {
+                der::nested(
+                    input,
+                    der::Tag::ContextSpecificConstructed3,
+                    Error::Malformed,
+                    |input| {
+                        der::small_nonnegative_integer(input)
+                            .map_err(|error::Unspecified| Error::Malformed)
+                    },
+                )?
+            } else {
+                1
+            };
+
+            Ok((hash, mgf1_hash, salt_len, trailer))
+        })?;
+
+    // The whole encoding is checked before the parameters are.
+    let (hash, mgf1_hash) = (hash?, mgf1_hash?);
+    if hash != mgf1_hash {
+        return Err(Error::InconsistentPssParameters);
+    }
+    if salt_len != hash.output_len() || trailer != 1 {
+        return Err(Error::UnsupportedPssParameters);
+    }
+    if hash == &digest::SHA256 {
+        Ok(AlgorithmIdentifier::RsaPssSha256)
+    } else if hash == &digest::SHA384 {
+        Ok(AlgorithmIdentifier::RsaPssSha384)
+    } else if hash == &digest::SHA512 {
+        Ok(AlgorithmIdentifier::RsaPssSha512)
+    } else {
+        Err(Error::UnsupportedPssParameters)
+    }
+}
+
+// Parses the contents of a `HashAlgorithm`. The outer result is for
+// encoding errors; the inner result is for unsupported hash functions.
+pub(crate) fn parse_hash_algorithm(
+    input: &mut untrusted::Reader,
+) -> Result<Result<&'static digest::Algorithm, Error>, Error> {
+    der::nested(input, der::Tag::Sequence, Error::Malformed, |input| {
+        let oid = der::expect_tag_and_get_value(input, der::Tag::OID)
+            .map_err(|error::Unspecified| Error::Malformed)?;
+        // The parameters are NULL or absent (RFC 4055 Section 2.1).
+        if !input.at_end() {
+            let _ = der::expect_tag_and_get_value(input, der::Tag::Null)
+                .ok()
+                .filter(|null| null.is_empty())
+                .ok_or(Error::Malformed)?;
+        }
+        Ok(match oid.as_slice_less_safe() {
+            SHA1_OID => Ok(&digest::SHA1_FOR_LEGACY_USE_ONLY),
+            SHA256_OID => Ok(&digest::SHA256),
+            SHA384_OID => Ok(&digest::SHA384),
+            SHA512_OID => Ok(&digest::SHA512),
+            _ => Err(Error::UnsupportedPssParameters),
+        })
+    })
+}
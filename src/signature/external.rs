@@ -0,0 +1,144 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{SignatureLen, VerificationAlgorithm};
+use crate::{error, sealed};
+
+/// A signature verification algorithm implemented outside of *ring*, e.g.
+/// ML-DSA.
+///
+/// [`VerificationAlgorithm`] is sealed so that *ring* can add methods to it
+/// without breaking anybody. Implement this trait instead and wrap the
+/// implementation in an [`ExternalAlgorithm`], which implements
+/// `VerificationAlgorithm`, so it can be used with [`super::UnparsedPublicKey`],
+/// [`super::verify_all`], and any other code that takes a
+/// `&dyn VerificationAlgorithm`.
+///
+/// *ring* guarantees the following about how `verify` is called, and won't
+/// change it in a semver-compatible release:
+///
+/// * `public_key`, `msg`, and `signature` are exactly the bytes the caller
+///   gave to *ring*, e.g. to `UnparsedPublicKey::new()` and
+///   `UnparsedPublicKey::verify()`. *ring* doesn't parse, hash, or otherwise
+///   transform any of them.
+/// * `signature.len()` is within `self.signature_len_hint()`; other
+///   signatures are rejected without calling `verify`.
+pub trait ExternalVerificationAlgorithm: core::fmt::Debug + Sync {
+    /// Verify the signature `signature` of message `msg` with the public key
+    /// `public_key`.
+    fn verify(
+        &self,
+        public_key: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified>;
+
+    /// The lengths of the signatures this algorithm can verify.
+    fn signature_len_hint(&self) -> SignatureLen;
+}
+
+/// Adapts an [`ExternalVerificationAlgorithm`] to [`VerificationAlgorithm`].
+///
+/// ```
+/// use ring::{error, signature};
+///
+/// #[derive(Debug)]
+/// struct AlwaysValid;
+///
+/// impl signature::ExternalVerificationAlgorithm for AlwaysValid {
+///     fn verify(&self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), error::Unspecified> {
+///         Ok(())
+///     }
+///
+///     fn signature_len_hint(&self) -> signature::SignatureLen {
+///         signature::SignatureLen::Exact(4)
+///     }
+/// }
+///
+/// static ALWAYS_VALID: signature::ExternalAlgorithm<AlwaysValid> =
+///     signature::ExternalAlgorithm::new(AlwaysValid);
+///
+/// let public_key = signature::UnparsedPublicKey::new(&ALWAYS_VALID, b"key");
+/// assert!(public_key.verify(b"message", b"sig!").is_ok());
+/// assert!(public_key.verify(b"message", b"sig").is_err());
+/// ```
+pub struct ExternalAlgorithm<A>(A);
+
+impl<A> ExternalAlgorithm<A> {
+    /// Wraps `algorithm`.
+    #[inline]
+    pub const fn new(algorithm: A) -> Self {
+        Self(algorithm)
+    }
+
+    /// The wrapped algorithm.
+    #[inline]
+    pub fn get(&self) -> &A {
+        &self.0
+    }
+}
+
+impl<A: ExternalVerificationAlgorithm> VerificationAlgorithm for ExternalAlgorithm<A> {
+    fn verify(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::Unspecified> {
+        if !self.0.signature_len_hint().contains(signature.len()) {
+            return Err(error::Unspecified);
+        }
+        self.0.verify(
+            public_key.as_slice_less_safe(),
+            msg.as_slice_less_safe(),
+            signature.as_slice_less_safe(),
+        )
+    }
+
+    /// Reports signatures outside `signature_len_hint()` as malformed and
+    /// every other failure as a mismatch, since an external algorithm's
+    /// `verify` doesn't say why it failed.
+    #[cfg(feature = "diagnostics")]
+    fn verify_diagnostic(
+        &self,
+        public_key: untrusted::Input,
+        msg: untrusted::Input,
+        signature: untrusted::Input,
+    ) -> Result<(), error::VerifyFailure> {
+        if !self.0.signature_len_hint().contains(signature.len()) {
+            return Err(error::VerifyFailure::MalformedSignature);
+        }
+        self.0
+            .verify(
+                public_key.as_slice_less_safe(),
+                msg.as_slice_less_safe(),
+                signature.as_slice_less_safe(),
+            )
+            .map_err(|error::Unspecified| error::VerifyFailure::Mismatch)
+    }
+
+    fn signature_len_hint(&self) -> SignatureLen {
+        self.0.signature_len_hint()
+    }
+}
+
+impl<A> sealed::Sealed for ExternalAlgorithm<A> {}
+
+impl<A: core::fmt::Debug> core::fmt::Debug for ExternalAlgorithm<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        // The "ExternalAlgorithm" wrapper keeps the output distinct from that
+        // of *ring*'s own algorithms.
+        f.debug_tuple("ExternalAlgorithm").field(&self.0).finish()
+    }
+}
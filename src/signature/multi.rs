@@ -0,0 +1,104 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use super::VerificationAlgorithm;
+use crate::{cpu, error};
+
+/// A signature to verify with [`verify_all`] or [`verify_any`]: the
+/// algorithm, the public key, and the signature.
+pub type VerificationEntry<'a> = (&'a dyn VerificationAlgorithm, &'a [u8], &'a [u8]);
+
+/// The error returned by [`verify_all`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyAllError {
+    index: Option<usize>,
+}
+
+impl VerifyAllError {
+    /// The index of the first entry that failed verification, or `None` if
+    /// there were no entries.
+    #[inline]
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+}
+
+impl core::fmt::Display for VerifyAllError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "signature {} is invalid", index),
+            None => f.write_str("no signatures"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyAllError {}
+
+impl From<VerifyAllError> for error::Unspecified {
+    fn from(_: VerifyAllError) -> Self {
+        Self
+    }
+}
+
+/// Verifies that every entry of `entries` is a valid signature of `message`.
+///
+/// The entries are verified in order, stopping at the first invalid one. An
+/// empty `entries` is an error, so that a bundle stripped of its signatures
+/// isn't accepted.
+///
+/// Each entry is verified independently. In particular, the same public key
+/// may appear in more than one entry; callers that require signatures from
+/// distinct signers must choose the keys accordingly.
+pub fn verify_all(message: &[u8], entries: &[VerificationEntry]) -> Result<(), VerifyAllError> {
+    if entries.is_empty() {
+        return Err(VerifyAllError { index: None });
+    }
+    let _ = cpu::features();
+    let message = untrusted::Input::from(message);
+    entries.iter().enumerate().try_for_each(|(index, entry)| {
+        verify_entry(message, entry)
+            .map_err(|error::Unspecified| VerifyAllError { index: Some(index) })
+    })
+}
+
+/// Verifies that at least one entry of `entries` is a valid signature of
+/// `message`, returning the index of the first valid one.
+///
+/// The entries are verified in order, stopping at the first valid one.
+pub fn verify_any(
+    message: &[u8],
+    entries: &[VerificationEntry],
+) -> Result<usize, error::Unspecified> {
+    let _ = cpu::features();
+    let message = untrusted::Input::from(message);
+    entries
+        .iter()
+        .position(|entry| verify_entry(message, entry).is_ok())
+        .ok_or(error::Unspecified)
+}
+
+fn verify_entry(
+    message: untrusted::Input,
+    (algorithm, public_key, signature): &VerificationEntry,
+) -> Result<(), error::Unspecified> {
+    algorithm.verify(
+        untrusted::Input::from(public_key),
+        message,
+        untrusted::Input::from(signature),
+    )
+}
@@ -0,0 +1,168 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{UnparsedPublicKey, VerificationAlgorithm};
+use crate::error;
+
+/// A TLS `SignatureScheme` that *ring* can verify.
+///
+/// The discriminants are the code points from the [IANA TLS SignatureScheme
+/// registry], as used in the `signature_algorithms` and
+/// `signature_algorithms_cert` extensions ([RFC 8446 Section 4.2.3]). Schemes
+/// in the registry that *ring* doesn't implement (ECDSA with P-521,
+/// Ed448, ECDSA with SHA-1, and the Brainpool and SM2 schemes) have no
+/// variant, so `from_u16` returns `None` for them, the same as for unassigned
+/// code points.
+///
+/// The public key passed to [`SignatureScheme::verify`] is in the format
+/// documented for the corresponding verification algorithm: a DER-encoded
+/// `RSAPublicKey` for the RSA schemes, an uncompressed point for the ECDSA
+/// schemes, and the 32-byte public key for Ed25519.
+///
+/// The RSA schemes are only available in `alloc` mode; without it,
+/// `verification_algorithm` returns `None` for them and `verify` fails.
+///
+/// [IANA TLS SignatureScheme registry]:
+///     https://www.iana.org/assignments/tls-parameters/tls-parameters.xhtml#tls-signaturescheme
+/// [RFC 8446 Section 4.2.3]: https://www.rfc-editor.org/rfc/rfc8446#section-4.2.3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u16)]
+pub enum SignatureScheme {
+    /// `rsa_pkcs1_sha1`. Only for use with legacy certificates.
+    Rsa2048Pkcs1Sha1ForLegacyUseOnly = 0x0201,
+
+    /// `rsa_pkcs1_sha256`.
+    Rsa2048Pkcs1Sha256 = 0x0401,
+
+    /// `ecdsa_secp256r1_sha256`.
+    Ecdsa256Sha256 = 0x0403,
+
+    /// `rsa_pkcs1_sha384`.
+    Rsa2048Pkcs1Sha384 = 0x0501,
+
+    /// `ecdsa_secp384r1_sha384`.
+    Ecdsa384Sha384 = 0x0503,
+
+    /// `rsa_pkcs1_sha512`.
+    Rsa2048Pkcs1Sha512 = 0x0601,
+
+    /// `rsa_pss_rsae_sha256`.
+    Rsa2048PssSha256 = 0x0804,
+
+    /// `rsa_pss_rsae_sha384`.
+    Rsa2048PssSha384 = 0x0805,
+
+    /// `rsa_pss_rsae_sha512`.
+    Rsa2048PssSha512 = 0x0806,
+
+    /// `ed25519`.
+    Ed25519 = 0x0807,
+
+    /// `rsa_pss_pss_sha256`. The caller is responsible for checking that the
+    /// key's `RSASSA-PSS` parameters, if any, match.
+    Rsa2048PssPssSha256 = 0x0809,
+
+    /// `rsa_pss_pss_sha384`. The caller is responsible for checking that the
+    /// key's `RSASSA-PSS` parameters, if any, match.
+    Rsa2048PssPssSha384 = 0x080a,
+
+    /// `rsa_pss_pss_sha512`. The caller is responsible for checking that the
+    /// key's `RSASSA-PSS` parameters, if any, match.
+    Rsa2048PssPssSha512 = 0x080b,
+}
+
+impl SignatureScheme {
+    /// Returns the scheme with TLS code point `v`, or `None` if `v` is not
+    /// the code point of a scheme that *ring* supports.
+    pub fn from_u16(v: u16) -> Option<Self> {
+        let scheme = match v {
+            0x0201 => Self::Rsa2048Pkcs1Sha1ForLegacyUseOnly,
+            0x0401 => Self::Rsa2048Pkcs1Sha256,
+            0x0403 => Self::Ecdsa256Sha256,
+            0x0501 => Self::Rsa2048Pkcs1Sha384,
+            0x0503 => Self::Ecdsa384Sha384,
+            0x0601 => Self::Rsa2048Pkcs1Sha512,
+            0x0804 => Self::Rsa2048PssSha256,
+            0x0805 => Self::Rsa2048PssSha384,
+            0x0806 => Self::Rsa2048PssSha512,
+            0x0807 => Self::Ed25519,
+            0x0809 => Self::Rsa2048PssPssSha256,
+            0x080a => Self::Rsa2048PssPssSha384,
+            0x080b => Self::Rsa2048PssPssSha512,
+            _ => {
+                return None;
+            }
+        };
+        Some(scheme)
+    }
+
+    /// The TLS code point of the scheme.
+    #[inline]
+    pub fn as_u16(self) -> u16 {
+        self as u16
+    }
+
+    /// The verification algorithm that implements the scheme, or `None` if it
+    /// isn't available in this configuration.
+    pub fn verification_algorithm(self) -> Option<&'static dyn VerificationAlgorithm> {
+        let alg: &'static dyn VerificationAlgorithm = match self {
+            Self::Ecdsa256Sha256 => &super::ECDSA_P256_SHA256_ASN1,
+            Self::Ecdsa384Sha384 => &super::ECDSA_P384_SHA384_ASN1,
+            Self::Ed25519 => &super::ED25519,
+
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048Pkcs1Sha1ForLegacyUseOnly => {
+                &super::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY
+            }
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048Pkcs1Sha256 => &super::RSA_PKCS1_2048_8192_SHA256,
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048Pkcs1Sha384 => &super::RSA_PKCS1_2048_8192_SHA384,
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048Pkcs1Sha512 => &super::RSA_PKCS1_2048_8192_SHA512,
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048PssSha256 | Self::Rsa2048PssPssSha256 => &super::RSA_PSS_2048_8192_SHA256,
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048PssSha384 | Self::Rsa2048PssPssSha384 => &super::RSA_PSS_2048_8192_SHA384,
+            #[cfg(feature = "alloc")]
+            Self::Rsa2048PssSha512 | Self::Rsa2048PssPssSha512 => &super::RSA_PSS_2048_8192_SHA512,
+
+            #[cfg(not(feature = "alloc"))]
+            _ => {
+                return None;
+            }
+        };
+        Some(alg)
+    }
+
+    /// Verifies that `sig` is a valid signature of `msg` under `public_key`
+    /// using this scheme.
+    pub fn verify(
+        self,
+        msg: &[u8],
+        sig: &[u8],
+        public_key: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let alg = self.verification_algorithm().ok_or(error::Unspecified)?;
+        UnparsedPublicKey::new(alg, public_key).verify(msg, sig)
+    }
+}
+
+impl From<SignatureScheme> for u16 {
+    #[inline]
+    fn from(scheme: SignatureScheme) -> Self {
+        scheme.as_u16()
+    }
+}
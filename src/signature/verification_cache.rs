@@ -0,0 +1,196 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+extern crate std;
+
+use super::VerificationAlgorithm;
+use crate::{digest, error, polyfill};
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// A bounded, least-recently-used cache of signature verification results,
+/// for use with [`super::UnparsedPublicKey::verify_cached()`].
+///
+/// This is useful when the same signature is verified repeatedly, e.g. a
+/// manifest signature checked at every stage of a boot chain.
+///
+/// Entries are keyed by the identity of the verification algorithm, i.e. the
+/// address of the `&'static` algorithm, and by the SHA-256 digest of the
+/// public key, the SHA-256 digest of the message, and the signature. Only
+/// whether verification succeeded is stored. All of these inputs are public,
+/// so looking them up in variable time leaks nothing secret.
+///
+/// Lookups are linear in the capacity, which is expected to be small.
+pub struct VerificationCache {
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+struct State {
+    // Most recently used first.
+    entries: VecDeque<(CacheKey, bool)>,
+    hits: u64,
+    verifications: u64,
+}
+
+impl VerificationCache {
+    /// Constructs an empty cache that holds up to `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(State {
+                entries: VecDeque::with_capacity(capacity),
+                hits: 0,
+                verifications: 0,
+            }),
+        }
+    }
+
+    /// The number of verifications answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /// The number of signatures that were actually verified, i.e. the number
+    /// of cache misses.
+    pub fn verifications(&self) -> u64 {
+        self.lock().verifications
+    }
+
+    /// The number of results in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all results from the cache.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    pub(super) fn verify(
+        &self,
+        algorithm: &'static dyn VerificationAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), error::Unspecified> {
+        let key = CacheKey::new(algorithm, public_key, message, signature);
+
+        let cached = {
+            let mut state = self.lock();
+            let cached = state
+                .entries
+                .iter()
+                .position(|(k, _)| k.matches(&key))
+                .and_then(|i| state.entries.remove(i));
+            match cached {
+                Some(entry) => {
+                    state.entries.push_front(entry);
+                    state.hits += 1;
+                    Some(entry.1)
+                }
+                None => {
+                    state.verifications += 1;
+                    None
+                }
+            }
+        };
+        if let Some(valid) = cached {
+            return if valid {
+                Ok(())
+            } else {
+                Err(error::Unspecified)
+            };
+        }
+
+        // Don't hold the lock while verifying.
+        let result = algorithm.verify(
+            untrusted::Input::from(public_key),
+            untrusted::Input::from(message),
+            untrusted::Input::from(signature),
+        );
+
+        if self.capacity > 0 {
+            let mut state = self.lock();
+            if !state.entries.iter().any(|(k, _)| k.matches(&key)) {
+                if state.entries.len() == self.capacity {
+                    let _ = state.entries.pop_back();
+                }
+                state.entries.push_front((key, result.is_ok()));
+            }
+        }
+
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // `State` is consistent even if a thread panicked while holding the
+        // lock.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl core::fmt::Debug for VerificationCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        let state = self.lock();
+        f.debug_struct("VerificationCache")
+            .field("capacity", &self.capacity)
+            .field("len", &state.entries.len())
+            .field("hits", &state.hits)
+            .field("verifications", &state.verifications)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct CacheKey {
+    algorithm: &'static dyn VerificationAlgorithm,
+    digest: [u8; digest::SHA256_OUTPUT_LEN],
+}
+
+impl CacheKey {
+    fn new(
+        algorithm: &'static dyn VerificationAlgorithm,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Self {
+        let message_digest = digest::digest(&digest::SHA256, message);
+
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        for field in [public_key, message_digest.as_ref(), signature] {
+            ctx.update(&polyfill::u64_from_usize(field.len()).to_be_bytes());
+            ctx.update(field);
+        }
+        Self {
+            algorithm,
+            digest: ctx.finish().as_ref().try_into().unwrap(),
+        }
+    }
+
+    // The vtable is compared along with the address so that distinct
+    // zero-sized algorithms, which may share an address, are told apart.
+    // Comparing vtables may give false negatives, e.g. when a vtable is
+    // duplicated across codegen units, which only costs a cache miss.
+    fn matches(&self, other: &Self) -> bool {
+        core::ptr::eq(self.algorithm, other.algorithm) && self.digest == other.digest
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Self-describing encodings of digests and HMAC tags.
+//!
+//! A 32-byte value could be a SHA-256 digest, a SHA-512/256 digest, or an
+//! HMAC-SHA256 tag. [`digest::Digest::to_tagged_bytes()`] and
+//! [`hmac::Tag::to_tagged_bytes()`] encode the value along with the
+//! algorithm that produced it, as
+//!
+//! ```text
+//! id (2 bytes, big-endian) || len (1 byte) || value (len bytes)
+//! ```
+//!
+//! where `len` is always the algorithm's output length. [`verify_tagged()`]
+//! verifies an encoded value using the algorithm it names.
+//!
+//! Only the algorithms listed below have ids. Encoding a value produced by
+//! any other algorithm, e.g. one constructed with the `external-digest`
+//! feature or HMAC-SM3, is an error.
+//!
+//! # Algorithm ids
+//!
+//! | Id       | Algorithm                                |
+//! |----------|------------------------------------------|
+//! | `0x0001` | `digest::SHA1_FOR_LEGACY_USE_ONLY`       |
+//! | `0x0002` | `digest::SHA256`                         |
+//! | `0x0003` | `digest::SHA384`                         |
+//! | `0x0004` | `digest::SHA512`                         |
+//! | `0x0005` | `digest::SHA512_256`                     |
+//! | `0x0006` | `digest::SM3`                            |
+//! | `0x0101` | `hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY`    |
+//! | `0x0102` | `hmac::HMAC_SHA256`                      |
+//! | `0x0103` | `hmac::HMAC_SHA384`                      |
+//! | `0x0104` | `hmac::HMAC_SHA512`                      |
+//!
+//! The ids are stored alongside the values, so this registry is
+//! append-only: an id is never removed or given to another algorithm. A new
+//! digest algorithm gets the next unused id starting at `0x0001`, and a new
+//! HMAC algorithm the next unused id starting at `0x0101`. Id `0x0000` is
+//! never assigned.
+
+use crate::{constant_time, debug, digest, error, hmac};
+
+/// The length of the id and length that precede the value.
+pub const HEADER_LEN: usize = 3;
+
+/// The maximum length of an encoded value.
+pub const MAX_LEN: usize = HEADER_LEN + digest::MAX_OUTPUT_LEN;
+
+static DIGEST_IDS: [(u16, &digest::Algorithm); 6] = [
+    (0x0001, &digest::SHA1_FOR_LEGACY_USE_ONLY),
+    (0x0002, &digest::SHA256),
+    (0x0003, &digest::SHA384),
+    (0x0004, &digest::SHA512),
+    (0x0005, &digest::SHA512_256),
+    (0x0006, &digest::SM3),
+];
+
+static HMAC_IDS: [(u16, &hmac::Algorithm); 4] = [
+    (0x0101, &hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY),
+    (0x0102, &hmac::HMAC_SHA256),
+    (0x0103, &hmac::HMAC_SHA384),
+    (0x0104, &hmac::HMAC_SHA512),
+];
+
+/// A digest or HMAC tag encoded along with the id of its algorithm.
+///
+/// Use `as_ref()` to get the encoding as a `&[u8]`.
+#[derive(Clone, Copy)]
+pub struct TaggedBytes {
+    bytes: [u8; MAX_LEN],
+    len: usize,
+}
+
+impl TaggedBytes {
+    fn new(id: u16, value: &[u8]) -> Self {
+        let mut bytes = [0; MAX_LEN];
+        let [id_hi, id_lo] = id.to_be_bytes();
+        bytes[0] = id_hi;
+        bytes[1] = id_lo;
+        bytes[2] = u8::try_from(value.len()).unwrap();
+        let len = HEADER_LEN + value.len();
+        bytes[HEADER_LEN..len].copy_from_slice(value);
+        Self { bytes, len }
+    }
+
+    pub(crate) fn from_digest(digest: &digest::Digest) -> Result<Self, error::Unspecified> {
+        Ok(Self::new(digest_id(digest.algorithm())?, digest.as_ref()))
+    }
+
+    pub(crate) fn from_hmac_tag(
+        algorithm: hmac::Algorithm,
+        tag: &hmac::Tag,
+    ) -> Result<Self, error::Unspecified> {
+        Ok(Self::new(hmac_id(algorithm)?, tag.as_ref()))
+    }
+}
+
+impl AsRef<[u8]> for TaggedBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl core::fmt::Debug for TaggedBytes {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        debug::write_hex_bytes(f, self.as_ref())
+    }
+}
+
+/// Verifies that `tagged` is the encoding of the digest of `data`, when
+/// `key` is `None`, or of the HMAC tag of `data` under `key`.
+///
+/// The algorithm is the one named by the id in `tagged`. Fails if the id is
+/// unknown; if it names a digest algorithm and `key` isn't `None`; if it
+/// names an HMAC algorithm and `key` is `None` or is for another algorithm;
+/// or if the encoded length isn't the algorithm's output length. The value
+/// is compared in constant time.
+pub fn verify_tagged(
+    key: Option<&hmac::Key>,
+    data: &[u8],
+    tagged: &[u8],
+) -> Result<(), error::Unspecified> {
+    if tagged.len() < HEADER_LEN {
+        return Err(error::Unspecified);
+    }
+    let (header, value) = tagged.split_at(HEADER_LEN);
+    let id = u16::from_be_bytes([header[0], header[1]]);
+    if usize::from(header[2]) != value.len() {
+        return Err(error::Unspecified);
+    }
+
+    match key {
+        None => {
+            let algorithm = lookup(&DIGEST_IDS, id)?;
+            if value.len() != algorithm.output_len() {
+                return Err(error::Unspecified);
+            }
+            constant_time::verify_slices_are_equal(digest::digest(algorithm, data).as_ref(), value)
+        }
+        Some(key) => {
+            let algorithm = lookup(&HMAC_IDS, id)?;
+            if *algorithm != key.algorithm()
+                || value.len() != algorithm.digest_algorithm().output_len()
+            {
+                return Err(error::Unspecified);
+            }
+            hmac::verify(key, data, value)
+        }
+    }
+}
+
+fn lookup<A>(ids: &[(u16, &'static A)], id: u16) -> Result<&'static A, error::Unspecified> {
+    ids.iter()
+        .find(|(candidate, _)| *candidate == id)
+        .map(|(_, algorithm)| *algorithm)
+        .ok_or(error::Unspecified)
+}
+
+fn digest_id(algorithm: &digest::Algorithm) -> Result<u16, error::Unspecified> {
+    DIGEST_IDS
+        .iter()
+        .find(|(_, candidate)| *candidate == algorithm)
+        .map(|(id, _)| *id)
+        .ok_or(error::Unspecified)
+}
+
+fn hmac_id(algorithm: hmac::Algorithm) -> Result<u16, error::Unspecified> {
+    HMAC_IDS
+        .iter()
+        .find(|(_, candidate)| **candidate == algorithm)
+        .map(|(id, _)| *id)
+        .ok_or(error::Unspecified)
+}
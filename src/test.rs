@@ -69,6 +69,33 @@
 //! reference to `digest::SHA1_FOR_LEGACY_USE_ONLY`, "SHA256" to
 //! `digest::SHA256`, etc.
 //!
+//! ## Expected Errors
+//!
+//! A test case that must fail has an `Expect` attribute of the form
+//! `ERROR:<reason>`, where `<reason>` is the `Display` output of the error:
+//!
+//! ```text
+//! Input = 3003020100
+//! Expect = ERROR:InvalidEncoding
+//! ```
+//!
+//! Pass the result of the operation being tested to
+//! `TestCase::check_result()`, which returns the value if the test case is
+//! expected to succeed and `None` if it is expected to fail, and panics if
+//! the result doesn't match the expectation:
+//!
+//! ```ignore
+//! let input = test_case.consume_bytes("Input");
+//! let key_pair = match test_case.check_result(rsa::KeyPair::from_pkcs8(&input)) {
+//!     Some(key_pair) => key_pair,
+//!     None => return Ok(()),
+//! };
+//! ```
+//!
+//! A test case that expects an error fails if the callback returns `Ok(())`
+//! without `check_result()` having seen that error, so a test case can't
+//! pass by accidentally skipping the failing operation.
+//!
 //! ## Output When a Test Fails
 //!
 //! When a test case fails, the framework automatically prints out the test
@@ -126,6 +153,9 @@ use crate::{bits, digest, error};
 #[cfg(any(feature = "std", feature = "test_logging"))]
 extern crate std;
 
+#[cfg(feature = "timing-tests")]
+pub mod timing;
+
 /// `compile_time_assert_clone::<T>();` fails to compile if `T` doesn't
 /// implement `Clone`.
 pub fn compile_time_assert_clone<T: Clone>() {}
@@ -159,9 +189,37 @@ pub fn compile_time_assert_std_error_error<T: std::error::Error>() {}
 #[derive(Debug)]
 pub struct TestCase {
     attributes: Vec<(String, String, bool)>,
+
+    // `None` until the `Expect` attribute has been consumed.
+    expected_error: Option<Option<ExpectedError>>,
+
+    // Whether `check_result` saw the expected error.
+    expected_error_checked: bool,
+}
+
+/// An error that a test case expects, from an `Expect = ERROR:<reason>`
+/// attribute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedError {
+    reason: String,
+}
+
+impl ExpectedError {
+    /// The expected `Display` output of the error.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
 }
 
 impl TestCase {
+    fn new(attributes: Vec<(String, String, bool)>) -> Self {
+        Self {
+            attributes,
+            expected_error: None,
+            expected_error_checked: false,
+        }
+    }
+
     /// Maps the string "true" to true and the string "false" to false.
     pub fn consume_bool(&mut self, key: &str) -> bool {
         match self.consume_string(key).as_ref() {
@@ -185,6 +243,7 @@ impl TestCase {
             "SHA384" => Some(&digest::SHA384),
             "SHA512" => Some(&digest::SHA512),
             "SHA512_256" => Some(&digest::SHA512_256),
+            "SM3" => Some(&digest::SM3),
             _ => panic!("Unsupported digest algorithm: {}", name),
         }
     }
@@ -276,6 +335,56 @@ impl TestCase {
             .unwrap_or_else(|| panic!("No attribute named \"{}\"", key))
     }
 
+    /// Returns the error the test case expects, from its `Expect` attribute,
+    /// or `None` if the test case is expected to succeed. The attribute must
+    /// have the form `Expect = ERROR:<reason>`, where `<reason>` is the
+    /// `Display` output of the error.
+    ///
+    /// This may be called more than once; the attribute is only consumed the
+    /// first time. When it returns `Some`, `run` will fail the test case
+    /// unless `check_result` saw the expected error.
+    pub fn expected_error(&mut self) -> Option<ExpectedError> {
+        if self.expected_error.is_none() {
+            let expected_error = self.consume_optional_string("Expect").map(|value| {
+                let reason = value
+                    .strip_prefix("ERROR:")
+                    .unwrap_or_else(|| panic!("Invalid Expect value: {}", value));
+                ExpectedError {
+                    reason: String::from(reason),
+                }
+            });
+            self.expected_error = Some(expected_error);
+        }
+        self.expected_error.clone().unwrap()
+    }
+
+    /// Checks `result` against `expected_error()`. If the test case expects an
+    /// error then `result` must be an error with that reason, and `None` is
+    /// returned; otherwise `result` must be `Ok` and its value is returned.
+    /// Panics if the result isn't what was expected.
+    pub fn check_result<T, E: core::fmt::Display>(&mut self, result: Result<T, E>) -> Option<T> {
+        match (result, self.expected_error()) {
+            (Ok(value), None) => Some(value),
+            (Err(actual), None) => {
+                panic!("Failed with error \"{}\", but expected to succeed", actual)
+            }
+            (Ok(_), Some(expected)) => {
+                panic!("Succeeded, but expected error \"{}\"", expected.reason)
+            }
+            (Err(actual), Some(expected)) => {
+                let actual = format!("{}", actual);
+                if actual != expected.reason {
+                    panic!(
+                        "Failed with error \"{}\", but expected error \"{}\"",
+                        actual, expected.reason
+                    );
+                }
+                self.expected_error_checked = true;
+                None
+            }
+        }
+    }
+
     /// Like `consume_string()` except it returns `None` if the test case
     /// doesn't have the attribute.
     pub fn consume_optional_string(&mut self, key: &str) -> Option<String> {
@@ -327,15 +436,20 @@ where
     while let Some(mut test_case) = parse_test_case(&mut current_section, lines) {
         let result = match f(&current_section, &mut test_case) {
             Ok(()) => {
-                if !test_case
+                if test_case
                     .attributes
                     .iter()
                     .any(|&(_, _, consumed)| !consumed)
                 {
-                    Ok(())
-                } else {
                     failed = true;
                     Err("Test didn't consume all attributes.")
+                } else if matches!(test_case.expected_error, Some(Some(_)))
+                    && !test_case.expected_error_checked
+                {
+                    failed = true;
+                    Err("Test expected an error, but didn't fail with it.")
+                } else {
+                    Ok(())
                 }
             }
             Err(error::Unspecified) => Err("Test returned Err(error::Unspecified)."),
@@ -420,13 +534,13 @@ fn parse_test_case(
 
             // End of the file on a non-empty test cases ends the test case.
             None => {
-                return Some(TestCase { attributes });
+                return Some(TestCase::new(attributes));
             }
 
             // A blank line ends a test case if the test case isn't empty.
             Some(line) if line.is_empty() => {
                 if !is_first_line {
-                    return Some(TestCase { attributes });
+                    return Some(TestCase::new(attributes));
                 }
                 // Ignore leading blank lines.
             }
@@ -473,7 +587,7 @@ fn parse_test_case(
 /// useful for some types of fuzzing.
 #[doc(hidden)]
 pub mod rand {
-    use crate::{error, rand};
+    use crate::{digest, error, rand};
 
     /// An implementation of `SecureRandom` that always fills the output slice
     /// with the given byte.
@@ -504,6 +618,8 @@ pub mod rand {
         }
     }
 
+    impl rand::ConsumedAfterUse for FixedSliceRandom<'_> {}
+
     /// An implementation of `SecureRandom` where each slice in `bytes` is a
     /// test vector for one call to `fill()`. *Not thread-safe.*
     ///
@@ -532,6 +648,8 @@ pub mod rand {
         }
     }
 
+    impl rand::ConsumedAfterUse for FixedSliceSequenceRandom<'_> {}
+
     impl Drop for FixedSliceSequenceRandom<'_> {
         fn drop(&mut self) {
             // Ensure that `fill()` was called exactly the right number of
@@ -539,10 +657,153 @@ pub mod rand {
             assert_eq!(unsafe { *self.current.get() }, self.bytes.len());
         }
     }
+
+    /// An implementation of `SecureRandom` that outputs a reproducible
+    /// pseudorandom stream determined by `seed`. *Not thread-safe.*
+    ///
+    /// Each 32 bytes of output are SHA-256(`seed` || `counter`), with both
+    /// encoded as big-endian `u64`s and `counter` incremented after each
+    /// block; a block is never split across calls to `fill()`. This is for
+    /// tests that need lots of random-looking inputs that are the same on
+    /// every run; it's obviously not secure.
+    #[derive(Debug)]
+    pub struct DeterministicRandom {
+        seed: u64,
+        counter: core::cell::Cell<u64>,
+    }
+
+    impl DeterministicRandom {
+        /// Constructs a generator whose output is determined by `seed`.
+        pub fn new(seed: u64) -> Self {
+            Self {
+                seed,
+                counter: core::cell::Cell::new(0),
+            }
+        }
+    }
+
+    impl rand::sealed::SecureRandom for DeterministicRandom {
+        fn fill_impl(&self, dest: &mut [u8]) -> Result<(), error::Unspecified> {
+            for chunk in dest.chunks_mut(digest::SHA256_OUTPUT_LEN) {
+                let counter = self.counter.get();
+                self.counter.set(counter + 1);
+                let mut ctx = digest::Context::new(&digest::SHA256);
+                ctx.update(&self.seed.to_be_bytes());
+                ctx.update(&counter.to_be_bytes());
+                chunk.copy_from_slice(&ctx.finish().as_ref()[..chunk.len()]);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Adversarial implementations of `std::io::Read` and `std::io::Write`.
+///
+/// These are useful for testing that code handles short reads, partial
+/// writes, and `std::io::ErrorKind::Interrupted` correctly.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod io {
+    use super::std::io::{self, Read, Write};
+    use alloc::vec::Vec;
+
+    /// A reader that returns at most one byte from `inner` per call to
+    /// `read()`, and fails every other call with
+    /// `std::io::ErrorKind::Interrupted`.
+    #[derive(Debug)]
+    pub struct OneByteReader<R> {
+        inner: R,
+        interrupt: bool,
+    }
+
+    impl<R: Read> OneByteReader<R> {
+        /// Constructs a reader that reads from `inner` one byte at a time.
+        pub fn new(inner: R) -> Self {
+            Self {
+                inner,
+                interrupt: true,
+            }
+        }
+    }
+
+    impl<R: Read> Read for OneByteReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(1);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    /// A writer that collects its output in `written` but accepts at most
+    /// `max_write` bytes per call to `write()`, and fails every other call
+    /// with `std::io::ErrorKind::Interrupted`.
+    #[derive(Debug)]
+    pub struct PartialWriter {
+        pub written: Vec<u8>,
+        max_write: usize,
+        interrupt: bool,
+    }
+
+    impl PartialWriter {
+        /// Constructs a writer that accepts at most `max_write` bytes at a
+        /// time; `max_write` must not be zero.
+        pub fn new(max_write: usize) -> Self {
+            assert!(max_write > 0);
+            Self {
+                written: Vec::new(),
+                max_write,
+                interrupt: true,
+            }
+        }
+    }
+
+    impl Write for PartialWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(self.max_write);
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A reader that fails with `kind` once `inner` has been read
+    /// completely, instead of reporting the end of the stream.
+    #[derive(Debug)]
+    pub struct FailingReader<R> {
+        inner: R,
+        kind: io::ErrorKind,
+    }
+
+    impl<R: Read> FailingReader<R> {
+        /// Constructs a reader that reads `inner` and then fails with `kind`.
+        pub fn new(inner: R, kind: io::ErrorKind) -> Self {
+            Self { inner, kind }
+        }
+    }
+
+    impl<R: Read> Read for FailingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.inner.read(buf)? {
+                0 if !buf.is_empty() => Err(self.kind.into()),
+                n => Ok(n),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Vec;
     use crate::{error, test};
 
     #[test]
@@ -633,6 +894,81 @@ mod tests {
         });
     }
 
+    fn check_value(value: &str) -> Result<i32, &'static str> {
+        match value.parse::<i32>() {
+            Ok(value) if value < 0 => Err("negative"),
+            Ok(value) => Ok(value),
+            Err(_) => Err("not a number"),
+        }
+    }
+
+    #[test]
+    fn expect_ok() {
+        let mut successes = 0;
+        test::run(test_file!("test_expect_tests.txt"), |_, test_case| {
+            let value = test_case.consume_string("Value");
+            if let Some(value) = test_case.check_result(check_value(&value)) {
+                assert_eq!(value, 1);
+                successes += 1;
+            }
+            Ok(())
+        });
+        assert_eq!(successes, 1);
+    }
+
+    #[test]
+    fn expected_error() {
+        let mut reasons = Vec::new();
+        test::run(test_file!("test_expect_tests.txt"), |_, test_case| {
+            let value = test_case.consume_string("Value");
+            reasons.push(test_case.expected_error());
+            // Calling `expected_error` again doesn't consume `Expect` twice.
+            let expected_error = test_case.expected_error();
+            let _ = test_case.check_result(check_value(&value));
+            assert_eq!(expected_error, *reasons.last().unwrap());
+            Ok(())
+        });
+        let reasons: Vec<_> = reasons
+            .iter()
+            .map(|e| e.as_ref().map(|e| e.reason()))
+            .collect();
+        assert_eq!(reasons, [None, Some("negative"), Some("not a number")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Test failed.")]
+    fn expected_error_unchecked() {
+        test::run(test_file!("test_expect_tests.txt"), |_, test_case| {
+            let value = test_case.consume_string("Value");
+            if test_case.expected_error().is_none() {
+                let _ = test_case.check_result(check_value(&value));
+            }
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Succeeded, but expected error \"negative\"")]
+    fn expected_error_succeeds() {
+        test::run(test_file!("test_expect_tests.txt"), |_, test_case| {
+            let value = test_case.consume_string("Value");
+            let _ = test_case.check_result(check_value(value.trim_start_matches('-')));
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Failed with error \"negative\", but expected error \"not a number\""
+    )]
+    fn expected_error_wrong_reason() {
+        test::run(test_file!("test_expect_tests.txt"), |_, test_case| {
+            let value = test_case.consume_string("Value");
+            let _ = test_case.check_result(check_value(&value).map_err(|_| "negative"));
+            Ok(())
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Syntax error: Expected Key = Value.")]
     fn syntax_error() {
@@ -0,0 +1,668 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Statistical checks that operations on secrets take time independent of
+//! the secrets.
+//!
+//! This is the method of dudect ([Reparaz, Balasch, and Verbauwhede, "Dude,
+//! is my code constant time?"]). A [`Target`] prepares inputs in two classes:
+//! [`Class::Fixed`] inputs all have the same secret, usually one chosen to
+//! provoke any special-casing, and [`Class::Random`] inputs have random
+//! secrets. [`measure`] times the target on a random interleaving of inputs
+//! of the two classes and compares the two distributions of times with
+//! Welch's *t*-test. The test is repeated on the measurements below several
+//! percentiles, since a leak is often only visible once the slowest
+//! measurements, which are mostly noise, are cropped. A large |*t*| is
+//! evidence that the time depends on the class, and so on the secret; dudect
+//! considers |*t*| > 10 to be a definite leak.
+//!
+//! The classes are assigned and the random inputs are generated with
+//! [`super::rand::DeterministicRandom`], so every run measures the same
+//! inputs in the same order.
+//!
+//! Timing is measured with the time-stamp counter on x86 and x86-64, and
+//! with `std::time::Instant` elsewhere. Measurements on a shared machine are
+//! noisy enough to produce false positives, so [`Report::check`] only fails
+//! if enforcement was explicitly requested. [`Config::from_env`] reads:
+//!
+//! * `RING_TIMING_TESTS_ENFORCE`: if `1`, fail when |*t*| exceeds the
+//!   threshold; otherwise only report it.
+//! * `RING_TIMING_TESTS_T_THRESHOLD`: the threshold; 10 by default.
+//! * `RING_TIMING_TESTS_MEASUREMENTS`: the number of measurements; 10,000 by
+//!   default.
+//! * `RING_TIMING_TESTS_SEED`: the seed of the inputs; 0 by default.
+//!
+//! The measurements are only meaningful in an optimized build:
+//!
+//! ```sh
+//! RING_TIMING_TESTS_ENFORCE=1 cargo test --release --features=timing-tests \
+//!     --test timing_tests -- --nocapture --test-threads=1
+//! ```
+//!
+//! Only available with the `timing-tests` feature.
+//!
+//! [Reparaz, Balasch, and Verbauwhede, "Dude, is my code constant time?"]:
+//!     https://eprint.iacr.org/2016/1123
+
+extern crate std;
+
+use super::rand::{DeterministicRandom, FixedByteRandom, FixedSliceRandom};
+use crate::{aead, agreement, digest, hmac, pbkdf2, rand, signature};
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
+use std::{env, fmt};
+
+/// The class of an input to a [`Target`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    /// An input with the target's fixed secret.
+    Fixed,
+
+    /// An input with a random secret.
+    Random,
+}
+
+/// An operation whose timing is checked by [`measure`].
+pub trait Target {
+    /// The input to one measured operation.
+    type Input;
+
+    /// The name of the operation, for the [`Report`].
+    fn name(&self) -> &'static str;
+
+    /// Prepares the input for one operation in `class`. Random secrets must
+    /// be generated with `rng`.
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input;
+
+    /// Performs the operation being measured.
+    fn run(&self, input: Self::Input);
+}
+
+/// The parameters of [`measure`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The number of measurements, across both classes.
+    pub measurements: usize,
+
+    /// The largest acceptable |*t*|.
+    pub t_threshold: f64,
+
+    /// Whether [`Report::check`] fails when |*t*| exceeds `t_threshold`.
+    pub enforce: bool,
+
+    /// The seed of the class assignments and random inputs.
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            measurements: 10_000,
+            t_threshold: 10.0,
+            enforce: false,
+            seed: 0,
+        }
+    }
+}
+
+impl Config {
+    /// The default configuration overridden by the `RING_TIMING_TESTS_*`
+    /// environment variables described in the module documentation.
+    ///
+    /// Panics if a variable is set to an invalid value.
+    pub fn from_env() -> Self {
+        fn var<T: core::str::FromStr>(name: &str, default: T) -> T {
+            match env::var(name) {
+                Ok(value) => value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid value {:?} for {}", value, name)),
+                Err(_) => default,
+            }
+        }
+        let default = Self::default();
+        Self {
+            measurements: var("RING_TIMING_TESTS_MEASUREMENTS", default.measurements),
+            t_threshold: var("RING_TIMING_TESTS_T_THRESHOLD", default.t_threshold),
+            enforce: env::var("RING_TIMING_TESTS_ENFORCE").as_deref() == Ok("1"),
+            seed: var("RING_TIMING_TESTS_SEED", default.seed),
+        }
+    }
+}
+
+/// The result of [`measure`].
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// The name of the target.
+    pub name: &'static str,
+
+    /// The number of measurements of each class, `[fixed, random]`.
+    pub measurements: [usize; 2],
+
+    /// The *t* statistic with the largest magnitude over all the croppings.
+    pub max_t: f64,
+
+    /// The threshold from the `Config`.
+    pub t_threshold: f64,
+
+    /// Whether `check` enforces the threshold.
+    pub enforce: bool,
+}
+
+impl Report {
+    /// Whether |*t*| is within the threshold.
+    pub fn passed(&self) -> bool {
+        self.max_t.abs() <= self.t_threshold
+    }
+
+    /// Panics if the threshold is exceeded and enforcement is enabled.
+    pub fn check(&self) {
+        if self.enforce && !self.passed() {
+            panic!("Timing leak suspected: {}", self);
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verdict = match (self.passed(), self.enforce) {
+            (true, _) => "ok",
+            (false, true) => "LEAK",
+            (false, false) => "LEAK? (not enforced)",
+        };
+        write!(
+            f,
+            "{}: max |t| = {:.2} (threshold {:.2}) over {} fixed and {} random measurements: {}",
+            self.name,
+            self.max_t.abs(),
+            self.t_threshold,
+            self.measurements[0],
+            self.measurements[1],
+            verdict
+        )
+    }
+}
+
+// The number of measurements made and discarded before the real ones, to
+// warm up caches and branch predictors.
+const WARMUP: usize = 100;
+
+// The number of percentiles at which measurements are cropped, in addition
+// to the uncropped measurements. They approach 100% exponentially, as in
+// dudect.
+const CROPS: usize = 10;
+
+/// Measures `target` and compares the timings of its two classes of inputs.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)] // Statistics only; precision loss is harmless.
+pub fn measure<T: Target>(target: &T, config: &Config) -> Report {
+    let rng = DeterministicRandom::new(config.seed);
+    let total = WARMUP + config.measurements;
+
+    let mut class_bits = alloc::vec![0u8; (total + 7) / 8];
+    rand::SecureRandom::fill(&rng, &mut class_bits).unwrap();
+    let classes = (0..total)
+        .map(|i| {
+            if class_bits[i / 8] & (1 << (i % 8)) == 0 {
+                Class::Fixed
+            } else {
+                Class::Random
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let inputs = classes
+        .iter()
+        .map(|class| target.prepare(*class, &rng))
+        .collect::<Vec<_>>();
+
+    let mut times = Vec::with_capacity(total);
+    for input in inputs {
+        times.push(time(|| target.run(input)));
+    }
+    let classes = &classes[WARMUP..];
+    let times = &times[WARMUP..];
+
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    let mut cutoffs = Vec::with_capacity(CROPS + 1);
+    cutoffs.push(u64::MAX);
+    for k in 0..CROPS {
+        let p = 1.0 - 0.5f64.powf(10.0 * ((k + 1) as f64) / (CROPS as f64));
+        let i = ((sorted.len() as f64) * p) as usize;
+        cutoffs.push(sorted[i.min(sorted.len() - 1)]);
+    }
+
+    let mut welch = alloc::vec![Welch::default(); cutoffs.len()];
+    for (class, time) in classes.iter().zip(times) {
+        for (welch, cutoff) in welch.iter_mut().zip(&cutoffs) {
+            if *time <= *cutoff {
+                welch.push(*class, *time as f64);
+            }
+        }
+    }
+
+    let max_t = welch
+        .iter()
+        .map(Welch::t)
+        .fold(0.0, |max: f64, t| if t.abs() > max.abs() { t } else { max });
+
+    Report {
+        name: target.name(),
+        measurements: welch[0].n.map(|n| n as usize),
+        max_t,
+        t_threshold: config.t_threshold,
+        enforce: config.enforce,
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn time(f: impl FnOnce()) -> u64 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::_rdtsc;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::_rdtsc;
+
+    let start = unsafe { _rdtsc() };
+    f();
+    let end = unsafe { _rdtsc() };
+    end.wrapping_sub(start)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[allow(clippy::cast_possible_truncation)]
+fn time(f: impl FnOnce()) -> u64 {
+    let start = std::time::Instant::now();
+    f();
+    start.elapsed().as_nanos() as u64
+}
+
+// Welch's t-test, with the means and variances computed incrementally with
+// Welford's algorithm.
+#[derive(Clone, Default)]
+struct Welch {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl Welch {
+    fn push(&mut self, class: Class, x: f64) {
+        let i = match class {
+            Class::Fixed => 0,
+            Class::Random => 1,
+        };
+        self.n[i] += 1.0;
+        let delta = x - self.mean[i];
+        self.mean[i] += delta / self.n[i];
+        self.m2[i] += delta * (x - self.mean[i]);
+    }
+
+    fn t(&self) -> f64 {
+        if self.n[0] < 2.0 || self.n[1] < 2.0 {
+            return 0.0;
+        }
+        let var = [
+            self.m2[0] / (self.n[0] - 1.0),
+            self.m2[1] / (self.n[1] - 1.0),
+        ];
+        let denominator = (var[0] / self.n[0] + var[1] / self.n[1]).sqrt();
+        if denominator == 0.0 {
+            return 0.0;
+        }
+        (self.mean[0] - self.mean[1]) / denominator
+    }
+}
+
+const MSG: &[u8] = b"timing";
+
+/// Ed25519 signing, with a fixed all-zero seed or a random seed.
+pub struct Ed25519Signing;
+
+impl Target for Ed25519Signing {
+    type Input = signature::Ed25519KeyPair;
+
+    fn name(&self) -> &'static str {
+        "Ed25519 signing"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        let mut seed = [0u8; 32];
+        if class == Class::Random {
+            rng.fill(&mut seed).unwrap();
+        }
+        signature::Ed25519KeyPair::from_seed_unchecked(&seed).unwrap()
+    }
+
+    fn run(&self, key_pair: Self::Input) {
+        let _ = key_pair.sign(MSG);
+    }
+}
+
+/// P-256 ECDSA signing, with the fixed private key 1 or a random private
+/// key. The nonces are derived from the private key and a fixed "random"
+/// input, so they are fixed for the fixed class too.
+pub struct EcdsaP256Signing;
+
+impl Target for EcdsaP256Signing {
+    type Input = signature::EcdsaKeyPair;
+
+    fn name(&self) -> &'static str {
+        "P-256 ECDSA signing"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+        let mut private_key = [0u8; 32];
+        match class {
+            Class::Fixed => private_key[31] = 1,
+            Class::Random => rng.fill(&mut private_key).unwrap(),
+        }
+        let private_key_rng = FixedSliceRandom {
+            bytes: &private_key,
+        };
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &private_key_rng).unwrap();
+        signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &FixedByteRandom { byte: 0 })
+            .unwrap()
+    }
+
+    fn run(&self, key_pair: Self::Input) {
+        let _ = key_pair.sign(&FixedByteRandom { byte: 0 }, MSG).unwrap();
+    }
+}
+
+/// X25519 key agreement with a fixed peer public key, with a fixed all-zero
+/// private key (before clamping) or a random private key.
+pub struct X25519Agreement {
+    peer_public_key: [u8; 32],
+}
+
+impl X25519Agreement {
+    /// The peer's public key is the base point.
+    pub fn new() -> Self {
+        let mut peer_public_key = [0u8; 32];
+        peer_public_key[0] = 9;
+        Self { peer_public_key }
+    }
+}
+
+impl Target for X25519Agreement {
+    type Input = agreement::EphemeralPrivateKey;
+
+    fn name(&self) -> &'static str {
+        "X25519 agreement"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        match class {
+            Class::Fixed => agreement::EphemeralPrivateKey::generate(
+                &agreement::X25519,
+                &FixedByteRandom { byte: 0 },
+            ),
+            Class::Random => agreement::EphemeralPrivateKey::generate(&agreement::X25519, rng),
+        }
+        .unwrap()
+    }
+
+    fn run(&self, private_key: Self::Input) {
+        let peer_public_key =
+            agreement::UnparsedPublicKey::new(&agreement::X25519, &self.peer_public_key);
+        let _ = agreement::agree_ephemeral(private_key, &peer_public_key, |shared| shared[0]);
+    }
+}
+
+// Returns the correct tag with its last byte flipped for the fixed class, so
+// that the comparison with the correct tag fails as late as possible, or a
+// random tag.
+fn wrong_tag(class: Class, correct: &[u8], rng: &dyn rand::SecureRandom) -> Vec<u8> {
+    let mut tag = correct.to_vec();
+    match class {
+        Class::Fixed => *tag.last_mut().unwrap() ^= 1,
+        Class::Random => {
+            rng.fill(&mut tag).unwrap();
+            if tag == correct {
+                *tag.last_mut().unwrap() ^= 1;
+            }
+        }
+    }
+    tag
+}
+
+/// AES-128-GCM opening of a fixed ciphertext with a wrong tag, which is
+/// either the correct tag with its last byte flipped or a random tag.
+pub struct AesGcmOpenTag {
+    key: aead::LessSafeKey,
+    sealed: Vec<u8>,
+}
+
+const AES_GCM_NONCE: [u8; aead::NONCE_LEN] = [0; aead::NONCE_LEN];
+
+impl AesGcmOpenTag {
+    /// Seals a fixed 64-byte plaintext with a fixed key and nonce.
+    pub fn new() -> Self {
+        let key =
+            aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &[0x42; 16]).unwrap());
+        let mut sealed = alloc::vec![0u8; 64];
+        key.seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key(AES_GCM_NONCE),
+            aead::Aad::empty(),
+            &mut sealed,
+        )
+        .unwrap();
+        Self { key, sealed }
+    }
+}
+
+impl Target for AesGcmOpenTag {
+    type Input = Vec<u8>;
+
+    fn name(&self) -> &'static str {
+        "AES-GCM open tag comparison"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        let (ciphertext, tag) = self.sealed.split_at(self.sealed.len() - aead::MAX_TAG_LEN);
+        [ciphertext, &wrong_tag(class, tag, rng)].concat()
+    }
+
+    fn run(&self, mut in_out: Self::Input) {
+        let _ = self
+            .key
+            .open_in_place(
+                aead::Nonce::assume_unique_for_key(AES_GCM_NONCE),
+                aead::Aad::empty(),
+                &mut in_out,
+            )
+            .unwrap_err();
+    }
+}
+
+/// HMAC-SHA256 verification of a fixed message with a wrong tag, which is
+/// either the correct tag with its last byte flipped or a random tag.
+pub struct HmacVerify {
+    key: hmac::Key,
+    tag: hmac::Tag,
+}
+
+impl HmacVerify {
+    /// Computes the tag of a fixed message with a fixed key.
+    pub fn new() -> Self {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &[0x42; digest::SHA256_OUTPUT_LEN]);
+        let tag = hmac::sign(&key, MSG);
+        Self { key, tag }
+    }
+}
+
+impl Target for HmacVerify {
+    type Input = Vec<u8>;
+
+    fn name(&self) -> &'static str {
+        "HMAC verify"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        wrong_tag(class, self.tag.as_ref(), rng)
+    }
+
+    fn run(&self, tag: Self::Input) {
+        let _ = hmac::verify(&self.key, MSG, &tag).unwrap_err();
+    }
+}
+
+/// PBKDF2-HMAC-SHA256 verification of a two-block derived key, with the
+/// correct password or a random wrong password of the same length.
+///
+/// The derived key is two blocks long so that a mismatch in the first block
+/// could end the verification early.
+pub struct Pbkdf2Verify {
+    iterations: NonZeroU32,
+    derived: [u8; 2 * digest::SHA256_OUTPUT_LEN],
+}
+
+const PBKDF2_ALGORITHM: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA256;
+const PBKDF2_SALT: &[u8] = b"salt";
+const PBKDF2_PASSWORD: &[u8] = b"password";
+
+impl Pbkdf2Verify {
+    /// Derives the key of a fixed password.
+    pub fn new() -> Self {
+        let iterations = NonZeroU32::new(1).unwrap();
+        let mut derived = [0u8; 2 * digest::SHA256_OUTPUT_LEN];
+        pbkdf2::derive(
+            PBKDF2_ALGORITHM,
+            iterations,
+            PBKDF2_SALT,
+            PBKDF2_PASSWORD,
+            &mut derived,
+        );
+        Self {
+            iterations,
+            derived,
+        }
+    }
+}
+
+impl Target for Pbkdf2Verify {
+    type Input = Vec<u8>;
+
+    fn name(&self) -> &'static str {
+        "PBKDF2 verify"
+    }
+
+    fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+        let mut password = PBKDF2_PASSWORD.to_vec();
+        if class == Class::Random {
+            while password == PBKDF2_PASSWORD {
+                rng.fill(&mut password).unwrap();
+            }
+        }
+        password
+    }
+
+    fn run(&self, password: Self::Input) {
+        let _ = pbkdf2::verify(
+            PBKDF2_ALGORITHM,
+            self.iterations,
+            PBKDF2_SALT,
+            &password,
+            &self.derived,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welch_t() {
+        let mut welch = Welch::default();
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            welch.push(Class::Fixed, x);
+        }
+        for x in [3.0, 4.0, 5.0, 6.0] {
+            welch.push(Class::Random, x);
+        }
+        // Both variances are 5/3, so t = -2 / sqrt(5/3 / 4 * 2).
+        let expected = -2.0 / (5.0f64 / 6.0).sqrt();
+        assert!((welch.t() - expected).abs() < 1e-12);
+
+        // Identical distributions.
+        let mut welch = Welch::default();
+        for x in [1.0, 2.0, 3.0] {
+            welch.push(Class::Fixed, x);
+            welch.push(Class::Random, x);
+        }
+        assert_eq!(welch.t(), 0.0);
+    }
+
+    // A comparison that returns at the first difference leaks how long the
+    // common prefix is, which the harness must notice.
+    struct EarlyExitCompare;
+
+    impl Target for EarlyExitCompare {
+        type Input = Vec<u8>;
+
+        fn name(&self) -> &'static str {
+            "early-exit comparison"
+        }
+
+        fn prepare(&self, class: Class, rng: &dyn rand::SecureRandom) -> Self::Input {
+            wrong_tag(class, &[0u8; 4096], rng)
+        }
+
+        fn run(&self, input: Self::Input) {
+            let _ = input.iter().position(|b| *b != 0);
+        }
+    }
+
+    #[test]
+    fn detects_leak() {
+        let config = Config {
+            measurements: 2_000,
+            enforce: true,
+            ..Config::default()
+        };
+        let report = measure(&EarlyExitCompare, &config);
+        assert!(!report.passed(), "{}", report);
+        assert_eq!(report.measurements.iter().sum::<usize>(), 2_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timing leak suspected")]
+    fn check_enforced() {
+        Report {
+            name: "test",
+            measurements: [1, 1],
+            max_t: -11.0,
+            t_threshold: 10.0,
+            enforce: true,
+        }
+        .check();
+    }
+
+    #[test]
+    fn check_not_enforced() {
+        Report {
+            name: "test",
+            measurements: [1, 1],
+            max_t: 11.0,
+            t_threshold: 10.0,
+            enforce: false,
+        }
+        .check();
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! TLS 1.3 record protection.
+//!
+//! [`AeadContext`] protects the records sent in one direction with one
+//! traffic key, as specified in [RFC 8446 Section 5.2]. It keeps the record
+//! sequence number and derives each record's nonce from it as specified in
+//! [RFC 8446 Section 5.3]: the sequence number, left-padded with zeros to the
+//! length of the IV, XORed with the IV.
+//!
+//! The sequence number isn't allowed to wrap. After 2<sup>64</sup> records
+//! `seal_record` fails, and the connection must be rekeyed or closed.
+//!
+//! [RFC 8446 Section 5.2]: https://www.rfc-editor.org/rfc/rfc8446#section-5.2
+//! [RFC 8446 Section 5.3]: https://www.rfc-editor.org/rfc/rfc8446#section-5.3
+
+use crate::{aead, error};
+use alloc::vec::Vec;
+
+/// The maximum length of a record's content, 2<sup>14</sup> bytes.
+pub const MAX_PLAINTEXT_LEN: usize = 1 << 14;
+
+// The header of a `TLSCiphertext`: `opaque_type`, `legacy_record_version`,
+// and `length`.
+const HEADER_LEN: usize = 5;
+
+// `opaque_type`, `application_data`.
+const OPAQUE_TYPE: u8 = 23;
+
+// `legacy_record_version`, TLS 1.2.
+const LEGACY_RECORD_VERSION: [u8; 2] = [0x03, 0x03];
+
+/// The record protection state for one direction of a TLS 1.3 connection.
+pub struct AeadContext {
+    key: aead::LessSafeKey,
+    iv: [u8; aead::NONCE_LEN],
+
+    // `None` once all 2**64 sequence numbers have been used.
+    sequence_number: Option<u64>,
+}
+
+impl AeadContext {
+    /// Constructs a context for the traffic key `key` and IV `iv` of
+    /// `algorithm`, with sequence number zero.
+    ///
+    /// `iv` must be `aead::NONCE_LEN` bytes long, which is the IV length of
+    /// all the TLS 1.3 cipher suites *ring* supports.
+    pub fn new(
+        algorithm: &'static aead::Algorithm,
+        key: &[u8],
+        iv: &[u8],
+    ) -> Result<Self, error::Unspecified> {
+        let key = aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, key)?);
+        let iv = iv.try_into()?;
+        Ok(Self {
+            key,
+            iv,
+            sequence_number: Some(0),
+        })
+    }
+
+    /// The AEAD algorithm of the traffic key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static aead::Algorithm {
+        self.key.algorithm()
+    }
+
+    /// Protects `plaintext`, the content of a record of type `content_type`,
+    /// and returns the complete `TLSCiphertext`, including its header.
+    ///
+    /// The record is sent without padding. Fails, without using a sequence
+    /// number, if `plaintext` is longer than `MAX_PLAINTEXT_LEN`; and fails
+    /// if all the sequence numbers have been used.
+    pub fn seal_record(
+        &mut self,
+        content_type: u8,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, error::Unspecified> {
+        if plaintext.len() > MAX_PLAINTEXT_LEN {
+            return Err(error::Unspecified);
+        }
+        let nonce = self.next_nonce()?;
+
+        // `TLSInnerPlaintext` is the content followed by its type.
+        let inner_len = plaintext.len() + 1;
+        let record_len = inner_len + self.algorithm().tag_len();
+        let [len_hi, len_lo] = u16::try_from(record_len)
+            .map_err(|_| error::Unspecified)?
+            .to_be_bytes();
+        let header = [
+            OPAQUE_TYPE,
+            LEGACY_RECORD_VERSION[0],
+            LEGACY_RECORD_VERSION[1],
+            len_hi,
+            len_lo,
+        ];
+
+        let mut record = Vec::with_capacity(HEADER_LEN + record_len);
+        record.extend_from_slice(&header);
+        record.extend_from_slice(plaintext);
+        record.push(content_type);
+        let tag = self.key.seal_in_place_separate_tag(
+            nonce,
+            aead::Aad::from(header),
+            &mut record[HEADER_LEN..],
+        )?;
+        record.extend_from_slice(tag.as_ref());
+        Ok(record)
+    }
+
+    // Returns the nonce for the current sequence number and advances it.
+    fn next_nonce(&mut self) -> Result<aead::Nonce, error::Unspecified> {
+        let sequence_number = self.sequence_number.ok_or(error::Unspecified)?;
+        self.sequence_number = sequence_number.checked_add(1);
+
+        let mut nonce = self.iv;
+        nonce[(aead::NONCE_LEN - 8)..]
+            .iter_mut()
+            .zip(sequence_number.to_be_bytes())
+            .for_each(|(n, sn)| *n ^= sn);
+        Ok(aead::Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+impl core::fmt::Debug for AeadContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AeadContext")
+            .field("algorithm", self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_number_overflow() {
+        let mut ctx = AeadContext::new(&aead::AES_128_GCM, &[0; 16], &[0; 12]).unwrap();
+        ctx.sequence_number = Some(u64::MAX - 1);
+        assert!(ctx.seal_record(23, b"").is_ok());
+        assert!(ctx.seal_record(23, b"").is_ok());
+        assert_eq!(ctx.seal_record(23, b""), Err(error::Unspecified));
+        assert_eq!(ctx.seal_record(23, b""), Err(error::Unspecified));
+    }
+}
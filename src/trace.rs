@@ -0,0 +1,73 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Structured logging of cryptographic operations.
+//!
+//! When the `tracing` feature is enabled, *ring* emits [`tracing`] spans at
+//! the `DEBUG` level for the operations listed below, and `WARN` events when
+//! deprecated APIs are used. When the feature is disabled, nothing is
+//! emitted and there is no runtime cost.
+//!
+//! | Span name                | Emitted by                                        | Fields |
+//! |--------------------------|---------------------------------------------------|--------|
+//! | [`KEY_GENERATION`]       | `Ed25519KeyPair::generate`, `Ed25519KeyPair::generate_pkcs8`, `EcdsaKeyPair::generate_pkcs8`, `RsaKeyPair::generate`, `EphemeralPrivateKey::generate` | `algorithm`, `key_bits` |
+//! | [`AEAD_SEAL`]            | `aead::{LessSafeKey, SealingKey}`                  | `algorithm`, `plaintext_len`, `aad_len` |
+//! | [`AEAD_OPEN`]            | `aead::{LessSafeKey, OpeningKey}`                  | `algorithm`, `ciphertext_len`, `aad_len` |
+//! | [`SIGN`]                 | `EcdsaKeyPair::sign`, `Ed25519KeyPair::sign`, `RsaKeyPair::sign` | `algorithm`, and `message_digest` (ECDSA and RSA) or `message_len` (Ed25519, which doesn't prehash the message) |
+//! | [`AGREEMENT`]            | `agreement::agree_ephemeral`                       | `algorithm` |
+//!
+//! The duration of an operation is the lifetime of its span, which
+//! subscribers can record (e.g. `tracing_subscriber`'s `FmtSpan::CLOSE`);
+//! *ring* does not read a clock itself.
+//!
+//! Span and event values never include secret key material, private key
+//! seeds, nonces, plaintext, or shared secrets, and must not in the future.
+//!
+//! [`tracing`]: https://docs.rs/tracing
+
+/// The name of spans for key generation.
+pub const KEY_GENERATION: &str = "ring::key_generation";
+
+/// The name of spans for AEAD sealing.
+pub const AEAD_SEAL: &str = "ring::aead::seal";
+
+/// The name of spans for AEAD opening.
+pub const AEAD_OPEN: &str = "ring::aead::open";
+
+/// The name of spans for signature creation.
+pub const SIGN: &str = "ring::signature::sign";
+
+/// The name of spans for key agreement.
+pub const AGREEMENT: &str = "ring::agreement";
+
+// Enters a `DEBUG` span for the rest of the enclosing scope.
+macro_rules! trace_span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+// Emits a `WARN` event noting the use of a deprecated API.
+#[allow(unused_macros)] // Only used by RSA, which requires `alloc`.
+macro_rules! trace_deprecated {
+    ($api:expr, $replacement:expr) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::warn!(
+            api = $api,
+            replacement = $replacement,
+            "use of deprecated *ring* API"
+        );
+    };
+}
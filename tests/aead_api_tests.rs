@@ -0,0 +1,165 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Tests that the `aead` traits stay usable from generic code. Most of these
+//! would fail to compile, rather than fail at runtime, if the API changed
+//! incompatibly.
+
+use ring::{
+    aead::{self, BoundKey, NonceSequence},
+    error,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A counter that allows `limit` nonces.
+#[derive(Debug)]
+struct Counter {
+    next: u32,
+    limit: u32,
+}
+
+impl Counter {
+    fn new(limit: u32) -> Self {
+        Self { next: 0, limit }
+    }
+}
+
+impl NonceSequence for Counter {
+    fn advance(&mut self) -> Result<aead::Nonce, error::Unspecified> {
+        if self.next >= self.limit {
+            return Err(error::Unspecified);
+        }
+        let mut nonce = [0; aead::NONCE_LEN];
+        nonce[8..].copy_from_slice(&self.next.to_be_bytes());
+        self.next += 1;
+        Ok(aead::Nonce::assume_unique_for_key(nonce))
+    }
+}
+
+/// A record layer that is generic over the kind of key, like one that would
+/// wrap a key in a session type.
+struct RecordLayer<K> {
+    key: K,
+}
+
+impl<K: BoundKey<Counter>> RecordLayer<K> {
+    fn new(algorithm: &'static aead::Algorithm, key_bytes: &[u8], limit: u32) -> Self {
+        let key = aead::UnboundKey::new(algorithm, key_bytes).unwrap();
+        Self {
+            key: K::new(key, Counter::new(limit)),
+        }
+    }
+
+    fn algorithm(&self) -> &'static aead::Algorithm {
+        self.key.algorithm()
+    }
+}
+
+impl RecordLayer<aead::SealingKey<Counter>> {
+    fn seal(&mut self, record: &mut Vec<u8>) -> Result<(), error::Unspecified> {
+        self.key
+            .seal_in_place_append_tag(aead::Aad::from(b"record"), record)
+    }
+
+    fn records_sealed(&self) -> u32 {
+        self.key.nonce_sequence().next
+    }
+}
+
+impl RecordLayer<aead::OpeningKey<Counter>> {
+    fn open<'r>(&mut self, record: &'r mut [u8]) -> Result<&'r mut [u8], error::Unspecified> {
+        self.key.open_in_place(aead::Aad::from(b"record"), record)
+    }
+}
+
+fn algorithm_of<N: NonceSequence>(key: &dyn BoundKey<N>) -> &'static aead::Algorithm {
+    key.algorithm()
+}
+
+#[test]
+fn aead_bound_key_is_implemented_test() {
+    fn assert_bound_key<N: NonceSequence, K: BoundKey<N> + core::fmt::Debug>() {}
+
+    assert_bound_key::<Counter, aead::SealingKey<Counter>>();
+    assert_bound_key::<Counter, aead::OpeningKey<Counter>>();
+    assert_bound_key::<Counter, aead::StrictSealingKey<Counter>>();
+    assert_bound_key::<Counter, aead::StrictOpeningKey<Counter>>();
+}
+
+#[test]
+fn aead_bound_key_trait_object_test() {
+    let key_bytes = [0x42; 32];
+    let sealing_key: aead::SealingKey<Counter> =
+        RecordLayer::new(&aead::CHACHA20_POLY1305, &key_bytes, 1).key;
+    let strict_opening_key: aead::StrictOpeningKey<Counter> = BoundKey::new(
+        aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap(),
+        Counter::new(1),
+    );
+
+    let keys: [&dyn BoundKey<Counter>; 2] = [&sealing_key, &strict_opening_key];
+    for key in keys.iter() {
+        assert_eq!(algorithm_of(*key), &aead::CHACHA20_POLY1305);
+    }
+
+    // The inherent method agrees with the trait method.
+    assert_eq!(sealing_key.algorithm(), BoundKey::algorithm(&sealing_key));
+}
+
+#[test]
+fn aead_generic_record_layer_test() {
+    for &(algorithm, key_len) in &[
+        (&aead::AES_128_GCM, 16),
+        (&aead::AES_256_GCM, 32),
+        (&aead::CHACHA20_POLY1305, 32),
+    ] {
+        const RECORDS: u32 = 3;
+        let key_bytes = vec![0x42; key_len];
+
+        let mut sealer =
+            RecordLayer::<aead::SealingKey<Counter>>::new(algorithm, &key_bytes, RECORDS);
+        let mut opener =
+            RecordLayer::<aead::OpeningKey<Counter>>::new(algorithm, &key_bytes, RECORDS);
+        assert_eq!(sealer.algorithm(), algorithm);
+        assert_eq!(opener.algorithm(), algorithm);
+
+        let mut records = Vec::new();
+        for i in 0..RECORDS {
+            let mut record = format!("record {}", i).into_bytes();
+            sealer.seal(&mut record).unwrap();
+            records.push(record);
+        }
+        assert_eq!(sealer.records_sealed(), RECORDS);
+
+        // The nonce sequence enforces its limit.
+        let mut record = b"one too many".to_vec();
+        assert!(sealer.seal(&mut record).is_err());
+
+        // Skipping a nonce makes the next record fail to open.
+        let mut skipped = records[0].clone();
+        opener.key.nonce_sequence_mut().next = 1;
+        assert!(opener.open(&mut skipped).is_err());
+        opener.key.nonce_sequence_mut().next = 0;
+
+        for (i, record) in records.iter_mut().enumerate() {
+            let plaintext = opener.open(record).unwrap();
+            assert_eq!(plaintext, format!("record {}", i).as_bytes());
+        }
+        assert_eq!(opener.key.nonce_sequence().next, RECORDS);
+    }
+}
@@ -19,7 +19,10 @@ use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
 wasm_bindgen_test_configure!(run_in_browser);
 
 use core::ops::RangeFrom;
-use ring::{aead, error, test, test_file};
+use ring::{
+    aead::{self, BoundKey},
+    error, test, test_file,
+};
 
 /// Generate the known answer test functions for the given algorithm and test
 /// case input file, where each test is implemented by a test in `$test`.
@@ -59,12 +62,16 @@ macro_rules! test_aead {
                         [
                             less_safe_key_open_in_place,
                             less_safe_key_open_within,
+                            less_safe_key_open_tag_first,
                             less_safe_key_seal_in_place_append_tag,
                             less_safe_key_seal_in_place_separate_tag,
+                            less_safe_key_seal_from_slice_tag_first,
                             opening_key_open_in_place,
                             opening_key_open_within,
                             sealing_key_seal_in_place_append_tag,
                             sealing_key_seal_in_place_separate_tag,
+                            streaming_seal,
+                            streaming_open,
                             test_open_in_place_seperate_tag,
                         ]);
 
@@ -397,6 +404,254 @@ fn less_safe_key_open_within(
     })
 }
 
+fn less_safe_key_seal_from_slice_tag_first(
+    alg: &'static aead::Algorithm,
+    tc: KnownAnswerTestCase,
+) -> Result<(), error::Unspecified> {
+    let key = make_less_safe_key(alg, tc.key);
+    let nonce = aead::Nonce::assume_unique_for_key(tc.nonce);
+    let mut out = vec![0u8; tc.tag.len() + tc.plaintext.len()];
+    key.seal_from_slice_tag_first(nonce, tc.aad, tc.plaintext, &mut out)?;
+
+    let mut expected_tag_and_ciphertext = Vec::from(tc.tag);
+    expected_tag_and_ciphertext.extend_from_slice(tc.ciphertext);
+    assert_eq!(out, expected_tag_and_ciphertext);
+
+    Ok(())
+}
+
+fn less_safe_key_open_tag_first(
+    alg: &'static aead::Algorithm,
+    tc: KnownAnswerTestCase,
+) -> Result<(), error::Unspecified> {
+    let key = make_less_safe_key(alg, tc.key);
+    let nonce = aead::Nonce::assume_unique_for_key(tc.nonce);
+    let mut in_out = Vec::from(tc.tag);
+    in_out.extend_from_slice(tc.ciphertext);
+
+    let actual_plaintext = key.open_tag_first(nonce, tc.aad, &mut in_out)?;
+    assert_eq!(actual_plaintext, tc.plaintext);
+    assert_eq!(&in_out[..tc.plaintext.len()], tc.plaintext);
+
+    Ok(())
+}
+
+// Part lengths that split the input at, and away from, the block boundaries
+// of both AES (16 bytes) and ChaCha20 (64 bytes).
+const STREAMING_PART_LENS: &[usize] = &[1, 3, 15, 16, 17, 63, 64, 65, 200];
+
+// Calls `update` with consecutive parts of `in_out`, each `part_len` long
+// except maybe the last, with an empty part between each.
+fn update_in_parts(
+    in_out: &mut [u8],
+    part_len: usize,
+    mut update: impl FnMut(&mut [u8]) -> Result<(), error::Unspecified>,
+) -> Result<(), error::Unspecified> {
+    update(&mut [])?;
+    for part in in_out.chunks_mut(part_len) {
+        update(part)?;
+        update(&mut [])?;
+    }
+    Ok(())
+}
+
+fn streaming_seal(
+    alg: &'static aead::Algorithm,
+    tc: KnownAnswerTestCase,
+) -> Result<(), error::Unspecified> {
+    let key = make_less_safe_key(alg, tc.key);
+    for &part_len in STREAMING_PART_LENS {
+        test_seal_separate_tag(&tc, |nonce, in_out| {
+            let mut ctx = aead::SealingContext::new(&key, nonce, tc.aad);
+            update_in_parts(in_out, part_len, |part| ctx.update(part))?;
+            Ok(ctx.finish())
+        })?;
+    }
+    Ok(())
+}
+
+fn streaming_open(
+    alg: &'static aead::Algorithm,
+    tc: KnownAnswerTestCase,
+) -> Result<(), error::Unspecified> {
+    let key = make_less_safe_key(alg, tc.key);
+    for &part_len in STREAMING_PART_LENS {
+        let mut in_out = Vec::from(tc.ciphertext);
+        let mut ctx =
+            aead::OpeningContext::new(&key, aead::Nonce::assume_unique_for_key(tc.nonce), tc.aad);
+        update_in_parts(&mut in_out, part_len, |part| ctx.update(part))?;
+        ctx.finish(aead::Tag::try_from(tc.tag)?)?;
+        assert_eq!(in_out, tc.plaintext);
+    }
+    Ok(())
+}
+
+#[test]
+fn aead_streaming_round_trip() {
+    use ring::rand::{SecureRandom, SystemRandom};
+
+    let rng = SystemRandom::new();
+    for alg in [&aead::AES_256_GCM, &aead::CHACHA20_POLY1305] {
+        for len in 0..=300 {
+            let mut key_bytes = vec![0u8; alg.key_len()];
+            let mut nonce = [0u8; aead::NONCE_LEN];
+            let mut aad = vec![0u8; len % 37];
+            let mut plaintext = vec![0u8; len];
+            rng.fill(&mut key_bytes).unwrap();
+            rng.fill(&mut nonce).unwrap();
+            rng.fill(&mut aad).unwrap();
+            rng.fill(&mut plaintext).unwrap();
+            let key = make_less_safe_key(alg, &key_bytes);
+
+            let mut expected = plaintext.clone();
+            key.seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(&aad),
+                &mut expected,
+            )
+            .unwrap();
+            let (expected_ciphertext, expected_tag) = expected.split_at(len);
+
+            // Parts of varying lengths, so that the boundaries fall at many
+            // different offsets within a block.
+            let part_lens = (1..=len).cycle().take(len).chain(core::iter::once(0));
+
+            let mut ciphertext = plaintext.clone();
+            let mut ctx = aead::SealingContext::new(
+                &key,
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(&aad),
+            );
+            let mut rest = &mut ciphertext[..];
+            for part_len in part_lens.clone() {
+                let (part, r) = rest.split_at_mut(core::cmp::min(part_len, rest.len()));
+                ctx.update(part).unwrap();
+                rest = r;
+            }
+            let tag = ctx.finish();
+            assert_eq!(ciphertext, expected_ciphertext);
+            assert_eq!(tag.as_ref(), expected_tag);
+
+            let open = |received_tag: aead::Tag| {
+                let mut in_out = ciphertext.clone();
+                let mut ctx = aead::OpeningContext::new(
+                    &key,
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::from(&aad),
+                );
+                let mut rest = &mut in_out[..];
+                for part_len in part_lens.clone() {
+                    let (part, r) = rest.split_at_mut(core::cmp::min(part_len, rest.len()));
+                    ctx.update(part).unwrap();
+                    rest = r;
+                }
+                ctx.finish(received_tag).map(|()| in_out)
+            };
+            assert_eq!(open(tag), Ok(plaintext));
+
+            let mut wrong_tag = [0u8; aead::MAX_TAG_LEN];
+            wrong_tag.copy_from_slice(tag.as_ref());
+            wrong_tag[len % wrong_tag.len()] ^= 1;
+            assert_eq!(open(aead::Tag::from(wrong_tag)), Err(error::Unspecified));
+        }
+    }
+}
+
+#[test]
+fn aead_tag_first_round_trip() {
+    use ring::rand::{SecureRandom, SystemRandom};
+
+    let rng = SystemRandom::new();
+    for alg in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        for len in 0..=300 {
+            let mut key_bytes = vec![0u8; alg.key_len()];
+            let mut nonce = [0u8; aead::NONCE_LEN];
+            let mut aad = vec![0u8; len % 37];
+            let mut plaintext = vec![0u8; len];
+            rng.fill(&mut key_bytes).unwrap();
+            rng.fill(&mut nonce).unwrap();
+            rng.fill(&mut aad).unwrap();
+            rng.fill(&mut plaintext).unwrap();
+
+            let key = make_less_safe_key(alg, &key_bytes);
+            let mut sealed = vec![0u8; alg.tag_len() + len];
+            key.seal_from_slice_tag_first(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(&aad),
+                &plaintext,
+                &mut sealed,
+            )
+            .unwrap();
+
+            // The same output as `seal_in_place_separate_tag`, rearranged.
+            let mut ciphertext = plaintext.clone();
+            let tag = key
+                .seal_in_place_separate_tag(
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::from(&aad),
+                    &mut ciphertext,
+                )
+                .unwrap();
+            assert_eq!(&sealed[..alg.tag_len()], tag.as_ref());
+            assert_eq!(&sealed[alg.tag_len()..], &ciphertext[..]);
+
+            let mut in_out = sealed.clone();
+            let opened = key
+                .open_tag_first(
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::from(&aad),
+                    &mut in_out,
+                )
+                .unwrap();
+            assert_eq!(opened, &plaintext[..]);
+
+            // Corrupting the tag or the ciphertext is detected.
+            let middle = (alg.tag_len() + len / 2).min(sealed.len() - 1);
+            for i in [0, alg.tag_len() - 1, middle, sealed.len() - 1] {
+                let mut in_out = sealed.clone();
+                in_out[i] ^= 0x80;
+                assert!(key
+                    .open_tag_first(
+                        aead::Nonce::assume_unique_for_key(nonce),
+                        aead::Aad::from(&aad),
+                        &mut in_out,
+                    )
+                    .is_err());
+            }
+
+            // `out` must be exactly the right length.
+            for out_len in [sealed.len() - 1, sealed.len() + 1] {
+                let mut out = vec![0u8; out_len];
+                assert!(key
+                    .seal_from_slice_tag_first(
+                        aead::Nonce::assume_unique_for_key(nonce),
+                        aead::Aad::from(&aad),
+                        &plaintext,
+                        &mut out,
+                    )
+                    .is_err());
+            }
+        }
+
+        // Inputs shorter than a tag are rejected.
+        let key = make_less_safe_key(alg, &vec![0u8; alg.key_len()]);
+        for len in 0..alg.tag_len() {
+            let mut in_out = vec![0u8; len];
+            assert!(key
+                .open_tag_first(
+                    aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+                    aead::Aad::empty(),
+                    &mut in_out,
+                )
+                .is_err());
+        }
+    }
+}
+
 #[allow(clippy::range_plus_one)]
 fn key_sizes(aead_alg: &'static aead::Algorithm) {
     let key_len = aead_alg.key_len();
@@ -489,6 +744,38 @@ fn aead_chacha20_poly1305_openssh() {
     );
 }
 
+#[test]
+fn aead_polyval() {
+    use aead::polyval;
+
+    test::run(
+        test_file!("aead_polyval_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let h: [u8; polyval::BLOCK_LEN] = test_case.consume_bytes("H").try_into().unwrap();
+            let input = test_case.consume_bytes("IN");
+            let expected = test_case.consume_bytes("OUT");
+
+            let key = polyval::Key::new(&h);
+
+            // All at once, and also split at every block boundary.
+            let mut ctx = polyval::Context::new(&key);
+            ctx.update_blocks(&input);
+            assert_eq!(&ctx.finish()[..], &expected[..]);
+
+            for split in (0..=input.len()).step_by(polyval::BLOCK_LEN) {
+                let (a, b) = input.split_at(split);
+                let mut ctx = polyval::Context::new(&key);
+                ctx.update_blocks(a);
+                ctx.update_blocks(b);
+                assert_eq!(&ctx.finish()[..], &expected[..]);
+            }
+
+            Ok(())
+        },
+    );
+}
+
 #[test]
 fn aead_test_aad_traits() {
     test::compile_time_assert_copy::<aead::Aad<&'_ [u8]>>();
@@ -599,6 +886,558 @@ fn test_aead_lesssafekey_clone_chacha20_poly1305() {
     test_aead_lesssafekey_clone_for_algorithm(&aead::CHACHA20_POLY1305);
 }
 
+fn test_aead_key_rotation_for_algorithm(algorithm: &'static aead::Algorithm) {
+    let key_bytes: Vec<u8> = (0..algorithm.key_len()).map(|i| i as u8).collect();
+    let nonce = [0; aead::NONCE_LEN];
+    let make_nonce = || aead::Nonce::try_assume_unique_for_key(&nonce).unwrap();
+
+    let sealing_key =
+        aead::RotatingSealingKey::new(algorithm, &key_bytes, OneNonceSequence::new(make_nonce()))
+            .unwrap();
+    let opening_key =
+        aead::RotatingOpeningKey::new(algorithm, &key_bytes, OneNonceSequence::new(make_nonce()))
+            .unwrap();
+    let stale_opening_key: aead::OpeningKey<OneNonceSequence> =
+        make_key(algorithm, &key_bytes, make_nonce());
+
+    // The nonce sequences are reset by rotation, so the same nonce can be
+    // used again under the new key.
+    let mut sealing_key = sealing_key
+        .rotate(b"epoch 1", OneNonceSequence::new(make_nonce()))
+        .unwrap();
+    let mut opening_key = opening_key
+        .rotate(b"epoch 1", OneNonceSequence::new(make_nonce()))
+        .unwrap();
+    assert_eq!(sealing_key.algorithm(), algorithm);
+
+    let mut record = b"hello, world".to_vec();
+    sealing_key
+        .seal_in_place_append_tag(aead::Aad::empty(), &mut record)
+        .unwrap();
+
+    // A key that wasn't rotated can't open the record.
+    let mut stale_record = record.clone();
+    let mut stale_opening_key = stale_opening_key;
+    assert!(stale_opening_key
+        .open_in_place(aead::Aad::empty(), &mut stale_record)
+        .is_err());
+
+    // Neither can a key rotated with different key material.
+    let mut wrong_record = record.clone();
+    let mut wrong_opening_key =
+        aead::RotatingOpeningKey::new(algorithm, &key_bytes, OneNonceSequence::new(make_nonce()))
+            .unwrap()
+            .rotate(b"epoch 2", OneNonceSequence::new(make_nonce()))
+            .unwrap();
+    assert!(wrong_opening_key
+        .open_in_place(aead::Aad::empty(), &mut wrong_record)
+        .is_err());
+
+    let plaintext = opening_key
+        .open_in_place(aead::Aad::empty(), &mut record)
+        .unwrap();
+    assert_eq!(plaintext, b"hello, world");
+}
+
+#[test]
+fn test_aead_key_rotation_aes_128_gcm() {
+    test_aead_key_rotation_for_algorithm(&aead::AES_128_GCM);
+}
+
+#[test]
+fn test_aead_key_rotation_aes_256_gcm() {
+    test_aead_key_rotation_for_algorithm(&aead::AES_256_GCM);
+}
+
+#[test]
+fn test_aead_key_rotation_chacha20_poly1305() {
+    test_aead_key_rotation_for_algorithm(&aead::CHACHA20_POLY1305);
+}
+
+#[test]
+fn test_aead_derive_key() {
+    let mut master_key = [0u8; 32];
+    for (i, b) in master_key.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let seal = |key: aead::UnboundKey| {
+        let key = aead::LessSafeKey::new(key);
+        let mut in_out = b"hello, world".to_vec();
+        key.seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+            aead::Aad::empty(),
+            &mut in_out,
+        )
+        .unwrap();
+        in_out
+    };
+    let derive =
+        |algorithm, key_id: &[u8]| seal(aead::derive_key(&master_key, algorithm, key_id).unwrap());
+
+    // HKDF-SHA256(salt = "", IKM = master_key, info = "AES_256_GCM" || "key-1").
+    let expected_key =
+        test::from_hex("86675699a41bb63c3dae8b07eb641c32690a3bc40cfd7d453170db109c970868").unwrap();
+    assert_eq!(
+        derive(&aead::AES_256_GCM, b"key-1"),
+        seal(aead::UnboundKey::new(&aead::AES_256_GCM, &expected_key).unwrap())
+    );
+
+    for algorithm in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        assert_eq!(derive(algorithm, b"key-1"), derive(algorithm, b"key-1"));
+        assert_ne!(derive(algorithm, b"key-1"), derive(algorithm, b"key-2"));
+        assert_ne!(derive(algorithm, b""), derive(algorithm, b"key-1"));
+    }
+}
+
+#[test]
+fn test_aead_split_keys() {
+    const KEY: [u8; 32] = [0x42; 32];
+    let nonce = || aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+    let unbound_key = || aead::UnboundKey::new(&aead::CHACHA20_POLY1305, &KEY).unwrap();
+
+    let sealing_key = unbound_key().split_sealing();
+    assert_eq!(sealing_key.algorithm(), &aead::CHACHA20_POLY1305);
+    assert!(format!("{:?}", sealing_key).starts_with("UnboundSealingKey"));
+    let opening_key = aead::UnboundOpeningKey::from(unbound_key());
+    assert_eq!(opening_key.algorithm(), &aead::CHACHA20_POLY1305);
+    assert!(format!("{:?}", opening_key).starts_with("UnboundOpeningKey"));
+
+    let mut sealing_key = aead::SealingKey::new(sealing_key, OneNonceSequence::new(nonce()));
+    let mut opening_key = aead::OpeningKey::new(opening_key, OneNonceSequence::new(nonce()));
+
+    let mut in_out = b"log record".to_vec();
+    sealing_key
+        .seal_in_place_append_tag(aead::Aad::empty(), &mut in_out)
+        .unwrap();
+    let plaintext = opening_key
+        .open_in_place(aead::Aad::empty(), &mut in_out)
+        .unwrap();
+    assert_eq!(plaintext, b"log record");
+
+    // An `UnboundKey` is still accepted directly, and is interchangeable with
+    // its split forms.
+    let mut sealing_key = aead::SealingKey::new(unbound_key(), OneNonceSequence::new(nonce()));
+    let mut in_out2 = b"log record".to_vec();
+    sealing_key
+        .seal_in_place_append_tag(aead::Aad::empty(), &mut in_out2)
+        .unwrap();
+    let mut opening_key: aead::OpeningKey<_> =
+        BoundKey::new(unbound_key(), OneNonceSequence::new(nonce()));
+    let plaintext = opening_key
+        .open_in_place(aead::Aad::empty(), &mut in_out2)
+        .unwrap();
+    assert_eq!(plaintext, b"log record");
+}
+
+#[cfg(feature = "nonce_audit")]
+#[test]
+fn test_aead_audited_nonce_sequence() {
+    use aead::NonceSequence;
+
+    // Returns the nonces 0, 1, 2, 0, 1, 2, ...
+    struct Wrapping(u8);
+
+    impl NonceSequence for Wrapping {
+        fn advance(&mut self) -> Result<aead::Nonce, error::Unspecified> {
+            let mut nonce = [0u8; aead::NONCE_LEN];
+            nonce[aead::NONCE_LEN - 1] = self.0;
+            self.0 = (self.0 + 1) % 3;
+            Ok(aead::Nonce::assume_unique_for_key(nonce))
+        }
+    }
+
+    let mut nonces = aead::AuditedNonceSequence::new(Wrapping(0));
+    for _ in 0..3 {
+        assert!(nonces.advance().is_ok());
+    }
+    assert_eq!(nonces.used_count(), 3);
+    assert_eq!(nonces.check(), Ok(()));
+
+    assert!(nonces.advance().is_err());
+    let reused = nonces.check().unwrap_err();
+    assert_eq!(reused.nonce(), &[0u8; aead::NONCE_LEN]);
+    assert_eq!(nonces.used_count(), 3);
+
+    // Failure is permanent until `reset`.
+    assert!(nonces.advance().is_err());
+    nonces.reset();
+    assert_eq!(nonces.used_count(), 0);
+    assert_eq!(nonces.check(), Ok(()));
+    assert!(nonces.advance().is_ok());
+    assert_eq!(nonces.used_count(), 1);
+
+    // A sequence that fails for other reasons isn't reported as reuse.
+    let mut nonces = aead::AuditedNonceSequence::new(OneNonceSequence::new(
+        aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+    ));
+    {
+        let key = aead::UnboundKey::new(&aead::AES_128_GCM, &[0; 16]).unwrap();
+        let mut key = aead::SealingKey::new(key, &mut nonces);
+        let mut in_out = *b"hello";
+        assert!(key
+            .seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out)
+            .is_ok());
+        assert!(key
+            .seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out)
+            .is_err());
+    }
+    assert_eq!(nonces.used_count(), 1);
+    assert_eq!(nonces.check(), Ok(()));
+}
+
+#[test]
+fn test_aead_strict_aad() {
+    const KEY: [u8; 16] = [0x42; 16];
+    let nonce = || aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]);
+
+    assert!(aead::StrictAad::new(aead::Aad::empty()).is_err());
+    assert!(aead::StrictAad::new(aead::Aad::from(&b""[..])).is_err());
+    let aad = aead::StrictAad::new(aead::Aad::from(b"session 1")).unwrap();
+    assert_eq!(aad.as_ref(), b"session 1");
+
+    // `OneNonceSequence` only allows one use, so these also check that a
+    // rejected AAD doesn't consume a nonce.
+    let mut sealing_key: aead::StrictSealingKey<OneNonceSequence> =
+        make_key(&aead::AES_128_GCM, &KEY, nonce());
+    let mut in_out = b"hello".to_vec();
+    assert!(sealing_key
+        .seal_in_place_append_tag(aead::Aad::empty(), &mut in_out)
+        .is_err());
+    assert!(sealing_key
+        .seal_in_place_separate_tag(aead::Aad::empty(), &mut in_out)
+        .is_err());
+    assert_eq!(in_out, b"hello");
+    sealing_key
+        .seal_in_place_append_tag(aead::Aad::from(b"session 1"), &mut in_out)
+        .unwrap();
+
+    let mut opening_key: aead::StrictOpeningKey<OneNonceSequence> =
+        make_key(&aead::AES_128_GCM, &KEY, nonce());
+    let sealed = in_out.clone();
+    assert!(opening_key
+        .open_in_place(aead::Aad::empty(), &mut in_out)
+        .is_err());
+    assert!(opening_key
+        .open_within(aead::Aad::empty(), &mut in_out, 0..)
+        .is_err());
+    assert_eq!(in_out, sealed);
+    assert_eq!(
+        opening_key
+            .open_in_place(aead::Aad::from(b"session 1"), &mut in_out)
+            .unwrap(),
+        b"hello"
+    );
+
+    // The ciphertext is the same as a non-strict key would produce.
+    let mut expected = b"hello".to_vec();
+    make_key::<aead::SealingKey<OneNonceSequence>>(&aead::AES_128_GCM, &KEY, nonce())
+        .seal_in_place_append_tag(aead::Aad::from(b"session 1"), &mut expected)
+        .unwrap();
+    assert_eq!(sealed, expected);
+}
+
+#[test]
+fn test_aead_committed_chacha20_poly1305() {
+    const KEY: [u8; 32] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f,
+    ];
+    const OTHER_KEY: [u8; 32] = [0x42; 32];
+    let alg = &aead::COMMITTED_CHACHA20_POLY1305;
+    let nonces = || OneNonceSequence::new(aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]));
+    let aad = || aead::Aad::from(b"header");
+
+    assert_eq!(alg.key_len(), 32);
+    assert_eq!(alg.commitment_len(), 32);
+    assert_eq!(alg.tag_len(), aead::MAX_TAG_LEN);
+    assert_eq!(alg.nonce_len(), aead::NONCE_LEN);
+    assert!(aead::CommittedSealingKey::new(alg, &KEY[..31], nonces()).is_err());
+    assert!(aead::CommittedOpeningKey::new(alg, &KEY[..31], nonces()).is_err());
+
+    // Generated independently from the HKDF construction documented on
+    // `COMMITTED_CHACHA20_POLY1305`.
+    let expected = test::from_hex(
+        "42a72951faee3c6380fb100b52a6ac70febcfc417abb8117f01c5ead864ad9ff\
+         ea0e4b0e3f0e27649ff95d63134bf7a0ed5de5ea04f0298d8f021234",
+    )
+    .unwrap();
+
+    let mut sealed = b"hello, world".to_vec();
+    aead::CommittedSealingKey::new(alg, &KEY, nonces())
+        .unwrap()
+        .seal_in_place_append_tag(aad(), &mut sealed)
+        .unwrap();
+    assert_eq!(sealed, expected);
+
+    let mut in_out = sealed.clone();
+    let mut opening_key = aead::CommittedOpeningKey::new(alg, &KEY, nonces()).unwrap();
+    assert_eq!(
+        opening_key.open_in_place(aad(), &mut in_out).unwrap(),
+        b"hello, world"
+    );
+
+    // The same ciphertext can't be opened under a different key. The
+    // commitment is rejected before the nonce is consumed.
+    let mut in_out = sealed.clone();
+    let mut opening_key = aead::CommittedOpeningKey::new(alg, &OTHER_KEY, nonces()).unwrap();
+    assert!(opening_key.open_in_place(aad(), &mut in_out).is_err());
+    assert_eq!(in_out, sealed);
+
+    // Nor with the other key's commitment swapped in.
+    let mut other_sealed = Vec::new();
+    aead::CommittedSealingKey::new(alg, &OTHER_KEY, nonces())
+        .unwrap()
+        .seal_in_place_append_tag(aad(), &mut other_sealed)
+        .unwrap();
+    let mut in_out = other_sealed[..alg.commitment_len()].to_vec();
+    in_out.extend_from_slice(&sealed[alg.commitment_len()..]);
+    assert!(opening_key.open_in_place(aad(), &mut in_out).is_err());
+
+    // Any modification is detected, including one to the commitment.
+    for i in [0, alg.commitment_len(), sealed.len() - 1] {
+        let mut in_out = sealed.clone();
+        in_out[i] ^= 1;
+        let mut opening_key = aead::CommittedOpeningKey::new(alg, &KEY, nonces()).unwrap();
+        assert!(opening_key.open_in_place(aad(), &mut in_out).is_err());
+    }
+    let mut opening_key = aead::CommittedOpeningKey::new(alg, &KEY, nonces()).unwrap();
+    assert!(opening_key
+        .open_in_place(aad(), &mut sealed[..alg.commitment_len() - 1])
+        .is_err());
+
+    // `open_within` with a prefix.
+    let mut in_out = b"prefix".to_vec();
+    in_out.extend_from_slice(&sealed);
+    let mut opening_key = aead::CommittedOpeningKey::new(alg, &KEY, nonces()).unwrap();
+    assert_eq!(
+        opening_key
+            .open_within(aad(), &mut in_out, b"prefix".len()..)
+            .unwrap(),
+        b"hello, world"
+    );
+}
+
+#[test]
+fn test_aead_random_nonce_key() {
+    const KEY: [u8; 32] = [0x42; 32];
+    const LIMIT: u64 = 3;
+    let rng = ring::rand::SystemRandom::new();
+
+    for algorithm in [&aead::AES_256_GCM, &aead::CHACHA20_POLY1305] {
+        let key = aead::UnboundKey::new(algorithm, &KEY).unwrap();
+        let default = aead::RandomNonceKey::new(key, rng.clone());
+        assert_eq!(default.limit(), 1 << 32);
+        assert_eq!(default.invocations(), 0);
+        let key = aead::UnboundKey::new(algorithm, &KEY).unwrap();
+        assert!(aead::RandomNonceKey::with_limit(key, rng.clone(), (1 << 32) + 1).is_err());
+
+        let key = aead::UnboundKey::new(algorithm, &KEY).unwrap();
+        let key = aead::RandomNonceKey::with_limit(key, rng.clone(), LIMIT).unwrap();
+        let less_safe_key = make_less_safe_key(algorithm, &KEY);
+        let mut nonces = Vec::new();
+        for i in 0..LIMIT {
+            let mut in_out = b"hello".to_vec();
+            let nonce = key
+                .seal_append(aead::Aad::from(b"aad"), &mut in_out)
+                .unwrap();
+            assert_eq!(key.invocations(), i + 1);
+            nonces.push(*nonce.as_ref());
+
+            // Interoperates with `LessSafeKey` given the emitted nonce.
+            assert_eq!(
+                less_safe_key
+                    .open_in_place(nonce, aead::Aad::from(b"aad"), &mut in_out)
+                    .unwrap(),
+                b"hello"
+            );
+        }
+        nonces.sort_unstable();
+        nonces.dedup();
+        assert_eq!(nonces.len() as u64, LIMIT);
+
+        // The limit has been reached.
+        let mut in_out = b"hello".to_vec();
+        assert!(key
+            .seal_append(aead::Aad::from(b"aad"), &mut in_out)
+            .is_err());
+        assert_eq!(in_out, b"hello");
+        assert_eq!(key.invocations(), LIMIT);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_aead_random_nonce_key_shared() {
+    use std::{sync::Arc, thread};
+
+    const LIMIT: u64 = 100;
+    let rng = ring::rand::SystemRandom::new();
+    let key = aead::UnboundKey::new(&aead::AES_128_GCM, &[0x42; 16]).unwrap();
+    let key = Arc::new(aead::RandomNonceKey::with_limit(key, rng, LIMIT).unwrap());
+
+    // Between them, the threads try to seal more messages than the limit
+    // allows; exactly `LIMIT` succeed.
+    let threads = (0..4)
+        .map(|_| {
+            let key = Arc::clone(&key);
+            thread::spawn(move || {
+                (0..LIMIT)
+                    .filter(|_| {
+                        let mut in_out = b"hello".to_vec();
+                        key.seal_append(aead::Aad::empty(), &mut in_out).is_ok()
+                    })
+                    .count()
+            })
+        })
+        .collect::<Vec<_>>();
+    let sealed: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+    assert_eq!(sealed as u64, LIMIT);
+    assert_eq!(key.invocations(), LIMIT);
+}
+
+// When authentication fails, no open function may leave the would-be
+// plaintext in the caller's buffer.
+#[test]
+fn test_aead_open_authentication_failure() {
+    const PLAINTEXT: [u8; 100] = [0xa5; 100];
+    const AAD: &[u8] = b"aad";
+    let nonce = || aead::Nonce::assume_unique_for_key([1; aead::NONCE_LEN]);
+    let rng = ring::rand::SystemRandom::new();
+
+    for algorithm in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        let key_bytes = vec![0x42; algorithm.key_len()];
+        let key = make_less_safe_key(algorithm, &key_bytes);
+        let mut ciphertext = PLAINTEXT.to_vec();
+        let tag = key
+            .seal_in_place_separate_tag(nonce(), aead::Aad::from(AAD), &mut ciphertext)
+            .unwrap();
+        let tag = tag.as_ref();
+
+        let mut bad_ciphertext = ciphertext.clone();
+        bad_ciphertext[0] ^= 1;
+        let mut bad_tag = tag.to_vec();
+        bad_tag[aead::MAX_TAG_LEN - 1] ^= 1;
+
+        for (ciphertext, tag, aad) in [
+            (&bad_ciphertext[..], tag, AAD),
+            (&ciphertext[..], &bad_tag[..], AAD),
+            (&ciphertext[..], tag, &b"bad"[..]),
+        ] {
+            let aad = || aead::Aad::from(aad);
+            let sealed = [ciphertext, tag].concat();
+
+            let mut in_out = sealed.clone();
+            let mut opening_key: aead::OpeningKey<_> = make_key(algorithm, &key_bytes, nonce());
+            assert!(opening_key.open_in_place(aad(), &mut in_out).is_err());
+            assert_plaintext_zeroed(&in_out, &sealed, PLAINTEXT.len());
+
+            let mut in_out = sealed.clone();
+            assert!(key.open_in_place(nonce(), aad(), &mut in_out).is_err());
+            assert_plaintext_zeroed(&in_out, &sealed, PLAINTEXT.len());
+
+            let mut in_out = sealed.clone();
+            let mut opening_key: aead::StrictOpeningKey<_> =
+                make_key(algorithm, &key_bytes, nonce());
+            assert!(opening_key.open_in_place(aad(), &mut in_out).is_err());
+            assert_plaintext_zeroed(&in_out, &sealed, PLAINTEXT.len());
+
+            let mut in_out = sealed.clone();
+            let random_nonce_key = aead::RandomNonceKey::new(
+                aead::UnboundKey::new(algorithm, &key_bytes).unwrap(),
+                rng.clone(),
+            );
+            assert!(random_nonce_key
+                .open_in_place(nonce(), aad(), &mut in_out)
+                .is_err());
+            assert_plaintext_zeroed(&in_out, &sealed, PLAINTEXT.len());
+
+            let tag_first = [tag, ciphertext].concat();
+            let mut in_out = tag_first.clone();
+            assert!(key.open_tag_first(nonce(), aad(), &mut in_out).is_err());
+            assert_plaintext_zeroed(&in_out, &tag_first, PLAINTEXT.len());
+
+            for prefix_len in [1, aead::MAX_TAG_LEN, PLAINTEXT.len() + 1] {
+                let with_prefix = [&vec![0x5a; prefix_len][..], &sealed].concat();
+
+                let mut in_out = with_prefix.clone();
+                let mut opening_key: aead::OpeningKey<_> = make_key(algorithm, &key_bytes, nonce());
+                assert!(opening_key
+                    .open_within(aad(), &mut in_out, prefix_len..)
+                    .is_err());
+                assert_plaintext_zeroed(&in_out, &with_prefix, PLAINTEXT.len());
+
+                let mut in_out = with_prefix.clone();
+                assert!(key
+                    .open_within(nonce(), aad(), &mut in_out, prefix_len..)
+                    .is_err());
+                assert_plaintext_zeroed(&in_out, &with_prefix, PLAINTEXT.len());
+
+                let mut in_out = with_prefix.clone();
+                let mut opening_key: aead::StrictOpeningKey<_> =
+                    make_key(algorithm, &key_bytes, nonce());
+                assert!(opening_key
+                    .open_within(aad(), &mut in_out, prefix_len..)
+                    .is_err());
+                assert_plaintext_zeroed(&in_out, &with_prefix, PLAINTEXT.len());
+
+                let without_tag = &with_prefix[..(with_prefix.len() - aead::MAX_TAG_LEN)];
+                let mut in_out = without_tag.to_vec();
+                let tag = aead::Tag::try_from(tag).unwrap();
+                assert!(key
+                    .open_in_place_separate_tag(nonce(), aad(), tag, &mut in_out, prefix_len..)
+                    .is_err());
+                assert_plaintext_zeroed(&in_out, without_tag, PLAINTEXT.len());
+            }
+        }
+    }
+
+    // A committed key zeroes the plaintext when the tag doesn't match.
+    let alg = &aead::COMMITTED_CHACHA20_POLY1305;
+    let mut sealed = PLAINTEXT.to_vec();
+    aead::CommittedSealingKey::new(alg, &[0x42; 32], OneNonceSequence::new(nonce()))
+        .unwrap()
+        .seal_in_place_append_tag(aead::Aad::from(AAD), &mut sealed)
+        .unwrap();
+    let last = sealed.len() - 1;
+    sealed[last] ^= 1;
+    let mut in_out = sealed.clone();
+    assert!(
+        aead::CommittedOpeningKey::new(alg, &[0x42; 32], OneNonceSequence::new(nonce()))
+            .unwrap()
+            .open_in_place(aead::Aad::from(AAD), &mut in_out)
+            .is_err()
+    );
+    assert_plaintext_zeroed(&in_out, &sealed, PLAINTEXT.len());
+
+    // The OpenSSH construction verifies the tag first, so nothing changes.
+    use aead::chacha20_poly1305_openssh::{OpeningKey, SealingKey, KEY_LEN, TAG_LEN};
+    let mut sealed = PLAINTEXT.to_vec();
+    let mut tag = [0; TAG_LEN];
+    SealingKey::new(&[0x42; KEY_LEN]).seal_in_place(7, &mut sealed, &mut tag);
+    tag[0] ^= 1;
+    let mut in_out = sealed.clone();
+    assert!(OpeningKey::new(&[0x42; KEY_LEN])
+        .open_in_place(7, &mut in_out, &tag)
+        .is_err());
+    assert_eq!(in_out, sealed);
+}
+
+fn assert_plaintext_zeroed(in_out: &[u8], original: &[u8], plaintext_len: usize) {
+    assert_eq!(in_out.len(), original.len());
+    assert!(in_out[..plaintext_len].iter().all(|&b| b == 0));
+    assert_eq!(&in_out[plaintext_len..], &original[plaintext_len..]);
+}
+
 fn make_key<K: aead::BoundKey<OneNonceSequence>>(
     algorithm: &'static aead::Algorithm,
     key: &[u8],
@@ -629,3 +1468,172 @@ impl aead::NonceSequence for OneNonceSequence {
         self.0.take().ok_or(error::Unspecified)
     }
 }
+
+#[cfg(feature = "alloc")]
+#[test]
+fn aead_aes_siv() {
+    use aead::aes_siv::{AesSiv128Key, AesSiv256Key};
+
+    test::run(
+        test_file!("aead_aes_siv_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let key = test_case.consume_bytes("KEY");
+            let ad = (0..)
+                .map_while(|i| test_case.consume_optional_string(&format!("AD{}", i)))
+                .map(|ad| match ad.as_str() {
+                    "\"\"" => Vec::new(),
+                    hex => test::from_hex(hex).unwrap(),
+                })
+                .collect::<Vec<_>>();
+            let ad = ad.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let plaintext = test_case.consume_bytes("IN");
+            let expected = test_case.consume_bytes("OUT");
+
+            match key.len() {
+                AesSiv128Key::KEY_LEN => {
+                    let key = AesSiv128Key::new(&key)?;
+                    check_aes_siv(
+                        &|ad, pt| key.seal_multi_ad(ad, pt),
+                        &|ad, ct| key.open_multi_ad(ad, ct),
+                        &ad,
+                        &plaintext,
+                        &expected,
+                    );
+                }
+                AesSiv256Key::KEY_LEN => {
+                    let key = AesSiv256Key::new(&key)?;
+                    check_aes_siv(
+                        &|ad, pt| key.seal_multi_ad(ad, pt),
+                        &|ad, ct| key.open_multi_ad(ad, ct),
+                        &ad,
+                        &plaintext,
+                        &expected,
+                    );
+                }
+                _ => unreachable!(),
+            }
+
+            Ok(())
+        },
+    );
+
+    assert!(AesSiv128Key::new(&[0; 64]).is_err());
+    assert!(AesSiv256Key::new(&[0; 32]).is_err());
+    assert!(AesSiv256Key::new(&[0; 64])
+        .unwrap()
+        .open_multi_ad(&[], &[0; 15])
+        .is_err());
+}
+
+#[cfg(feature = "alloc")]
+type AesSivFn<'a> = dyn Fn(&[&[u8]], &[u8]) -> Result<Vec<u8>, error::Unspecified> + 'a;
+
+#[cfg(feature = "alloc")]
+fn check_aes_siv(
+    seal: &AesSivFn,
+    open: &AesSivFn,
+    ad: &[&[u8]],
+    plaintext: &[u8],
+    expected: &[u8],
+) {
+    assert_eq!(seal(ad, plaintext).unwrap(), expected);
+    assert_eq!(open(ad, expected).unwrap(), plaintext);
+
+    // Any change to the sealed data or the AD is detected.
+    for i in 0..expected.len() {
+        let mut bad = expected.to_vec();
+        bad[i] ^= 1;
+        assert!(open(ad, &bad).is_err());
+    }
+    assert!(open(ad, &expected[..(expected.len() - 1)]).is_err());
+    if let Some((_, fewer)) = ad.split_last() {
+        assert!(open(fewer, expected).is_err());
+    }
+    let mut more = ad.to_vec();
+    more.push(b"");
+    assert!(open(&more, expected).is_err());
+
+    // There is a limit on the number of AD components.
+    if ad.len() == aead::aes_siv::MAX_AD_COMPONENTS {
+        assert!(seal(&more, plaintext).is_err());
+        assert!(open(&more, expected).is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_aead_copy_encrypt() {
+    use std::io::ErrorKind;
+    use test::io::{FailingReader, OneByteReader, PartialWriter};
+
+    const BUFFER_LEN: usize = 64 * 1024;
+    let nonce = || aead::Nonce::assume_unique_for_key([7; aead::NONCE_LEN]);
+    let data = (0..(2 * BUFFER_LEN + 17))
+        .map(|i| i as u8)
+        .collect::<Vec<_>>();
+
+    for algorithm in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        let key_bytes = vec![0x42; algorithm.key_len()];
+        let key = aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, &key_bytes).unwrap());
+        let aad = aead::Aad::from(b"aad");
+
+        for len in [
+            0,
+            1,
+            15,
+            16,
+            17,
+            63,
+            64,
+            65,
+            BUFFER_LEN - 1,
+            BUFFER_LEN,
+            BUFFER_LEN + 1,
+            2 * BUFFER_LEN,
+            data.len(),
+        ] {
+            let plaintext = &data[..len];
+            let mut expected = plaintext.to_vec();
+            key.seal_in_place_append_tag(nonce(), aad, &mut expected)
+                .unwrap();
+
+            let mut actual = Vec::new();
+            let read = aead::copy_encrypt(&key, nonce(), aad, plaintext, &mut actual).unwrap();
+            assert_eq!(read, len as u64);
+            assert_eq!(actual, expected);
+
+            let mut opened = actual.clone();
+            assert_eq!(
+                key.open_in_place(nonce(), aad, &mut opened).unwrap(),
+                plaintext
+            );
+        }
+
+        // Short reads, interrupted reads and writes, and partial writes.
+        let mut expected = data.clone();
+        key.seal_in_place_append_tag(nonce(), aad, &mut expected)
+            .unwrap();
+        for max_write in [1, 13, BUFFER_LEN + 1] {
+            let mut w = PartialWriter::new(max_write);
+            let read =
+                aead::copy_encrypt(&key, nonce(), aad, OneByteReader::new(&data[..]), &mut w)
+                    .unwrap();
+            assert_eq!(read, data.len() as u64);
+            assert_eq!(w.written, expected);
+        }
+
+        // I/O errors are distinguished from cryptographic errors.
+        let r = FailingReader::new(&data[..], ErrorKind::BrokenPipe);
+        match aead::copy_encrypt(&key, nonce(), aad, r, std::io::sink()) {
+            Err(aead::CopyError::Io(e)) => assert_eq!(e.kind(), ErrorKind::BrokenPipe),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    test::compile_time_assert_std_error_error::<aead::CopyError>();
+}
@@ -20,7 +20,7 @@ wasm_bindgen_test_configure!(run_in_browser);
 
 extern crate alloc;
 
-use ring::{agreement, error, rand, test, test_file};
+use ring::{agreement, digest, error, hkdf, rand, test, test_file};
 
 #[test]
 fn agreement_traits() {
@@ -82,6 +82,16 @@ fn agreement_agree_ephemeral() {
         match test_case.consume_optional_string("Error") {
             None => {
                 let my_private = test_case.consume_bytes("D");
+                let my_private_once = {
+                    let bytes = [&my_private[..]];
+                    let rng = test::rand::FixedSliceSequenceRandom {
+                        bytes: &bytes,
+                        current: core::cell::UnsafeCell::new(0),
+                    };
+                    // `rng` is dropped by `generate_once`, which checks that it
+                    // was used exactly once.
+                    agreement::EphemeralPrivateKey::generate_once(alg, rng)?
+                };
                 let my_private = {
                     let rng = test::rand::FixedSliceRandom { bytes: &my_private };
                     agreement::EphemeralPrivateKey::generate(alg, &rng)?
@@ -89,6 +99,11 @@ fn agreement_agree_ephemeral() {
                 let my_public = test_case.consume_bytes("MyQ");
                 let output = test_case.consume_bytes("Output");
 
+                assert_eq!(
+                    my_private_once.compute_public_key().unwrap().as_ref(),
+                    &my_public[..]
+                );
+
                 assert_eq!(my_private.algorithm(), alg);
 
                 let computed_public = my_private.compute_public_key().unwrap();
@@ -96,6 +111,8 @@ fn agreement_agree_ephemeral() {
 
                 assert_eq!(my_private.algorithm(), alg);
 
+                assert!(peer_public.validate_point().is_ok());
+
                 let result = agreement::agree_ephemeral(my_private, &peer_public, |key_material| {
                     assert_eq!(key_material, &output[..]);
                 });
@@ -103,6 +120,8 @@ fn agreement_agree_ephemeral() {
             }
 
             Some(_) => {
+                assert!(peer_public.validate_point().is_err());
+
                 // In the no-heap mode, some algorithms aren't supported so
                 // we have to skip those algorithms' test cases.
                 let dummy_private_key = agreement::EphemeralPrivateKey::generate(alg, &rng)?;
@@ -177,6 +196,171 @@ fn test_agreement_ecdh_x25519_rfc_iterated() {
     }
 }
 
+#[test]
+fn agreement_pinned_public_key() {
+    let rng = rand::SystemRandom::new();
+
+    // The DER encoding of a `SubjectPublicKeyInfo` without its public key.
+    for (alg, spki_prefix) in [
+        (&agreement::X25519, "302a300506032b656e032100"),
+        (
+            &agreement::ECDH_P256,
+            "3059301306072a8648ce3d020106082a8648ce3d030107034200",
+        ),
+        (
+            &agreement::ECDH_P384,
+            "3076301006072a8648ce3d020106052b81040022036200",
+        ),
+    ] {
+        let peer_private_key = agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+        let peer_public_key = peer_private_key.compute_public_key().unwrap();
+        let spki = [&h(spki_prefix)[..], peer_public_key.as_ref()].concat();
+        let fingerprint: [u8; 32] = digest::digest(&digest::SHA256, &spki)
+            .as_ref()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            peer_public_key.spki_fingerprint().as_ref(),
+            &fingerprint[..]
+        );
+        let peer_public_key = agreement::UnparsedPublicKey::new(alg, peer_public_key);
+
+        let my_private_key = || agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+
+        let pin = agreement::PinnedPublicKey::new(alg, &fingerprint);
+        assert_eq!(pin.algorithm(), alg);
+        assert_eq!(
+            pin.agree_ephemeral(my_private_key(), &peer_public_key, |_| ()),
+            Ok(())
+        );
+
+        let mut wrong_fingerprint = fingerprint;
+        wrong_fingerprint[0] ^= 1;
+        let wrong_pin = agreement::PinnedPublicKey::new(alg, &wrong_fingerprint);
+        assert_eq!(
+            wrong_pin.agree_ephemeral(my_private_key(), &peer_public_key, |_| ()),
+            Err(agreement::PinnedKeyError::KeyMismatch)
+        );
+
+        // The algorithm is part of the pin.
+        let other_alg = if alg == &agreement::X25519 {
+            &agreement::ECDH_P256
+        } else {
+            &agreement::X25519
+        };
+        let other_pin = agreement::PinnedPublicKey::new(other_alg, &fingerprint);
+        assert_eq!(
+            other_pin.agree_ephemeral(my_private_key(), &peer_public_key, |_| ()),
+            Err(agreement::PinnedKeyError::KeyMismatch)
+        );
+
+        // A matching peer key with the wrong private key is still rejected.
+        let other_private_key = agreement::EphemeralPrivateKey::generate(other_alg, &rng).unwrap();
+        assert_eq!(
+            pin.agree_ephemeral(other_private_key, &peer_public_key, |_| ()),
+            Err(agreement::PinnedKeyError::Unspecified)
+        );
+    }
+}
+
+#[test]
+fn agreement_ecdh_p256_compact() {
+    assert_ne!(&agreement::ECDH_P256, &agreement::ECDH_P256_COMPACT);
+    assert_eq!(
+        format!("{:?}", agreement::ECDH_P256),
+        "Algorithm { curve: P256 }"
+    );
+    assert_eq!(
+        format!("{:?}", agreement::ECDH_P256_COMPACT),
+        "Algorithm { curve: P256Compact }"
+    );
+
+    let seed = h("f1a2ac7b8b39c4fb0d4f3fcc2a59efe5b6b24b6a5a2ed1e1d8e3fbd3b1f70fb1");
+    let compute_public_key = |alg| {
+        let rng = test::rand::FixedSliceRandom { bytes: &seed };
+        let private_key = agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+        private_key.compute_public_key().unwrap().as_ref().to_vec()
+    };
+    let uncompressed = compute_public_key(&agreement::ECDH_P256);
+    let compact = compute_public_key(&agreement::ECDH_P256_COMPACT);
+    assert_eq!(compact, &uncompressed[1..33]);
+
+    // Keys can't be mixed between the two encodings.
+    let rng = rand::SystemRandom::new();
+    let private_key =
+        agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+    let peer_public_key = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256_COMPACT, compact);
+    assert_eq!(
+        agreement::agree_ephemeral(private_key, &peer_public_key, |_| ()),
+        Err(error::Unspecified)
+    );
+}
+
+#[test]
+fn agreement_ecdh_brainpoolp384r1_roundtrip() {
+    let alg = &agreement::ECDH_BRAINPOOLP384R1;
+    assert_ne!(alg, &agreement::ECDH_P384);
+
+    let rng = rand::SystemRandom::new();
+    let a = agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+    let b = agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+    let a_public = a.compute_public_key().unwrap();
+    let b_public = b.compute_public_key().unwrap();
+    assert_eq!(a_public.as_ref().len(), 1 + 2 * 48);
+
+    let agree = |private_key, peer_public_key: &agreement::PublicKey| {
+        let peer_public_key = agreement::UnparsedPublicKey::new(alg, peer_public_key.as_ref());
+        agreement::agree_ephemeral(private_key, &peer_public_key, |secret| secret.to_vec()).unwrap()
+    };
+    assert_eq!(agree(a, &b_public), agree(b, &a_public));
+}
+
+#[test]
+fn agreement_compute_public_key_into() {
+    const _: () = assert!(agreement::X25519_PUBLIC_KEY_LEN == 32);
+    const _: () = assert!(agreement::ECDH_P256_PUBLIC_KEY_LEN == 65);
+    const _: () = assert!(agreement::ECDH_P256_COMPACT_PUBLIC_KEY_LEN == 32);
+    const _: () = assert!(agreement::ECDH_P384_PUBLIC_KEY_LEN == 97);
+    const _: () = assert!(agreement::ECDH_BRAINPOOLP384R1_PUBLIC_KEY_LEN == 97);
+
+    let rng = rand::SystemRandom::new();
+
+    for (alg, expected_len) in [
+        (&agreement::X25519, agreement::X25519_PUBLIC_KEY_LEN),
+        (&agreement::ECDH_P256, agreement::ECDH_P256_PUBLIC_KEY_LEN),
+        (
+            &agreement::ECDH_P256_COMPACT,
+            agreement::ECDH_P256_COMPACT_PUBLIC_KEY_LEN,
+        ),
+        (&agreement::ECDH_P384, agreement::ECDH_P384_PUBLIC_KEY_LEN),
+        (
+            &agreement::ECDH_BRAINPOOLP384R1,
+            agreement::ECDH_BRAINPOOLP384R1_PUBLIC_KEY_LEN,
+        ),
+    ] {
+        assert!(expected_len <= agreement::MAX_PUBLIC_KEY_LEN);
+
+        let private_key = agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
+        let public_key = private_key.compute_public_key().unwrap();
+        assert_eq!(public_key.as_ref().len(), expected_len);
+
+        let mut out = [0u8; agreement::MAX_PUBLIC_KEY_LEN];
+        let len = private_key.compute_public_key_into(&mut out).unwrap();
+        assert_eq!(len, expected_len);
+        assert_eq!(&out[..len], public_key.as_ref());
+
+        let mut exact = [0u8; agreement::MAX_PUBLIC_KEY_LEN];
+        assert_eq!(
+            private_key.compute_public_key_into(&mut exact[..expected_len]),
+            Ok(expected_len)
+        );
+        assert_eq!(
+            private_key.compute_public_key_into(&mut exact[..(expected_len - 1)]),
+            Err(error::Unspecified)
+        );
+    }
+}
+
 fn x25519(private_key: &[u8], public_key: &[u8]) -> Vec<u8> {
     x25519_(private_key, public_key).unwrap()
 }
@@ -199,14 +383,146 @@ fn h(s: &str) -> Vec<u8> {
     }
 }
 
+#[test]
+fn agreement_validate_point() {
+    let rng = rand::SystemRandom::new();
+
+    test::run(
+        test_file!("agreement_validate_point_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let alg = alg_from_curve_name(&curve_name);
+            let peer_public =
+                agreement::UnparsedPublicKey::new(alg, test_case.consume_bytes("PeerQ"));
+            let expected_result = test_case.consume_string("Result");
+
+            let result = peer_public.validate_point();
+            let actual_result = match result {
+                Ok(()) => "Valid".to_owned(),
+                Err(e) => e.to_string(),
+            };
+            assert_eq!(actual_result, expected_result);
+
+            // Key agreement must agree with `validate_point`.
+            let private_key = agreement::EphemeralPrivateKey::generate(alg, &rng)?;
+            assert_eq!(
+                agreement::agree_ephemeral(private_key, &peer_public, |_| ()).is_ok(),
+                result.is_ok()
+            );
+
+            Ok(())
+        },
+    );
+}
+
 fn alg_from_curve_name(curve_name: &str) -> &'static agreement::Algorithm {
     if curve_name == "P-256" {
         &agreement::ECDH_P256
+    } else if curve_name == "P-256 compact" {
+        &agreement::ECDH_P256_COMPACT
     } else if curve_name == "P-384" {
         &agreement::ECDH_P384
+    } else if curve_name == "brainpoolP384r1" {
+        &agreement::ECDH_BRAINPOOLP384R1
     } else if curve_name == "X25519" {
         &agreement::X25519
     } else {
         panic!("Unsupported curve: {}", curve_name);
     }
 }
+
+#[test]
+fn agreement_cascade_ecdh() {
+    // RFC 7748 Section 6.1's Alice and Bob, and a third party.
+    let private_keys = [
+        h("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"),
+        h("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"),
+        h("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20"),
+    ];
+    let public_keys = [
+        h("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"),
+        h("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"),
+        h("07a37cbc142093c8b755dc1b10e86cb426374ad16aa853ed0bdfc0b2b86d1c7c"),
+    ]
+    .map(|public_key| agreement::UnparsedPublicKey::new(&agreement::X25519, public_key));
+    let private_key = |i: usize| {
+        let rng = test::rand::FixedSliceRandom {
+            bytes: &private_keys[i],
+        };
+        agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap()
+    };
+
+    // Each party agrees with the next one.
+    let parties = || (0..3).map(|i| (private_key(i), &public_keys[(i + 1) % 3]));
+    assert_eq!(
+        agreement::cascade_ecdh(parties(), b"ring cascade test"),
+        Ok(
+            h("80696d41686b2bb03c487370f636a9f3abc4747be7f4b2ea68d3268b81975692")
+                .try_into()
+                .unwrap()
+        )
+    );
+    assert_ne!(
+        agreement::cascade_ecdh(parties(), b"other info"),
+        agreement::cascade_ecdh(parties(), b"ring cascade test")
+    );
+
+    // A single agreement is HKDF over its shared secret.
+    let expected = agreement::agree_ephemeral(private_key(0), &public_keys[1], |ss| {
+        let mut key = [0; agreement::CASCADE_KEY_LEN];
+        hkdf::Salt::new(hkdf::HKDF_SHA256, &[])
+            .extract(ss)
+            .expand(&[b"info"], hkdf::HKDF_SHA256)
+            .unwrap()
+            .fill(&mut key)
+            .unwrap();
+        key
+    });
+    assert_eq!(
+        agreement::cascade_ecdh([(private_key(0), &public_keys[1])], b"info"),
+        expected
+    );
+
+    // At least one agreement is required.
+    let no_parties: [(
+        agreement::EphemeralPrivateKey,
+        &agreement::UnparsedPublicKey<Vec<u8>>,
+    ); 0] = [];
+    assert_eq!(
+        agreement::cascade_ecdh(no_parties, b""),
+        Err(error::Unspecified)
+    );
+
+    // Every agreement must succeed.
+    let invalid = agreement::UnparsedPublicKey::new(&agreement::X25519, vec![0; 32]);
+    assert_eq!(
+        agreement::cascade_ecdh(
+            [
+                (private_key(0), &public_keys[1]),
+                (private_key(1), &invalid)
+            ],
+            b""
+        ),
+        Err(error::Unspecified)
+    );
+
+    // Only X25519 is supported.
+    let rng = rand::SystemRandom::new();
+    let p256_private_key =
+        agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+    let p256_public_key = agreement::UnparsedPublicKey::new(
+        &agreement::ECDH_P256,
+        agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+            .unwrap()
+            .compute_public_key()
+            .unwrap()
+            .as_ref()
+            .to_vec(),
+    );
+    assert_eq!(
+        agreement::cascade_ecdh([(p256_private_key, &p256_public_key)], b""),
+        Err(error::Unspecified)
+    );
+}
@@ -0,0 +1,77 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "std")]
+
+use ring::{cms, digest, test, test_file};
+use std::io;
+
+// 1.2.840.113549.1.7.1
+const ID_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+
+struct FailingReader;
+
+impl io::Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+#[test]
+fn cms_verify_detached() {
+    test::run(
+        test_file!("cms_verify_detached_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let spki = test_case.consume_bytes("SPKI");
+            let content = test_case.consume_bytes("Content");
+            let signed_data = test_case.consume_bytes("SignedData");
+
+            let result = cms::verify_detached(&signed_data, &content[..], &spki);
+            let info = match test_case.check_result(result) {
+                Some(info) => info,
+                None => return Ok(()),
+            };
+
+            let digest_alg = test_case.consume_digest_alg("Digest").unwrap();
+            let signature_alg = test_case.consume_string("SignatureAlgorithm");
+            assert_eq!(info.content_type(), ID_DATA);
+            assert_eq!(info.digest_algorithm(), digest_alg);
+            assert_eq!(
+                info.message_digest().as_ref(),
+                digest::digest(digest_alg, &content).as_ref()
+            );
+            assert_eq!(format!("{:?}", info.signature_algorithm()), signature_alg);
+
+            // The content is streamed, so it may be read in pieces.
+            let chained = io::Read::chain(&content[..1], &content[1..]);
+            assert!(cms::verify_detached(&signed_data, chained, &spki).is_ok());
+
+            assert_eq!(
+                cms::verify_detached(&signed_data, FailingReader, &spki).unwrap_err(),
+                cms::Error::Io(io::ErrorKind::BrokenPipe)
+            );
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn cms_verify_detached_rejects_malformed_spki() {
+    assert_eq!(
+        cms::verify_detached(&[], &b""[..], &[0x30, 0x00]).unwrap_err(),
+        cms::Error::InvalidSignerKey
+    );
+}
@@ -0,0 +1,73 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::cpu::CpuFeatures;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[test]
+fn cpu_features_debug_test() {
+    let features = CpuFeatures::current();
+    let debug = format!("{:?}", features);
+    assert!(debug.starts_with("CpuFeatures"));
+
+    #[cfg(target_arch = "x86_64")]
+    assert_eq!(
+        debug,
+        format!(
+            "CpuFeatures {{ aes_ni: {}, sha_ni: {}, avx2: {}, pclmulqdq: {} }}",
+            features.has_aes_ni(),
+            features.has_sha_ni(),
+            features.has_avx2(),
+            features.has_pclmulqdq()
+        )
+    );
+}
+
+// The features must agree with the standard library's detection, which also
+// takes the operating system's support into account.
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn cpu_features_x86_64_test() {
+    let features = CpuFeatures::current();
+    assert_eq!(features.has_aes_ni(), std::is_x86_feature_detected!("aes"));
+    assert_eq!(features.has_sha_ni(), std::is_x86_feature_detected!("sha"));
+    assert_eq!(features.has_avx2(), std::is_x86_feature_detected!("avx2"));
+    assert_eq!(
+        features.has_pclmulqdq(),
+        std::is_x86_feature_detected!("pclmulqdq")
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn cpu_features_aarch64_test() {
+    let features = CpuFeatures::current();
+    // Every AArch64 CPU has NEON.
+    assert!(features.has_neon());
+    if !cfg!(feature = "unstable-testing-arm-no-hw") {
+        assert_eq!(
+            features.has_pmull(),
+            std::arch::is_aarch64_feature_detected!("pmull")
+        );
+        assert_eq!(
+            features.has_sha2(),
+            std::arch::is_aarch64_feature_detected!("sha2")
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{
+    agreement, derive, digest, error, hkdf, rand,
+    signature::{self, KeyPair},
+    test, test_file,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[test]
+fn derive_test() {
+    let rng = rand::SystemRandom::new();
+
+    test::run(test_file!("derive_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let alg = match test_case.consume_digest_alg("Hash").unwrap() {
+            alg if alg == &digest::SHA256 => hkdf::HKDF_SHA256,
+            alg if alg == &digest::SHA384 => hkdf::HKDF_SHA384,
+            alg if alg == &digest::SHA512 => hkdf::HKDF_SHA512,
+            alg => unreachable!("{:?}", alg),
+        };
+        let master = hkdf::Prk::new_less_safe(alg, &test_case.consume_bytes("Master"));
+        let label = test_case.consume_bytes("Label");
+        let ed25519_public_key = test_case.consume_bytes("Ed25519PublicKey");
+        let x25519_public_key = test_case.consume_bytes("X25519PublicKey");
+        let p256_public_key = test_case.consume_bytes("P256PublicKey");
+
+        let key_pair = derive::derive_ed25519(&master, &label)?;
+        assert_eq!(key_pair.public_key().as_ref(), &ed25519_public_key[..]);
+
+        let private_key = derive::derive_x25519_static(&master, &label)?;
+        assert_eq!(
+            private_key.compute_public_key()?.as_ref(),
+            &x25519_public_key[..]
+        );
+
+        for signing_alg in [
+            &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+        ] {
+            let key_pair = derive::derive_ecdsa_p256(&master, &label, signing_alg, &rng)?;
+            assert_eq!(key_pair.public_key().as_ref(), &p256_public_key[..]);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn derive_sign_and_agree_test() {
+    let rng = rand::SystemRandom::new();
+    let master = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"master secret");
+    const MESSAGE: &[u8] = b"hello, world";
+
+    let key_pair = derive::derive_ed25519(&master, b"ed25519").unwrap();
+    let sig = key_pair.sign(MESSAGE);
+    let public_key =
+        signature::UnparsedPublicKey::new(&signature::ED25519, key_pair.public_key().as_ref());
+    assert!(public_key.verify(MESSAGE, sig.as_ref()).is_ok());
+
+    let key_pair = derive::derive_ecdsa_p256(
+        &master,
+        b"ecdsa",
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        &rng,
+    )
+    .unwrap();
+    let sig = key_pair.sign(&rng, MESSAGE).unwrap();
+    let public_key = signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_ASN1,
+        key_pair.public_key().as_ref(),
+    );
+    assert!(public_key.verify(MESSAGE, sig.as_ref()).is_ok());
+
+    // Two parties that derive static keys from their own masters agree.
+    let other_master = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"other secret");
+    let agree = |master: &hkdf::Prk, peer_public_key: &agreement::PublicKey| {
+        let private_key = derive::derive_x25519_static(master, b"x25519").unwrap();
+        let peer_public_key =
+            agreement::UnparsedPublicKey::new(&agreement::X25519, peer_public_key.as_ref());
+        agreement::agree_ephemeral(private_key, &peer_public_key, |secret| secret.to_vec()).unwrap()
+    };
+    let public_key = derive::derive_x25519_static(&master, b"x25519")
+        .unwrap()
+        .compute_public_key()
+        .unwrap();
+    let other_public_key = derive::derive_x25519_static(&other_master, b"x25519")
+        .unwrap()
+        .compute_public_key()
+        .unwrap();
+    assert_ne!(public_key.as_ref(), other_public_key.as_ref());
+    assert_eq!(
+        agree(&master, &other_public_key),
+        agree(&other_master, &public_key)
+    );
+}
+
+#[test]
+fn derive_independence_test() {
+    let master = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"master secret");
+
+    let ed25519 = |label: &[u8]| {
+        derive::derive_ed25519(&master, label)
+            .unwrap()
+            .public_key()
+            .as_ref()
+            .to_vec()
+    };
+    assert_eq!(ed25519(b"a"), ed25519(b"a"));
+    assert_ne!(ed25519(b"a"), ed25519(b"b"));
+    assert_ne!(ed25519(b""), ed25519(b"\0"));
+}
+
+#[test]
+fn derive_ecdsa_p256_wrong_curve_test() {
+    let rng = rand::SystemRandom::new();
+    let master = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"master secret");
+    for alg in [
+        &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+    ] {
+        assert_eq!(
+            derive::derive_ecdsa_p256(&master, b"label", alg, &rng).err(),
+            Some(error::Unspecified)
+        );
+    }
+}
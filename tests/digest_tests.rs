@@ -46,6 +46,131 @@ fn digest_misc() {
     });
 }
 
+#[test]
+fn digest_finish_into() {
+    for alg in [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(b"hello");
+        let hello = digest::digest(alg, b"hello");
+        let len = alg.output_len();
+
+        // Short buffers are rejected without being written to.
+        let mut out = [0xaa; digest::MAX_OUTPUT_LEN + 1];
+        assert!(ctx.finish_copy_into(&mut out[..(len - 1)]).is_err());
+        assert!(ctx.clone().finish_into(&mut out[..(len - 1)]).is_err());
+        assert!(out.iter().all(|&b| b == 0xaa));
+        assert!(ctx.clone().finish_into(&mut []).is_err());
+
+        // Only the first `output_len` bytes of a long buffer are written.
+        assert_eq!(ctx.finish_copy_into(&mut out), Ok(len));
+        assert_eq!(&out[..len], hello.as_ref());
+        assert!(out[len..].iter().all(|&b| b == 0xaa));
+
+        // The context is still usable after `finish_copy_into`.
+        ctx.update(b", world");
+        let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+        assert_eq!(ctx.finish_into(&mut out[..len]), Ok(len));
+        assert_eq!(&out[..len], digest::digest(alg, b"hello, world").as_ref());
+    }
+}
+
+#[test]
+fn digest_reset() {
+    let algs = [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ];
+    for alg in algs {
+        let mut ctx = digest::Context::new(alg);
+        // Leave some data pending and some in completed blocks.
+        ctx.update(&[0x5a; 200]);
+        ctx.reset();
+        assert!(core::ptr::eq(ctx.algorithm(), alg));
+        ctx.update(b"hello");
+        assert_eq!(
+            ctx.clone().finish().as_ref(),
+            digest::digest(alg, b"hello").as_ref()
+        );
+
+        for other in algs {
+            let mut ctx = ctx.clone();
+            assert_eq!(ctx.reset_with_algorithm(other), Ok(()));
+            assert!(core::ptr::eq(ctx.algorithm(), other));
+            ctx.update(b"world");
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(other, b"world").as_ref()
+            );
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn digest_update_vectored() {
+    use std::io::IoSlice;
+
+    for alg in [&digest::SHA256, &digest::SHA512] {
+        let block_len = alg.block_len();
+        let data = (0..(3 * block_len + 7))
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let expected = digest::digest(alg, &data);
+
+        // Split the data into three slices, with each boundary at every offset
+        // within its block.
+        for first in 0..block_len {
+            for second in block_len..(2 * block_len) {
+                let mut ctx = digest::Context::new(alg);
+                ctx.update_vectored(&[
+                    IoSlice::new(&data[..first]),
+                    IoSlice::new(&data[first..second]),
+                    IoSlice::new(&data[second..]),
+                ]);
+                assert_eq!(ctx.finish().as_ref(), expected.as_ref());
+            }
+        }
+
+        let mut ctx = digest::Context::new(alg);
+        ctx.update_vectored(&[]);
+        assert_eq!(ctx.finish().as_ref(), digest::digest(alg, b"").as_ref());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn digest_reader() {
+    use std::io::ErrorKind;
+    use test::io::{FailingReader, OneByteReader};
+
+    // Longer than the 64 KiB internal buffer.
+    let data = (0..(64 * 1024 + 1000)).map(|i| i as u8).collect::<Vec<_>>();
+
+    for alg in [&digest::SHA256, &digest::SHA512] {
+        for len in [0, 1, alg.block_len() + 1, data.len()] {
+            let expected = digest::digest(alg, &data[..len]);
+            let actual = digest::digest_reader(alg, &data[..len]).unwrap();
+            assert_eq!(actual.as_ref(), expected.as_ref());
+        }
+
+        let actual = digest::digest_reader(alg, OneByteReader::new(&data[..])).unwrap();
+        assert_eq!(actual.as_ref(), digest::digest(alg, &data).as_ref());
+
+        let err = digest::digest_reader(alg, FailingReader::new(&data[..], ErrorKind::BrokenPipe))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+}
+
 mod digest_shavs {
     use ring::{digest, test};
 
@@ -68,6 +193,15 @@ mod digest_shavs {
             let actual = digest::digest(digest_alg, &msg);
             assert_eq!(&expected, &actual.as_ref());
 
+            let mut ctx = digest::Context::new(digest_alg);
+            ctx.update(&msg);
+            let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+            assert_eq!(ctx.finish_copy_into(&mut out), Ok(expected.len()));
+            assert_eq!(&expected, &out[..expected.len()]);
+            let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+            assert_eq!(ctx.finish_into(&mut out), Ok(expected.len()));
+            assert_eq!(&expected, &out[..expected.len()]);
+
             Ok(())
         });
     }
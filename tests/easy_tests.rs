@@ -0,0 +1,147 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{digest, easy, hmac, test, test_file};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[test]
+fn easy_digest_test() {
+    assert_eq!(
+        &easy::sha256(b"abc")[..],
+        &test::from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+            .unwrap()[..]
+    );
+
+    let input = [0x5a; 300];
+    for len in 0..input.len() {
+        let input = &input[..len];
+        assert_eq!(
+            &easy::sha256(input)[..],
+            digest::digest(&digest::SHA256, input).as_ref()
+        );
+        assert_eq!(
+            &easy::sha512(input)[..],
+            digest::digest(&digest::SHA512, input).as_ref()
+        );
+    }
+}
+
+#[test]
+fn easy_hmac_test() {
+    test::run(test_file!("hmac_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let digest_alg = test_case.consume_digest_alg("HMAC");
+        let key = test_case.consume_bytes("Key");
+        let input = test_case.consume_bytes("Input");
+        let output = test_case.consume_bytes("Output");
+        if digest_alg != Some(&digest::SHA256) {
+            return Ok(());
+        }
+
+        let tag = easy::hmac_sha256(&key, &input);
+        assert_eq!(&tag[..], &output[..]);
+        assert_eq!(
+            &tag[..],
+            hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), &input).as_ref()
+        );
+
+        assert!(easy::hmac_verify_sha256(&key, &input, &output));
+        assert!(!easy::hmac_verify_sha256(&key, &input, &output[1..]));
+        let mut bad = output.clone();
+        bad[0] ^= 1;
+        assert!(!easy::hmac_verify_sha256(&key, &input, &bad));
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "alloc")]
+use ring::{aead, error};
+
+#[cfg(feature = "alloc")]
+#[test]
+fn easy_aead_test() {
+    for algorithm in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        let key = vec![0x42; algorithm.key_len()];
+        let aad = b"header";
+
+        for len in [0, 1, 15, 16, 17, 100] {
+            let plaintext = vec![0xa5; len];
+            let sealed = easy::aead_seal(algorithm, &key, aad, &plaintext).unwrap();
+            assert_eq!(
+                sealed.len(),
+                aead::NONCE_LEN + plaintext.len() + algorithm.tag_len()
+            );
+
+            // The output is the nonce, then what `LessSafeKey` would output.
+            let (nonce, ciphertext_and_tag) = sealed.split_at(aead::NONCE_LEN);
+            let less_safe_key =
+                aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, &key).unwrap());
+            let mut expected = plaintext.clone();
+            less_safe_key
+                .seal_in_place_append_tag(
+                    aead::Nonce::try_assume_unique_for_key(nonce).unwrap(),
+                    aead::Aad::from(aad),
+                    &mut expected,
+                )
+                .unwrap();
+            assert_eq!(ciphertext_and_tag, &expected[..]);
+
+            assert_eq!(
+                easy::aead_open(algorithm, &key, aad, &sealed).as_deref(),
+                Ok(&plaintext[..])
+            );
+
+            // A random nonce is generated for each message.
+            let sealed_again = easy::aead_seal(algorithm, &key, aad, &plaintext).unwrap();
+            assert_ne!(sealed[..aead::NONCE_LEN], sealed_again[..aead::NONCE_LEN]);
+
+            // Tampering with the nonce, ciphertext, tag, or AAD is detected.
+            for i in 0..sealed.len() {
+                let mut bad = sealed.clone();
+                bad[i] ^= 1;
+                assert_eq!(
+                    easy::aead_open(algorithm, &key, aad, &bad),
+                    Err(error::Unspecified)
+                );
+            }
+            assert_eq!(
+                easy::aead_open(algorithm, &key, b"other", &sealed),
+                Err(error::Unspecified)
+            );
+        }
+
+        // Truncated inputs are rejected.
+        let sealed = easy::aead_seal(algorithm, &key, aad, b"").unwrap();
+        for len in 0..sealed.len() {
+            assert_eq!(
+                easy::aead_open(algorithm, &key, aad, &sealed[..len]),
+                Err(error::Unspecified)
+            );
+        }
+
+        // Keys of the wrong length are rejected.
+        assert!(easy::aead_seal(algorithm, &key[1..], aad, b"").is_err());
+        assert!(easy::aead_open(algorithm, &key[1..], aad, &sealed).is_err());
+    }
+}
@@ -13,7 +13,7 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use ring::{
-    rand,
+    error, rand,
     signature::{self, KeyPair},
     test, test_file,
 };
@@ -110,6 +110,187 @@ fn ecdsa_generate_pkcs8_test() {
     }
 }
 
+// The expected outputs don't depend on the target, so running this test on
+// both 32-bit and 64-bit targets checks that serialization doesn't depend on
+// the limb size.
+#[test]
+fn ecdsa_serialization_test() {
+    test::run(
+        test_file!("ecdsa_serialization_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let algs = match curve_name.as_str() {
+                "P-256" => [
+                    &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                ],
+                "P-384" => [
+                    &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                    &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                ],
+                _ => unreachable!(),
+            };
+            let private_key = test_case.consume_bytes("PrivateKey");
+            let pkcs8 = test_case.consume_bytes("PKCS8");
+            let sec1 = test_case.consume_bytes("SEC1");
+            let sec1_minimal = test_case.consume_bytes("SEC1Minimal");
+
+            for alg in algs {
+                // The RNG is only used for signing nonces, so it mustn't
+                // affect the serialization.
+                let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+                    alg,
+                    &pkcs8,
+                    &test::rand::FixedByteRandom { byte: 0 },
+                )
+                .unwrap();
+                let key_pairs = [
+                    signature::EcdsaKeyPair::from_private_key_and_public_key(
+                        alg,
+                        &private_key,
+                        key_pair.public_key().as_ref(),
+                        &test::rand::FixedByteRandom { byte: 1 },
+                    )
+                    .unwrap(),
+                    signature::EcdsaKeyPair::from_sec1(
+                        alg,
+                        &sec1_minimal,
+                        &rand::SystemRandom::new(),
+                    )
+                    .unwrap(),
+                    key_pair,
+                ];
+
+                for key_pair in &key_pairs {
+                    for _ in 0..2 {
+                        assert_eq!(key_pair.to_pkcs8().as_ref(), &pkcs8[..]);
+                        #[cfg(feature = "alloc")]
+                        {
+                            use signature::EcdsaPublicKeyInclusion::{Include, Omit};
+                            assert_eq!(&key_pair.to_sec1(Include)[..], &sec1[..]);
+                            assert_eq!(&key_pair.to_sec1(Omit)[..], &sec1_minimal[..]);
+                        }
+                    }
+                }
+
+                let rng = rand::SystemRandom::new();
+                let key_pair = signature::EcdsaKeyPair::from_sec1(alg, &sec1, &rng).unwrap();
+                assert_eq!(key_pair.to_pkcs8().as_ref(), &pkcs8[..]);
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn ecdsa_to_pkcs8_matches_generate_pkcs8_test() {
+    let rng = rand::SystemRandom::new();
+
+    for alg in &[
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+    ] {
+        for _ in 0..16 {
+            let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+            assert_eq!(key_pair.to_pkcs8().as_ref(), pkcs8.as_ref());
+        }
+    }
+}
+
+#[test]
+fn ecdsa_from_sec1_test() {
+    let rng = rand::SystemRandom::new();
+    let sec1 = test::from_hex(
+        "30770201010420656c55cc5abedcc48a93764eb27ab38b8db73aad44d6e3ed82b249d518eb5796a00a\
+         06082a8648ce3d030107a14403420004db8d325341db64c332420a2a19498783f96fcd40c2157c210f\
+         2e1c38bb7b593ee8ffddde31adba41d43170e46c230816169a669822d69efb49d96611eee6c400",
+    )
+    .unwrap();
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    assert!(signature::EcdsaKeyPair::from_sec1(alg, &sec1, &rng).is_ok());
+
+    let from_sec1 = |alg, sec1: &[u8]| {
+        signature::EcdsaKeyPair::from_sec1(alg, sec1, &rng)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    // The parameters name the wrong curve.
+    assert_eq!(
+        from_sec1(&signature::ECDSA_P384_SHA384_ASN1_SIGNING, &sec1),
+        Err("WrongAlgorithm".into())
+    );
+
+    // The public key doesn't match the private key.
+    let mut inconsistent = sec1.clone();
+    *inconsistent.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        from_sec1(alg, &inconsistent),
+        Err("InconsistentComponents".into())
+    );
+
+    // A PKCS#8 document isn't SEC1; its version is 0 instead of 1.
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+    assert_eq!(
+        from_sec1(alg, pkcs8.as_ref()),
+        Err("VersionNotSupported".into())
+    );
+
+    // Trailing garbage.
+    let mut trailing = sec1;
+    trailing.push(0);
+    assert_eq!(from_sec1(alg, &trailing), Err("InvalidEncoding".into()));
+}
+
+#[test]
+fn ecdsa_key_pair_traits() {
+    test::compile_time_assert_send::<signature::EcdsaKeyPair>();
+    test::compile_time_assert_sync::<signature::EcdsaKeyPair>();
+}
+
+// Sign concurrently with a key pair shared through `Arc`.
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn ecdsa_key_pair_shared_between_threads() {
+    use std::{sync::Arc, thread};
+
+    let rng = rand::SystemRandom::new();
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+    let key_pair =
+        Arc::new(signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap());
+    let public_key = signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_ASN1,
+        key_pair.public_key().as_ref().to_vec(),
+    );
+
+    let threads = (0u8..4)
+        .map(|t| {
+            let key_pair = Arc::clone(&key_pair);
+            thread::spawn(move || {
+                let rng = rand::SystemRandom::new();
+                (0u8..16)
+                    .map(|i| {
+                        let msg = [t, i];
+                        let sig = key_pair.sign(&rng, &msg).unwrap();
+                        (msg, sig)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        for (msg, sig) in thread.join().unwrap() {
+            assert_eq!(public_key.verify(&msg, sig.as_ref()), Ok(()));
+        }
+    }
+}
+
 #[test]
 fn signature_ecdsa_verify_asn1_test() {
     test::run(
@@ -129,6 +310,7 @@ fn signature_ecdsa_verify_asn1_test() {
                 ("P-256", "SHA384") => &signature::ECDSA_P256_SHA384_ASN1,
                 ("P-384", "SHA256") => &signature::ECDSA_P384_SHA256_ASN1,
                 ("P-384", "SHA384") => &signature::ECDSA_P384_SHA384_ASN1,
+                ("brainpoolP384r1", "SHA384") => &signature::ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
                 _ => {
                     panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
                 }
@@ -138,6 +320,26 @@ fn signature_ecdsa_verify_asn1_test() {
                 signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
             assert_eq!(actual_result.is_ok(), is_valid);
 
+            #[cfg(feature = "diagnostics")]
+            {
+                use signature::VerificationAlgorithm;
+                let diagnostic = alg.verify_diagnostic(
+                    untrusted::Input::from(&public_key),
+                    untrusted::Input::from(&msg),
+                    untrusted::Input::from(&sig),
+                );
+                assert_eq!(diagnostic.is_ok(), is_valid);
+            }
+
+            if curve_name == "P-256" {
+                if let (Ok(public_key), Ok(sig)) = (public_key[..].try_into(), sig[..].try_into()) {
+                    assert_eq!(
+                        signature::ecdsa_p256_fixed_verify(public_key, &msg, sig),
+                        actual_result
+                    );
+                }
+            }
+
             Ok(())
         },
     );
@@ -177,6 +379,32 @@ fn signature_ecdsa_verify_fixed_test() {
     );
 }
 
+#[test]
+fn ecdsa_test_public_key_into() {
+    const _: () = assert!(signature::EcdsaKeyPair::MAX_PUBLIC_KEY_LEN == 97);
+
+    let rng = rand::SystemRandom::new();
+
+    for (alg, expected_len) in [
+        (&signature::ECDSA_P256_SHA256_FIXED_SIGNING, 65),
+        (&signature::ECDSA_P256_SHA256_ASN1_SIGNING, 65),
+        (&signature::ECDSA_P384_SHA384_FIXED_SIGNING, 97),
+        (&signature::ECDSA_P384_SHA384_ASN1_SIGNING, 97),
+    ] {
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+        assert_eq!(key_pair.public_key().as_ref().len(), expected_len);
+
+        let mut out = [0u8; signature::EcdsaKeyPair::MAX_PUBLIC_KEY_LEN];
+        assert_eq!(key_pair.public_key_into(&mut out), Ok(expected_len));
+        assert_eq!(&out[..expected_len], key_pair.public_key().as_ref());
+        assert_eq!(
+            key_pair.public_key_into(&mut out[..(expected_len - 1)]),
+            Err(error::Unspecified)
+        );
+    }
+}
+
 #[test]
 fn ecdsa_test_public_key_coverage() {
     const PRIVATE_KEY: &[u8] = include_bytes!("ecdsa_test_private_key_p256.p8");
@@ -194,6 +422,9 @@ fn ecdsa_test_public_key_coverage() {
     // Test `AsRef<[u8]>`
     assert_eq!(key_pair.public_key().as_ref(), PUBLIC_KEY);
 
+    // The x-coordinate follows the 0x04 "uncompressed" marker.
+    assert_eq!(key_pair.public_key_x_only(), &PUBLIC_KEY[1..33]);
+
     // Test `Clone`.
     #[allow(clippy::clone_on_copy, clippy::redundant_clone)]
     let _: <signature::EcdsaKeyPair as KeyPair>::PublicKey = key_pair.public_key().clone();
@@ -316,3 +547,515 @@ fn signature_ecdsa_sign_asn1_test() {
         },
     );
 }
+
+// Even if the RNG is completely broken, signatures of different messages
+// must not share a nonce; signing just becomes deterministic.
+#[test]
+fn signature_ecdsa_sign_broken_rng_test() {
+    test::run(
+        test_file!("../src/ec/suite_b/ecdsa/ecdsa_sign_rfc6979_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let digest_name = test_case.consume_string("Digest");
+
+            let msg = test_case.consume_bytes("Msg");
+            let d = test_case.consume_bytes("d");
+            let q = test_case.consume_bytes("Q");
+
+            // Ignored since the RNG adds (constant) entropy to the nonce.
+            let _expected_result = test_case.consume_bytes("Sig");
+
+            let (signing_alg, verification_alg) = match (curve_name.as_str(), digest_name.as_str())
+            {
+                ("P-256", "SHA256") => (
+                    &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    &signature::ECDSA_P256_SHA256_FIXED,
+                ),
+                ("P-384", "SHA384") => (
+                    &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                    &signature::ECDSA_P384_SHA384_FIXED,
+                ),
+                _ => {
+                    panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name);
+                }
+            };
+
+            let zeros = test::rand::FixedByteRandom { byte: 0 };
+            let private_key = signature::EcdsaKeyPair::from_private_key_and_public_key(
+                signing_alg,
+                &d,
+                &q,
+                &zeros,
+            )
+            .unwrap();
+            let public_key = signature::UnparsedPublicKey::new(verification_alg, &q);
+
+            let mut other_msg = msg.clone();
+            other_msg.push(0);
+
+            let sig = private_key.sign(&zeros, &msg).unwrap();
+            let other_sig = private_key.sign(&zeros, &other_msg).unwrap();
+            assert_eq!(public_key.verify(&msg, sig.as_ref()), Ok(()));
+            assert_eq!(public_key.verify(&other_msg, other_sig.as_ref()), Ok(()));
+
+            // In a fixed-length signature, `r` is the first half. Different
+            // values of `r` mean different nonces.
+            let r_len = sig.as_ref().len() / 2;
+            assert_ne!(sig.as_ref()[..r_len], other_sig.as_ref()[..r_len]);
+
+            assert_eq!(
+                private_key.sign(&zeros, &msg).unwrap().as_ref(),
+                sig.as_ref()
+            );
+
+            // The RNG's output still affects the nonce.
+            let ones = test::rand::FixedByteRandom { byte: 1 };
+            let ones_sig = private_key.sign(&ones, &msg).unwrap();
+            assert_eq!(public_key.verify(&msg, ones_sig.as_ref()), Ok(()));
+            assert_ne!(sig.as_ref()[..r_len], ones_sig.as_ref()[..r_len]);
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn signature_ecdsa_low_s_test() {
+    // `n` is odd, so floor(n/2) + 1 = ceil(n/2) and n - ceil(n/2) = floor(n/2).
+    fn half(n: &[u8]) -> Vec<u8> {
+        let mut carry = 0;
+        n.iter()
+            .map(|b| {
+                let r = (carry << 7) | (b >> 1);
+                carry = b & 1;
+                r
+            })
+            .collect()
+    }
+    fn add_small(a: &[u8], b: u8) -> Vec<u8> {
+        let mut r = a.to_vec();
+        let mut carry = u16::from(b);
+        for r in r.iter_mut().rev() {
+            let sum = u16::from(*r) + carry;
+            *r = sum.to_be_bytes()[1];
+            carry = sum >> 8;
+        }
+        r
+    }
+    fn sub_one(a: &[u8]) -> Vec<u8> {
+        let mut r = a.to_vec();
+        for r in r.iter_mut().rev() {
+            let (diff, borrow) = r.overflowing_sub(1);
+            *r = diff;
+            if !borrow {
+                break;
+            }
+        }
+        r
+    }
+    fn sig(r: &[u8], s: &[u8]) -> Vec<u8> {
+        [r, s].concat()
+    }
+
+    for (alg, n) in [
+        (
+            &signature::ECDSA_P256_SHA256_FIXED,
+            "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_FIXED,
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF\
+             C7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973",
+        ),
+    ] {
+        let n = test::from_hex(n).unwrap();
+        let zero = vec![0u8; n.len()];
+        let one = add_small(&zero, 1);
+        let r = add_small(&zero, 2);
+        let half_floor = half(&n);
+        let half_ceil = add_small(&half_floor, 1);
+        let n_minus_one = sub_one(&n);
+
+        // (s, is low, normalized s)
+        for (s, low, normalized) in [
+            (&half_floor, true, &half_floor),
+            (&half_ceil, false, &half_floor),
+            (&one, true, &one),
+            (&n_minus_one, false, &one),
+        ] {
+            let input = sig(&r, s);
+            assert_eq!(signature::ecdsa_is_low_s(alg, &input), low);
+            let output = signature::ecdsa_normalize_s(alg, &input).unwrap();
+            assert_eq!(output.as_ref(), &sig(&r, normalized)[..]);
+            assert!(signature::ecdsa_is_low_s(alg, output.as_ref()));
+        }
+
+        // Malformed signatures.
+        for input in [
+            sig(&r, &zero),
+            sig(&r, &n),
+            sig(&zero, &one),
+            sig(&n, &one),
+            sig(&r, &one[1..]),
+            [sig(&r, &one), vec![0]].concat(),
+        ] {
+            assert!(!signature::ecdsa_is_low_s(alg, &input));
+            assert!(signature::ecdsa_normalize_s(alg, &input).is_err());
+        }
+    }
+}
+
+#[test]
+fn signature_ecdsa_normalize_s_verify_test() {
+    let rng = rand::SystemRandom::new();
+    let msg = b"hello, world";
+
+    for (signing_alg, verification_alg) in [
+        (
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            &signature::ECDSA_P256_SHA256_FIXED,
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            &signature::ECDSA_P384_SHA384_FIXED,
+        ),
+    ] {
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(signing_alg, &rng).unwrap();
+        let key_pair =
+            signature::EcdsaKeyPair::from_pkcs8(signing_alg, pkcs8.as_ref(), &rng).unwrap();
+        let public_key =
+            signature::UnparsedPublicKey::new(verification_alg, key_pair.public_key().as_ref());
+
+        // A random signature has a high s about half the time.
+        let (mut saw_low, mut saw_high) = (false, false);
+        while !(saw_low && saw_high) {
+            let sig = key_pair.sign(&rng, msg).unwrap();
+            let low = signature::ecdsa_is_low_s(verification_alg, sig.as_ref());
+            saw_low |= low;
+            saw_high |= !low;
+
+            let normalized = signature::ecdsa_normalize_s(verification_alg, sig.as_ref()).unwrap();
+            assert!(signature::ecdsa_is_low_s(
+                verification_alg,
+                normalized.as_ref()
+            ));
+            assert_eq!(normalized.as_ref() == sig.as_ref(), low);
+            assert!(public_key.verify(msg, normalized.as_ref()).is_ok());
+        }
+    }
+}
+
+// Returns the fixed-length signature `sig` with s replaced by n - s.
+fn negate_s(n: &[u8], sig: &[u8]) -> Vec<u8> {
+    let (r, s) = sig.split_at(n.len());
+    let mut neg_s = vec![0u8; n.len()];
+    let mut borrow = 0;
+    for ((neg_s, n), s) in neg_s.iter_mut().zip(n).zip(s).rev() {
+        let diff = i16::from(*n) - i16::from(*s) - borrow;
+        *neg_s = diff.to_be_bytes()[1];
+        borrow = i16::from(diff < 0);
+    }
+    [r, &neg_s].concat()
+}
+
+fn fixed_to_asn1(curve: signature::EcdsaCurve, sig: &[u8]) -> Vec<u8> {
+    use signature::{EcdsaSignature, EcdsaSignatureFormat};
+    EcdsaSignature::parse(EcdsaSignatureFormat::FixedP1363, curve, sig)
+        .unwrap()
+        .serialize(EcdsaSignatureFormat::Asn1)
+        .as_ref()
+        .to_vec()
+}
+
+#[test]
+fn signature_ecdsa_low_s_variants_test() {
+    let rng = rand::SystemRandom::new();
+    let msg = b"hello, world";
+
+    struct Variants {
+        signing: &'static signature::EcdsaSigningAlgorithm,
+        fixed: &'static signature::EcdsaVerificationAlgorithm,
+        fixed_low_s: &'static signature::EcdsaVerificationAlgorithm,
+        asn1: &'static signature::EcdsaVerificationAlgorithm,
+        asn1_low_s: &'static signature::EcdsaVerificationAlgorithm,
+        n: &'static str,
+    }
+
+    for v in [
+        Variants {
+            signing: &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            fixed: &signature::ECDSA_P256_SHA256_FIXED,
+            fixed_low_s: &signature::ECDSA_P256_SHA256_FIXED_LOW_S,
+            asn1: &signature::ECDSA_P256_SHA256_ASN1,
+            asn1_low_s: &signature::ECDSA_P256_SHA256_ASN1_LOW_S,
+            n: "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+        },
+        Variants {
+            signing: &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            fixed: &signature::ECDSA_P384_SHA384_FIXED,
+            fixed_low_s: &signature::ECDSA_P384_SHA384_FIXED_LOW_S,
+            asn1: &signature::ECDSA_P384_SHA384_ASN1,
+            asn1_low_s: &signature::ECDSA_P384_SHA384_ASN1_LOW_S,
+            n: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF\
+                C7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973",
+        },
+    ] {
+        assert!(!v.fixed.requires_low_s());
+        assert!(!v.asn1.requires_low_s());
+        assert!(v.fixed_low_s.requires_low_s());
+        assert!(v.asn1_low_s.requires_low_s());
+        assert_eq!(v.fixed_low_s.curve(), v.fixed.curve());
+        assert_eq!(v.asn1_low_s.curve(), v.asn1.curve());
+
+        let n = test::from_hex(v.n).unwrap();
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(v.signing, &rng).unwrap();
+        let key_pair =
+            signature::EcdsaKeyPair::from_pkcs8(v.signing, pkcs8.as_ref(), &rng).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let verify = |alg, sig: &[u8]| {
+            signature::UnparsedPublicKey::new(alg, public_key)
+                .verify(msg, sig)
+                .is_ok()
+        };
+
+        for _ in 0..4 {
+            let sig = key_pair.sign(&rng, msg).unwrap();
+            let low = signature::ecdsa_normalize_s(v.fixed, sig.as_ref()).unwrap();
+            let low = low.as_ref();
+            let high = negate_s(&n, low);
+            assert!(!signature::ecdsa_is_low_s(v.fixed, &high));
+
+            assert!(verify(v.fixed, low));
+            assert!(verify(v.fixed, &high));
+            assert!(verify(v.fixed_low_s, low));
+            assert!(!verify(v.fixed_low_s, &high));
+
+            let curve = v.fixed.curve();
+            let (low, high) = (fixed_to_asn1(curve, low), fixed_to_asn1(curve, &high));
+            assert!(verify(v.asn1, &low));
+            assert!(verify(v.asn1, &high));
+            assert!(verify(v.asn1_low_s, &low));
+            assert!(!verify(v.asn1_low_s, &high));
+
+            let parsed = signature::EcdsaSignature::parse(
+                signature::EcdsaSignatureFormat::Asn1,
+                curve,
+                &high,
+            )
+            .unwrap();
+            assert!(v.asn1.verify_parsed(public_key, msg, &parsed).is_ok());
+            assert!(v
+                .asn1_low_s
+                .verify_parsed(public_key, msg, &parsed)
+                .is_err());
+        }
+    }
+}
+
+// See the comments in the test file for details.
+#[test]
+fn signature_ecdsa_asn1_rejects_ber_test() {
+    test::run(
+        test_file!("ecdsa_verify_asn1_ber_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let curve_name = test_case.consume_string("Curve");
+            let digest_name = test_case.consume_string("Digest");
+            let (curve, algs) = match (curve_name.as_str(), digest_name.as_str()) {
+                ("P-256", "SHA256") => (
+                    signature::EcdsaCurve::P256,
+                    [
+                        &signature::ECDSA_P256_SHA256_ASN1,
+                        &signature::ECDSA_P256_SHA256_ASN1_LOW_S,
+                    ],
+                ),
+                ("P-384", "SHA384") => (
+                    signature::EcdsaCurve::P384,
+                    [
+                        &signature::ECDSA_P384_SHA384_ASN1,
+                        &signature::ECDSA_P384_SHA384_ASN1_LOW_S,
+                    ],
+                ),
+                _ => panic!("Unsupported curve+digest: {}+{}", curve_name, digest_name),
+            };
+            let msg = test_case.consume_bytes("Msg");
+            let public_key = test_case.consume_bytes("Q");
+            let sig = test_case.consume_bytes("Sig");
+
+            let parsed = signature::EcdsaSignature::parse(
+                signature::EcdsaSignatureFormat::Asn1,
+                curve,
+                &sig,
+            );
+            let is_valid = test_case.check_result(parsed).is_some();
+
+            for alg in algs {
+                let actual_result =
+                    signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
+                assert_eq!(actual_result.is_ok(), is_valid);
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn signature_ecdsa_parsed_signature_test() {
+    use signature::{EcdsaCurve, EcdsaSignature, EcdsaSignatureError, EcdsaSignatureFormat};
+
+    fn sub_one(a: &[u8]) -> Vec<u8> {
+        let mut r = a.to_vec();
+        for r in r.iter_mut().rev() {
+            let (diff, borrow) = r.overflowing_sub(1);
+            *r = diff;
+            if !borrow {
+                break;
+            }
+        }
+        r
+    }
+
+    let rng = rand::SystemRandom::new();
+    let msg = b"hello, world";
+
+    for (curve, n, signing_alg, fixed_alg, asn1_alg, other_curve) in [
+        (
+            EcdsaCurve::P256,
+            "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            &signature::ECDSA_P256_SHA256_FIXED,
+            &signature::ECDSA_P256_SHA256_ASN1,
+            EcdsaCurve::P384,
+        ),
+        (
+            EcdsaCurve::P384,
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF\
+             C7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973",
+            &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            &signature::ECDSA_P384_SHA384_FIXED,
+            &signature::ECDSA_P384_SHA384_ASN1,
+            EcdsaCurve::P256,
+        ),
+    ] {
+        assert_eq!(fixed_alg.curve(), curve);
+        assert_eq!(asn1_alg.curve(), curve);
+
+        let n = test::from_hex(n).unwrap();
+        assert_eq!(curve.scalar_len(), n.len());
+        let zero = vec![0u8; n.len()];
+        let mut one = zero.clone();
+        *one.last_mut().unwrap() = 1;
+        let n_minus_one = sub_one(&n);
+
+        // Boundary values round-trip through both formats.
+        for (r, s) in [(&one, &n_minus_one), (&n_minus_one, &one)] {
+            let fixed = [&r[..], &s[..]].concat();
+            let parsed =
+                EcdsaSignature::parse(EcdsaSignatureFormat::FixedP1363, curve, &fixed).unwrap();
+            assert_eq!(parsed.curve(), curve);
+            assert_eq!(
+                parsed.serialize(EcdsaSignatureFormat::FixedP1363).as_ref(),
+                &fixed[..]
+            );
+            let asn1 = parsed.serialize(EcdsaSignatureFormat::Asn1);
+            let reparsed =
+                EcdsaSignature::parse(EcdsaSignatureFormat::Asn1, curve, asn1.as_ref()).unwrap();
+            assert_eq!(reparsed, parsed);
+        }
+
+        // Out-of-range values.
+        for (r, s, expected) in [
+            (&zero, &one, EcdsaSignatureError::ZeroScalar),
+            (&one, &zero, EcdsaSignatureError::ZeroScalar),
+            (&n, &one, EcdsaSignatureError::ScalarOutOfRange),
+            (&one, &n, EcdsaSignatureError::ScalarOutOfRange),
+        ] {
+            let fixed = [&r[..], &s[..]].concat();
+            assert_eq!(
+                EcdsaSignature::parse(EcdsaSignatureFormat::FixedP1363, curve, &fixed),
+                Err(expected)
+            );
+        }
+
+        // Wrong lengths.
+        for len in [0, 2 * n.len() - 1, 2 * n.len() + 1] {
+            assert_eq!(
+                EcdsaSignature::parse(EcdsaSignatureFormat::FixedP1363, curve, &vec![1; len]),
+                Err(EcdsaSignatureError::WrongLength {
+                    expected: 2 * n.len(),
+                    actual: len
+                })
+            );
+        }
+
+        // Malformed DER.
+        for asn1 in [&[][..], &[0x30, 0x00], &[0x30, 0x03, 0x02, 0x01, 0x01]] {
+            assert_eq!(
+                EcdsaSignature::parse(EcdsaSignatureFormat::Asn1, curve, asn1),
+                Err(EcdsaSignatureError::InvalidEncoding)
+            );
+        }
+
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(signing_alg, &rng).unwrap();
+        let key_pair =
+            signature::EcdsaKeyPair::from_pkcs8(signing_alg, pkcs8.as_ref(), &rng).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let fixed = key_pair.sign(&rng, msg).unwrap();
+
+        let parsed =
+            EcdsaSignature::parse(EcdsaSignatureFormat::FixedP1363, curve, fixed.as_ref()).unwrap();
+        assert!(fixed_alg.verify_parsed(public_key, msg, &parsed).is_ok());
+        assert!(asn1_alg.verify_parsed(public_key, msg, &parsed).is_ok());
+        assert!(fixed_alg
+            .verify_parsed(public_key, b"hello", &parsed)
+            .is_err());
+
+        // The ASN.1 serialization verifies with the ASN.1 algorithm.
+        let asn1 = parsed.serialize(EcdsaSignatureFormat::Asn1);
+        assert!(signature::UnparsedPublicKey::new(asn1_alg, public_key)
+            .verify(msg, asn1.as_ref())
+            .is_ok());
+
+        // A fixed-length signature for one curve is never accepted for the
+        // other.
+        assert_eq!(
+            EcdsaSignature::parse(
+                EcdsaSignatureFormat::FixedP1363,
+                other_curve,
+                fixed.as_ref()
+            ),
+            Err(EcdsaSignatureError::WrongLength {
+                expected: 2 * other_curve.scalar_len(),
+                actual: 2 * curve.scalar_len(),
+            })
+        );
+
+        // A parsed signature can't be verified with the other curve.
+        let other_alg = match other_curve {
+            EcdsaCurve::P256 => &signature::ECDSA_P256_SHA256_ASN1,
+            EcdsaCurve::P384 => &signature::ECDSA_P384_SHA384_ASN1,
+            EcdsaCurve::BrainpoolP384r1 => &signature::ECDSA_BRAINPOOLP384R1_SHA384_ASN1,
+        };
+        assert!(other_alg.verify_parsed(public_key, msg, &parsed).is_err());
+
+        // A P-384 ASN.1 signature is too large for P-256 (except with
+        // negligible probability), while a P-256 one is in range for P-384 but
+        // doesn't verify.
+        let cross = EcdsaSignature::parse(EcdsaSignatureFormat::Asn1, other_curve, asn1.as_ref());
+        match curve {
+            EcdsaCurve::P384 => {
+                assert_eq!(cross, Err(EcdsaSignatureError::ScalarOutOfRange));
+            }
+            EcdsaCurve::P256 => {
+                let cross = cross.unwrap();
+                assert_eq!(cross.curve(), EcdsaCurve::P384);
+                assert!(other_alg.verify_parsed(public_key, msg, &cross).is_err());
+            }
+            EcdsaCurve::BrainpoolP384r1 => unreachable!(),
+        }
+    }
+}
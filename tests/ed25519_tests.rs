@@ -99,6 +99,13 @@ fn test_signature_verification(
         expected_result,
         signature::UnparsedPublicKey::new(&signature::ED25519, public_key).verify(msg, sig)
     );
+
+    if let (Ok(public_key), Ok(sig)) = (public_key.try_into(), sig.try_into()) {
+        assert_eq!(
+            expected_result,
+            signature::ed25519_verify(public_key, msg, sig)
+        );
+    }
 }
 
 #[test]
@@ -118,6 +125,87 @@ fn test_ed25519_from_seed_and_public_key_misuse() {
     assert!(Ed25519KeyPair::from_seed_and_public_key(PUBLIC_KEY, PRIVATE_KEY).is_err());
 }
 
+// The example key pair and signature from RFC 8037 Appendix A.
+#[test]
+fn test_ed25519_from_seed_and_public_key_rfc8037() {
+    const SEED: [u8; 32] = [
+        0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c,
+        0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae,
+        0x7f, 0x60,
+    ];
+    const PUBLIC_KEY: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+        0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+        0x51, 0x1a,
+    ];
+    const JWS_SIGNING_INPUT: &[u8] = b"eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc";
+    let expected_sig = test::from_hex(
+        "860c98d2297f3060a33f42739672d61b53cf3adefed3d3c672f320dc021b411e\
+         9d59b8628dc351e248b88b29468e0e41855b0fb7d83bb15be902bfccb8cd0a02",
+    )
+    .unwrap();
+
+    let key_pair = Ed25519KeyPair::from_seed_and_public_key(&SEED, &PUBLIC_KEY).unwrap();
+    assert_eq!(key_pair.public_key().as_ref(), &PUBLIC_KEY[..]);
+    assert_eq!(key_pair.sign(JWS_SIGNING_INPUT).as_ref(), &expected_sig[..]);
+
+    // A single flipped bit in either component must be detected.
+    let mut bad_public_key = PUBLIC_KEY;
+    bad_public_key[0] ^= 1;
+    assert!(Ed25519KeyPair::from_seed_and_public_key(&SEED, &bad_public_key).is_err());
+    let mut bad_seed = SEED;
+    bad_seed[31] ^= 0x80;
+    assert!(Ed25519KeyPair::from_seed_and_public_key(&bad_seed, &PUBLIC_KEY).is_err());
+}
+
+#[test]
+fn test_ed25519_sign_hedged() {
+    const MSG: &[u8] = b"hello, world";
+
+    let key_pair =
+        Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8")).unwrap();
+    let public_key =
+        signature::UnparsedPublicKey::new(&signature::ED25519, key_pair.public_key().as_ref());
+
+    let rng = rand::SystemRandom::new();
+    let sig1 = key_pair.sign_hedged(&rng, MSG).unwrap();
+    let sig2 = key_pair.sign_hedged(&rng, MSG).unwrap();
+    assert_ne!(sig1.as_ref(), sig2.as_ref());
+    assert_ne!(sig1.as_ref(), key_pair.sign(MSG).as_ref());
+    for sig in [&sig1, &sig2] {
+        assert!(public_key.verify(MSG, sig.as_ref()).is_ok());
+        assert!(public_key.verify(b"hello, world!", sig.as_ref()).is_err());
+    }
+
+    // An all-zero `Z` is still valid, and is still not the same as
+    // deterministic signing.
+    let zero = test::rand::FixedByteRandom { byte: 0 };
+    let sig = key_pair.sign_hedged(&zero, MSG).unwrap();
+    assert!(public_key.verify(MSG, sig.as_ref()).is_ok());
+    assert_eq!(
+        sig.as_ref(),
+        key_pair.sign_hedged(&zero, MSG).unwrap().as_ref()
+    );
+    assert_ne!(sig.as_ref(), key_pair.sign(MSG).as_ref());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_ed25519_sign_batch() {
+    let key_pair =
+        Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8")).unwrap();
+
+    assert!(key_pair.sign_batch(&[]).is_empty());
+
+    let long = vec![0xab; 1000];
+    let messages: [&[u8]; 5] = [b"", b"hello, world", b"hello, world", &long, b"\x00"];
+    let signatures = key_pair.sign_batch(&messages);
+    assert_eq!(signatures.len(), messages.len());
+    for (msg, sig) in messages.iter().zip(&signatures) {
+        assert_eq!(sig.as_ref(), key_pair.sign(msg).as_ref());
+    }
+}
+
 enum FromPkcs8Variant {
     Checked,
     MaybeUnchecked,
@@ -201,6 +289,63 @@ fn ed25519_test_generate_pkcs8() {
     assert_eq!(&generated[..2], &[0x30, 0x51]);
 }
 
+#[test]
+fn ed25519_test_generate() {
+    const MSG: &[u8] = b"hello, world";
+
+    let rng = rand::SystemRandom::new();
+    let key_pair = Ed25519KeyPair::generate(&rng).unwrap();
+    let sig = key_pair.sign(MSG);
+    let public_key =
+        signature::UnparsedPublicKey::new(&signature::ED25519, key_pair.public_key().as_ref());
+    assert!(public_key.verify(MSG, sig.as_ref()).is_ok());
+
+    // The key pair is the same as the one parsed from the PKCS#8 document
+    // generated from the same seed.
+    let rng = test::rand::FixedByteRandom { byte: 0x42 };
+    let generated = Ed25519KeyPair::generate(&rng).unwrap();
+    let roundtripped =
+        Ed25519KeyPair::from_pkcs8(Ed25519KeyPair::generate_pkcs8(&rng).unwrap().as_ref()).unwrap();
+    assert_eq!(
+        generated.public_key().as_ref(),
+        roundtripped.public_key().as_ref()
+    );
+    assert_eq!(
+        generated.sign(MSG).as_ref(),
+        roundtripped.sign(MSG).as_ref()
+    );
+
+    let (public_key, sig) = signature::ed25519_keygen_sign(&rng, MSG).unwrap();
+    assert_eq!(&public_key[..], roundtripped.public_key().as_ref());
+    assert_eq!(&sig[..], roundtripped.sign(MSG).as_ref());
+    assert!(signature::ed25519_verify(&public_key, MSG, &sig).is_ok());
+    assert!(signature::ed25519_verify(&public_key, b"hello, world!", &sig).is_err());
+}
+
+#[test]
+fn ed25519_test_public_key_into() {
+    const _: () = assert!(Ed25519KeyPair::MAX_PUBLIC_KEY_LEN == 32);
+
+    let rng = rand::SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    assert_eq!(
+        key_pair.public_key().as_ref().len(),
+        Ed25519KeyPair::MAX_PUBLIC_KEY_LEN
+    );
+
+    let mut out = [0u8; Ed25519KeyPair::MAX_PUBLIC_KEY_LEN];
+    assert_eq!(
+        key_pair.public_key_into(&mut out),
+        Ok(Ed25519KeyPair::MAX_PUBLIC_KEY_LEN)
+    );
+    assert_eq!(&out[..], key_pair.public_key().as_ref());
+    assert_eq!(
+        key_pair.public_key_into(&mut out[1..]),
+        Err(error::Unspecified)
+    );
+}
+
 #[test]
 fn ed25519_test_public_key_coverage() {
     const PRIVATE_KEY: &[u8] = include_bytes!("ed25519_test_private_key.p8");
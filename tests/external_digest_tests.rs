@@ -0,0 +1,544 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "external-digest")]
+
+use ring::{digest, hkdf, hmac, pbkdf2, test, test_file};
+use std::num::NonZeroU32;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// 64-bit FNV-1a, with an artificially small block so that the block and
+/// finalize functions both get exercised.
+mod fnv1a {
+    use ring::digest;
+
+    const BLOCK_LEN: usize = 16;
+    const OUTPUT_LEN: usize = 8;
+
+    pub static FNV1A_64: digest::Algorithm =
+        digest::Algorithm::new_external(OUTPUT_LEN, BLOCK_LEN, INIT, block_fn, finalize);
+
+    const INIT: digest::ExternalState = {
+        let mut state = [0; digest::EXTERNAL_STATE_WORDS];
+        state[0] = 0xcbf29ce484222325;
+        state
+    };
+
+    fn block_fn(state: &mut digest::ExternalState, blocks: &[u8]) {
+        assert!(!blocks.is_empty());
+        assert_eq!(blocks.len() % BLOCK_LEN, 0);
+        update(state, blocks);
+    }
+
+    fn finalize(state: &mut digest::ExternalState, remainder: &[u8], out: &mut [u8]) {
+        assert!(remainder.len() < BLOCK_LEN);
+        update(state, remainder);
+        out.copy_from_slice(&state[0].to_be_bytes());
+    }
+
+    fn update(state: &mut digest::ExternalState, input: &[u8]) {
+        state[0] = input.iter().fold(state[0], |h, &b| {
+            (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+        });
+    }
+}
+
+/// Streebog-256 (GOST R 34.11-2012, RFC 6986), written for these tests only.
+/// It isn't constant-time.
+mod streebog {
+    use ring::digest;
+
+    pub static STREEBOG256: digest::Algorithm =
+        digest::Algorithm::new_external(256 / 8, BLOCK_LEN, INIT, block_fn, finalize);
+
+    const BLOCK_LEN: usize = 512 / 8;
+
+    // A 512-bit value as little-endian 64-bit words.
+    type Block = [u64; 8];
+
+    // The state is h, N, and Σ, in that order.
+    const H: usize = 0;
+    const N: usize = 1;
+    const SIGMA: usize = 2;
+
+    // The IV of Streebog-256 is 0x01 repeated.
+    const INIT: digest::ExternalState = {
+        let mut state = [0; digest::EXTERNAL_STATE_WORDS];
+        let mut i = 0;
+        while i < 8 {
+            state[i] = 0x0101010101010101;
+            i += 1;
+        }
+        state
+    };
+
+    fn block_fn(state: &mut digest::ExternalState, blocks: &[u8]) {
+        for block in blocks.chunks_exact(BLOCK_LEN) {
+            let m = block_from_bytes(block);
+            compress(state, &m, 512);
+        }
+    }
+
+    fn finalize(state: &mut digest::ExternalState, remainder: &[u8], out: &mut [u8]) {
+        let mut padded = [0u8; BLOCK_LEN];
+        padded[..remainder.len()].copy_from_slice(remainder);
+        padded[remainder.len()] = 1;
+        let m = block_from_bytes(&padded);
+        compress(state, &m, 8 * (remainder.len() as u64));
+
+        let zero = [0; 8];
+        let h = g(&get(state, H), &zero, &get(state, N));
+        let h = g(&h, &zero, &get(state, SIGMA));
+        for (out, word) in out.chunks_exact_mut(8).zip(&h[4..]) {
+            out.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    fn compress(state: &mut digest::ExternalState, m: &Block, bits: u64) {
+        let n = get(state, N);
+        set(state, H, &g(&get(state, H), &n, m));
+        set(state, N, &add(&n, &[bits, 0, 0, 0, 0, 0, 0, 0]));
+        set(state, SIGMA, &add(&get(state, SIGMA), m));
+    }
+
+    fn g(h: &Block, n: &Block, m: &Block) -> Block {
+        let mut k = lps(&xor(h, n));
+        let mut t = *m;
+        for c in C.iter() {
+            t = lps(&xor(&t, &k));
+            k = lps(&xor(&k, c));
+        }
+        xor(&xor(&xor(&t, &k), h), m)
+    }
+
+    fn lps(x: &Block) -> Block {
+        let mut r = [0; 8];
+        for (i, r) in r.iter_mut().enumerate() {
+            // P transposes the bytes, S substitutes each one.
+            let v = x.iter().enumerate().fold(0u64, |v, (j, x)| {
+                let b = PI[usize::from(x.to_le_bytes()[i])];
+                v | (u64::from(b) << (8 * j))
+            });
+            // L multiplies by A; A[0] is the row for the most significant bit.
+            *r = A.iter().enumerate().fold(0, |acc, (bit, row)| {
+                acc ^ (row & 0u64.wrapping_sub((v >> (63 - bit)) & 1))
+            });
+        }
+        r
+    }
+
+    fn xor(a: &Block, b: &Block) -> Block {
+        let mut r = *a;
+        r.iter_mut().zip(b).for_each(|(r, b)| *r ^= b);
+        r
+    }
+
+    fn add(a: &Block, b: &Block) -> Block {
+        let mut r = [0; 8];
+        let mut carry = false;
+        for ((r, a), b) in r.iter_mut().zip(a).zip(b) {
+            let (sum, c1) = a.overflowing_add(*b);
+            let (sum, c2) = sum.overflowing_add(u64::from(carry));
+            *r = sum;
+            carry = c1 | c2;
+        }
+        r
+    }
+
+    fn block_from_bytes(bytes: &[u8]) -> Block {
+        let mut r = [0; 8];
+        for (r, bytes) in r.iter_mut().zip(bytes.chunks_exact(8)) {
+            *r = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        r
+    }
+
+    fn get(state: &digest::ExternalState, i: usize) -> Block {
+        state[(8 * i)..][..8].try_into().unwrap()
+    }
+
+    fn set(state: &mut digest::ExternalState, i: usize, value: &Block) {
+        state[(8 * i)..][..8].copy_from_slice(value)
+    }
+
+    const PI: [u8; 256] = [
+        0xfc, 0xee, 0xdd, 0x11, 0xcf, 0x6e, 0x31, 0x16, 0xfb, 0xc4, 0xfa, 0xda, 0x23, 0xc5, 0x04,
+        0x4d, 0xe9, 0x77, 0xf0, 0xdb, 0x93, 0x2e, 0x99, 0xba, 0x17, 0x36, 0xf1, 0xbb, 0x14, 0xcd,
+        0x5f, 0xc1, 0xf9, 0x18, 0x65, 0x5a, 0xe2, 0x5c, 0xef, 0x21, 0x81, 0x1c, 0x3c, 0x42, 0x8b,
+        0x01, 0x8e, 0x4f, 0x05, 0x84, 0x02, 0xae, 0xe3, 0x6a, 0x8f, 0xa0, 0x06, 0x0b, 0xed, 0x98,
+        0x7f, 0xd4, 0xd3, 0x1f, 0xeb, 0x34, 0x2c, 0x51, 0xea, 0xc8, 0x48, 0xab, 0xf2, 0x2a, 0x68,
+        0xa2, 0xfd, 0x3a, 0xce, 0xcc, 0xb5, 0x70, 0x0e, 0x56, 0x08, 0x0c, 0x76, 0x12, 0xbf, 0x72,
+        0x13, 0x47, 0x9c, 0xb7, 0x5d, 0x87, 0x15, 0xa1, 0x96, 0x29, 0x10, 0x7b, 0x9a, 0xc7, 0xf3,
+        0x91, 0x78, 0x6f, 0x9d, 0x9e, 0xb2, 0xb1, 0x32, 0x75, 0x19, 0x3d, 0xff, 0x35, 0x8a, 0x7e,
+        0x6d, 0x54, 0xc6, 0x80, 0xc3, 0xbd, 0x0d, 0x57, 0xdf, 0xf5, 0x24, 0xa9, 0x3e, 0xa8, 0x43,
+        0xc9, 0xd7, 0x79, 0xd6, 0xf6, 0x7c, 0x22, 0xb9, 0x03, 0xe0, 0x0f, 0xec, 0xde, 0x7a, 0x94,
+        0xb0, 0xbc, 0xdc, 0xe8, 0x28, 0x50, 0x4e, 0x33, 0x0a, 0x4a, 0xa7, 0x97, 0x60, 0x73, 0x1e,
+        0x00, 0x62, 0x44, 0x1a, 0xb8, 0x38, 0x82, 0x64, 0x9f, 0x26, 0x41, 0xad, 0x45, 0x46, 0x92,
+        0x27, 0x5e, 0x55, 0x2f, 0x8c, 0xa3, 0xa5, 0x7d, 0x69, 0xd5, 0x95, 0x3b, 0x07, 0x58, 0xb3,
+        0x40, 0x86, 0xac, 0x1d, 0xf7, 0x30, 0x37, 0x6b, 0xe4, 0x88, 0xd9, 0xe7, 0x89, 0xe1, 0x1b,
+        0x83, 0x49, 0x4c, 0x3f, 0xf8, 0xfe, 0x8d, 0x53, 0xaa, 0x90, 0xca, 0xd8, 0x85, 0x61, 0x20,
+        0x71, 0x67, 0xa4, 0x2d, 0x2b, 0x09, 0x5b, 0xcb, 0x9b, 0x25, 0xd0, 0xbe, 0xe5, 0x6c, 0x52,
+        0x59, 0xa6, 0x74, 0xd2, 0xe6, 0xf4, 0xb4, 0xc0, 0xd1, 0x66, 0xaf, 0xc2, 0x39, 0x4b, 0x63,
+        0xb6,
+    ];
+
+    const A: [u64; 64] = [
+        0x8e20faa72ba0b470,
+        0x47107ddd9b505a38,
+        0xad08b0e0c3282d1c,
+        0xd8045870ef14980e,
+        0x6c022c38f90a4c07,
+        0x3601161cf205268d,
+        0x1b8e0b0e798c13c8,
+        0x83478b07b2468764,
+        0xa011d380818e8f40,
+        0x5086e740ce47c920,
+        0x2843fd2067adea10,
+        0x14aff010bdd87508,
+        0x0ad97808d06cb404,
+        0x05e23c0468365a02,
+        0x8c711e02341b2d01,
+        0x46b60f011a83988e,
+        0x90dab52a387ae76f,
+        0x486dd4151c3dfdb9,
+        0x24b86a840e90f0d2,
+        0x125c354207487869,
+        0x092e94218d243cba,
+        0x8a174a9ec8121e5d,
+        0x4585254f64090fa0,
+        0xaccc9ca9328a8950,
+        0x9d4df05d5f661451,
+        0xc0a878a0a1330aa6,
+        0x60543c50de970553,
+        0x302a1e286fc58ca7,
+        0x18150f14b9ec46dd,
+        0x0c84890ad27623e0,
+        0x0642ca05693b9f70,
+        0x0321658cba93c138,
+        0x86275df09ce8aaa8,
+        0x439da0784e745554,
+        0xafc0503c273aa42a,
+        0xd960281e9d1d5215,
+        0xe230140fc0802984,
+        0x71180a8960409a42,
+        0xb60c05ca30204d21,
+        0x5b068c651810a89e,
+        0x456c34887a3805b9,
+        0xac361a443d1c8cd2,
+        0x561b0d22900e4669,
+        0x2b838811480723ba,
+        0x9bcf4486248d9f5d,
+        0xc3e9224312c8c1a0,
+        0xeffa11af0964ee50,
+        0xf97d86d98a327728,
+        0xe4fa2054a80b329c,
+        0x727d102a548b194e,
+        0x39b008152acb8227,
+        0x9258048415eb419d,
+        0x492c024284fbaec0,
+        0xaa16012142f35760,
+        0x550b8e9e21f7a530,
+        0xa48b474f9ef5dc18,
+        0x70a6a56e2440598e,
+        0x3853dc371220a247,
+        0x1ca76e95091051ad,
+        0x0edd37c48a08a6d8,
+        0x07e095624504536c,
+        0x8d70c431ac02a736,
+        0xc83862965601dd1b,
+        0x641c314b2b8ee083,
+    ];
+
+    const C: [[u64; 8]; 12] = [
+        [
+            0xdd806559f2a64507,
+            0x05767436cc744d23,
+            0xa2422a08a460d315,
+            0x4b7ce09192676901,
+            0x714eb88d7585c4fc,
+            0x2f6a76432e45d016,
+            0xebcb2f81c0657c1f,
+            0xb1085bda1ecadae9,
+        ],
+        [
+            0xe679047021b19bb7,
+            0x55dda21bd7cbcd56,
+            0x5cb561c2db0aa7ca,
+            0x9ab5176b12d69958,
+            0x61d55e0f16b50131,
+            0xf3feea720a232b98,
+            0x4fe39d460f70b5d7,
+            0x6fa3b58aa99d2f1a,
+        ],
+        [
+            0x991e96f50aba0ab2,
+            0xc2b6f443867adb31,
+            0xc1c93a376062db09,
+            0xd3e20fe490359eb1,
+            0xf2ea7514b1297b7b,
+            0x06f15e5f529c1f8b,
+            0x0a39fc286a3d8435,
+            0xf574dcac2bce2fc7,
+        ],
+        [
+            0x220cbebc84e3d12e,
+            0x3453eaa193e837f1,
+            0xd8b71333935203be,
+            0xa9d72c82ed03d675,
+            0x9d721cad685e353f,
+            0x488e857e335c3c7d,
+            0xf948e1a05d71e4dd,
+            0xef1fdfb3e81566d2,
+        ],
+        [
+            0x601758fd7c6cfe57,
+            0x7a56a27ea9ea63f5,
+            0xdfff00b723271a16,
+            0xbfcd1747253af5a3,
+            0x359e35d7800fffbd,
+            0x7f151c1f1686104a,
+            0x9a3f410c6ca92363,
+            0x4bea6bacad474799,
+        ],
+        [
+            0xfa68407a46647d6e,
+            0xbf71c57236904f35,
+            0x0af21f66c2bec6b6,
+            0xcffaa6b71c9ab7b4,
+            0x187f9ab49af08ec6,
+            0x2d66c4f95142a46c,
+            0x6fa4c33b7a3039c0,
+            0xae4faeae1d3ad3d9,
+        ],
+        [
+            0x8886564d3a14d493,
+            0x3517454ca23c4af3,
+            0x06476983284a0504,
+            0x0992abc52d822c37,
+            0xd3473e33197a93c9,
+            0x399ec6c7e6bf87c9,
+            0x51ac86febf240954,
+            0xf4c70e16eeaac5ec,
+        ],
+        [
+            0xa47f0dd4bf02e71e,
+            0x36acc2355951a8d9,
+            0x69d18d2bd1a5c42f,
+            0xf4892bcb929b0690,
+            0x89b4443b4ddbc49a,
+            0x4eb7f8719c36de1e,
+            0x03e7aa020c6e4141,
+            0x9b1f5b424d93c9a7,
+        ],
+        [
+            0x7261445183235adb,
+            0x0e38dc92cb1f2a60,
+            0x7b2b8a9aa6079c54,
+            0x800a440bdbb2ceb1,
+            0x3cd955b7e00d0984,
+            0x3a7d3a1b25894224,
+            0x944c9ad8ec165fde,
+            0x378f5a541631229b,
+        ],
+        [
+            0x74b4c7fb98459ced,
+            0x3698fad1153bb6c3,
+            0x7a1e6c303b7652f4,
+            0x9fe76702af69334b,
+            0x1fffe18a1b336103,
+            0x8941e71cff8a78db,
+            0x382ae548b2e4f3f3,
+            0xabbedea680056f52,
+        ],
+        [
+            0x6bcaa4cd81f32d1b,
+            0xdea2594ac06fd85d,
+            0xefbacd1d7d476e98,
+            0x8a1d71efea48b9ca,
+            0x2001802114846679,
+            0xd8fa6bbbebab0761,
+            0x3002c6cd635afe94,
+            0x7bcd9ed0efc889fb,
+        ],
+        [
+            0x48bc924af11bd720,
+            0xfaf417d5d9b21b99,
+            0xe71da4aa88e12852,
+            0x5d80ef9d1891cc86,
+            0xf82012d430219f9b,
+            0xcda43c32bcdf1d77,
+            0xd21380b00449b17a,
+            0x378ee767f11631ba,
+        ],
+    ];
+}
+
+use fnv1a::FNV1A_64;
+use streebog::STREEBOG256;
+
+static HMAC_STREEBOG256: hmac::Algorithm = hmac::Algorithm::from_digest_algorithm(&STREEBOG256);
+
+#[test]
+fn external_digest_fnv1a_test() {
+    // From the FNV reference implementation's test suite.
+    for &(input, expected) in &[
+        (&b""[..], 0xcbf29ce484222325u64),
+        (b"a", 0xaf63dc4c8601ec8c),
+        (b"foobar", 0x85944171f73967e8),
+    ] {
+        let actual = digest::digest(&FNV1A_64, input);
+        assert_eq!(actual.as_ref(), &expected.to_be_bytes());
+        assert_eq!(actual.algorithm(), &FNV1A_64);
+    }
+
+    assert_eq!(FNV1A_64.output_len(), 8);
+    assert_eq!(FNV1A_64.block_len(), 16);
+    assert_ne!(&FNV1A_64, &STREEBOG256);
+    assert_ne!(&FNV1A_64, &digest::SHA256);
+}
+
+#[test]
+fn external_digest_chunking_test() {
+    let input: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    let expected = digest::digest(&FNV1A_64, &input);
+
+    for chunk_len in &[1, 7, 15, 16, 17, 33, 999] {
+        let mut ctx = digest::Context::new(&FNV1A_64);
+        let mut cloned = None;
+        for (i, chunk) in input.chunks(*chunk_len).enumerate() {
+            ctx.update(chunk);
+            if i == 0 {
+                cloned = Some(ctx.clone());
+            }
+        }
+        let mut out = [0u8; digest::MAX_OUTPUT_LEN];
+        assert_eq!(ctx.finish_into(&mut out), Ok(8));
+        assert_eq!(&out[..8], expected.as_ref());
+
+        let mut cloned = cloned.unwrap();
+        cloned.update(&input[*chunk_len..]);
+        assert_eq!(cloned.finish().as_ref(), expected.as_ref());
+    }
+}
+
+#[test]
+fn external_digest_streebog_test() {
+    test::run(
+        test_file!("external_digest_tests.txt"),
+        |section, test_case| {
+            match section {
+                "Digest" => {
+                    let input = test_case.consume_bytes("Input");
+                    let output = test_case.consume_bytes("Output");
+                    let actual = digest::digest(&STREEBOG256, &input);
+                    assert_eq!(actual.as_ref(), &output[..]);
+                }
+                "HMAC" => {
+                    let key_value = test_case.consume_bytes("Key");
+                    let input = test_case.consume_bytes("Input");
+                    let output = test_case.consume_bytes("Output");
+                    let key = hmac::Key::new(HMAC_STREEBOG256, &key_value);
+                    assert_eq!(hmac::sign(&key, &input).as_ref(), &output[..]);
+                    assert!(hmac::verify(&key, &input, &output).is_ok());
+                }
+                "HKDF" => {
+                    let secret = test_case.consume_bytes("IKM");
+                    let salt = test_case.consume_bytes("salt");
+                    let info = test_case.consume_bytes("info");
+                    let expected_out = test_case.consume_bytes("OKM");
+                    let alg = hkdf::Algorithm::from_hmac_algorithm(HMAC_STREEBOG256);
+                    let My(out) = hkdf::Salt::new(alg, &salt)
+                        .extract(&secret)
+                        .expand(&[&info], My(expected_out.len()))
+                        .unwrap()
+                        .into();
+                    assert_eq!(out, expected_out);
+                }
+                "PBKDF2" => {
+                    let secret = test_case.consume_bytes("P");
+                    let salt = test_case.consume_bytes("S");
+                    let iterations = test_case.consume_usize("c");
+                    let dk = test_case.consume_bytes("DK");
+                    let alg = pbkdf2::Algorithm::from_hmac_algorithm(HMAC_STREEBOG256);
+                    let iterations = NonZeroU32::new(iterations.try_into().unwrap()).unwrap();
+                    let mut out = vec![0u8; dk.len()];
+                    pbkdf2::derive(alg, iterations, &salt, &secret, &mut out);
+                    assert_eq!(out, dk);
+                    assert!(pbkdf2::verify(alg, iterations, &salt, &secret, &dk).is_ok());
+                }
+                _ => unreachable!("unexpected section {}", section),
+            }
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn external_digest_hmac_key_eq_test() {
+    let key = hmac::Key::new(HMAC_STREEBOG256, b"key");
+    assert_eq!(key, hmac::Key::new(HMAC_STREEBOG256, b"key"));
+    assert_ne!(key, hmac::Key::new(HMAC_STREEBOG256, b"other key"));
+    assert_eq!(key.algorithm(), HMAC_STREEBOG256);
+    assert_eq!(key.algorithm().digest_algorithm(), &STREEBOG256);
+
+    let fnv = hmac::Algorithm::from_digest_algorithm(&FNV1A_64);
+    assert_ne!(fnv, HMAC_STREEBOG256);
+}
+
+#[test]
+fn external_digest_tagged_bytes_test() {
+    // Algorithms without an id in `ring::tagged` can't be encoded.
+    let digest = digest::digest(&FNV1A_64, b"hello, world");
+    assert!(digest.to_tagged_bytes().is_err());
+
+    for algorithm in [
+        HMAC_STREEBOG256,
+        hmac::Algorithm::from_digest_algorithm(&digest::SM3),
+    ] {
+        let key = hmac::Key::new(algorithm, b"key");
+        let tag = hmac::sign(&key, b"hello, world");
+        assert!(tag.to_tagged_bytes().is_err());
+    }
+
+    // HMAC built from a digest algorithm that has one does have an id.
+    let key = hmac::Key::new(
+        hmac::Algorithm::from_digest_algorithm(&digest::SHA256),
+        b"key",
+    );
+    assert!(hmac::sign(&key, b"hello, world").to_tagged_bytes().is_ok());
+}
+
+/// Generic newtype wrapper that lets us implement traits for externally-defined
+/// types.
+#[derive(Debug, PartialEq)]
+struct My<T: core::fmt::Debug + PartialEq>(T);
+
+impl hkdf::KeyType for My<usize> {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<hkdf::Okm<'_, My<usize>>> for My<Vec<u8>> {
+    fn from(okm: hkdf::Okm<My<usize>>) -> Self {
+        let mut r = vec![0u8; okm.len().0];
+        okm.fill(&mut r).unwrap();
+        Self(r)
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+// The expected values here are frozen. They were computed independently, as
+// `openssl pkey -pubout -outform DER | openssl dgst -sha256`, and must never
+// change.
+
+use ring::{
+    fingerprint, rand,
+    signature::{self, KeyPair},
+    test,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn check(fingerprint: fingerprint::PublicKeyFingerprint, expected_hex: &str, expected_b64: &str) {
+    assert_eq!(
+        fingerprint.as_ref(),
+        &test::from_hex(expected_hex).unwrap()[..]
+    );
+    assert_eq!(fingerprint.to_string(), expected_hex);
+    assert_eq!(fingerprint.base64url().to_string(), expected_b64);
+}
+
+#[test]
+fn fingerprint_ed25519() {
+    let key_pair =
+        signature::Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8"))
+            .unwrap();
+    check(
+        key_pair.spki_fingerprint(),
+        "0837d59f302d53c7be3b8050b76fa94c02fe7f9bb36475bab7ecb2d21e34a2d0",
+        "CDfVnzAtU8e-O4BQt2-pTAL-f5uzZHW6t-yy0h40otA",
+    );
+
+    // The fingerprint is the digest of the SPKI.
+    let mut spki = test::from_hex("302a300506032b6570032100").unwrap();
+    spki.extend_from_slice(key_pair.public_key().as_ref());
+    assert_eq!(
+        fingerprint::spki_sha256(&spki),
+        <[u8; fingerprint::FINGERPRINT_LEN]>::from(key_pair.spki_fingerprint())
+    );
+}
+
+#[test]
+fn fingerprint_ecdsa() {
+    let rng = rand::SystemRandom::new();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p256.p8"),
+        &rng,
+    )
+    .unwrap();
+    check(
+        key_pair.spki_fingerprint(),
+        "d94a9de174403555d3d34d2842d00897c6900ca0f6789ca86376e9525af87d2c",
+        "2Uqd4XRANVXT000oQtAIl8aQDKD2eJyoY3bpUlr4fSw",
+    );
+
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(
+        &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+        include_bytes!("ecdsa_test_private_key_p384.p8"),
+        &rng,
+    )
+    .unwrap();
+    check(
+        key_pair.spki_fingerprint(),
+        "5a0973b21a2b71b237fa29e3dfc752b3f3f270d5f763a57ce412aad31db40e49",
+        "WglzshorcbI3-inj38dSs_PycNX3Y6V85BKq0x20Dkk",
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn fingerprint_rsa() {
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    check(
+        key_pair.spki_fingerprint(),
+        "b4f547362d13b5f343cb2795be20017b6c932cbd6830a1b454e51a02b9890ba3",
+        "tPVHNi0TtfNDyyeVviABe2yTLL1oMKG0VOUaArmJC6M",
+    );
+}
+
+// From RFC 7638 Section 3.1.
+#[test]
+fn fingerprint_jwk_thumbprint() {
+    const CANONICAL_JWK: &[u8] = b"{\"e\":\"AQAB\",\"kty\":\"RSA\",\"n\":\"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw\"}";
+    assert_eq!(
+        fingerprint::jwk_thumbprint(CANONICAL_JWK),
+        [
+            0x37, 0x36, 0xcb, 0xb1, 0x78, 0x7c, 0xb8, 0x30, 0x9c, 0x77, 0xee, 0x8c, 0x37, 0x05,
+            0xc5, 0xe1, 0x6f, 0xfb, 0x9e, 0x85, 0x97, 0x15, 0x90, 0x1f, 0x1e, 0x4c, 0x59, 0xb1,
+            0x11, 0x82, 0xf5, 0x7b
+        ]
+    );
+}
@@ -55,6 +55,49 @@ fn hkdf_tests() {
     });
 }
 
+#[test]
+fn hkdf_prk_debug_tests() {
+    test::run(test_file!("hkdf_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let _ = test_case.consume_digest_alg("Hash");
+        let secret = test_case.consume_bytes("IKM");
+        let salt = test_case.consume_bytes("salt");
+        let _ = test_case.consume_bytes("info");
+        let expected_prk = test_case.consume_bytes("PRK");
+        let _ = test_case.consume_bytes("OKM");
+
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &salt).extract(&secret);
+
+        #[cfg(feature = "debug-prk")]
+        {
+            let hex: String = expected_prk.iter().map(|b| format!("{:02x}", b)).collect();
+            assert_eq!(format!("{:?}", prk), format!("Prk(\"{}\")", hex));
+            assert_eq!(prk.as_bytes_dangerous(), &expected_prk[..]);
+        }
+
+        #[cfg(not(feature = "debug-prk"))]
+        {
+            let _ = expected_prk;
+            assert_eq!(format!("{:?}", prk), "Prk { [REDACTED] }");
+        }
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "debug-prk")]
+#[test]
+fn hkdf_prk_from_okm_value_test() {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"salt").extract(b"secret");
+    let derived: hkdf::Prk = prk.expand(&[b"info"], hkdf::HKDF_SHA256).unwrap().into();
+
+    let My(expected) = prk
+        .expand(&[b"info"], My(digest::SHA256_OUTPUT_LEN))
+        .unwrap()
+        .into();
+    assert_eq!(derived.as_bytes_dangerous(), &expected[..]);
+}
+
 #[test]
 fn hkdf_output_len_tests() {
     for &alg in &[hkdf::HKDF_SHA256, hkdf::HKDF_SHA384, hkdf::HKDF_SHA512] {
@@ -99,6 +99,53 @@ fn hmac_test_case_inner(
     }
 }
 
+#[cfg(feature = "std")]
+#[test]
+fn hmac_update_vectored() {
+    use std::io::IoSlice;
+
+    for algorithm in [hmac::HMAC_SHA256, hmac::HMAC_SHA512] {
+        let key = hmac::Key::new(algorithm, b"key");
+        let block_len = algorithm.digest_algorithm().block_len();
+        let data = (0..(2 * block_len + 7))
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let expected = hmac::sign(&key, &data);
+
+        for split in 0..=data.len() {
+            let mut ctx = hmac::Context::with_key(&key);
+            ctx.update_vectored(&[IoSlice::new(&data[..split]), IoSlice::new(&data[split..])]);
+            assert_eq!(ctx.sign().as_ref(), expected.as_ref());
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn hmac_sign_reader() {
+    use std::io::ErrorKind;
+    use test::io::{FailingReader, OneByteReader};
+
+    // Longer than the 64 KiB internal buffer.
+    let data = (0..(64 * 1024 + 1000)).map(|i| i as u8).collect::<Vec<_>>();
+
+    for algorithm in [hmac::HMAC_SHA256, hmac::HMAC_SHA512] {
+        let key = hmac::Key::new(algorithm, b"key");
+        for len in [0, 1, data.len()] {
+            let expected = hmac::sign(&key, &data[..len]);
+            let actual = hmac::sign_reader(&key, &data[..len]).unwrap();
+            assert_eq!(actual.as_ref(), expected.as_ref());
+        }
+
+        let actual = hmac::sign_reader(&key, OneByteReader::new(&data[..])).unwrap();
+        assert_eq!(actual.as_ref(), hmac::sign(&key, &data).as_ref());
+
+        let err = hmac::sign_reader(&key, FailingReader::new(&data[..], ErrorKind::BrokenPipe))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+}
+
 #[test]
 fn hmac_debug() {
     let key = hmac::Key::new(hmac::HMAC_SHA256, &[0; 32]);
@@ -107,3 +154,38 @@ fn hmac_debug() {
     let ctx = hmac::Context::with_key(&key);
     assert_eq!("Context { algorithm: SHA256 }", format!("{:?}", &ctx));
 }
+
+#[test]
+fn hmac_key_eq() {
+    for algorithm in [
+        hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        hmac::HMAC_SHA256,
+        hmac::HMAC_SHA384,
+        hmac::HMAC_SHA512,
+    ] {
+        let key = hmac::Key::new(algorithm, b"key");
+        assert_eq!(key, hmac::Key::new(algorithm, b"key"));
+        assert_eq!(key, key.clone());
+        assert_ne!(key, hmac::Key::new(algorithm, b"kez"));
+        assert_ne!(key, hmac::Key::new(algorithm, b""));
+
+        // RFC 2104 pads short keys with zeros.
+        assert_eq!(key, hmac::Key::new(algorithm, b"key\0\0"));
+
+        // RFC 2104 hashes keys longer than a block.
+        let long_key_value = [1; 200];
+        let long_key = hmac::Key::new(algorithm, &long_key_value);
+        let hashed = digest::digest(algorithm.digest_algorithm(), &long_key_value);
+        assert_eq!(long_key, hmac::Key::new(algorithm, hashed.as_ref()));
+        assert_ne!(long_key, key);
+    }
+
+    assert_ne!(
+        hmac::Key::new(hmac::HMAC_SHA384, b"key"),
+        hmac::Key::new(hmac::HMAC_SHA512, b"key")
+    );
+    assert_ne!(
+        hmac::Key::new(hmac::HMAC_SHA256, b"key"),
+        hmac::Key::new(hmac::HMAC_SHA512, b"key")
+    );
+}
@@ -0,0 +1,125 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::inventory::{self, AlgorithmKind, Availability};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+const ALWAYS_LEN: usize = 44;
+const ALLOC_LEN: usize = 23;
+
+fn find(name: &str) -> Option<&'static inventory::AlgorithmInfo> {
+    inventory::inventory().find(|info| info.name() == name)
+}
+
+#[test]
+fn inventory_matches_features() {
+    let always = inventory::inventory()
+        .filter(|info| info.availability() == Availability::Always)
+        .count();
+    let alloc = inventory::inventory()
+        .filter(|info| info.availability() == Availability::Alloc)
+        .count();
+    assert_eq!(always, ALWAYS_LEN);
+    assert_eq!(
+        alloc,
+        if cfg!(feature = "alloc") {
+            ALLOC_LEN
+        } else {
+            0
+        }
+    );
+
+    assert_eq!(
+        find("RSA_PSS_2048_8192_SHA256").is_some(),
+        cfg!(feature = "alloc")
+    );
+    assert_eq!(find("RSA_PKCS1_SHA256").is_some(), cfg!(feature = "alloc"));
+}
+
+#[test]
+fn inventory_names_are_unique() {
+    let mut names = inventory::inventory()
+        .map(|info| info.name())
+        .collect::<Vec<_>>();
+    let len = names.len();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), len);
+}
+
+#[test]
+fn inventory_entries() {
+    let aes = find("AES_256_GCM").unwrap();
+    assert_eq!(aes.kind(), AlgorithmKind::Aead);
+    assert_eq!(aes.key_bits(), Some(256..=256));
+    assert_eq!(aes.availability(), Availability::Always);
+
+    let sha256 = find("SHA256").unwrap();
+    assert_eq!(sha256.kind(), AlgorithmKind::Digest);
+    assert_eq!(sha256.key_bits(), None);
+
+    assert_eq!(find("HMAC_SHA384").unwrap().kind(), AlgorithmKind::Hmac);
+    assert_eq!(find("HKDF_SHA256").unwrap().kind(), AlgorithmKind::Kdf);
+    assert_eq!(
+        find("PBKDF2_HMAC_SHA512").unwrap().kind(),
+        AlgorithmKind::Kdf
+    );
+
+    let ecdh = find("ECDH_P384").unwrap();
+    assert_eq!(ecdh.kind(), AlgorithmKind::Agreement);
+    assert_eq!(ecdh.key_bits(), Some(384..=384));
+
+    let ecdh = find("ECDH_P256_COMPACT").unwrap();
+    assert_eq!(ecdh.kind(), AlgorithmKind::Agreement);
+    assert_eq!(ecdh.key_bits(), Some(256..=256));
+    assert_eq!(
+        find("ECDH_BRAINPOOLP384R1").unwrap().kind(),
+        AlgorithmKind::Agreement
+    );
+
+    let committed = find("COMMITTED_CHACHA20_POLY1305").unwrap();
+    assert_eq!(committed.kind(), AlgorithmKind::Aead);
+    assert_eq!(committed.key_bits(), Some(256..=256));
+
+    for name in [
+        "ECDSA_P256_SHA256_ASN1_LOW_S",
+        "ECDSA_P256_SHA256_FIXED_LOW_S",
+        "ECDSA_P384_SHA384_ASN1_LOW_S",
+        "ECDSA_P384_SHA384_FIXED_LOW_S",
+        "ECDSA_BRAINPOOLP384R1_SHA384_ASN1",
+        "SM2_SM3_ASN1",
+    ] {
+        assert_eq!(find(name).unwrap().kind(), AlgorithmKind::Signature);
+    }
+
+    assert_eq!(find("ED25519").unwrap().kind(), AlgorithmKind::Signature);
+
+    #[cfg(feature = "alloc")]
+    {
+        let rsa = find("RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY").unwrap();
+        assert_eq!(rsa.kind(), AlgorithmKind::Signature);
+        assert_eq!(rsa.key_bits(), Some(1024..=8192));
+        assert_eq!(rsa.availability(), Availability::Alloc);
+
+        let rsa = find("RSA_PSS_2048_16384_SHA256").unwrap();
+        assert_eq!(rsa.key_bits(), Some(2048..=ring::rsa::MAX_RSA_BITS));
+    }
+
+    assert!(find("AES_128_CBC").is_none());
+}
@@ -0,0 +1,318 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "alloc")]
+
+use ring::{
+    agreement,
+    keytext::{Base64, Ed25519PublicKey, Error, X25519PublicKey},
+    rand,
+    signature::{self, KeyPair},
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+// The public key of RFC 8032 Section 7.1 TEST 1.
+const ED25519_HEX: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const ED25519_BASE64: &str = "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=";
+const ED25519_BASE64URL: &str = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+
+// Alice's public key from RFC 7748 Section 6.1.
+const X25519_HEX: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+const X25519_BASE64: &str = "hSDwCYkwp1R0i33ctD73Wg2/Og0mOBr066SpjqqbTmo=";
+const X25519_BASE64URL: &str = "hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo";
+
+#[test]
+fn keytext_ed25519_known_answer() {
+    let key: Ed25519PublicKey = ED25519_HEX.parse().unwrap();
+    assert_eq!(key.to_string(), ED25519_HEX);
+    assert_eq!(key.to_base64(Base64::Standard), ED25519_BASE64);
+    assert_eq!(key.to_base64(Base64::UrlSafe), ED25519_BASE64URL);
+    assert_eq!(
+        ED25519_HEX.to_uppercase().parse::<Ed25519PublicKey>(),
+        Ok(key)
+    );
+    for encoded in [
+        ED25519_BASE64,
+        ED25519_BASE64.trim_end_matches('='),
+        ED25519_BASE64URL,
+        &format!("{}=", ED25519_BASE64URL),
+    ] {
+        assert_eq!(
+            Ed25519PublicKey::from_base64(encoded),
+            Ok(key),
+            "{}",
+            encoded
+        );
+    }
+}
+
+#[test]
+fn keytext_x25519_known_answer() {
+    let key: X25519PublicKey = X25519_HEX.parse().unwrap();
+    assert_eq!(key.to_string(), X25519_HEX);
+    assert_eq!(key.to_base64(Base64::Standard), X25519_BASE64);
+    assert_eq!(key.to_base64(Base64::UrlSafe), X25519_BASE64URL);
+    assert_eq!(
+        X25519_HEX.to_uppercase().parse::<X25519PublicKey>(),
+        Ok(key)
+    );
+    for encoded in [
+        X25519_BASE64,
+        X25519_BASE64.trim_end_matches('='),
+        X25519_BASE64URL,
+        &format!("{}=", X25519_BASE64URL),
+    ] {
+        assert_eq!(
+            X25519PublicKey::from_base64(encoded),
+            Ok(key),
+            "{}",
+            encoded
+        );
+    }
+}
+
+#[test]
+fn keytext_ed25519_round_trip() {
+    const MESSAGE: &[u8] = b"hello, world";
+    for seed in 0..=255u8 {
+        let key_pair = signature::Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap();
+        let key = Ed25519PublicKey::from_bytes(key_pair.public_key().as_ref()).unwrap();
+        assert_eq!(key.as_ref(), key_pair.public_key().as_ref());
+
+        let hex = key.to_string();
+        assert_eq!(hex.parse::<Ed25519PublicKey>(), Ok(key));
+        for alphabet in [Base64::Standard, Base64::UrlSafe] {
+            let base64 = key.to_base64(alphabet);
+            assert_eq!(Ed25519PublicKey::from_base64(&base64), Ok(key));
+        }
+
+        let signature = key_pair.sign(MESSAGE);
+        let parsed: Ed25519PublicKey = hex.parse().unwrap();
+        assert_eq!(
+            parsed.unparsed().verify(MESSAGE, signature.as_ref()),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn keytext_x25519_round_trip() {
+    let rng = rand::SystemRandom::new();
+    for _ in 0..16 {
+        let my_private_key =
+            agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap();
+        let peer_private_key =
+            agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap();
+        let my_public_key = my_private_key.compute_public_key().unwrap();
+        let peer_public_key = peer_private_key.compute_public_key().unwrap();
+
+        let key = X25519PublicKey::from_bytes(peer_public_key.as_ref()).unwrap();
+        let hex = key.to_string();
+        assert_eq!(hex.parse::<X25519PublicKey>(), Ok(key));
+        for alphabet in [Base64::Standard, Base64::UrlSafe] {
+            let base64 = key.to_base64(alphabet);
+            assert_eq!(X25519PublicKey::from_base64(&base64), Ok(key));
+        }
+
+        let parsed = X25519PublicKey::from_base64(&key.to_base64(Base64::UrlSafe)).unwrap();
+        let mine =
+            agreement::agree_ephemeral(my_private_key, &parsed.unparsed(), |k| k.to_vec()).unwrap();
+        let theirs = agreement::agree_ephemeral(
+            peer_private_key,
+            &agreement::UnparsedPublicKey::new(&agreement::X25519, my_public_key.as_ref()),
+            |k| k.to_vec(),
+        )
+        .unwrap();
+        assert_eq!(mine, theirs);
+    }
+}
+
+#[test]
+fn keytext_malformed_hex() {
+    let parse = |s: &str| s.parse::<Ed25519PublicKey>();
+
+    assert_eq!(
+        parse(""),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        parse(&ED25519_HEX[..62]),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+    assert_eq!(
+        parse(&format!("{}00", ED25519_HEX)),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 33
+        })
+    );
+    assert_eq!(parse(&ED25519_HEX[..63]), Err(Error::Truncated));
+    assert_eq!(
+        parse(&format!("{}\n", ED25519_HEX)),
+        Err(Error::InvalidCharacter { offset: 64 })
+    );
+    assert_eq!(
+        parse(&format!(" {}", ED25519_HEX)),
+        Err(Error::InvalidCharacter { offset: 0 })
+    );
+    assert_eq!(
+        parse(&format!("0x{}", &ED25519_HEX[2..])),
+        Err(Error::InvalidCharacter { offset: 1 })
+    );
+    assert_eq!(
+        parse(&format!("{}G", &ED25519_HEX[..63])),
+        Err(Error::InvalidCharacter { offset: 63 })
+    );
+    // A multi-byte character is reported at the offset of its first byte.
+    assert_eq!(
+        parse(&format!("{}é", &ED25519_HEX[..62])),
+        Err(Error::InvalidCharacter { offset: 62 })
+    );
+    assert_eq!(
+        parse(&format!("D{}", &ED25519_HEX[1..])),
+        Err(Error::MixedCase)
+    );
+
+    // Base64 isn't accepted as hex.
+    assert_eq!(
+        parse(ED25519_BASE64),
+        Err(Error::InvalidCharacter { offset: 2 })
+    );
+}
+
+#[test]
+fn keytext_malformed_base64() {
+    let parse = X25519PublicKey::from_base64;
+    let unpadded = X25519_BASE64.trim_end_matches('=');
+
+    assert_eq!(
+        parse(""),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        parse(&unpadded[..40]),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 30
+        })
+    );
+    assert_eq!(
+        parse(&format!("{}A", unpadded)),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 33
+        })
+    );
+    assert_eq!(parse(&unpadded[..41]), Err(Error::Truncated));
+
+    // Mixing the standard and URL-safe alphabets is ambiguous.
+    assert!(X25519_BASE64.contains('/'));
+    assert_eq!(
+        parse(&format!("-{}", &X25519_BASE64[1..])),
+        Err(Error::MixedAlphabets)
+    );
+    assert_eq!(
+        parse(&format!("+{}", &X25519_BASE64URL[1..])),
+        Err(Error::MixedAlphabets)
+    );
+
+    assert_eq!(
+        parse(&format!("{}==", unpadded)),
+        Err(Error::InvalidPadding)
+    );
+    assert_eq!(
+        parse(&format!("{}===", unpadded)),
+        Err(Error::InvalidPadding)
+    );
+    assert_eq!(parse("AAAA===="), Err(Error::InvalidPadding));
+    assert_eq!(
+        parse(&format!("{}=A", unpadded)),
+        Err(Error::InvalidPadding)
+    );
+    assert_eq!(
+        parse(&format!("{}\n", X25519_BASE64)),
+        Err(Error::InvalidCharacter { offset: 44 })
+    );
+    assert_eq!(
+        parse(&format!(
+            "{} {}",
+            &X25519_BASE64[..20],
+            &X25519_BASE64[20..]
+        )),
+        Err(Error::InvalidCharacter { offset: 20 })
+    );
+
+    // The last character has unused bits, which must be zero.
+    assert_eq!(
+        parse(&format!("{}n", &unpadded[..42])),
+        Err(Error::NonCanonical)
+    );
+}
+
+#[test]
+fn keytext_from_bytes() {
+    assert_eq!(
+        Ed25519PublicKey::from_bytes(&[0; 31]),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+    assert_eq!(
+        X25519PublicKey::from_bytes(&[0; 33]),
+        Err(Error::WrongLength {
+            expected: 32,
+            actual: 33
+        })
+    );
+}
+
+#[test]
+fn keytext_error_display() {
+    assert_eq!(
+        Error::InvalidCharacter { offset: 3 }.to_string(),
+        "invalid character at offset 3"
+    );
+    assert_eq!(
+        Error::WrongLength {
+            expected: 32,
+            actual: 31
+        }
+        .to_string(),
+        "key is 31 bytes; expected 32 bytes"
+    );
+}
+
+#[test]
+fn keytext_debug() {
+    let key: Ed25519PublicKey = ED25519_HEX.parse().unwrap();
+    assert_eq!(
+        format!("{:?}", key),
+        format!("Ed25519PublicKey(\"{}\")", ED25519_HEX)
+    );
+}
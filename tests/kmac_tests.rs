@@ -0,0 +1,124 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{kmac, test, test_file};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[test]
+fn kmac_tests() {
+    test::run(test_file!("kmac_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+        let algorithm = test_case.consume_string("Algorithm");
+        let key = test_case.consume_bytes("Key");
+        let data = test_case.consume_bytes("Data");
+        let customization = String::from_utf8(test_case.consume_bytes("S")).unwrap();
+        let output = test_case.consume_bytes("Output");
+
+        let start = match algorithm.as_ref() {
+            "KMAC128" => kmac::Kmac128Key::new(&key, &customization).unwrap().start(),
+            "KMAC256" => kmac::Kmac256Key::new(&key, &customization).unwrap().start(),
+            _ => panic!("Unsupported algorithm: {}", algorithm),
+        };
+
+        // One-shot.
+        let mut ctx = start.clone();
+        ctx.update(&data);
+        let mut actual = vec![0; output.len()];
+        ctx.finish_into(&mut actual);
+        assert_eq!(actual, output);
+
+        // Incremental, in chunks of every size.
+        for chunk_len in 1..=data.len() {
+            let mut ctx = start.clone();
+            for chunk in data.chunks(chunk_len) {
+                ctx.update(chunk);
+            }
+            assert!(ctx.verify(&output).is_ok());
+        }
+
+        // A truncated tag is not a shorter tag.
+        let mut ctx = start.clone();
+        ctx.update(&data);
+        assert!(ctx.verify(&output[..output.len() - 1]).is_err());
+
+        let mut wrong = output.clone();
+        wrong[output.len() - 1] ^= 1;
+        let mut ctx = start;
+        ctx.update(&data);
+        assert!(ctx.verify(&wrong).is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn kmac_key_test() {
+    const KEY: &[u8] = &[0x42; 32];
+
+    assert!(kmac::Kmac128Key::new(&KEY[..15], "").is_err());
+    assert!(kmac::Kmac128Key::new(&KEY[..16], "").is_ok());
+    assert!(kmac::Kmac256Key::new(&KEY[..31], "").is_err());
+    assert!(kmac::Kmac256Key::new(KEY, "").is_ok());
+
+    // The customization string separates uses of the same key.
+    let a = kmac::Kmac256Key::new(KEY, "a").unwrap();
+    let b = kmac::Kmac256Key::new(KEY, "b").unwrap();
+    let mut tag = [0u8; 32];
+    let mut ctx = a.start();
+    ctx.update(b"msg");
+    ctx.finish_into(&mut tag);
+    assert!(a.verify(b"msg", &tag).is_ok());
+    assert!(b.verify(b"msg", &tag).is_err());
+    assert!(a.verify(b"msh", &tag).is_err());
+
+    // Tags longer than the rate.
+    let mut long_tag = [0u8; 300];
+    let mut ctx = a.start();
+    ctx.update(b"msg");
+    ctx.finish_into(&mut long_tag);
+    assert!(a.verify(b"msg", &long_tag).is_ok());
+    assert_ne!(long_tag[..32], tag[..]);
+
+    // Short tags are rejected even when they're correct.
+    let mut short_tag = [0u8; 3];
+    let mut ctx = a.start();
+    ctx.update(b"msg");
+    ctx.finish_into(&mut short_tag);
+    assert!(a.verify(b"msg", &short_tag).is_err());
+    assert!(a.verify(b"msg", &[]).is_err());
+
+    assert_eq!(format!("{:?}", a), "Kmac256Key { .. }");
+    assert_eq!(
+        format!("{:?}", a.start()),
+        r#"KmacContext { algorithm: "KMAC256" }"#
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn kmac_mac_test() {
+    let key = kmac::Kmac128Key::new(&[0x42; 16], "").unwrap();
+    let tag = key.mac(b"msg", 40);
+    assert_eq!(tag.len(), 40);
+    assert!(key.verify(b"msg", &tag).is_ok());
+
+    let mut ctx = key.start();
+    ctx.update(b"msg");
+    assert_eq!(ctx.finish(40), tag);
+}
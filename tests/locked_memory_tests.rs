@@ -0,0 +1,69 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "locked-memory")]
+
+//! The rest of the test suite checks that keys work the same with the
+//! `locked-memory` feature; these tests check the lock status. The fallback
+//! used when memory can't be locked is tested by the unit tests of the
+//! allocator, since whether this process can lock memory depends on the
+//! environment.
+
+use ring::{hmac, rand, rsa, signature, test};
+
+// Whether this process can lock a page of memory right now.
+#[cfg(unix)]
+fn can_lock_memory() -> bool {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let layout = std::alloc::Layout::from_size_align(page_size, page_size).unwrap();
+    let page = unsafe { std::alloc::alloc(layout) };
+    assert!(!page.is_null());
+    let locked = unsafe { libc::mlock(page.cast(), page_size) } == 0;
+    if locked {
+        assert_eq!(unsafe { libc::munlock(page.cast(), page_size) }, 0);
+    }
+    unsafe { std::alloc::dealloc(page, layout) };
+    locked
+}
+
+#[cfg(not(unix))]
+fn can_lock_memory() -> bool {
+    cfg!(windows)
+}
+
+#[test]
+fn locked_memory_status_test() {
+    let expected = can_lock_memory();
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &[0x0b; 20]);
+    assert_eq!(key.was_locked(), expected);
+    let clone = key.clone();
+    assert_eq!(clone.was_locked(), expected);
+    // RFC 4231 Section 4.2.
+    assert_eq!(
+        hmac::sign(&clone, b"Hi There").as_ref(),
+        test::from_hex("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7").unwrap()
+    );
+
+    let rng = rand::SystemRandom::new();
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    assert_eq!(key_pair.was_locked(), expected);
+
+    let key_pair = rsa::KeyPair::from_der(include_bytes!(
+        "../src/rsa/signature_rsa_example_private_key.der"
+    ))
+    .unwrap();
+    assert_eq!(key_pair.was_locked(), expected);
+}
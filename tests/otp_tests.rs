@@ -0,0 +1,120 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{error, hmac, otp};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+// RFC 4226 Appendix D.
+#[test]
+fn otp_hotp_rfc4226_test() {
+    const CODES: [u32; 10] = [
+        755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489,
+    ];
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, b"12345678901234567890");
+    for (counter, expected) in (0..).zip(CODES) {
+        assert_eq!(otp::hotp(&key, counter, 6), Ok(expected));
+    }
+}
+
+// RFC 6238 Appendix B.
+#[cfg(feature = "alloc")]
+#[test]
+fn otp_totp_rfc6238_test() {
+    let keys = [
+        hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, b"12345678901234567890"),
+        hmac::Key::new(hmac::HMAC_SHA256, b"12345678901234567890123456789012"),
+        hmac::Key::new(
+            hmac::HMAC_SHA512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+        ),
+    ];
+    let vectors: [(u64, [&str; 3]); 6] = [
+        (59, ["94287082", "46119246", "90693936"]),
+        (1111111109, ["07081804", "68084774", "25091201"]),
+        (1111111111, ["14050471", "67062674", "99943326"]),
+        (1234567890, ["89005924", "91819424", "93441116"]),
+        (2000000000, ["69279037", "90698825", "38618901"]),
+        (20000000000, ["65353130", "77737706", "47863826"]),
+    ];
+    for (unix_time, codes) in vectors {
+        for (key, expected) in keys.iter().zip(codes) {
+            let code = otp::totp(key, unix_time, 30, 8).unwrap();
+            assert_eq!(otp::format_code(code, 8).unwrap(), expected);
+            assert_eq!(otp::verify_totp(key, unix_time, 30, 8, 0, code), Ok(()));
+
+            // Fewer digits are a suffix of the 8-digit code.
+            for digits in [6, 7] {
+                let code = otp::totp(key, unix_time, 30, digits).unwrap();
+                assert_eq!(
+                    otp::format_code(code, digits).unwrap(),
+                    expected[usize::from(8 - digits)..]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn otp_verify_totp_window_test() {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, b"12345678901234567890123456789012");
+    let now = 1234567890;
+    let code = otp::totp(&key, now, 30, 6).unwrap();
+
+    assert_eq!(otp::verify_totp(&key, now, 30, 6, 0, code), Ok(()));
+    assert_eq!(otp::verify_totp(&key, now - 30, 30, 6, 1, code), Ok(()));
+    assert_eq!(otp::verify_totp(&key, now + 30, 30, 6, 1, code), Ok(()));
+    assert_eq!(
+        otp::verify_totp(&key, now + 30, 30, 6, 0, code),
+        Err(error::Unspecified)
+    );
+    assert_eq!(
+        otp::verify_totp(&key, now + 60, 30, 6, 1, code),
+        Err(error::Unspecified)
+    );
+    assert_eq!(
+        otp::verify_totp(&key, now, 30, 6, 1, (code + 1) % 1_000_000),
+        Err(error::Unspecified)
+    );
+
+    // The window is clamped at the epoch.
+    let code = otp::totp(&key, 0, 30, 6).unwrap();
+    assert_eq!(otp::verify_totp(&key, 10, 30, 6, 5, code), Ok(()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn otp_invalid_parameters_test() {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, b"key");
+    for digits in [0, 5, 9, 10] {
+        assert_eq!(otp::hotp(&key, 0, digits), Err(error::Unspecified));
+        assert_eq!(otp::totp(&key, 0, 30, digits), Err(error::Unspecified));
+        assert_eq!(
+            otp::verify_totp(&key, 0, 30, digits, 1, 0),
+            Err(error::Unspecified)
+        );
+        assert_eq!(otp::format_code(0, digits), Err(error::Unspecified));
+    }
+    assert_eq!(otp::totp(&key, 0, 0, 6), Err(error::Unspecified));
+    assert_eq!(
+        otp::verify_totp(&key, 0, 0, 6, 1, 0),
+        Err(error::Unspecified)
+    );
+    assert_eq!(otp::format_code(1_000_000, 6), Err(error::Unspecified));
+    assert_eq!(otp::format_code(42, 6).unwrap(), "000042");
+}
@@ -0,0 +1,148 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{
+    password_hash::{PasswordHash, Pbkdf2PasswordHasher, MIN_ITERATIONS, MIN_SALT_LEN},
+    rand,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+// Generated by Python's passlib, `pbkdf2_sha256.hash("password")`, with a
+// low iteration count.
+const PASSLIB_HASH: &str =
+    "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M";
+
+// `hashlib.pbkdf2_hmac("sha256", b"correct horse battery staple",
+// bytes(range(16)), 100000)`, encoded as passlib would.
+const CURRENT_HASH: &str =
+    "$pbkdf2-sha256$100000$AAECAwQFBgcICQoLDA0ODw$SdScJfWXhGIJ8Nkud3CrZOHHXpS0zmxQkmXuZxddKh4";
+
+fn hasher() -> Pbkdf2PasswordHasher {
+    Pbkdf2PasswordHasher::new(MIN_ITERATIONS, MIN_SALT_LEN).unwrap()
+}
+
+#[test]
+fn password_hasher_new() {
+    assert!(Pbkdf2PasswordHasher::new(MIN_ITERATIONS - 1, MIN_SALT_LEN).is_err());
+    assert!(Pbkdf2PasswordHasher::new(0, MIN_SALT_LEN).is_err());
+    assert!(Pbkdf2PasswordHasher::new(MIN_ITERATIONS, MIN_SALT_LEN - 1).is_err());
+    assert!(Pbkdf2PasswordHasher::new(MIN_ITERATIONS, 65).is_err());
+    assert!(Pbkdf2PasswordHasher::new(MIN_ITERATIONS, 64).is_ok());
+    assert!(Pbkdf2PasswordHasher::new(u32::MAX, MIN_SALT_LEN).is_ok());
+}
+
+#[test]
+fn password_hash_round_trip() {
+    let rng = rand::SystemRandom::new();
+    let hasher = hasher();
+
+    let hash = hasher.hash_password(b"hunter2", &rng).unwrap();
+    assert_eq!(hash.iterations().get(), MIN_ITERATIONS);
+    assert_eq!(hash.salt().len(), MIN_SALT_LEN);
+    assert!(hasher.verify_password(b"hunter2", &hash).is_ok());
+    assert!(hasher.verify_password(b"hunter3", &hash).is_err());
+    assert!(!hasher.needs_rehash(&hash));
+
+    let encoded = hash.to_string();
+    assert!(encoded.starts_with("$pbkdf2-sha256$100000$"));
+    let parsed: PasswordHash = encoded.parse().unwrap();
+    assert_eq!(parsed.to_string(), encoded);
+    assert!(hasher.verify_password(b"hunter2", &parsed).is_ok());
+
+    // Salts are random.
+    let other = hasher.hash_password(b"hunter2", &rng).unwrap();
+    assert_ne!(other.salt(), hash.salt());
+}
+
+#[test]
+fn password_hash_interop() {
+    let hasher = hasher();
+
+    let hash: PasswordHash = CURRENT_HASH.parse().unwrap();
+    assert_eq!(hash.to_string(), CURRENT_HASH);
+    assert_eq!(
+        hash.salt(),
+        &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+    );
+    assert!(hasher
+        .verify_password(b"correct horse battery staple", &hash)
+        .is_ok());
+    assert!(hasher.verify_password(b"password", &hash).is_err());
+
+    let hash: PasswordHash = PASSLIB_HASH.parse().unwrap();
+    assert_eq!(hash.to_string(), PASSLIB_HASH);
+    assert!(hasher.verify_password(b"password", &hash).is_ok());
+}
+
+#[test]
+fn password_hash_upgrade() {
+    let rng = rand::SystemRandom::new();
+    let hasher = hasher();
+
+    let old: PasswordHash = PASSLIB_HASH.parse().unwrap();
+    assert!(hasher.needs_rehash(&old));
+    assert!(hasher.verify_and_upgrade(b"wrong", &old, &rng).is_err());
+    let new = hasher
+        .verify_and_upgrade(b"password", &old, &rng)
+        .unwrap()
+        .unwrap();
+    assert_eq!(new.iterations().get(), MIN_ITERATIONS);
+    assert!(hasher.verify_password(b"password", &new).is_ok());
+    assert!(!hasher.needs_rehash(&new));
+
+    let current: PasswordHash = CURRENT_HASH.parse().unwrap();
+    assert!(hasher
+        .verify_and_upgrade(b"correct horse battery staple", &current, &rng)
+        .unwrap()
+        .is_none());
+
+    // A longer salt also triggers a rehash.
+    let longer_salt = Pbkdf2PasswordHasher::new(MIN_ITERATIONS, 32).unwrap();
+    assert!(longer_salt.needs_rehash(&current));
+}
+
+#[test]
+fn password_hash_rejects_other_formats() {
+    for s in [
+        // bcrypt, e.g. from a system being migrated.
+        "$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW",
+        "$2a$10$N9qo8uLOickgx2ZMRZoMyeIjZAgcfl7p92ldGxad68LJZdL17lhWy",
+        // Other PBKDF2 variants.
+        "$pbkdf2-sha512$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        "$pbkdf2$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        // PHC-style parameters.
+        "$pbkdf2-sha256$i=6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        // Bad iteration counts.
+        "$pbkdf2-sha256$0$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        "$pbkdf2-sha256$06400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        "$pbkdf2-sha256$+6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        "$pbkdf2-sha256$4294967296$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        // Empty salt.
+        "$pbkdf2-sha256$6400$$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        // Truncated hash.
+        "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9",
+        // Standard base64.
+        "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw==$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+        // Trailing field.
+        "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M$",
+        "",
+    ] {
+        assert!(s.parse::<PasswordHash>().is_err(), "{}", s);
+    }
+}
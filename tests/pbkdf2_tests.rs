@@ -13,7 +13,7 @@
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
 use core::num::NonZeroU32;
-use ring::{digest, error, pbkdf2, test, test_file};
+use ring::{digest, error, hmac, pbkdf2, test, test_file};
 
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
@@ -66,3 +66,188 @@ pub fn pbkdf2_tests() {
         Ok(())
     });
 }
+
+#[test]
+pub fn pbkdf2_derive_hmac_key_test() {
+    const MESSAGE: &[u8] = b"RADIUS message";
+    let iterations = NonZeroU32::new(100).unwrap();
+    for (algorithm, hmac_algorithm) in [
+        (
+            pbkdf2::PBKDF2_HMAC_SHA1,
+            hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        ),
+        (pbkdf2::PBKDF2_HMAC_SHA256, hmac::HMAC_SHA256),
+        (pbkdf2::PBKDF2_HMAC_SHA384, hmac::HMAC_SHA384),
+        (pbkdf2::PBKDF2_HMAC_SHA512, hmac::HMAC_SHA512),
+    ] {
+        let key = pbkdf2::derive_hmac_key(algorithm, iterations, b"salt", b"password");
+        assert_eq!(key.algorithm(), hmac_algorithm);
+
+        // The key is the `output_len`-byte PBKDF2 output.
+        let mut key_value = vec![0u8; hmac_algorithm.digest_algorithm().output_len()];
+        pbkdf2::derive(algorithm, iterations, b"salt", b"password", &mut key_value);
+        let expected = hmac::Key::new(hmac_algorithm, &key_value);
+        let tag = hmac::sign(&key, MESSAGE);
+        assert_eq!(tag.as_ref(), hmac::sign(&expected, MESSAGE).as_ref());
+        assert!(hmac::verify(&expected, MESSAGE, tag.as_ref()).is_ok());
+
+        let other = pbkdf2::derive_hmac_key(algorithm, iterations, b"salt", b"passwore");
+        assert!(hmac::verify(&other, MESSAGE, tag.as_ref()).is_err());
+    }
+}
+
+#[test]
+pub fn pbkdf2_verify_phc_test() {
+    use pbkdf2::VerifyError;
+
+    for (stored, password) in [
+        // passlib's pbkdf2_sha1 and pbkdf2_sha256 documentation examples,
+        // converted from passlib's format ("$<id>$<iterations>$...", with `.`
+        // in place of `+` in the base64) to the PHC format.
+        (
+            "$pbkdf2$i=1212$OB+dtnSEXZK8U5cgxU/GYQ$y5LKPOplRmok7CZp/aqVDVg8zGI",
+            "password",
+        ),
+        (
+            "$pbkdf2-sha256$i=6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+            "password",
+        ),
+        // Generated with Python's `hashlib.pbkdf2_hmac`.
+        (
+            "$pbkdf2-sha512$i=1000,l=64$++++ASNFZ4mrze/7774BIw$kvN0U4vYmR00Co4P+A9q9Bj+QgtUx12VwiTUnsfnxpTI6OTuIjX6QxSdo4NGzPX1Bo6zD7VAhZBL1GO13fHDUw",
+            "correct horse battery staple",
+        ),
+        (
+            "$pbkdf2-sha256$i=1000$++++ASNFZ4mrze/7774BIw$c3S1xm3j/bKA52QjebclEA",
+            "hunter2",
+        ),
+    ] {
+        assert_eq!(pbkdf2::verify_phc(stored, password.as_bytes()), Ok(()));
+        assert_eq!(
+            pbkdf2::verify_phc(stored, b"wrong password"),
+            Err(VerifyError::Mismatch)
+        );
+    }
+
+    const SALT: &str = "0ZrzXitFSGltTQnBWOsdAw";
+    const HASH: &str = "Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M";
+    for (stored, expected) in [
+        ("", VerifyError::Malformed),
+        ("pbkdf2-sha256$i=6400$salt$hash", VerifyError::Malformed),
+        ("$pbkdf2-sha256$i=6400$salt", VerifyError::Malformed),
+        ("$pbkdf2-sha256$i=6400$$", VerifyError::Malformed),
+        // passlib's format.
+        (
+            "$pbkdf2-sha256$6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M",
+            VerifyError::Malformed,
+        ),
+        // Other algorithms.
+        (
+            "$2b$12$GhvMmNVjRW29ulnudl.LbuAnUtN/LRfe1JsBm1Xu6LE3059z5Tr8m",
+            VerifyError::UnsupportedAlgorithm,
+        ),
+        (
+            "$argon2id$v=19$m=65536,t=2,p=1$gZiV/M1gPc22ElAH/Jh1Hw$CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno",
+            VerifyError::UnsupportedAlgorithm,
+        ),
+        ("$pbkdf2-sha3-256$i=6400$AAAA$AAAA", VerifyError::UnsupportedAlgorithm),
+    ] {
+        assert_eq!(pbkdf2::verify_phc(stored, b"password"), Err(expected));
+    }
+
+    for (params, salt, hash, expected) in [
+        ("i=6400", SALT, HASH, Ok(())),
+        ("i=6400,l=32", SALT, HASH, Ok(())),
+        ("l=32,i=6400", SALT, HASH, Ok(())),
+        ("i=1000", SALT, HASH, Err(VerifyError::Mismatch)),
+        ("i=999", SALT, HASH, Err(VerifyError::WeakParameters)),
+        ("i=0", SALT, HASH, Err(VerifyError::WeakParameters)),
+        (
+            "i=6400",
+            SALT,
+            // 15 bytes.
+            "AAAAAAAAAAAAAAAAAAAA",
+            Err(VerifyError::WeakParameters),
+        ),
+        ("", SALT, HASH, Err(VerifyError::Malformed)),
+        ("l=32", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=6400,l=31", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=6400,i=6400", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=06400", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=+6400", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=4294967296", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=6400,p=1", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i", SALT, HASH, Err(VerifyError::Malformed)),
+        ("i=6400", "", HASH, Err(VerifyError::Malformed)),
+        (
+            "i=6400",
+            "0ZrzXitFSGltTQnBWOsdAw==",
+            HASH,
+            Err(VerifyError::Malformed),
+        ),
+        (
+            "i=6400",
+            "0ZrzXitFSGltTQnBWOsdAx",
+            HASH,
+            Err(VerifyError::Malformed),
+        ),
+        (
+            "i=6400",
+            SALT,
+            "Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M=",
+            Err(VerifyError::Malformed),
+        ),
+    ] {
+        let stored = format!("$pbkdf2-sha256${}${}${}", params, salt, hash);
+        assert_eq!(
+            pbkdf2::verify_phc(&stored, b"password"),
+            expected,
+            "{}",
+            stored
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+pub fn pbkdf2_format_phc_test() {
+    let iterations = NonZeroU32::new(6400).unwrap();
+    let salt = test::from_hex("d19af35e2b4548696d4d09c158eb1d03").unwrap();
+    let mut derived = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        b"password",
+        &mut derived,
+    );
+    assert_eq!(
+        pbkdf2::format_phc(pbkdf2::PBKDF2_HMAC_SHA256, iterations, &salt, &derived),
+        "$pbkdf2-sha256$i=6400$0ZrzXitFSGltTQnBWOsdAw$Y11AchqV4b0sUisdZd0Xr97KWoymNE0LNNrnEgY4H9M"
+    );
+
+    // Round trips.
+    for algorithm in [
+        pbkdf2::PBKDF2_HMAC_SHA1,
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        pbkdf2::PBKDF2_HMAC_SHA384,
+        pbkdf2::PBKDF2_HMAC_SHA512,
+    ] {
+        for (salt_len, derived_len) in [(1, 16), (16, 20), (17, 33), (64, 64)] {
+            let salt = vec![0xfb; salt_len];
+            let mut derived = vec![0u8; derived_len];
+            pbkdf2::derive(algorithm, iterations, &salt, b"password", &mut derived);
+            let stored = pbkdf2::format_phc(algorithm, iterations, &salt, &derived);
+            assert_eq!(
+                pbkdf2::verify_phc(&stored, b"password"),
+                Ok(()),
+                "{}",
+                stored
+            );
+            assert_eq!(
+                pbkdf2::verify_phc(&stored, b"Password"),
+                Err(pbkdf2::VerifyError::Mismatch)
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "alloc")]
+
+use ring::{
+    aead, error, pkcs8, rand,
+    signature::{self, KeyPair},
+    test,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+const KEK_ALGORITHMS: [&aead::Algorithm; 3] = [
+    &aead::AES_128_GCM,
+    &aead::AES_256_GCM,
+    &aead::CHACHA20_POLY1305,
+];
+
+fn kek(algorithm: &'static aead::Algorithm, byte: u8) -> aead::LessSafeKey {
+    let key_bytes = vec![byte; algorithm.key_len()];
+    aead::LessSafeKey::new(aead::UnboundKey::new(algorithm, &key_bytes).unwrap())
+}
+
+#[test]
+fn pkcs8_seal_round_trip() {
+    let rng = rand::SystemRandom::new();
+
+    let documents = [
+        (
+            "ECDSA P-256",
+            signature::EcdsaKeyPair::generate_pkcs8(
+                &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                &rng,
+            )
+            .unwrap(),
+        ),
+        (
+            "ECDSA P-384",
+            signature::EcdsaKeyPair::generate_pkcs8(
+                &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+                &rng,
+            )
+            .unwrap(),
+        ),
+        (
+            "Ed25519",
+            signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap(),
+        ),
+    ];
+
+    for (name, document) in &documents {
+        for algorithm in KEK_ALGORITHMS {
+            let kek = kek(algorithm, 0x42);
+            let sealed = document.seal(&kek, b"signing key", &rng).unwrap();
+            assert_eq!(
+                sealed.len(),
+                2 + aead::NONCE_LEN + document.as_ref().len() + algorithm.tag_len(),
+                "{}",
+                name
+            );
+            assert_eq!(sealed[0], pkcs8::SEALED_VERSION);
+
+            let opened = pkcs8::Document::open_sealed(&kek, b"signing key", &sealed).unwrap();
+            assert_eq!(opened.as_ref(), document.as_ref(), "{}", name);
+
+            // Sealing is randomized.
+            let sealed_again = document.seal(&kek, b"signing key", &rng).unwrap();
+            assert_ne!(sealed, sealed_again);
+        }
+    }
+
+    // The opened documents are usable.
+    let kek = kek(&aead::AES_256_GCM, 0x42);
+    let sealed = documents[2].1.seal(&kek, b"", &rng).unwrap();
+    let opened = pkcs8::Document::open_sealed(&kek, b"", &sealed).unwrap();
+    let original = signature::Ed25519KeyPair::from_pkcs8(documents[2].1.as_ref()).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(opened.as_ref()).unwrap();
+    assert_eq!(
+        key_pair.public_key().as_ref(),
+        original.public_key().as_ref()
+    );
+}
+
+#[test]
+fn pkcs8_open_sealed_errors() {
+    let rng = rand::SystemRandom::new();
+    let document = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+
+    for algorithm in KEK_ALGORITHMS {
+        let kek = kek(algorithm, 0x42);
+        let sealed = document.seal(&kek, b"tenant 1", &rng).unwrap();
+        let open = |kek: &aead::LessSafeKey, aad: &[u8], sealed: &[u8]| {
+            pkcs8::Document::open_sealed(kek, aad, sealed).map(|_| ())
+        };
+        assert_eq!(open(&kek, b"tenant 1", &sealed), Ok(()));
+
+        // Wrong key.
+        assert_eq!(
+            open(&self::kek(algorithm, 0x43), b"tenant 1", &sealed),
+            Err(error::Unspecified)
+        );
+
+        // Wrong algorithm.
+        for other in KEK_ALGORITHMS {
+            if other != algorithm {
+                assert_eq!(
+                    open(&self::kek(other, 0x42), b"tenant 1", &sealed),
+                    Err(error::Unspecified)
+                );
+            }
+        }
+
+        // Wrong AAD.
+        assert_eq!(open(&kek, b"tenant 2", &sealed), Err(error::Unspecified));
+        assert_eq!(open(&kek, b"", &sealed), Err(error::Unspecified));
+
+        // Truncated.
+        for len in 0..sealed.len() {
+            assert_eq!(
+                open(&kek, b"tenant 1", &sealed[..len]),
+                Err(error::Unspecified)
+            );
+        }
+
+        // Modified, including the header.
+        for i in 0..sealed.len() {
+            let mut bad = sealed.clone();
+            bad[i] ^= 1;
+            assert_eq!(open(&kek, b"tenant 1", &bad), Err(error::Unspecified));
+        }
+
+        // A version from the future.
+        let mut future = sealed.clone();
+        future[0] = pkcs8::SEALED_VERSION + 1;
+        assert_eq!(open(&kek, b"tenant 1", &future), Err(error::Unspecified));
+    }
+}
+
+#[test]
+fn pkcs8_seal_layout() {
+    // Generate a deterministic document.
+    let seed = test::rand::FixedByteRandom { byte: 0x11 };
+    let document = signature::Ed25519KeyPair::generate_pkcs8(&seed).unwrap();
+
+    let kek = kek(&aead::AES_128_GCM, 0x42);
+    let nonce = test::from_hex("000102030405060708090a0b").unwrap();
+    let rng = test::rand::FixedSliceRandom { bytes: &nonce };
+    let sealed = document.seal(&kek, b"tenant 7", &rng).unwrap();
+
+    // version || alg_id || nonce || ciphertext || tag
+    assert_eq!(sealed[..2], [1, 1]);
+    assert_eq!(&sealed[2..14], &nonce[..]);
+
+    // The header is authenticated along with the caller's AAD.
+    let mut expected = document.as_ref().to_vec();
+    kek.seal_in_place_append_tag(
+        aead::Nonce::try_assume_unique_for_key(&nonce).unwrap(),
+        aead::Aad::from(b"\x01\x01tenant 7"),
+        &mut expected,
+    )
+    .unwrap();
+    assert_eq!(&sealed[14..], &expected[..]);
+
+    assert_eq!(
+        sealed,
+        test::from_hex(concat!(
+            "0101",                                                             // version, alg_id
+            "000102030405060708090a0b",                                         // nonce
+            "9597502fe9bbd549e7b8ef6a587187e70b9a8e6b3cd3b8f787b1752a95c37083", // ciphertext
+            "646b0bb1de3ae718dd4223a057ada5edeca154148acfcbf9e97198e055f97bf7",
+            "e5e68e2cdaee82927f283633d3151cc4895f78",
+            "d322527b6f332480f16967cbf291a70b", // tag
+        ))
+        .unwrap()
+    );
+}
@@ -0,0 +1,201 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{
+    aead, error, inventory,
+    policy::{Policy, PolicyError},
+    rand,
+    signature::{self, KeyPair},
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+// Every test in this file must behave the same whether or not
+// `policy_default_test` has installed this as the default policy.
+const ALLOWED: &[&str] = &[
+    "ED25519",
+    "ECDSA_P256_SHA256_ASN1",
+    #[cfg(feature = "alloc")]
+    "RSA_PKCS1_3072_8192_SHA384",
+    "AES_256_GCM",
+    "CHACHA20_POLY1305",
+];
+
+fn restrictive_policy() -> Policy {
+    Policy::from_allow_list(ALLOWED.iter().copied()).unwrap()
+}
+
+#[test]
+fn policy_from_allow_list_test() {
+    assert_eq!(
+        Policy::from_allow_list(["ED25519", "SHA1"]),
+        Err(PolicyError::UnknownAlgorithm)
+    );
+    assert_eq!(
+        Policy::from_allow_list(["ED25519", ""]),
+        Err(PolicyError::UnknownAlgorithm)
+    );
+
+    // Names of other kinds of algorithms are accepted but don't matter.
+    assert_eq!(
+        Policy::from_allow_list(ALLOWED.iter().copied().chain(["SHA256", "X25519"])),
+        Ok(restrictive_policy())
+    );
+
+    // Every name in the inventory is accepted.
+    assert!(Policy::from_allow_list(inventory::inventory().map(|info| info.name())).is_ok());
+}
+
+#[test]
+fn policy_wrap_verification_test() {
+    let policy = restrictive_policy();
+
+    let banned: &[&'static dyn signature::VerificationAlgorithm] = &[
+        &signature::ECDSA_P256_SHA256_FIXED,
+        &signature::ECDSA_P256_SHA384_ASN1,
+        &signature::ECDSA_P384_SHA256_ASN1,
+        &signature::ECDSA_P384_SHA384_ASN1,
+        &signature::ECDSA_P384_SHA384_FIXED,
+        #[cfg(feature = "alloc")]
+        &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+        #[cfg(feature = "alloc")]
+        &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
+        #[cfg(feature = "alloc")]
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+        #[cfg(feature = "alloc")]
+        &signature::RSA_PSS_2048_8192_SHA256,
+    ];
+    for &alg in banned {
+        assert_eq!(
+            policy.wrap_verification(alg).err(),
+            Some(PolicyError::Disallowed),
+            "{:?}",
+            alg
+        );
+    }
+
+    let allowed: &[&'static dyn signature::VerificationAlgorithm] = &[
+        &signature::ED25519,
+        &signature::ECDSA_P256_SHA256_ASN1,
+        #[cfg(feature = "alloc")]
+        &signature::RSA_PKCS1_3072_8192_SHA384,
+    ];
+    for &alg in allowed {
+        let wrapped = policy.wrap_verification(alg).unwrap();
+        assert!(core::ptr::eq(
+            wrapped as *const _ as *const u8,
+            alg as *const _ as *const u8
+        ));
+    }
+
+    // A wrapped algorithm behaves exactly like the unwrapped one.
+    let rng = rand::SystemRandom::new();
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let sig = key_pair.sign(b"message");
+    let wrapped = policy.wrap_verification(&signature::ED25519).unwrap();
+    for alg in [wrapped, &signature::ED25519] {
+        let public_key = signature::UnparsedPublicKey::new(alg, key_pair.public_key().as_ref());
+        assert!(public_key.verify(b"message", sig.as_ref()).is_ok());
+        assert!(public_key.verify(b"massage", sig.as_ref()).is_err());
+    }
+}
+
+#[test]
+fn policy_wrap_verification_external_test() {
+    #[derive(Debug)]
+    struct AlwaysValid;
+
+    impl signature::ExternalVerificationAlgorithm for AlwaysValid {
+        fn verify(&self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), error::Unspecified> {
+            Ok(())
+        }
+
+        fn signature_len_hint(&self) -> signature::SignatureLen {
+            signature::SignatureLen::Exact(64)
+        }
+    }
+
+    static ALWAYS_VALID: signature::ExternalAlgorithm<AlwaysValid> =
+        signature::ExternalAlgorithm::new(AlwaysValid);
+
+    // Even though `ALWAYS_VALID` is zero-sized, like `ED25519`.
+    let policy = Policy::from_allow_list(inventory::inventory().map(|info| info.name())).unwrap();
+    assert_eq!(
+        policy.wrap_verification(&ALWAYS_VALID).err(),
+        Some(PolicyError::Disallowed)
+    );
+}
+
+#[test]
+fn policy_check_aead_test() {
+    let policy = restrictive_policy();
+    assert_eq!(
+        policy.check_aead(&aead::AES_128_GCM),
+        Err(PolicyError::Disallowed)
+    );
+    assert_eq!(policy.check_aead(&aead::AES_256_GCM), Ok(()));
+    assert_eq!(policy.check_aead(&aead::CHACHA20_POLY1305), Ok(()));
+}
+
+#[test]
+fn policy_default_test() {
+    let policy = restrictive_policy();
+    assert_eq!(policy.install_default(), Ok(()));
+    assert_eq!(Policy::default_policy(), Some(&policy));
+    assert_eq!(
+        Policy::from_allow_list(["AES_128_GCM"])
+            .unwrap()
+            .install_default(),
+        Err(PolicyError::DefaultAlreadyInstalled)
+    );
+    assert_eq!(Policy::default_policy(), Some(&policy));
+
+    // A valid P-384 signature is rejected by `UnparsedPublicKey`.
+    let rng = rand::SystemRandom::new();
+    let signing_alg = &signature::ECDSA_P384_SHA384_ASN1_SIGNING;
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(signing_alg, &rng).unwrap();
+    let key_pair = signature::EcdsaKeyPair::from_pkcs8(signing_alg, pkcs8.as_ref(), &rng).unwrap();
+    let sig = key_pair.sign(&rng, b"message").unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    assert!(signature::VerificationAlgorithm::verify(
+        &signature::ECDSA_P384_SHA384_ASN1,
+        untrusted::Input::from(public_key),
+        untrusted::Input::from(b"message"),
+        untrusted::Input::from(sig.as_ref()),
+    )
+    .is_ok());
+    assert!(
+        signature::UnparsedPublicKey::new(&signature::ECDSA_P384_SHA384_ASN1, public_key)
+            .verify(b"message", sig.as_ref())
+            .is_err()
+    );
+
+    #[cfg(feature = "alloc")]
+    {
+        use ring::easy;
+
+        let key = [0x42; 32];
+        assert!(easy::aead_seal(&aead::AES_128_GCM, &key[..16], b"", b"secret").is_err());
+        let sealed = easy::aead_seal(&aead::AES_256_GCM, &key, b"", b"secret").unwrap();
+        assert_eq!(
+            easy::aead_open(&aead::AES_256_GCM, &key, b"", &sealed).unwrap(),
+            b"secret"
+        );
+    }
+}
@@ -12,7 +12,10 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use ring::{aead::quic, test, test_file};
+use ring::{
+    aead::{self, quic},
+    hkdf, test, test_file,
+};
 
 #[test]
 fn quic_aes_128() {
@@ -68,3 +71,100 @@ fn test_sample_len(alg: &'static quic::Algorithm) {
     // Sample is empty.
     assert!(key.new_mask(&[]).is_err());
 }
+
+fn seal(key: &aead::LessSafeKey) -> Vec<u8> {
+    let mut in_out = b"QUIC packet payload".to_vec();
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key([0; aead::NONCE_LEN]),
+        aead::Aad::empty(),
+        &mut in_out,
+    )
+    .unwrap();
+    in_out
+}
+
+// Checks that `actual` is the key `expected` by sealing with both.
+fn assert_key_eq(actual: &aead::LessSafeKey, expected: &[u8]) {
+    let expected =
+        aead::LessSafeKey::new(aead::UnboundKey::new(actual.algorithm(), expected).unwrap());
+    assert_eq!(seal(actual), seal(&expected));
+}
+
+fn assert_hp_eq(actual: &quic::HeaderProtectionKey, expected: &[u8]) {
+    let expected = quic::HeaderProtectionKey::new(actual.algorithm(), expected).unwrap();
+    let sample = [0x5a; 16];
+    assert_eq!(
+        actual.new_mask(&sample).unwrap(),
+        expected.new_mask(&sample).unwrap()
+    );
+}
+
+// RFC 9001 Appendix A.1.
+#[test]
+fn quic_traffic_keys_initial() {
+    struct Vector {
+        secret: &'static str,
+        key: &'static str,
+        iv: &'static str,
+        hp: &'static str,
+    }
+    for v in [
+        // client_initial_secret
+        Vector {
+            secret: "c00cf151ca5be075ed0ebfb5c80323c42d6b7db67881289af4008f1f6c357aea",
+            key: "1f369613dd76d5467730efcbe3b1a22d",
+            iv: "fa044b2f42a3fd3b46fb255c",
+            hp: "9f50449e04a0e810283a1e9933adedd2",
+        },
+        // server_initial_secret
+        Vector {
+            secret: "3c199828fd139efd216c155ad844cc81fb82fa8d7446fa7d78be803acdda951b",
+            key: "cf3a5331653c364c88f0f379b6067e37",
+            iv: "0ac1493ca1905853b0bba03e",
+            hp: "c206b8d9b9f0f37644430b490eeaa314",
+        },
+    ] {
+        let secret = test::from_hex(v.secret).unwrap();
+        let secret = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &secret);
+        let keys = aead::TrafficKeys::derive(&secret, &aead::AES_128_GCM).unwrap();
+        assert_key_eq(keys.key(), &test::from_hex(v.key).unwrap());
+        assert_eq!(&keys.iv()[..], &test::from_hex(v.iv).unwrap()[..]);
+        assert_hp_eq(keys.header_protection_key(), &test::from_hex(v.hp).unwrap());
+        assert_eq!(keys.header_protection_key().algorithm(), &quic::AES_128);
+    }
+}
+
+// RFC 9001 Appendix A.5.
+#[test]
+fn quic_traffic_keys_chacha20_key_update() {
+    const SECRET: &str = "9ac312a7f877468ebe69422748ad00a15443f18203a07d6060f688f30f21632b";
+    const KEY: &str = "c6d98ff3441c3fe1b2182094f69caa2ed4b716b65488960a7a984979fb23e1c8";
+    const IV: &str = "e0459b3474bdd0e44a41c144";
+    const HP: &str = "25a282b9e82f06f21f488917a4fc8f1b73573685608597d0efcb076b0ab7a7a4";
+    const KU: &str = "1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9";
+
+    let secret = test::from_hex(SECRET).unwrap();
+    let secret = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &secret);
+    let keys = aead::TrafficKeys::derive(&secret, &aead::CHACHA20_POLY1305).unwrap();
+    assert_key_eq(keys.key(), &test::from_hex(KEY).unwrap());
+    assert_eq!(&keys.iv()[..], &test::from_hex(IV).unwrap()[..]);
+    assert_hp_eq(keys.header_protection_key(), &test::from_hex(HP).unwrap());
+
+    // The packet with number 654360564 uses this nonce.
+    assert_eq!(
+        &keys.nonce(654360564).as_ref()[..],
+        &test::from_hex("e0459b3474bdd0e46d417eb0").unwrap()[..]
+    );
+
+    // The next generation is derived from the secret "ku", and keeps the
+    // header protection key.
+    let next = keys.next_generation().unwrap();
+    let ku = test::from_hex(KU).unwrap();
+    let ku = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &ku);
+    let expected = aead::TrafficKeys::derive(&ku, &aead::CHACHA20_POLY1305).unwrap();
+    assert_eq!(next.iv(), expected.iv());
+    assert_ne!(next.iv(), keys.iv());
+    assert_eq!(seal(next.key()), seal(expected.key()));
+    assert_ne!(seal(next.key()), seal(keys.key()));
+    assert_hp_eq(next.header_protection_key(), &test::from_hex(HP).unwrap());
+}
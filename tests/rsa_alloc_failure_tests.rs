@@ -0,0 +1,113 @@
+// Copyright 2017 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "alloc")]
+
+//! These tests are in their own test binary because they replace the global
+//! allocator.
+
+use ring::rsa;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Fails the allocation after the current thread's allowance is used up.
+struct FailingAllocator;
+
+thread_local! {
+    static ALLOCATIONS_UNTIL_FAILURE: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn allocation_allowed() -> bool {
+    ALLOCATIONS_UNTIL_FAILURE.with(|remaining| match remaining.get() {
+        None => true,
+        Some(0) => false,
+        Some(n) => {
+            remaining.set(Some(n - 1));
+            true
+        }
+    })
+}
+
+unsafe impl GlobalAlloc for FailingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !allocation_allowed() {
+            return core::ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !allocation_allowed() {
+            return core::ptr::null_mut();
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if !allocation_allowed() {
+            return core::ptr::null_mut();
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: FailingAllocator = FailingAllocator;
+
+fn with_allocations_until_failure<R>(n: usize, f: impl FnOnce() -> R) -> R {
+    ALLOCATIONS_UNTIL_FAILURE.with(|remaining| remaining.set(Some(n)));
+    let r = f();
+    ALLOCATIONS_UNTIL_FAILURE.with(|remaining| remaining.set(None));
+    r
+}
+
+// Fails each allocation in turn, until there are enough allocations for the
+// key to be constructed.
+fn test_try_from_pkcs8_allocation_failures(pkcs8: &[u8]) {
+    for n in 0.. {
+        match with_allocations_until_failure(n, || rsa::KeyPair::try_from_pkcs8(pkcs8)) {
+            Ok(_) => {
+                assert!(n > 0);
+                return;
+            }
+            Err(e) => assert_eq!(e.to_string(), "OutOfMemory"),
+        }
+    }
+}
+
+#[test]
+fn rsa_try_from_pkcs8_allocation_failure_test() {
+    test_try_from_pkcs8_allocation_failures(include_bytes!("rsa_test_private_key_2048.p8"));
+}
+
+// When `p < q` the primes are swapped and `qInv` is recalculated, which
+// allocates more.
+#[test]
+fn rsa_try_from_pkcs8_allocation_failure_p_less_than_q_test() {
+    test_try_from_pkcs8_allocation_failures(include_bytes!(
+        "rsa_test_private_key_2048_p_less_than_q.p8"
+    ));
+}
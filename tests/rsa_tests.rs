@@ -21,6 +21,7 @@ use ring::{
     signature::{self, KeyPair},
     test, test_file,
 };
+use std::task::Poll;
 
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
@@ -36,15 +37,7 @@ fn rsa_from_pkcs8_test() {
             assert_eq!(section, "");
 
             let input = test_case.consume_bytes("Input");
-            let error = test_case.consume_optional_string("Error");
-
-            match (rsa::KeyPair::from_pkcs8(&input), error) {
-                (Ok(_), None) => {}
-                (Err(e), None) => panic!("Failed with error \"{}\", but expected to succeed", e),
-                (Ok(_), Some(e)) => panic!("Succeeded, but expected error \"{}\"", e),
-                (Err(actual), Some(expected)) => assert_eq!(format!("{}", actual), expected),
-            };
-
+            let _ = test_case.check_result(rsa::KeyPair::from_pkcs8(&input));
             Ok(())
         },
     );
@@ -70,14 +63,12 @@ fn test_signature_rsa_pkcs1_sign() {
             let private_key = test_case.consume_bytes("Key");
             let msg = test_case.consume_bytes("Msg");
             let expected = test_case.consume_bytes("Sig");
-            let result = test_case.consume_string("Result");
 
-            let key_pair = rsa::KeyPair::from_der(&private_key);
-            if result == "Fail-Invalid-Key" {
-                assert!(key_pair.is_err());
-                return Ok(());
-            }
-            let key_pair = key_pair.unwrap();
+            let key_pair = match test_case.check_result(rsa::KeyPair::from_der(&private_key)) {
+                Some(key_pair) => key_pair,
+                None => return Ok(()),
+            };
+            let result = test_case.consume_string("Result");
 
             // XXX: This test is too slow on Android ARM Travis CI builds.
             // TODO: re-enable these tests on Android ARM.
@@ -128,6 +119,48 @@ fn test_signature_rsa_pss_sign() {
     );
 }
 
+#[test]
+fn test_signature_rsa_pss_sign_stepwise() {
+    test::run(
+        test_file!("rsa_pss_sign_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let digest_name = test_case.consume_string("Digest");
+            let alg = match digest_name.as_ref() {
+                "SHA256" => &signature::RSA_PSS_SHA256,
+                "SHA384" => &signature::RSA_PSS_SHA384,
+                "SHA512" => &signature::RSA_PSS_SHA512,
+                _ => panic!("Unsupported digest: {}", digest_name),
+            };
+
+            let result = test_case.consume_string("Result");
+            let private_key = test_case.consume_bytes("Key");
+            let key_pair = match rsa::KeyPair::from_der(&private_key) {
+                Ok(key_pair) => key_pair,
+                Err(_) => return Ok(()),
+            };
+            let msg = test_case.consume_bytes("Msg");
+            let salt = test_case.consume_bytes("Salt");
+            let expected = test_case.consume_bytes("Sig");
+
+            let rng = test::rand::FixedSliceRandom { bytes: &salt };
+
+            let mut actual = vec![0u8; key_pair.public().modulus_len()];
+            let mut op = key_pair.sign_stepwise(alg, &rng, &msg, &mut actual)?;
+            let signed = loop {
+                if let Poll::Ready(signed) = op.step(50) {
+                    break signed;
+                }
+            };
+            signed?;
+            drop(op);
+            assert_eq!(actual.as_slice() == &expected[..], result == "Pass");
+            Ok(())
+        },
+    );
+}
+
 // `KeyPair::sign` requires that the output buffer is the same length as
 // the public key modulus. Test what happens when it isn't the same length.
 #[test]
@@ -237,6 +270,91 @@ fn test_signature_rsa_pkcs1_verify() {
     );
 }
 
+// The NIST vectors in rsa_pkcs1_verify_tests.txt include 1024-bit keys, but
+// those are all encoded by hand; these were produced by OpenSSL, the way
+// legacy firmware signatures typically are.
+#[cfg(feature = "alloc")]
+#[test]
+fn test_signature_rsa_pkcs1_verify_1024() {
+    test::run(
+        test_file!("rsa_pkcs1_verify_1024_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let digest_name = test_case.consume_string("Digest");
+            let (legacy_alg, alg): (&signature::RsaParameters, &signature::RsaParameters) =
+                match digest_name.as_ref() {
+                    "SHA1" => (
+                        &signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY,
+                        &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
+                    ),
+                    "SHA256" => (
+                        &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
+                        &signature::RSA_PKCS1_2048_8192_SHA256,
+                    ),
+                    _ => panic!("Unsupported digest: {}", digest_name),
+                };
+
+            let public_key = test_case.consume_bytes("Key");
+            let msg = test_case.consume_bytes("Msg");
+            let sig = test_case.consume_bytes("Sig");
+            let is_valid = test_case.consume_string("Result") == "P";
+
+            let actual_result =
+                signature::UnparsedPublicKey::new(legacy_alg, &public_key).verify(&msg, &sig);
+            assert_eq!(actual_result.is_ok(), is_valid);
+
+            // The 2048-bit minimum must reject the key regardless of whether
+            // the signature is valid.
+            assert!(signature::UnparsedPublicKey::new(alg, &public_key)
+                .verify(&msg, &sig)
+                .is_err());
+
+            Ok(())
+        },
+    );
+}
+
+// Keys larger than 8192 bits are accepted only by the `*_16384` algorithms,
+// and only when *ring* is built with a larger `$RING_MAX_RSA_BITS`.
+#[cfg(feature = "alloc")]
+#[test]
+fn test_signature_rsa_pkcs1_verify_max_rsa_bits() {
+    test::run(
+        test_file!("rsa_pkcs1_verify_16384_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+
+            let digest_name = test_case.consume_string("Digest");
+            assert_eq!(digest_name, "SHA256");
+            let public_key = test_case.consume_bytes("Key");
+            let msg = test_case.consume_bytes("Msg");
+            let sig = test_case.consume_bytes("Sig");
+
+            let verify =
+                |alg| signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig);
+            assert!(verify(&signature::RSA_PKCS1_2048_8192_SHA256).is_err());
+            assert_eq!(
+                verify(&signature::RSA_PKCS1_2048_16384_SHA256).is_ok(),
+                rsa::MAX_RSA_BITS >= 16384
+            );
+
+            let parsed = rsa::ParsedPublicKey::from_der(&public_key);
+            assert_eq!(parsed.is_ok(), rsa::MAX_RSA_BITS >= 16384);
+            if let Ok(parsed) = parsed {
+                assert!(parsed
+                    .verify(&signature::RSA_PKCS1_2048_8192_SHA256, &msg, &sig)
+                    .is_err());
+                assert!(parsed
+                    .verify(&signature::RSA_PKCS1_2048_16384_SHA256, &msg, &sig)
+                    .is_ok());
+            }
+
+            Ok(())
+        },
+    );
+}
+
 #[cfg(feature = "alloc")]
 #[test]
 fn test_signature_rsa_pss_verify() {
@@ -328,6 +446,66 @@ fn rsa_test_keypair_coverage() {
     );
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn rsa_public_key_eq_test() {
+    let key_pair =
+        rsa::KeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    // The same key, with the primes in the other order.
+    let same =
+        rsa::KeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048_p_less_than_q.p8"))
+            .unwrap();
+    let other = rsa::KeyPair::from_der(include_bytes!(
+        "../src/rsa/signature_rsa_example_private_key.der"
+    ))
+    .unwrap();
+
+    assert_eq!(key_pair.public(), key_pair.public());
+    assert_eq!(key_pair.public(), &key_pair.public().clone());
+    assert_eq!(key_pair.public(), same.public());
+    assert_ne!(key_pair.public(), other.public());
+    assert_ne!(other.public(), key_pair.public());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn rsa_key_pair_modulus_and_exponent_test() {
+    // Generated with `openssl genpkey -algorithm RSA`.
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    const PUBLIC_KEY_MODULUS_BE_BYTES: &[u8] = include_bytes!("rsa_test_public_modulus.bin");
+
+    let key_pair = rsa::KeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    assert_eq!(key_pair.key_size_bits(), 2048);
+    assert_eq!(key_pair.modulus(), PUBLIC_KEY_MODULUS_BE_BYTES);
+    assert_eq!(key_pair.modulus().len(), key_pair.public().modulus_len());
+    assert_eq!(key_pair.modulus().len() * 8, key_pair.key_size_bits());
+    assert_ne!(key_pair.modulus()[0], 0);
+    assert_eq!(key_pair.public_exponent(), &[0x01, 0x00, 0x01]);
+
+    let components = rsa::PublicKeyComponents::<Vec<_>>::from(key_pair.public());
+    assert_eq!(key_pair.modulus(), &components.n[..]);
+    assert_eq!(key_pair.public_exponent(), &components.e[..]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn rsa_key_pair_public_key_into_test() {
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    const _: () = assert!(rsa::KeyPair::MAX_PUBLIC_KEY_LEN == 528);
+
+    let key_pair = rsa::KeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    assert!(public_key.len() <= rsa::KeyPair::MAX_PUBLIC_KEY_LEN);
+
+    let mut out = [0u8; rsa::KeyPair::MAX_PUBLIC_KEY_LEN];
+    assert_eq!(key_pair.public_key_into(&mut out), Ok(public_key.len()));
+    assert_eq!(&out[..public_key.len()], public_key);
+    assert_eq!(
+        key_pair.public_key_into(&mut out[..(public_key.len() - 1)]),
+        Err(error::Unspecified)
+    );
+}
+
 fn test_public_key_coverage(key: &rsa::PublicKey) {
     // Test `AsRef<[u8]>`
     const PUBLIC_KEY: &[u8] = include_bytes!("rsa_test_public_key_2048.der");
@@ -343,3 +521,188 @@ fn test_public_key_coverage(key: &rsa::PublicKey) {
     const _65537: &[u8] = &[0x01, 0x00, 0x01];
     assert_eq!(_65537, &components.e);
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_signature_rsa_verify_cached() {
+    const MESSAGE: &[u8] = b"hello, world";
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    let rng = rand::SystemRandom::new();
+    let key_pair = rsa::KeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    let mut sig = vec![0; key_pair.public().modulus_len()];
+    key_pair
+        .sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE, &mut sig)
+        .unwrap();
+    let public_key_bytes = key_pair.public_key().as_ref();
+
+    let cache = signature::VerificationCache::new(4);
+    let verify = |alg, public_key: &[u8], msg: &[u8], sig: &[u8]| {
+        signature::UnparsedPublicKey::new(alg, public_key).verify_cached(&cache, msg, sig)
+    };
+    let alg = &signature::RSA_PKCS1_2048_8192_SHA256;
+
+    // The first verification misses; later ones are answered from the cache.
+    assert_eq!(verify(alg, public_key_bytes, MESSAGE, &sig), Ok(()));
+    assert_eq!((cache.hits(), cache.verifications()), (0, 1));
+    for i in 1..=3 {
+        assert_eq!(verify(alg, public_key_bytes, MESSAGE, &sig), Ok(()));
+        assert_eq!((cache.hits(), cache.verifications()), (i, 1));
+    }
+
+    // Any single-byte change to the key, message, or signature misses.
+    let mut expected_verifications = 1;
+    let mut check_miss = |public_key: &[u8], msg: &[u8], sig: &[u8]| {
+        assert!(verify(alg, public_key, msg, sig).is_err());
+        expected_verifications += 1;
+        assert_eq!(
+            (cache.hits(), cache.verifications()),
+            (3, expected_verifications)
+        );
+    };
+    for i in 0..public_key_bytes.len() {
+        let mut bad = public_key_bytes.to_vec();
+        bad[i] ^= 1;
+        check_miss(&bad, MESSAGE, &sig);
+    }
+    for i in 0..MESSAGE.len() {
+        let mut bad = MESSAGE.to_vec();
+        bad[i] ^= 1;
+        check_miss(public_key_bytes, &bad, &sig);
+    }
+    for i in 0..sig.len() {
+        let mut bad = sig.clone();
+        bad[i] ^= 1;
+        check_miss(public_key_bytes, MESSAGE, &bad);
+    }
+
+    // The algorithm is part of the key too.
+    cache.clear();
+    assert!(cache.is_empty());
+    let verifications = cache.verifications();
+    assert_eq!(verify(alg, public_key_bytes, MESSAGE, &sig), Ok(()));
+    assert!(verify(
+        &signature::RSA_PKCS1_2048_8192_SHA512,
+        public_key_bytes,
+        MESSAGE,
+        &sig
+    )
+    .is_err());
+    assert_eq!(cache.verifications(), verifications + 2);
+
+    // Failures are cached too.
+    let hits = cache.hits();
+    assert!(verify(
+        &signature::RSA_PKCS1_2048_8192_SHA512,
+        public_key_bytes,
+        MESSAGE,
+        &sig
+    )
+    .is_err());
+    assert_eq!(cache.hits(), hits + 1);
+    assert_eq!(cache.verifications(), verifications + 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_signature_verification_cache_eviction() {
+    const PRIVATE_KEY: &[u8] = include_bytes!("rsa_test_private_key_2048.p8");
+    let rng = rand::SystemRandom::new();
+    let key_pair = rsa::KeyPair::from_pkcs8(PRIVATE_KEY).unwrap();
+    let public_key = signature::UnparsedPublicKey::new(
+        &signature::RSA_PKCS1_2048_8192_SHA256,
+        key_pair.public_key(),
+    );
+    let messages: [&[u8]; 3] = [b"a", b"b", b"c"];
+    let sigs = messages.map(|msg| {
+        let mut sig = vec![0; key_pair.public().modulus_len()];
+        key_pair
+            .sign(&signature::RSA_PKCS1_SHA256, &rng, msg, &mut sig)
+            .unwrap();
+        sig
+    });
+
+    let cache = signature::VerificationCache::new(2);
+    let verify = |i: usize| {
+        public_key
+            .verify_cached(&cache, messages[i], &sigs[i])
+            .unwrap()
+    };
+
+    verify(0);
+    verify(1);
+    verify(0); // Now 1 is the least recently used.
+    verify(2); // Evicts 1.
+    assert_eq!(cache.len(), 2);
+    assert_eq!((cache.hits(), cache.verifications()), (1, 3));
+    verify(0);
+    verify(2);
+    assert_eq!((cache.hits(), cache.verifications()), (3, 3));
+    verify(1);
+    assert_eq!((cache.hits(), cache.verifications()), (3, 4));
+
+    // A cache with no capacity never hits.
+    let cache = signature::VerificationCache::new(0);
+    for _ in 0..2 {
+        public_key
+            .verify_cached(&cache, messages[0], &sigs[0])
+            .unwrap();
+    }
+    assert_eq!((cache.hits(), cache.verifications()), (0, 2));
+    assert!(cache.is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn rsa_key_pair_generate_test() {
+    const MESSAGE: &[u8] = b"hello, world";
+
+    let rng = rand::SystemRandom::new();
+    for &(size, count) in &[
+        (rsa::KeySize::Rsa2048, 3),
+        (rsa::KeySize::Rsa3072, 2),
+        (rsa::KeySize::Rsa4096, 1),
+    ] {
+        for _ in 0..count {
+            let key_pair = rsa::KeyPair::generate(size, &rng).unwrap();
+            assert_eq!(key_pair.key_size_bits(), size.len_bits());
+            assert_eq!(key_pair.public_exponent(), &[0x01, 0x00, 0x01]);
+
+            for &(padding, verification_alg) in &[
+                (
+                    &signature::RSA_PKCS1_SHA256 as &dyn signature::RsaEncoding,
+                    &signature::RSA_PKCS1_2048_8192_SHA256,
+                ),
+                (
+                    &signature::RSA_PSS_SHA256,
+                    &signature::RSA_PSS_2048_8192_SHA256,
+                ),
+            ] {
+                let mut sig = vec![0; key_pair.public().modulus_len()];
+                key_pair.sign(padding, &rng, MESSAGE, &mut sig).unwrap();
+                let public_key =
+                    signature::UnparsedPublicKey::new(verification_alg, key_pair.public_key());
+                assert_eq!(public_key.verify(MESSAGE, &sig), Ok(()));
+            }
+
+            let pkcs8 = key_pair.to_pkcs8().unwrap();
+            let imported = rsa::KeyPair::from_pkcs8(&pkcs8).unwrap();
+            assert_eq!(imported.public(), key_pair.public());
+            assert_eq!(imported.to_pkcs8().unwrap(), pkcs8);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn rsa_key_pair_to_pkcs8_test() {
+    for &pkcs8 in &[
+        &include_bytes!("rsa_test_private_key_2048.p8")[..],
+        &include_bytes!("rsa_test_private_key_2048_p_less_than_q.p8")[..],
+    ] {
+        let key_pair = rsa::KeyPair::from_pkcs8(pkcs8).unwrap();
+        let exported = key_pair.to_pkcs8().unwrap();
+        let imported = rsa::KeyPair::from_pkcs8(&exported).unwrap();
+        assert_eq!(imported.public(), key_pair.public());
+        assert_eq!(imported.to_pkcs8().unwrap(), exported);
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "diagnostics")]
+
+use ring::{
+    error::VerifyFailure,
+    signature::{self, VerificationAlgorithm},
+    test, test_file,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn alg_from_name(name: &str) -> &'static dyn VerificationAlgorithm {
+    match name {
+        "ECDSA_P256_SHA256_ASN1" => &signature::ECDSA_P256_SHA256_ASN1,
+        "ECDSA_P256_SHA256_ASN1_LOW_S" => &signature::ECDSA_P256_SHA256_ASN1_LOW_S,
+        "ECDSA_P256_SHA256_FIXED" => &signature::ECDSA_P256_SHA256_FIXED,
+        "ED25519" => &signature::ED25519,
+        #[cfg(feature = "alloc")]
+        "RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY" => {
+            &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY
+        }
+        #[cfg(feature = "alloc")]
+        "RSA_PKCS1_2048_8192_SHA256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+        #[cfg(feature = "alloc")]
+        "RSA_PSS_2048_8192_SHA256" => &signature::RSA_PSS_2048_8192_SHA256,
+        _ => panic!("unsupported algorithm: {}", name),
+    }
+}
+
+#[test]
+fn signature_verify_diagnostic_test() {
+    test::run(
+        test_file!("signature_diagnostic_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let alg_name = test_case.consume_string("Alg");
+            let public_key = test_case.consume_bytes("Key");
+            let msg = test_case.consume_bytes("Msg");
+            let sig = test_case.consume_bytes("Sig");
+            let expected = match test_case.consume_string("Result").as_str() {
+                "OK" => Ok(()),
+                "MalformedSignature" => Err(VerifyFailure::MalformedSignature),
+                "MalformedKey" => Err(VerifyFailure::MalformedKey),
+                "OutOfRangeComponent" => Err(VerifyFailure::OutOfRangeComponent),
+                "Mismatch" => Err(VerifyFailure::Mismatch),
+                s => panic!("unexpected result: {}", s),
+            };
+
+            if cfg!(not(feature = "alloc")) && alg_name.starts_with("RSA_") {
+                return Ok(());
+            }
+            let alg = alg_from_name(&alg_name);
+
+            let (public_key, msg, sig) = (
+                untrusted::Input::from(&public_key),
+                untrusted::Input::from(&msg),
+                untrusted::Input::from(&sig),
+            );
+            let actual = alg.verify_diagnostic(public_key, msg, sig);
+            assert_eq!(actual, expected);
+            assert_eq!(alg.verify(public_key, msg, sig).is_ok(), actual.is_ok());
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn signature_verify_diagnostic_external_test() {
+    #[derive(Debug)]
+    struct NeverValid;
+
+    impl signature::ExternalVerificationAlgorithm for NeverValid {
+        fn verify(&self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), ring::error::Unspecified> {
+            Err(ring::error::Unspecified)
+        }
+
+        fn signature_len_hint(&self) -> signature::SignatureLen {
+            signature::SignatureLen::Exact(4)
+        }
+    }
+
+    static NEVER_VALID: signature::ExternalAlgorithm<NeverValid> =
+        signature::ExternalAlgorithm::new(NeverValid);
+
+    let verify = |sig: &[u8]| {
+        NEVER_VALID.verify_diagnostic(
+            untrusted::Input::from(b"key"),
+            untrusted::Input::from(b"message"),
+            untrusted::Input::from(sig),
+        )
+    };
+    assert_eq!(verify(b"sig"), Err(VerifyFailure::MalformedSignature));
+    assert_eq!(verify(b"sig!"), Err(VerifyFailure::Mismatch));
+}
+
+#[test]
+fn verify_failure_display_test() {
+    assert_eq!(
+        VerifyFailure::OutOfRangeComponent.to_string(),
+        "OutOfRangeComponent"
+    );
+    assert_eq!(
+        ring::error::Unspecified::from(VerifyFailure::Mismatch),
+        ring::error::Unspecified
+    );
+}
@@ -1,4 +1,4 @@
-use ring::{signature, test};
+use ring::{signature, test, test_file};
 
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
@@ -24,3 +24,643 @@ fn signature_impl_test() {
     // Test `AsRef<[u8]>`
     assert_eq!(unparsed_public_key.as_ref(), &[0x01, 0x02, 0x03]);
 }
+
+#[test]
+fn signature_scheme_code_points() {
+    let mut supported = 0;
+    for v in 0..=u16::MAX {
+        if let Some(scheme) = signature::SignatureScheme::from_u16(v) {
+            assert_eq!(scheme.as_u16(), v);
+            assert_eq!(u16::from(scheme), v);
+            supported += 1;
+        }
+    }
+    assert_eq!(supported, 13);
+
+    assert_eq!(
+        signature::SignatureScheme::from_u16(0x0403),
+        Some(signature::SignatureScheme::Ecdsa256Sha256)
+    );
+    assert_eq!(
+        signature::SignatureScheme::from_u16(0x0804),
+        Some(signature::SignatureScheme::Rsa2048PssSha256)
+    );
+    assert_eq!(
+        signature::SignatureScheme::from_u16(0x0807),
+        Some(signature::SignatureScheme::Ed25519)
+    );
+
+    // ecdsa_sha1, ecdsa_secp521r1_sha512, ed448.
+    for v in [0x0203, 0x0603, 0x0808] {
+        assert_eq!(signature::SignatureScheme::from_u16(v), None);
+    }
+}
+
+#[test]
+fn signature_scheme_verify() {
+    use ring::{rand, signature::KeyPair};
+
+    const MSG: &[u8] = b"TLS 1.3, server CertificateVerify";
+
+    let rng = rand::SystemRandom::new();
+
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let ed25519 = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let sig = ed25519.sign(MSG);
+    let public_key = ed25519.public_key().as_ref();
+    assert!(signature::SignatureScheme::Ed25519
+        .verify(MSG, sig.as_ref(), public_key)
+        .is_ok());
+    assert!(signature::SignatureScheme::Ed25519
+        .verify(b"another message", sig.as_ref(), public_key)
+        .is_err());
+    assert!(signature::SignatureScheme::Ecdsa256Sha256
+        .verify(MSG, sig.as_ref(), public_key)
+        .is_err());
+
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+    let ecdsa = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+    let sig = ecdsa.sign(&rng, MSG).unwrap();
+    let public_key = ecdsa.public_key().as_ref();
+    assert!(signature::SignatureScheme::Ecdsa256Sha256
+        .verify(MSG, sig.as_ref(), public_key)
+        .is_ok());
+    assert!(signature::SignatureScheme::Ecdsa384Sha384
+        .verify(MSG, sig.as_ref(), public_key)
+        .is_err());
+}
+
+#[cfg(feature = "alloc")]
+struct Signer {
+    algorithm: &'static dyn signature::VerificationAlgorithm,
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl Signer {
+    fn entry(&self) -> signature::VerificationEntry<'_> {
+        (self.algorithm, &self.public_key, &self.signature)
+    }
+}
+
+// An Ed25519 signer, an ECDSA P-256 signer, and an RSA-PSS signer, all
+// signing `msg`.
+#[cfg(feature = "alloc")]
+fn new_signers(msg: &[u8]) -> [Signer; 3] {
+    use ring::{rand, signature::KeyPair};
+
+    let rng = rand::SystemRandom::new();
+
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let ed25519 = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+    let alg = &signature::ECDSA_P256_SHA256_ASN1_SIGNING;
+    let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+    let ecdsa = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+
+    let rsa =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    let mut rsa_signature = vec![0; rsa.public().modulus_len()];
+    rsa.sign(&signature::RSA_PSS_SHA256, &rng, msg, &mut rsa_signature)
+        .unwrap();
+
+    [
+        Signer {
+            algorithm: &signature::ED25519,
+            public_key: ed25519.public_key().as_ref().to_vec(),
+            signature: ed25519.sign(msg).as_ref().to_vec(),
+        },
+        Signer {
+            algorithm: &signature::ECDSA_P256_SHA256_ASN1,
+            public_key: ecdsa.public_key().as_ref().to_vec(),
+            signature: ecdsa.sign(&rng, msg).unwrap().as_ref().to_vec(),
+        },
+        Signer {
+            algorithm: &signature::RSA_PSS_2048_8192_SHA256,
+            public_key: rsa.public_key().as_ref().to_vec(),
+            signature: rsa_signature,
+        },
+    ]
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn signature_verify_all() {
+    const MSG: &[u8] = b"firmware bundle";
+
+    let signers = new_signers(MSG);
+    let mut invalid = new_signers(b"another bundle");
+    // Pair each wrong signature with the right key.
+    for (invalid, signer) in invalid.iter_mut().zip(&signers) {
+        invalid.public_key = signer.public_key.clone();
+    }
+
+    // Empty.
+    let err = signature::verify_all(MSG, &[]).unwrap_err();
+    assert_eq!(err.index(), None);
+    assert!(signature::verify_any(MSG, &[]).is_err());
+
+    // All valid.
+    let entries = [signers[0].entry(), signers[1].entry(), signers[2].entry()];
+    assert!(signature::verify_all(MSG, &entries).is_ok());
+    assert_eq!(signature::verify_any(MSG, &entries), Ok(0));
+
+    // First invalid.
+    let entries = [invalid[0].entry(), signers[1].entry(), signers[2].entry()];
+    let err = signature::verify_all(MSG, &entries).unwrap_err();
+    assert_eq!(err.index(), Some(0));
+    assert_eq!(err.to_string(), "signature 0 is invalid");
+    assert_eq!(signature::verify_any(MSG, &entries), Ok(1));
+
+    // Last invalid.
+    let entries = [signers[0].entry(), signers[1].entry(), invalid[2].entry()];
+    assert_eq!(
+        signature::verify_all(MSG, &entries).unwrap_err().index(),
+        Some(2)
+    );
+    assert_eq!(signature::verify_any(MSG, &entries), Ok(0));
+
+    // Several invalid; the first is reported.
+    let entries = [signers[0].entry(), invalid[1].entry(), invalid[2].entry()];
+    assert_eq!(
+        signature::verify_all(MSG, &entries).unwrap_err().index(),
+        Some(1)
+    );
+
+    // None valid.
+    let entries = [invalid[0].entry(), invalid[1].entry(), invalid[2].entry()];
+    assert!(signature::verify_all(MSG, &entries).is_err());
+    assert!(signature::verify_any(MSG, &entries).is_err());
+
+    // Duplicate keys are verified independently.
+    let entries = [signers[2].entry(), signers[2].entry()];
+    assert!(signature::verify_all(MSG, &entries).is_ok());
+    let entries = [signers[2].entry(), invalid[2].entry()];
+    assert_eq!(
+        signature::verify_all(MSG, &entries).unwrap_err().index(),
+        Some(1)
+    );
+    assert_eq!(signature::verify_any(MSG, &entries), Ok(0));
+
+    // A key used with the wrong algorithm.
+    let entries = [(
+        &signature::ECDSA_P256_SHA256_ASN1 as &dyn signature::VerificationAlgorithm,
+        &signers[0].public_key[..],
+        &signers[0].signature[..],
+    )];
+    assert_eq!(
+        signature::verify_all(MSG, &entries).unwrap_err().index(),
+        Some(0)
+    );
+}
+
+#[test]
+fn signature_algorithm_identifier_from_der() {
+    test::run(
+        test_file!("signature_algorithm_identifier_tests.txt"),
+        |section, test_case| {
+            assert_eq!(section, "");
+            let input = test_case.consume_bytes("AlgorithmIdentifier");
+            let expected = test_case.consume_string("Result");
+
+            let actual = signature::AlgorithmIdentifier::from_der(&input);
+            assert_eq!(format!("{:?}", actual), expected);
+
+            // Every supported algorithm has a canonical encoding.
+            if let Ok(alg) = actual {
+                let value = alg.der_value();
+                let mut der = vec![0x30, u8::try_from(value.len()).unwrap()];
+                der.extend_from_slice(value);
+                assert_eq!(signature::AlgorithmIdentifier::from_der(&der), Ok(alg));
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn signature_algorithm_identifier_to_verification_params() {
+    use signature::{AlgorithmIdentifier as Id, AlgorithmIdentifierError as Error};
+
+    fn same(
+        a: &dyn signature::VerificationAlgorithm,
+        b: &dyn signature::VerificationAlgorithm,
+    ) -> bool {
+        core::ptr::eq(
+            (a as *const dyn signature::VerificationAlgorithm).cast::<u8>(),
+            (b as *const dyn signature::VerificationAlgorithm).cast::<u8>(),
+        )
+    }
+
+    let expected: &[(Id, Id, &dyn signature::VerificationAlgorithm)] = &[
+        (Id::Ed25519, Id::Ed25519, &signature::ED25519),
+        (
+            Id::EcdsaSha256,
+            Id::EcPublicKeyP256,
+            &signature::ECDSA_P256_SHA256_ASN1,
+        ),
+        (
+            Id::EcdsaSha384,
+            Id::EcPublicKeyP256,
+            &signature::ECDSA_P256_SHA384_ASN1,
+        ),
+        (
+            Id::EcdsaSha256,
+            Id::EcPublicKeyP384,
+            &signature::ECDSA_P384_SHA256_ASN1,
+        ),
+        (
+            Id::EcdsaSha384,
+            Id::EcPublicKeyP384,
+            &signature::ECDSA_P384_SHA384_ASN1,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPkcs1Sha1ForLegacyUseOnly,
+            Id::RsaEncryption,
+            &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPkcs1Sha256,
+            Id::RsaEncryption,
+            &signature::RSA_PKCS1_2048_8192_SHA256,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPkcs1Sha384,
+            Id::RsaEncryption,
+            &signature::RSA_PKCS1_2048_8192_SHA384,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPkcs1Sha512,
+            Id::RsaEncryption,
+            &signature::RSA_PKCS1_2048_8192_SHA512,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPssSha256,
+            Id::RsaEncryption,
+            &signature::RSA_PSS_2048_8192_SHA256,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPssSha384,
+            Id::RsaEncryption,
+            &signature::RSA_PSS_2048_8192_SHA384,
+        ),
+        #[cfg(feature = "alloc")]
+        (
+            Id::RsaPssSha512,
+            Id::RsaEncryption,
+            &signature::RSA_PSS_2048_8192_SHA512,
+        ),
+    ];
+    for (sig_alg, key_alg, expected) in expected {
+        let actual = sig_alg.to_verification_params(*key_alg).unwrap();
+        assert!(same(actual, *expected), "{:?} {:?}", sig_alg, key_alg);
+    }
+
+    for (sig_alg, key_alg) in [
+        (Id::EcdsaSha256, Id::RsaEncryption),
+        (Id::EcdsaSha256, Id::Ed25519),
+        (Id::RsaPssSha256, Id::EcPublicKeyP256),
+        (Id::Ed25519, Id::EcPublicKeyP256),
+        // Public key algorithms aren't signature algorithms.
+        (Id::EcPublicKeyP256, Id::EcPublicKeyP256),
+        (Id::RsaEncryption, Id::RsaEncryption),
+        // Signature algorithms aren't public key algorithms.
+        (Id::EcdsaSha256, Id::EcdsaSha256),
+    ] {
+        assert_eq!(
+            sig_alg.to_verification_params(key_alg).map(|_| ()),
+            Err(Error::IncompatiblePublicKey)
+        );
+    }
+}
+
+#[test]
+fn signature_algorithm_identifier_from_signing_params() {
+    use signature::AlgorithmIdentifier as Id;
+
+    assert_eq!(
+        Id::from_ecdsa_signing_params(&signature::ECDSA_P256_SHA256_ASN1_SIGNING),
+        Some(Id::EcdsaSha256)
+    );
+    assert_eq!(
+        Id::from_ecdsa_signing_params(&signature::ECDSA_P384_SHA384_ASN1_SIGNING),
+        Some(Id::EcdsaSha384)
+    );
+    assert_eq!(
+        Id::from_ecdsa_signing_params(&signature::ECDSA_P256_SHA256_FIXED_SIGNING),
+        None
+    );
+    assert_eq!(
+        Id::from_ecdsa_signing_params(&signature::ECDSA_P384_SHA384_FIXED_SIGNING),
+        None
+    );
+
+    #[cfg(feature = "alloc")]
+    for (encoding, expected) in [
+        (
+            &signature::RSA_PKCS1_SHA256 as &dyn signature::RsaEncoding,
+            Id::RsaPkcs1Sha256,
+        ),
+        (&signature::RSA_PKCS1_SHA384, Id::RsaPkcs1Sha384),
+        (&signature::RSA_PKCS1_SHA512, Id::RsaPkcs1Sha512),
+        (&signature::RSA_PSS_SHA256, Id::RsaPssSha256),
+        (&signature::RSA_PSS_SHA384, Id::RsaPssSha384),
+        (&signature::RSA_PSS_SHA512, Id::RsaPssSha512),
+    ] {
+        assert_eq!(Id::from_rsa_signing_params(encoding), expected);
+    }
+}
+
+// Signatures verify with the algorithm their identifiers resolve to.
+#[cfg(feature = "alloc")]
+#[test]
+fn signature_algorithm_identifier_verify() {
+    use signature::AlgorithmIdentifier as Id;
+
+    const MSG: &[u8] = b"tbsCertificate";
+
+    let signers = new_signers(MSG);
+    let ids = [
+        (Id::Ed25519, Id::Ed25519),
+        (
+            Id::from_ecdsa_signing_params(&signature::ECDSA_P256_SHA256_ASN1_SIGNING).unwrap(),
+            Id::EcPublicKeyP256,
+        ),
+        (
+            Id::from_rsa_signing_params(&signature::RSA_PSS_SHA256),
+            Id::RsaEncryption,
+        ),
+    ];
+    for (signer, (sig_alg, key_alg)) in signers.iter().zip(ids) {
+        let alg = sig_alg.to_verification_params(key_alg).unwrap();
+        let public_key = signature::UnparsedPublicKey::new(alg, &signer.public_key);
+        assert!(public_key.verify(MSG, &signer.signature).is_ok());
+    }
+}
+
+#[test]
+fn signature_len_hint() {
+    use signature::{SignatureLen, VerificationAlgorithm};
+
+    const MSG: &[u8] = b"message";
+
+    // Signatures of the wrong length are rejected before the public key is
+    // even parsed, so the key doesn't need to match the algorithm.
+    let ed25519_public_key = &include_bytes!("ed25519_test_public_key.bin")[..];
+    let p256_public_key = &include_bytes!("ecdsa_test_public_key_p256.der")[..];
+
+    let cases: &[(&dyn VerificationAlgorithm, SignatureLen, &[u8])] = &[
+        (
+            &signature::ED25519,
+            SignatureLen::Exact(64),
+            ed25519_public_key,
+        ),
+        (
+            &signature::ECDSA_P256_SHA256_FIXED,
+            SignatureLen::Exact(64),
+            p256_public_key,
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_FIXED,
+            SignatureLen::Exact(96),
+            p256_public_key,
+        ),
+        (
+            &signature::ECDSA_P256_SHA256_ASN1,
+            SignatureLen::Range(8, 72),
+            p256_public_key,
+        ),
+        (
+            &signature::ECDSA_P384_SHA384_ASN1,
+            SignatureLen::Range(8, 104),
+            p256_public_key,
+        ),
+    ];
+    for (alg, expected, public_key) in cases {
+        assert_eq!(alg.signature_len_hint(), *expected, "{:?}", alg);
+        let public_key = signature::UnparsedPublicKey::new(*alg, public_key);
+        let (min, max) = match expected {
+            SignatureLen::Exact(n) => (*n, *n),
+            SignatureLen::Range(min, max) => (*min, *max),
+        };
+        for len in [0, 1, min - 1, max + 1, 1000] {
+            assert!(!expected.contains(len));
+            assert!(public_key.verify(MSG, &vec![0; len]).is_err());
+        }
+        assert!(expected.contains(min));
+        assert!(expected.contains(max));
+    }
+
+    // A valid signature has a length within the hint.
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let sig = key_pair.sign(MSG);
+    assert!(signature::ED25519
+        .signature_len_hint()
+        .contains(sig.as_ref().len()));
+    {
+        use signature::KeyPair;
+        let public_key =
+            signature::UnparsedPublicKey::new(&signature::ED25519, key_pair.public_key());
+        assert!(public_key.verify(MSG, sig.as_ref()).is_ok());
+        // Truncated and extended signatures.
+        assert!(public_key.verify(MSG, &sig.as_ref()[..63]).is_err());
+        let mut extended = sig.as_ref().to_vec();
+        extended.push(0);
+        assert!(public_key.verify(MSG, &extended).is_err());
+    }
+}
+
+// The length of a valid RSA signature is the length of the key's modulus, not
+// any constant.
+#[cfg(feature = "alloc")]
+#[test]
+fn signature_len_hint_rsa() {
+    use signature::{SignatureLen, VerificationAlgorithm};
+
+    const MSG: &[u8] = b"message";
+
+    assert_eq!(
+        signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY.signature_len_hint(),
+        SignatureLen::Range(128, 8192 / 8)
+    );
+    assert_eq!(
+        signature::RSA_PSS_2048_8192_SHA256.signature_len_hint(),
+        SignatureLen::Range(256, 8192 / 8)
+    );
+    assert_eq!(
+        signature::RSA_PKCS1_3072_8192_SHA384.signature_len_hint(),
+        SignatureLen::Range(384, 8192 / 8)
+    );
+    assert_eq!(
+        signature::RSA_PSS_2048_16384_SHA256.signature_len_hint(),
+        SignatureLen::Range(256, ring::rsa::MAX_RSA_BITS / 8)
+    );
+
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    let rng = ring::rand::SystemRandom::new();
+    let mut sig = vec![0; key_pair.public().modulus_len()];
+    key_pair
+        .sign(&signature::RSA_PKCS1_SHA256, &rng, MSG, &mut sig)
+        .unwrap();
+    assert_eq!(sig.len(), 256);
+
+    let alg = &signature::RSA_PKCS1_2048_8192_SHA256;
+    let public_key = {
+        use signature::KeyPair;
+        signature::UnparsedPublicKey::new(alg, key_pair.public_key().as_ref().to_vec())
+    };
+    assert!(public_key.verify(MSG, &sig).is_ok());
+
+    // Lengths allowed by the hint but not by this key.
+    for len in [257, 384, 512] {
+        assert!(alg.signature_len_hint().contains(len));
+        let mut padded = vec![0; len - sig.len()];
+        padded.extend_from_slice(&sig);
+        assert!(public_key.verify(MSG, &padded).is_err());
+    }
+    assert!(public_key.verify(MSG, &sig[1..]).is_err());
+}
+
+#[test]
+fn signature_external_algorithm() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use ring::{digest, error, signature::KeyPair};
+    use signature::{ExternalAlgorithm, ExternalVerificationAlgorithm, SignatureLen};
+
+    // A stand-in for an algorithm like ML-DSA: the "signature" is
+    // SHA-256(public_key || msg).
+    #[derive(Debug)]
+    struct DummyAlgorithm {
+        calls: AtomicUsize,
+    }
+
+    impl ExternalVerificationAlgorithm for DummyAlgorithm {
+        fn verify(
+            &self,
+            public_key: &[u8],
+            msg: &[u8],
+            signature: &[u8],
+        ) -> Result<(), error::Unspecified> {
+            let _ = self.calls.fetch_add(1, Ordering::Relaxed);
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update(public_key);
+            ctx.update(msg);
+            ring::constant_time::verify_slices_are_equal(ctx.finish().as_ref(), signature)
+        }
+
+        fn signature_len_hint(&self) -> SignatureLen {
+            SignatureLen::Exact(digest::SHA256_OUTPUT_LEN)
+        }
+    }
+
+    fn dummy_sign(public_key: &[u8], msg: &[u8]) -> Vec<u8> {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(public_key);
+        ctx.update(msg);
+        ctx.finish().as_ref().to_vec()
+    }
+
+    static DUMMY: ExternalAlgorithm<DummyAlgorithm> = ExternalAlgorithm::new(DummyAlgorithm {
+        calls: AtomicUsize::new(0),
+    });
+    const PUBLIC_KEY: &[u8] = b"dummy public key";
+    const MSG: &[u8] = b"manifest";
+
+    let public_key = signature::UnparsedPublicKey::new(&DUMMY, PUBLIC_KEY);
+    let sig = dummy_sign(PUBLIC_KEY, MSG);
+    assert!(public_key.verify(MSG, &sig).is_ok());
+    assert!(public_key.verify(b"another manifest", &sig).is_err());
+    assert_eq!(DUMMY.get().calls.load(Ordering::Relaxed), 2);
+
+    // Signatures of the wrong length are rejected without calling the
+    // external implementation.
+    assert!(public_key.verify(MSG, &sig[1..]).is_err());
+    assert!(public_key.verify(MSG, &[]).is_err());
+    assert_eq!(DUMMY.get().calls.load(Ordering::Relaxed), 2);
+
+    assert_eq!(
+        format!("{:?}", public_key),
+        r#"UnparsedPublicKey { algorithm: ExternalAlgorithm(DummyAlgorithm { calls: 2 }), bytes: "64756d6d79207075626c6963206b6579" }"#
+    );
+
+    // It works with code that is generic over `&dyn VerificationAlgorithm`,
+    // alongside *ring*'s own algorithms.
+    let key_pair =
+        signature::Ed25519KeyPair::from_pkcs8(include_bytes!("ed25519_test_private_key.p8"))
+            .unwrap();
+    let ed25519_sig = key_pair.sign(MSG);
+    let entries: [signature::VerificationEntry; 2] = [
+        (
+            &signature::ED25519,
+            key_pair.public_key().as_ref(),
+            ed25519_sig.as_ref(),
+        ),
+        (&DUMMY, PUBLIC_KEY, &sig),
+    ];
+    assert!(signature::verify_all(MSG, &entries).is_ok());
+    let bad_sig = dummy_sign(PUBLIC_KEY, b"another manifest");
+    let entries = [entries[0], (&DUMMY, PUBLIC_KEY, &bad_sig[..])];
+    assert_eq!(
+        signature::verify_all(MSG, &entries).unwrap_err().index(),
+        Some(1)
+    );
+}
+
+// Algorithms are told apart by identity, not by their `Debug` output.
+#[cfg(feature = "std")]
+#[test]
+fn signature_verification_cache_external_algorithms() {
+    use signature::{ExternalAlgorithm, ExternalVerificationAlgorithm, SignatureLen};
+
+    #[derive(Debug)]
+    struct Accept;
+
+    impl ExternalVerificationAlgorithm for Accept {
+        fn verify(&self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), ring::error::Unspecified> {
+            Ok(())
+        }
+
+        fn signature_len_hint(&self) -> SignatureLen {
+            SignatureLen::Exact(4)
+        }
+    }
+
+    mod other {
+        #[derive(Debug)]
+        pub struct Accept;
+    }
+
+    impl ExternalVerificationAlgorithm for other::Accept {
+        fn verify(&self, _: &[u8], _: &[u8], _: &[u8]) -> Result<(), ring::error::Unspecified> {
+            Err(ring::error::Unspecified)
+        }
+
+        fn signature_len_hint(&self) -> SignatureLen {
+            SignatureLen::Exact(4)
+        }
+    }
+
+    static ACCEPT: ExternalAlgorithm<Accept> = ExternalAlgorithm::new(Accept);
+    static REJECT: ExternalAlgorithm<other::Accept> = ExternalAlgorithm::new(other::Accept);
+    assert_eq!(format!("{:?}", ACCEPT.get()), format!("{:?}", REJECT.get()));
+
+    let cache = signature::VerificationCache::new(4);
+    let accept = signature::UnparsedPublicKey::new(&ACCEPT, b"key");
+    let reject = signature::UnparsedPublicKey::new(&REJECT, b"key");
+    assert!(accept.verify_cached(&cache, b"msg", b"sig!").is_ok());
+    assert!(reject.verify_cached(&cache, b"msg", b"sig!").is_err());
+    assert!(accept.verify_cached(&cache, b"msg", b"sig!").is_ok());
+    assert_eq!((cache.hits(), cache.verifications()), (1, 2));
+}
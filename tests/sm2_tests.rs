@@ -0,0 +1,90 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{
+    signature::{self, VerificationAlgorithm},
+    test, test_file,
+};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[test]
+fn sm2_verify_test() {
+    test::run(test_file!("sm2_verify_tests.txt"), |section, test_case| {
+        assert_eq!(section, "");
+
+        let user_id = test_case.consume_bytes("UserID");
+        let msg = test_case.consume_bytes("Msg");
+        let public_key = test_case.consume_bytes("Q");
+        let sig = test_case.consume_bytes("Sig");
+        let is_valid = test_case.consume_string("Result") == "P";
+
+        let alg = &signature::SM2_SM3_ASN1;
+        let actual_result = alg.verify_with_user_id(&public_key, &user_id, &msg, &sig);
+        assert_eq!(actual_result.is_ok(), is_valid);
+
+        if user_id == signature::SM2_DEFAULT_USER_ID {
+            assert_eq!(
+                signature::UnparsedPublicKey::new(alg, &public_key).verify(&msg, &sig),
+                actual_result
+            );
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn sm2_with_user_id_test() {
+    static ALICE: signature::Sm2VerificationAlgorithm =
+        signature::Sm2VerificationAlgorithm::with_user_id(b"ALICE123@YAHOO.COM");
+
+    // The third test vector in sm2_verify_tests.txt.
+    let public_key = test::from_hex("04d2a83b9a0aca9795966273074d23bb1b8c7b48f3b1c1150e930fc9d6912bcbc9931c37ae20216d4d4d60f5978f5fffe182bba2a68c5bfd331b8f999cfa6ae960").unwrap();
+    let msg = test::from_hex("de037e357297da3c209abb83e40c28e0250a23c9680a4ece2b8c12d6f6c279281b7ea8f516727e7ab27a05a4567f836a255c5c520b1e2f6e26f2031001f89dd6dd20f77ef2848e9fe63255406c4ac3e2cede6641fc51a63c976643a756ec0eb79f773650db6c8d32ac2976645eba71b48dbe522cd9cb927d2f1ef7d539089e978bcddf242b9ade44026ef9c42b5e515dcd16aeffc9b5fd898fdeacf4bc175f14988d665ed8498a024cad04a365b3b09551fb6c1cf9a1f000f4f732ab7c6c9f0151ee70c9a977d31d42c6fe61a26a3bb8ce352ac4985b12f4a47ccc9024fb7b1814f69d073746d5b91c41e234f16d7f9e3b7b193facf9cfc01870a45c46683c549db0ffa25f3b4e8b72b5d1993f301093ffdb805b25847b3a8033908fe4582bce3e441476e45102b2f72e44e3").unwrap();
+    let sig = test::from_hex("3045022100934598b667b2e6439e65231737c5749aa50f101de9dcfa0bdb606d297a6c222102203ce377f9adfaa0a4d06106827b6ca7186ae75c696105c77e389b0120c194bbee").unwrap();
+
+    assert_eq!(ALICE.user_id(), b"ALICE123@YAHOO.COM");
+    assert!(signature::UnparsedPublicKey::new(&ALICE, &public_key)
+        .verify(&msg, &sig)
+        .is_ok());
+    assert!(
+        signature::UnparsedPublicKey::new(&signature::SM2_SM3_ASN1, &public_key)
+            .verify(&msg, &sig)
+            .is_err()
+    );
+}
+
+#[test]
+fn sm2_user_id_too_long_test() {
+    // The length of the user ID in bits must fit in 16 bits.
+    let public_key = test::from_hex("04e9ad8012ea6b10cdc5b01883a15ba74d7f617658f39b581b7cee183b1be0cafb8b947284bf7f20ca043b0c002d150e641dcb82340e1735c927ef5b5a38addc5b").unwrap();
+    let sig = test::from_hex("304502201269b4b69b7312e0509f4303b909cc355c826784aeb727d81ffe262fa92c1a58022100e7d490e1356af1ad7b69b4f7354ec9da74f2c573165e6c51f479e97b188c6fb2").unwrap();
+    let user_id = [b'a'; 8192];
+    assert!(signature::SM2_SM3_ASN1
+        .verify_with_user_id(&public_key, &user_id, b"message digest", &sig)
+        .is_err());
+}
+
+#[test]
+fn sm2_signature_len_hint_test() {
+    assert_eq!(
+        signature::SM2_SM3_ASN1.signature_len_hint(),
+        signature::SignatureLen::Range(8, 72)
+    );
+}
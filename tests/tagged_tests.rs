@@ -0,0 +1,161 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use ring::{digest, error, hmac, tagged};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+// These ids are stored in encoded values so they must never change.
+static DIGEST_IDS: &[(u16, &digest::Algorithm)] = &[
+    (0x0001, &digest::SHA1_FOR_LEGACY_USE_ONLY),
+    (0x0002, &digest::SHA256),
+    (0x0003, &digest::SHA384),
+    (0x0004, &digest::SHA512),
+    (0x0005, &digest::SHA512_256),
+    (0x0006, &digest::SM3),
+];
+
+static HMAC_IDS: &[(u16, &hmac::Algorithm)] = &[
+    (0x0101, &hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY),
+    (0x0102, &hmac::HMAC_SHA256),
+    (0x0103, &hmac::HMAC_SHA384),
+    (0x0104, &hmac::HMAC_SHA512),
+];
+
+const DATA: &[u8] = b"hello, world";
+
+#[test]
+fn tagged_digest_test() {
+    for &(id, algorithm) in DIGEST_IDS {
+        let digest = digest::digest(algorithm, DATA);
+        let tagged = digest.to_tagged_bytes().unwrap();
+        let tagged = tagged.as_ref();
+        assert_eq!(tagged.len(), tagged::HEADER_LEN + algorithm.output_len());
+        assert_eq!(&tagged[..2], &id.to_be_bytes());
+        assert_eq!(usize::from(tagged[2]), algorithm.output_len());
+        assert_eq!(&tagged[tagged::HEADER_LEN..], digest.as_ref());
+
+        assert_eq!(tagged::verify_tagged(None, DATA, tagged), Ok(()));
+        assert_eq!(
+            tagged::verify_tagged(None, b"hello, worle", tagged),
+            Err(error::Unspecified)
+        );
+        check_malformed(None, tagged);
+
+        // A digest isn't accepted where an HMAC tag is expected.
+        let key = hmac::Key::new(hmac::HMAC_SHA256, &[0; 32]);
+        assert_eq!(
+            tagged::verify_tagged(Some(&key), DATA, tagged),
+            Err(error::Unspecified)
+        );
+    }
+}
+
+#[test]
+fn tagged_hmac_test() {
+    for &(id, algorithm) in HMAC_IDS {
+        let key = hmac::Key::new(*algorithm, &[0x42; 32]);
+        let tag = hmac::sign(&key, DATA);
+        let tagged = tag.to_tagged_bytes().unwrap();
+        let tagged = tagged.as_ref();
+        let output_len = algorithm.digest_algorithm().output_len();
+        assert_eq!(tagged.len(), tagged::HEADER_LEN + output_len);
+        assert_eq!(&tagged[..2], &id.to_be_bytes());
+        assert_eq!(usize::from(tagged[2]), output_len);
+        assert_eq!(&tagged[tagged::HEADER_LEN..], tag.as_ref());
+
+        assert_eq!(tagged::verify_tagged(Some(&key), DATA, tagged), Ok(()));
+        assert_eq!(
+            tagged::verify_tagged(Some(&key), b"hello, worle", tagged),
+            Err(error::Unspecified)
+        );
+        let other_key = hmac::Key::new(*algorithm, &[0x43; 32]);
+        assert_eq!(
+            tagged::verify_tagged(Some(&other_key), DATA, tagged),
+            Err(error::Unspecified)
+        );
+        check_malformed(Some(&key), tagged);
+
+        // An HMAC tag isn't accepted without a key, or with a key for
+        // another algorithm.
+        assert_eq!(
+            tagged::verify_tagged(None, DATA, tagged),
+            Err(error::Unspecified)
+        );
+        for &(_, other) in HMAC_IDS.iter().filter(|(other_id, _)| *other_id != id) {
+            let key = hmac::Key::new(*other, &[0x42; 32]);
+            assert_eq!(
+                tagged::verify_tagged(Some(&key), DATA, tagged),
+                Err(error::Unspecified)
+            );
+        }
+    }
+}
+
+#[test]
+fn tagged_unknown_id_test() {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &[0; 32]);
+    let tag = hmac::sign(&key, DATA);
+    let digest = digest::digest(&digest::SHA256, DATA);
+    for id in [0x0000u16, 0x0006, 0x0100, 0x0105, 0x0202, 0xffff] {
+        let mut tagged = id.to_be_bytes().to_vec();
+        tagged.push(32);
+        tagged.extend_from_slice(digest.as_ref());
+        assert!(tagged::verify_tagged(None, DATA, &tagged).is_err());
+
+        tagged.truncate(tagged::HEADER_LEN);
+        tagged.extend_from_slice(tag.as_ref());
+        assert!(tagged::verify_tagged(Some(&key), DATA, &tagged).is_err());
+    }
+}
+
+fn check_malformed(key: Option<&hmac::Key>, tagged: &[u8]) {
+    // Truncated.
+    for len in 0..tagged.len() {
+        assert_eq!(
+            tagged::verify_tagged(key, DATA, &tagged[..len]),
+            Err(error::Unspecified)
+        );
+    }
+
+    // Extended.
+    let mut extended = tagged.to_vec();
+    extended.push(0);
+    assert_eq!(
+        tagged::verify_tagged(key, DATA, &extended),
+        Err(error::Unspecified)
+    );
+
+    // A length that matches a truncated value.
+    let mut truncated = tagged[..(tagged.len() - 1)].to_vec();
+    truncated[2] -= 1;
+    assert_eq!(
+        tagged::verify_tagged(key, DATA, &truncated),
+        Err(error::Unspecified)
+    );
+
+    // Any modified byte.
+    for i in 0..tagged.len() {
+        let mut modified = tagged.to_vec();
+        modified[i] ^= 1;
+        assert_eq!(
+            tagged::verify_tagged(key, DATA, &modified),
+            Err(error::Unspecified)
+        );
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Statistical timing checks; see the documentation of `ring::test::timing`
+//! for how to run them with enforcement enabled.
+
+#![cfg(feature = "timing-tests")]
+
+use ring::test::timing::{self, Target};
+
+fn check<T: Target>(target: &T) {
+    let report = timing::measure(target, &timing::Config::from_env());
+    println!("{}", report);
+    report.check();
+}
+
+#[test]
+fn timing_ed25519_sign() {
+    check(&timing::Ed25519Signing);
+}
+
+#[test]
+fn timing_ecdsa_p256_sign() {
+    check(&timing::EcdsaP256Signing);
+}
+
+#[test]
+fn timing_x25519_agree() {
+    check(&timing::X25519Agreement::new());
+}
+
+#[test]
+fn timing_aes_gcm_open_tag() {
+    check(&timing::AesGcmOpenTag::new());
+}
+
+#[test]
+fn timing_hmac_verify() {
+    check(&timing::HmacVerify::new());
+}
+
+#[test]
+fn timing_pbkdf2_verify() {
+    check(&timing::Pbkdf2Verify::new());
+}
@@ -0,0 +1,147 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(feature = "alloc")]
+
+use ring::{aead, error, test, tls13};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+wasm_bindgen_test_configure!(run_in_browser);
+
+const HANDSHAKE: u8 = 22;
+const APPLICATION_DATA: u8 = 23;
+
+fn h(s: &str) -> Vec<u8> {
+    test::from_hex(s).unwrap()
+}
+
+// Records from the "Simple 1-RTT Handshake" of [RFC 8448 Section 3].
+//
+// [RFC 8448 Section 3]: https://www.rfc-editor.org/rfc/rfc8448#section-3
+#[test]
+fn tls13_seal_record_rfc8448() {
+    // The client's Finished, the first record under the client handshake
+    // traffic key.
+    let mut ctx = tls13::AeadContext::new(
+        &aead::AES_128_GCM,
+        &h("dbfaa693d1762c5b666af5d950258d01"),
+        &h("5bd3c71b836e0b76bb73265f"),
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.seal_record(
+            HANDSHAKE,
+            &h("14000020a8ec436d677634ae525ac1fcebe11a039ec17694fac6e98527b642f2edd5ce61")
+        )
+        .unwrap(),
+        h("170303003575ec4dc238cce60b298044a71e219c56cc77b0517fe9b93c7a4bfc44d87f38f80338ac98fc46deb384bd1caeacab6867d726c40546")
+    );
+
+    let application_data = (0..50).collect::<Vec<u8>>();
+
+    // The client's application data, the first record under the client
+    // application traffic key.
+    let mut ctx = tls13::AeadContext::new(
+        &aead::AES_128_GCM,
+        &h("17422dda596ed5d9acd890e3c63f5051"),
+        &h("5b78923dee08579033e523d9"),
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.seal_record(APPLICATION_DATA, &application_data).unwrap(),
+        h("1703030043a23f7054b62c94d0affafe8228ba55cbefacea42f914aa66bcab3f2b9819a8a5b46b395bd54a9a20441e2b62974e1f5a6292a2977014bd1e3deae63aeebb21694915e4")
+    );
+
+    // The server's application data, the second record under the server
+    // application traffic key; the first is its NewSessionTicket, whose
+    // content doesn't affect the second record.
+    let mut ctx = tls13::AeadContext::new(
+        &aead::AES_128_GCM,
+        &h("9f02283b6c9c07efc26bb9f2ac92e356"),
+        &h("cf782b88dd83549aadf1e984"),
+    )
+    .unwrap();
+    let _ = ctx.seal_record(HANDSHAKE, b"").unwrap();
+    assert_eq!(
+        ctx.seal_record(APPLICATION_DATA, &application_data).unwrap(),
+        h("17030300432e937e11ef4ac740e538ad36005fc4a46932fc3225d05f82aa1b36e30efaf97d90e6dffc602dcb501a59a8fcc49c4bf2e5f0a21c0047c2abf332540dd032e167c2955d")
+    );
+}
+
+#[test]
+fn tls13_seal_record_round_trip() {
+    const KEY: [u8; 32] = [1; 32];
+    const IV: [u8; 12] = [2; 12];
+
+    for alg in [
+        &aead::AES_128_GCM,
+        &aead::AES_256_GCM,
+        &aead::CHACHA20_POLY1305,
+    ] {
+        let key = &KEY[..alg.key_len()];
+        let mut ctx = tls13::AeadContext::new(alg, key, &IV).unwrap();
+        assert!(core::ptr::eq(ctx.algorithm(), alg));
+        let opening_key = aead::LessSafeKey::new(aead::UnboundKey::new(alg, key).unwrap());
+
+        for sequence_number in 0u64..3 {
+            let plaintext = vec![sequence_number as u8; 10];
+            let mut record = ctx.seal_record(APPLICATION_DATA, &plaintext).unwrap();
+            let (header, ciphertext) = record.split_at_mut(5);
+            assert_eq!(&header[..3], &[23, 3, 3]);
+            assert_eq!(
+                usize::from(u16::from_be_bytes([header[3], header[4]])),
+                ciphertext.len()
+            );
+
+            let mut nonce = IV;
+            nonce[4..]
+                .iter_mut()
+                .zip(sequence_number.to_be_bytes())
+                .for_each(|(n, sn)| *n ^= sn);
+            let inner = opening_key
+                .open_in_place(
+                    aead::Nonce::assume_unique_for_key(nonce),
+                    aead::Aad::from(&*header),
+                    ciphertext,
+                )
+                .unwrap();
+            assert_eq!(
+                inner.split_last(),
+                Some((&APPLICATION_DATA, &plaintext[..]))
+            );
+        }
+    }
+}
+
+#[test]
+fn tls13_aead_context_errors() {
+    assert!(tls13::AeadContext::new(&aead::AES_128_GCM, &[0; 15], &[0; 12]).is_err());
+    assert!(tls13::AeadContext::new(&aead::AES_128_GCM, &[0; 16], &[0; 8]).is_err());
+
+    let mut ctx = tls13::AeadContext::new(&aead::AES_128_GCM, &[0; 16], &[0; 12]).unwrap();
+    let too_long = vec![0; tls13::MAX_PLAINTEXT_LEN + 1];
+    assert_eq!(
+        ctx.seal_record(APPLICATION_DATA, &too_long),
+        Err(error::Unspecified)
+    );
+    let max_len = ctx.seal_record(APPLICATION_DATA, &too_long[1..]).unwrap();
+    assert_eq!(max_len.len(), 5 + tls13::MAX_PLAINTEXT_LEN + 1 + 16);
+    assert_eq!(
+        format!("{:?}", ctx),
+        "AeadContext { algorithm: AES_128_GCM, .. }"
+    );
+}
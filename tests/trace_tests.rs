@@ -0,0 +1,146 @@
+// Copyright 2023 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![cfg(all(feature = "tracing", feature = "std"))]
+
+use ring::{aead, agreement, rand, signature, trace};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<&'static str>>>,
+    warnings: Arc<Mutex<usize>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl tracing::Subscriber for Recorder {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        self.spans.lock().unwrap().push(attrs.metadata().name());
+        tracing::span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        if *event.metadata().level() == tracing::Level::WARN {
+            *self.warnings.lock().unwrap() += 1;
+        }
+    }
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+fn record(f: impl FnOnce()) -> Recorder {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), f);
+    recorder
+}
+
+fn spans(recorder: &Recorder) -> Vec<&'static str> {
+    recorder.spans.lock().unwrap().clone()
+}
+
+#[test]
+fn trace_key_generation_and_signing() {
+    let rng = rand::SystemRandom::new();
+    let recorder = record(|| {
+        let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let _ = key_pair.sign(b"hello");
+
+        let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+        let _ = key_pair.sign(&rng, b"hello").unwrap();
+    });
+    assert_eq!(
+        spans(&recorder),
+        [
+            trace::KEY_GENERATION,
+            trace::SIGN,
+            trace::KEY_GENERATION,
+            trace::SIGN
+        ]
+    );
+}
+
+#[test]
+fn trace_rsa_signing_and_deprecation() {
+    let rng = rand::SystemRandom::new();
+    let key_pair =
+        signature::RsaKeyPair::from_pkcs8(include_bytes!("rsa_test_private_key_2048.p8")).unwrap();
+    let recorder = record(|| {
+        #[allow(deprecated)]
+        let mut sig = vec![0; key_pair.public_modulus_len()];
+        key_pair
+            .sign(&signature::RSA_PKCS1_SHA256, &rng, b"hello", &mut sig)
+            .unwrap();
+    });
+    assert_eq!(spans(&recorder), [trace::SIGN]);
+    assert_eq!(*recorder.warnings.lock().unwrap(), 1);
+}
+
+#[test]
+fn trace_aead() {
+    let recorder = record(|| {
+        let key = aead::UnboundKey::new(&aead::AES_128_GCM, &[0; 16]).unwrap();
+        let key = aead::LessSafeKey::new(key);
+        let mut in_out = b"hello".to_vec();
+        key.seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key([0; 12]),
+            aead::Aad::from(b"aad"),
+            &mut in_out,
+        )
+        .unwrap();
+        key.open_in_place(
+            aead::Nonce::assume_unique_for_key([0; 12]),
+            aead::Aad::from(b"aad"),
+            &mut in_out,
+        )
+        .unwrap();
+    });
+    assert_eq!(spans(&recorder), [trace::AEAD_SEAL, trace::AEAD_OPEN]);
+}
+
+#[test]
+fn trace_agreement() {
+    let rng = rand::SystemRandom::new();
+    let recorder = record(|| {
+        let peer = agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap();
+        let peer_public_key = peer.compute_public_key().unwrap();
+        let peer_public_key =
+            agreement::UnparsedPublicKey::new(&agreement::X25519, peer_public_key);
+        let my = agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng).unwrap();
+        agreement::agree_ephemeral(my, &peer_public_key, |_| ()).unwrap();
+    });
+    assert_eq!(
+        spans(&recorder),
+        [
+            trace::KEY_GENERATION,
+            trace::KEY_GENERATION,
+            trace::AGREEMENT
+        ]
+    );
+}
@@ -604,7 +604,7 @@ ge_p3_add_p3_precomp_4(ge_p3_4 *r, const ge_p3_4 *p, const ge_precomp_4 *q) {
 
 __attribute__((always_inline)) // 25% speedup with clang14 and zen2
 static inline void table_select_4(ge_precomp_4 *t, const int pos,
-                                  const signed char b) {
+                                  const signed char b, int scan_all) {
   uint8_t bnegative = constant_time_msb_w(b);
   uint8_t babs = b - ((bnegative & b) << 1);
 
@@ -614,10 +614,16 @@ static inline void table_select_4(ge_precomp_4 *t, const int pos,
   __asm__("" : "+m" (t_bytes) : /*no inputs*/);
 #endif
   OPENSSL_STATIC_ASSERT(sizeof(t_bytes) == sizeof(k25519Precomp[pos][0]), "");
-  for (int i = 0; i < 8; i++) {
-    constant_time_conditional_memxor(t_bytes, k25519Precomp[pos][i],
-                                     sizeof(t_bytes),
-                                     constant_time_eq_w(babs, 1 + i));
+  int first_row = scan_all ? 0 : pos;
+  int last_row = scan_all ? 31 : pos;
+  for (int row = first_row; row <= last_row; row++) {
+    crypto_word_t in_row =
+        constant_time_eq_w((crypto_word_t)row, (crypto_word_t)pos);
+    for (int i = 0; i < 8; i++) {
+      constant_time_conditional_memxor(t_bytes, k25519Precomp[row][i],
+                                       sizeof(t_bytes),
+                                       in_row & constant_time_eq_w(babs, 1 + i));
+    }
   }
 
   OPENSSL_STATIC_ASSERT(sizeof(t_bytes) == sizeof(ge_precomp_4), "");
@@ -642,7 +648,8 @@ static inline void table_select_4(ge_precomp_4 *t, const int pos,
 //   a[31] <= 127
 __attribute__((noinline)) // https://github.com/rust-lang/rust/issues/116573
 __attribute__((target("adx,bmi2")))
-void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32]) {
+void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32],
+                                   int scan_all) {
   signed char e[64];
   signed char carry;
 
@@ -666,7 +673,7 @@ void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32]) {
   ge_p3_4 r = {{0}, {1}, {1}, {0}};
   for (unsigned i = 1; i < 64; i += 2) {
     ge_precomp_4 t;
-    table_select_4(&t, i / 2, e[i]);
+    table_select_4(&t, i / 2, e[i], scan_all);
     ge_p3_add_p3_precomp_4(&r, &r, &t);
   }
 
@@ -677,7 +684,7 @@ void x25519_ge_scalarmult_base_adx(uint8_t h[4][32], const uint8_t a[32]) {
 
   for (unsigned i = 0; i < 64; i += 2) {
     ge_precomp_4 t;
-    table_select_4(&t, i / 2, e[i]);
+    table_select_4(&t, i / 2, e[i], scan_all);
     ge_p3_add_p3_precomp_4(&r, &r, &t);
   }
 